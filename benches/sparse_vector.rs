@@ -0,0 +1,55 @@
+// Sparse price ladder vs BTreeMap for tick-indexed levels
+//
+// Run with `cargo bench --bench sparse_vector`.
+
+use std::collections::BTreeMap;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use crypto_orderbook::utils::SparseVector;
+
+/// Ticks around a mid with a gap, like a book after a sweep
+fn ticks() -> Vec<usize> {
+    (10_000..10_500).chain(12_000..12_500).collect()
+}
+
+fn set(c: &mut Criterion) {
+    let ticks = ticks();
+    c.bench_function("sparse_vector/set", |b| {
+        b.iter(|| {
+            let mut ladder = SparseVector::new();
+            for &tick in &ticks {
+                ladder.set(tick, tick as f64);
+            }
+            black_box(ladder)
+        })
+    });
+    c.bench_function("btree_map/insert", |b| {
+        b.iter(|| {
+            let mut ladder = BTreeMap::new();
+            for &tick in &ticks {
+                ladder.insert(tick, tick as f64);
+            }
+            black_box(ladder)
+        })
+    });
+}
+
+fn get(c: &mut Criterion) {
+    let ticks = ticks();
+    let sparse: SparseVector<f64> = ticks.iter().map(|&t| (t, t as f64)).collect();
+    let btree: BTreeMap<usize, f64> = ticks.iter().map(|&t| (t, t as f64)).collect();
+
+    c.bench_function("sparse_vector/get", |b| {
+        b.iter(|| ticks.iter().filter_map(|t| sparse.get(*t)).sum::<f64>())
+    });
+    c.bench_function("btree_map/get", |b| {
+        b.iter(|| ticks.iter().filter_map(|t| btree.get(t)).sum::<f64>())
+    });
+    c.bench_function("sparse_vector/iter", |b| {
+        b.iter(|| sparse.iter().map(|(_, v)| *v).sum::<f64>())
+    });
+    c.bench_function("btree_map/iter", |b| b.iter(|| btree.values().sum::<f64>()));
+}
+
+criterion_group!(benches, set, get);
+criterion_main!(benches);
@@ -0,0 +1,20 @@
+// Regenerates the C header for the `ffi` feature
+
+fn main() {
+    #[cfg(feature = "ffi")]
+    generate_header();
+}
+
+#[cfg(feature = "ffi")]
+fn generate_header() {
+    let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    println!("cargo:rerun-if-changed=src/ffi.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+
+    match cbindgen::generate(&crate_dir) {
+        Ok(bindings) => {
+            bindings.write_to_file(format!("{}/include/crypto_orderbook.h", crate_dir));
+        }
+        Err(e) => println!("cargo:warning=failed to generate C header: {}", e),
+    }
+}
@@ -0,0 +1,148 @@
+#ifndef CRYPTO_ORDERBOOK_H
+#define CRYPTO_ORDERBOOK_H
+
+/* Generated by cbindgen from src/ffi.rs - do not edit by hand */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Order side
+ */
+typedef enum ObSide {
+  OB_SIDE_BUY = 0,
+  OB_SIDE_SELL = 1,
+} ObSide;
+
+/**
+ * Opaque order book handle
+ */
+typedef struct ObOrderBook ObOrderBook;
+
+/**
+ * Trade reported through the trade callback
+ */
+typedef struct ObTrade {
+  uint64_t maker_order_id;
+  uint64_t taker_order_id;
+  double price;
+  double quantity;
+  /**
+   * Milliseconds since the Unix epoch
+   */
+  int64_t timestamp_ms;
+} ObTrade;
+
+/**
+ * Called once per trade generated by a submission; NULL disables the callback
+ */
+typedef void (*ObTradeCallback)(const struct ObTrade *trade, void *user_data);
+
+/**
+ * One price level
+ */
+typedef struct ObLevel {
+  double price;
+  double quantity;
+} ObLevel;
+
+/**
+ * Called with a depth snapshot; level pointers are only valid during the call
+ */
+typedef void (*ObSnapshotCallback)(const struct ObLevel *bids,
+                                   size_t bid_count,
+                                   const struct ObLevel *asks,
+                                   size_t ask_count,
+                                   void *user_data);
+
+/**
+ * Create a book for `symbol` (NUL-terminated UTF-8). Returns NULL on invalid input.
+ *
+ * # Safety
+ * `symbol` must be NULL or a valid NUL-terminated string.
+ */
+struct ObOrderBook *ob_book_new(const char *symbol);
+
+/**
+ * Destroy a book created by `ob_book_new`
+ *
+ * # Safety
+ * `book` must be NULL or a handle from `ob_book_new` not yet freed.
+ */
+void ob_book_free(struct ObOrderBook *book);
+
+/**
+ * Register (or clear, with NULL) the callback invoked for each trade
+ *
+ * # Safety
+ * `book` must be a live handle; `user_data` is passed through untouched.
+ */
+void ob_set_trade_callback(struct ObOrderBook *book, ObTradeCallback callback, void *user_data);
+
+/**
+ * Submit a limit order. Returns the order id, or 0 on invalid input.
+ *
+ * # Safety
+ * `book` must be a live handle.
+ */
+uint64_t ob_submit_limit(struct ObOrderBook *book, enum ObSide side, double price, double quantity);
+
+/**
+ * Submit a market order. Returns the order id, or 0 on invalid input.
+ *
+ * # Safety
+ * `book` must be a live handle.
+ */
+uint64_t ob_submit_market(struct ObOrderBook *book, enum ObSide side, double quantity);
+
+/**
+ * Cancel a resting order. Returns true if it was found.
+ *
+ * # Safety
+ * `book` must be a live handle.
+ */
+bool ob_cancel(struct ObOrderBook *book, uint64_t order_id);
+
+/**
+ * Copy up to `max_levels` levels of one side into `out`, best price first.
+ * Returns the number of levels written.
+ *
+ * # Safety
+ * `book` must be a live handle and `out` must point to `max_levels` writable levels.
+ */
+size_t ob_depth(const struct ObOrderBook *book,
+                enum ObSide side,
+                struct ObLevel *out,
+                size_t max_levels);
+
+/**
+ * Invoke `callback` with the top `levels` of both sides
+ *
+ * # Safety
+ * `book` must be a live handle.
+ */
+void ob_snapshot(const struct ObOrderBook *book,
+                 size_t levels,
+                 ObSnapshotCallback callback,
+                 void *user_data);
+
+/**
+ * Best bid price, or NaN if the bid side is empty
+ *
+ * # Safety
+ * `book` must be a live handle.
+ */
+double ob_best_bid(const struct ObOrderBook *book);
+
+/**
+ * Best ask price, or NaN if the ask side is empty
+ *
+ * # Safety
+ * `book` must be a live handle.
+ */
+double ob_best_ask(const struct ObOrderBook *book);
+
+#endif  /* CRYPTO_ORDERBOOK_H */
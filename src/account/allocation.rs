@@ -0,0 +1,216 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::account::{AccountId, Activity, FillKey};
+use crate::types::{OrderId, OrderSide, Symbol};
+
+/// Quantities closer than this are treated as equal
+const EPSILON: f64 = 1e-9;
+
+/// Account and quantity it is filled up to before the next one gets any
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PriorityTarget {
+    pub account: AccountId,
+    pub quantity: f64,
+}
+
+/// How the fills of one parent order are split between sub-accounts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum AllocationScheme {
+    /// In proportion to each account's weight
+    ProRata { weights: BTreeMap<AccountId, f64> },
+    /// Each account in turn up to its quantity; fills beyond every target
+    /// go to the last account
+    Priority { targets: Vec<PriorityTarget> },
+}
+
+impl AllocationScheme {
+    pub fn validate(&self) -> Result<(), String> {
+        let positive = |value: f64| value.is_finite() && value > 0.0;
+        match self {
+            AllocationScheme::ProRata { weights } => {
+                if weights.is_empty() {
+                    return Err("pro-rata allocation needs at least one account".to_string());
+                }
+                if let Some((account, _)) = weights.iter().find(|(_, &w)| !positive(w)) {
+                    return Err(format!("weight of {} must be positive", account));
+                }
+            }
+            AllocationScheme::Priority { targets } => {
+                if targets.is_empty() {
+                    return Err("priority allocation needs at least one account".to_string());
+                }
+                if let Some(target) = targets.iter().find(|t| !positive(t.quantity)) {
+                    return Err(format!("quantity of {} must be positive", target.account));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// What each account should hold once `filled` of the parent has traded
+    fn targets(&self, filled: f64, lot_size: Option<f64>) -> Vec<(AccountId, f64)> {
+        match self {
+            AllocationScheme::ProRata { weights } => {
+                let total: f64 = weights.values().sum();
+                let round = |quantity: f64| match lot_size {
+                    Some(lot) => (quantity / lot + EPSILON).floor() * lot,
+                    None => quantity,
+                };
+                let mut targets: Vec<(AccountId, f64)> = weights
+                    .iter()
+                    .map(|(account, weight)| (account.clone(), round(filled * weight / total)))
+                    .collect();
+                // Lots rounded away go to the heaviest account
+                let residual = filled - targets.iter().map(|(_, q)| q).sum::<f64>();
+                let heaviest = weights
+                    .values()
+                    .enumerate()
+                    .max_by(|(_, a), (_, b)| a.total_cmp(b))
+                    .map_or(0, |(i, _)| i);
+                targets[heaviest].1 += residual;
+                targets
+            }
+            AllocationScheme::Priority { targets } => {
+                let mut left = filled;
+                let mut allocated: Vec<(AccountId, f64)> = targets
+                    .iter()
+                    .map(|target| {
+                        let quantity = left.min(target.quantity);
+                        left -= quantity;
+                        (target.account.clone(), quantity)
+                    })
+                    .collect();
+                if let Some(last) = allocated.last_mut() {
+                    last.1 += left;
+                }
+                allocated
+            }
+        }
+    }
+}
+
+/// Fills of one parent order allocated so far
+///
+/// Every fill moves each account towards its share of the total filled, so
+/// rounding to lots never drifts over many small fills.
+#[derive(Debug, Clone, Serialize)]
+pub struct ParentAllocation {
+    pub order_id: OrderId,
+    pub scheme: AllocationScheme,
+    /// Sub-account quantities are whole multiples of this, when set
+    pub lot_size: Option<f64>,
+    pub filled: f64,
+    pub allocated: BTreeMap<AccountId, f64>,
+    fills: u64,
+}
+
+impl ParentAllocation {
+    pub fn new(order_id: OrderId, scheme: AllocationScheme) -> Self {
+        Self {
+            order_id,
+            scheme,
+            lot_size: None,
+            filled: 0.0,
+            allocated: BTreeMap::new(),
+            fills: 0,
+        }
+    }
+
+    pub fn with_lot_size(mut self, lot_size: f64) -> Self {
+        self.lot_size = Some(lot_size);
+        self
+    }
+
+    /// Split a fill of the parent into one trade per sub-account it goes to,
+    /// each charged its share of `fee`
+    ///
+    /// The trades carry the parent's order ID and fill sequence, so a
+    /// replayed fill is applied to each sub-account once.
+    pub fn allocate(
+        &mut self,
+        symbol: &Symbol,
+        side: OrderSide,
+        price: f64,
+        quantity: f64,
+        fee: f64,
+        timestamp: DateTime<Utc>,
+    ) -> Vec<(AccountId, Activity)> {
+        self.filled += quantity;
+        let fill = FillKey {
+            order_id: self.order_id,
+            fill_seq: self.fills,
+        };
+        self.fills += 1;
+
+        let mut trades = Vec::new();
+        for (account, target) in self.scheme.targets(self.filled, self.lot_size) {
+            let allocated = self.allocated.entry(account.clone()).or_default();
+            let share = target - *allocated;
+            if share <= EPSILON {
+                continue;
+            }
+            *allocated = target;
+            trades.push((
+                account,
+                Activity::Trade {
+                    symbol: symbol.clone(),
+                    side,
+                    price,
+                    quantity: share,
+                    fee: fee * share / quantity,
+                    timestamp,
+                    fill: Some(fill),
+                },
+            ));
+        }
+        trades
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account::Accounts;
+
+    #[test]
+    fn test_fills_follow_the_scheme_across_partial_fills() {
+        let symbol = Symbol::new("BTCUSDT");
+        let (a, b) = (AccountId::from("fund-a"), AccountId::from("fund-b"));
+        let scheme = AllocationScheme::ProRata {
+            weights: BTreeMap::from([(a.clone(), 2.0), (b.clone(), 1.0)]),
+        };
+        let mut parent = ParentAllocation::new(OrderId(7), scheme).with_lot_size(1.0);
+        let mut accounts = Accounts::new();
+        for quantity in [4.0, 2.0, 3.0] {
+            for (id, trade) in
+                parent.allocate(&symbol, OrderSide::Buy, 100.0, quantity, 0.9, Utc::now())
+            {
+                accounts.apply(&id, trade);
+            }
+        }
+        let position = |id| accounts.get(id).unwrap().positions[&symbol].quantity;
+        assert_eq!((position(&a), position(&b)), (6.0, 3.0));
+
+        let scheme = AllocationScheme::Priority {
+            targets: vec![
+                PriorityTarget {
+                    account: a.clone(),
+                    quantity: 5.0,
+                },
+                PriorityTarget {
+                    account: b.clone(),
+                    quantity: 1.0,
+                },
+            ],
+        };
+        let mut parent = ParentAllocation::new(OrderId(8), scheme);
+        let first = parent.allocate(&symbol, OrderSide::Sell, 100.0, 4.0, 0.0, Utc::now());
+        assert_eq!(first.len(), 1);
+        parent.allocate(&symbol, OrderSide::Sell, 100.0, 3.0, 0.0, Utc::now());
+        assert_eq!(parent.allocated, BTreeMap::from([(a, 5.0), (b, 2.0)]));
+    }
+}
@@ -0,0 +1,580 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::io;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::account::ledger::LedgerWriter;
+use crate::queue::{QueueConsumer, SharedPersistentQueue};
+use crate::risk::RiskLimits;
+use crate::types::{OrderId, OrderSide, Symbol};
+
+/// Identifier of a trading account
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct AccountId(pub String);
+
+impl fmt::Display for AccountId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<&str> for AccountId {
+    fn from(id: &str) -> Self {
+        Self(id.to_string())
+    }
+}
+
+/// Identifies one fill of one order, so a replayed report is applied once
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FillKey {
+    pub order_id: OrderId,
+    /// Position of this fill among the order's fills
+    pub fill_seq: u64,
+}
+
+/// Anything that changes an account's balance or positions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum Activity {
+    Trade {
+        symbol: Symbol,
+        side: OrderSide,
+        price: f64,
+        quantity: f64,
+        fee: f64,
+        timestamp: DateTime<Utc>,
+        /// Dedup key of the execution report, when it has one
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        fill: Option<FillKey>,
+    },
+    /// Funding paid (negative) or received (positive) on a position
+    Funding {
+        symbol: Symbol,
+        amount: f64,
+        timestamp: DateTime<Utc>,
+    },
+    /// Deposit (positive) or withdrawal (negative)
+    Transfer {
+        amount: f64,
+        timestamp: DateTime<Utc>,
+    },
+    /// Switch a position's margin mode, setting aside `amount` of the
+    /// balance for it when isolated
+    Margin {
+        symbol: Symbol,
+        mode: MarginMode,
+        amount: f64,
+        timestamp: DateTime<Utc>,
+    },
+    /// Switch how the account's cross positions are margined
+    MarginMethod {
+        method: MarginMethod,
+        timestamp: DateTime<Utc>,
+    },
+}
+
+impl Activity {
+    pub fn timestamp(&self) -> DateTime<Utc> {
+        match self {
+            Activity::Trade { timestamp, .. }
+            | Activity::Funding { timestamp, .. }
+            | Activity::Transfer { timestamp, .. }
+            | Activity::Margin { timestamp, .. }
+            | Activity::MarginMethod { timestamp, .. } => *timestamp,
+        }
+    }
+
+    pub fn fill_key(&self) -> Option<FillKey> {
+        match self {
+            Activity::Trade { fill, .. } => *fill,
+            _ => None,
+        }
+    }
+}
+
+/// Activity applied to an account, as sent to downstream consumers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccountUpdate {
+    pub account: AccountId,
+    pub activity: Activity,
+}
+
+/// What collateral a position can lose
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MarginMode {
+    /// Backed by the whole account's equity, shared with other cross positions
+    #[default]
+    Cross,
+    /// Backed only by the margin set aside for it; losses stop there
+    Isolated,
+}
+
+/// How an account's cross positions are margined
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MarginMethod {
+    /// Each position at its own rates, summed
+    #[default]
+    Standard,
+    /// The worst loss of the positions together across stress scenarios, so
+    /// hedges offset
+    Portfolio,
+}
+
+/// Net position in one symbol
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Position {
+    pub symbol: Symbol,
+    /// Signed size, negative when short
+    pub quantity: f64,
+    /// Average price of the open quantity
+    pub entry_price: f64,
+    pub realized_pnl: f64,
+    #[serde(default)]
+    pub margin_mode: MarginMode,
+    /// Part of the balance set aside for an isolated position
+    #[serde(default)]
+    pub isolated_margin: f64,
+    /// Mark at which the position is liquidated, as of the last revaluation
+    #[serde(default)]
+    pub liquidation_price: Option<f64>,
+}
+
+impl Position {
+    fn new(symbol: Symbol) -> Self {
+        Self {
+            symbol,
+            quantity: 0.0,
+            entry_price: 0.0,
+            realized_pnl: 0.0,
+            margin_mode: MarginMode::Cross,
+            isolated_margin: 0.0,
+            liquidation_price: None,
+        }
+    }
+
+    pub fn is_isolated(&self) -> bool {
+        self.margin_mode == MarginMode::Isolated
+    }
+
+    /// Apply a fill and return the PnL it realized
+    fn fill(&mut self, side: OrderSide, price: f64, quantity: f64) -> f64 {
+        let signed = match side {
+            OrderSide::Buy => quantity,
+            OrderSide::Sell => -quantity,
+        };
+        let mut realized = 0.0;
+        if self.quantity * signed < 0.0 {
+            let closed = signed.abs().min(self.quantity.abs());
+            realized = closed * (price - self.entry_price) * self.quantity.signum();
+        }
+
+        let quantity = self.quantity + signed;
+        if quantity == 0.0 {
+            self.entry_price = 0.0;
+        } else if self.quantity * signed >= 0.0 {
+            // Adding to (or opening) the position averages the entry
+            self.entry_price =
+                (self.entry_price * self.quantity.abs() + price * signed.abs()) / quantity.abs();
+        } else if self.quantity * quantity < 0.0 {
+            // Flipped through zero: the remainder opened at this price
+            self.entry_price = price;
+        }
+        self.quantity = quantity;
+        self.realized_pnl += realized;
+        if self.is_isolated() {
+            // Realized PnL settles against the set-aside margin, which is
+            // released once the position is closed
+            self.isolated_margin = if quantity == 0.0 {
+                0.0
+            } else {
+                (self.isolated_margin + realized).max(0.0)
+            };
+        }
+        realized
+    }
+
+    /// Most an order on `side` can trade without increasing the position's
+    /// exposure: all of it on the closing side, nothing on the other
+    pub fn reducible(&self, side: OrderSide) -> f64 {
+        match side {
+            OrderSide::Buy => (-self.quantity).max(0.0),
+            OrderSide::Sell => self.quantity.max(0.0),
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.quantity != 0.0
+    }
+}
+
+/// Collateral balance, positions and the activity that produced them
+///
+/// The balance moves with transfers, realized PnL, fees and funding;
+/// unrealized PnL of open positions is not included.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Account {
+    pub id: AccountId,
+    pub balance: f64,
+    pub positions: BTreeMap<Symbol, Position>,
+    pub activity: Vec<Activity>,
+    /// Fills already applied
+    #[serde(default)]
+    pub fills: HashSet<FillKey>,
+    #[serde(default)]
+    pub margin_method: MarginMethod,
+}
+
+impl Account {
+    pub fn new(id: AccountId) -> Self {
+        Self {
+            id,
+            balance: 0.0,
+            positions: BTreeMap::new(),
+            activity: Vec::new(),
+            fills: HashSet::new(),
+            margin_method: MarginMethod::Standard,
+        }
+    }
+
+    /// Whether `activity` is a fill this account has already applied
+    pub fn is_duplicate(&self, activity: &Activity) -> bool {
+        activity
+            .fill_key()
+            .is_some_and(|key| self.fills.contains(&key))
+    }
+
+    /// Apply `activity`; returns false for an already applied fill
+    pub fn apply(&mut self, activity: Activity) -> bool {
+        if let Some(key) = activity.fill_key() {
+            if !self.fills.insert(key) {
+                tracing::debug!("Skipping duplicate fill {:?} on account {}", key, self.id);
+                return false;
+            }
+        }
+        match &activity {
+            Activity::Trade {
+                symbol,
+                side,
+                price,
+                quantity,
+                fee,
+                ..
+            } => {
+                let position = self
+                    .positions
+                    .entry(symbol.clone())
+                    .or_insert_with(|| Position::new(symbol.clone()));
+                self.balance += position.fill(*side, *price, *quantity) - fee;
+            }
+            Activity::Funding { amount, .. } | Activity::Transfer { amount, .. } => {
+                self.balance += amount;
+            }
+            Activity::Margin {
+                symbol,
+                mode,
+                amount,
+                ..
+            } => {
+                let position = self
+                    .positions
+                    .entry(symbol.clone())
+                    .or_insert_with(|| Position::new(symbol.clone()));
+                position.margin_mode = *mode;
+                position.isolated_margin = match mode {
+                    MarginMode::Cross => 0.0,
+                    MarginMode::Isolated => amount.max(0.0),
+                };
+            }
+            Activity::MarginMethod { method, .. } => self.margin_method = *method,
+        }
+        self.activity.push(activity);
+        true
+    }
+
+    /// Balance backing cross positions: everything not set aside for
+    /// isolated ones
+    pub fn cross_balance(&self) -> f64 {
+        let isolated: f64 = self.positions.values().map(|p| p.isolated_margin).sum();
+        self.balance - isolated
+    }
+
+    pub fn open_positions(&self) -> Vec<Position> {
+        self.positions
+            .values()
+            .filter(|position| position.is_open())
+            .cloned()
+            .collect()
+    }
+
+    /// State of the account after replaying activity before `until`
+    pub fn as_of(&self, until: DateTime<Utc>) -> Account {
+        let mut account = Account::new(self.id.clone());
+        for activity in self.activity.iter().filter(|a| a.timestamp() < until) {
+            account.apply(activity.clone());
+        }
+        account
+    }
+}
+
+/// Every account known to this process
+#[derive(Debug, Default)]
+pub struct Accounts {
+    accounts: HashMap<AccountId, Account>,
+    updates: Option<SharedPersistentQueue<AccountUpdate>>,
+    ledger: Option<LedgerWriter>,
+    /// Limits liquidation prices are kept up to date with
+    risk: Option<RiskLimits>,
+    /// Last mark of each symbol, for liquidation prices
+    marks: HashMap<Symbol, f64>,
+}
+
+impl Accounts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Write every update to `queue` before applying it, for portfolio and
+    /// audit consumers that must not miss any
+    pub fn with_update_queue(mut self, queue: SharedPersistentQueue<AccountUpdate>) -> Self {
+        self.updates = Some(queue);
+        self
+    }
+
+    /// Write every applied activity and the resulting balance to `ledger`
+    pub fn with_ledger(mut self, ledger: LedgerWriter) -> Self {
+        self.ledger = Some(ledger);
+        self
+    }
+
+    /// Keep the liquidation price of every position up to date under
+    /// `limits`, revalued on each activity and each new mark
+    pub fn with_risk_limits(mut self, limits: RiskLimits) -> Self {
+        self.risk = Some(limits);
+        self
+    }
+
+    /// Revalue liquidation prices of accounts holding `symbol` at `price`
+    pub fn update_mark(&mut self, symbol: &Symbol, price: f64) {
+        self.marks.insert(symbol.clone(), price);
+        let Some(limits) = &self.risk else {
+            return;
+        };
+        for account in self.accounts.values_mut() {
+            if account.positions.get(symbol).is_some_and(Position::is_open) {
+                limits.update_liquidation_prices(account, |s| self.marks.get(s).copied());
+            }
+        }
+    }
+
+    /// Record `activity`, opening the account on first use
+    ///
+    /// Fills already applied are ignored, and not queued again.
+    pub fn apply(&mut self, id: &AccountId, activity: Activity) -> bool {
+        let account = self
+            .accounts
+            .entry(id.clone())
+            .or_insert_with(|| Account::new(id.clone()));
+        if account.is_duplicate(&activity) {
+            return false;
+        }
+        if let Some(queue) = &self.updates {
+            let update = AccountUpdate {
+                account: id.clone(),
+                activity: activity.clone(),
+            };
+            if let Err(e) = queue.send(&update) {
+                tracing::warn!("Failed to queue update for account {}: {}", id, e);
+            }
+        }
+        let entry = self.ledger.as_ref().map(|_| activity.clone());
+        let applied = account.apply(activity);
+        if let (true, Some(limits)) = (applied, &self.risk) {
+            limits.update_liquidation_prices(account, |s| self.marks.get(s).copied());
+        }
+        if let (true, Some(ledger), Some(entry)) = (applied, &self.ledger, entry) {
+            ledger.balance_change(id, &entry, account.balance);
+        }
+        applied
+    }
+
+    /// Apply every pending update from `consumer`, acknowledging as it goes
+    ///
+    /// Replays after a restart are safe: fills already applied are skipped.
+    /// Returns how many updates changed an account.
+    pub fn consume(&mut self, consumer: &mut QueueConsumer<AccountUpdate>) -> io::Result<usize> {
+        let mut applied = 0;
+        while let Some((seq, update)) = consumer.poll()? {
+            if self.apply(&update.account, update.activity) {
+                applied += 1;
+            }
+            consumer.ack(seq)?;
+        }
+        Ok(applied)
+    }
+
+    pub fn get(&self, id: &AccountId) -> Option<&Account> {
+        self.accounts.get(id)
+    }
+
+    pub fn ids(&self) -> Vec<AccountId> {
+        let mut ids: Vec<AccountId> = self.accounts.keys().cloned().collect();
+        ids.sort();
+        ids
+    }
+}
+
+/// Thread-safe wrapper for Accounts
+pub struct SharedAccounts {
+    inner: Arc<Mutex<Accounts>>,
+}
+
+impl SharedAccounts {
+    pub fn new(accounts: Accounts) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(accounts)),
+        }
+    }
+
+    pub fn apply(&self, id: &AccountId, activity: Activity) -> bool {
+        self.inner.lock().unwrap().apply(id, activity)
+    }
+
+    pub fn consume(&self, consumer: &mut QueueConsumer<AccountUpdate>) -> io::Result<usize> {
+        self.inner.lock().unwrap().consume(consumer)
+    }
+
+    pub fn update_mark(&self, symbol: &Symbol, price: f64) {
+        self.inner.lock().unwrap().update_mark(symbol, price)
+    }
+
+    pub fn get(&self, id: &AccountId) -> Option<Account> {
+        self.inner.lock().unwrap().get(id).cloned()
+    }
+
+    pub fn ids(&self) -> Vec<AccountId> {
+        self.inner.lock().unwrap().ids()
+    }
+}
+
+impl Clone for SharedAccounts {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl Default for SharedAccounts {
+    fn default() -> Self {
+        Self::new(Accounts::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(side: OrderSide, price: f64, quantity: f64) -> Activity {
+        Activity::Trade {
+            symbol: Symbol::new("BTCUSDT"),
+            side,
+            price,
+            quantity,
+            fee: 1.0,
+            timestamp: Utc::now(),
+            fill: None,
+        }
+    }
+
+    #[test]
+    fn test_balance_follows_realized_pnl_fees_and_funding() {
+        let mut account = Account::new("alice".into());
+        account.apply(Activity::Transfer {
+            amount: 1_000.0,
+            timestamp: Utc::now(),
+        });
+        account.apply(trade(OrderSide::Buy, 100.0, 2.0));
+        account.apply(trade(OrderSide::Buy, 110.0, 2.0));
+        assert_eq!(account.positions["BTCUSDT"].entry_price, 105.0);
+
+        // Sell 5: closes 4 at +15 each and opens a 1 lot short at 120
+        account.apply(trade(OrderSide::Sell, 120.0, 5.0));
+        let position = &account.positions["BTCUSDT"];
+        assert_eq!((position.quantity, position.entry_price), (-1.0, 120.0));
+        assert_eq!(position.realized_pnl, 60.0);
+        // Only a buy can reduce the short
+        assert_eq!(position.reducible(OrderSide::Buy), 1.0);
+        assert_eq!(position.reducible(OrderSide::Sell), 0.0);
+
+        account.apply(Activity::Funding {
+            symbol: Symbol::new("BTCUSDT"),
+            amount: -0.5,
+            timestamp: Utc::now(),
+        });
+        assert_eq!(account.balance, 1_000.0 + 60.0 - 3.0 - 0.5);
+    }
+
+    #[test]
+    fn test_liquidation_price_follows_positions_and_balance() {
+        let mut accounts = Accounts::new().with_risk_limits(RiskLimits::default());
+        let id = AccountId::from("alice");
+        let transfer = |amount| Activity::Transfer {
+            amount,
+            timestamp: Utc::now(),
+        };
+        accounts.apply(&id, transfer(500.0));
+        accounts.apply(&id, trade(OrderSide::Buy, 100.0, 10.0));
+        let liquidation =
+            |accounts: &Accounts| accounts.get(&id).unwrap().positions["BTCUSDT"].liquidation_price;
+        // 5% maintenance plus the 0.5% liquidation fee
+        assert!((liquidation(&accounts).unwrap() - 501.0 / 9.45).abs() < 1e-9);
+
+        accounts.apply(&id, transfer(600.0));
+        assert_eq!(liquidation(&accounts), None);
+    }
+
+    #[test]
+    fn test_replayed_fills_are_applied_once() {
+        use crate::queue::{PersistentQueue, QueueConfig};
+
+        let dir = std::env::temp_dir().join(format!("account-updates-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let queue = SharedPersistentQueue::new(
+            PersistentQueue::open(&dir, QueueConfig::default()).unwrap(),
+        );
+        let mut trading = Accounts::new().with_update_queue(queue.clone());
+        let id = AccountId::from("alice");
+        let order_id = OrderId(42);
+        for fill_seq in 0..3 {
+            let mut fill = trade(OrderSide::Buy, 100.0, 1.0);
+            if let Activity::Trade { fill: key, .. } = &mut fill {
+                *key = Some(FillKey { order_id, fill_seq });
+            }
+            assert!(trading.apply(&id, fill.clone()));
+            // The same report arriving twice is neither applied nor queued
+            assert!(!trading.apply(&id, fill));
+        }
+
+        // Portfolio applies two updates, then restarts before acknowledging
+        let mut portfolio = Accounts::new();
+        let mut consumer = queue.consumer("portfolio").unwrap();
+        for _ in 0..2 {
+            let (_, update) = consumer.poll().unwrap().unwrap();
+            portfolio.apply(&update.account, update.activity);
+        }
+        let mut consumer = queue.consumer("portfolio").unwrap();
+        assert_eq!(portfolio.consume(&mut consumer).unwrap(), 1);
+        assert_eq!(
+            portfolio.get(&id).unwrap().positions["BTCUSDT"].quantity,
+            3.0
+        );
+        assert_eq!(consumer.acked(), 3);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
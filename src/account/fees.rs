@@ -0,0 +1,251 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::account::AccountId;
+use crate::orderbook::Liquidity;
+
+/// Days of traded volume a fee tier is decided on
+pub const VOLUME_WINDOW_DAYS: i64 = 30;
+
+/// Rates that apply from a level of 30-day traded volume
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FeeTier {
+    /// Notional traded over the window needed to reach this tier
+    pub min_volume: f64,
+    /// Negative for a rebate paid to the maker
+    pub maker_bps: f64,
+    pub taker_bps: f64,
+}
+
+impl FeeTier {
+    pub fn rate_bps(&self, liquidity: Liquidity) -> f64 {
+        match liquidity {
+            Liquidity::Maker => self.maker_bps,
+            Liquidity::Taker => self.taker_bps,
+        }
+    }
+}
+
+/// Volume-based fee tiers, cheapest for the most active accounts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeeSchedule {
+    /// Sorted by `min_volume`; the first applies below every threshold
+    pub tiers: Vec<FeeTier>,
+}
+
+impl FeeSchedule {
+    pub fn new(mut tiers: Vec<FeeTier>) -> Self {
+        tiers.sort_by(|a, b| a.min_volume.total_cmp(&b.min_volume));
+        Self { tiers }
+    }
+
+    /// One tier charging `bps` on both sides, whatever the volume
+    pub fn flat(bps: f64) -> Self {
+        Self::new(vec![FeeTier {
+            min_volume: 0.0,
+            maker_bps: bps,
+            taker_bps: bps,
+        }])
+    }
+
+    /// Tier reached with `volume` traded over the window, by index
+    pub fn tier_index(&self, volume: f64) -> usize {
+        self.tiers
+            .iter()
+            .rposition(|tier| volume >= tier.min_volume)
+            .unwrap_or(0)
+    }
+
+    pub fn tier(&self, volume: f64) -> Option<&FeeTier> {
+        self.tiers.get(self.tier_index(volume))
+    }
+
+    /// Rate a fill is charged at with `volume` traded over the window
+    pub fn rate_bps(&self, volume: f64, liquidity: Liquidity) -> f64 {
+        self.tier(volume)
+            .map_or(0.0, |tier| tier.rate_bps(liquidity))
+    }
+}
+
+impl Default for FeeSchedule {
+    fn default() -> Self {
+        Self::flat(0.0)
+    }
+}
+
+/// An account's place in the fee schedule
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FeeStatus {
+    pub volume_30d: f64,
+    pub tier: usize,
+    pub maker_bps: f64,
+    pub taker_bps: f64,
+    /// Volume still needed to reach the next tier, None at the top one
+    pub next_tier_volume: Option<f64>,
+}
+
+/// Rolling 30-day volume per account, and the fees it earns
+#[derive(Debug, Default)]
+pub struct FeeTracker {
+    schedule: FeeSchedule,
+    /// Fill notionals by account, oldest first
+    volume: HashMap<AccountId, VecDeque<(DateTime<Utc>, f64)>>,
+}
+
+impl FeeTracker {
+    pub fn new(schedule: FeeSchedule) -> Self {
+        Self {
+            schedule,
+            volume: HashMap::new(),
+        }
+    }
+
+    pub fn schedule(&self) -> &FeeSchedule {
+        &self.schedule
+    }
+
+    /// Notional `account` traded in the 30 days up to `now`
+    pub fn volume_30d(&self, account: &AccountId, now: DateTime<Utc>) -> f64 {
+        let since = now - Duration::days(VOLUME_WINDOW_DAYS);
+        self.volume.get(account).map_or(0.0, |fills| {
+            fills
+                .iter()
+                .filter(|&&(at, _)| at > since && at <= now)
+                .map(|&(_, notional)| notional)
+                .sum()
+        })
+    }
+
+    /// Rate `account` would pay on its next fill at `now`
+    pub fn rate_bps(&self, account: &AccountId, liquidity: Liquidity, now: DateTime<Utc>) -> f64 {
+        let volume = self.volume_30d(account, now);
+        self.schedule.rate_bps(volume, liquidity)
+    }
+
+    /// Fee on a fill of `notional`, negative for a rebate, at the tier
+    /// reached before it; the fill then counts towards the account's volume
+    pub fn charge(
+        &mut self,
+        account: &AccountId,
+        liquidity: Liquidity,
+        notional: f64,
+        at: DateTime<Utc>,
+    ) -> f64 {
+        let fee = notional * self.rate_bps(account, liquidity, at) / 10_000.0;
+        let fills = self.volume.entry(account.clone()).or_default();
+        fills.push_back((at, notional));
+        // Fills arrive roughly in time order, so the oldest are at the front
+        let since = at - Duration::days(VOLUME_WINDOW_DAYS);
+        while fills.front().is_some_and(|&(filled, _)| filled <= since) {
+            fills.pop_front();
+        }
+        fee
+    }
+
+    pub fn status(&self, account: &AccountId, now: DateTime<Utc>) -> FeeStatus {
+        let volume_30d = self.volume_30d(account, now);
+        let tier = self.schedule.tier_index(volume_30d);
+        let rate = |liquidity| self.schedule.rate_bps(volume_30d, liquidity);
+        FeeStatus {
+            volume_30d,
+            tier,
+            maker_bps: rate(Liquidity::Maker),
+            taker_bps: rate(Liquidity::Taker),
+            next_tier_volume: self
+                .schedule
+                .tiers
+                .get(tier + 1)
+                .map(|next| next.min_volume - volume_30d),
+        }
+    }
+}
+
+/// Thread-safe wrapper for FeeTracker
+#[derive(Default)]
+pub struct SharedFeeTracker {
+    inner: Arc<Mutex<FeeTracker>>,
+}
+
+impl SharedFeeTracker {
+    pub fn new(tracker: FeeTracker) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(tracker)),
+        }
+    }
+
+    pub fn rate_bps(&self, account: &AccountId, liquidity: Liquidity, now: DateTime<Utc>) -> f64 {
+        self.inner.lock().unwrap().rate_bps(account, liquidity, now)
+    }
+
+    pub fn charge(
+        &self,
+        account: &AccountId,
+        liquidity: Liquidity,
+        notional: f64,
+        at: DateTime<Utc>,
+    ) -> f64 {
+        self.inner
+            .lock()
+            .unwrap()
+            .charge(account, liquidity, notional, at)
+    }
+
+    pub fn status(&self, account: &AccountId, now: DateTime<Utc>) -> FeeStatus {
+        self.inner.lock().unwrap().status(account, now)
+    }
+}
+
+impl Clone for SharedFeeTracker {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tiers_follow_rolling_volume() {
+        let schedule = FeeSchedule::new(vec![
+            FeeTier {
+                min_volume: 1_000_000.0,
+                maker_bps: -0.5,
+                taker_bps: 3.0,
+            },
+            FeeTier {
+                min_volume: 0.0,
+                maker_bps: 1.0,
+                taker_bps: 5.0,
+            },
+        ]);
+        let mut tracker = FeeTracker::new(schedule);
+        let account = AccountId("mm".to_string());
+        let start = Utc::now();
+
+        // The fill that crosses the threshold still pays the old rate
+        assert_eq!(
+            tracker.charge(&account, Liquidity::Taker, 1_000_000.0, start),
+            500.0
+        );
+        assert_eq!(
+            tracker.charge(&account, Liquidity::Maker, 100_000.0, start),
+            -5.0
+        );
+        let status = tracker.status(&account, start);
+        assert_eq!((status.tier, status.next_tier_volume), (1, None));
+
+        // Volume older than 30 days no longer counts
+        let later = start + Duration::days(VOLUME_WINDOW_DAYS);
+        assert_eq!(tracker.volume_30d(&account, later), 0.0);
+        assert_eq!(
+            tracker.charge(&account, Liquidity::Maker, 100_000.0, later),
+            10.0
+        );
+    }
+}
@@ -0,0 +1,344 @@
+use std::fmt::{self, Write as _};
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::account::balances::{AccountId, Activity};
+use crate::orderbook::ExecutionReport;
+use crate::types::{Order, OrderSide};
+
+/// Layout of ledger lines
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LedgerFormat {
+    /// Aligned `key=value` lines for reading
+    #[default]
+    Text,
+    /// One row per entry under a fixed header, for spreadsheets and diffing
+    Csv,
+}
+
+const CSV_HEADER: &str =
+    "seq,timestamp,kind,account,order_id,symbol,side,price,quantity,amount,balance,detail";
+
+/// One ledger line before formatting; empty fields are left out of text lines
+#[derive(Debug, Default)]
+struct Row<'a> {
+    timestamp: Option<DateTime<Utc>>,
+    kind: &'a str,
+    account: Option<&'a AccountId>,
+    order_id: Option<u64>,
+    symbol: Option<&'a str>,
+    side: Option<OrderSide>,
+    price: Option<f64>,
+    quantity: Option<f64>,
+    amount: Option<f64>,
+    balance: Option<f64>,
+    detail: Option<String>,
+}
+
+struct Inner {
+    out: Box<dyn Write + Send>,
+    format: LedgerFormat,
+    seq: u64,
+}
+
+/// Append-only, human-readable record of order actions and balance changes
+///
+/// Kept apart from tracing output so it stays complete whatever the log
+/// level, and stable enough to diff between runs: lines are numbered and
+/// every field is printed the same way each time. Every entry is flushed as
+/// it is written.
+#[derive(Clone)]
+pub struct LedgerWriter {
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl fmt::Debug for LedgerWriter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LedgerWriter")
+            .field("format", &self.inner.lock().unwrap().format)
+            .finish_non_exhaustive()
+    }
+}
+
+impl LedgerWriter {
+    /// Append to the ledger at `path`, writing the CSV header to a new file
+    pub fn open(path: impl AsRef<Path>, format: LedgerFormat) -> io::Result<Self> {
+        let file: File = OpenOptions::new().create(true).append(true).open(path)?;
+        let is_new = file.metadata()?.len() == 0;
+        let mut out = BufWriter::new(file);
+        if is_new && format == LedgerFormat::Csv {
+            writeln!(out, "{}", CSV_HEADER)?;
+            out.flush()?;
+        }
+        Ok(Self::new(out, format))
+    }
+
+    /// Write to `out` as is; no header is written
+    pub fn new(out: impl Write + Send + 'static, format: LedgerFormat) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Inner {
+                out: Box::new(out),
+                format,
+                seq: 0,
+            })),
+        }
+    }
+
+    pub fn order_submitted(&self, order: &Order) {
+        self.write(Row {
+            timestamp: Some(order.timestamp),
+            kind: "submit",
+            order_id: Some(order.id.0),
+            symbol: Some(order.symbol.as_str()),
+            side: Some(order.side),
+            price: Some(order.price.value()),
+            quantity: Some(order.initial_quantity.value()),
+            detail: Some(format!("{:?}", order.order_type)),
+            ..Default::default()
+        });
+    }
+
+    pub fn execution(&self, report: &ExecutionReport) {
+        let row = match report {
+            ExecutionReport::Fill {
+                order_id,
+                symbol,
+                price,
+                quantity,
+                liquidity,
+                timestamp,
+            } => Row {
+                timestamp: Some(*timestamp),
+                kind: "fill",
+                order_id: Some(order_id.0),
+                symbol: Some(symbol.as_str()),
+                price: Some(*price),
+                quantity: Some(*quantity),
+                detail: Some(format!("{:?}", liquidity)),
+                ..Default::default()
+            },
+            ExecutionReport::OrderUpdate {
+                order_id,
+                symbol,
+                side,
+                status,
+                filled_quantity,
+                remaining_quantity,
+                timestamp,
+                ..
+            } => Row {
+                timestamp: Some(*timestamp),
+                kind: "order",
+                order_id: Some(order_id.0),
+                symbol: Some(symbol.as_str()),
+                side: Some(*side),
+                quantity: Some(*filled_quantity),
+                detail: Some(format!("{:?} remaining={}", status, remaining_quantity)),
+                ..Default::default()
+            },
+        };
+        self.write(row);
+    }
+
+    /// An applied account activity and the balance it left behind
+    pub fn balance_change(&self, account: &AccountId, activity: &Activity, balance: f64) {
+        let row = match activity {
+            Activity::Trade {
+                symbol,
+                side,
+                price,
+                quantity,
+                fee,
+                timestamp,
+                fill,
+            } => Row {
+                timestamp: Some(*timestamp),
+                kind: "trade",
+                order_id: fill.map(|fill| fill.order_id.0),
+                symbol: Some(symbol.as_str()),
+                side: Some(*side),
+                price: Some(*price),
+                quantity: Some(*quantity),
+                amount: Some(-fee),
+                ..Default::default()
+            },
+            Activity::Funding {
+                symbol,
+                amount,
+                timestamp,
+            } => Row {
+                timestamp: Some(*timestamp),
+                kind: "funding",
+                symbol: Some(symbol.as_str()),
+                amount: Some(*amount),
+                ..Default::default()
+            },
+            Activity::Transfer { amount, timestamp } => Row {
+                timestamp: Some(*timestamp),
+                kind: "transfer",
+                amount: Some(*amount),
+                ..Default::default()
+            },
+            Activity::Margin {
+                symbol,
+                mode,
+                amount,
+                timestamp,
+            } => Row {
+                timestamp: Some(*timestamp),
+                kind: "margin",
+                symbol: Some(symbol.as_str()),
+                amount: Some(*amount),
+                detail: Some(format!("{:?}", mode)),
+                ..Default::default()
+            },
+            Activity::MarginMethod { method, timestamp } => Row {
+                timestamp: Some(*timestamp),
+                kind: "margin",
+                detail: Some(format!("{:?}", method)),
+                ..Default::default()
+            },
+        };
+        self.write(Row {
+            account: Some(account),
+            balance: Some(balance),
+            ..row
+        });
+    }
+
+    fn write(&self, row: Row<'_>) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.seq += 1;
+        let line = match inner.format {
+            LedgerFormat::Text => text_line(inner.seq, &row),
+            LedgerFormat::Csv => csv_line(inner.seq, &row),
+        };
+        let written = writeln!(inner.out, "{}", line).and_then(|_| inner.out.flush());
+        if let Err(e) = written {
+            tracing::warn!("Failed to write ledger entry {}: {}", inner.seq, e);
+        }
+    }
+}
+
+fn side_str(side: OrderSide) -> &'static str {
+    match side {
+        OrderSide::Buy => "buy",
+        OrderSide::Sell => "sell",
+    }
+}
+
+fn timestamp_str(timestamp: Option<DateTime<Utc>>) -> String {
+    timestamp
+        .map(|t| t.to_rfc3339_opts(SecondsFormat::Millis, true))
+        .unwrap_or_default()
+}
+
+fn text_line(seq: u64, row: &Row<'_>) -> String {
+    let mut line = format!(
+        "{:>8} {} {:<8}",
+        seq,
+        timestamp_str(row.timestamp),
+        row.kind
+    );
+    let mut field = |key: &str, value: Option<String>| {
+        if let Some(value) = value {
+            let _ = write!(line, " {}={}", key, value);
+        }
+    };
+    field("account", row.account.map(|a| a.to_string()));
+    field("order", row.order_id.map(|id| id.to_string()));
+    field("symbol", row.symbol.map(str::to_string));
+    field("side", row.side.map(|s| side_str(s).to_string()));
+    field("price", row.price.map(|p| p.to_string()));
+    field("qty", row.quantity.map(|q| q.to_string()));
+    field("amount", row.amount.map(|a| a.to_string()));
+    field("balance", row.balance.map(|b| b.to_string()));
+    field("detail", row.detail.clone());
+    line
+}
+
+fn csv_line(seq: u64, row: &Row<'_>) -> String {
+    let number = |value: Option<f64>| value.map(|v| v.to_string()).unwrap_or_default();
+    [
+        seq.to_string(),
+        timestamp_str(row.timestamp),
+        row.kind.to_string(),
+        row.account.map(|a| csv_field(&a.0)).unwrap_or_default(),
+        row.order_id.map(|id| id.to_string()).unwrap_or_default(),
+        row.symbol.map(csv_field).unwrap_or_default(),
+        row.side.map(side_str).unwrap_or_default().to_string(),
+        number(row.price),
+        number(row.quantity),
+        number(row.amount),
+        number(row.balance),
+        row.detail.as_deref().map(csv_field).unwrap_or_default(),
+    ]
+    .join(",")
+}
+
+/// Quote a field when it would break the row
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    /// Shares the written bytes with the test
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_csv_rows_for_orders_and_balances() {
+        let buffer = Buffer::default();
+        let ledger = LedgerWriter::new(buffer.clone(), LedgerFormat::Csv);
+        let timestamp = Utc.timestamp_opt(0, 0).unwrap();
+
+        let order = Order::new_limit("BTCUSDT", OrderSide::Buy, 100.0, 2.0);
+        ledger.order_submitted(&order);
+        ledger.balance_change(
+            &AccountId::from("desk, one"),
+            &Activity::Transfer {
+                amount: 500.0,
+                timestamp,
+            },
+            500.0,
+        );
+
+        let written = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<&str> = written.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("1,"));
+        assert!(lines[0].contains(&format!(",submit,,{},BTCUSDT,buy,100,2,,,", order.id.0)));
+        assert_eq!(
+            lines[1],
+            "2,1970-01-01T00:00:00.000Z,transfer,\"desk, one\",,,,,,500,500,"
+        );
+        assert_eq!(
+            lines[1].split(',').count(),
+            CSV_HEADER.split(',').count() + 1
+        );
+    }
+}
@@ -0,0 +1,23 @@
+// Account balances, positions and end-of-day statements
+//
+// Balances are collateral: they move with transfers, realized PnL, fees and
+// funding. Fees follow volume tiers on each account's 30-day traded volume.
+// Statements are rebuilt from the activity history so any past day can be
+// regenerated for reconciliation.
+
+pub mod allocation;
+pub mod balances;
+pub mod fees;
+pub mod ledger;
+pub mod statement;
+
+pub use allocation::{AllocationScheme, ParentAllocation, PriorityTarget};
+pub use balances::{
+    Account, AccountId, AccountUpdate, Accounts, Activity, FillKey, MarginMethod, MarginMode,
+    Position, SharedAccounts,
+};
+pub use fees::{FeeSchedule, FeeStatus, FeeTier, FeeTracker, SharedFeeTracker};
+pub use ledger::{LedgerFormat, LedgerWriter};
+#[cfg(feature = "net")]
+pub use statement::start_daily;
+pub use statement::{generate_all, Statement, StatementStore};
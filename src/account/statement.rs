@@ -0,0 +1,255 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::account::balances::{Account, AccountId, Activity, Position, SharedAccounts};
+
+/// End-of-day summary of one account, in UTC days
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Statement {
+    pub account: AccountId,
+    pub date: NaiveDate,
+    pub opening_balance: f64,
+    pub closing_balance: f64,
+    pub trades: Vec<Activity>,
+    pub realized_pnl: f64,
+    pub fees: f64,
+    pub funding: f64,
+    pub transfers: f64,
+    /// Positions still open at the end of the day
+    pub open_positions: Vec<Position>,
+    pub generated_at: DateTime<Utc>,
+}
+
+impl Statement {
+    /// Summarize `date` from the account's full activity history
+    pub fn generate(account: &Account, date: NaiveDate) -> Self {
+        let start = date.and_hms_opt(0, 0, 0).unwrap().and_utc();
+        let end = start + chrono::Duration::days(1);
+        let opening = account.as_of(start);
+        let closing = account.as_of(end);
+        let realized =
+            |account: &Account| -> f64 { account.positions.values().map(|p| p.realized_pnl).sum() };
+
+        let mut statement = Self {
+            account: account.id.clone(),
+            date,
+            opening_balance: opening.balance,
+            closing_balance: closing.balance,
+            trades: Vec::new(),
+            realized_pnl: realized(&closing) - realized(&opening),
+            fees: 0.0,
+            funding: 0.0,
+            transfers: 0.0,
+            open_positions: closing.open_positions(),
+            generated_at: Utc::now(),
+        };
+        let during = account
+            .activity
+            .iter()
+            .filter(|activity| (start..end).contains(&activity.timestamp()));
+        for activity in during {
+            match activity {
+                Activity::Trade { fee, .. } => {
+                    statement.fees += fee;
+                    statement.trades.push(activity.clone());
+                }
+                Activity::Funding { amount, .. } => statement.funding += amount,
+                Activity::Transfer { amount, .. } => statement.transfers += amount,
+                Activity::Margin { .. } | Activity::MarginMethod { .. } => {}
+            }
+        }
+        statement
+    }
+}
+
+/// Generated statements, optionally mirrored to `<dir>/<account>/<date>.json`
+pub struct StatementStore {
+    statements: RwLock<BTreeMap<(AccountId, NaiveDate), Statement>>,
+    dir: Option<PathBuf>,
+}
+
+impl StatementStore {
+    /// In-memory store, lost on restart
+    pub fn in_memory() -> Self {
+        Self {
+            statements: RwLock::new(BTreeMap::new()),
+            dir: None,
+        }
+    }
+
+    /// Store persisted under `dir`, loading any statements already there
+    pub fn open(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+
+        let mut statements = BTreeMap::new();
+        for account_dir in fs::read_dir(&dir)? {
+            let account_dir = account_dir?.path();
+            if !account_dir.is_dir() {
+                continue;
+            }
+            for entry in fs::read_dir(&account_dir)? {
+                let path = entry?.path();
+                if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                    continue;
+                }
+
+                match serde_json::from_slice::<Statement>(&fs::read(&path)?) {
+                    Ok(statement) => {
+                        statements.insert((statement.account.clone(), statement.date), statement);
+                    }
+                    Err(e) => tracing::warn!("Skipping unreadable statement {:?}: {}", path, e),
+                }
+            }
+        }
+
+        Ok(Self {
+            statements: RwLock::new(statements),
+            dir: Some(dir),
+        })
+    }
+
+    /// Persist a statement, replacing any earlier one for the same day
+    pub fn save(&self, statement: Statement) -> io::Result<()> {
+        if let Some(dir) = &self.dir {
+            let account_dir = dir.join(&statement.account.0);
+            fs::create_dir_all(&account_dir)?;
+            let json = serde_json::to_vec_pretty(&statement).map_err(io::Error::other)?;
+            fs::write(account_dir.join(format!("{}.json", statement.date)), json)?;
+        }
+
+        self.statements
+            .write()
+            .unwrap()
+            .insert((statement.account.clone(), statement.date), statement);
+        Ok(())
+    }
+
+    /// Dates with a statement for `account`, oldest first
+    pub fn dates(&self, account: &AccountId) -> Vec<NaiveDate> {
+        self.statements
+            .read()
+            .unwrap()
+            .keys()
+            .filter(|(id, _)| id == account)
+            .map(|&(_, date)| date)
+            .collect()
+    }
+
+    pub fn get(&self, account: &AccountId, date: NaiveDate) -> Option<Statement> {
+        self.statements
+            .read()
+            .unwrap()
+            .get(&(account.clone(), date))
+            .cloned()
+    }
+}
+
+/// Generate and save `date`'s statement for every account
+pub fn generate_all(
+    accounts: &SharedAccounts,
+    store: &StatementStore,
+    date: NaiveDate,
+) -> io::Result<usize> {
+    let ids = accounts.ids();
+    for id in &ids {
+        if let Some(account) = accounts.get(id) {
+            store.save(Statement::generate(&account, date))?;
+        }
+    }
+    Ok(ids.len())
+}
+
+/// Generate the previous day's statements shortly after each UTC midnight
+#[cfg(feature = "net")]
+pub fn start_daily(accounts: SharedAccounts, store: std::sync::Arc<StatementStore>) {
+    tokio::spawn(async move {
+        loop {
+            let now = Utc::now();
+            let midnight = (now.date_naive() + chrono::Duration::days(1))
+                .and_hms_opt(0, 0, 0)
+                .unwrap()
+                .and_utc();
+            let wait = (midnight - now).to_std().unwrap_or_default();
+            tokio::time::sleep(wait).await;
+
+            let date = midnight.date_naive() - chrono::Duration::days(1);
+            match generate_all(&accounts, &store, date) {
+                Ok(count) => tracing::info!("Generated {} statements for {}", count, date),
+                Err(e) => tracing::warn!("Statement generation for {} failed: {}", date, e),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{OrderSide, Symbol};
+    use chrono::TimeZone;
+
+    fn at(day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 6, day, hour, 0, 0).unwrap()
+    }
+
+    fn trade(side: OrderSide, price: f64, timestamp: DateTime<Utc>) -> Activity {
+        Activity::Trade {
+            symbol: Symbol::new("ETHUSDT"),
+            side,
+            price,
+            quantity: 1.0,
+            fee: 0.5,
+            timestamp,
+            fill: None,
+        }
+    }
+
+    #[test]
+    fn test_statement_covers_one_day() {
+        let mut account = Account::new("bob".into());
+        account.apply(Activity::Transfer {
+            amount: 500.0,
+            timestamp: at(1, 9),
+        });
+        account.apply(trade(OrderSide::Buy, 2_000.0, at(1, 10)));
+        account.apply(trade(OrderSide::Sell, 2_030.0, at(2, 10)));
+        account.apply(trade(OrderSide::Buy, 2_010.0, at(2, 11)));
+        account.apply(Activity::Funding {
+            symbol: Symbol::new("ETHUSDT"),
+            amount: -0.25,
+            timestamp: at(2, 16),
+        });
+        account.apply(trade(OrderSide::Sell, 2_050.0, at(3, 10)));
+
+        let statement = Statement::generate(&account, NaiveDate::from_ymd_opt(2024, 6, 2).unwrap());
+        assert_eq!(statement.opening_balance, 499.5);
+        assert_eq!(statement.closing_balance, 499.5 + 30.0 - 1.0 - 0.25);
+        assert_eq!(statement.trades.len(), 2);
+        assert_eq!((statement.fees, statement.funding), (1.0, -0.25));
+        assert_eq!(statement.realized_pnl, 30.0);
+        assert_eq!(statement.open_positions.len(), 1);
+        assert_eq!(statement.open_positions[0].entry_price, 2_010.0);
+    }
+
+    #[test]
+    fn test_store_persists_statements() {
+        let dir = std::env::temp_dir().join(format!("statements-{}", std::process::id()));
+        let mut account = Account::new("carol".into());
+        account.apply(trade(OrderSide::Buy, 2_000.0, at(4, 12)));
+        let date = NaiveDate::from_ymd_opt(2024, 6, 4).unwrap();
+
+        let store = StatementStore::open(&dir).unwrap();
+        store.save(Statement::generate(&account, date)).unwrap();
+
+        let reopened = StatementStore::open(&dir).unwrap();
+        assert_eq!(reopened.dates(&account.id), vec![date]);
+        assert_eq!(reopened.get(&account.id, date).unwrap().fees, 0.5);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,289 @@
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use chrono::{NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::account::{
+    Account, AccountId, Activity, FeeStatus, MarginMethod, MarginMode, Position, Statement,
+};
+use crate::api::risk::var_report;
+use crate::api::{ApiError, ApiResult, AppState};
+use crate::market::{carry_cost, hedge_plan, CarryCost, CarryRates, HedgePlan};
+use crate::risk::{MarginSummary, PositionChange, WhatIf};
+use crate::types::Symbol;
+use crate::var::{VarConfig, VarReport};
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/v1/accounts/:account/statements", get(list_statements))
+        .route(
+            "/api/v1/accounts/:account/statements/:date",
+            get(get_statement),
+        )
+        .route("/api/v1/accounts/:account/what-if", post(what_if))
+        .route("/api/v1/accounts/:account/fees", get(get_fees))
+        .route("/api/v1/accounts/:account/carry", get(get_carry))
+        .route("/api/v1/accounts/:account/hedges", get(get_hedges))
+        .route("/api/v1/accounts/:account/var", get(get_var))
+        .route("/api/v1/accounts/:account/positions", get(list_positions))
+        .route(
+            "/api/v1/accounts/:account/positions/:symbol/margin",
+            post(set_margin_mode),
+        )
+        .route(
+            "/api/v1/accounts/:account/margin-method",
+            post(set_margin_method),
+        )
+}
+
+#[derive(Debug, Serialize)]
+struct PositionView {
+    #[serde(flatten)]
+    position: Position,
+    mark_price: Option<f64>,
+    unrealized_pnl: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MarginModeRequest {
+    mode: MarginMode,
+    /// Collateral to set aside for an isolated position
+    #[serde(default)]
+    margin: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct MarginMethodRequest {
+    method: MarginMethod,
+}
+
+fn account_of(state: &AppState, account: &str) -> Result<Account, ApiError> {
+    state
+        .accounts
+        .get(&AccountId(account.to_string()))
+        .ok_or_else(|| ApiError::not_found(format!("account {} not found", account)))
+}
+
+/// `position` with its liquidation price revalued at the current marks
+fn position_view(state: &AppState, account: &Account, mut position: Position) -> PositionView {
+    let mark = |symbol: &Symbol| state.books.mark_price(symbol);
+    let mark_price = mark(&position.symbol);
+    position.liquidation_price =
+        state
+            .risk_limits
+            .liquidation_price(account, &position.symbol, mark);
+    PositionView {
+        unrealized_pnl: mark_price.map(|price| position.quantity * (price - position.entry_price)),
+        mark_price,
+        position,
+    }
+}
+
+/// GET /api/v1/accounts/:account/positions
+///
+/// Open positions with their margin mode and liquidation price at the
+/// current marks.
+async fn list_positions(
+    State(state): State<AppState>,
+    Path(account): Path<String>,
+) -> ApiResult<Vec<PositionView>> {
+    let account = account_of(&state, &account)?;
+    Ok(Json(
+        account
+            .open_positions()
+            .into_iter()
+            .map(|position| position_view(&state, &account, position))
+            .collect(),
+    ))
+}
+
+/// POST /api/v1/accounts/:account/positions/:symbol/margin
+///
+/// Switch a position between cross and isolated margin; isolated positions
+/// need margin set aside from the cross balance.
+async fn set_margin_mode(
+    State(state): State<AppState>,
+    Path((account, symbol)): Path<(String, String)>,
+    Json(request): Json<MarginModeRequest>,
+) -> ApiResult<PositionView> {
+    let current = account_of(&state, &account)?;
+    let symbol = Symbol::from(symbol.to_uppercase());
+    if request.mode == MarginMode::Isolated {
+        let already = current
+            .positions
+            .get(&symbol)
+            .map_or(0.0, |p| p.isolated_margin);
+        let available = current.cross_balance() + already;
+        if !(request.margin.is_finite() && request.margin > 0.0 && request.margin <= available) {
+            return Err(ApiError::new(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                format!(
+                    "isolated margin must be positive and at most {:.2}",
+                    available
+                ),
+            ));
+        }
+    }
+
+    let id = AccountId(account.clone());
+    state.accounts.apply(
+        &id,
+        Activity::Margin {
+            symbol: symbol.clone(),
+            mode: request.mode,
+            amount: request.margin,
+            timestamp: Utc::now(),
+        },
+    );
+    let account = account_of(&state, &account)?;
+    let position = account.positions[&symbol].clone();
+    Ok(Json(position_view(&state, &account, position)))
+}
+
+/// POST /api/v1/accounts/:account/margin-method
+///
+/// Switch the account between standard and portfolio margining, returning
+/// its margin under the new method.
+async fn set_margin_method(
+    State(state): State<AppState>,
+    Path(account): Path<String>,
+    Json(request): Json<MarginMethodRequest>,
+) -> ApiResult<MarginSummary> {
+    account_of(&state, &account)?;
+    state.accounts.apply(
+        &AccountId(account.clone()),
+        Activity::MarginMethod {
+            method: request.method,
+            timestamp: Utc::now(),
+        },
+    );
+    let account = account_of(&state, &account)?;
+    Ok(Json(state.risk_limits.margin(
+        &account,
+        |symbol| state.books.mark_price(symbol),
+        0.0,
+    )))
+}
+
+/// GET /api/v1/accounts/:account/carry
+///
+/// Funding and borrow cost per day of the open positions at the latest
+/// fetched rates.
+async fn get_carry(
+    State(state): State<AppState>,
+    Path(account): Path<String>,
+) -> ApiResult<CarryCost> {
+    let account = account_of(&state, &account)?;
+    let rates = CarryRates::new(state.timeseries.clone(), state.instruments.list());
+    Ok(Json(carry_cost(
+        &account,
+        |symbol| state.books.mark_price(symbol),
+        &rates,
+    )))
+}
+
+#[derive(Debug, Deserialize)]
+struct HedgeQuery {
+    /// Currency the balance is held in; exposure to it is wanted
+    #[serde(default = "default_home")]
+    home: String,
+}
+
+fn default_home() -> String {
+    "USDT".to_string()
+}
+
+/// GET /api/v1/accounts/:account/hedges?home=USDT
+///
+/// Exposure to quote currencies other than the home one, and the trades
+/// through their crosses with it that would neutralize it.
+async fn get_hedges(
+    State(state): State<AppState>,
+    Path(account): Path<String>,
+    Query(query): Query<HedgeQuery>,
+) -> ApiResult<HedgePlan> {
+    let account = account_of(&state, &account)?;
+    Ok(Json(hedge_plan(
+        &account,
+        &query.home.to_uppercase(),
+        &state.instruments.list(),
+        |symbol| state.books.mark_price(symbol),
+    )))
+}
+
+/// GET /api/v1/accounts/:account/var?confidence=0.99&horizon_days=1&participation=0.1
+///
+/// Standard and liquidity-adjusted VaR of the open positions, with the tail
+/// metrics of the account's equity; see [`var_report`].
+async fn get_var(
+    State(state): State<AppState>,
+    Path(account): Path<String>,
+    Query(config): Query<VarConfig>,
+) -> ApiResult<VarReport> {
+    config
+        .validate()
+        .map_err(|e| ApiError::new(StatusCode::BAD_REQUEST, e))?;
+    let account = account_of(&state, &account)?;
+    Ok(Json(var_report(&state, &account, &config, Utc::now())))
+}
+
+/// GET /api/v1/accounts/:account/fees
+///
+/// 30-day traded volume, the fee tier it reaches and how far the next one is.
+async fn get_fees(
+    State(state): State<AppState>,
+    Path(account): Path<String>,
+) -> ApiResult<FeeStatus> {
+    let fees = state
+        .fees
+        .as_ref()
+        .ok_or_else(|| ApiError::not_found("no fee schedule configured"))?;
+    Ok(Json(fees.status(&AccountId(account), Utc::now())))
+}
+
+/// GET /api/v1/accounts/:account/statements
+async fn list_statements(
+    State(state): State<AppState>,
+    Path(account): Path<String>,
+) -> Json<Vec<NaiveDate>> {
+    Json(state.statements.dates(&AccountId(account)))
+}
+
+/// GET /api/v1/accounts/:account/statements/:date
+async fn get_statement(
+    State(state): State<AppState>,
+    Path((account, date)): Path<(String, NaiveDate)>,
+) -> ApiResult<Statement> {
+    state
+        .statements
+        .get(&AccountId(account.clone()), date)
+        .map(Json)
+        .ok_or_else(|| ApiError::not_found(format!("no statement for {} on {}", account, date)))
+}
+
+/// POST /api/v1/accounts/:account/what-if
+///
+/// Risk of the account with a set of hypothetical position changes applied,
+/// next to its current risk. Nothing is booked.
+async fn what_if(
+    State(state): State<AppState>,
+    Path(account): Path<String>,
+    Json(changes): Json<Vec<PositionChange>>,
+) -> ApiResult<WhatIf> {
+    let account = state
+        .accounts
+        .get(&AccountId(account.clone()))
+        .ok_or_else(|| ApiError::not_found(format!("account {} not found", account)))?;
+    state
+        .risk_limits
+        .what_if(&account, &changes, |symbol| state.books.mark_price(symbol))
+        .map(Json)
+        .map_err(|symbol| {
+            ApiError::new(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                format!("no price or mark for {}", symbol),
+            )
+        })
+}
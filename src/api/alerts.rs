@@ -0,0 +1,109 @@
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::Response;
+use axum::routing::{delete, get};
+use axum::{Json, Router};
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use crate::api::{ApiError, ApiResult, AppState};
+use crate::market::{Alert, AlertCondition, AlertEvent, AlertId, AlertRequest};
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/api/v1/users/:user/alerts",
+            get(list_alerts).post(create_alert),
+        )
+        .route("/api/v1/users/:user/alerts/:id", delete(delete_alert))
+        .route("/api/v1/users/:user/alerts/stream", get(stream))
+}
+
+/// GET /api/v1/users/:user/alerts
+async fn list_alerts(State(state): State<AppState>, Path(user): Path<String>) -> Json<Vec<Alert>> {
+    Json(state.alerts.list(&user))
+}
+
+/// POST /api/v1/users/:user/alerts
+async fn create_alert(
+    State(state): State<AppState>,
+    Path(user): Path<String>,
+    Json(request): Json<AlertRequest>,
+) -> Result<(StatusCode, Json<Alert>), ApiError> {
+    let valid = match request.condition {
+        AlertCondition::CrossesAbove { level } | AlertCondition::CrossesBelow { level } => {
+            level.is_finite() && level > 0.0
+        }
+        AlertCondition::Move {
+            percent,
+            window_secs,
+        } => percent.is_finite() && percent > 0.0 && window_secs > 0,
+        AlertCondition::LiquidationProximity { .. } => {
+            return Err(ApiError::new(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "liquidation alerts are raised by the risk monitor",
+            ));
+        }
+        AlertCondition::Depeg { .. } => {
+            return Err(ApiError::new(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "depeg alerts are raised by the depeg monitor",
+            ));
+        }
+    };
+    if !valid {
+        return Err(ApiError::new(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "alert levels, moves and windows must be positive",
+        ));
+    }
+    let alert = state.alerts.create(&user, request);
+    Ok((StatusCode::CREATED, Json(alert)))
+}
+
+/// DELETE /api/v1/users/:user/alerts/:id
+async fn delete_alert(
+    State(state): State<AppState>,
+    Path((user, id)): Path<(String, u64)>,
+) -> ApiResult<AlertId> {
+    if state.alerts.remove(&user, AlertId(id)) {
+        Ok(Json(AlertId(id)))
+    } else {
+        Err(ApiError::not_found(format!("alert {} not found", id)))
+    }
+}
+
+/// GET /api/v1/users/:user/alerts/stream, upgraded to a WebSocket of the
+/// user's fired alerts
+async fn stream(
+    State(state): State<AppState>,
+    Path(user): Path<String>,
+    ws: WebSocketUpgrade,
+) -> Response {
+    let events = state.alert_events.subscribe();
+    ws.on_upgrade(move |socket| forward_alerts(socket, user, events))
+}
+
+async fn forward_alerts(
+    mut socket: WebSocket,
+    user: String,
+    mut events: broadcast::Receiver<AlertEvent>,
+) {
+    loop {
+        let event = match events.recv().await {
+            Ok(event) => event,
+            // Alerts missed by a slow client are not replayed
+            Err(RecvError::Lagged(_)) => continue,
+            Err(RecvError::Closed) => break,
+        };
+        if event.user != user {
+            continue;
+        }
+        let Ok(text) = serde_json::to_string(&event) else {
+            continue;
+        };
+        if socket.send(Message::Text(text)).await.is_err() {
+            break;
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use axum::extract::{Path, State};
+use axum::routing::get;
+use axum::{Json, Router};
+
+use crate::api::{ApiError, ApiResult, AppState};
+use crate::backtest::{RunDiff, RunId, RunSummary, StoredRun};
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/v1/backtests", get(list_runs))
+        .route("/api/v1/backtests/:id", get(get_run))
+        .route("/api/v1/backtests/:id/diff/:other", get(diff_runs))
+}
+
+/// GET /api/v1/backtests
+async fn list_runs(State(state): State<AppState>) -> Json<Vec<RunSummary>> {
+    Json(state.backtests.list())
+}
+
+/// GET /api/v1/backtests/:id
+async fn get_run(State(state): State<AppState>, Path(id): Path<u64>) -> ApiResult<StoredRun> {
+    state
+        .backtests
+        .get(RunId(id))
+        .map(Json)
+        .ok_or_else(|| ApiError::not_found(format!("backtest run {} not found", id)))
+}
+
+/// GET /api/v1/backtests/:id/diff/:other
+async fn diff_runs(
+    State(state): State<AppState>,
+    Path((id, other)): Path<(u64, u64)>,
+) -> ApiResult<RunDiff> {
+    state
+        .backtests
+        .diff(RunId(id), RunId(other))
+        .map(Json)
+        .ok_or_else(|| ApiError::not_found(format!("backtest run {} or {} not found", id, other)))
+}
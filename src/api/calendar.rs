@@ -0,0 +1,30 @@
+use axum::extract::{Path, State};
+use axum::routing::get;
+use axum::{Json, Router};
+use chrono::Utc;
+
+use crate::api::AppState;
+use crate::calendar::{CalendarAdjustment, ScheduledEvent};
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/v1/calendar", get(upcoming))
+        .route("/api/v1/calendar/:symbol", get(adjustment))
+}
+
+/// GET /api/v1/calendar
+async fn upcoming(State(state): State<AppState>) -> Json<Vec<ScheduledEvent>> {
+    Json(state.calendar.upcoming(Utc::now()))
+}
+
+/// GET /api/v1/calendar/:symbol
+async fn adjustment(
+    State(state): State<AppState>,
+    Path(symbol): Path<String>,
+) -> Json<CalendarAdjustment> {
+    Json(
+        state
+            .calendar
+            .adjustment(&symbol.to_uppercase(), Utc::now()),
+    )
+}
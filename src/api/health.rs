@@ -0,0 +1,39 @@
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::Serialize;
+
+use crate::api::AppState;
+use crate::health::{HealthReport, HealthStatus};
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/health", get(health))
+        .route("/health/detailed", get(detailed))
+}
+
+#[derive(Debug, Serialize)]
+struct HealthSummary {
+    status: HealthStatus,
+}
+
+/// 503 unless every service is healthy, so load balancers drain degraded instances
+fn status_code(status: HealthStatus) -> StatusCode {
+    match status {
+        HealthStatus::Healthy => StatusCode::OK,
+        HealthStatus::Degraded | HealthStatus::Down => StatusCode::SERVICE_UNAVAILABLE,
+    }
+}
+
+/// GET /health
+async fn health(State(state): State<AppState>) -> (StatusCode, Json<HealthSummary>) {
+    let status = state.health.check().status;
+    (status_code(status), Json(HealthSummary { status }))
+}
+
+/// GET /health/detailed
+async fn detailed(State(state): State<AppState>) -> (StatusCode, Json<HealthReport>) {
+    let report = state.health.check();
+    (status_code(report.status), Json(report))
+}
@@ -0,0 +1,385 @@
+use axum::extract::{Path, Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::api::{ApiError, ApiResult, AppState};
+use crate::indicators::IndicatorValues;
+use crate::market::{
+    scan_basis, Basis, CarryRates, Entitlement, Instrument, InstrumentStatus, StatsCheck,
+    TapeTrade, TickerStats,
+};
+use crate::orderbook::{
+    AuctionQuote, AuctionResult, BookKind, BookPhase, ExecutionReport, Heatmap, HeatmapQuery,
+};
+use crate::types::Symbol;
+use crate::utils::Filter;
+use crate::overload::Priority;
+
+/// Header carrying the caller's API key
+const API_KEY_HEADER: &str = "x-api-key";
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/v1/market/symbols", get(symbols))
+        .route("/api/v1/market/metadata", get(all_metadata))
+        .route("/api/v1/market/basis", get(basis))
+        .route("/api/v1/market/:symbol/metadata", get(metadata))
+        .route("/api/v1/market/:symbol/stats24h", get(stats_24h))
+        .route("/api/v1/market/:symbol/heatmap", get(heatmap))
+        .route("/api/v1/market/:symbol/trades", get(recent_trades))
+        .route("/api/v1/market/:symbol/indicators", get(indicators))
+        .route("/api/v1/market/:symbol/auction", post(auction))
+}
+
+#[derive(Debug, Deserialize)]
+struct TradesQuery {
+    limit: Option<usize>,
+    /// Filter expression, see [`Filter`]; searches spilled trades too
+    #[serde(default)]
+    filter: Option<String>,
+}
+
+/// What the symbol picker shows for one symbol
+#[derive(Debug, Serialize)]
+struct SymbolMetadata {
+    symbol: Symbol,
+    /// Registry status; None for symbols that are only streamed
+    status: Option<InstrumentStatus>,
+    instrument: Option<Instrument>,
+    /// Exchange 24h statistics from the ticker stream
+    stats_24h: Option<TickerStats>,
+}
+
+impl SymbolMetadata {
+    fn of(state: &AppState, symbol: Symbol) -> Self {
+        let instrument = state.instruments.get(symbol.as_str());
+        Self {
+            status: instrument.as_ref().map(|instrument| instrument.status),
+            instrument,
+            stats_24h: state.tickers.get(symbol.as_str()),
+            symbol,
+        }
+    }
+
+    fn is_known(&self) -> bool {
+        self.instrument.is_some() || self.stats_24h.is_some()
+    }
+}
+
+/// Locally computed 24h statistics next to the exchange's
+#[derive(Debug, Serialize)]
+struct Stats24h {
+    local: Option<TickerStats>,
+    exchange: Option<TickerStats>,
+    check: Option<StatsCheck>,
+}
+
+/// Resolve the caller's entitlement; unrestricted when entitlements are off
+///
+/// Market queries are low priority and rejected while the engine sheds load.
+fn entitlement(state: &AppState, headers: &HeaderMap) -> Result<Entitlement, ApiError> {
+    if !state.shedder.admit(Priority::Low) {
+        return Err(ApiError::unavailable("overloaded, retry later"));
+    }
+
+    let Some(entitlements) = &state.entitlements else {
+        return Ok(Entitlement::default());
+    };
+
+    let api_key = headers
+        .get(API_KEY_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .ok_or_else(|| ApiError::unauthorized("missing x-api-key header"))?;
+
+    entitlements
+        .get(api_key)
+        .cloned()
+        .ok_or_else(|| ApiError::unauthorized("unknown API key"))
+}
+
+/// GET /api/v1/market/symbols
+async fn symbols(State(state): State<AppState>, headers: HeaderMap) -> ApiResult<Vec<Symbol>> {
+    let entitlement = entitlement(&state, &headers)?;
+    let mut symbols: Vec<Symbol> = state
+        .trades
+        .symbols()
+        .into_iter()
+        .filter(|symbol| entitlement.allows_symbol(symbol.as_str()))
+        .collect();
+    symbols.sort();
+    Ok(Json(symbols))
+}
+
+/// GET /api/v1/market/basis
+///
+/// Cash-and-carry of every registered instrument with a funding rate, best
+/// first.
+async fn basis(State(state): State<AppState>, headers: HeaderMap) -> ApiResult<Vec<Basis>> {
+    let entitlement = entitlement(&state, &headers)?;
+    let rates = CarryRates::new(state.timeseries.clone(), state.instruments.list());
+    Ok(Json(
+        scan_basis(&rates)
+            .into_iter()
+            .filter(|basis| entitlement.allows_symbol(basis.symbol.as_str()))
+            .collect(),
+    ))
+}
+
+/// GET /api/v1/market/metadata
+///
+/// Every registered or traded symbol the caller is entitled to.
+async fn all_metadata(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> ApiResult<Vec<SymbolMetadata>> {
+    let entitlement = entitlement(&state, &headers)?;
+    let mut symbols: Vec<Symbol> = state
+        .instruments
+        .list()
+        .into_iter()
+        .map(|instrument| instrument.symbol)
+        .chain(state.trades.symbols())
+        .filter(|symbol| entitlement.allows_symbol(symbol.as_str()))
+        .collect();
+    symbols.sort();
+    symbols.dedup();
+    Ok(Json(
+        symbols
+            .into_iter()
+            .map(|symbol| SymbolMetadata::of(&state, symbol))
+            .collect(),
+    ))
+}
+
+/// GET /api/v1/market/:symbol/metadata
+async fn metadata(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(symbol): Path<String>,
+) -> ApiResult<SymbolMetadata> {
+    let symbol = Symbol::new(symbol);
+    let entitlement = entitlement(&state, &headers)?;
+    if !entitlement.allows_symbol(symbol.as_str()) {
+        return Err(ApiError::forbidden(format!("not entitled to {}", symbol)));
+    }
+
+    let traded = !state.trades.recent(symbol.as_str(), 1).is_empty();
+    let metadata = SymbolMetadata::of(&state, symbol);
+    if !traded && !metadata.is_known() {
+        return Err(ApiError::not_found(format!(
+            "unknown symbol {}",
+            metadata.symbol
+        )));
+    }
+    Ok(Json(metadata))
+}
+
+/// GET /api/v1/market/:symbol/stats24h
+async fn stats_24h(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(symbol): Path<String>,
+) -> ApiResult<Stats24h> {
+    let symbol = symbol.to_uppercase();
+    let entitlement = entitlement(&state, &headers)?;
+    if !entitlement.allows_symbol(&symbol) {
+        return Err(ApiError::forbidden(format!("not entitled to {}", symbol)));
+    }
+
+    let now_ms = Utc::now().timestamp_millis();
+    let local = state.rolling.stats(&symbol, now_ms);
+    let exchange = state.tickers.get(&symbol);
+    if local.is_none() && exchange.is_none() {
+        return Err(ApiError::not_found(format!("no statistics for {}", symbol)));
+    }
+    let check = exchange.as_ref().and_then(|exchange| {
+        state
+            .rolling
+            .check(exchange, now_ms, &state.stats_tolerance)
+    });
+    Ok(Json(Stats24h {
+        local,
+        exchange,
+        check,
+    }))
+}
+
+/// GET /api/v1/market/:symbol/heatmap?from_ms=&to_ms=&bucket_ms=&price_step=
+async fn heatmap(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(symbol): Path<String>,
+    Query(query): Query<HeatmapQuery>,
+) -> ApiResult<Heatmap> {
+    let symbol = symbol.to_uppercase();
+    let entitlement = entitlement(&state, &headers)?;
+    if !entitlement.allows_symbol(&symbol) {
+        return Err(ApiError::forbidden(format!("not entitled to {}", symbol)));
+    }
+    if query.from_ms > query.to_ms || query.bucket_ms <= 0 {
+        return Err(ApiError::new(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "from_ms must not exceed to_ms and bucket_ms must be positive",
+        ));
+    }
+
+    Ok(Json(state.depth.heatmap(&symbol, &query)))
+}
+
+/// GET /api/v1/market/:symbol/trades?limit=N&filter=price > 50000 AND side = buy
+async fn recent_trades(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(symbol): Path<String>,
+    Query(query): Query<TradesQuery>,
+) -> ApiResult<Vec<TapeTrade>> {
+    let symbol = symbol.to_uppercase();
+    let entitlement = entitlement(&state, &headers)?;
+    if !entitlement.allows_symbol(&symbol) {
+        return Err(ApiError::forbidden(format!("not entitled to {}", symbol)));
+    }
+
+    let limit = query.limit.unwrap_or(100);
+    let trades = match &query.filter {
+        Some(filter) => {
+            let filter = Filter::compile::<TapeTrade>(filter).map_err(|e| {
+                ApiError::new(StatusCode::BAD_REQUEST, format!("invalid filter: {}", e))
+            })?;
+            state.trades.query(&symbol, &filter, limit)
+        }
+        None => state.trades.recent(&symbol, limit),
+    };
+    let trades = trades
+        .into_iter()
+        .filter(|trade| entitlement.allows_venue(trade.source.venue()))
+        .collect();
+    Ok(Json(trades))
+}
+
+/// GET /api/v1/market/:symbol/indicators
+async fn indicators(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(symbol): Path<String>,
+) -> ApiResult<IndicatorValues> {
+    let symbol = symbol.to_uppercase();
+    let entitlement = entitlement(&state, &headers)?;
+    if !entitlement.allows_symbol(&symbol) {
+        return Err(ApiError::forbidden(format!("not entitled to {}", symbol)));
+    }
+
+    state
+        .indicators
+        .get(&symbol)
+        .map(Json)
+        .ok_or_else(|| ApiError::not_found(format!("no trades seen for {}", symbol)))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "action")]
+enum AuctionAction {
+    /// Open the call phase, uncrossing by itself at `uncross_at` if given
+    Call {
+        #[serde(default)]
+        uncross_at: Option<DateTime<Utc>>,
+    },
+    /// Uncross the auction now
+    Uncross,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+enum AuctionResponse {
+    Called {
+        symbol: Symbol,
+        phase: BookPhase,
+        uncross_at: Option<DateTime<Utc>>,
+        /// Where the book would uncross if it did now
+        indicative: Option<AuctionQuote>,
+    },
+    Uncrossed(AuctionResult),
+}
+
+/// POST /api/v1/market/:symbol/auction
+///
+/// `{"action":"call","uncross_at":"..."}` collects orders without matching
+/// them, `{"action":"uncross"}` executes everything that crosses at the
+/// single price that trades the most.
+async fn auction(
+    State(state): State<AppState>,
+    Path(symbol): Path<String>,
+    Json(action): Json<AuctionAction>,
+) -> ApiResult<AuctionResponse> {
+    let symbol = Symbol::new(symbol.to_uppercase());
+    match action {
+        AuctionAction::Call { uncross_at } => {
+            if uncross_at.is_some_and(|at| at <= Utc::now()) {
+                return Err(ApiError::new(
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    "uncross_at must be in the future",
+                ));
+            }
+            state.books.start_auction(symbol.clone());
+            if let Some(at) = uncross_at {
+                schedule_uncross(&state, symbol.clone(), at);
+            }
+            let book = state.books.matching(symbol.clone());
+            Ok(Json(AuctionResponse::Called {
+                symbol,
+                phase: book.phase(),
+                uncross_at,
+                indicative: book.indicative_auction(),
+            }))
+        }
+        AuctionAction::Uncross => {
+            let in_auction = state
+                .books
+                .get(&symbol, BookKind::Matching)
+                .is_some_and(|book| book.phase() == BookPhase::Auction);
+            if !in_auction {
+                return Err(ApiError::new(
+                    StatusCode::CONFLICT,
+                    format!("no auction open on {}", symbol),
+                ));
+            }
+            Ok(Json(AuctionResponse::Uncrossed(uncross(&state, &symbol))))
+        }
+    }
+}
+
+fn schedule_uncross(state: &AppState, symbol: Symbol, at: DateTime<Utc>) {
+    let state = state.clone();
+    tokio::spawn(async move {
+        let wait = (at - Utc::now()).to_std().unwrap_or_default();
+        tokio::time::sleep(wait).await;
+        // Already uncrossed by hand
+        if state.books.matching(symbol.clone()).phase() == BookPhase::Auction {
+            uncross(&state, &symbol);
+        }
+    });
+}
+
+/// Uncross `symbol` and report fills and cancelled leftovers
+fn uncross(state: &AppState, symbol: &Symbol) -> AuctionResult {
+    let result = state
+        .books
+        .uncross(symbol)
+        .expect("auctions run on matching books");
+    tracing::info!(
+        "{} uncrossed at {:?} with {} trades",
+        symbol,
+        result.quote.map(|quote| quote.price),
+        result.trades.len()
+    );
+    crate::api::v2::publish(
+        state,
+        crate::orderbook::Actor::Auction,
+        ExecutionReport::fills(&result.trades)
+            .into_iter()
+            .chain(result.cancelled.iter().map(ExecutionReport::cancelled)),
+    );
+    result
+}
@@ -0,0 +1,411 @@
+// REST API, enabled with the `web` feature
+
+pub mod accounts;
+pub mod alerts;
+pub mod backtest;
+pub mod calendar;
+pub mod health;
+pub mod market;
+pub mod orders;
+pub mod risk;
+pub mod system;
+pub mod timeseries;
+pub mod v2;
+pub mod watchlists;
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::{Json, Router};
+use serde::Serialize;
+use tokio::sync::broadcast;
+use tower_http::cors::CorsLayer;
+
+use crate::account::{LedgerWriter, SharedAccounts, SharedFeeTracker, StatementStore};
+use crate::backtest::BacktestStore;
+use crate::calendar::SharedCalendar;
+use crate::health::HealthRegistry;
+use crate::indicators::SharedIndicators;
+use crate::market::{
+    AlertEvent, Entitlements, SharedAlertEngine, SharedInstruments, SharedRollingStats,
+    SharedTickers, SharedTradeTape, SharedWatchlists, StatsTolerance,
+};
+use crate::memory::MemoryRegistry;
+use crate::orderbook::{
+    BookManager, ExecutionReport, FlowEvent, MatchingShards, OrderThrottle, SharedClientOrders,
+    SharedDepthRecorder, SharedOrderAudit, SharedOrderHistory, SharedOrderOwners,
+    SharedOrderThrottle, SharedQuoteTracker, ThrottleConfig,
+};
+use crate::overload::SharedLoadShedder;
+use crate::risk::RiskLimits;
+use crate::risk_journal::SharedRiskJournal;
+use crate::routing::SharedOrderRouter;
+use crate::throughput::SharedThroughputMeter;
+use crate::timeseries::SharedTimeSeriesStore;
+
+/// Execution reports buffered per stream subscriber
+const EXECUTION_BUFFER: usize = 1_024;
+/// Order-flow events buffered per visualizer subscriber
+const FLOW_BUFFER: usize = 1_024;
+/// Fired alerts buffered per stream subscriber
+const ALERT_BUFFER: usize = 256;
+
+/// Shared state handed to every handler
+#[derive(Clone)]
+pub struct AppState {
+    pub backtests: Arc<BacktestStore>,
+    pub trades: SharedTradeTape,
+    pub indicators: SharedIndicators,
+    /// Per-key market data entitlements; None leaves market endpoints open
+    pub entitlements: Option<Arc<Entitlements>>,
+    pub shedder: SharedLoadShedder,
+    pub throughput: SharedThroughputMeter,
+    pub calendar: SharedCalendar,
+    pub statements: Arc<StatementStore>,
+    /// Books v2 orders are submitted to
+    pub books: BookManager,
+    pub executions: broadcast::Sender<ExecutionReport>,
+    /// Arrivals, cancels and trades of v2 orders, for book animations
+    pub order_flow: broadcast::Sender<FlowEvent>,
+    /// Client order IDs of v2 orders, so resubmissions find the original
+    pub client_orders: SharedClientOrders,
+    /// Accounts of open v2 orders, whose fills are charged and booked to them
+    pub order_owners: SharedOrderOwners,
+    /// Refresh and expiry counters of v2 mass quotes
+    pub quotes: SharedQuoteTracker,
+    /// Accounts v2 fills are booked to, and simulations and what-if queries
+    /// are margined against
+    pub accounts: SharedAccounts,
+    pub risk_limits: RiskLimits,
+    /// Fee charged on simulated fill notional, in basis points
+    pub fee_bps: f64,
+    /// Volume-tiered fees charged instead of `fee_bps` for known accounts
+    pub fees: Option<SharedFeeTracker>,
+    /// User price alerts, fired into `alert_events`
+    pub alerts: SharedAlertEngine,
+    pub alert_events: broadcast::Sender<AlertEvent>,
+    pub instruments: SharedInstruments,
+    pub tickers: SharedTickers,
+    /// 24h statistics computed from the trade feed, checked against `tickers`
+    pub rolling: SharedRollingStats,
+    pub stats_tolerance: StatsTolerance,
+    pub watchlists: SharedWatchlists,
+    /// Periodic depth snapshots the liquidity heatmap is built from
+    pub depth: SharedDepthRecorder,
+    /// Venue ack latencies and the routing decisions made with them
+    pub router: SharedOrderRouter,
+    /// Extra subsystems reported next to the state's own on the memory endpoint
+    pub memory: MemoryRegistry,
+    /// Human-readable record of submitted orders and their executions
+    pub ledger: Option<LedgerWriter>,
+    /// Services reported on by the health endpoints
+    pub health: HealthRegistry,
+    /// Prices, spreads, equity and latency at several resolutions
+    pub timeseries: SharedTimeSeriesStore,
+    /// Every published execution report, for history queries
+    pub order_history: SharedOrderHistory,
+    /// Every transition of local orders, for compliance review
+    pub order_audit: SharedOrderAudit,
+    /// Per-client order rate limit on v2 order entry, off when absent
+    pub throttle: Option<SharedOrderThrottle>,
+    /// Periodic risk snapshots of every account
+    pub risk_journal: SharedRiskJournal,
+    /// Workers v2 orders are matched on, per symbol in arrival order; when
+    /// absent each order is matched on a task of its own
+    pub shards: Option<MatchingShards>,
+}
+
+impl AppState {
+    pub fn new(backtests: BacktestStore) -> Self {
+        let alert_events = broadcast::channel(ALERT_BUFFER).0;
+        let alerts = SharedAlertEngine::default();
+        alerts.add_sink(stream_sink(&alert_events));
+        Self {
+            backtests: Arc::new(backtests),
+            trades: SharedTradeTape::default(),
+            indicators: SharedIndicators::default(),
+            entitlements: None,
+            shedder: SharedLoadShedder::default(),
+            throughput: SharedThroughputMeter::default(),
+            calendar: SharedCalendar::default(),
+            statements: Arc::new(StatementStore::in_memory()),
+            books: BookManager::new(),
+            executions: broadcast::channel(EXECUTION_BUFFER).0,
+            order_flow: broadcast::channel(FLOW_BUFFER).0,
+            client_orders: SharedClientOrders::default(),
+            order_owners: SharedOrderOwners::default(),
+            quotes: SharedQuoteTracker::default(),
+            accounts: SharedAccounts::default(),
+            risk_limits: RiskLimits::default(),
+            fee_bps: 0.0,
+            fees: None,
+            alerts,
+            alert_events,
+            instruments: SharedInstruments::default(),
+            tickers: SharedTickers::default(),
+            rolling: SharedRollingStats::default(),
+            stats_tolerance: StatsTolerance::default(),
+            watchlists: SharedWatchlists::default(),
+            depth: SharedDepthRecorder::default(),
+            router: SharedOrderRouter::default(),
+            memory: MemoryRegistry::new(),
+            ledger: None,
+            health: HealthRegistry::new(),
+            timeseries: SharedTimeSeriesStore::default(),
+            order_history: SharedOrderHistory::default(),
+            order_audit: SharedOrderAudit::default(),
+            throttle: None,
+            risk_journal: SharedRiskJournal::default(),
+            shards: None,
+        }
+    }
+
+    pub fn with_trade_tape(mut self, trades: SharedTradeTape) -> Self {
+        self.trades = trades;
+        self
+    }
+
+    /// Serve indicators computed from the live trade feed
+    pub fn with_indicators(mut self, indicators: SharedIndicators) -> Self {
+        self.indicators = indicators;
+        self
+    }
+
+    /// Shed market data queries when `shedder` reports overload
+    pub fn with_load_shedder(mut self, shedder: SharedLoadShedder) -> Self {
+        self.shedder = shedder;
+        self
+    }
+
+    /// Report capacity from the meter shared with order entry and feeds
+    pub fn with_throughput(mut self, throughput: SharedThroughputMeter) -> Self {
+        self.throughput = throughput;
+        self
+    }
+
+    /// Expose the event schedule and its current adjustments
+    pub fn with_calendar(mut self, calendar: SharedCalendar) -> Self {
+        self.calendar = calendar;
+        self
+    }
+
+    /// Serve account statements from `statements`
+    pub fn with_statements(mut self, statements: Arc<StatementStore>) -> Self {
+        self.statements = statements;
+        self
+    }
+
+    /// Route v2 order submissions to `books`
+    pub fn with_books(mut self, books: BookManager) -> Self {
+        self.books = books;
+        self
+    }
+
+    pub fn with_quote_tracker(mut self, quotes: SharedQuoteTracker) -> Self {
+        self.quotes = quotes;
+        self
+    }
+
+    /// Margin order simulations against `accounts`
+    pub fn with_accounts(mut self, accounts: SharedAccounts) -> Self {
+        self.accounts = accounts;
+        self
+    }
+
+    /// Check simulated orders against `limits` and charge `fee_bps`; symbols
+    /// of instruments with margin tiers are margined by those
+    pub fn with_risk_limits(mut self, limits: RiskLimits, fee_bps: f64) -> Self {
+        self.risk_limits = limits.with_margin_tiers(&self.instruments.list());
+        self.fee_bps = fee_bps;
+        self
+    }
+
+    /// Charge simulations of an account at its tier in `fees`
+    pub fn with_fee_tracker(mut self, fees: SharedFeeTracker) -> Self {
+        self.fees = Some(fees);
+        self
+    }
+
+    /// Manage alerts of `alerts`, which should be fed by the trade stream
+    pub fn with_alerts(mut self, alerts: SharedAlertEngine) -> Self {
+        alerts.add_sink(stream_sink(&self.alert_events));
+        self.alerts = alerts;
+        self
+    }
+
+    /// Describe symbols from `instruments`; watchlists only accept its symbols
+    /// unless it is empty, and their margin tiers feed the risk limits
+    pub fn with_instruments(mut self, instruments: SharedInstruments) -> Self {
+        self.risk_limits = self
+            .risk_limits
+            .clone()
+            .with_margin_tiers(&instruments.list());
+        self.instruments = instruments;
+        self
+    }
+
+    /// Serve 24h statistics from the ticker stream feeding `tickers`
+    pub fn with_tickers(mut self, tickers: SharedTickers) -> Self {
+        self.tickers = tickers;
+        self
+    }
+
+    /// Serve locally computed 24h statistics, flagging drift beyond `tolerance`
+    pub fn with_rolling_stats(
+        mut self,
+        rolling: SharedRollingStats,
+        tolerance: StatsTolerance,
+    ) -> Self {
+        self.rolling = rolling;
+        self.stats_tolerance = tolerance;
+        self
+    }
+
+    pub fn with_watchlists(mut self, watchlists: SharedWatchlists) -> Self {
+        self.watchlists = watchlists;
+        self
+    }
+
+    pub fn with_depth_recorder(mut self, depth: SharedDepthRecorder) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    pub fn with_order_router(mut self, router: SharedOrderRouter) -> Self {
+        self.router = router;
+        self
+    }
+
+    pub fn with_memory_registry(mut self, memory: MemoryRegistry) -> Self {
+        self.memory = memory;
+        self
+    }
+
+    pub fn with_ledger(mut self, ledger: LedgerWriter) -> Self {
+        self.ledger = Some(ledger);
+        self
+    }
+
+    pub fn with_health(mut self, health: HealthRegistry) -> Self {
+        self.health = health;
+        self
+    }
+
+    pub fn with_timeseries(mut self, timeseries: SharedTimeSeriesStore) -> Self {
+        self.timeseries = timeseries;
+        self
+    }
+
+    pub fn with_order_history(mut self, history: SharedOrderHistory) -> Self {
+        self.order_history = history;
+        self
+    }
+
+    pub fn with_order_audit(mut self, audit: SharedOrderAudit) -> Self {
+        self.order_audit = audit;
+        self
+    }
+
+    pub fn with_risk_journal(mut self, journal: SharedRiskJournal) -> Self {
+        self.risk_journal = journal;
+        self
+    }
+
+    pub fn with_throttle(mut self, config: ThrottleConfig) -> Self {
+        self.throttle = Some(SharedOrderThrottle::new(OrderThrottle::new(config)));
+        self
+    }
+
+    pub fn with_matching_shards(mut self, shards: MatchingShards) -> Self {
+        self.shards = Some(shards);
+        self
+    }
+
+    /// Require an entitled `x-api-key` header on market endpoints
+    pub fn with_entitlements(mut self, entitlements: Entitlements) -> Self {
+        self.entitlements = Some(Arc::new(entitlements));
+        self
+    }
+}
+
+/// Sink forwarding fired alerts to WebSocket subscribers
+fn stream_sink(events: &broadcast::Sender<AlertEvent>) -> impl Fn(&AlertEvent) + Send + Sync {
+    let events = events.clone();
+    move |event: &AlertEvent| {
+        // No subscribers is fine; alerts are not buffered for later
+        let _ = events.send(event.clone());
+    }
+}
+
+/// Error body returned by every endpoint
+#[derive(Debug, Serialize)]
+pub struct ApiError {
+    #[serde(skip)]
+    pub status: StatusCode,
+    pub error: String,
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, error: impl Into<String>) -> Self {
+        Self {
+            status,
+            error: error.into(),
+        }
+    }
+
+    pub fn not_found(error: impl Into<String>) -> Self {
+        Self::new(StatusCode::NOT_FOUND, error)
+    }
+
+    pub fn unauthorized(error: impl Into<String>) -> Self {
+        Self::new(StatusCode::UNAUTHORIZED, error)
+    }
+
+    pub fn forbidden(error: impl Into<String>) -> Self {
+        Self::new(StatusCode::FORBIDDEN, error)
+    }
+
+    pub fn unavailable(error: impl Into<String>) -> Self {
+        Self::new(StatusCode::SERVICE_UNAVAILABLE, error)
+    }
+
+    pub fn internal(error: impl Into<String>) -> Self {
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR, error)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.status, Json(self)).into_response()
+    }
+}
+
+pub type ApiResult<T> = Result<Json<T>, ApiError>;
+
+/// Build the application router
+pub fn router(state: AppState) -> Router {
+    Router::new()
+        .merge(accounts::routes())
+        .merge(alerts::routes())
+        .merge(backtest::routes())
+        .merge(calendar::routes())
+        .merge(health::routes())
+        .merge(market::routes())
+        .merge(orders::routes())
+        .merge(risk::routes())
+        .merge(system::routes())
+        .merge(timeseries::routes())
+        .merge(v2::routes())
+        .merge(watchlists::routes())
+        .layer(CorsLayer::permissive())
+        .with_state(state)
+}
+
+/// Serve the API until the process exits
+pub async fn serve(addr: SocketAddr, state: AppState) -> std::io::Result<()> {
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    tracing::info!("✓ REST API listening on http://{}", addr);
+    axum::serve(listener, router(state)).await
+}
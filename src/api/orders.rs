@@ -0,0 +1,349 @@
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::routing::{delete, get, post};
+use axum::{Json, Router};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::account::AccountId;
+use crate::api::v2::{across_books, pre_trade, publish, ErrorDetail, OrderRequest};
+use crate::api::{ApiError, ApiResult, AppState};
+use crate::orderbook::{Actor, AuditEntry, ExecutionReport, Liquidity, Simulation};
+use crate::risk::{Breach, OrderMargin, PositionChange, QuoteCheck};
+use crate::types::{OrderId, OrderSide, Symbol};
+use crate::utils::Filter;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/v1/orders/simulate", post(simulate_order))
+        .route("/api/v1/orders/check", post(check_orders))
+        .route("/api/v1/orders", delete(cancel_all))
+        .route("/api/v1/orders/history", get(order_history))
+        .route("/api/v1/orders/:order_id/history", get(order_audit))
+}
+
+#[derive(Debug, Deserialize)]
+struct SimulateRequest {
+    #[serde(flatten)]
+    order: OrderRequest,
+}
+
+#[derive(Debug, Serialize)]
+struct SimulateResponse {
+    /// Whether submission would accept the order and pass every risk check
+    accepted: bool,
+    /// Why submission would reject the order outright
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rejection: Option<ErrorDetail>,
+    breaches: Vec<Breach>,
+    #[serde(flatten)]
+    simulation: Simulation,
+    /// Margin the order needs against what the account has free, measured
+    /// as submission does, when an account was given
+    margin: Option<OrderMargin>,
+}
+
+/// POST /api/v1/orders/simulate
+///
+/// Validates the order and runs it through the checks submission makes,
+/// then walks the books and risk checks the expected fills, without placing
+/// it. A reduce-only order is simulated at the size submission cuts it to.
+async fn simulate_order(
+    State(state): State<AppState>,
+    Json(request): Json<SimulateRequest>,
+) -> ApiResult<SimulateResponse> {
+    let account = request.order.account.clone();
+    let bracket = request.order.bracket;
+    let mut order = request
+        .order
+        .into_order()
+        .map_err(|e| ApiError::new(e.status, e.error.message))?;
+    let rejection = pre_trade(
+        &state,
+        &mut order,
+        account.clone().map(AccountId).as_ref(),
+        bracket.as_ref(),
+    )
+    .err()
+    .map(|e| e.error);
+    // An account pays the taker rate of its volume tier
+    let fee_bps = match (&state.fees, &account) {
+        (Some(fees), Some(id)) => {
+            fees.rate_bps(&AccountId(id.clone()), Liquidity::Taker, Utc::now())
+        }
+        _ => state.fee_bps,
+    };
+    let simulation = state.books.simulate(&order, fee_bps);
+    let reserved = simulation.resting_quantity * order.price.value();
+
+    // Caps tighten as the symbol gets more volatile
+    let volatility = state.trades.realized_volatility(order.symbol.as_str());
+    let limits = &state.risk_limits.scaled(volatility);
+    let mut breaches = limits.check_order(&order, simulation.notional + reserved);
+    let mut margin = None;
+    if let Some(id) = account {
+        let id = AccountId(id);
+        let mut account = state
+            .accounts
+            .get(&id)
+            .ok_or_else(|| ApiError::not_found(format!("account {} not found", id)))?;
+        let mark = |symbol: &_| state.books.mark_price(symbol);
+        let order_margin = limits
+            .order_margin(&account, &order, mark)
+            .map_err(|symbol| {
+                ApiError::new(
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    format!("no mark price for {}", symbol),
+                )
+            })?;
+        if !order_margin.sufficient() {
+            breaches.push(Breach::Margin {
+                required: order_margin.required,
+                available: order_margin.available,
+            });
+        }
+        for trade in simulation.trades() {
+            account.apply(trade);
+        }
+        let report = limits.check_account(&account, mark, reserved);
+        // Directional caps count the resting remainder as if it filled
+        let resting = [PositionChange {
+            symbol: order.symbol.clone(),
+            quantity: match order.side {
+                OrderSide::Buy => simulation.resting_quantity,
+                OrderSide::Sell => -simulation.resting_quantity,
+            },
+            price: Some(order.price.value()),
+        }];
+        // Margin was checked on the order above, the way submission does
+        breaches.extend(report.breaches.into_iter().filter(|breach| {
+            !matches!(
+                breach,
+                Breach::LongNotional { .. } | Breach::ShortNotional { .. } | Breach::Margin { .. }
+            )
+        }));
+        breaches.extend(limits.check_exposure(&account, mark, &resting));
+        margin = Some(order_margin);
+    }
+
+    Ok(Json(SimulateResponse {
+        accepted: rejection.is_none() && breaches.is_empty(),
+        rejection,
+        breaches,
+        simulation,
+        margin,
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+struct CheckRequest {
+    account: String,
+    orders: Vec<OrderRequest>,
+}
+
+/// POST /api/v1/orders/check
+///
+/// Risk checks a set of orders together, so a strategy can validate a whole
+/// requote before sending any of it. Nothing is placed.
+async fn check_orders(
+    State(state): State<AppState>,
+    Json(request): Json<CheckRequest>,
+) -> ApiResult<QuoteCheck> {
+    let id = AccountId(request.account);
+    let account = state
+        .accounts
+        .get(&id)
+        .ok_or_else(|| ApiError::not_found(format!("account {} not found", id)))?;
+    let orders = request
+        .orders
+        .into_iter()
+        .enumerate()
+        .map(|(i, order)| {
+            order
+                .into_order()
+                .map_err(|e| ApiError::new(e.status, format!("order {}: {}", i, e.error.message)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let check = state
+        .risk_limits
+        .check_quotes(&account, &orders, |symbol| state.books.mark_price(symbol))
+        .map_err(|symbol| {
+            ApiError::new(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                format!("no mark price for {}", symbol),
+            )
+        })?;
+    Ok(Json(check))
+}
+
+#[derive(Debug, Deserialize)]
+struct CancelAllQuery {
+    client_id: String,
+    /// Only orders on this symbol
+    #[serde(default)]
+    symbol: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct CancelledOrder {
+    order_id: OrderId,
+    symbol: Symbol,
+    side: OrderSide,
+    remaining_quantity: f64,
+}
+
+#[derive(Debug, Serialize)]
+struct CancelAllResponse {
+    client_id: String,
+    symbol: Option<Symbol>,
+    count: usize,
+    cancelled: Vec<CancelledOrder>,
+}
+
+/// DELETE /api/v1/orders?client_id=..&symbol=..
+///
+/// Cancels every open order of a client at once, across all symbols unless
+/// one is given, and answers with everything that was taken off. Orders
+/// accepted before the request are cancelled too, even when still queued
+/// for matching.
+async fn cancel_all(
+    State(state): State<AppState>,
+    Query(query): Query<CancelAllQuery>,
+) -> ApiResult<CancelAllResponse> {
+    if query.client_id.is_empty() {
+        return Err(ApiError::new(
+            StatusCode::BAD_REQUEST,
+            "client_id is required",
+        ));
+    }
+    let symbol = query.symbol.map(|s| Symbol::from(s.to_uppercase()));
+    let job = {
+        let state = state.clone();
+        let client_id = query.client_id.clone();
+        move |symbol: Option<&Symbol>| {
+            let cancelled = state.books.cancel_all(&client_id, symbol);
+            publish(
+                &state,
+                Actor::Client,
+                cancelled.iter().map(ExecutionReport::cancelled),
+            );
+            cancelled
+        }
+    };
+    let cancelled = across_books(&state, symbol.clone(), job).await;
+    Ok(Json(CancelAllResponse {
+        client_id: query.client_id,
+        symbol,
+        count: cancelled.len(),
+        cancelled: cancelled
+            .iter()
+            .map(|order| CancelledOrder {
+                order_id: order.id,
+                symbol: order.symbol.clone(),
+                side: order.side,
+                remaining_quantity: order.remaining_quantity.value(),
+            })
+            .collect(),
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+struct HistoryQuery {
+    /// Filter expression, see [`Filter`]; everything when left out
+    #[serde(default)]
+    filter: String,
+    limit: Option<usize>,
+}
+
+/// GET /api/v1/orders/history?filter=status = Filled AND time in 2024-01-01..2024-01-02&limit=N
+///
+/// Execution reports, spilled ones included, that the filter keeps, oldest
+/// first.
+async fn order_history(
+    State(state): State<AppState>,
+    Query(query): Query<HistoryQuery>,
+) -> ApiResult<Vec<ExecutionReport>> {
+    let filter = Filter::compile::<ExecutionReport>(&query.filter)
+        .map_err(|e| ApiError::new(StatusCode::BAD_REQUEST, format!("invalid filter: {}", e)))?;
+    let limit = query.limit.unwrap_or(100);
+    Ok(Json(state.order_history.query(&filter, limit)))
+}
+
+/// GET /api/v1/orders/:order_id/history
+///
+/// Audit trail of one order: every transition with when it happened, the
+/// component behind it and the order's state before and after, oldest first.
+async fn order_audit(
+    State(state): State<AppState>,
+    Path(order_id): Path<u64>,
+) -> ApiResult<Vec<AuditEntry>> {
+    let history = state.order_audit.history(OrderId(order_id));
+    if history.is_empty() {
+        return Err(ApiError::not_found(format!("order {} not found", order_id)));
+    }
+    Ok(Json(history))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account::Activity;
+    use crate::market::InstrumentStatus;
+    use crate::testkit::SYMBOL;
+
+    #[tokio::test]
+    async fn test_simulation_rejects_what_submission_would() {
+        let state = AppState::new(crate::backtest::BacktestStore::in_memory());
+        let simulate = |reduce_only: bool| {
+            let request = serde_json::from_value(serde_json::json!({
+                "symbol": SYMBOL, "side": "Buy", "type": "limit", "price": 100.0,
+                "quantity": 1.0, "reduce_only": reduce_only
+            }))
+            .unwrap();
+            simulate_order(State(state.clone()), Json(request))
+        };
+
+        let Json(response) = simulate(false).await.unwrap();
+        assert!(response.accepted);
+        let Json(response) = simulate(true).await.unwrap();
+        assert!(!response.accepted);
+        assert_eq!(response.rejection.unwrap().code, "invalid_reduce_only");
+
+        let instrument = serde_json::from_value(serde_json::json!({
+            "symbol": SYMBOL, "base": "BTC", "quote": "USDT", "tick_size": 0.01,
+            "lot_size": 0.00001, "price_precision": 2, "quantity_precision": 5
+        }))
+        .unwrap();
+        state.instruments.insert(instrument);
+        state
+            .instruments
+            .set_status(SYMBOL, InstrumentStatus::Halted);
+        let Json(response) = simulate(false).await.unwrap();
+        assert!(!response.accepted);
+        assert_eq!(response.rejection.unwrap().code, "symbol_not_trading");
+    }
+
+    #[tokio::test]
+    async fn test_simulation_measures_margin_like_submission() {
+        let state = AppState::new(crate::backtest::BacktestStore::in_memory());
+        let deposit = Activity::Transfer {
+            amount: 50.0,
+            timestamp: Utc::now(),
+        };
+        state.accounts.apply(&AccountId::from("alice"), deposit);
+        let request = serde_json::from_value(serde_json::json!({
+            "symbol": SYMBOL, "side": "Buy", "type": "limit", "price": 100.0,
+            "quantity": 10.0, "account": "alice"
+        }))
+        .unwrap();
+        let Json(response) = simulate_order(State(state), Json(request)).await.unwrap();
+
+        assert!(!response.accepted);
+        let margin = response.margin.unwrap();
+        assert_eq!((margin.required, margin.available), (100.0, 50.0));
+        // Submission rejects it on the very same figures
+        let rejection = response.rejection.unwrap();
+        assert_eq!(rejection.code, "insufficient_margin");
+        assert_eq!(rejection.details, serde_json::to_value(&margin).ok());
+    }
+}
@@ -0,0 +1,136 @@
+use axum::extract::{Path, Query, State};
+use axum::routing::get;
+use axum::{Json, Router};
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
+
+use crate::account::{Account, AccountId};
+use crate::api::AppState;
+use crate::risk::EffectiveLimits;
+use crate::risk_journal::RiskSnapshot;
+use crate::timeseries::Resolution;
+use crate::types::Symbol;
+use crate::var::{
+    daily_volatility, log_returns, value_at_risk, SymbolMarket, VarConfig, VarReport,
+};
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/v1/risk/:symbol/limits", get(effective_limits))
+        .route("/api/v1/risk/journal/:account", get(risk_journal))
+}
+/// GET /api/v1/risk/:symbol/limits
+///
+/// Limits in force for the symbol after scaling with its realized volatility.
+async fn effective_limits(
+    State(state): State<AppState>,
+    Path(symbol): Path<String>,
+) -> Json<EffectiveLimits> {
+    let symbol = Symbol::new(symbol);
+    let volatility = state.trades.realized_volatility(symbol.as_str());
+    let band = state
+        .instruments
+        .get(symbol.as_str())
+        .and_then(|instrument| instrument.price_band);
+    Json(
+        state
+            .risk_limits
+            .effective(&symbol, volatility, band.as_ref()),
+    )
+}
+
+#[derive(Debug, Deserialize)]
+struct JournalQuery {
+    /// Defaults to the start of the day of `to`
+    #[serde(default)]
+    from: Option<DateTime<Utc>>,
+    /// Defaults to now
+    #[serde(default)]
+    to: Option<DateTime<Utc>>,
+}
+
+/// GET /api/v1/risk/journal/:account?from=..&to=..
+///
+/// Risk snapshots of the account taken in the range, oldest first.
+async fn risk_journal(
+    State(state): State<AppState>,
+    Path(account): Path<String>,
+    Query(query): Query<JournalQuery>,
+) -> Json<Vec<RiskSnapshot>> {
+    let to = query.to.unwrap_or_else(Utc::now);
+    let from = query.from.unwrap_or_else(|| {
+        to.date_naive()
+            .and_hms_opt(0, 0, 0)
+            .map_or(to, |midnight| midnight.and_utc())
+    });
+    Json(state.risk_journal.snapshots(&AccountId(account), from, to))
+}
+
+/// Book levels a position is taken to be liquidated into
+const VAR_DEPTH_LEVELS: usize = 100;
+
+/// VaR and LVaR of `account` as of `now`, with the tail metrics of its
+/// equity
+///
+/// Volatility comes from the last day of minute prices in the time-series
+/// store, the unwind pace from the rolling 24h volume. Tail metrics are per
+/// minute of the account's recorded equity over the same day.
+pub(crate) fn var_report(
+    state: &AppState,
+    account: &Account,
+    config: &VarConfig,
+    now: DateTime<Utc>,
+) -> VarReport {
+    let last_day = |series: String| {
+        state.timeseries.query(
+            &series,
+            now - Duration::days(1),
+            now,
+            Some(Resolution::Minute),
+        )
+    };
+    let market = |symbol: &Symbol| SymbolMarket {
+        mark: state.books.mark_price(symbol),
+        daily_volatility: last_day(format!("price:{}", symbol))
+            .as_ref()
+            .and_then(daily_volatility),
+        daily_volume: state
+            .rolling
+            .stats(symbol.as_str(), now.timestamp_millis())
+            .map(|stats| stats.volume),
+        depth: state
+            .books
+            .depth(symbol, VAR_DEPTH_LEVELS)
+            .unwrap_or_default(),
+    };
+    let returns = last_day(format!("equity:{}", account.id))
+        .map(|range| log_returns(&range))
+        .unwrap_or_default();
+    value_at_risk(account, config, market).with_returns(&returns)
+}
+
+/// Journal a risk snapshot of every account every `interval`, at the
+/// default VaR settings
+pub fn start_risk_journal(state: &AppState, interval: std::time::Duration) {
+    let state = state.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        loop {
+            interval.tick().await;
+            let now = Utc::now();
+            for id in state.accounts.ids() {
+                let Some(account) = state.accounts.get(&id) else {
+                    continue;
+                };
+                let mark = |symbol: &Symbol| state.books.mark_price(symbol);
+                let equity = state.risk_limits.margin(&account, mark, 0.0).equity;
+                let metrics = state.risk_limits.metrics(&account, mark);
+                let var = var_report(&state, &account, &VarConfig::default(), now);
+                state
+                    .risk_journal
+                    .record(RiskSnapshot::new(id, now, equity, metrics, &var));
+            }
+        }
+    });
+}
@@ -0,0 +1,69 @@
+use std::collections::BTreeMap;
+
+use axum::extract::{Query, State};
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::Deserialize;
+
+use crate::api::AppState;
+use crate::latency::LatencySummary;
+use crate::market::AlertEvent;
+use crate::memory::{MemoryReport, MemoryUsage};
+use crate::orderbook::ExecutionReport;
+use crate::overload::OverloadMetrics;
+use crate::routing::RoutingDecision;
+use crate::throughput::CapacityReport;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/v1/system/overload", get(overload))
+        .route("/api/v1/system/capacity", get(capacity))
+        .route("/api/v1/system/memory", get(memory))
+        .route("/api/v1/system/routing/latency", get(routing_latency))
+        .route("/api/v1/system/routing/decisions", get(routing_decisions))
+}
+
+#[derive(Debug, Deserialize)]
+struct DecisionsQuery {
+    limit: Option<usize>,
+}
+
+/// GET /api/v1/system/overload
+async fn overload(State(state): State<AppState>) -> Json<OverloadMetrics> {
+    Json(state.shedder.metrics())
+}
+
+/// GET /api/v1/system/capacity
+async fn capacity(State(state): State<AppState>) -> Json<CapacityReport> {
+    Json(state.throughput.report())
+}
+
+/// GET /api/v1/system/memory
+async fn memory(State(state): State<AppState>) -> Json<MemoryReport> {
+    Json(state.memory.report(&[
+        ("books", state.books.heap_bytes()),
+        ("trade_tape", state.trades.heap_bytes()),
+        ("depth_snapshots", state.depth.heap_bytes()),
+        (
+            "execution_channel",
+            state.executions.len() * std::mem::size_of::<ExecutionReport>(),
+        ),
+        (
+            "alert_channel",
+            state.alert_events.len() * std::mem::size_of::<AlertEvent>(),
+        ),
+    ]))
+}
+
+/// GET /api/v1/system/routing/latency
+async fn routing_latency(State(state): State<AppState>) -> Json<BTreeMap<String, LatencySummary>> {
+    Json(state.router.ack_latencies())
+}
+
+/// GET /api/v1/system/routing/decisions?limit=N
+async fn routing_decisions(
+    State(state): State<AppState>,
+    Query(query): Query<DecisionsQuery>,
+) -> Json<Vec<RoutingDecision>> {
+    Json(state.router.decisions(query.limit.unwrap_or(100)))
+}
@@ -0,0 +1,94 @@
+use axum::extract::{Path, Query, State};
+use axum::routing::get;
+use axum::{Json, Router};
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
+
+use crate::api::{ApiError, ApiResult, AppState};
+use crate::orderbook::BookKind;
+use crate::timeseries::{Resolution, SeriesRange};
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/v1/timeseries", get(list_series))
+        .route("/api/v1/timeseries/:name", get(query_series))
+}
+
+/// GET /api/v1/timeseries
+async fn list_series(State(state): State<AppState>) -> Json<Vec<String>> {
+    Json(state.timeseries.names())
+}
+
+#[derive(Debug, Deserialize)]
+struct RangeQuery {
+    /// Defaults to an hour before `to`
+    #[serde(default)]
+    from: Option<DateTime<Utc>>,
+    /// Defaults to now
+    #[serde(default)]
+    to: Option<DateTime<Utc>>,
+    /// Defaults to the finest tier still holding `from`
+    #[serde(default)]
+    resolution: Option<Resolution>,
+}
+
+/// GET /api/v1/timeseries/:name?from=..&to=..&resolution=1m
+async fn query_series(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Query(query): Query<RangeQuery>,
+) -> ApiResult<SeriesRange> {
+    let to = query.to.unwrap_or_else(Utc::now);
+    let from = query.from.unwrap_or(to - Duration::hours(1));
+    state
+        .timeseries
+        .query(&name, from, to, query.resolution)
+        .map(Json)
+        .ok_or_else(|| ApiError::not_found(format!("series {} not found", name)))
+}
+
+/// Record the mid and spread of every book, the equity of every account and
+/// the p99 matching latency of every matching book every `interval`
+pub fn start_sampler(state: &AppState, interval: std::time::Duration) {
+    let state = state.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        loop {
+            interval.tick().await;
+            sample(&state, Utc::now());
+        }
+    });
+}
+
+fn sample(state: &AppState, at: DateTime<Utc>) {
+    let series = &state.timeseries;
+    for kind in [BookKind::Mirror, BookKind::Matching] {
+        for symbol in state.books.symbols(kind) {
+            let Some(book) = state.books.get(&symbol, kind) else {
+                continue;
+            };
+            // The mirror book is the market when there is one
+            let quoted =
+                kind == BookKind::Mirror || state.books.get(&symbol, BookKind::Mirror).is_none();
+            if let (true, Some(bid), Some(ask)) = (quoted, book.best_bid(), book.best_ask()) {
+                series.record_quote(&symbol, bid, ask, at);
+            }
+            if kind == BookKind::Matching {
+                if let Some(latency) = book.matching_latency() {
+                    let metric = format!("matching.{}", symbol);
+                    series.record_latency(&metric, latency.p99_ns / 1e6, at);
+                }
+            }
+        }
+    }
+    for id in state.accounts.ids() {
+        if let Some(account) = state.accounts.get(&id) {
+            let margin =
+                state
+                    .risk_limits
+                    .margin(&account, |symbol| state.books.mark_price(symbol), 0.0);
+            series.record_equity(&id.to_string(), margin.equity, at);
+        }
+    }
+}
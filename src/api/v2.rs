@@ -0,0 +1,1634 @@
+// Version 2 of the REST API
+//
+// Orders are acknowledged as soon as they are accepted; matching happens in
+// the background and fills arrive as execution reports on the WebSocket
+// stream, the way exchanges report them. A request may ask to wait for its
+// matching pass instead and get the outcome in the response. Errors carry a
+// stable machine readable code next to the message.
+
+use std::collections::BTreeMap;
+
+use axum::extract::rejection::JsonRejection;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::sync::oneshot;
+
+use crate::account::{Account, AccountId, Activity, FillKey};
+use crate::api::AppState;
+use crate::market::{InstrumentStatus, PriceBand, TapeTrade};
+use crate::orderbook::{
+    Actor, Admission, BookKind, Bracket, CancelFilter, ClientOrder, ExecutionReport, FlowEvent,
+    FlowThrottle, PostOnlyMode, QuoteStats,
+};
+use crate::risk::{Breach, PositionChange};
+use crate::types::{
+    Order, OrderId, OrderSide, OrderStatus, OrderType, Peg, Symbol, TimeInForce, Trade,
+};
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/v2/orders", post(submit_order))
+        .route("/api/v2/orders/cancel", post(mass_cancel))
+        .route("/api/v2/quotes", post(mass_quote))
+        .route("/api/v2/quotes/stats", get(quote_stats))
+        .route("/api/v2/stream", get(stream))
+        .route("/api/v2/stream/flow", get(flow_stream))
+}
+
+#[derive(Debug, Serialize)]
+pub struct ErrorDetail {
+    pub code: &'static str,
+    pub message: String,
+    /// Figures behind the error, e.g. required and available margin
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
+}
+
+/// Error body of every v2 endpoint: `{"error": {"code": ..., "message": ...}}`
+#[derive(Debug, Serialize)]
+pub struct V2Error {
+    #[serde(skip)]
+    pub status: StatusCode,
+    pub error: ErrorDetail,
+}
+
+impl V2Error {
+    pub fn new(status: StatusCode, code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            error: ErrorDetail {
+                code,
+                message: message.into(),
+                details: None,
+            },
+        }
+    }
+
+    pub fn with_details(mut self, details: impl Serialize) -> Self {
+        self.error.details = serde_json::to_value(details).ok();
+        self
+    }
+
+    pub fn invalid(code: &'static str, message: impl Into<String>) -> Self {
+        Self::new(StatusCode::UNPROCESSABLE_ENTITY, code, message)
+    }
+}
+
+impl IntoResponse for V2Error {
+    fn into_response(self) -> Response {
+        (self.status, Json(self)).into_response()
+    }
+}
+
+impl From<JsonRejection> for V2Error {
+    fn from(rejection: JsonRejection) -> Self {
+        Self::new(rejection.status(), "invalid_body", rejection.body_text())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum OrderKind {
+    Limit,
+    Market,
+    StopMarket,
+    StopLimit,
+}
+
+/// When a submission is answered
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum AckMode {
+    /// Once the order passed the checks; the outcome follows on the stream
+    #[default]
+    Async,
+    /// Once the order went through matching, with the outcome in the answer
+    Sync,
+}
+
+/// Order body shared with the v1 simulation endpoint
+#[derive(Debug, Deserialize)]
+pub(crate) struct OrderRequest {
+    symbol: String,
+    side: OrderSide,
+    #[serde(rename = "type")]
+    kind: OrderKind,
+    price: Option<f64>,
+    /// Last trade price that activates a stop order
+    #[serde(default)]
+    stop_price: Option<f64>,
+    quantity: f64,
+    #[serde(default)]
+    time_in_force: TimeInForce,
+    /// Expiry of a good-till-date order
+    #[serde(default)]
+    expires_at: Option<DateTime<Utc>>,
+    /// Quantity an iceberg order shows at a time
+    #[serde(default)]
+    display_quantity: Option<f64>,
+    /// Reject rather than take liquidity
+    #[serde(default)]
+    post_only: bool,
+    /// Only close the account's position, resized down to it when larger
+    #[serde(default)]
+    reduce_only: bool,
+    /// Track the book instead of resting at `price`, which is then optional
+    #[serde(default)]
+    peg: Option<Peg>,
+    /// Take-profit and stop-loss placed as the order fills
+    #[serde(default)]
+    pub(crate) bracket: Option<Bracket>,
+    /// Account to margin the order against
+    #[serde(default)]
+    pub(crate) account: Option<String>,
+    /// Client the order is placed for, which cancel-all works by
+    #[serde(default)]
+    client_id: Option<String>,
+    /// The client's own ID for the order; resubmitting it returns the
+    /// original order instead of placing another
+    #[serde(default)]
+    client_order_id: Option<String>,
+    /// Whether to answer before or after matching
+    #[serde(default)]
+    pub(crate) ack: AckMode,
+}
+
+impl OrderRequest {
+    pub(crate) fn into_order(self) -> Result<Order, V2Error> {
+        if self.symbol.is_empty() {
+            return Err(V2Error::invalid("invalid_symbol", "symbol is required"));
+        }
+        if !(self.quantity.is_finite() && self.quantity > 0.0) {
+            return Err(V2Error::invalid(
+                "invalid_quantity",
+                "quantity must be positive",
+            ));
+        }
+        let symbol = self.symbol.to_uppercase();
+        let positive = |value: Option<f64>| value.filter(|v| v.is_finite() && *v > 0.0);
+        let price = || {
+            positive(self.price).ok_or_else(|| {
+                V2Error::invalid("invalid_price", "limit orders need a positive price")
+            })
+        };
+        let stop_price = || {
+            positive(self.stop_price).ok_or_else(|| {
+                V2Error::invalid(
+                    "invalid_stop_price",
+                    "stop orders need a positive stop_price",
+                )
+            })
+        };
+        if let Some(peg) = self.peg {
+            if !matches!(self.kind, OrderKind::Limit) || !peg.offset.is_finite() {
+                return Err(V2Error::invalid(
+                    "invalid_peg",
+                    "only limit orders can be pegged, with a finite offset",
+                ));
+            }
+        }
+        let mut order = match self.kind {
+            // The book prices pegged orders as they arrive
+            OrderKind::Limit if self.peg.is_some() => {
+                Order::new_limit(symbol, self.side, 0.0, self.quantity)
+            }
+            OrderKind::Limit => Order::new_limit(symbol, self.side, price()?, self.quantity),
+            OrderKind::Market => Order::new_market(symbol, self.side, self.quantity),
+            OrderKind::StopMarket => {
+                Order::new_stop_market(symbol, self.side, stop_price()?, self.quantity)
+            }
+            OrderKind::StopLimit => {
+                Order::new_stop_limit(symbol, self.side, stop_price()?, price()?, self.quantity)
+            }
+        };
+        if let Some(display) = self.display_quantity {
+            let resting = matches!(self.kind, OrderKind::Limit | OrderKind::StopLimit);
+            if !(resting && display.is_finite() && display > 0.0 && display < self.quantity) {
+                return Err(V2Error::invalid(
+                    "invalid_display_quantity",
+                    "display_quantity must be positive, below quantity and on a limit order",
+                ));
+            }
+            order = order.with_display_quantity(display);
+        }
+        if self.post_only && !matches!(self.kind, OrderKind::Limit | OrderKind::StopLimit) {
+            return Err(V2Error::invalid(
+                "invalid_post_only",
+                "only limit orders can be post-only",
+            ));
+        }
+        order = order
+            .with_post_only(self.post_only)
+            .with_reduce_only(self.reduce_only);
+        if let Some(peg) = self.peg {
+            order = order.with_peg(peg);
+        }
+        if let Some(client_id) = &self.client_id {
+            order = order.with_client_id(client_id);
+        }
+        if let Some(client_order_id) = &self.client_order_id {
+            if self.client_id.is_none() || client_order_id.is_empty() {
+                return Err(V2Error::invalid(
+                    "invalid_client_order_id",
+                    "client_order_id must be non-empty and needs a client_id",
+                ));
+            }
+            order = order.with_client_order_id(client_order_id);
+        }
+        match (self.time_in_force, self.expires_at) {
+            (TimeInForce::Gtd, Some(expires_at)) if expires_at > Utc::now() => {
+                Ok(order.good_till(expires_at))
+            }
+            (TimeInForce::Gtd, _) => Err(V2Error::invalid(
+                "invalid_expiry",
+                "good-till-date orders need a future expires_at",
+            )),
+            (time_in_force, None) => Ok(order.with_time_in_force(time_in_force)),
+            (_, Some(_)) => Err(V2Error::invalid(
+                "invalid_expiry",
+                "expires_at only applies to gtd orders",
+            )),
+        }
+    }
+}
+
+/// Answer to a submission; unless it was synchronous, the outcome follows on
+/// the stream
+#[derive(Debug, Serialize)]
+struct OrderAck {
+    order_id: OrderId,
+    symbol: Symbol,
+    order_type: OrderType,
+    status: OrderStatus,
+    /// Quantity a reduce-only order was cut down to, when it was
+    #[serde(skip_serializing_if = "Option::is_none")]
+    resized_to: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    client_order_id: Option<String>,
+    accepted_at: DateTime<Utc>,
+    /// Orders of the client queued at the throttle, this one included, when
+    /// it goes to the book later
+    #[serde(skip_serializing_if = "Option::is_none")]
+    queue_depth: Option<u32>,
+    /// Latest state of the original order, when this was a resubmission
+    /// of its client order ID, or of this one after a synchronous submission
+    #[serde(skip_serializing_if = "Option::is_none")]
+    report: Option<ExecutionReport>,
+    /// Fills of the order in its matching pass, for a synchronous submission
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    fills: Vec<ExecutionReport>,
+}
+
+impl OrderAck {
+    /// Answer to a resubmission: the order first placed with the ID
+    fn original(original: ClientOrder, client_order_id: Option<String>) -> Self {
+        let status = match &original.report {
+            Some(ExecutionReport::OrderUpdate { status, .. }) => *status,
+            _ => OrderStatus::Pending,
+        };
+        Self {
+            order_id: original.order_id,
+            symbol: original.symbol,
+            order_type: original.order_type,
+            status,
+            resized_to: None,
+            client_order_id,
+            accepted_at: original.accepted_at,
+            queue_depth: None,
+            report: original.report,
+            fills: Vec::new(),
+        }
+    }
+}
+
+/// POST /api/v2/orders
+async fn submit_order(
+    State(state): State<AppState>,
+    request: Result<Json<OrderRequest>, JsonRejection>,
+) -> Result<(StatusCode, Json<OrderAck>), V2Error> {
+    let Json(request) = request?;
+    let account = request.account.clone();
+    let bracket = request.bracket;
+    let ack_mode = request.ack;
+    let mut order = request.into_order()?;
+    if let (Some(client_id), Some(client_order_id)) = (&order.client_id, &order.client_order_id) {
+        if let Some(original) = state.client_orders.get(client_id, client_order_id) {
+            let ack = OrderAck::original(original, order.client_order_id.clone());
+            return Ok((StatusCode::OK, Json(ack)));
+        }
+    }
+    let account = account.map(AccountId);
+    let resized_to = pre_trade(&state, &mut order, account.as_ref(), bracket.as_ref())?;
+    let queued = throttle(&state, &order, account.as_ref())?;
+    let accepted_at = Utc::now();
+    // Claimed only once the order passed every check, so a rejected
+    // submission can be retried with the same ID
+    if let Err(original) = state.client_orders.claim(&order, accepted_at) {
+        let ack = OrderAck::original(original, order.client_order_id.clone());
+        return Ok((StatusCode::OK, Json(ack)));
+    }
+    if let Some(id) = account {
+        state.order_owners.insert(&order, id);
+    }
+    let mut ack = OrderAck {
+        order_id: order.id,
+        symbol: order.symbol.clone(),
+        order_type: order.order_type,
+        status: OrderStatus::Pending,
+        resized_to,
+        client_order_id: order.client_order_id.clone(),
+        accepted_at,
+        queue_depth: queued.map(|(depth, _)| depth),
+        report: None,
+        fills: Vec::new(),
+    };
+
+    // Matched off the handler either way, so a caller hanging up on a
+    // synchronous submission does not stop its order midway
+    let order_id = order.id;
+    let symbol = order.symbol.clone();
+    let shards = state.shards.clone();
+    // Opened now, so a cancel across every book queues behind this order
+    // even while it waits for its shard
+    state.books.matching(symbol.clone());
+    let (done, execution) = oneshot::channel();
+    let job = move || {
+        let depth = queued.map(|(depth, _)| depth);
+        let _ = done.send(execute_order(&state, order, bracket, depth));
+    };
+    match (shards, queued) {
+        // Queued right here so orders on a symbol match in arrival order
+        (Some(shards), None) => shards.dispatch(&symbol, job),
+        (shards, queued) => {
+            tokio::spawn(async move {
+                if let Some((_, delay)) = queued {
+                    tokio::time::sleep(delay).await;
+                }
+                match shards {
+                    Some(shards) => shards.dispatch(&symbol, job),
+                    None => job(),
+                }
+            });
+        }
+    }
+    if ack_mode == AckMode::Async {
+        return Ok((StatusCode::ACCEPTED, Json(ack)));
+    }
+    let reports = execution.await.map_err(|_| {
+        tracing::error!("Matching order #{} did not complete", order_id.0);
+        V2Error::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "matching_failed",
+            "the order's matching pass did not complete",
+        )
+    })?;
+    for report in reports.into_iter().filter(|r| r.order_id() == order_id) {
+        match report {
+            ExecutionReport::OrderUpdate { status, .. } => {
+                ack.status = status;
+                ack.report = Some(report);
+            }
+            fill => ack.fills.push(fill),
+        }
+    }
+    Ok((StatusCode::OK, Json(ack)))
+}
+
+/// Send an accepted order to its book, publishing and returning the
+/// execution reports of its matching pass
+fn execute_order(
+    state: &AppState,
+    order: Order,
+    bracket: Option<Bracket>,
+    queue_depth: Option<u32>,
+) -> Vec<ExecutionReport> {
+    if let Some(ledger) = &state.ledger {
+        ledger.order_submitted(&order);
+    }
+    state.order_audit.submitted(&order, Actor::Client);
+    let trades = match bracket {
+        Some(bracket) => state.books.submit_bracket(order.clone(), bracket),
+        None => state.books.submit(order.clone()),
+    };
+    publish_flow(
+        state,
+        FlowEvent::submission(std::slice::from_ref(&order), &trades),
+    );
+    let reports: Vec<_> = ExecutionReport::for_submission(&order, &trades)
+        .into_iter()
+        .map(|report| match queue_depth {
+            Some(depth) => report.with_queue_depth(depth),
+            None => report,
+        })
+        .collect();
+    publish(state, Actor::Matching, reports.clone());
+    reports
+}
+
+/// Run `order` past every check submission makes before accepting it, for
+/// `account` when given
+///
+/// A reduce-only order larger than the account's position is cut down to
+/// it, and its new size returned. Nothing is placed or reserved, so the
+/// simulation endpoint runs the same checks.
+pub(crate) fn pre_trade(
+    state: &AppState,
+    order: &mut Order,
+    account: Option<&AccountId>,
+    bracket: Option<&Bracket>,
+) -> Result<Option<f64>, V2Error> {
+    if let Some(bracket) = bracket {
+        check_bracket(order, bracket)?;
+    }
+    check_trading(state, &order.symbol)?;
+    check_price_band(state, order)?;
+    check_post_only(state, order)?;
+    let resized_to = match (account, order.reduce_only) {
+        (Some(id), true) => check_reduce_only(state, id, order)?,
+        (None, true) => {
+            return Err(V2Error::invalid(
+                "invalid_reduce_only",
+                "reduce-only orders need an account",
+            ))
+        }
+        (_, false) => None,
+    };
+    check_order_limits(state, order, account)?;
+    if let Some(id) = account {
+        check_exposure(state, id, &[PositionChange::from_order(order)])?;
+        check_margin(state, id, order)?;
+    }
+    Ok(resized_to)
+}
+
+fn find_account(state: &AppState, id: &AccountId) -> Result<Account, V2Error> {
+    state.accounts.get(id).ok_or_else(|| {
+        V2Error::new(
+            StatusCode::NOT_FOUND,
+            "unknown_account",
+            format!("account {} not found", id),
+        )
+    })
+}
+
+/// Reject a bracket on a stop entry, or with exits on the wrong side of the
+/// entry: the take-profit beyond its price and the stop-loss short of it
+fn check_bracket(order: &Order, bracket: &Bracket) -> Result<(), V2Error> {
+    let entry_price = match order.order_type {
+        OrderType::Market => None,
+        OrderType::StopMarket | OrderType::StopLimit => {
+            return Err(V2Error::invalid(
+                "invalid_bracket",
+                "brackets only apply to limit and market entries",
+            ))
+        }
+        _ => Some(order.price.value()),
+    };
+    if !bracket.is_valid_for(order.side, entry_price) {
+        return Err(V2Error::invalid(
+            "invalid_bracket",
+            "take_profit and stop_loss must be positive and on either side of the entry",
+        )
+        .with_details(bracket));
+    }
+    Ok(())
+}
+
+/// Reject orders on registered instruments that are halted or delisted
+fn check_trading(state: &AppState, symbol: &Symbol) -> Result<(), V2Error> {
+    match state.instruments.get(symbol.as_str()).map(|i| i.status) {
+        None | Some(InstrumentStatus::Trading) => Ok(()),
+        Some(status) => Err(V2Error::invalid(
+            "symbol_not_trading",
+            format!("{} is {:?}", symbol, status).to_lowercase(),
+        )),
+    }
+}
+
+/// Reject `order` when it is priced outside its symbol's band around the mark
+///
+/// Symbols without a band or a mark, and orders without a limit price, pass.
+fn check_price_band(state: &AppState, order: &Order) -> Result<(), V2Error> {
+    if matches!(order.order_type, OrderType::Market | OrderType::StopMarket) {
+        return Ok(());
+    }
+    let symbol = order.symbol.as_str();
+    let Some(band) = state.instruments.get(symbol).and_then(|i| i.price_band) else {
+        return Ok(());
+    };
+    let Some(reference) = state.books.mark_price(&order.symbol) else {
+        return Ok(());
+    };
+    let volatility = state.trades.realized_volatility(symbol);
+    let widened = band.is_widened(Utc::now(), volatility);
+    // The band widens as volatility scaling tightens the other limits
+    let band = PriceBand {
+        percent: band.percent / state.risk_limits.volatility_factor(volatility),
+        ..band
+    };
+    let (low, high) = band.range(reference, widened);
+    let price = order.price.value();
+    if (low..=high).contains(&price) {
+        return Ok(());
+    }
+    Err(V2Error::invalid(
+        "price_out_of_band",
+        format!(
+            "price {} is outside {:.2}..{:.2} around the mark {:.2}",
+            price, low, high, reference
+        ),
+    )
+    .with_details(serde_json::json!({
+        "reference": reference,
+        "low": low,
+        "high": high,
+        "widened": widened,
+    })))
+}
+
+/// Reject a post-only order that would cross when the book rejects those,
+/// so the client hears about it now rather than from the book
+fn check_post_only(state: &AppState, order: &Order) -> Result<(), V2Error> {
+    if !order.post_only || order.is_stop() {
+        return Ok(());
+    }
+    let book = state.books.matching(order.symbol.clone());
+    if book.post_only_mode() == PostOnlyMode::Reject && book.would_cross(order) {
+        return Err(V2Error::invalid(
+            "post_only_would_cross",
+            "post-only order would take liquidity",
+        ));
+    }
+    Ok(())
+}
+
+/// Run `order` past its client's rate limit, keyed by client ID, else by
+/// account
+///
+/// Returns the queue depth and delay when the order has to wait.
+fn throttle(
+    state: &AppState,
+    order: &Order,
+    account: Option<&AccountId>,
+) -> Result<Option<(u32, std::time::Duration)>, V2Error> {
+    let Some(throttle) = &state.throttle else {
+        return Ok(None);
+    };
+    let client = order
+        .client_id
+        .as_deref()
+        .or(account.map(|id| id.0.as_str()))
+        .unwrap_or("anonymous");
+    match throttle.admit(client, std::time::Instant::now()) {
+        Admission::Now => Ok(None),
+        Admission::Queued { depth, delay } => Ok(Some((depth, delay))),
+        Admission::Rejected => Err(V2Error::new(
+            StatusCode::TOO_MANY_REQUESTS,
+            "rate_limited",
+            format!("{} is over its order rate", client),
+        )),
+    }
+}
+
+/// Reject a reduce-only order that can only grow the account's position,
+/// and cut one larger than the position down to it, returning the new size
+///
+/// The account's open reduce-only orders on the same side already close
+/// part of the position, so only the rest is left to this one.
+fn check_reduce_only(
+    state: &AppState,
+    id: &AccountId,
+    order: &mut Order,
+) -> Result<Option<f64>, V2Error> {
+    let account = find_account(state, id)?;
+    let position = account.positions.get(&order.symbol);
+    let open = state
+        .order_owners
+        .open_reduce_only(id, &order.symbol, order.side);
+    let reducible = position.map_or(0.0, |position| position.reducible(order.side)) - open;
+    if reducible <= 0.0 {
+        return Err(V2Error::invalid(
+            "reduce_only_would_increase",
+            format!("no {} position left to reduce", order.symbol),
+        )
+        .with_details(serde_json::json!({
+            "position": position.map_or(0.0, |position| position.quantity),
+            "open_reduce_only": open,
+        })));
+    }
+    if order.remaining_quantity.value() <= reducible {
+        return Ok(None);
+    }
+    order.resize(reducible);
+    Ok(Some(reducible))
+}
+
+/// Reject `order` over the order size caps, or growing `account`'s position
+/// past its cap, with the caps scaled for the symbol's realized volatility
+///
+/// Limit orders are valued at their price, others at the mark and then
+/// their stop price; one with neither is only capped by quantity.
+fn check_order_limits(
+    state: &AppState,
+    order: &Order,
+    account: Option<&AccountId>,
+) -> Result<(), V2Error> {
+    let volatility = state.trades.realized_volatility(order.symbol.as_str());
+    let limits = state.risk_limits.scaled(volatility);
+    let change = PositionChange::from_order(order);
+    let price = match order.order_type {
+        OrderType::Market | OrderType::StopMarket => {
+            state.books.mark_price(&order.symbol).or(change.price)
+        }
+        _ => change.price,
+    };
+    let quantity = order.remaining_quantity.value();
+    let mut breaches = limits.check_order(order, price.map_or(0.0, |p| quantity * p));
+    if let (Some(limit), Some(id), Some(price)) = (limits.max_position_notional, account, price) {
+        let held = find_account(state, id)?
+            .positions
+            .get(&order.symbol)
+            .map_or(0.0, |position| position.quantity);
+        let after = held + change.quantity;
+        let notional = after.abs() * price;
+        if after.abs() > held.abs() && notional > limit {
+            breaches.push(Breach::PositionNotional {
+                symbol: order.symbol.clone(),
+                notional,
+                limit,
+            });
+        }
+    }
+    let message = match breaches.first() {
+        None => return Ok(()),
+        Some(Breach::OrderQuantity { quantity, limit }) => {
+            format!("quantity {} is over the {} limit", quantity, limit)
+        }
+        Some(Breach::OrderNotional { notional, limit }) => {
+            format!("notional {:.2} is over the {:.2} limit", notional, limit)
+        }
+        Some(Breach::PositionNotional {
+            symbol,
+            notional,
+            limit,
+        }) => format!(
+            "{} position would reach {:.2}, over the {:.2} limit",
+            symbol, notional, limit
+        ),
+        Some(_) => unreachable!("order limits only report size and position caps"),
+    };
+    Err(
+        V2Error::invalid("order_limit_exceeded", message).with_details(serde_json::json!({
+            "breaches": breaches,
+            "volatility": volatility,
+            "factor": state.risk_limits.volatility_factor(volatility),
+        })),
+    )
+}
+
+/// Reject `changes` when they would take the account past a long or short
+/// cap, on a symbol or as a whole
+///
+/// Orders count in full since any of them may fill. A cap the account is
+/// already over only stops changes that add to it, so it can still trade
+/// back under.
+fn check_exposure(
+    state: &AppState,
+    id: &AccountId,
+    changes: &[PositionChange],
+) -> Result<(), V2Error> {
+    let account = find_account(state, id)?;
+    let mark = |symbol: &Symbol| state.books.mark_price(symbol);
+    let held = state.risk_limits.check_exposure(&account, mark, &[]);
+    let mut breaches = state.risk_limits.check_exposure(&account, mark, changes);
+    breaches.retain(|breach| {
+        let (side, symbol, notional, _) = directional(breach);
+        !held.iter().any(|held| {
+            let (held_side, held_symbol, held_notional, _) = directional(held);
+            (held_side, held_symbol) == (side, symbol) && notional <= held_notional
+        })
+    });
+    let Some(breach) = breaches.first() else {
+        return Ok(());
+    };
+    let (side, symbol, notional, limit) = directional(breach);
+    let mut positions = BTreeMap::new();
+    for change in changes {
+        let held = account
+            .positions
+            .get(&change.symbol)
+            .map_or(0.0, |position| position.quantity);
+        *positions.entry(change.symbol.clone()).or_insert(held) += change.quantity;
+    }
+    let scope = match symbol {
+        Some(symbol) => format!("{}'s", symbol),
+        None => "the account's".to_string(),
+    };
+    Err(V2Error::invalid(
+        "exposure_limit_exceeded",
+        format!(
+            "{} notional would reach {:.2}, over {} {} limit of {:.2}",
+            side, notional, scope, side, limit
+        ),
+    )
+    .with_details(serde_json::json!({
+        "breaches": breaches,
+        "positions": positions,
+    })))
+}
+
+/// Side, symbol, notional and limit of a long or short cap breach
+fn directional(breach: &Breach) -> (&'static str, Option<&Symbol>, f64, f64) {
+    match breach {
+        Breach::LongNotional {
+            symbol,
+            notional,
+            limit,
+        } => ("long", symbol.as_ref(), *notional, *limit),
+        Breach::ShortNotional {
+            symbol,
+            notional,
+            limit,
+        } => ("short", symbol.as_ref(), *notional, *limit),
+        _ => unreachable!("exposure checks only report long and short caps"),
+    }
+}
+
+/// Reject `order` when `account` lacks the free margin it needs
+fn check_margin(state: &AppState, id: &AccountId, order: &Order) -> Result<(), V2Error> {
+    let account = find_account(state, id)?;
+    let margin = state
+        .risk_limits
+        .order_margin(&account, order, |symbol| state.books.mark_price(symbol))
+        .map_err(|symbol| {
+            V2Error::invalid("no_mark_price", format!("no mark price for {}", symbol))
+        })?;
+    if margin.sufficient() {
+        return Ok(());
+    }
+    Err(V2Error::invalid(
+        "insufficient_margin",
+        format!(
+            "order needs {:.2} initial margin, {:.2} available",
+            margin.required, margin.available
+        ),
+    )
+    .with_details(margin))
+}
+
+#[derive(Debug, Deserialize)]
+struct QuoteLevel {
+    price: f64,
+    quantity: f64,
+}
+
+/// A strategy's full two-sided quote set for one symbol
+#[derive(Debug, Deserialize)]
+struct QuoteRequest {
+    symbol: String,
+    strategy: String,
+    #[serde(default)]
+    bids: Vec<QuoteLevel>,
+    #[serde(default)]
+    asks: Vec<QuoteLevel>,
+    /// Account to risk check the whole set against
+    #[serde(default)]
+    account: Option<String>,
+    /// Cancel the quotes unless refreshed within this many milliseconds
+    #[serde(default)]
+    ttl_ms: Option<u64>,
+}
+
+impl QuoteRequest {
+    fn into_orders(self) -> Result<(Symbol, String, Vec<Order>), V2Error> {
+        if self.symbol.is_empty() || self.strategy.is_empty() {
+            return Err(V2Error::invalid(
+                "invalid_quote",
+                "symbol and strategy are required",
+            ));
+        }
+        let symbol = Symbol::from(self.symbol.to_uppercase());
+        let levels = self
+            .bids
+            .iter()
+            .map(|level| (OrderSide::Buy, level))
+            .chain(self.asks.iter().map(|level| (OrderSide::Sell, level)));
+        if self.ttl_ms == Some(0) {
+            return Err(V2Error::invalid("invalid_quote", "ttl_ms must be positive"));
+        }
+        let expires_at = self
+            .ttl_ms
+            .map(|ttl| Utc::now() + chrono::Duration::milliseconds(ttl as i64));
+        let mut quotes = Vec::with_capacity(self.bids.len() + self.asks.len());
+        for (side, level) in levels {
+            let positive = |v: f64| v.is_finite() && v > 0.0;
+            if !(positive(level.price) && positive(level.quantity)) {
+                return Err(V2Error::invalid(
+                    "invalid_quote",
+                    "quote prices and quantities must be positive",
+                ));
+            }
+            let quote = Order::new_limit(symbol.clone(), side, level.price, level.quantity)
+                .with_strategy(&self.strategy);
+            quotes.push(match expires_at {
+                Some(expires_at) => quote.good_till(expires_at),
+                None => quote,
+            });
+        }
+        let best_bid = self.bids.iter().map(|l| l.price).fold(f64::NAN, f64::max);
+        let best_ask = self.asks.iter().map(|l| l.price).fold(f64::NAN, f64::min);
+        if best_bid >= best_ask {
+            return Err(V2Error::invalid(
+                "crossed_quotes",
+                format!("bid {} crosses ask {}", best_bid, best_ask),
+            ));
+        }
+        Ok((symbol, self.strategy, quotes))
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct QuoteAck {
+    symbol: Symbol,
+    strategy: String,
+    placed: Vec<OrderId>,
+    cancelled: Vec<OrderId>,
+    /// Trades the new quotes made on placement; fills follow on the stream
+    trades: usize,
+}
+
+/// POST /api/v2/quotes
+///
+/// Replaces the strategy's quotes on the symbol with the given set in one
+/// matching-engine pass. Sending no levels pulls all of its quotes. Quotes
+/// sent with a TTL are cancelled by the expiry sweeper unless a new set
+/// replaces them first, so a stalled strategy does not leave stale quotes.
+async fn mass_quote(
+    State(state): State<AppState>,
+    request: Result<Json<QuoteRequest>, JsonRejection>,
+) -> Result<Json<QuoteAck>, V2Error> {
+    let Json(request) = request?;
+    let account = request.account.clone();
+    let ttl_ms = request.ttl_ms;
+    let (symbol, strategy, quotes) = request.into_orders()?;
+    check_trading(&state, &symbol)?;
+    for quote in &quotes {
+        check_price_band(&state, quote)?;
+        check_order_limits(&state, quote, None)?;
+    }
+    if let Some(id) = account.map(AccountId) {
+        // Either side of the set may be swept, so each is capped on its own
+        let (bids, asks): (Vec<_>, Vec<_>) = quotes
+            .iter()
+            .map(PositionChange::from_order)
+            .partition(|change| change.quantity > 0.0);
+        check_exposure(&state, &id, &bids)?;
+        check_exposure(&state, &id, &asks)?;
+        check_quotes(&state, &id, &symbol, &quotes)?;
+    }
+
+    if let Some(ledger) = &state.ledger {
+        for quote in &quotes {
+            ledger.order_submitted(quote);
+        }
+    }
+    for quote in &quotes {
+        state.order_audit.submitted(quote, Actor::Client);
+    }
+    let placed: Vec<OrderId> = quotes.iter().map(|q| q.id).collect();
+    // Opened now, so a cancel across every book queues behind these quotes
+    state.books.matching(symbol.clone());
+    let job = {
+        let (state, symbol, strategy) = (state.clone(), symbol.clone(), strategy.clone());
+        move || replace_quotes(&state, &symbol, &strategy, quotes, ttl_ms)
+    };
+    let (cancelled, trades) = on_book(&state, &symbol, job).await.ok_or_else(|| {
+        tracing::error!(
+            "Replacing {} quotes on {} did not complete",
+            strategy,
+            symbol
+        );
+        V2Error::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "matching_failed",
+            "the quotes' matching pass did not complete",
+        )
+    })?;
+
+    Ok(Json(QuoteAck {
+        symbol,
+        strategy,
+        placed,
+        cancelled: cancelled.iter().map(|o| o.id).collect(),
+        trades: trades.len(),
+    }))
+}
+
+/// Swap `strategy`'s quotes on `symbol` for `quotes`, publishing what was
+/// cancelled and traded
+fn replace_quotes(
+    state: &AppState,
+    symbol: &Symbol,
+    strategy: &str,
+    quotes: Vec<Order>,
+    ttl_ms: Option<u64>,
+) -> (Vec<Order>, Vec<Trade>) {
+    let (cancelled, trades) = state.books.replace_quotes(symbol, strategy, quotes.clone());
+    state.quotes.record_refresh(
+        strategy,
+        symbol,
+        quotes.len(),
+        cancelled.len(),
+        ttl_ms,
+        Utc::now(),
+    );
+    publish_flow(state, FlowEvent::submission(&quotes, &trades));
+    publish(
+        state,
+        Actor::Client,
+        cancelled.iter().map(ExecutionReport::cancelled),
+    );
+    let reports = quotes.iter().flat_map(|quote| {
+        let own: Vec<_> = trades
+            .iter()
+            .filter(|t| t.taker_order_id == quote.id)
+            .cloned()
+            .collect();
+        ExecutionReport::for_submission(quote, &own)
+    });
+    publish(state, Actor::Matching, reports);
+    (cancelled, trades)
+}
+
+/// GET /api/v2/quotes/stats
+///
+/// Refresh and expiry counters by strategy, then symbol.
+async fn quote_stats(
+    State(state): State<AppState>,
+) -> Json<BTreeMap<String, BTreeMap<Symbol, QuoteStats>>> {
+    Json(state.quotes.stats())
+}
+
+/// Reject a quote set on `symbol` that would breach a risk limit as a whole,
+/// with the caps scaled for the symbol's realized volatility
+fn check_quotes(
+    state: &AppState,
+    id: &AccountId,
+    symbol: &Symbol,
+    quotes: &[Order],
+) -> Result<(), V2Error> {
+    let account = find_account(state, id)?;
+    let volatility = state.trades.realized_volatility(symbol.as_str());
+    let check = state
+        .risk_limits
+        .scaled(volatility)
+        .check_quotes(&account, quotes, |symbol| state.books.mark_price(symbol))
+        .map_err(|symbol| {
+            V2Error::invalid("no_mark_price", format!("no mark price for {}", symbol))
+        })?;
+    if check.accepted {
+        return Ok(());
+    }
+    Err(V2Error::invalid("risk_check_failed", "quote set breaches risk limits").with_details(check))
+}
+
+/// Orders to mass cancel: everything on the symbol, or every symbol when absent
+#[derive(Debug, Deserialize)]
+struct MassCancelRequest {
+    #[serde(default)]
+    symbol: Option<String>,
+    #[serde(flatten)]
+    filter: CancelFilter,
+}
+
+#[derive(Debug, Serialize)]
+struct MassCancelAck {
+    cancelled: Vec<OrderId>,
+}
+
+/// POST /api/v2/orders/cancel
+async fn mass_cancel(
+    State(state): State<AppState>,
+    request: Result<Json<MassCancelRequest>, JsonRejection>,
+) -> Result<Json<MassCancelAck>, V2Error> {
+    let Json(request) = request?;
+    let symbol = request.symbol.map(|s| Symbol::from(s.to_uppercase()));
+    let job = {
+        let state = state.clone();
+        move |symbol: Option<&Symbol>| {
+            let cancelled = state.books.mass_cancel(symbol, &request.filter);
+            publish(
+                &state,
+                Actor::Client,
+                cancelled.iter().map(ExecutionReport::cancelled),
+            );
+            cancelled
+        }
+    };
+    let cancelled = across_books(&state, symbol, job).await;
+    Ok(Json(MassCancelAck {
+        cancelled: cancelled.iter().map(|o| o.id).collect(),
+    }))
+}
+
+/// Take expired good-till-date orders and quotes off the books every
+/// `interval`, reporting them on the stream as expired and counting expired
+/// quotes
+///
+/// Each book is swept in line with the orders sent to it.
+pub fn start_expiry_sweeper(state: &AppState, interval: std::time::Duration) {
+    let state = state.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        loop {
+            interval.tick().await;
+            let job = {
+                let state = state.clone();
+                move |symbol: Option<&Symbol>| {
+                    let expired = state.books.expire_orders(symbol, Utc::now());
+                    if !expired.is_empty() {
+                        state.quotes.record_expired(&expired, Utc::now());
+                        publish(
+                            &state,
+                            Actor::ExpirySweeper,
+                            expired.iter().map(ExecutionReport::expired),
+                        );
+                    }
+                    expired
+                }
+            };
+            let expired = across_books(&state, None, job).await;
+            if !expired.is_empty() {
+                tracing::debug!("Expired {} good-till-date orders", expired.len());
+            }
+        }
+    });
+}
+
+/// Run `job` on the books of `symbol` behind the orders already sent to them
+///
+/// With matching shards it queues on the symbol's shard like an order, so
+/// it never overtakes one accepted before it; otherwise it runs right away.
+/// None if the job panicked.
+pub(crate) async fn on_book<T: Send + 'static>(
+    state: &AppState,
+    symbol: &Symbol,
+    job: impl FnOnce() -> T + Send + 'static,
+) -> Option<T> {
+    let Some(shards) = &state.shards else {
+        return Some(job());
+    };
+    let (done, result) = oneshot::channel();
+    shards.dispatch(symbol, move || {
+        let _ = done.send(job());
+    });
+    result.await.ok()
+}
+
+/// Run `job` for `symbol`, or for every matching book when None, each time
+/// behind the orders already sent to the book, collecting what it returns
+///
+/// Without matching shards `job` runs once with `symbol` as given, so the
+/// book manager can hold every book it covers at once.
+pub(crate) async fn across_books<T: Send + 'static>(
+    state: &AppState,
+    symbol: Option<Symbol>,
+    job: impl Fn(Option<&Symbol>) -> Vec<T> + Clone + Send + 'static,
+) -> Vec<T> {
+    if state.shards.is_none() {
+        return job(symbol.as_ref());
+    }
+    let symbols = match symbol {
+        Some(symbol) => vec![symbol],
+        None => state.books.symbols(BookKind::Matching),
+    };
+    let mut results = Vec::new();
+    for symbol in symbols {
+        let job = job.clone();
+        let on = symbol.clone();
+        match on_book(state, &symbol, move || job(Some(&on))).await {
+            Some(done) => results.extend(done),
+            None => tracing::error!("Book job on {} did not complete", symbol),
+        }
+    }
+    results
+}
+
+/// Record `reports` in the ledger and the audit trail, as transitions
+/// `actor` made, and send them to stream subscribers, cancels to order-flow
+/// subscribers too
+pub(crate) fn publish(
+    state: &AppState,
+    actor: Actor,
+    reports: impl IntoIterator<Item = ExecutionReport>,
+) {
+    for report in reports {
+        if let Some(ledger) = &state.ledger {
+            ledger.execution(&report);
+        }
+        settle_fill(state, &report);
+        state.order_owners.record(&report);
+        state.client_orders.record(&report);
+        state.order_audit.record(&report, actor);
+        state.order_history.record(report.clone());
+        publish_flow(state, FlowEvent::from_report(&report));
+        // No subscribers is fine; reports are not buffered for later
+        let _ = state.executions.send(report);
+    }
+}
+
+/// Book a fill to the account of its order, charged at the account's fee
+/// tier, which the fill then counts towards
+fn settle_fill(state: &AppState, report: &ExecutionReport) {
+    let ExecutionReport::Fill {
+        order_id,
+        price,
+        quantity,
+        liquidity,
+        timestamp,
+        ..
+    } = report
+    else {
+        return;
+    };
+    let Some(owner) = state.order_owners.get(*order_id) else {
+        return;
+    };
+    let notional = price * quantity;
+    let fee = match &state.fees {
+        Some(fees) => fees.charge(&owner.account, *liquidity, notional, *timestamp),
+        None => notional * state.fee_bps / 10_000.0,
+    };
+    state.accounts.apply(
+        &owner.account,
+        Activity::Trade {
+            symbol: owner.symbol,
+            side: owner.side,
+            price: *price,
+            quantity: *quantity,
+            fee,
+            timestamp: *timestamp,
+            fill: Some(FillKey {
+                order_id: *order_id,
+                fill_seq: owner.fills,
+            }),
+        },
+    );
+}
+
+/// Fill resting orders as exchange trades in `trades` go through their
+/// prices, publishing the fills as they happen
+///
+/// Only does anything with the books crossing with the market; see
+/// [`BookManager::fill_through`](crate::orderbook::BookManager::fill_through).
+pub fn start_pending_fills(state: &AppState, mut trades: broadcast::Receiver<TapeTrade>) {
+    let state = state.clone();
+    tokio::spawn(async move {
+        loop {
+            let trade = match trades.recv().await {
+                Ok(trade) => trade,
+                // Orders a missed trade went through fill on a later one
+                Err(RecvError::Lagged(missed)) => {
+                    tracing::warn!("Pending fills missed {} market trades", missed);
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            };
+            let job = {
+                let state = state.clone();
+                move |symbol: Option<&Symbol>| {
+                    let Some(symbol) = symbol else {
+                        return Vec::new();
+                    };
+                    let fills = state.books.fill_through(symbol, trade.price.value());
+                    if !fills.is_empty() {
+                        tracing::debug!("{} orders filled through on {}", fills.len(), symbol);
+                        publish(&state, Actor::Market, ExecutionReport::fills(&fills));
+                    }
+                    fills
+                }
+            };
+            across_books(&state, Some(trade.symbol.clone()), job).await;
+        }
+    });
+}
+
+fn publish_flow(state: &AppState, events: impl IntoIterator<Item = FlowEvent>) {
+    for event in events {
+        // No visualizer connected is the usual case
+        let _ = state.order_flow.send(event);
+    }
+}
+
+/// Order-flow events a visualizer gets at most per second unless it asks for fewer
+const MAX_FLOW_RATE: u32 = 100;
+
+#[derive(Debug, Deserialize)]
+struct FlowQuery {
+    /// Only events of this symbol
+    #[serde(default)]
+    symbol: Option<String>,
+    #[serde(default = "default_flow_rate")]
+    max_per_sec: u32,
+}
+
+fn default_flow_rate() -> u32 {
+    20
+}
+
+/// GET /api/v2/stream/flow, upgraded to a WebSocket of order arrivals,
+/// cancels and trades for driving book animations
+///
+/// The feed is capped at `max_per_sec` events, at most 100; events over
+/// the cap are dropped and their count sent before the next one.
+async fn flow_stream(
+    State(state): State<AppState>,
+    Query(query): Query<FlowQuery>,
+    ws: WebSocketUpgrade,
+) -> Response {
+    let events = state.order_flow.subscribe();
+    let symbol = query.symbol.map(|s| Symbol::from(s.to_uppercase()));
+    let throttle = FlowThrottle::new(query.max_per_sec.clamp(1, MAX_FLOW_RATE));
+    ws.on_upgrade(move |socket| forward_flow(socket, symbol, throttle, events))
+}
+
+async fn forward_flow(
+    mut socket: WebSocket,
+    symbol: Option<Symbol>,
+    mut throttle: FlowThrottle,
+    mut events: broadcast::Receiver<FlowEvent>,
+) {
+    loop {
+        let event = match events.recv().await {
+            Ok(event) => event,
+            // Lagging only loses events the throttle would mostly drop anyway
+            Err(RecvError::Lagged(_)) => continue,
+            Err(RecvError::Closed) => break,
+        };
+        if symbol.as_ref().is_some_and(|s| s != event.symbol()) {
+            continue;
+        }
+        if !throttle.admit(std::time::Instant::now()) {
+            continue;
+        }
+        let dropped = throttle.take_dropped();
+        if dropped > 0 {
+            let text = serde_json::json!({ "type": "dropped", "count": dropped }).to_string();
+            if socket.send(Message::Text(text)).await.is_err() {
+                break;
+            }
+        }
+        let Ok(text) = serde_json::to_string(&event) else {
+            continue;
+        };
+        if socket.send(Message::Text(text)).await.is_err() {
+            break;
+        }
+    }
+}
+
+/// GET /api/v2/stream, upgraded to a WebSocket of execution reports
+async fn stream(State(state): State<AppState>, ws: WebSocketUpgrade) -> Response {
+    let reports = state.executions.subscribe();
+    ws.on_upgrade(move |socket| forward_reports(socket, reports))
+}
+
+async fn forward_reports(mut socket: WebSocket, mut reports: broadcast::Receiver<ExecutionReport>) {
+    loop {
+        let text = match reports.recv().await {
+            Ok(report) => serde_json::to_string(&report),
+            // A slow client is told how much it missed rather than disconnected
+            Err(RecvError::Lagged(missed)) => {
+                serde_json::to_string(&serde_json::json!({ "type": "lagged", "missed": missed }))
+            }
+            Err(RecvError::Closed) => break,
+        };
+        let Ok(text) = text else {
+            continue;
+        };
+        if socket.send(Message::Text(text)).await.is_err() {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account::{FeeSchedule, FeeTier, FeeTracker, SharedFeeTracker};
+    use crate::orderbook::MatchingShards;
+    use crate::testkit::{assert_golden, golden_time, SYMBOL};
+
+    #[test]
+    fn test_v2_payloads_match_golden_files() {
+        assert_golden(
+            "v2_order_ack",
+            &OrderAck {
+                order_id: OrderId(1),
+                symbol: Symbol::new(SYMBOL),
+                order_type: OrderType::StopLimit,
+                status: OrderStatus::Pending,
+                resized_to: None,
+                client_order_id: None,
+                accepted_at: golden_time(),
+                queue_depth: None,
+                report: None,
+                fills: Vec::new(),
+            },
+        );
+        assert_golden(
+            "v2_quote_ack",
+            &QuoteAck {
+                symbol: Symbol::new(SYMBOL),
+                strategy: "mm".to_string(),
+                placed: vec![OrderId(2), OrderId(3)],
+                cancelled: vec![OrderId(1)],
+                trades: 0,
+            },
+        );
+        let error = V2Error::invalid("price_out_of_band", "price 120 is outside 95..105")
+            .with_details(serde_json::json!({ "low": 95.0, "high": 105.0 }));
+        assert_golden("v2_error", &error);
+    }
+
+    #[tokio::test]
+    async fn test_sync_ack_waits_for_matching() {
+        let state = AppState::new(crate::backtest::BacktestStore::in_memory());
+        let submit = |body: serde_json::Value| {
+            let request = serde_json::from_value(body).unwrap();
+            submit_order(State(state.clone()), Ok(Json(request)))
+        };
+        let (status, Json(resting)) = submit(serde_json::json!({
+            "symbol": SYMBOL, "side": "Sell", "type": "limit", "price": 100.0, "quantity": 1.0
+        }))
+        .await
+        .unwrap();
+        assert_eq!(status, StatusCode::ACCEPTED);
+        assert!(resting.report.is_none());
+
+        // Let the resting order reach its book first
+        tokio::task::yield_now().await;
+        let (status, Json(ack)) = submit(serde_json::json!({
+            "symbol": SYMBOL, "side": "Buy", "type": "limit", "price": 100.0,
+            "quantity": 0.4, "ack": "sync"
+        }))
+        .await
+        .unwrap();
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(ack.status, OrderStatus::Filled);
+        assert_eq!(ack.fills.len(), 1);
+        assert!(matches!(
+            ack.report,
+            Some(ExecutionReport::OrderUpdate { filled_quantity, .. }) if filled_quantity == 0.4
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_sharded_orders_match_in_arrival_order() {
+        let state = AppState::new(crate::backtest::BacktestStore::in_memory())
+            .with_matching_shards(MatchingShards::new(2));
+        let submit = |side: &str, ack: &str| {
+            let request = serde_json::from_value(serde_json::json!({
+                "symbol": SYMBOL, "side": side, "type": "limit", "price": 100.0,
+                "quantity": 1.0, "ack": ack
+            }))
+            .unwrap();
+            submit_order(State(state.clone()), Ok(Json(request)))
+        };
+        // No yielding in between: the shard alone keeps the two in order
+        let (status, _) = submit("Sell", "async").await.unwrap();
+        assert_eq!(status, StatusCode::ACCEPTED);
+        let (_, Json(ack)) = submit("Buy", "sync").await.unwrap();
+        assert_eq!(ack.status, OrderStatus::Filled);
+        assert_eq!(ack.fills.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_sharded_cancels_wait_for_earlier_orders() {
+        let state = AppState::new(crate::backtest::BacktestStore::in_memory())
+            .with_matching_shards(MatchingShards::new(2));
+        let request = serde_json::from_value(serde_json::json!({
+            "symbol": SYMBOL, "side": "Sell", "type": "limit", "price": 100.0,
+            "quantity": 1.0
+        }))
+        .unwrap();
+        let (status, Json(ack)) = submit_order(State(state.clone()), Ok(Json(request)))
+            .await
+            .unwrap();
+        assert_eq!(status, StatusCode::ACCEPTED);
+
+        // Sent before the order may have reached its book
+        let request = serde_json::from_value(serde_json::json!({})).unwrap();
+        let Json(cancel) = mass_cancel(State(state.clone()), Ok(Json(request)))
+            .await
+            .unwrap();
+        assert_eq!(cancel.cancelled, vec![ack.order_id]);
+        assert_eq!(state.books.matching(SYMBOL).order_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_fills_move_both_accounts_up_their_fee_tiers() {
+        let fees = FeeTracker::new(FeeSchedule::new(vec![
+            FeeTier {
+                min_volume: 0.0,
+                maker_bps: 1.0,
+                taker_bps: 5.0,
+            },
+            FeeTier {
+                min_volume: 1_000.0,
+                maker_bps: 0.0,
+                taker_bps: 3.0,
+            },
+        ]));
+        let state = AppState::new(crate::backtest::BacktestStore::in_memory())
+            .with_fee_tracker(SharedFeeTracker::new(fees));
+        for account in ["alice", "bob"] {
+            let deposit = Activity::Transfer {
+                amount: 100_000.0,
+                timestamp: Utc::now(),
+            };
+            state.accounts.apply(&AccountId::from(account), deposit);
+        }
+        let submit = |account: &str, side: &str| {
+            let request = serde_json::from_value(serde_json::json!({
+                "symbol": SYMBOL, "side": side, "type": "limit", "price": 100.0,
+                "quantity": 10.0, "account": account, "ack": "sync"
+            }))
+            .unwrap();
+            submit_order(State(state.clone()), Ok(Json(request)))
+        };
+        let tier = |account: &str| {
+            let fees = state.fees.as_ref().unwrap();
+            fees.status(&AccountId::from(account), Utc::now()).tier
+        };
+
+        let (_, Json(resting)) = submit("bob", "Sell").await.unwrap();
+        assert_eq!(resting.status, OrderStatus::Pending);
+        assert_eq!((tier("alice"), tier("bob")), (0, 0));
+        let (_, Json(ack)) = submit("alice", "Buy").await.unwrap();
+        assert_eq!(ack.status, OrderStatus::Filled);
+        // 1,000 traded on each side, as taker and as maker
+        assert_eq!((tier("alice"), tier("bob")), (1, 1));
+    }
+
+    #[tokio::test]
+    async fn test_reduce_only_orders_share_the_position() {
+        let state = AppState::new(crate::backtest::BacktestStore::in_memory());
+        let (alice, bob) = (AccountId::from("alice"), AccountId::from("bob"));
+        for id in [&alice, &bob] {
+            let deposit = Activity::Transfer {
+                amount: 100_000.0,
+                timestamp: Utc::now(),
+            };
+            state.accounts.apply(id, deposit);
+        }
+        let submit = |account: &str, side: &str, price: f64, quantity: f64, reduce_only| {
+            let request = serde_json::from_value(serde_json::json!({
+                "symbol": SYMBOL, "side": side, "type": "limit", "price": price,
+                "quantity": quantity, "account": account, "reduce_only": reduce_only,
+                "ack": "sync"
+            }))
+            .unwrap();
+            submit_order(State(state.clone()), Ok(Json(request)))
+        };
+
+        // Nothing to reduce before alice trades
+        let error = submit("alice", "Sell", 110.0, 1.0, true).await.unwrap_err();
+        assert_eq!(error.error.code, "reduce_only_would_increase");
+
+        // The fill reaches both accounts
+        let (status, _) = submit("bob", "Sell", 100.0, 2.0, false).await.unwrap();
+        assert_eq!(status, StatusCode::OK);
+        let (_, Json(ack)) = submit("alice", "Buy", 100.0, 2.0, false).await.unwrap();
+        assert_eq!(ack.status, OrderStatus::Filled);
+        let position = |id| state.accounts.get(id).unwrap().positions[SYMBOL].quantity;
+        assert_eq!((position(&alice), position(&bob)), (2.0, -2.0));
+
+        // A resting reduce-only sell of 1.5 leaves 0.5 for the next one
+        let (_, Json(first)) = submit("alice", "Sell", 110.0, 1.5, true).await.unwrap();
+        assert_eq!(
+            (first.status, first.resized_to),
+            (OrderStatus::Pending, None)
+        );
+        let (_, Json(second)) = submit("alice", "Sell", 111.0, 1.0, true).await.unwrap();
+        assert_eq!(second.resized_to, Some(0.5));
+        let error = submit("alice", "Sell", 112.0, 1.0, true).await.unwrap_err();
+        assert_eq!(error.error.code, "reduce_only_would_increase");
+        assert!(submit("alice", "Buy", 90.0, 1.0, true).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_orders_and_quotes_stay_within_exposure_caps() {
+        let mut limits = crate::risk::RiskLimits::default();
+        limits.symbol_exposure.max_long_notional = Some(250.0);
+        let state = AppState::new(crate::backtest::BacktestStore::in_memory())
+            .with_risk_limits(limits, 0.0);
+        let alice = AccountId::from("alice");
+        let deposit = Activity::Transfer {
+            amount: 100_000.0,
+            timestamp: Utc::now(),
+        };
+        state.accounts.apply(&alice, deposit);
+        let submit = |side: &str, quantity: f64| {
+            let request = serde_json::from_value(serde_json::json!({
+                "symbol": SYMBOL, "side": side, "type": "limit", "price": 100.0,
+                "quantity": quantity, "account": "alice"
+            }))
+            .unwrap();
+            submit_order(State(state.clone()), Ok(Json(request)))
+        };
+
+        let error = submit("Buy", 3.0).await.unwrap_err();
+        assert_eq!(error.error.code, "exposure_limit_exceeded");
+        let details = error.error.details.unwrap();
+        assert_eq!(details["positions"][SYMBOL], 3.0);
+        assert_eq!(details["breaches"][0]["limit"], 250.0);
+        let (status, _) = submit("Buy", 2.0).await.unwrap();
+        assert_eq!(status, StatusCode::ACCEPTED);
+        // Sells only shrink the long side
+        let (status, _) = submit("Sell", 5.0).await.unwrap();
+        assert_eq!(status, StatusCode::ACCEPTED);
+
+        let quote = |bids: serde_json::Value| {
+            let request = serde_json::from_value(serde_json::json!({
+                "symbol": SYMBOL, "strategy": "mm", "account": "alice",
+                "bids": bids, "asks": [{ "price": 101.0, "quantity": 5.0 }]
+            }))
+            .unwrap();
+            mass_quote(State(state.clone()), Ok(Json(request)))
+        };
+        let error = quote(serde_json::json!([
+            { "price": 99.0, "quantity": 2.0 },
+            { "price": 98.0, "quantity": 1.0 }
+        ]))
+        .await
+        .unwrap_err();
+        assert_eq!(error.error.code, "exposure_limit_exceeded");
+        let levels = serde_json::json!([{ "price": 99.0, "quantity": 2.0 }]);
+        assert!(quote(levels).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_order_caps_tighten_with_volatility() {
+        let limits = crate::risk::RiskLimits {
+            max_order_quantity: Some(20.0),
+            max_position_notional: Some(1_000.0),
+            volatility_scaling: Some(crate::risk::VolatilityScaling::Steps {
+                steps: vec![crate::risk::VolatilityStep {
+                    max_volatility: 1.0,
+                    factor: 0.5,
+                }],
+            }),
+            ..Default::default()
+        };
+        let state = AppState::new(crate::backtest::BacktestStore::in_memory())
+            .with_risk_limits(limits, 0.0);
+        for trade in crate::testkit::market()
+            .prices(&[100.0, 101.0, 99.0, 100.0])
+            .trades()
+        {
+            state.trades.record(trade.clone());
+        }
+        let deposit = Activity::Transfer {
+            amount: 100_000.0,
+            timestamp: Utc::now(),
+        };
+        state.accounts.apply(&AccountId::from("alice"), deposit);
+        let submit = |quantity: f64, account: Option<&str>| {
+            let request = serde_json::from_value(serde_json::json!({
+                "symbol": SYMBOL, "side": "Buy", "type": "limit", "price": 100.0,
+                "quantity": quantity, "account": account
+            }))
+            .unwrap();
+            submit_order(State(state.clone()), Ok(Json(request)))
+        };
+
+        // Halved from 20 and 1,000 by the volatility on the tape
+        let error = submit(12.0, None).await.unwrap_err();
+        assert_eq!(error.error.code, "order_limit_exceeded");
+        assert_eq!(error.error.details.unwrap()["factor"], 0.5);
+        let error = submit(6.0, Some("alice")).await.unwrap_err();
+        assert_eq!(error.error.code, "order_limit_exceeded");
+        assert!(error.error.message.contains("position"));
+        let (status, _) = submit(4.0, Some("alice")).await.unwrap();
+        assert_eq!(status, StatusCode::ACCEPTED);
+
+        let request = serde_json::from_value(serde_json::json!({
+            "symbol": SYMBOL, "strategy": "mm",
+            "bids": [{ "price": 99.0, "quantity": 12.0 }]
+        }))
+        .unwrap();
+        let error = mass_quote(State(state.clone()), Ok(Json(request)))
+            .await
+            .unwrap_err();
+        assert_eq!(error.error.code, "order_limit_exceeded");
+    }
+}
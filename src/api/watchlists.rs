@@ -0,0 +1,128 @@
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::routing::{delete, get};
+use axum::{Json, Router};
+use serde::Deserialize;
+
+use crate::api::{ApiError, ApiResult, AppState};
+use crate::market::Watchlist;
+use crate::types::Symbol;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/v1/users/:user/watchlists", get(list_watchlists))
+        .route(
+            "/api/v1/users/:user/watchlists/:name",
+            get(get_watchlist)
+                .put(put_watchlist)
+                .delete(delete_watchlist),
+        )
+        .route(
+            "/api/v1/users/:user/watchlists/:name/symbols/:symbol",
+            delete(remove_symbol).put(add_symbol),
+        )
+}
+
+#[derive(Debug, Deserialize)]
+struct WatchlistBody {
+    symbols: Vec<Symbol>,
+}
+
+/// Reject symbols missing from a non-empty instrument registry
+fn check_symbols(state: &AppState, symbols: &[Symbol]) -> Result<(), ApiError> {
+    if state.instruments.is_empty() {
+        return Ok(());
+    }
+    match symbols
+        .iter()
+        .find(|symbol| state.instruments.get(symbol.as_str()).is_none())
+    {
+        Some(symbol) => Err(ApiError::new(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!("unknown symbol {}", symbol),
+        )),
+        None => Ok(()),
+    }
+}
+
+fn not_found(name: &str) -> ApiError {
+    ApiError::not_found(format!("watchlist {} not found", name))
+}
+
+/// GET /api/v1/users/:user/watchlists
+async fn list_watchlists(
+    State(state): State<AppState>,
+    Path(user): Path<String>,
+) -> Json<Vec<Watchlist>> {
+    Json(state.watchlists.list(&user))
+}
+
+/// GET /api/v1/users/:user/watchlists/:name
+async fn get_watchlist(
+    State(state): State<AppState>,
+    Path((user, name)): Path<(String, String)>,
+) -> ApiResult<Watchlist> {
+    state
+        .watchlists
+        .get(&user, &name)
+        .map(Json)
+        .ok_or_else(|| not_found(&name))
+}
+
+/// PUT /api/v1/users/:user/watchlists/:name, creating or replacing the list
+async fn put_watchlist(
+    State(state): State<AppState>,
+    Path((user, name)): Path<(String, String)>,
+    Json(body): Json<WatchlistBody>,
+) -> Result<(StatusCode, Json<Watchlist>), ApiError> {
+    check_symbols(&state, &body.symbols)?;
+    let created = state.watchlists.put(&user, &name, body.symbols);
+    let status = if created {
+        StatusCode::CREATED
+    } else {
+        StatusCode::OK
+    };
+    let list = state
+        .watchlists
+        .get(&user, &name)
+        .ok_or_else(|| not_found(&name))?;
+    Ok((status, Json(list)))
+}
+
+/// DELETE /api/v1/users/:user/watchlists/:name
+async fn delete_watchlist(
+    State(state): State<AppState>,
+    Path((user, name)): Path<(String, String)>,
+) -> Result<StatusCode, ApiError> {
+    if state.watchlists.delete(&user, &name) {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(not_found(&name))
+    }
+}
+
+/// PUT /api/v1/users/:user/watchlists/:name/symbols/:symbol
+async fn add_symbol(
+    State(state): State<AppState>,
+    Path((user, name, symbol)): Path<(String, String, String)>,
+) -> ApiResult<Watchlist> {
+    let symbol = Symbol::new(symbol);
+    check_symbols(&state, std::slice::from_ref(&symbol))?;
+    state
+        .watchlists
+        .add(&user, &name, symbol)
+        .map(Json)
+        .ok_or_else(|| not_found(&name))
+}
+
+/// DELETE /api/v1/users/:user/watchlists/:name/symbols/:symbol
+async fn remove_symbol(
+    State(state): State<AppState>,
+    Path((user, name, symbol)): Path<(String, String, String)>,
+) -> ApiResult<Watchlist> {
+    state
+        .watchlists
+        .remove(&user, &name, &symbol.to_uppercase())
+        .map(Json)
+        .ok_or_else(|| not_found(&name))
+}
@@ -0,0 +1,263 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::ops::Range;
+use std::path::Path;
+
+use chrono::DateTime;
+use memmap2::Mmap;
+
+use crate::backtest::runner::MarketSnapshot;
+
+/// Read-only historical data a backtest can replay
+pub trait SnapshotSource: Sync {
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Feed every snapshot to `visit` in time order
+    fn replay(&self, visit: &mut dyn FnMut(&MarketSnapshot));
+}
+
+impl SnapshotSource for [MarketSnapshot] {
+    fn len(&self) -> usize {
+        <[MarketSnapshot]>::len(self)
+    }
+
+    fn replay(&self, visit: &mut dyn FnMut(&MarketSnapshot)) {
+        self.iter().for_each(visit);
+    }
+}
+
+const MAGIC: &[u8; 8] = b"OBHIST01";
+const HEADER_LEN: usize = 16;
+const RECORD_HEADER_LEN: usize = 16;
+const LEVEL_LEN: usize = 16;
+
+/// Snapshot history stored in a compact binary file and memory-mapped, so
+/// concurrent backtests share one copy of the data through the page cache
+///
+/// Layout (little endian): `OBHIST01`, u64 record count, then per record an
+/// i64 timestamp in nanoseconds, u32 bid count, u32 ask count and that many
+/// (f64 price, f64 quantity) pairs, bids first.
+pub struct MappedHistory {
+    mmap: Mmap,
+    offsets: Vec<usize>,
+}
+
+impl MappedHistory {
+    /// Write snapshots to `path` in the mapped history format
+    pub fn write(path: impl AsRef<Path>, snapshots: &[MarketSnapshot]) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        out.write_all(MAGIC)?;
+        out.write_all(&(snapshots.len() as u64).to_le_bytes())?;
+
+        for snapshot in snapshots {
+            let nanos = snapshot.timestamp.timestamp_nanos_opt().ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidInput, "timestamp out of range")
+            })?;
+            out.write_all(&nanos.to_le_bytes())?;
+            out.write_all(&(snapshot.bids.len() as u32).to_le_bytes())?;
+            out.write_all(&(snapshot.asks.len() as u32).to_le_bytes())?;
+
+            for &(price, quantity) in snapshot.bids.iter().chain(&snapshot.asks) {
+                out.write_all(&price.to_le_bytes())?;
+                out.write_all(&quantity.to_le_bytes())?;
+            }
+        }
+
+        out.flush()
+    }
+
+    /// Memory-map an existing history file and index its records
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = File::open(path)?;
+        // SAFETY: history files are written once and treated as immutable
+        // while mapped; nothing in this crate writes to an open mapping.
+        let mmap = unsafe { Mmap::map(&file)? };
+
+        if mmap.len() < HEADER_LEN || &mmap[..8] != MAGIC {
+            return Err(invalid("not a snapshot history file"));
+        }
+        // Every record takes at least its header, so a count the file cannot
+        // hold is corrupt and must not size the allocation below
+        let count = read_u64(&mmap, 8);
+        if count > ((mmap.len() - HEADER_LEN) / RECORD_HEADER_LEN) as u64 {
+            return Err(invalid("record count exceeds the file"));
+        }
+        let count = count as usize;
+
+        let mut offsets = Vec::with_capacity(count);
+        let mut offset = HEADER_LEN;
+        for _ in 0..count {
+            if offset + RECORD_HEADER_LEN > mmap.len() {
+                return Err(invalid("truncated record header"));
+            }
+            let levels =
+                read_u32(&mmap, offset + 8) as usize + read_u32(&mmap, offset + 12) as usize;
+            let end = offset + RECORD_HEADER_LEN + levels * LEVEL_LEN;
+            if end > mmap.len() {
+                return Err(invalid("truncated record levels"));
+            }
+
+            offsets.push(offset);
+            offset = end;
+        }
+
+        Ok(Self { mmap, offsets })
+    }
+
+    /// Decode a single snapshot
+    pub fn get(&self, index: usize) -> Option<MarketSnapshot> {
+        let mut snapshot = empty_snapshot();
+        self.decode_into(*self.offsets.get(index)?, &mut snapshot);
+        Some(snapshot)
+    }
+
+    /// View of a contiguous range of records, e.g. a walk-forward window
+    pub fn range(&self, range: Range<usize>) -> MappedRange<'_> {
+        let end = range.end.min(self.offsets.len());
+        let start = range.start.min(end);
+        MappedRange {
+            history: self,
+            range: start..end,
+        }
+    }
+
+    fn decode_into(&self, offset: usize, snapshot: &mut MarketSnapshot) {
+        let data = &self.mmap[..];
+        let nanos = read_u64(data, offset) as i64;
+        let bid_count = read_u32(data, offset + 8) as usize;
+        let ask_count = read_u32(data, offset + 12) as usize;
+
+        snapshot.timestamp = DateTime::from_timestamp_nanos(nanos);
+        snapshot.bids.clear();
+        snapshot.asks.clear();
+
+        let mut cursor = offset + RECORD_HEADER_LEN;
+        for i in 0..bid_count + ask_count {
+            let level = (read_f64(data, cursor), read_f64(data, cursor + 8));
+            if i < bid_count {
+                snapshot.bids.push(level);
+            } else {
+                snapshot.asks.push(level);
+            }
+            cursor += LEVEL_LEN;
+        }
+    }
+
+    fn replay_offsets(&self, offsets: &[usize], visit: &mut dyn FnMut(&MarketSnapshot)) {
+        // Reuse one snapshot buffer for the whole replay
+        let mut snapshot = empty_snapshot();
+        for &offset in offsets {
+            self.decode_into(offset, &mut snapshot);
+            visit(&snapshot);
+        }
+    }
+}
+
+impl SnapshotSource for MappedHistory {
+    fn len(&self) -> usize {
+        self.offsets.len()
+    }
+
+    fn replay(&self, visit: &mut dyn FnMut(&MarketSnapshot)) {
+        self.replay_offsets(&self.offsets, visit);
+    }
+}
+
+/// Sub-range of a [`MappedHistory`]
+pub struct MappedRange<'a> {
+    history: &'a MappedHistory,
+    range: Range<usize>,
+}
+
+impl SnapshotSource for MappedRange<'_> {
+    fn len(&self) -> usize {
+        self.range.len()
+    }
+
+    fn replay(&self, visit: &mut dyn FnMut(&MarketSnapshot)) {
+        self.history
+            .replay_offsets(&self.history.offsets[self.range.clone()], visit);
+    }
+}
+
+fn empty_snapshot() -> MarketSnapshot {
+    MarketSnapshot {
+        timestamp: DateTime::UNIX_EPOCH,
+        bids: Vec::new(),
+        asks: Vec::new(),
+    }
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+fn read_f64(data: &[u8], offset: usize) -> f64 {
+    f64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone, Utc};
+
+    fn history() -> Vec<MarketSnapshot> {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        (0..4)
+            .map(|i| MarketSnapshot {
+                timestamp: start + Duration::seconds(i),
+                bids: vec![(99.0 + i as f64, 1.0), (98.0, 2.5)],
+                asks: vec![(101.0 + i as f64, 1.5)],
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_round_trip_mapped_history() {
+        let path = std::env::temp_dir().join(format!("obhist-{}.bin", std::process::id()));
+        let snapshots = history();
+        MappedHistory::write(&path, &snapshots).unwrap();
+
+        let mapped = MappedHistory::open(&path).unwrap();
+        assert_eq!(SnapshotSource::len(&mapped), 4);
+
+        let decoded = mapped.get(2).unwrap();
+        assert_eq!(decoded.timestamp, snapshots[2].timestamp);
+        assert_eq!(decoded.bids, snapshots[2].bids);
+        assert_eq!(decoded.asks, snapshots[2].asks);
+
+        let mut seen = Vec::new();
+        mapped.range(1..3).replay(&mut |s| seen.push(s.best_bid()));
+        assert_eq!(seen, vec![Some(100.0), Some(101.0)]);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_rejects_foreign_file() {
+        let path = std::env::temp_dir().join(format!("obhist-bad-{}.bin", std::process::id()));
+        std::fs::write(&path, b"definitely not a history file").unwrap();
+
+        assert!(MappedHistory::open(&path).is_err());
+
+        // A header claiming more records than the file holds
+        let mut header = MAGIC.to_vec();
+        header.extend_from_slice(&u64::MAX.to_le_bytes());
+        std::fs::write(&path, header).unwrap();
+        assert!(MappedHistory::open(&path).is_err());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,219 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+use rayon::prelude::*;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use serde::Serialize;
+
+use crate::backtest::data::SnapshotSource;
+use crate::backtest::optimizer::ParameterSet;
+use crate::backtest::runner::{Backtest, BacktestConfig, BacktestResult, Strategy};
+use crate::backtest::store::BacktestStore;
+
+/// Progress counters shared between a running batch and its observers
+#[derive(Debug)]
+pub struct JobProgress {
+    total: AtomicUsize,
+    completed: AtomicUsize,
+    started_at: Instant,
+}
+
+/// Point-in-time view of a batch's progress
+#[derive(Debug, Clone, Serialize)]
+pub struct ProgressReport {
+    pub total: usize,
+    pub completed: usize,
+    pub elapsed_ms: u128,
+    /// Estimated time to completion at the current run rate
+    pub eta_ms: Option<u128>,
+}
+
+impl JobProgress {
+    fn new() -> Self {
+        Self {
+            total: AtomicUsize::new(0),
+            completed: AtomicUsize::new(0),
+            started_at: Instant::now(),
+        }
+    }
+
+    pub fn report(&self) -> ProgressReport {
+        let total = self.total.load(Ordering::Relaxed);
+        let completed = self.completed.load(Ordering::Relaxed);
+        let elapsed = self.started_at.elapsed();
+
+        let eta_ms = (completed > 0).then(|| {
+            let per_job = elapsed / completed as u32;
+            (per_job * total.saturating_sub(completed) as u32).as_millis()
+        });
+
+        ProgressReport {
+            total,
+            completed,
+            elapsed_ms: elapsed.as_millis(),
+            eta_ms,
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.completed.load(Ordering::Relaxed) >= self.total.load(Ordering::Relaxed)
+    }
+}
+
+/// Distributes backtest runs over a dedicated rayon thread pool
+///
+/// Historical data is borrowed read-only by every worker, so a
+/// [`MappedHistory`](crate::backtest::MappedHistory) is shared without copies.
+pub struct JobRunner {
+    pool: ThreadPool,
+    progress: Arc<JobProgress>,
+    store: Option<Arc<BacktestStore>>,
+}
+
+impl JobRunner {
+    /// Create a runner with `threads` workers (0 = one per core)
+    pub fn new(threads: usize) -> Self {
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .thread_name(|i| format!("backtest-{}", i))
+            .build()
+            .expect("failed to build backtest thread pool");
+
+        Self {
+            pool,
+            progress: Arc::new(JobProgress::new()),
+            store: None,
+        }
+    }
+
+    /// Persist every completed run to `store`
+    pub fn with_store(mut self, store: Arc<BacktestStore>) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// Handle for polling progress from another thread or task
+    pub fn progress(&self) -> Arc<JobProgress> {
+        Arc::clone(&self.progress)
+    }
+
+    /// Run one backtest per parameter set; results keep the input order
+    pub fn run_all<S, F, D>(
+        &self,
+        config: &BacktestConfig,
+        jobs: &[ParameterSet],
+        data: &D,
+        make_strategy: F,
+    ) -> Vec<BacktestResult>
+    where
+        S: Strategy,
+        F: Fn(&ParameterSet) -> S + Sync,
+        D: SnapshotSource + ?Sized,
+    {
+        self.progress.total.fetch_add(jobs.len(), Ordering::Relaxed);
+        let log_every = (jobs.len() / 10).max(1);
+
+        self.pool.install(|| {
+            jobs.par_iter()
+                .map(|params| {
+                    let mut strategy = make_strategy(params);
+                    let result = Backtest::new(config.clone()).run_source(&mut strategy, data);
+
+                    if let Some(store) = &self.store {
+                        if let Err(e) = store.save(result.clone()) {
+                            tracing::error!("Failed to persist backtest run: {}", e);
+                        }
+                    }
+
+                    let done = self.progress.completed.fetch_add(1, Ordering::Relaxed) + 1;
+                    if done.is_multiple_of(log_every) {
+                        let report = self.progress.report();
+                        tracing::debug!(
+                            "Backtests {}/{} complete ({} ms elapsed)",
+                            report.completed,
+                            report.total,
+                            report.elapsed_ms
+                        );
+                    }
+
+                    result
+                })
+                .collect()
+        })
+    }
+}
+
+impl Default for JobRunner {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backtest::optimizer::ParameterGrid;
+    use crate::backtest::runner::{MarketSnapshot, OrderIntent};
+    use crate::types::OrderSide;
+    use chrono::Utc;
+
+    struct BuyOnce {
+        size: f64,
+        done: bool,
+    }
+
+    impl Strategy for BuyOnce {
+        fn on_snapshot(
+            &mut self,
+            _snapshot: &MarketSnapshot,
+            _position: f64,
+        ) -> Option<OrderIntent> {
+            if std::mem::replace(&mut self.done, true) {
+                return None;
+            }
+            Some(OrderIntent {
+                side: OrderSide::Buy,
+                quantity: self.size,
+            })
+        }
+    }
+
+    #[test]
+    fn test_run_all_preserves_order_and_tracks_progress() {
+        let snapshots: Vec<MarketSnapshot> = (0..10)
+            .map(|i| MarketSnapshot {
+                timestamp: Utc::now(),
+                bids: vec![(99.0 + i as f64, 100.0)],
+                asks: vec![(101.0 + i as f64, 100.0)],
+            })
+            .collect();
+        let jobs = ParameterGrid::new()
+            .with("size", vec![1.0, 2.0, 3.0, 4.0])
+            .combinations();
+
+        let store = Arc::new(BacktestStore::in_memory());
+        let runner = JobRunner::new(2).with_store(Arc::clone(&store));
+        let progress = runner.progress();
+        let results = runner.run_all(
+            &BacktestConfig::new("BTCUSDT".to_string(), 1_000.0),
+            &jobs,
+            snapshots.as_slice(),
+            |params| BuyOnce {
+                size: params["size"],
+                done: false,
+            },
+        );
+
+        assert_eq!(results.len(), 4);
+        for (result, size) in results.iter().zip([1.0, 2.0, 3.0, 4.0]) {
+            assert_eq!(result.fills[0].quantity, size);
+        }
+
+        let report = progress.report();
+        assert_eq!(report.total, 4);
+        assert_eq!(report.completed, 4);
+        assert!(progress.is_done());
+        assert_eq!(store.list().len(), 4);
+    }
+}
@@ -0,0 +1,242 @@
+use std::collections::VecDeque;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::backtest::runner::{MarketSnapshot, OrderIntent};
+
+/// Delay, in milliseconds, drawn afresh for every snapshot or order
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum LatencyDistribution {
+    Fixed {
+        ms: f64,
+    },
+    Uniform {
+        min_ms: f64,
+        max_ms: f64,
+    },
+    /// Cut off at zero
+    Normal {
+        mean_ms: f64,
+        std_dev_ms: f64,
+    },
+    /// Mostly short with a long tail, like queueing delays
+    Exponential {
+        mean_ms: f64,
+    },
+}
+
+impl LatencyDistribution {
+    pub fn validate(&self) -> Result<(), String> {
+        let non_negative = |value: f64| value.is_finite() && value >= 0.0;
+        let valid = match *self {
+            LatencyDistribution::Fixed { ms } => non_negative(ms),
+            LatencyDistribution::Uniform { min_ms, max_ms } => {
+                non_negative(min_ms) && non_negative(max_ms) && min_ms <= max_ms
+            }
+            LatencyDistribution::Normal {
+                mean_ms,
+                std_dev_ms,
+            } => non_negative(mean_ms) && non_negative(std_dev_ms),
+            LatencyDistribution::Exponential { mean_ms } => non_negative(mean_ms),
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(format!("invalid latency distribution {:?}", self))
+        }
+    }
+
+    pub fn sample(&self, rng: &mut LatencyRng) -> Duration {
+        let ms = match *self {
+            LatencyDistribution::Fixed { ms } => ms,
+            LatencyDistribution::Uniform { min_ms, max_ms } => {
+                min_ms + (max_ms - min_ms) * rng.next_f64()
+            }
+            LatencyDistribution::Normal {
+                mean_ms,
+                std_dev_ms,
+            } => {
+                // Box-Muller; 1 - u keeps the logarithm finite
+                let (u, v) = (1.0 - rng.next_f64(), rng.next_f64());
+                let z = (-2.0 * u.ln()).sqrt() * (2.0 * std::f64::consts::PI * v).cos();
+                mean_ms + std_dev_ms * z
+            }
+            LatencyDistribution::Exponential { mean_ms } => -mean_ms * (1.0 - rng.next_f64()).ln(),
+        };
+        Duration::microseconds((ms.max(0.0) * 1_000.0).round() as i64)
+    }
+}
+
+/// Latencies of the venue a backtest trades on
+///
+/// A run replays one venue's feed, so a comparison across venues is one run
+/// per venue, each with its own profile.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LatencyConfig {
+    /// How late each snapshot reaches the strategy
+    #[serde(default)]
+    pub feed_delay: Option<LatencyDistribution>,
+    /// How long each order takes to reach the venue and be acknowledged;
+    /// it executes against the book as it is then
+    #[serde(default)]
+    pub order_ack: Option<LatencyDistribution>,
+    /// Same seed, same delays, so runs stay reproducible
+    #[serde(default)]
+    pub seed: u64,
+}
+
+impl LatencyConfig {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            feed_delay: None,
+            order_ack: None,
+            seed,
+        }
+    }
+
+    pub fn with_feed_delay(mut self, delay: LatencyDistribution) -> Self {
+        self.feed_delay = Some(delay);
+        self
+    }
+
+    pub fn with_order_ack(mut self, latency: LatencyDistribution) -> Self {
+        self.order_ack = Some(latency);
+        self
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        [self.feed_delay, self.order_ack]
+            .iter()
+            .flatten()
+            .try_for_each(LatencyDistribution::validate)
+    }
+}
+
+/// Small seeded generator (SplitMix64) for latency sampling
+#[derive(Debug, Clone)]
+pub struct LatencyRng {
+    state: u64,
+}
+
+impl LatencyRng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in [0, 1)
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Snapshots on their way to the strategy, and its orders on their way to
+/// the venue
+#[derive(Debug)]
+pub(crate) struct LatencySim {
+    config: LatencyConfig,
+    rng: LatencyRng,
+    /// Snapshots with the time each reaches the strategy
+    feed: VecDeque<(DateTime<Utc>, MarketSnapshot)>,
+    /// Newest snapshot the strategy has received
+    view: Option<MarketSnapshot>,
+    /// Orders with the time each reaches the venue, soonest first
+    in_flight: Vec<(DateTime<Utc>, OrderIntent)>,
+}
+
+impl LatencySim {
+    pub(crate) fn new(config: LatencyConfig) -> Self {
+        Self {
+            rng: LatencyRng::new(config.seed),
+            config,
+            feed: VecDeque::new(),
+            view: None,
+            in_flight: Vec::new(),
+        }
+    }
+
+    /// Feed `snapshot` in and return what the strategy sees as it is taken,
+    /// None until the first snapshot arrives
+    ///
+    /// A snapshot overtaken by a newer one on the way is never seen.
+    pub(crate) fn observe<'a>(
+        &'a mut self,
+        snapshot: &'a MarketSnapshot,
+    ) -> Option<&'a MarketSnapshot> {
+        let Some(delay) = self.config.feed_delay else {
+            return Some(snapshot);
+        };
+        let now = snapshot.timestamp;
+        let arrives = now + delay.sample(&mut self.rng);
+        self.feed.push_back((arrives, snapshot.clone()));
+        let mut pending = VecDeque::with_capacity(self.feed.len());
+        for (arrives, snapshot) in self.feed.drain(..) {
+            if arrives > now {
+                pending.push_back((arrives, snapshot));
+            } else if self
+                .view
+                .as_ref()
+                .is_none_or(|view| view.timestamp <= snapshot.timestamp)
+            {
+                self.view = Some(snapshot);
+            }
+        }
+        self.feed = pending;
+        self.view.as_ref()
+    }
+
+    /// Send an order decided at `now`, returning it straight back when
+    /// orders take no time to reach the venue
+    pub(crate) fn send(&mut self, intent: OrderIntent, now: DateTime<Utc>) -> Option<OrderIntent> {
+        let Some(latency) = self.config.order_ack else {
+            return Some(intent);
+        };
+        let arrives = now + latency.sample(&mut self.rng);
+        let at = self.in_flight.partition_point(|(t, _)| *t <= arrives);
+        self.in_flight.insert(at, (arrives, intent));
+        None
+    }
+
+    /// Orders that reached the venue by `now`, in arrival order
+    pub(crate) fn arrived(&mut self, now: DateTime<Utc>) -> Vec<OrderIntent> {
+        let count = self.in_flight.partition_point(|(t, _)| *t <= now);
+        self.in_flight
+            .drain(..count)
+            .map(|(_, intent)| intent)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_samples_are_seeded_and_in_range() {
+        let uniform = LatencyDistribution::Uniform {
+            min_ms: 5.0,
+            max_ms: 10.0,
+        };
+        let (mut a, mut b) = (LatencyRng::new(7), LatencyRng::new(7));
+        for _ in 0..100 {
+            let sample = uniform.sample(&mut a);
+            assert_eq!(sample, uniform.sample(&mut b));
+            assert!(sample >= Duration::milliseconds(5) && sample <= Duration::milliseconds(10));
+        }
+        let normal = LatencyDistribution::Normal {
+            mean_ms: 1.0,
+            std_dev_ms: 50.0,
+        };
+        assert!((0..100).all(|_| normal.sample(&mut a) >= Duration::zero()));
+        assert!(LatencyDistribution::Fixed { ms: -1.0 }.validate().is_err());
+    }
+}
@@ -0,0 +1,21 @@
+pub mod data;
+pub mod jobs;
+pub mod latency;
+pub mod optimizer;
+pub mod runner;
+pub mod slippage;
+pub mod store;
+
+pub use data::{MappedHistory, MappedRange, SnapshotSource};
+pub use jobs::{JobProgress, JobRunner, ProgressReport};
+pub use latency::{LatencyConfig, LatencyDistribution, LatencyRng};
+pub use optimizer::{
+    Objective, ParameterGrid, ParameterSet, StabilityMetrics, WalkForwardConfig,
+    WalkForwardOptimizer, WalkForwardReport, WalkForwardSplit, WindowResult,
+};
+pub use runner::{
+    compare_slippage_models, Backtest, BacktestConfig, BacktestMetrics, BacktestResult, Fill,
+    MarketSnapshot, ModelComparison, OrderIntent, Strategy,
+};
+pub use slippage::{BookWalk, FixedBps, SlippageConfig, SlippageModel, SquareRootImpact};
+pub use store::{BacktestStore, FillChange, MetricDelta, RunDiff, RunId, RunSummary, StoredRun};
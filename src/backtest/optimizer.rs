@@ -0,0 +1,364 @@
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+
+use crate::backtest::jobs::JobRunner;
+use crate::backtest::runner::{
+    Backtest, BacktestConfig, BacktestMetrics, MarketSnapshot, Strategy,
+};
+
+/// Named strategy parameters for one backtest run
+pub type ParameterSet = BTreeMap<String, f64>;
+
+/// Cartesian grid of parameter values to sweep
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ParameterGrid {
+    params: Vec<(String, Vec<f64>)>,
+}
+
+impl ParameterGrid {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a parameter axis to the grid
+    pub fn with(mut self, name: &str, values: Vec<f64>) -> Self {
+        self.params.push((name.to_string(), values));
+        self
+    }
+
+    /// Every combination of the grid's parameter values
+    pub fn combinations(&self) -> Vec<ParameterSet> {
+        let mut sets = vec![ParameterSet::new()];
+
+        for (name, values) in &self.params {
+            sets = sets
+                .into_iter()
+                .flat_map(|set| {
+                    values.iter().map(move |&value| {
+                        let mut next = set.clone();
+                        next.insert(name.clone(), value);
+                        next
+                    })
+                })
+                .collect();
+        }
+
+        sets
+    }
+}
+
+/// Score used to rank parameter sets on the training window
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Objective {
+    #[default]
+    TotalReturn,
+    /// Total return divided by max drawdown (Calmar-style)
+    ReturnOverDrawdown,
+}
+
+impl Objective {
+    pub fn score(&self, metrics: &BacktestMetrics) -> f64 {
+        match self {
+            Objective::TotalReturn => metrics.total_return,
+            Objective::ReturnOverDrawdown => {
+                if metrics.max_drawdown > 0.0 {
+                    metrics.total_return / metrics.max_drawdown
+                } else {
+                    metrics.total_return
+                }
+            }
+        }
+    }
+}
+
+/// Rolling train/test window sizes
+#[derive(Debug, Clone, Copy)]
+pub struct WalkForwardConfig {
+    pub train_window: Duration,
+    pub test_window: Duration,
+    /// How far each split advances; usually equal to `test_window`
+    pub step: Duration,
+    pub objective: Objective,
+}
+
+/// Snapshot index ranges of a single train/test split
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WalkForwardSplit {
+    pub train: Range<usize>,
+    pub test: Range<usize>,
+}
+
+impl WalkForwardConfig {
+    /// Split time-ordered snapshots into rolling train/test windows
+    pub fn splits(&self, snapshots: &[MarketSnapshot]) -> Vec<WalkForwardSplit> {
+        let mut splits = Vec::new();
+        let (Some(first), Some(last)) = (snapshots.first(), snapshots.last()) else {
+            return splits;
+        };
+        if self.step <= Duration::zero() {
+            return splits;
+        }
+
+        let index_at = |time| snapshots.partition_point(|s| s.timestamp < time);
+
+        let mut train_start = first.timestamp;
+        loop {
+            let test_start = train_start + self.train_window;
+            let test_end = test_start + self.test_window;
+            if test_end > last.timestamp + Duration::nanoseconds(1) {
+                break;
+            }
+
+            let train = index_at(train_start)..index_at(test_start);
+            let test = index_at(test_start)..index_at(test_end);
+            if !train.is_empty() && !test.is_empty() {
+                splits.push(WalkForwardSplit { train, test });
+            }
+
+            train_start += self.step;
+        }
+
+        splits
+    }
+}
+
+/// Best in-sample parameters of one split and how they held up out of sample
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WindowResult {
+    pub params: ParameterSet,
+    pub in_sample_score: f64,
+    pub out_of_sample_score: f64,
+    pub out_of_sample: BacktestMetrics,
+}
+
+/// Out-of-sample stability summary across all walk-forward windows
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StabilityMetrics {
+    pub mean_out_of_sample_score: f64,
+    pub std_out_of_sample_score: f64,
+    /// Mean out-of-sample score over mean in-sample score
+    pub walk_forward_efficiency: f64,
+    /// Fraction of windows with a positive out-of-sample score
+    pub positive_window_ratio: f64,
+    /// Number of distinct parameter sets selected across windows
+    pub distinct_parameter_sets: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalkForwardReport {
+    pub windows: Vec<WindowResult>,
+    pub stability: StabilityMetrics,
+}
+
+/// Parameter sweep and walk-forward optimizer
+pub struct WalkForwardOptimizer {
+    base: BacktestConfig,
+    walk_forward: WalkForwardConfig,
+    runner: JobRunner,
+}
+
+impl WalkForwardOptimizer {
+    pub fn new(base: BacktestConfig, walk_forward: WalkForwardConfig) -> Self {
+        Self {
+            base,
+            walk_forward,
+            runner: JobRunner::default(),
+        }
+    }
+
+    /// Use a specific job runner, e.g. to cap threads or observe progress
+    pub fn with_runner(mut self, runner: JobRunner) -> Self {
+        self.runner = runner;
+        self
+    }
+
+    /// Run the full grid on every training window, then evaluate the winner on
+    /// the following test window
+    pub fn run<S, F>(
+        &self,
+        grid: &ParameterGrid,
+        snapshots: &[MarketSnapshot],
+        make_strategy: F,
+    ) -> WalkForwardReport
+    where
+        S: Strategy,
+        F: Fn(&ParameterSet) -> S + Sync,
+    {
+        let combinations = grid.combinations();
+        let objective = self.walk_forward.objective;
+        let mut windows = Vec::new();
+
+        for split in self.walk_forward.splits(snapshots) {
+            let train = &snapshots[split.train.clone()];
+            let results = self
+                .runner
+                .run_all(&self.base, &combinations, train, &make_strategy);
+
+            let best = results
+                .iter()
+                .enumerate()
+                .map(|(i, result)| (i, objective.score(&result.metrics)))
+                .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+
+            let Some((best_index, in_sample_score)) = best else {
+                continue;
+            };
+            let params = combinations[best_index].clone();
+
+            let mut strategy = make_strategy(&params);
+            let out_of_sample = Backtest::new(self.base.clone())
+                .run(&mut strategy, &snapshots[split.test.clone()])
+                .metrics;
+
+            windows.push(WindowResult {
+                out_of_sample_score: objective.score(&out_of_sample),
+                params,
+                in_sample_score,
+                out_of_sample,
+            });
+        }
+
+        let stability = stability(&windows);
+        WalkForwardReport { windows, stability }
+    }
+}
+
+fn stability(windows: &[WindowResult]) -> StabilityMetrics {
+    if windows.is_empty() {
+        return StabilityMetrics::default();
+    }
+
+    let n = windows.len() as f64;
+    let mean_oos = windows.iter().map(|w| w.out_of_sample_score).sum::<f64>() / n;
+    let mean_is = windows.iter().map(|w| w.in_sample_score).sum::<f64>() / n;
+    let variance = windows
+        .iter()
+        .map(|w| (w.out_of_sample_score - mean_oos).powi(2))
+        .sum::<f64>()
+        / n;
+
+    let mut distinct: Vec<&ParameterSet> = Vec::new();
+    for window in windows {
+        if !distinct.contains(&&window.params) {
+            distinct.push(&window.params);
+        }
+    }
+
+    StabilityMetrics {
+        mean_out_of_sample_score: mean_oos,
+        std_out_of_sample_score: variance.sqrt(),
+        walk_forward_efficiency: if mean_is != 0.0 {
+            mean_oos / mean_is
+        } else {
+            0.0
+        },
+        positive_window_ratio: windows
+            .iter()
+            .filter(|w| w.out_of_sample_score > 0.0)
+            .count() as f64
+            / n,
+        distinct_parameter_sets: distinct.len(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backtest::runner::OrderIntent;
+    use crate::types::OrderSide;
+    use chrono::{TimeZone, Utc};
+
+    /// Buys `size` on the first snapshot and holds
+    struct BuyAndHold {
+        size: f64,
+        bought: bool,
+    }
+
+    impl Strategy for BuyAndHold {
+        fn on_snapshot(
+            &mut self,
+            _snapshot: &MarketSnapshot,
+            _position: f64,
+        ) -> Option<OrderIntent> {
+            if self.bought {
+                return None;
+            }
+            self.bought = true;
+            Some(OrderIntent {
+                side: OrderSide::Buy,
+                quantity: self.size,
+            })
+        }
+    }
+
+    fn rising_market(minutes: i64) -> Vec<MarketSnapshot> {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        (0..minutes)
+            .map(|i| {
+                let mid = 100.0 + i as f64;
+                MarketSnapshot {
+                    timestamp: start + Duration::minutes(i),
+                    bids: vec![(mid - 0.5, 10.0)],
+                    asks: vec![(mid + 0.5, 10.0)],
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_grid_combinations() {
+        let grid = ParameterGrid::new()
+            .with("fast", vec![1.0, 2.0])
+            .with("slow", vec![10.0, 20.0, 30.0]);
+
+        let sets = grid.combinations();
+        assert_eq!(sets.len(), 6);
+        assert_eq!(sets[0]["fast"], 1.0);
+        assert_eq!(sets[0]["slow"], 10.0);
+    }
+
+    #[test]
+    fn test_rolling_splits() {
+        let config = WalkForwardConfig {
+            train_window: Duration::minutes(10),
+            test_window: Duration::minutes(5),
+            step: Duration::minutes(5),
+            objective: Objective::TotalReturn,
+        };
+
+        let splits = config.splits(&rising_market(30));
+        assert_eq!(splits.len(), 3);
+        assert_eq!(splits[0].train, 0..10);
+        assert_eq!(splits[0].test, 10..15);
+        assert_eq!(splits[1].train, 5..15);
+    }
+
+    #[test]
+    fn test_walk_forward_prefers_larger_size_in_uptrend() {
+        let walk_forward = WalkForwardConfig {
+            train_window: Duration::minutes(10),
+            test_window: Duration::minutes(5),
+            step: Duration::minutes(5),
+            objective: Objective::TotalReturn,
+        };
+        let optimizer = WalkForwardOptimizer::new(
+            BacktestConfig::new("BTCUSDT".to_string(), 10_000.0),
+            walk_forward,
+        );
+        let grid = ParameterGrid::new().with("size", vec![1.0, 5.0]);
+
+        let report = optimizer.run(&grid, &rising_market(30), |params| BuyAndHold {
+            size: params["size"],
+            bought: false,
+        });
+
+        assert_eq!(report.windows.len(), 3);
+        assert!(report.windows.iter().all(|w| w.params["size"] == 5.0));
+        assert_eq!(report.stability.distinct_parameter_sets, 1);
+        assert_eq!(report.stability.positive_window_ratio, 1.0);
+    }
+}
@@ -0,0 +1,479 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::account::{AccountId, FeeSchedule, FeeTracker};
+use crate::backtest::data::SnapshotSource;
+use crate::backtest::latency::{LatencyConfig, LatencySim};
+use crate::backtest::slippage::SlippageConfig;
+use crate::orderbook::{Liquidity, OrderBook};
+use crate::types::{Notional, OrderSide, Price, Qty, Symbol};
+
+/// Point-in-time view of the book replayed by a backtest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketSnapshot {
+    pub timestamp: DateTime<Utc>,
+    /// (price, quantity), best bid first
+    pub bids: Vec<(f64, f64)>,
+    /// (price, quantity), best ask first
+    pub asks: Vec<(f64, f64)>,
+}
+
+impl MarketSnapshot {
+    /// Capture the top `levels` of a live order book
+    pub fn from_book(book: &OrderBook, levels: usize, timestamp: DateTime<Utc>) -> Self {
+        let (bids, asks) = book.get_depth(levels);
+        Self {
+            timestamp,
+            bids,
+            asks,
+        }
+    }
+
+    pub fn best_bid(&self) -> Option<f64> {
+        self.bids.first().map(|&(price, _)| price)
+    }
+
+    pub fn best_ask(&self) -> Option<f64> {
+        self.asks.first().map(|&(price, _)| price)
+    }
+
+    pub fn mid_price(&self) -> Option<f64> {
+        match (self.best_bid(), self.best_ask()) {
+            (Some(bid), Some(ask)) => Some((bid + ask) / 2.0),
+            _ => None,
+        }
+    }
+
+    /// Average of the volume-weighted bid and ask over the top N levels
+    pub fn weighted_mid_price(&self, depth_levels: usize) -> Option<f64> {
+        let vwap = |levels: &[(f64, f64)]| {
+            let (notional, quantity) = levels
+                .iter()
+                .take(depth_levels)
+                .fold((0.0, 0.0), |(n, q), &(price, qty)| {
+                    (n + price * qty, q + qty)
+                });
+            (quantity > 0.0).then(|| notional / quantity)
+        };
+        Some((vwap(&self.bids)? + vwap(&self.asks)?) / 2.0)
+    }
+
+    /// Touch prices weighted by the opposite side's size
+    pub fn micro_price(&self) -> Option<f64> {
+        let (&(bid, bid_qty), &(ask, ask_qty)) = (self.bids.first()?, self.asks.first()?);
+        let total = bid_qty + ask_qty;
+        if total <= 0.0 {
+            return self.mid_price();
+        }
+        Some((bid * ask_qty + ask * bid_qty) / total)
+    }
+
+    /// Price an aggressive order on `side` would trade at first
+    pub fn touch_price(&self, side: OrderSide) -> Option<f64> {
+        match side {
+            OrderSide::Buy => self.best_ask(),
+            OrderSide::Sell => self.best_bid(),
+        }
+    }
+}
+
+/// Order a strategy wants executed at the current snapshot
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct OrderIntent {
+    pub side: OrderSide,
+    pub quantity: f64,
+}
+
+/// Strategy driven by a backtest
+pub trait Strategy {
+    /// Called once per snapshot with the current signed position
+    fn on_snapshot(&mut self, snapshot: &MarketSnapshot, position: f64) -> Option<OrderIntent>;
+}
+
+/// Configuration for a single backtest run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestConfig {
+    pub symbol: Symbol,
+    pub initial_cash: f64,
+    /// Fee charged on fill notional, in basis points
+    pub fee_bps: f64,
+    /// Volume tiers charged instead of `fee_bps`, progressing with the
+    /// run's own 30-day traded volume
+    #[serde(default)]
+    pub fee_schedule: Option<FeeSchedule>,
+    pub slippage: SlippageConfig,
+    /// Feed delays and order latencies to trade under; without them the
+    /// strategy sees every snapshot and fills on it instantly
+    #[serde(default)]
+    pub latency: Option<LatencyConfig>,
+}
+
+impl BacktestConfig {
+    pub fn new(symbol: impl Into<Symbol>, initial_cash: f64) -> Self {
+        Self {
+            symbol: symbol.into(),
+            initial_cash,
+            fee_bps: 0.0,
+            fee_schedule: None,
+            slippage: SlippageConfig::default(),
+            latency: None,
+        }
+    }
+
+    pub fn with_fee_schedule(mut self, schedule: FeeSchedule) -> Self {
+        self.fee_schedule = Some(schedule);
+        self
+    }
+
+    pub fn with_latency(mut self, latency: LatencyConfig) -> Self {
+        self.latency = Some(latency);
+        self
+    }
+}
+
+/// Simulated execution produced by a backtest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Fill {
+    pub timestamp: DateTime<Utc>,
+    pub side: OrderSide,
+    pub quantity: Qty,
+    pub price: Price,
+    /// Cost versus mid price, always >= 0 for adverse slippage
+    pub slippage_cost: Notional,
+    pub fee: Notional,
+}
+
+/// Summary statistics of a backtest run
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BacktestMetrics {
+    pub final_equity: f64,
+    pub total_return: f64,
+    pub max_drawdown: f64,
+    pub trade_count: usize,
+    pub total_fees: f64,
+    pub total_slippage_cost: f64,
+}
+
+/// Full output of a backtest run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestResult {
+    pub config: BacktestConfig,
+    pub metrics: BacktestMetrics,
+    pub equity_curve: Vec<(DateTime<Utc>, f64)>,
+    pub fills: Vec<Fill>,
+}
+
+/// Replays snapshots through a strategy and simulates its fills
+pub struct Backtest {
+    config: BacktestConfig,
+}
+
+impl Backtest {
+    pub fn new(config: BacktestConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn run<S: Strategy>(
+        &self,
+        strategy: &mut S,
+        snapshots: &[MarketSnapshot],
+    ) -> BacktestResult {
+        self.run_source(strategy, snapshots)
+    }
+
+    /// Run against any snapshot source, e.g. a memory-mapped history file
+    pub fn run_source<S, D>(&self, strategy: &mut S, source: &D) -> BacktestResult
+    where
+        S: Strategy,
+        D: SnapshotSource + ?Sized,
+    {
+        let model = self.config.slippage.build();
+        let schedule = self
+            .config
+            .fee_schedule
+            .clone()
+            .unwrap_or_else(|| FeeSchedule::flat(self.config.fee_bps));
+        let mut fee_tracker = FeeTracker::new(schedule);
+        let account = AccountId("backtest".to_string());
+
+        let mut cash = self.config.initial_cash;
+        let mut position = 0.0;
+        let mut fills = Vec::new();
+        let mut equity_curve = Vec::with_capacity(source.len());
+
+        let mut latency = self.config.latency.clone().map(LatencySim::new);
+
+        source.replay(&mut |snapshot| {
+            let now = snapshot.timestamp;
+            // Orders sent earlier reach the venue first; any still on their
+            // way when the data ends never do
+            let mut orders = latency
+                .as_mut()
+                .map_or_else(Vec::new, |sim| sim.arrived(now));
+            let view = match latency.as_mut() {
+                Some(sim) => sim.observe(snapshot),
+                None => Some(snapshot),
+            };
+            if let Some(intent) = view.and_then(|view| strategy.on_snapshot(view, position)) {
+                orders.extend(match latency.as_mut() {
+                    Some(sim) => sim.send(intent, now),
+                    None => Some(intent),
+                });
+            }
+
+            for intent in orders {
+                let executed = model.fill_price(intent.side, intent.quantity, snapshot);
+
+                if let (Some(price), Some(mid)) = (executed, snapshot.mid_price()) {
+                    let notional = price * intent.quantity;
+                    // Strategy intents cross the spread, so every fill is a taker's
+                    let fee = fee_tracker.charge(
+                        &account,
+                        Liquidity::Taker,
+                        notional,
+                        snapshot.timestamp,
+                    );
+
+                    match intent.side {
+                        OrderSide::Buy => {
+                            cash -= notional + fee;
+                            position += intent.quantity;
+                        }
+                        OrderSide::Sell => {
+                            cash += notional - fee;
+                            position -= intent.quantity;
+                        }
+                    }
+
+                    fills.push(Fill {
+                        timestamp: snapshot.timestamp,
+                        side: intent.side,
+                        quantity: Qty::new(intent.quantity),
+                        price: Price::new(price),
+                        slippage_cost: Notional::new((price - mid).abs() * intent.quantity),
+                        fee: Notional::new(fee),
+                    });
+                }
+            }
+
+            // Mark to mid; fall back to zero exposure value on a one-sided book
+            let mark = snapshot.mid_price().unwrap_or(0.0);
+            equity_curve.push((snapshot.timestamp, cash + position * mark));
+        });
+
+        let metrics = self.compute_metrics(&equity_curve, &fills);
+
+        BacktestResult {
+            config: self.config.clone(),
+            metrics,
+            equity_curve,
+            fills,
+        }
+    }
+
+    fn compute_metrics(
+        &self,
+        equity_curve: &[(DateTime<Utc>, f64)],
+        fills: &[Fill],
+    ) -> BacktestMetrics {
+        let initial = self.config.initial_cash;
+        let final_equity = equity_curve.last().map(|&(_, e)| e).unwrap_or(initial);
+
+        let mut peak = initial;
+        let mut max_drawdown: f64 = 0.0;
+        for &(_, equity) in equity_curve {
+            peak = peak.max(equity);
+            if peak > 0.0 {
+                max_drawdown = max_drawdown.max((peak - equity) / peak);
+            }
+        }
+
+        BacktestMetrics {
+            final_equity,
+            total_return: if initial != 0.0 {
+                (final_equity - initial) / initial
+            } else {
+                0.0
+            },
+            max_drawdown,
+            trade_count: fills.len(),
+            total_fees: fills.iter().map(|f| f.fee).sum::<Notional>().value(),
+            total_slippage_cost: fills
+                .iter()
+                .map(|f| f.slippage_cost)
+                .sum::<Notional>()
+                .value(),
+        }
+    }
+}
+
+/// One row of a slippage model comparison
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelComparison {
+    pub model: String,
+    pub metrics: BacktestMetrics,
+}
+
+/// Run the same strategy once per slippage model and report the metrics side by side
+///
+/// `make_strategy` is called per run so every model starts from fresh strategy state.
+pub fn compare_slippage_models<S, F>(
+    config: &BacktestConfig,
+    models: &[SlippageConfig],
+    snapshots: &[MarketSnapshot],
+    mut make_strategy: F,
+) -> Vec<ModelComparison>
+where
+    S: Strategy,
+    F: FnMut() -> S,
+{
+    models
+        .iter()
+        .map(|slippage| {
+            let mut run_config = config.clone();
+            run_config.slippage = *slippage;
+
+            let mut strategy = make_strategy();
+            let result = Backtest::new(run_config).run(&mut strategy, snapshots);
+
+            ModelComparison {
+                model: slippage.build().name().to_string(),
+                metrics: result.metrics,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backtest::latency::LatencyDistribution;
+    use crate::backtest::slippage::{BookWalk, FixedBps};
+    use crate::testkit;
+    use crate::types::Order;
+
+    /// Buys once on the first snapshot, sells on the last
+    struct RoundTrip {
+        ticks: usize,
+        seen: usize,
+    }
+
+    impl Strategy for RoundTrip {
+        fn on_snapshot(
+            &mut self,
+            _snapshot: &MarketSnapshot,
+            position: f64,
+        ) -> Option<OrderIntent> {
+            self.seen += 1;
+            if self.seen == 1 {
+                Some(OrderIntent {
+                    side: OrderSide::Buy,
+                    quantity: 2.0,
+                })
+            } else if self.seen == self.ticks && position > 0.0 {
+                Some(OrderIntent {
+                    side: OrderSide::Sell,
+                    quantity: position,
+                })
+            } else {
+                None
+            }
+        }
+    }
+
+    fn snapshots() -> Vec<MarketSnapshot> {
+        (0..5)
+            .map(|i| {
+                let mid = 100.0 + i as f64;
+                MarketSnapshot {
+                    timestamp: Utc::now(),
+                    bids: vec![(mid - 0.5, 1.0), (mid - 1.5, 5.0)],
+                    asks: vec![(mid + 0.5, 1.0), (mid + 1.5, 5.0)],
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_snapshot_from_book() {
+        let mut book = OrderBook::new("BTCUSDT".to_string());
+        book.add_order(Order::new_limit(
+            "BTCUSDT".to_string(),
+            OrderSide::Buy,
+            99.0,
+            1.0,
+        ));
+        book.add_order(Order::new_limit(
+            "BTCUSDT".to_string(),
+            OrderSide::Sell,
+            101.0,
+            1.0,
+        ));
+
+        let snapshot = MarketSnapshot::from_book(&book, 5, Utc::now());
+        assert_eq!(snapshot.mid_price(), Some(100.0));
+        assert_eq!(snapshot.weighted_mid_price(5), book.weighted_mid_price(5));
+        assert_eq!(snapshot.micro_price(), book.micro_price());
+        assert_eq!(snapshot.touch_price(OrderSide::Buy), Some(101.0));
+    }
+
+    #[test]
+    fn test_round_trip_backtest() {
+        let config = BacktestConfig::new("BTCUSDT".to_string(), 1_000.0);
+        let mut strategy = RoundTrip { ticks: 5, seen: 0 };
+        let result = Backtest::new(config).run(&mut strategy, &snapshots());
+
+        assert_eq!(result.fills.len(), 2);
+        assert_eq!(result.equity_curve.len(), 5);
+        // Bought 2 @ 100.5, sold 2 @ 103.5
+        assert!((result.metrics.final_equity - 1_006.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_compare_slippage_models() {
+        let config = BacktestConfig::new("BTCUSDT".to_string(), 1_000.0);
+        let models = [
+            SlippageConfig::FixedBps(FixedBps { bps: 0.0 }),
+            SlippageConfig::BookWalk(BookWalk {
+                exhausted_penalty_bps: 0.0,
+            }),
+        ];
+
+        let report = compare_slippage_models(&config, &models, &snapshots(), || RoundTrip {
+            ticks: 5,
+            seen: 0,
+        });
+
+        assert_eq!(report.len(), 2);
+        assert_eq!(report[0].model, "fixed_bps");
+        assert_eq!(report[1].model, "book_walk");
+        // Walking past the first level costs more than filling at the touch
+        assert!(report[1].metrics.final_equity < report[0].metrics.final_equity);
+    }
+
+    #[test]
+    fn test_latency_delays_decisions_and_fills() {
+        let market = (0..5).fold(testkit::market(), |market, i| {
+            let bid = 100.0 + 2.0 * i as f64;
+            market.quote(bid, bid + 1.0, 10.0)
+        });
+        let second = LatencyDistribution::Fixed { ms: 1_000.0 };
+        let run = |latency: LatencyConfig| {
+            let config = BacktestConfig::new(testkit::SYMBOL, 1_000.0).with_latency(latency);
+            let mut strategy = RoundTrip { ticks: 5, seen: 0 };
+            Backtest::new(config).run(&mut strategy, market.snapshots())
+        };
+
+        // The buy reaches the venue a snapshot later; the closing sell is
+        // still on its way when the data ends
+        let result = run(LatencyConfig::new(1).with_order_ack(second));
+        assert_eq!(result.fills.len(), 1);
+        assert_eq!(result.fills[0].price, 103.0);
+
+        // A late feed holds the first decision back a snapshot, so the
+        // strategy sees only four and never reaches its closing tick
+        let result = run(LatencyConfig::new(1).with_feed_delay(second));
+        assert_eq!(result.fills.len(), 1);
+        assert_eq!(result.fills[0].timestamp, market.snapshots()[1].timestamp);
+    }
+}
@@ -0,0 +1,204 @@
+use serde::{Deserialize, Serialize};
+
+use crate::backtest::runner::MarketSnapshot;
+use crate::types::OrderSide;
+
+/// Estimates the execution price of a simulated fill
+pub trait SlippageModel: Send + Sync {
+    /// Short name used in comparison reports
+    fn name(&self) -> &'static str;
+
+    /// Average execution price for `quantity` on `side`, or None if the
+    /// snapshot has no liquidity on the opposite side
+    fn fill_price(&self, side: OrderSide, quantity: f64, snapshot: &MarketSnapshot) -> Option<f64>;
+}
+
+/// Touch price plus a constant cost in basis points
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FixedBps {
+    pub bps: f64,
+}
+
+impl SlippageModel for FixedBps {
+    fn name(&self) -> &'static str {
+        "fixed_bps"
+    }
+
+    fn fill_price(
+        &self,
+        side: OrderSide,
+        _quantity: f64,
+        snapshot: &MarketSnapshot,
+    ) -> Option<f64> {
+        let touch = snapshot.touch_price(side)?;
+        Some(apply_bps(side, touch, self.bps))
+    }
+}
+
+/// Square-root market impact: `impact_bps = coefficient_bps * sqrt(quantity / reference_volume)`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SquareRootImpact {
+    /// Impact in basis points when trading exactly `reference_volume`
+    pub coefficient_bps: f64,
+    /// Typical traded volume the impact is scaled against (e.g. daily volume)
+    pub reference_volume: f64,
+}
+
+impl SlippageModel for SquareRootImpact {
+    fn name(&self) -> &'static str {
+        "sqrt_impact"
+    }
+
+    fn fill_price(&self, side: OrderSide, quantity: f64, snapshot: &MarketSnapshot) -> Option<f64> {
+        let touch = snapshot.touch_price(side)?;
+        if self.reference_volume <= 0.0 {
+            return Some(touch);
+        }
+
+        let impact_bps = self.coefficient_bps * (quantity / self.reference_volume).sqrt();
+        Some(apply_bps(side, touch, impact_bps))
+    }
+}
+
+/// Walks the snapshot's depth level by level, consuming visible liquidity
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BookWalk {
+    /// Extra cost applied to any quantity beyond the visible depth
+    pub exhausted_penalty_bps: f64,
+}
+
+impl SlippageModel for BookWalk {
+    fn name(&self) -> &'static str {
+        "book_walk"
+    }
+
+    fn fill_price(&self, side: OrderSide, quantity: f64, snapshot: &MarketSnapshot) -> Option<f64> {
+        let levels = match side {
+            OrderSide::Buy => &snapshot.asks,
+            OrderSide::Sell => &snapshot.bids,
+        };
+        let (last_price, _) = *levels.last()?;
+
+        let mut remaining = quantity;
+        let mut notional = 0.0;
+
+        for &(price, available) in levels {
+            if remaining <= 0.0 {
+                break;
+            }
+            let take = remaining.min(available);
+            notional += take * price;
+            remaining -= take;
+        }
+
+        // Anything left over trades beyond the visible book
+        if remaining > 0.0 {
+            notional += remaining * apply_bps(side, last_price, self.exhausted_penalty_bps);
+        }
+
+        if quantity > 0.0 {
+            Some(notional / quantity)
+        } else {
+            Some(levels[0].0)
+        }
+    }
+}
+
+/// Serializable slippage model selection for a backtest run
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "model", rename_all = "snake_case")]
+pub enum SlippageConfig {
+    FixedBps(FixedBps),
+    SqrtImpact(SquareRootImpact),
+    BookWalk(BookWalk),
+}
+
+impl SlippageConfig {
+    pub fn build(&self) -> Box<dyn SlippageModel> {
+        match *self {
+            SlippageConfig::FixedBps(model) => Box::new(model),
+            SlippageConfig::SqrtImpact(model) => Box::new(model),
+            SlippageConfig::BookWalk(model) => Box::new(model),
+        }
+    }
+}
+
+impl Default for SlippageConfig {
+    fn default() -> Self {
+        SlippageConfig::FixedBps(FixedBps { bps: 0.0 })
+    }
+}
+
+/// Move a price against the trader by `bps` basis points
+fn apply_bps(side: OrderSide, price: f64, bps: f64) -> f64 {
+    match side {
+        OrderSide::Buy => price * (1.0 + bps / 10_000.0),
+        OrderSide::Sell => price * (1.0 - bps / 10_000.0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn snapshot() -> MarketSnapshot {
+        MarketSnapshot {
+            timestamp: Utc::now(),
+            bids: vec![(99.0, 1.0), (98.0, 2.0)],
+            asks: vec![(101.0, 1.0), (102.0, 2.0)],
+        }
+    }
+
+    #[test]
+    fn test_fixed_bps() {
+        let model = FixedBps { bps: 100.0 };
+        assert_eq!(
+            model.fill_price(OrderSide::Buy, 1.0, &snapshot()),
+            Some(101.0 * 1.01)
+        );
+        assert_eq!(
+            model.fill_price(OrderSide::Sell, 1.0, &snapshot()),
+            Some(99.0 * 0.99)
+        );
+    }
+
+    #[test]
+    fn test_sqrt_impact_grows_with_size() {
+        let model = SquareRootImpact {
+            coefficient_bps: 50.0,
+            reference_volume: 100.0,
+        };
+        let small = model.fill_price(OrderSide::Buy, 1.0, &snapshot()).unwrap();
+        let large = model.fill_price(OrderSide::Buy, 25.0, &snapshot()).unwrap();
+
+        assert!(small > 101.0);
+        assert!(large > small);
+    }
+
+    #[test]
+    fn test_book_walk() {
+        let model = BookWalk {
+            exhausted_penalty_bps: 0.0,
+        };
+
+        // 1.0 @ 101 + 1.0 @ 102
+        let price = model.fill_price(OrderSide::Buy, 2.0, &snapshot()).unwrap();
+        assert!((price - 101.5).abs() < 1e-9);
+
+        // Beyond visible depth the remainder trades at the last level
+        let price = model.fill_price(OrderSide::Sell, 4.0, &snapshot()).unwrap();
+        assert!((price - (99.0 + 98.0 * 3.0) / 4.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_empty_book_has_no_price() {
+        let empty = MarketSnapshot {
+            timestamp: Utc::now(),
+            bids: Vec::new(),
+            asks: Vec::new(),
+        };
+        let model = SlippageConfig::default().build();
+        assert_eq!(model.fill_price(OrderSide::Buy, 1.0, &empty), None);
+    }
+}
@@ -0,0 +1,286 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::backtest::runner::{BacktestConfig, BacktestMetrics, BacktestResult, Fill};
+
+/// Identifier of a stored backtest run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct RunId(pub u64);
+
+/// A persisted backtest run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredRun {
+    pub id: RunId,
+    pub created_at: DateTime<Utc>,
+    pub result: BacktestResult,
+}
+
+/// Lightweight listing entry without the equity curve and fills
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunSummary {
+    pub id: RunId,
+    pub created_at: DateTime<Utc>,
+    pub config: BacktestConfig,
+    pub metrics: BacktestMetrics,
+}
+
+/// Stores every backtest run, optionally mirrored to a directory of JSON files
+pub struct BacktestStore {
+    runs: RwLock<BTreeMap<RunId, StoredRun>>,
+    dir: Option<PathBuf>,
+}
+
+impl BacktestStore {
+    /// In-memory store, lost on restart
+    pub fn in_memory() -> Self {
+        Self {
+            runs: RwLock::new(BTreeMap::new()),
+            dir: None,
+        }
+    }
+
+    /// Store persisted under `dir`, loading any runs already there
+    pub fn open(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+
+        let mut runs = BTreeMap::new();
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+
+            match serde_json::from_slice::<StoredRun>(&fs::read(&path)?) {
+                Ok(run) => {
+                    runs.insert(run.id, run);
+                }
+                Err(e) => tracing::warn!("Skipping unreadable backtest run {:?}: {}", path, e),
+            }
+        }
+
+        Ok(Self {
+            runs: RwLock::new(runs),
+            dir: Some(dir),
+        })
+    }
+
+    /// Persist a finished run and return its id
+    pub fn save(&self, result: BacktestResult) -> io::Result<RunId> {
+        let mut runs = self.runs.write().unwrap();
+        let id = RunId(runs.keys().next_back().map_or(1, |last| last.0 + 1));
+        let run = StoredRun {
+            id,
+            created_at: Utc::now(),
+            result,
+        };
+
+        if let Some(dir) = &self.dir {
+            let json = serde_json::to_vec_pretty(&run).map_err(io::Error::other)?;
+            fs::write(dir.join(format!("{}.json", id.0)), json)?;
+        }
+
+        runs.insert(id, run);
+        Ok(id)
+    }
+
+    pub fn list(&self) -> Vec<RunSummary> {
+        self.runs
+            .read()
+            .unwrap()
+            .values()
+            .map(|run| RunSummary {
+                id: run.id,
+                created_at: run.created_at,
+                config: run.result.config.clone(),
+                metrics: run.result.metrics.clone(),
+            })
+            .collect()
+    }
+
+    pub fn get(&self, id: RunId) -> Option<StoredRun> {
+        self.runs.read().unwrap().get(&id).cloned()
+    }
+
+    /// Compare two runs' metrics and fills
+    pub fn diff(&self, a: RunId, b: RunId) -> Option<RunDiff> {
+        let runs = self.runs.read().unwrap();
+        Some(RunDiff::between(runs.get(&a)?, runs.get(&b)?))
+    }
+}
+
+/// Difference of a single metric between two runs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricDelta {
+    pub a: f64,
+    pub b: f64,
+    pub delta: f64,
+}
+
+impl MetricDelta {
+    fn new(a: f64, b: f64) -> Self {
+        Self { a, b, delta: b - a }
+    }
+}
+
+/// Fill present in both runs at the same time and side, but executed differently
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FillChange {
+    pub a: Fill,
+    pub b: Fill,
+}
+
+/// Metric and trade-set comparison of run `b` against run `a`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunDiff {
+    pub a: RunId,
+    pub b: RunId,
+    pub metrics: BTreeMap<String, MetricDelta>,
+    pub only_in_a: Vec<Fill>,
+    pub only_in_b: Vec<Fill>,
+    pub changed: Vec<FillChange>,
+}
+
+impl RunDiff {
+    fn between(a: &StoredRun, b: &StoredRun) -> Self {
+        let (ma, mb) = (&a.result.metrics, &b.result.metrics);
+        let metrics = [
+            ("final_equity", ma.final_equity, mb.final_equity),
+            ("total_return", ma.total_return, mb.total_return),
+            ("max_drawdown", ma.max_drawdown, mb.max_drawdown),
+            ("trade_count", ma.trade_count as f64, mb.trade_count as f64),
+            ("total_fees", ma.total_fees, mb.total_fees),
+            (
+                "total_slippage_cost",
+                ma.total_slippage_cost,
+                mb.total_slippage_cost,
+            ),
+        ]
+        .into_iter()
+        .map(|(name, a, b)| (name.to_string(), MetricDelta::new(a, b)))
+        .collect();
+
+        // Fills are matched on (timestamp, side)
+        let mut unmatched_b: Vec<&Fill> = b.result.fills.iter().collect();
+        let mut only_in_a = Vec::new();
+        let mut changed = Vec::new();
+
+        for fill in &a.result.fills {
+            let found = unmatched_b
+                .iter()
+                .position(|other| other.timestamp == fill.timestamp && other.side == fill.side);
+
+            match found {
+                Some(index) => {
+                    let other = unmatched_b.remove(index);
+                    if other.price != fill.price || other.quantity != fill.quantity {
+                        changed.push(FillChange {
+                            a: fill.clone(),
+                            b: other.clone(),
+                        });
+                    }
+                }
+                None => only_in_a.push(fill.clone()),
+            }
+        }
+
+        Self {
+            a: a.id,
+            b: b.id,
+            metrics,
+            only_in_a,
+            only_in_b: unmatched_b.into_iter().cloned().collect(),
+            changed,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backtest::slippage::{FixedBps, SlippageConfig};
+    use crate::types::{Notional, OrderSide, Price, Qty};
+
+    fn result(fee_bps: f64, fills: Vec<Fill>) -> BacktestResult {
+        let mut config = BacktestConfig::new("BTCUSDT".to_string(), 1_000.0);
+        config.fee_bps = fee_bps;
+        config.slippage = SlippageConfig::FixedBps(FixedBps { bps: 1.0 });
+        BacktestResult {
+            config,
+            metrics: BacktestMetrics {
+                final_equity: 1_000.0 + fills.len() as f64,
+                trade_count: fills.len(),
+                ..Default::default()
+            },
+            equity_curve: Vec::new(),
+            fills,
+        }
+    }
+
+    fn fill(timestamp: DateTime<Utc>, price: f64) -> Fill {
+        Fill {
+            timestamp,
+            side: OrderSide::Buy,
+            quantity: Qty::new(1.0),
+            price: Price::new(price),
+            slippage_cost: Notional::ZERO,
+            fee: Notional::ZERO,
+        }
+    }
+
+    #[test]
+    fn test_save_list_get() {
+        let store = BacktestStore::in_memory();
+        let first = store.save(result(0.0, Vec::new())).unwrap();
+        let second = store.save(result(5.0, Vec::new())).unwrap();
+
+        assert_eq!(first, RunId(1));
+        assert_eq!(second, RunId(2));
+        assert_eq!(store.list().len(), 2);
+        assert_eq!(store.get(second).unwrap().result.config.fee_bps, 5.0);
+        assert!(store.get(RunId(3)).is_none());
+    }
+
+    #[test]
+    fn test_diff_runs() {
+        let t0 = Utc::now();
+        let t1 = t0 + chrono::Duration::seconds(1);
+        let t2 = t0 + chrono::Duration::seconds(2);
+
+        let store = BacktestStore::in_memory();
+        let a = store
+            .save(result(0.0, vec![fill(t0, 100.0), fill(t1, 101.0)]))
+            .unwrap();
+        let b = store
+            .save(result(0.0, vec![fill(t0, 100.5), fill(t2, 102.0)]))
+            .unwrap();
+
+        let diff = store.diff(a, b).unwrap();
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].b.price, 100.5);
+        assert_eq!(diff.only_in_a[0].timestamp, t1);
+        assert_eq!(diff.only_in_b[0].timestamp, t2);
+        assert_eq!(diff.metrics["trade_count"].delta, 0.0);
+    }
+
+    #[test]
+    fn test_persisted_runs_reload() {
+        let dir = std::env::temp_dir().join(format!("backtest-store-{}", std::process::id()));
+        {
+            let store = BacktestStore::open(&dir).unwrap();
+            store.save(result(2.0, Vec::new())).unwrap();
+        }
+
+        let reopened = BacktestStore::open(&dir).unwrap();
+        assert_eq!(reopened.list().len(), 1);
+        assert_eq!(reopened.save(result(0.0, Vec::new())).unwrap(), RunId(2));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
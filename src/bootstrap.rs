@@ -0,0 +1,338 @@
+// Engine startup in dependency order
+//
+// Services are registered with the services they need and a start function
+// that resolves once the service is ready. The bootstrapper starts them one
+// layer at a time, market data before trading, trading before portfolio and
+// so on, and waits for each to be ready before starting anything that
+// depends on it. The first service that fails or does not become ready in
+// time stops the startup, and the report says what was running, what broke
+// and what was never started because of it.
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::health::ServiceHandle;
+
+/// How long a service may take to become ready unless told otherwise
+pub const DEFAULT_READY_TIMEOUT: Duration = Duration::from_secs(30);
+
+type Ready = Pin<Box<dyn Future<Output = Result<(), String>> + Send>>;
+type Start = Box<dyn FnOnce() -> Ready + Send>;
+
+struct ServiceSpec {
+    name: String,
+    depends_on: Vec<String>,
+    timeout: Duration,
+    start: Start,
+}
+
+/// Why a service did not start
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum StartupError {
+    /// The service names a dependency nobody registered
+    UnknownDependency { dependency: String },
+    /// The service is part of a dependency cycle
+    Cycle { services: Vec<String> },
+    /// Its start function reported an error
+    Failed { reason: String },
+    /// It did not become ready within its timeout
+    TimedOut { after_ms: u64 },
+}
+
+impl fmt::Display for StartupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StartupError::UnknownDependency { dependency } => {
+                write!(f, "unknown dependency {}", dependency)
+            }
+            StartupError::Cycle { services } => {
+                write!(f, "dependency cycle between {}", services.join(", "))
+            }
+            StartupError::Failed { reason } => write!(f, "failed: {}", reason),
+            StartupError::TimedOut { after_ms } => {
+                write!(f, "not ready after {}ms", after_ms)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StartedService {
+    pub name: String,
+    /// Time from calling its start function to it being ready
+    pub ready_ms: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StartupFailure {
+    pub service: String,
+    pub error: StartupError,
+}
+
+/// What a startup got through, and where it stopped
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct StartupReport {
+    /// Ready services, in the order they were started
+    pub started: Vec<StartedService>,
+    pub failure: Option<StartupFailure>,
+    /// Services left unstarted because of the failure
+    pub not_started: Vec<String>,
+    pub total_ms: u64,
+}
+
+impl StartupReport {
+    pub fn is_ok(&self) -> bool {
+        self.failure.is_none()
+    }
+}
+
+impl fmt::Display for StartupReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.failure {
+            Some(failure) => writeln!(
+                f,
+                "startup failed after {}ms: {} {}",
+                self.total_ms, failure.service, failure.error
+            )?,
+            None => writeln!(f, "startup completed in {}ms", self.total_ms)?,
+        }
+        for service in &self.started {
+            writeln!(f, "  ready    {} ({}ms)", service.name, service.ready_ms)?;
+        }
+        if let Some(failure) = &self.failure {
+            writeln!(f, "  failed   {}", failure.service)?;
+        }
+        for name in &self.not_started {
+            writeln!(f, "  skipped  {}", name)?;
+        }
+        Ok(())
+    }
+}
+
+/// Starts registered services in dependency order
+#[derive(Default)]
+pub struct Bootstrapper {
+    services: Vec<ServiceSpec>,
+}
+
+impl Bootstrapper {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register `name`, started once everything in `depends_on` is ready
+    ///
+    /// `start` launches the service and returns a future that resolves when
+    /// it is ready to be depended on.
+    pub fn service<F, R>(self, name: &str, depends_on: &[&str], start: F) -> Self
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Future<Output = Result<(), String>> + Send + 'static,
+    {
+        self.service_with_timeout(name, depends_on, DEFAULT_READY_TIMEOUT, start)
+    }
+
+    pub fn service_with_timeout<F, R>(
+        mut self,
+        name: &str,
+        depends_on: &[&str],
+        timeout: Duration,
+        start: F,
+    ) -> Self
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Future<Output = Result<(), String>> + Send + 'static,
+    {
+        self.services.push(ServiceSpec {
+            name: name.to_string(),
+            depends_on: depends_on.iter().map(|d| d.to_string()).collect(),
+            timeout,
+            start: Box::new(move || Box::pin(start())),
+        });
+        self
+    }
+
+    /// Service names in the order they would start
+    ///
+    /// Services whose dependencies are equally satisfied keep their
+    /// registration order.
+    pub fn order(&self) -> Result<Vec<String>, StartupFailure> {
+        let names: BTreeSet<&str> = self.services.iter().map(|s| s.name.as_str()).collect();
+        for spec in &self.services {
+            if let Some(dependency) = spec.depends_on.iter().find(|d| !names.contains(d.as_str())) {
+                return Err(StartupFailure {
+                    service: spec.name.clone(),
+                    error: StartupError::UnknownDependency {
+                        dependency: dependency.clone(),
+                    },
+                });
+            }
+        }
+
+        let mut ordered: Vec<String> = Vec::with_capacity(self.services.len());
+        while ordered.len() < self.services.len() {
+            let next = self.services.iter().find(|spec| {
+                !ordered.contains(&spec.name) && spec.depends_on.iter().all(|d| ordered.contains(d))
+            });
+            match next {
+                Some(spec) => ordered.push(spec.name.clone()),
+                None => {
+                    let services: Vec<String> = self
+                        .services
+                        .iter()
+                        .filter(|spec| !ordered.contains(&spec.name))
+                        .map(|spec| spec.name.clone())
+                        .collect();
+                    return Err(StartupFailure {
+                        service: services[0].clone(),
+                        error: StartupError::Cycle { services },
+                    });
+                }
+            }
+        }
+        Ok(ordered)
+    }
+
+    /// Start every service, stopping at the first that fails
+    ///
+    /// Services already started are left running on failure; the caller
+    /// decides whether to shut down or retry.
+    pub async fn start(mut self) -> Result<StartupReport, StartupReport> {
+        let began = Instant::now();
+        let mut report = StartupReport::default();
+        let order = match self.order() {
+            Ok(order) => order,
+            Err(failure) => {
+                report.not_started = self
+                    .services
+                    .iter()
+                    .map(|spec| spec.name.clone())
+                    .filter(|name| *name != failure.service)
+                    .collect();
+                report.failure = Some(failure);
+                return Err(report);
+            }
+        };
+
+        for (i, name) in order.iter().enumerate() {
+            let index = self.services.iter().position(|s| &s.name == name).unwrap();
+            let spec = self.services.swap_remove(index);
+            let started = Instant::now();
+            let ready = tokio::time::timeout(spec.timeout, (spec.start)()).await;
+            let ready_ms = started.elapsed().as_millis() as u64;
+            let error = match ready {
+                Ok(Ok(())) => {
+                    tracing::info!("Service {} ready in {}ms", name, ready_ms);
+                    report.started.push(StartedService {
+                        name: name.clone(),
+                        ready_ms,
+                    });
+                    continue;
+                }
+                Ok(Err(reason)) => StartupError::Failed { reason },
+                Err(_) => StartupError::TimedOut {
+                    after_ms: spec.timeout.as_millis() as u64,
+                },
+            };
+            tracing::error!("Service {} did not start: {}", name, error);
+            report.failure = Some(StartupFailure {
+                service: name.clone(),
+                error,
+            });
+            report.not_started = order[i + 1..].to_vec();
+            break;
+        }
+
+        report.total_ms = began.elapsed().as_millis() as u64;
+        if report.is_ok() {
+            Ok(report)
+        } else {
+            Err(report)
+        }
+    }
+}
+
+/// Readiness for services that report through a health handle: resolves once
+/// the service is running and has done something, e.g. a feed's first message
+pub async fn active(handle: ServiceHandle, poll: Duration) -> Result<(), String> {
+    let mut interval = tokio::time::interval(poll);
+    loop {
+        interval.tick().await;
+        if handle.is_active() {
+            return Ok(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    type Log = Arc<Mutex<Vec<&'static str>>>;
+
+    fn record(log: &Log, name: &'static str) -> impl FnOnce() -> Ready + Send + 'static {
+        let log = Arc::clone(log);
+        move || {
+            Box::pin(async move {
+                log.lock().unwrap().push(name);
+                Ok(())
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_services_start_in_dependency_order() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let report = Bootstrapper::new()
+            .service("api", &["risk"], record(&log, "api"))
+            .service("risk", &["trading"], record(&log, "risk"))
+            .service("trading", &["market_data"], record(&log, "trading"))
+            .service("market_data", &[], record(&log, "market_data"))
+            .start()
+            .await
+            .unwrap();
+
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec!["market_data", "trading", "risk", "api"]
+        );
+        assert_eq!(report.started.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_failure_stops_dependents() {
+        let report = Bootstrapper::new()
+            .service("market_data", &[], || async { Ok(()) })
+            .service_with_timeout(
+                "trading",
+                &["market_data"],
+                Duration::from_millis(10),
+                std::future::pending,
+            )
+            .service("api", &["trading"], || async { Ok(()) })
+            .start()
+            .await
+            .unwrap_err();
+
+        assert_eq!(report.started[0].name, "market_data");
+        let failure = report.failure.as_ref().unwrap();
+        assert_eq!(failure.service, "trading");
+        assert_eq!(failure.error, StartupError::TimedOut { after_ms: 10 });
+        assert_eq!(report.not_started, vec!["api"]);
+        assert!(report.to_string().contains("skipped  api"));
+
+        let cycle = Bootstrapper::new()
+            .service("a", &["b"], || async { Ok(()) })
+            .service("b", &["a"], || async { Ok(()) })
+            .order()
+            .unwrap_err();
+        assert!(matches!(cycle.error, StartupError::Cycle { .. }));
+    }
+}
@@ -0,0 +1,371 @@
+// Scheduled news and economic events
+//
+// Events are loaded from a file or any other `EventSource`. Around a
+// high-impact event the calendar reports a tighter risk scale and a wider
+// spread multiplier, and publishes both as `calendar.*` signals so risk
+// checks and quoting strategies can react without polling the schedule.
+
+use std::io;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::signals::{SharedSignalBus, Signal};
+use crate::types::Symbol;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Impact {
+    Low,
+    Medium,
+    High,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScheduledEvent {
+    pub name: String,
+    pub at: DateTime<Utc>,
+    pub impact: Impact,
+    /// Affected symbols; empty means every symbol
+    #[serde(default)]
+    pub symbols: Vec<Symbol>,
+}
+
+impl ScheduledEvent {
+    fn affects(&self, symbol: &str) -> bool {
+        self.symbols.is_empty() || self.symbols.iter().any(|s| s == symbol)
+    }
+}
+
+/// Where scheduled events come from
+pub trait EventSource: Send + Sync {
+    fn fetch(&self) -> io::Result<Vec<ScheduledEvent>>;
+}
+
+/// JSON array of [`ScheduledEvent`]s on disk
+#[derive(Debug, Clone)]
+pub struct FileEventSource {
+    pub path: PathBuf,
+}
+
+impl EventSource for FileEventSource {
+    fn fetch(&self) -> io::Result<Vec<ScheduledEvent>> {
+        let json = std::fs::read_to_string(&self.path)?;
+        serde_json::from_str(&json).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+/// Reaction to events of one impact level
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ImpactPolicy {
+    pub before_secs: i64,
+    pub after_secs: i64,
+    /// Multiplier applied to risk limits while active, e.g. 0.5
+    pub risk_scale: f64,
+    /// Multiplier applied to quoted spreads while active, e.g. 2.0
+    pub spread_multiplier: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CalendarPolicy {
+    pub high: ImpactPolicy,
+    pub medium: Option<ImpactPolicy>,
+}
+
+impl CalendarPolicy {
+    fn for_impact(&self, impact: Impact) -> Option<&ImpactPolicy> {
+        match impact {
+            Impact::High => Some(&self.high),
+            Impact::Medium => self.medium.as_ref(),
+            Impact::Low => None,
+        }
+    }
+}
+
+impl Default for CalendarPolicy {
+    fn default() -> Self {
+        Self {
+            high: ImpactPolicy {
+                before_secs: 300,
+                after_secs: 600,
+                risk_scale: 0.5,
+                spread_multiplier: 2.0,
+            },
+            medium: None,
+        }
+    }
+}
+
+/// Combined effect of every active event on one symbol
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CalendarAdjustment {
+    pub risk_scale: f64,
+    pub spread_multiplier: f64,
+    /// Names of the events in effect
+    pub events: Vec<String>,
+}
+
+impl Default for CalendarAdjustment {
+    fn default() -> Self {
+        Self {
+            risk_scale: 1.0,
+            spread_multiplier: 1.0,
+            events: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Calendar {
+    policy: CalendarPolicy,
+    events: Vec<ScheduledEvent>,
+    bus: Option<SharedSignalBus>,
+}
+
+impl Calendar {
+    pub fn new(policy: CalendarPolicy) -> Self {
+        Self {
+            policy,
+            events: Vec::new(),
+            bus: None,
+        }
+    }
+
+    /// Publish `calendar.risk_scale` and `calendar.spread_multiplier` signals
+    pub fn with_signal_bus(mut self, bus: SharedSignalBus) -> Self {
+        self.bus = Some(bus);
+        self
+    }
+
+    /// Replace the schedule with `events`, sorted by time
+    pub fn load(&mut self, mut events: Vec<ScheduledEvent>) {
+        events.sort_by_key(|event| event.at);
+        self.events = events;
+    }
+
+    /// Reload from `source`; on error the current schedule is kept
+    pub fn refresh(&mut self, source: &dyn EventSource) -> io::Result<usize> {
+        let events = source.fetch()?;
+        let count = events.len();
+        self.load(events);
+        Ok(count)
+    }
+
+    /// Events that have not finished affecting the market as of `now`
+    pub fn upcoming(&self, now: DateTime<Utc>) -> Vec<ScheduledEvent> {
+        self.events
+            .iter()
+            .filter(|event| {
+                let after = self
+                    .policy
+                    .for_impact(event.impact)
+                    .map_or(0, |p| p.after_secs);
+                event.at + Duration::seconds(after) >= now
+            })
+            .cloned()
+            .collect()
+    }
+
+    pub fn adjustment(&self, symbol: &str, now: DateTime<Utc>) -> CalendarAdjustment {
+        let mut adjustment = CalendarAdjustment::default();
+        for event in self.events.iter().filter(|event| event.affects(symbol)) {
+            let Some(policy) = self.policy.for_impact(event.impact) else {
+                continue;
+            };
+            let start = event.at - Duration::seconds(policy.before_secs);
+            let end = event.at + Duration::seconds(policy.after_secs);
+            if start <= now && now <= end {
+                adjustment.risk_scale = adjustment.risk_scale.min(policy.risk_scale);
+                adjustment.spread_multiplier =
+                    adjustment.spread_multiplier.max(policy.spread_multiplier);
+                adjustment.events.push(event.name.clone());
+            }
+        }
+        adjustment
+    }
+
+    /// Publish the current adjustment of each symbol on the signal bus
+    pub fn publish(&self, symbols: &[Symbol], now: DateTime<Utc>) {
+        let Some(bus) = &self.bus else {
+            return;
+        };
+        for symbol in symbols {
+            let adjustment = self.adjustment(symbol.as_str(), now);
+            let ts = now.timestamp_millis();
+            bus.publish(Signal::number(
+                "calendar.risk_scale",
+                symbol.as_str(),
+                adjustment.risk_scale,
+                ts,
+            ));
+            bus.publish(Signal::number(
+                "calendar.spread_multiplier",
+                symbol.as_str(),
+                adjustment.spread_multiplier,
+                ts,
+            ));
+        }
+    }
+}
+
+/// Thread-safe wrapper for Calendar
+pub struct SharedCalendar {
+    inner: Arc<Mutex<Calendar>>,
+}
+
+impl SharedCalendar {
+    pub fn new(calendar: Calendar) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(calendar)),
+        }
+    }
+
+    pub fn load(&self, events: Vec<ScheduledEvent>) {
+        self.inner.lock().unwrap().load(events)
+    }
+
+    /// Fetches before locking so a slow source never blocks readers
+    pub fn refresh(&self, source: &dyn EventSource) -> io::Result<usize> {
+        let events = source.fetch()?;
+        let count = events.len();
+        self.load(events);
+        Ok(count)
+    }
+
+    pub fn upcoming(&self, now: DateTime<Utc>) -> Vec<ScheduledEvent> {
+        self.inner.lock().unwrap().upcoming(now)
+    }
+
+    pub fn adjustment(&self, symbol: &str, now: DateTime<Utc>) -> CalendarAdjustment {
+        self.inner.lock().unwrap().adjustment(symbol, now)
+    }
+
+    pub fn publish(&self, symbols: &[Symbol], now: DateTime<Utc>) {
+        self.inner.lock().unwrap().publish(symbols, now)
+    }
+
+    /// Refresh from `source` every `refresh` and publish adjustments for
+    /// `symbols` every `tick`
+    #[cfg(feature = "net")]
+    pub fn start(
+        &self,
+        source: Box<dyn EventSource>,
+        symbols: Vec<Symbol>,
+        refresh: std::time::Duration,
+        tick: std::time::Duration,
+    ) {
+        let calendar = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(tick);
+            let mut last_refresh: Option<std::time::Instant> = None;
+            loop {
+                interval.tick().await;
+                if last_refresh.is_none_or(|at| at.elapsed() >= refresh) {
+                    match calendar.refresh(source.as_ref()) {
+                        Ok(count) => tracing::debug!("Loaded {} calendar events", count),
+                        Err(e) => tracing::warn!("Calendar refresh failed: {}", e),
+                    }
+                    last_refresh = Some(std::time::Instant::now());
+                }
+                calendar.publish(&symbols, Utc::now());
+            }
+        });
+    }
+}
+
+impl Clone for SharedCalendar {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl Default for SharedCalendar {
+    fn default() -> Self {
+        Self::new(Calendar::new(CalendarPolicy::default()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 6, 12, 12, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn test_adjustment_around_events() {
+        let mut calendar = Calendar::new(CalendarPolicy {
+            medium: Some(ImpactPolicy {
+                before_secs: 60,
+                after_secs: 60,
+                risk_scale: 0.8,
+                spread_multiplier: 3.0,
+            }),
+            ..Default::default()
+        });
+        calendar.load(vec![
+            ScheduledEvent {
+                name: "FOMC".to_string(),
+                at: at(30),
+                impact: Impact::High,
+                symbols: Vec::new(),
+            },
+            ScheduledEvent {
+                name: "ETH upgrade".to_string(),
+                at: at(32),
+                impact: Impact::Medium,
+                symbols: vec![Symbol::new("ETHUSDT")],
+            },
+        ]);
+
+        assert_eq!(
+            calendar.adjustment("BTCUSDT", at(20)),
+            CalendarAdjustment::default()
+        );
+        let btc = calendar.adjustment("BTCUSDT", at(26));
+        assert_eq!((btc.risk_scale, btc.spread_multiplier), (0.5, 2.0));
+
+        // Overlapping events take the tightest scale and widest spread
+        let eth = calendar.adjustment("ETHUSDT", at(31));
+        assert_eq!((eth.risk_scale, eth.spread_multiplier), (0.5, 3.0));
+        assert_eq!(eth.events, vec!["FOMC", "ETH upgrade"]);
+
+        assert_eq!(calendar.upcoming(at(41)).len(), 0);
+        assert_eq!(calendar.upcoming(at(33)).len(), 2);
+    }
+
+    #[test]
+    fn test_file_source_and_signals() {
+        let path = std::env::temp_dir().join(format!("calendar-{}.json", std::process::id()));
+        std::fs::write(
+            &path,
+            r#"[{"name": "CPI", "at": "2024-06-12T12:30:00Z", "impact": "high"}]"#,
+        )
+        .unwrap();
+
+        let bus = SharedSignalBus::default();
+        let mut signals = bus.subscribe(&["calendar.spread_multiplier"]);
+        let calendar = SharedCalendar::new(Calendar::default().with_signal_bus(bus));
+        let source = FileEventSource { path: path.clone() };
+        assert_eq!(calendar.refresh(&source).unwrap(), 1);
+
+        calendar.publish(&[Symbol::new("BTCUSDT")], at(29));
+        assert_eq!(
+            signals
+                .poll()
+                .value("calendar.spread_multiplier", "BTCUSDT"),
+            Some(2.0)
+        );
+
+        std::fs::write(&path, "not json").unwrap();
+        assert!(calendar.refresh(&source).is_err());
+        assert_eq!(calendar.upcoming(at(0)).len(), 1);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
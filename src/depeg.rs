@@ -0,0 +1,319 @@
+// Stablecoin depeg monitoring
+//
+// Stablecoins are valued against each other through their crosses
+// (USDCUSDT, DAIUSDT, USDCDAI, ...). A coin counts as off its peg only by
+// as much as its closest cross says, so one coin breaking away is not
+// blamed on the coins it is quoted against. Deviations raise critical
+// alerts as they pass each threshold, are published as `depeg` signals for
+// strategies, and past a configured deviation run protective actions such
+// as halting the instruments that trade the coin.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::market::{
+    AlertCondition, AlertEvent, AlertId, AlertSeverity, AlertSink, InstrumentStatus,
+    SharedInstruments,
+};
+use crate::signals::{SharedSignalBus, Signal};
+use crate::types::Symbol;
+
+/// Value every monitored stablecoin is pegged to
+const PEG: f64 = 1.0;
+
+/// A cross between two stablecoins, priced in `quote` per `base`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StablePair {
+    pub symbol: Symbol,
+    pub base: String,
+    pub quote: String,
+}
+
+impl StablePair {
+    pub fn new(symbol: impl Into<Symbol>, base: &str, quote: &str) -> Self {
+        Self {
+            symbol: symbol.into(),
+            base: base.to_string(),
+            quote: quote.to_string(),
+        }
+    }
+}
+
+/// A stablecoin that passed a deviation threshold
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DepegEvent {
+    pub asset: String,
+    /// Value in the other stablecoins, from the cross closest to the peg
+    pub value: f64,
+    pub deviation_percent: f64,
+    pub threshold_percent: f64,
+    /// Whether the protective actions ran for it
+    pub acted: bool,
+    pub timestamp_ms: i64,
+}
+
+/// Something done about a depeg, such as converting balances or halting
+/// trading
+///
+/// Any `Fn(&DepegEvent)` closure is an action.
+pub trait DepegAction: Send + Sync {
+    fn protect(&self, event: &DepegEvent);
+}
+
+impl<F> DepegAction for F
+where
+    F: Fn(&DepegEvent) + Send + Sync,
+{
+    fn protect(&self, event: &DepegEvent) {
+        self(event)
+    }
+}
+
+/// Action halting every registered instrument that trades the depegged coin
+pub fn halt_instruments(instruments: SharedInstruments) -> impl Fn(&DepegEvent) + Send + Sync {
+    move |event: &DepegEvent| {
+        for instrument in instruments.list() {
+            if instrument.status == InstrumentStatus::Trading
+                && (instrument.base == event.asset || instrument.quote == event.asset)
+            {
+                tracing::warn!("Halting {} on {} depeg", instrument.symbol, event.asset);
+                instruments.set_status(instrument.symbol.as_str(), InstrumentStatus::Halted);
+            }
+        }
+    }
+}
+
+/// Escalating alerts as stablecoins drift off their peg
+///
+/// Each coin alerts once per threshold on the way out and is re-armed for a
+/// threshold once it comes back inside it, the way the liquidation proximity
+/// monitor escalates. Actions run once per depeg, when the deviation first
+/// reaches `action_percent`, and again only after the coin has recovered
+/// below it. Alerts go out from `AlertId::SYSTEM` with the coin as symbol.
+pub struct DepegMonitor {
+    pairs: Vec<StablePair>,
+    /// Deviations in percent, smallest first
+    thresholds: Vec<f64>,
+    action_percent: Option<f64>,
+    /// How many thresholds each coin is already past
+    reached: HashMap<String, usize>,
+    acted: HashSet<String>,
+    sinks: Vec<Box<dyn AlertSink>>,
+    actions: Vec<Box<dyn DepegAction>>,
+    bus: Option<SharedSignalBus>,
+}
+
+impl DepegMonitor {
+    pub fn new(pairs: Vec<StablePair>, thresholds: &[f64]) -> Self {
+        let mut thresholds = thresholds.to_vec();
+        thresholds.sort_by(f64::total_cmp);
+        Self {
+            pairs,
+            thresholds,
+            action_percent: None,
+            reached: HashMap::new(),
+            acted: HashSet::new(),
+            sinks: Vec::new(),
+            actions: Vec::new(),
+            bus: None,
+        }
+    }
+
+    /// USDT, USDC and DAI through their Binance-style crosses, alerting at
+    /// 0.5%, 1% and 2%
+    pub fn stablecoins() -> Self {
+        Self::new(
+            vec![
+                StablePair::new("USDCUSDT", "USDC", "USDT"),
+                StablePair::new("DAIUSDT", "DAI", "USDT"),
+                StablePair::new("USDCDAI", "USDC", "DAI"),
+            ],
+            &[0.5, 1.0, 2.0],
+        )
+    }
+
+    pub fn with_sink(mut self, sink: impl AlertSink + 'static) -> Self {
+        self.sinks.push(Box::new(sink));
+        self
+    }
+
+    /// Run `action` once a coin is `percent` or more off its peg
+    ///
+    /// All actions run at the same deviation, the one given last.
+    pub fn with_action(mut self, percent: f64, action: impl DepegAction + 'static) -> Self {
+        self.action_percent = Some(percent);
+        self.actions.push(Box::new(action));
+        self
+    }
+
+    /// Publish each coin's deviation in percent as a `depeg` signal
+    pub fn with_signal_bus(mut self, bus: SharedSignalBus) -> Self {
+        self.bus = Some(bus);
+        self
+    }
+
+    /// Value of every coin with a priced cross, from the cross closest to
+    /// the peg
+    ///
+    /// With a single cross both of its coins take its deviation, as nothing
+    /// tells which one moved.
+    pub fn values(&self, price: impl Fn(&Symbol) -> Option<f64>) -> BTreeMap<String, f64> {
+        let mut values: BTreeMap<String, f64> = BTreeMap::new();
+        let mut value_of = |asset: &str, value: f64| {
+            let best = values.entry(asset.to_string()).or_insert(value);
+            if (value - PEG).abs() < (*best - PEG).abs() {
+                *best = value;
+            }
+        };
+        for pair in &self.pairs {
+            let Some(price) = price(&pair.symbol).filter(|p| *p > 0.0) else {
+                continue;
+            };
+            value_of(&pair.base, price);
+            value_of(&pair.quote, 1.0 / price);
+        }
+        values
+    }
+
+    /// Value the coins at `price` of their crosses, returning the depegs
+    /// raised
+    pub fn check(
+        &mut self,
+        price: impl Fn(&Symbol) -> Option<f64>,
+        timestamp_ms: i64,
+    ) -> Vec<DepegEvent> {
+        let mut fired = Vec::new();
+        for (asset, value) in self.values(price) {
+            let deviation_percent = (value - PEG).abs() / PEG * 100.0;
+            if let Some(bus) = &self.bus {
+                bus.publish(Signal::number(
+                    "depeg",
+                    &asset,
+                    deviation_percent,
+                    timestamp_ms,
+                ));
+            }
+            let acting = self
+                .action_percent
+                .is_some_and(|percent| deviation_percent >= percent);
+            if !acting {
+                self.acted.remove(&asset);
+            }
+
+            let past = self
+                .thresholds
+                .iter()
+                .filter(|t| deviation_percent >= **t)
+                .count();
+            let before = self.reached.insert(asset.clone(), past).unwrap_or(0);
+            let act = acting && self.acted.insert(asset.clone());
+            if past <= before && !act {
+                continue;
+            }
+            let threshold_percent = match past {
+                0 => self.action_percent.unwrap_or_default(),
+                _ => self.thresholds[past - 1],
+            };
+            tracing::error!(
+                "{} is {:.2}% off its peg at {:.4}",
+                asset,
+                deviation_percent,
+                value
+            );
+            fired.push(DepegEvent {
+                asset,
+                value,
+                deviation_percent,
+                threshold_percent,
+                acted: act,
+                timestamp_ms,
+            });
+        }
+
+        for event in &fired {
+            let alert = AlertEvent {
+                alert_id: AlertId::SYSTEM,
+                user: "system".to_string(),
+                symbol: Symbol::new(&event.asset),
+                condition: AlertCondition::Depeg {
+                    peg: PEG,
+                    threshold_percent: event.threshold_percent,
+                },
+                severity: AlertSeverity::Critical,
+                price: event.value,
+                timestamp_ms: event.timestamp_ms,
+            };
+            for sink in &self.sinks {
+                sink.deliver(&alert);
+            }
+            if event.acted {
+                for action in &self.actions {
+                    action.protect(event);
+                }
+            }
+        }
+        fired
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::market::{Instrument, InstrumentRegistry};
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_depegged_coin_alerts_escalate_and_halt_its_instruments() {
+        let instruments = SharedInstruments::new(InstrumentRegistry::new());
+        for (symbol, base, quote) in [("BTCUSDT", "BTC", "USDT"), ("BTCUSDC", "BTC", "USDC")] {
+            instruments.insert(Instrument {
+                symbol: Symbol::new(symbol),
+                base: base.to_string(),
+                quote: quote.to_string(),
+                tick_size: 0.01,
+                lot_size: 0.001,
+                price_precision: 2,
+                quantity_precision: 3,
+                status: InstrumentStatus::Trading,
+                margin_tiers: Vec::new(),
+                price_band: None,
+            });
+        }
+        let alerts = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&alerts);
+        let mut monitor = DepegMonitor::stablecoins()
+            .with_sink(move |alert: &AlertEvent| sink.lock().unwrap().push(alert.clone()))
+            .with_action(2.0, halt_instruments(instruments.clone()));
+
+        // USDT at 0.985: USDC and DAI still agree with each other
+        let mut prices = HashMap::from([
+            (Symbol::new("USDCUSDT"), 1.0 / 0.985),
+            (Symbol::new("DAIUSDT"), 1.0 / 0.985),
+            (Symbol::new("USDCDAI"), 1.0),
+        ]);
+        let price = |prices: &HashMap<Symbol, f64>| {
+            let prices = prices.clone();
+            move |symbol: &Symbol| prices.get(symbol).copied()
+        };
+        let events = monitor.check(price(&prices), 1);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].asset, "USDT");
+        assert_eq!((events[0].threshold_percent, events[0].acted), (1.0, false));
+        assert!(monitor.check(price(&prices), 2).is_empty());
+
+        // Past 2% the instruments quoted in USDT are halted
+        prices.insert(Symbol::new("USDCUSDT"), 1.0 / 0.97);
+        prices.insert(Symbol::new("DAIUSDT"), 1.0 / 0.97);
+        let events = monitor.check(price(&prices), 3);
+        assert!(events[0].acted);
+        let status = |symbol: &str| instruments.get(symbol).unwrap().status;
+        assert_eq!(status("BTCUSDT"), InstrumentStatus::Halted);
+        assert_eq!(status("BTCUSDC"), InstrumentStatus::Trading);
+
+        let alerts = alerts.lock().unwrap();
+        assert_eq!(alerts.len(), 2);
+        assert_eq!(alerts[1].severity, AlertSeverity::Critical);
+        assert_eq!(alerts[1].symbol, Symbol::new("USDT"));
+    }
+}
@@ -1,10 +1,27 @@
+use chrono::{DateTime, Utc};
 use futures_util::StreamExt;
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, mpsc, RwLock};
 use tokio_tungstenite::{connect_async, tungstenite::Message};
 
-use crate::orderbook::SharedOrderBook;
+use crate::exchange::clock::SharedClockSync;
+use crate::exchange::endpoints::{EndpointStatus, SharedEndpointPool};
+use crate::exchange::sequence::{SequenceStats, SequenceTracker, Sequenced};
+#[cfg(feature = "ipc")]
+use crate::ipc::SharedRingWriter;
+use crate::health::{HealthRegistry, ServiceHandle, ServiceRule};
+use crate::indicators::SharedIndicators;
+use crate::market::{
+    SharedAlertEngine, SharedAnomalyDetector, SharedFastPath, SharedRollingStats, SharedTickers,
+    SharedTradeTape, TapeTrade, TickerStats, TradeSource,
+};
+use crate::orderbook::{BookManager, BookUpdate, OrderBook, SharedOrderBook};
+use crate::overload::{Priority, SharedLoadShedder};
+use crate::throughput::SharedThroughputMeter;
+use crate::types::{OrderSide, Price, Qty, Symbol};
 
 /// Binance ticker message structure
 #[derive(Debug, Deserialize)]
@@ -13,6 +30,38 @@ struct BinanceTicker {
     symbol: String,
     #[serde(rename = "c")]
     price: String,
+    #[serde(rename = "E", default)]
+    event_time: Option<i64>,
+    #[serde(rename = "o", default)]
+    open: Option<String>,
+    #[serde(rename = "h", default)]
+    high: Option<String>,
+    #[serde(rename = "l", default)]
+    low: Option<String>,
+    #[serde(rename = "v", default)]
+    volume: Option<String>,
+    #[serde(rename = "q", default)]
+    quote_volume: Option<String>,
+    #[serde(rename = "P", default)]
+    change_percent: Option<String>,
+}
+
+impl BinanceTicker {
+    /// 24h statistics, when the message carries all of them
+    fn stats(&self, last: f64, updated_at: DateTime<Utc>) -> Option<TickerStats> {
+        let parse = |field: &Option<String>| field.as_deref()?.parse::<f64>().ok();
+        Some(TickerStats {
+            symbol: self.symbol.as_str().into(),
+            last,
+            open: parse(&self.open)?,
+            high: parse(&self.high)?,
+            low: parse(&self.low)?,
+            volume: parse(&self.volume)?,
+            quote_volume: parse(&self.quote_volume)?,
+            change_percent: parse(&self.change_percent)?,
+            updated_at,
+        })
+    }
 }
 
 /// Binance depth update structure
@@ -20,26 +69,285 @@ struct BinanceTicker {
 struct BinanceDepth {
     #[serde(rename = "s")]
     symbol: String,
+    #[serde(rename = "U")]
+    first_update_id: u64,
+    #[serde(rename = "u")]
+    final_update_id: u64,
     #[serde(rename = "b")]
     bids: Vec<[String; 2]>,
     #[serde(rename = "a")]
     asks: Vec<[String; 2]>,
+    #[serde(rename = "E", default)]
+    event_time: Option<i64>,
+}
+
+/// Binance trade message structure
+#[derive(Debug, Deserialize)]
+struct BinanceTrade {
+    #[serde(rename = "s")]
+    symbol: String,
+    #[serde(rename = "t")]
+    trade_id: u64,
+    #[serde(rename = "p")]
+    price: String,
+    #[serde(rename = "q")]
+    quantity: String,
+    #[serde(rename = "T")]
+    trade_time: i64,
+    /// True when the buyer was the resting (maker) side
+    #[serde(rename = "m")]
+    buyer_is_maker: bool,
+}
+
+/// Response of the REST server time endpoint
+#[derive(Debug, Deserialize)]
+struct BinanceServerTime {
+    #[serde(rename = "serverTime")]
+    server_time: i64,
+}
+
+/// Entry of the REST ticker price endpoint
+#[derive(Debug, Deserialize)]
+struct BinancePrice {
+    symbol: String,
+    price: String,
+}
+
+/// Response of the REST order book snapshot endpoint
+#[derive(Debug, Deserialize)]
+struct BinanceDepthSnapshot {
+    #[serde(rename = "lastUpdateId")]
+    last_update_id: u64,
+    bids: Vec<[String; 2]>,
+    asks: Vec<[String; 2]>,
+}
+
+const SERVER_TIME_URL: &str = "https://api.binance.com/api/v3/time";
+const DEPTH_SNAPSHOT_URL: &str = "https://api.binance.com/api/v3/depth";
+const TICKER_PRICE_URL: &str = "https://api.binance.com/api/v3/ticker/price";
+
+/// Fastest REST polling allowed, well inside Binance's request-weight limits
+const MIN_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Messages held back per stream while waiting for a missing update
+const REORDER_WINDOW: usize = 32;
+/// A feed with no message for this long is stale
+const FEED_MAX_IDLE: Duration = Duration::from_secs(10);
+/// Messages queued behind a feed loop before it counts as backlogged
+const FEED_MAX_BACKLOG: usize = 1_000;
+/// Unparseable messages in a row before a feed counts as degraded
+const FEED_MAX_ERRORS: u64 = 100;
+
+/// Parse Binance `[price, quantity]` string pairs, skipping malformed ones
+fn parse_levels(levels: &[[String; 2]]) -> Vec<(Price, Qty)> {
+    levels
+        .iter()
+        .filter_map(|[price, quantity]| {
+            Some((Price::try_new(price.parse().ok()?)?, Qty::try_new(quantity.parse().ok()?)?))
+        })
+        .collect()
+}
+
+impl BinanceDepthSnapshot {
+    fn book_update(&self) -> BookUpdate {
+        BookUpdate {
+            bids: parse_levels(&self.bids),
+            asks: parse_levels(&self.asks),
+        }
+    }
+}
+
+impl BinanceDepth {
+    fn book_update(&self) -> BookUpdate {
+        BookUpdate {
+            bids: parse_levels(&self.bids),
+            asks: parse_levels(&self.asks),
+        }
+    }
+}
+
+/// Connect to `path` on every endpoint, forwarding text frames tagged with
+/// the index of the endpoint that delivered them
+/// Count one received message against a feed's health
+fn observe_feed(health: &Option<ServiceHandle>, backlog: usize, parsed: bool) {
+    if let Some(health) = health {
+        health.set_backlog(backlog);
+        if parsed {
+            health.record_activity();
+            health.clear_errors();
+        } else {
+            health.record_error();
+        }
+    }
+}
+
+fn spawn_connections(
+    endpoints: &SharedEndpointPool,
+    path: &str,
+    feed: &'static str,
+) -> mpsc::UnboundedReceiver<(usize, String)> {
+    let (tx, rx) = mpsc::unbounded_channel();
+
+    for (index, base) in endpoints.urls().into_iter().enumerate() {
+        let url = format!("{}/ws/{}", base, path);
+        let endpoints = endpoints.clone();
+        let tx = tx.clone();
+
+        tokio::spawn(async move {
+            loop {
+                match connect_async(&url).await {
+                    Ok((ws_stream, _)) => {
+                        tracing::info!("✓ Connected to Binance {} feed via {}", feed, base);
+                        endpoints.record_connect(index);
+                        let (_, mut read) = ws_stream.split();
+
+                        while let Some(msg) = read.next().await {
+                            if let Ok(Message::Text(text)) = msg {
+                                if tx.send((index, text)).is_err() {
+                                    return;
+                                }
+                            }
+                        }
+                        endpoints.record_disconnect(index);
+                    }
+                    Err(e) => {
+                        tracing::error!("{} connection to {} failed: {}", feed, base, e);
+                        endpoints.record_disconnect(index);
+                    }
+                }
+                tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+            }
+        });
+    }
+
+    rx
+}
+
+/// Fetch price and top of book for `symbols` over REST
+async fn poll_rest(client: &reqwest::Client, symbols: &[String]) -> Vec<MarketData> {
+    let symbol_list = serde_json::to_string(symbols).unwrap_or_default();
+    let prices = match client
+        .get(TICKER_PRICE_URL)
+        .query(&[("symbols", symbol_list.as_str())])
+        .send()
+        .await
+    {
+        Ok(response) => response.json::<Vec<BinancePrice>>().await.unwrap_or_default(),
+        Err(e) => {
+            tracing::warn!("REST ticker poll failed: {}", e);
+            return Vec::new();
+        }
+    };
+
+    let mut updates = Vec::new();
+    for ticker in prices {
+        let Ok(price) = ticker.price.parse::<f64>() else {
+            continue;
+        };
+
+        let request = client
+            .get(DEPTH_SNAPSHOT_URL)
+            .query(&[("symbol", ticker.symbol.as_str()), ("limit", "5")]);
+        let top = match request.send().await {
+            Ok(response) => response.json::<BinanceDepthSnapshot>().await.ok(),
+            Err(e) => {
+                tracing::warn!("REST depth poll for {} failed: {}", ticker.symbol, e);
+                None
+            }
+        };
+
+        let mut mirror = OrderBook::mirror(ticker.symbol.as_str());
+        if let Some(snapshot) = &top {
+            mirror.apply_snapshot(&snapshot.book_update());
+        }
+        let bid_price = mirror.best_bid().unwrap_or(0.0);
+        let ask_price = mirror.best_ask().unwrap_or(0.0);
+
+        updates.push(MarketData {
+            symbol: ticker.symbol.into(),
+            price: price.into(),
+            bid_price: bid_price.into(),
+            ask_price: ask_price.into(),
+            spread: Price::new(if bid_price > 0.0 && ask_price > 0.0 { ask_price - bid_price } else { 0.0 }),
+            latency_ms: None,
+            quality: DataQuality::Degraded,
+        });
+    }
+    updates
+}
+
+/// Reload `symbol` from the REST snapshot and restart its sequence
+async fn resnapshot(
+    client: &reqwest::Client,
+    symbol: &str,
+    tracker: &mut SequenceTracker<BinanceDepth>,
+    mirror: &SharedOrderBook,
+) -> bool {
+    let request = client
+        .get(DEPTH_SNAPSHOT_URL)
+        .query(&[("symbol", symbol), ("limit", "1000")]);
+
+    match request.send().await {
+        Ok(response) => match response.json::<BinanceDepthSnapshot>().await {
+            Ok(snapshot) => {
+                mirror.apply_snapshot(&snapshot.book_update());
+                tracker.resync(snapshot.last_update_id);
+                true
+            }
+            Err(e) => {
+                tracing::warn!("Invalid depth snapshot for {}: {}", symbol, e);
+                false
+            }
+        },
+        Err(e) => {
+            tracing::warn!("Depth snapshot request for {} failed: {}", symbol, e);
+            false
+        }
+    }
+}
+
+/// How a market data snapshot was obtained
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataQuality {
+    /// Streamed over WebSocket
+    Live,
+    /// Polled over REST while the streams were down
+    Degraded,
 }
 
 /// Market data snapshot for a symbol
 #[derive(Debug, Clone)]
 pub struct MarketData {
-    pub symbol: String,
-    pub price: f64,
-    pub bid_price: f64,
-    pub ask_price: f64,
-    pub spread: f64,
+    pub symbol: Symbol,
+    pub price: Price,
+    pub bid_price: Price,
+    pub ask_price: Price,
+    pub spread: Price,
+    /// Feed latency of the last update, corrected for exchange clock offset
+    pub latency_ms: Option<f64>,
+    pub quality: DataQuality,
 }
 
 /// Binance WebSocket feed manager
 pub struct BinanceFeed {
     symbols: Vec<String>,
     market_data: Arc<RwLock<Vec<MarketData>>>,
+    /// Sequencing counters keyed by stream name, e.g. `btcusdt@depth`
+    stream_stats: Arc<RwLock<HashMap<String, SequenceStats>>>,
+    endpoints: SharedEndpointPool,
+    shedder: SharedLoadShedder,
+    clock: SharedClockSync,
+    throughput: SharedThroughputMeter,
+    indicators: Option<SharedIndicators>,
+    anomalies: Option<SharedAnomalyDetector>,
+    alerts: Option<SharedAlertEngine>,
+    tickers: Option<SharedTickers>,
+    rolling: Option<SharedRollingStats>,
+    health: Option<HealthRegistry>,
+    trade_events: Option<broadcast::Sender<TapeTrade>>,
+    fast_path: Option<SharedFastPath>,
+    #[cfg(feature = "ipc")]
+    events: Option<SharedRingWriter>,
 }
 
 impl BinanceFeed {
@@ -47,10 +355,141 @@ impl BinanceFeed {
         Self {
             symbols,
             market_data: Arc::new(RwLock::new(Vec::new())),
+            stream_stats: Arc::new(RwLock::new(HashMap::new())),
+            endpoints: SharedEndpointPool::default(),
+            shedder: SharedLoadShedder::default(),
+            clock: SharedClockSync::default(),
+            throughput: SharedThroughputMeter::default(),
+            indicators: None,
+            anomalies: None,
+            alerts: None,
+            tickers: None,
+            rolling: None,
+            health: None,
+            trade_events: None,
+            fast_path: None,
+            #[cfg(feature = "ipc")]
+            events: None,
         }
     }
 
+    /// Use these WebSocket base URLs instead of the public Binance endpoints
+    pub fn with_endpoints(mut self, urls: Vec<String>) -> Self {
+        self.endpoints = SharedEndpointPool::new(urls);
+        self
+    }
+
+    /// Report stream queue depths to `shedder` and shed optional work under load
+    pub fn with_load_shedder(mut self, shedder: SharedLoadShedder) -> Self {
+        self.shedder = shedder;
+        self
+    }
+
+    /// Count every received stream message on `throughput`
+    pub fn with_throughput(mut self, throughput: SharedThroughputMeter) -> Self {
+        self.throughput = throughput;
+        self
+    }
+
+    /// Update technical indicators from every exchange trade
+    pub fn with_indicators(mut self, indicators: SharedIndicators) -> Self {
+        self.indicators = Some(indicators);
+        self
+    }
+
+    /// Score trades and depth message rates for anomalies
+    pub fn with_anomaly_detector(mut self, anomalies: SharedAnomalyDetector) -> Self {
+        self.anomalies = Some(anomalies);
+        self
+    }
+
+    /// Evaluate user price alerts on every exchange trade
+    pub fn with_alerts(mut self, alerts: SharedAlertEngine) -> Self {
+        self.alerts = Some(alerts);
+        self
+    }
+
+    /// Keep the exchange's 24h statistics from the ticker stream in `tickers`
+    pub fn with_tickers(mut self, tickers: SharedTickers) -> Self {
+        self.tickers = Some(tickers);
+        self
+    }
+
+    /// Compute 24h statistics locally from every exchange trade
+    pub fn with_rolling_stats(mut self, rolling: SharedRollingStats) -> Self {
+        self.rolling = Some(rolling);
+        self
+    }
+
+    /// Send every exchange trade to `events` as it is put on the tape
+    pub fn with_trade_events(mut self, events: broadcast::Sender<TapeTrade>) -> Self {
+        self.trade_events = Some(events);
+        self
+    }
+
+    /// Deliver trades and top-of-book changes of the symbols strategies
+    /// registered on `fast_path` before any other processing
+    pub fn with_fast_path(mut self, fast_path: SharedFastPath) -> Self {
+        self.fast_path = Some(fast_path);
+        self
+    }
+
+    /// Publish trades and top-of-book changes to a shared-memory ring
+    #[cfg(feature = "ipc")]
+    pub fn with_event_ring(mut self, events: SharedRingWriter) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Report each feed as a critical service on `health`
+    pub fn with_health(mut self, health: HealthRegistry) -> Self {
+        self.health = Some(health);
+        self
+    }
+
+    fn feed_health(&self, name: &str) -> Option<ServiceHandle> {
+        let rule = ServiceRule::default()
+            .critical()
+            .with_max_idle(FEED_MAX_IDLE)
+            .with_max_backlog(FEED_MAX_BACKLOG)
+            .with_max_errors(FEED_MAX_ERRORS);
+        self.health
+            .as_ref()
+            .map(|health| health.register(name, rule))
+    }
+
+    /// Estimated offset between the local clock and Binance server time
+    pub fn clock(&self) -> SharedClockSync {
+        self.clock.clone()
+    }
+
+    /// Poll the REST time endpoint every `interval` to refine the clock offset
+    pub async fn start_clock_sync(&self, interval: Duration) {
+        let clock = self.clock.clone();
+
+        tokio::spawn(async move {
+            let client = reqwest::Client::new();
+            loop {
+                let sent = Utc::now();
+                match client.get(SERVER_TIME_URL).send().await {
+                    Ok(response) => match response.json::<BinanceServerTime>().await {
+                        Ok(time) => {
+                            clock.record_round_trip(sent, time.server_time, Utc::now());
+                            tracing::debug!("⏱ Binance clock offset: {:?}ms", clock.offset_ms());
+                        }
+                        Err(e) => tracing::warn!("Invalid server time response: {}", e),
+                    },
+                    Err(e) => tracing::warn!("Server time request failed: {}", e),
+                }
+                tokio::time::sleep(interval).await;
+            }
+        });
+    }
+
     /// Start the price feed (ticker stream)
+    ///
+    /// Every endpoint streams in parallel; prices are taken from the current
+    /// primary, which fails over when it degrades.
     pub async fn start_price_feed(&self) {
         let stream_names: Vec<String> = self
             .symbols
@@ -58,121 +497,361 @@ impl BinanceFeed {
             .map(|s| format!("{}@ticker", s.to_lowercase()))
             .collect();
 
-        let url = format!(
-            "wss://stream.binance.com:9443/ws/{}",
-            stream_names.join("/")
-        );
+        let mut messages = spawn_connections(&self.endpoints, &stream_names.join("/"), "ticker");
 
         let market_data = Arc::clone(&self.market_data);
+        let endpoints = self.endpoints.clone();
+        let shedder = self.shedder.clone();
+        let clock = self.clock.clone();
+        let throughput = self.throughput.clone();
+        let tickers = self.tickers.clone();
+        let health = self.feed_health("price_feed");
 
         tokio::spawn(async move {
-            loop {
-                match connect_async(&url).await {
-                    Ok((ws_stream, _)) => {
-                        tracing::info!("✓ Connected to Binance ticker feed");
-                        let (_, mut read) = ws_stream.split();
+            while let Some((endpoint, text)) = messages.recv().await {
+                shedder.record_queue_depth(messages.len());
+                throughput.record_market_messages(1);
+                // Direct parsing without wrapper
+                let parsed = serde_json::from_str::<BinanceTicker>(&text);
+                observe_feed(&health, messages.len(), parsed.is_ok());
+                if let Ok(ticker) = parsed {
+                    let latency_ms = ticker.event_time.and_then(|t| clock.observe_event(t));
+                    if !endpoints.observe_message(endpoint, latency_ms) {
+                        continue;
+                    }
 
-                        while let Some(msg) = read.next().await {
-                            if let Ok(Message::Text(text)) = msg {
-                                // Direct parsing without wrapper
-                                if let Ok(ticker) = serde_json::from_str::<BinanceTicker>(&text) {
-                                    if let Ok(price) = ticker.price.parse::<f64>() {
-                                        tracing::info!("📊 {} = ${:.2}", ticker.symbol, price);
-
-                                        // Update market data
-                                        let mut data = market_data.write().await;
-                                        if let Some(md) = data.iter_mut().find(|m| m.symbol == ticker.symbol) {
-                                            md.price = price;
-                                        } else {
-                                            data.push(MarketData {
-                                                symbol: ticker.symbol,
-                                                price,
-                                                bid_price: 0.0,
-                                                ask_price: 0.0,
-                                                spread: 0.0,
-                                            });
-                                        }
-                                    }
-                                }
+                    if let Ok(price) = ticker.price.parse::<f64>() {
+                        if shedder.admit(Priority::Low) {
+                            tracing::info!("📊 {} = ${:.2}", ticker.symbol, price);
+                        }
+                        if let Some(tickers) = &tickers {
+                            if let Some(stats) = ticker.stats(price, Utc::now()) {
+                                tickers.update(stats);
                             }
                         }
-                    }
-                    Err(e) => {
-                        tracing::error!("Connection failed: {}", e);
+
+                        // Update market data
+                        let mut data = market_data.write().await;
+                        if let Some(md) = data.iter_mut().find(|m| m.symbol == ticker.symbol) {
+                            md.price = price.into();
+                            md.latency_ms = latency_ms;
+                            md.quality = DataQuality::Live;
+                        } else {
+                            data.push(MarketData {
+                                symbol: ticker.symbol.into(),
+                                price: price.into(),
+                                bid_price: Price::ZERO,
+                                ask_price: Price::ZERO,
+                                spread: Price::ZERO,
+                                latency_ms,
+                                quality: DataQuality::Live,
+                            });
+                        }
                     }
                 }
-                tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+            }
+            if let Some(health) = &health {
+                health.set_running(false);
             }
         });
     }
 
     /// Start the depth feed (order book updates)
-    pub async fn start_depth_feed(&self, _orderbook: SharedOrderBook) {
+    ///
+    /// Diff events are sequenced by update id on top of a REST snapshot:
+    /// duplicates are dropped, small reorderings are buffered and gaps trigger
+    /// a resnapshot. Events from all endpoints are merged, so the sequencer
+    /// also removes the copies delivered by the backups. Each symbol is kept
+    /// in its mirror book in `books`; matching books are never touched.
+    pub async fn start_depth_feed(&self, books: BookManager) {
         let stream_names: Vec<String> = self
             .symbols
             .iter()
-            .map(|s| format!("{}@depth5@100ms", s.to_lowercase()))
+            .map(|s| format!("{}@depth@100ms", s.to_lowercase()))
             .collect();
 
-        let url = format!(
-            "wss://stream.binance.com:9443/ws/{}",
-            stream_names.join("/")
-        );
+        let mut messages = spawn_connections(&self.endpoints, &stream_names.join("/"), "depth");
 
         let market_data = Arc::clone(&self.market_data);
+        let stream_stats = Arc::clone(&self.stream_stats);
+        let endpoints = self.endpoints.clone();
+        let shedder = self.shedder.clone();
+        let clock = self.clock.clone();
+        let throughput = self.throughput.clone();
+        let anomalies = self.anomalies.clone();
+        let mut fast_path = self.fast_path.as_ref().map(SharedFastPath::publisher);
+        #[cfg(feature = "ipc")]
+        let events = self.events.clone();
+        let health = self.feed_health("depth_feed");
 
         tokio::spawn(async move {
-            loop {
-                match connect_async(&url).await {
-                    Ok((ws_stream, _)) => {
-                        tracing::info!("✓ Connected to Binance depth feed");
-                        let (_, mut read) = ws_stream.split();
+            let client = reqwest::Client::new();
+            let mut trackers: HashMap<String, SequenceTracker<BinanceDepth>> = HashMap::new();
 
-                        while let Some(msg) = read.next().await {
-                            if let Ok(Message::Text(text)) = msg {
-                                // Direct parsing without wrapper
-                                if let Ok(depth) = serde_json::from_str::<BinanceDepth>(&text) {
-                                    // Update market data with best bid/ask
-                                    if let (Some(best_bid), Some(best_ask)) =
-                                        (depth.bids.first(), depth.asks.first()) {
-
-                                        if let (Ok(bid_price), Ok(ask_price)) =
-                                            (best_bid[0].parse::<f64>(), best_ask[0].parse::<f64>()) {
-
-                                            let spread = ask_price - bid_price;
-
-                                            // Update market data
-                                            let mut data = market_data.write().await;
-                                            if let Some(md) = data.iter_mut().find(|m| m.symbol == depth.symbol) {
-                                                md.bid_price = bid_price;
-                                                md.ask_price = ask_price;
-                                                md.spread = spread;
-                                            }
-
-                                            tracing::debug!(
-                                                "📖 {} Bid: ${:.2} Ask: ${:.2} Spread: ${:.2}",
-                                                depth.symbol, bid_price, ask_price, spread
-                                            );
-                                        }
-                                    }
-                                }
+            while let Some((endpoint, text)) = messages.recv().await {
+                shedder.record_queue_depth(messages.len());
+                throughput.record_market_messages(1);
+                let parsed = serde_json::from_str::<BinanceDepth>(&text);
+                observe_feed(&health, messages.len(), parsed.is_ok());
+                if let Ok(depth) = parsed {
+                    let latency_ms = depth.event_time.and_then(|t| clock.observe_event(t));
+                    endpoints.observe_message(endpoint, latency_ms);
+
+                    let symbol = depth.symbol.clone();
+                    if let Some(anomalies) = &anomalies {
+                        anomalies.on_message(&symbol, Utc::now().timestamp_millis());
+                    }
+                    let tracker = trackers
+                        .entry(symbol.clone())
+                        .or_insert_with(|| SequenceTracker::new(REORDER_WINDOW));
+                    let mirror = books.mirror(symbol.as_str());
+
+                    // Diff events only apply on top of a REST snapshot
+                    if !tracker.is_synced() && !resnapshot(&client, &symbol, tracker, &mirror).await {
+                        continue;
+                    }
+
+                    match tracker.accept(depth.first_update_id, depth.final_update_id, depth) {
+                        Sequenced::Ready(updates) => {
+                            for update in &updates {
+                                mirror.apply_diff(&update.book_update());
                             }
                         }
+                        Sequenced::Gap => {
+                            tracing::warn!("Depth gap on {}, resnapshotting", symbol);
+                            resnapshot(&client, &symbol, tracker, &mirror).await;
+                        }
+                        Sequenced::Duplicate | Sequenced::Buffered => {}
                     }
-                    Err(e) => {
-                        tracing::error!("Depth connection failed: {}", e);
+
+                    // Registered strategies and sibling processes get every
+                    // top-of-book change, ahead of shedding
+                    if let (Some(fast_path), Some(bid), Some(ask)) = (&mut fast_path, mirror.best_bid(), mirror.best_ask()) {
+                        fast_path.publish_quote(&symbol, bid, ask);
+                    }
+                    #[cfg(feature = "ipc")]
+                    if let (Some(events), Some(bid), Some(ask)) = (&events, mirror.best_bid(), mirror.best_ask()) {
+                        events.publish_quote(&symbol, bid, ask);
+                    }
+
+                    stream_stats
+                        .write()
+                        .await
+                        .insert(format!("{}@depth", symbol.to_lowercase()), tracker.stats());
+
+                    // Update market data with best bid/ask; under load this is conflated
+                    // to whichever update is admitted next, the mirror stays exact
+                    if !shedder.admit(Priority::Low) {
+                        continue;
+                    }
+                    if let (Some(bid_price), Some(ask_price)) = (mirror.best_bid(), mirror.best_ask()) {
+                        let spread = ask_price - bid_price;
+
+                        let mut data = market_data.write().await;
+                        if let Some(md) = data.iter_mut().find(|m| m.symbol == symbol) {
+                            md.bid_price = bid_price.into();
+                            md.ask_price = ask_price.into();
+                            md.spread = spread.into();
+                            md.latency_ms = latency_ms.or(md.latency_ms);
+                            md.quality = DataQuality::Live;
+                        }
+
+                        tracing::debug!(
+                            "📖 {} Bid: ${:.2} Ask: ${:.2} Spread: ${:.2}",
+                            symbol, bid_price, ask_price, spread
+                        );
+                    }
+                }
+            }
+            if let Some(health) = &health {
+                health.set_running(false);
+            }
+        });
+    }
+
+    /// Start the trade feed, recording every exchange trade on the tape
+    ///
+    /// Trade times are converted to the local clock so they order correctly
+    /// against locally matched trades. Like depth, trades from all endpoints
+    /// are merged and deduplicated by trade id.
+    pub async fn start_trade_feed(&self, tape: SharedTradeTape) {
+        let stream_names: Vec<String> = self
+            .symbols
+            .iter()
+            .map(|s| format!("{}@trade", s.to_lowercase()))
+            .collect();
+
+        let mut messages = spawn_connections(&self.endpoints, &stream_names.join("/"), "trade");
+
+        let stream_stats = Arc::clone(&self.stream_stats);
+        let endpoints = self.endpoints.clone();
+        let shedder = self.shedder.clone();
+        let clock = self.clock.clone();
+        let throughput = self.throughput.clone();
+        let indicators = self.indicators.clone();
+        let anomalies = self.anomalies.clone();
+        let alerts = self.alerts.clone();
+        let rolling = self.rolling.clone();
+        let trade_events = self.trade_events.clone();
+        let mut fast_path = self.fast_path.as_ref().map(SharedFastPath::publisher);
+        #[cfg(feature = "ipc")]
+        let events = self.events.clone();
+        let health = self.feed_health("trade_feed");
+
+        tokio::spawn(async move {
+            let mut trackers: HashMap<String, SequenceTracker<BinanceTrade>> = HashMap::new();
+
+            while let Some((endpoint, text)) = messages.recv().await {
+                shedder.record_queue_depth(messages.len());
+                throughput.record_market_messages(1);
+                let parsed = serde_json::from_str::<BinanceTrade>(&text);
+                observe_feed(&health, messages.len(), parsed.is_ok());
+                if let Ok(trade) = parsed {
+                    let latency_ms = clock.observe_event(trade.trade_time);
+                    endpoints.observe_message(endpoint, latency_ms);
+
+                    let symbol = trade.symbol.clone();
+                    let tracker = trackers
+                        .entry(symbol.clone())
+                        .or_insert_with(|| SequenceTracker::new(REORDER_WINDOW));
+
+                    let trades = match tracker.accept(trade.trade_id, trade.trade_id, trade) {
+                        Sequenced::Ready(trades) => trades,
+                        Sequenced::Gap => {
+                            // Missed trades cannot be replayed; continue from the next one
+                            tracing::warn!("Trade gap on {}, some trades were missed", symbol);
+                            Vec::new()
+                        }
+                        Sequenced::Duplicate | Sequenced::Buffered => Vec::new(),
+                    };
+
+                    stream_stats
+                        .write()
+                        .await
+                        .insert(format!("{}@trade", symbol.to_lowercase()), tracker.stats());
+
+                    for trade in trades {
+                        if let (Ok(price), Ok(quantity)) =
+                            (trade.price.parse::<f64>(), trade.quantity.parse::<f64>()) {
+
+                            // The taker is the seller when the buyer was resting
+                            let aggressor = if trade.buyer_is_maker {
+                                OrderSide::Sell
+                            } else {
+                                OrderSide::Buy
+                            };
+
+                            #[cfg(feature = "ipc")]
+                            if let Some(events) = &events {
+                                events.publish_trade(&trade.symbol, price, quantity);
+                            }
+
+                            let timestamp = clock.to_local(trade.trade_time)
+                                .unwrap_or_else(Utc::now);
+                            let print = TapeTrade {
+                                symbol: trade.symbol.as_str().into(),
+                                price: price.into(),
+                                quantity: quantity.into(),
+                                aggressor: Some(aggressor),
+                                source: TradeSource::Exchange,
+                                timestamp,
+                                maker_order_id: None,
+                                taker_order_id: None,
+                                metadata: Default::default(),
+                            };
+                            // Registered strategies see the trade ahead of indicators and the tape
+                            if let Some(fast_path) = &mut fast_path {
+                                fast_path.publish_trade(&print);
+                            }
+
+                            if let Some(indicators) = &indicators {
+                                indicators.on_trade(&trade.symbol, price, quantity, timestamp.timestamp_millis());
+                            }
+                            if let Some(anomalies) = &anomalies {
+                                anomalies.on_trade(&trade.symbol, price, quantity, timestamp.timestamp_millis());
+                            }
+                            if let Some(alerts) = &alerts {
+                                alerts.on_trade(&trade.symbol, price, timestamp.timestamp_millis());
+                            }
+                            if let Some(rolling) = &rolling {
+                                rolling.on_trade(&trade.symbol, price, quantity, timestamp.timestamp_millis());
+                            }
+
+                            if let Some(trade_events) = &trade_events {
+                                // Nobody listening is fine; trades stay on the tape
+                                let _ = trade_events.send(print.clone());
+                            }
+                            tape.record(print);
+                        }
                     }
                 }
-                tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+            }
+            if let Some(health) = &health {
+                health.set_running(false);
             }
         });
     }
 
+    /// Poll REST ticker and depth endpoints while the streams are down
+    ///
+    /// Once no stream message has arrived for `threshold`, prices and top of
+    /// book are polled every `interval` (at least one second) and marked
+    /// [`DataQuality::Degraded`]. Polling stops as soon as streams resume.
+    pub async fn start_rest_fallback(&self, threshold: Duration, interval: Duration) {
+        let symbols = self.symbols.clone();
+        let market_data = Arc::clone(&self.market_data);
+        let endpoints = self.endpoints.clone();
+        let interval = interval.max(MIN_POLL_INTERVAL);
+        let started = Instant::now();
+
+        tokio::spawn(async move {
+            let client = reqwest::Client::new();
+            let mut degraded = false;
+
+            loop {
+                tokio::time::sleep(interval).await;
+
+                let last_message = endpoints.last_message().unwrap_or(started);
+                let streams_down = last_message.elapsed() > threshold;
+                if streams_down != degraded {
+                    degraded = streams_down;
+                    if degraded {
+                        tracing::warn!("Streams silent for {:?}, falling back to REST polling", threshold);
+                    } else {
+                        tracing::info!("✓ Streams recovered, stopping REST polling");
+                    }
+                }
+                if !degraded {
+                    continue;
+                }
+
+                let updates = poll_rest(&client, &symbols).await;
+                let mut data = market_data.write().await;
+                for update in updates {
+                    match data.iter_mut().find(|m| m.symbol == update.symbol) {
+                        Some(md) => *md = update,
+                        None => data.push(update),
+                    }
+                }
+            }
+        });
+    }
+
+    /// Health and primary flag of each WebSocket endpoint
+    pub fn endpoint_status(&self) -> Vec<EndpointStatus> {
+        self.endpoints.status()
+    }
+
     /// Get current market data snapshot
     pub async fn get_market_data(&self) -> Vec<MarketData> {
         self.market_data.read().await.clone()
     }
 
+    /// Duplicate, reorder and gap counters for each stream
+    pub async fn get_stream_stats(&self) -> HashMap<String, SequenceStats> {
+        self.stream_stats.read().await.clone()
+    }
+
     /// Get market data for a specific symbol
     pub async fn get_symbol_data(&self, symbol: &str) -> Option<MarketData> {
         self.market_data
@@ -188,6 +867,75 @@ impl BinanceFeed {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_trade_message_parsing() {
+        let json = r#"{"e":"trade","E":1700000000001,"s":"BTCUSDT","t":1,"p":"43000.10","q":"0.015","T":1700000000000,"m":true}"#;
+        let trade: BinanceTrade = serde_json::from_str(json).unwrap();
+
+        assert_eq!(trade.symbol, "BTCUSDT");
+        assert_eq!(trade.price, "43000.10");
+        assert!(trade.buyer_is_maker);
+    }
+
+    #[test]
+    fn test_event_time_is_optional() {
+        let json = r#"{"e":"24hrTicker","E":1700000000001,"s":"BTCUSDT","c":"43000.10"}"#;
+        let ticker: BinanceTicker = serde_json::from_str(json).unwrap();
+        assert_eq!(ticker.event_time, Some(1700000000001));
+
+        let json = r#"{"s":"BTCUSDT","U":1,"u":2,"b":[["43000.0","1.0"]],"a":[]}"#;
+        let depth: BinanceDepth = serde_json::from_str(json).unwrap();
+        assert!(depth.event_time.is_none());
+
+        let time: BinanceServerTime = serde_json::from_str(r#"{"serverTime":1700000000000}"#).unwrap();
+        assert_eq!(time.server_time, 1700000000000);
+    }
+
+    #[test]
+    fn test_ticker_24h_stats() {
+        let json = r#"{"e":"24hrTicker","s":"BTCUSDT","c":"43000.10","o":"42000","h":"43500","l":"41800","v":"1234.5","q":"52000000","P":"2.381"}"#;
+        let ticker: BinanceTicker = serde_json::from_str(json).unwrap();
+        let stats = ticker.stats(43000.1, Utc::now()).unwrap();
+        assert_eq!((stats.open, stats.high, stats.low), (42000.0, 43500.0, 41800.0));
+        assert_eq!(stats.change_percent, 2.381);
+
+        // Mini tickers without every field carry no stats
+        let json = r#"{"s":"BTCUSDT","c":"43000.10","o":"42000"}"#;
+        let ticker: BinanceTicker = serde_json::from_str(json).unwrap();
+        assert!(ticker.stats(43000.1, Utc::now()).is_none());
+    }
+
+    #[test]
+    fn test_rest_price_parsing() {
+        let json = r#"[{"symbol":"BTCUSDT","price":"43000.10"},{"symbol":"ETHUSDT","price":"2300.5"}]"#;
+        let prices: Vec<BinancePrice> = serde_json::from_str(json).unwrap();
+
+        assert_eq!(prices.len(), 2);
+        assert_eq!(prices[1].symbol, "ETHUSDT");
+        assert_eq!(prices[1].price, "2300.5");
+    }
+
+    #[test]
+    fn test_depth_mirror_applies_diffs() {
+        let level = |p: &str, q: &str| [p.to_string(), q.to_string()];
+        let snapshot = BinanceDepthSnapshot {
+            last_update_id: 10,
+            bids: vec![level("99.0", "1.0"), level("98.0", "2.0")],
+            asks: vec![level("101.0", "1.0")],
+        };
+
+        let mut mirror = OrderBook::mirror("BTCUSDT");
+        mirror.apply_snapshot(&snapshot.book_update());
+        assert_eq!(mirror.best_bid(), Some(99.0));
+
+        mirror.apply_diff(&BookUpdate {
+            bids: parse_levels(&[level("99.0", "0")]),
+            asks: parse_levels(&[level("100.5", "0.3"), level("oops", "1")]),
+        });
+        assert_eq!(mirror.best_bid(), Some(98.0));
+        assert_eq!(mirror.best_ask(), Some(100.5));
+    }
+
     #[test]
     fn test_feed_creation() {
         let feed = BinanceFeed::new(vec!["BTCUSDT".to_string(), "ETHUSDT".to_string()]);
@@ -0,0 +1,216 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+
+/// One request/response exchange with the server clock
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClockSample {
+    /// Server time minus local time, in milliseconds
+    pub offset_ms: f64,
+    /// Round-trip time of the request, in milliseconds
+    pub rtt_ms: f64,
+}
+
+/// NTP-style estimator of the offset between the local and exchange clocks
+///
+/// Round-trip samples from the REST time endpoint are preferred; the sample
+/// with the smallest round trip has the tightest error bound and wins. Until
+/// one is available, the offset is bounded from event timestamps alone by
+/// assuming the fastest observed message had zero latency.
+#[derive(Debug)]
+pub struct ClockSync {
+    window: usize,
+    samples: VecDeque<ClockSample>,
+    /// Local receive time minus server event time, in milliseconds
+    event_delays: VecDeque<i64>,
+}
+
+impl ClockSync {
+    pub fn new(window: usize) -> Self {
+        Self {
+            window: window.max(1),
+            samples: VecDeque::new(),
+            event_delays: VecDeque::new(),
+        }
+    }
+
+    /// Record a server time read between local times `sent` and `received`
+    pub fn record_round_trip(
+        &mut self,
+        sent: DateTime<Utc>,
+        server_time_ms: i64,
+        received: DateTime<Utc>,
+    ) {
+        let sent_ms = sent.timestamp_millis() as f64;
+        let received_ms = received.timestamp_millis() as f64;
+        if received_ms < sent_ms {
+            return;
+        }
+
+        if self.samples.len() == self.window {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(ClockSample {
+            offset_ms: server_time_ms as f64 - (sent_ms + received_ms) / 2.0,
+            rtt_ms: received_ms - sent_ms,
+        });
+    }
+
+    /// Record a stream event stamped `event_time_ms` by the server
+    pub fn record_event(&mut self, event_time_ms: i64, received: DateTime<Utc>) {
+        if self.event_delays.len() == self.window {
+            self.event_delays.pop_front();
+        }
+        self.event_delays
+            .push_back(received.timestamp_millis() - event_time_ms);
+    }
+
+    /// Best current round-trip sample, if any
+    pub fn best_sample(&self) -> Option<ClockSample> {
+        self.samples
+            .iter()
+            .copied()
+            .min_by(|a, b| a.rtt_ms.total_cmp(&b.rtt_ms))
+    }
+
+    /// Server time minus local time, in milliseconds
+    pub fn offset_ms(&self) -> Option<f64> {
+        if let Some(sample) = self.best_sample() {
+            return Some(sample.offset_ms);
+        }
+        self.event_delays.iter().min().map(|delay| -(*delay as f64))
+    }
+
+    /// Convert a server timestamp to the local clock
+    pub fn to_local(&self, server_time_ms: i64) -> Option<DateTime<Utc>> {
+        let offset = self.offset_ms().unwrap_or(0.0).round() as i64;
+        DateTime::from_timestamp_millis(server_time_ms - offset)
+    }
+
+    /// Feed latency of an event, corrected for clock offset
+    pub fn latency_ms(&self, event_time_ms: i64, received: DateTime<Utc>) -> Option<f64> {
+        let offset = self.offset_ms()?;
+        Some(received.timestamp_millis() as f64 - (event_time_ms as f64 - offset))
+    }
+}
+
+/// Thread-safe wrapper for ClockSync
+pub struct SharedClockSync {
+    inner: Arc<Mutex<ClockSync>>,
+}
+
+impl SharedClockSync {
+    pub fn new(window: usize) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(ClockSync::new(window))),
+        }
+    }
+
+    pub fn record_round_trip(
+        &self,
+        sent: DateTime<Utc>,
+        server_time_ms: i64,
+        received: DateTime<Utc>,
+    ) {
+        self.inner
+            .lock()
+            .unwrap()
+            .record_round_trip(sent, server_time_ms, received)
+    }
+
+    pub fn record_event(&self, event_time_ms: i64, received: DateTime<Utc>) {
+        self.inner
+            .lock()
+            .unwrap()
+            .record_event(event_time_ms, received)
+    }
+
+    pub fn offset_ms(&self) -> Option<f64> {
+        self.inner.lock().unwrap().offset_ms()
+    }
+
+    pub fn to_local(&self, server_time_ms: i64) -> Option<DateTime<Utc>> {
+        self.inner.lock().unwrap().to_local(server_time_ms)
+    }
+
+    pub fn latency_ms(&self, event_time_ms: i64, received: DateTime<Utc>) -> Option<f64> {
+        self.inner
+            .lock()
+            .unwrap()
+            .latency_ms(event_time_ms, received)
+    }
+
+    /// Record an event and return its offset-corrected latency
+    pub fn observe_event(&self, event_time_ms: i64) -> Option<f64> {
+        let received = Utc::now();
+        let mut clock = self.inner.lock().unwrap();
+        clock.record_event(event_time_ms, received);
+        clock.latency_ms(event_time_ms, received)
+    }
+}
+
+impl Clone for SharedClockSync {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl Default for SharedClockSync {
+    fn default() -> Self {
+        Self::new(64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn at(base: DateTime<Utc>, ms: i64) -> DateTime<Utc> {
+        base + Duration::milliseconds(ms)
+    }
+
+    #[test]
+    fn test_min_rtt_sample_wins() {
+        let base = DateTime::from_timestamp_millis(1_700_000_000_000).unwrap();
+        let mut clock = ClockSync::new(8);
+
+        // Server is 250ms ahead; the slow sample has an asymmetric delay
+        let server = base.timestamp_millis() + 250;
+        clock.record_round_trip(base, server + 80, at(base, 100));
+        clock.record_round_trip(base, server + 5, at(base, 10));
+
+        let best = clock.best_sample().unwrap();
+        assert_eq!(best.rtt_ms, 10.0);
+        assert_eq!(clock.offset_ms(), Some(250.0));
+        assert_eq!(clock.to_local(server + 5), Some(at(base, 5)));
+    }
+
+    #[test]
+    fn test_event_times_bound_offset_without_round_trips() {
+        let base = DateTime::from_timestamp_millis(1_700_000_000_000).unwrap();
+        let mut clock = ClockSync::new(8);
+        assert!(clock.offset_ms().is_none());
+
+        // Server 100ms behind; the 12ms message is taken as instantaneous
+        let event = base.timestamp_millis() - 100;
+        clock.record_event(event, at(base, 30));
+        clock.record_event(event + 50, at(base, 62));
+
+        assert_eq!(clock.offset_ms(), Some(-112.0));
+        assert_eq!(clock.latency_ms(event, at(base, 30)), Some(18.0));
+    }
+
+    #[test]
+    fn test_window_evicts_old_samples() {
+        let base = Utc::now();
+        let mut clock = ClockSync::new(1);
+        clock.record_round_trip(base, base.timestamp_millis(), at(base, 2));
+        clock.record_round_trip(base, base.timestamp_millis() + 40, at(base, 20));
+
+        assert_eq!(clock.offset_ms(), Some(30.0));
+    }
+}
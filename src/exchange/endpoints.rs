@@ -0,0 +1,283 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+/// Public Binance spot WebSocket endpoints, primary first
+pub const BINANCE_STREAM_ENDPOINTS: [&str; 3] = [
+    "wss://stream.binance.com:9443",
+    "wss://stream.binance.com:443",
+    "wss://data-stream.binance.vision",
+];
+
+/// Score advantage a backup needs before it replaces the primary
+const SWITCH_MARGIN: f64 = 10.0;
+
+/// Weight of the newest sample in the latency moving average
+const LATENCY_ALPHA: f64 = 0.2;
+
+/// Observed health of one endpoint
+#[derive(Debug, Clone, Default)]
+struct EndpointHealth {
+    connected: bool,
+    last_message: Option<Instant>,
+    latency_ms: Option<f64>,
+    messages: u64,
+    /// Disconnects since the last message, reset once data flows again
+    failures: u32,
+}
+
+/// Point-in-time view of an endpoint for monitoring
+#[derive(Debug, Clone, Serialize)]
+pub struct EndpointStatus {
+    pub url: String,
+    pub primary: bool,
+    pub connected: bool,
+    pub score: f64,
+    pub latency_ms: Option<f64>,
+    pub messages: u64,
+}
+
+/// Set of redundant endpoints with health scoring and primary selection
+///
+/// The score starts at 100 for a connected endpoint and loses 10 points per
+/// second without messages, a point per 10ms of smoothed latency and 5
+/// points per recent failure. Disconnected endpoints score 0.
+#[derive(Debug)]
+pub struct EndpointPool {
+    urls: Vec<String>,
+    health: Vec<EndpointHealth>,
+    primary: usize,
+}
+
+impl EndpointPool {
+    pub fn new(urls: Vec<String>) -> Self {
+        let health = vec![EndpointHealth::default(); urls.len()];
+        Self {
+            urls,
+            health,
+            primary: 0,
+        }
+    }
+
+    pub fn urls(&self) -> &[String] {
+        &self.urls
+    }
+
+    pub fn primary(&self) -> usize {
+        self.primary
+    }
+
+    pub fn record_connect(&mut self, index: usize) {
+        if let Some(health) = self.health.get_mut(index) {
+            health.connected = true;
+        }
+    }
+
+    pub fn record_disconnect(&mut self, index: usize) {
+        if let Some(health) = self.health.get_mut(index) {
+            health.connected = false;
+            health.failures += 1;
+        }
+    }
+
+    pub fn record_message(&mut self, index: usize, latency_ms: Option<f64>, now: Instant) {
+        let Some(health) = self.health.get_mut(index) else {
+            return;
+        };
+        health.connected = true;
+        health.last_message = Some(now);
+        health.messages += 1;
+        health.failures = 0;
+
+        if let Some(latency) = latency_ms {
+            health.latency_ms = Some(match health.latency_ms {
+                Some(avg) => avg + LATENCY_ALPHA * (latency - avg),
+                None => latency,
+            });
+        }
+    }
+
+    pub fn score(&self, index: usize, now: Instant) -> f64 {
+        let Some(health) = self.health.get(index) else {
+            return 0.0;
+        };
+        if !health.connected {
+            return 0.0;
+        }
+
+        let staleness = health
+            .last_message
+            .map_or(Duration::ZERO, |at| now.saturating_duration_since(at));
+        let score = 100.0
+            - 10.0 * staleness.as_secs_f64()
+            - health.latency_ms.unwrap_or(0.0).max(0.0) / 10.0
+            - 5.0 * health.failures as f64;
+        score.max(0.0)
+    }
+
+    /// Promote the healthiest endpoint if it clearly beats the primary
+    ///
+    /// Returns true when the primary changed.
+    pub fn select_primary(&mut self, now: Instant) -> bool {
+        let Some(best) =
+            (0..self.urls.len()).max_by(|&a, &b| self.score(a, now).total_cmp(&self.score(b, now)))
+        else {
+            return false;
+        };
+
+        let current = self.score(self.primary, now);
+        let promote = self.score(best, now) > current + SWITCH_MARGIN
+            || (current == 0.0 && self.score(best, now) > 0.0);
+        if best != self.primary && promote {
+            self.primary = best;
+            return true;
+        }
+        false
+    }
+
+    /// Time of the latest message on any endpoint
+    pub fn last_message(&self) -> Option<Instant> {
+        self.health.iter().filter_map(|h| h.last_message).max()
+    }
+
+    pub fn status(&self, now: Instant) -> Vec<EndpointStatus> {
+        self.urls
+            .iter()
+            .zip(&self.health)
+            .enumerate()
+            .map(|(index, (url, health))| EndpointStatus {
+                url: url.clone(),
+                primary: index == self.primary,
+                connected: health.connected,
+                score: self.score(index, now),
+                latency_ms: health.latency_ms,
+                messages: health.messages,
+            })
+            .collect()
+    }
+}
+
+impl Default for EndpointPool {
+    fn default() -> Self {
+        Self::new(
+            BINANCE_STREAM_ENDPOINTS
+                .iter()
+                .map(|url| url.to_string())
+                .collect(),
+        )
+    }
+}
+
+/// Thread-safe wrapper for EndpointPool
+pub struct SharedEndpointPool {
+    inner: Arc<Mutex<EndpointPool>>,
+}
+
+impl SharedEndpointPool {
+    pub fn new(urls: Vec<String>) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(EndpointPool::new(urls))),
+        }
+    }
+
+    pub fn urls(&self) -> Vec<String> {
+        self.inner.lock().unwrap().urls().to_vec()
+    }
+
+    pub fn primary(&self) -> usize {
+        self.inner.lock().unwrap().primary()
+    }
+
+    pub fn record_connect(&self, index: usize) {
+        self.inner.lock().unwrap().record_connect(index)
+    }
+
+    pub fn record_disconnect(&self, index: usize) {
+        self.inner.lock().unwrap().record_disconnect(index)
+    }
+
+    /// Record a message and return whether `index` is now the primary
+    pub fn observe_message(&self, index: usize, latency_ms: Option<f64>) -> bool {
+        let now = Instant::now();
+        let mut pool = self.inner.lock().unwrap();
+        pool.record_message(index, latency_ms, now);
+        if pool.select_primary(now) {
+            tracing::warn!("Switched primary endpoint to {}", pool.urls[pool.primary]);
+        }
+        pool.primary() == index
+    }
+
+    pub fn last_message(&self) -> Option<Instant> {
+        self.inner.lock().unwrap().last_message()
+    }
+
+    pub fn status(&self) -> Vec<EndpointStatus> {
+        self.inner.lock().unwrap().status(Instant::now())
+    }
+}
+
+impl Clone for SharedEndpointPool {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl Default for SharedEndpointPool {
+    fn default() -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(EndpointPool::default())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool() -> EndpointPool {
+        EndpointPool::new(vec!["wss://a".to_string(), "wss://b".to_string()])
+    }
+
+    #[test]
+    fn test_stale_primary_fails_over() {
+        let start = Instant::now();
+        let mut pool = pool();
+        pool.record_message(0, Some(20.0), start);
+        pool.record_message(1, Some(25.0), start);
+        assert!(!pool.select_primary(start));
+
+        // Primary goes quiet while the backup keeps streaming
+        let later = start + Duration::from_secs(3);
+        pool.record_message(1, Some(25.0), later);
+        assert!(pool.select_primary(later));
+        assert_eq!(pool.primary(), 1);
+        assert_eq!(pool.last_message(), Some(later));
+    }
+
+    #[test]
+    fn test_small_advantage_does_not_flap() {
+        let now = Instant::now();
+        let mut pool = pool();
+        pool.record_message(0, Some(60.0), now);
+        pool.record_message(1, Some(10.0), now);
+
+        assert!(!pool.select_primary(now));
+        assert_eq!(pool.primary(), 0);
+    }
+
+    #[test]
+    fn test_disconnected_primary_is_replaced() {
+        let now = Instant::now();
+        let mut pool = pool();
+        pool.record_message(0, None, now);
+        pool.record_message(1, None, now);
+        pool.record_disconnect(0);
+
+        assert_eq!(pool.score(0, now), 0.0);
+        assert!(pool.select_primary(now));
+        assert!(pool.status(now)[1].primary);
+    }
+}
@@ -1,3 +1,11 @@
 pub mod binance;
+pub mod clock;
+pub mod endpoints;
+pub mod rates;
+pub mod sequence;
 
-pub use binance::{BinanceFeed, MarketData};
+pub use binance::{BinanceFeed, DataQuality, MarketData};
+pub use clock::{ClockSample, ClockSync, SharedClockSync};
+pub use endpoints::{EndpointPool, EndpointStatus, SharedEndpointPool};
+pub use rates::{RateFeed, RateSample};
+pub use sequence::{SequenceStats, SequenceTracker, Sequenced};
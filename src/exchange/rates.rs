@@ -0,0 +1,207 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::time::Duration;
+
+use crate::timeseries::SharedTimeSeriesStore;
+use crate::types::Symbol;
+
+const FUNDING_RATE_URL: &str = "https://fapi.binance.com/fapi/v1/fundingRate";
+const BORROW_RATE_URL: &str = "https://api.binance.com/sapi/v1/margin/interestRateHistory";
+
+/// Funding and borrow rates fetched per request
+const RATE_HISTORY_LIMIT: usize = 100;
+
+/// Entry of the futures funding rate history endpoint
+#[derive(Debug, Deserialize)]
+struct BinanceFundingRate {
+    symbol: String,
+    #[serde(rename = "fundingRate")]
+    funding_rate: String,
+    #[serde(rename = "fundingTime")]
+    funding_time: i64,
+}
+
+/// Entry of the margin interest rate history endpoint
+#[derive(Debug, Deserialize)]
+struct BinanceBorrowRate {
+    asset: String,
+    #[serde(rename = "dailyInterestRate")]
+    daily_interest_rate: String,
+    timestamp: i64,
+}
+
+/// One funding or borrow rate, as a fraction
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateSample {
+    /// Symbol for a funding rate, asset for a borrow rate
+    pub key: String,
+    pub rate: f64,
+    pub at: DateTime<Utc>,
+}
+
+fn sample(key: String, rate: &str, at_ms: i64) -> Option<RateSample> {
+    Some(RateSample {
+        key,
+        rate: rate.parse().ok()?,
+        at: DateTime::from_timestamp_millis(at_ms)?,
+    })
+}
+
+/// Funding rates of a funding rate history response, oldest first
+pub fn parse_funding_rates(text: &str) -> Result<Vec<RateSample>, serde_json::Error> {
+    let rates: Vec<BinanceFundingRate> = serde_json::from_str(text)?;
+    let mut samples: Vec<_> = rates
+        .into_iter()
+        .filter_map(|r| sample(r.symbol, &r.funding_rate, r.funding_time))
+        .collect();
+    samples.sort_by_key(|s| s.at);
+    Ok(samples)
+}
+
+/// Daily borrow rates of an interest rate history response, oldest first
+pub fn parse_borrow_rates(text: &str) -> Result<Vec<RateSample>, serde_json::Error> {
+    let rates: Vec<BinanceBorrowRate> = serde_json::from_str(text)?;
+    let mut samples: Vec<_> = rates
+        .into_iter()
+        .filter_map(|r| sample(r.asset, &r.daily_interest_rate, r.timestamp))
+        .collect();
+    samples.sort_by_key(|s| s.at);
+    Ok(samples)
+}
+
+/// Polls perpetual funding rates and margin borrow rates into the
+/// time-series store, as `funding:<symbol>` and `borrow:<asset>`
+///
+/// Funding settles every eight hours and borrow rates change hourly, so a
+/// poll every few minutes is plenty. The interest rate history is a user
+/// data endpoint: borrow rates are only fetched with an API key, or from a
+/// proxy set with `with_borrow_url`.
+pub struct RateFeed {
+    symbols: Vec<Symbol>,
+    assets: Vec<String>,
+    store: SharedTimeSeriesStore,
+    funding_url: String,
+    borrow_url: String,
+    api_key: Option<String>,
+}
+
+impl RateFeed {
+    pub fn new(store: SharedTimeSeriesStore) -> Self {
+        Self {
+            symbols: Vec::new(),
+            assets: Vec::new(),
+            store,
+            funding_url: FUNDING_RATE_URL.to_string(),
+            borrow_url: BORROW_RATE_URL.to_string(),
+            api_key: None,
+        }
+    }
+
+    /// Perpetuals to fetch funding rates of
+    pub fn with_funding(mut self, symbols: Vec<Symbol>) -> Self {
+        self.symbols = symbols;
+        self
+    }
+
+    /// Assets to fetch margin borrow rates of
+    pub fn with_borrow(mut self, assets: Vec<String>) -> Self {
+        self.assets = assets;
+        self
+    }
+
+    pub fn with_funding_url(mut self, url: impl Into<String>) -> Self {
+        self.funding_url = url.into();
+        self
+    }
+
+    pub fn with_borrow_url(mut self, url: impl Into<String>) -> Self {
+        self.borrow_url = url.into();
+        self
+    }
+
+    /// Sent as `X-MBX-APIKEY` with borrow rate requests
+    pub fn with_api_key(mut self, key: impl Into<String>) -> Self {
+        self.api_key = Some(key.into());
+        self
+    }
+
+    /// Fetch every configured rate every `interval`
+    pub async fn start(self, interval: Duration) {
+        tokio::spawn(async move {
+            let client = reqwest::Client::new();
+            loop {
+                for symbol in &self.symbols {
+                    let request = client.get(&self.funding_url).query(&[
+                        ("symbol", symbol.to_string()),
+                        ("limit", RATE_HISTORY_LIMIT.to_string()),
+                    ]);
+                    match fetch(request, parse_funding_rates).await {
+                        Ok(rates) => {
+                            for rate in rates {
+                                self.store.record_funding(&rate.key, rate.rate, rate.at);
+                            }
+                        }
+                        Err(e) => tracing::warn!("Funding rates of {} failed: {}", symbol, e),
+                    }
+                }
+                let borrow = self.api_key.is_some() || self.borrow_url != BORROW_RATE_URL;
+                for asset in self.assets.iter().filter(|_| borrow) {
+                    let mut request = client.get(&self.borrow_url).query(&[
+                        ("asset", asset.clone()),
+                        ("limit", RATE_HISTORY_LIMIT.to_string()),
+                    ]);
+                    if let Some(key) = &self.api_key {
+                        request = request.header("X-MBX-APIKEY", key);
+                    }
+                    match fetch(request, parse_borrow_rates).await {
+                        Ok(rates) => {
+                            for rate in rates {
+                                self.store.record_borrow(&rate.key, rate.rate, rate.at);
+                            }
+                        }
+                        Err(e) => tracing::warn!("Borrow rates of {} failed: {}", asset, e),
+                    }
+                }
+                tokio::time::sleep(interval).await;
+            }
+        });
+    }
+}
+
+async fn fetch(
+    request: reqwest::RequestBuilder,
+    parse: fn(&str) -> Result<Vec<RateSample>, serde_json::Error>,
+) -> Result<Vec<RateSample>, String> {
+    let response = request.send().await.map_err(|e| e.to_string())?;
+    let response = response.error_for_status().map_err(|e| e.to_string())?;
+    let text = response.text().await.map_err(|e| e.to_string())?;
+    parse(&text).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rate_histories() {
+        let funding = parse_funding_rates(
+            r#"[{"symbol":"BTCUSDT","fundingRate":"0.00020000","fundingTime":1700028800000,"markPrice":"37000.1"},
+                {"symbol":"BTCUSDT","fundingRate":"0.00010000","fundingTime":1700000000000,"markPrice":"36900.0"},
+                {"symbol":"BTCUSDT","fundingRate":"n/a","fundingTime":1700057600000,"markPrice":"37100.0"}]"#,
+        )
+        .unwrap();
+        assert_eq!(funding.len(), 2);
+        assert_eq!(
+            (funding[0].key.as_str(), funding[0].rate),
+            ("BTCUSDT", 0.0001)
+        );
+        assert!(funding[0].at < funding[1].at);
+
+        let borrow = parse_borrow_rates(
+            r#"[{"asset":"BTC","dailyInterestRate":"0.00025000","timestamp":1700000000000,"vipLevel":0}]"#,
+        )
+        .unwrap();
+        assert_eq!((borrow[0].key.as_str(), borrow[0].rate), ("BTC", 0.00025));
+        assert!(parse_borrow_rates(r#"{"code":-2015,"msg":"Invalid API-key"}"#).is_err());
+    }
+}
@@ -0,0 +1,170 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+/// Per-stream counters of sequencing decisions
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct SequenceStats {
+    /// Messages passed on in order
+    pub delivered: u64,
+    /// Messages dropped because they were already seen
+    pub duplicates: u64,
+    /// Messages that arrived early and were delivered from the buffer
+    pub reordered: u64,
+    /// Irrecoverable gaps, each requiring a resnapshot
+    pub gaps: u64,
+}
+
+/// What to do with a message after sequencing
+#[derive(Debug, PartialEq)]
+pub enum Sequenced<T> {
+    /// Messages now deliverable, in order
+    Ready(Vec<T>),
+    /// Already seen; drop it
+    Duplicate,
+    /// Arrived early; held until the gap before it fills
+    Buffered,
+    /// The buffer overflowed; the stream must be resnapshotted
+    Gap,
+}
+
+/// Orders messages carrying inclusive update-id ranges `[first, last]`
+///
+/// Binance diff depth events carry `U..=u`; trades carry a single id, so
+/// `first == last`. A message overlapping the next expected id is applied,
+/// one entirely behind it is a duplicate and one ahead of it is buffered
+/// until at most `window` messages are pending.
+#[derive(Debug)]
+pub struct SequenceTracker<T> {
+    window: usize,
+    next: Option<u64>,
+    pending: BTreeMap<u64, (u64, T)>,
+    stats: SequenceStats,
+}
+
+impl<T> SequenceTracker<T> {
+    pub fn new(window: usize) -> Self {
+        Self {
+            window,
+            next: None,
+            pending: BTreeMap::new(),
+            stats: SequenceStats::default(),
+        }
+    }
+
+    /// Whether a starting point is known
+    pub fn is_synced(&self) -> bool {
+        self.next.is_some()
+    }
+
+    /// Restart from a snapshot whose last applied id is `last_id`
+    pub fn resync(&mut self, last_id: u64) {
+        self.next = Some(last_id + 1);
+        self.pending.retain(|_, (last, _)| *last > last_id);
+    }
+
+    /// Forget the position, e.g. after a reconnect; counters are kept
+    pub fn reset(&mut self) {
+        self.next = None;
+        self.pending.clear();
+    }
+
+    pub fn stats(&self) -> SequenceStats {
+        self.stats
+    }
+
+    /// Sequence a message; an unsynced tracker starts from the first message seen
+    pub fn accept(&mut self, first: u64, last: u64, message: T) -> Sequenced<T> {
+        let next = *self.next.get_or_insert(first);
+
+        if last < next || self.pending.contains_key(&first) {
+            self.stats.duplicates += 1;
+            return Sequenced::Duplicate;
+        }
+
+        if first > next {
+            self.pending.insert(first, (last, message));
+            if self.pending.len() > self.window {
+                self.stats.gaps += 1;
+                self.reset();
+                return Sequenced::Gap;
+            }
+            return Sequenced::Buffered;
+        }
+
+        let mut ready = vec![message];
+        self.next = Some(last + 1);
+        self.stats.delivered += 1;
+        self.drain_pending(&mut ready);
+        Sequenced::Ready(ready)
+    }
+
+    fn drain_pending(&mut self, ready: &mut Vec<T>) {
+        while let Some(entry) = self.pending.first_entry() {
+            let next = self.next.unwrap_or(0);
+            let (first, (last, _)) = (*entry.key(), entry.get());
+            if first > next {
+                break;
+            }
+
+            let last = *last;
+            let (_, message) = entry.remove();
+            if last < next {
+                self.stats.duplicates += 1;
+                continue;
+            }
+
+            ready.push(message);
+            self.next = Some(last + 1);
+            self.stats.delivered += 1;
+            self.stats.reordered += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_in_order_and_duplicates() {
+        let mut tracker = SequenceTracker::new(4);
+        assert_eq!(tracker.accept(1, 3, "a"), Sequenced::Ready(vec!["a"]));
+        assert_eq!(tracker.accept(4, 4, "b"), Sequenced::Ready(vec!["b"]));
+        assert_eq!(tracker.accept(2, 4, "dup"), Sequenced::Duplicate);
+        // Overlapping the next id is still applied
+        assert_eq!(tracker.accept(3, 6, "c"), Sequenced::Ready(vec!["c"]));
+
+        let stats = tracker.stats();
+        assert_eq!(stats.delivered, 3);
+        assert_eq!(stats.duplicates, 1);
+    }
+
+    #[test]
+    fn test_buffers_out_of_order_window() {
+        let mut tracker = SequenceTracker::new(4);
+        tracker.accept(10, 10, 10);
+        assert_eq!(tracker.accept(13, 13, 13), Sequenced::Buffered);
+        assert_eq!(tracker.accept(12, 12, 12), Sequenced::Buffered);
+        assert_eq!(tracker.accept(12, 12, 12), Sequenced::Duplicate);
+        assert_eq!(
+            tracker.accept(11, 11, 11),
+            Sequenced::Ready(vec![11, 12, 13])
+        );
+        assert_eq!(tracker.stats().reordered, 2);
+    }
+
+    #[test]
+    fn test_gap_requires_resync() {
+        let mut tracker = SequenceTracker::new(1);
+        tracker.accept(1, 1, 1);
+        assert_eq!(tracker.accept(5, 5, 5), Sequenced::Buffered);
+        assert_eq!(tracker.accept(6, 6, 6), Sequenced::Gap);
+        assert!(!tracker.is_synced());
+        assert_eq!(tracker.stats().gaps, 1);
+
+        tracker.resync(6);
+        assert_eq!(tracker.accept(5, 6, 6), Sequenced::Duplicate);
+        assert_eq!(tracker.accept(7, 8, 8), Sequenced::Ready(vec![8]));
+    }
+}
@@ -0,0 +1,368 @@
+// C ABI for embedding the matching engine, enabled with the `ffi` feature
+//
+// The generated header lives in `include/crypto_orderbook.h` (regenerated by
+// build.rs via cbindgen). A book handle is not thread-safe; callers must
+// serialize access to it.
+
+use std::ffi::{c_char, c_void, CStr};
+use std::ptr;
+
+use crate::orderbook::OrderBook;
+use crate::types::{Order, OrderId, OrderSide, Price, Qty, Trade};
+
+/// Order side
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObSide {
+    Buy = 0,
+    Sell = 1,
+}
+
+impl From<ObSide> for OrderSide {
+    fn from(side: ObSide) -> Self {
+        match side {
+            ObSide::Buy => OrderSide::Buy,
+            ObSide::Sell => OrderSide::Sell,
+        }
+    }
+}
+
+/// One price level
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ObLevel {
+    pub price: f64,
+    pub quantity: f64,
+}
+
+/// Trade reported through the trade callback
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ObTrade {
+    pub maker_order_id: u64,
+    pub taker_order_id: u64,
+    pub price: f64,
+    pub quantity: f64,
+    /// Milliseconds since the Unix epoch
+    pub timestamp_ms: i64,
+}
+
+impl From<&Trade> for ObTrade {
+    fn from(trade: &Trade) -> Self {
+        Self {
+            maker_order_id: trade.maker_order_id.0,
+            taker_order_id: trade.taker_order_id.0,
+            price: trade.price.value(),
+            quantity: trade.quantity.value(),
+            timestamp_ms: trade.timestamp.timestamp_millis(),
+        }
+    }
+}
+
+/// Called once per trade generated by a submission; NULL disables the callback
+pub type ObTradeCallback = Option<extern "C" fn(trade: *const ObTrade, user_data: *mut c_void)>;
+
+/// Called with a depth snapshot; level pointers are only valid during the call
+pub type ObSnapshotCallback = extern "C" fn(
+    bids: *const ObLevel,
+    bid_count: usize,
+    asks: *const ObLevel,
+    ask_count: usize,
+    user_data: *mut c_void,
+);
+
+/// Opaque order book handle
+pub struct ObOrderBook {
+    book: OrderBook,
+    on_trade: ObTradeCallback,
+    user_data: *mut c_void,
+}
+
+impl ObOrderBook {
+    fn submit(&mut self, order: Order) -> u64 {
+        let id = order.id.0;
+        let trades = self.book.add_order(order);
+
+        if let Some(callback) = self.on_trade {
+            for trade in &trades {
+                let trade = ObTrade::from(trade);
+                callback(&trade, self.user_data);
+            }
+        }
+
+        id
+    }
+}
+
+/// Create a book for `symbol` (NUL-terminated UTF-8). Returns NULL on invalid input.
+///
+/// # Safety
+/// `symbol` must be NULL or a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn ob_book_new(symbol: *const c_char) -> *mut ObOrderBook {
+    if symbol.is_null() {
+        return ptr::null_mut();
+    }
+    let Ok(symbol) = CStr::from_ptr(symbol).to_str() else {
+        return ptr::null_mut();
+    };
+
+    Box::into_raw(Box::new(ObOrderBook {
+        book: OrderBook::new(symbol.to_string()),
+        on_trade: None,
+        user_data: ptr::null_mut(),
+    }))
+}
+
+/// Destroy a book created by `ob_book_new`
+///
+/// # Safety
+/// `book` must be NULL or a handle from `ob_book_new` not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn ob_book_free(book: *mut ObOrderBook) {
+    if !book.is_null() {
+        drop(Box::from_raw(book));
+    }
+}
+
+/// Register (or clear, with NULL) the callback invoked for each trade
+///
+/// # Safety
+/// `book` must be a live handle; `user_data` is passed through untouched.
+#[no_mangle]
+pub unsafe extern "C" fn ob_set_trade_callback(
+    book: *mut ObOrderBook,
+    callback: ObTradeCallback,
+    user_data: *mut c_void,
+) {
+    if let Some(book) = book.as_mut() {
+        book.on_trade = callback;
+        book.user_data = user_data;
+    }
+}
+
+/// Submit a limit order. Returns the order id, or 0 on invalid input.
+///
+/// # Safety
+/// `book` must be a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn ob_submit_limit(
+    book: *mut ObOrderBook,
+    side: ObSide,
+    price: f64,
+    quantity: f64,
+) -> u64 {
+    let Some(book) = book.as_mut() else {
+        return 0;
+    };
+    let (Some(price), Some(quantity)) = (Price::try_positive(price), Qty::try_positive(quantity))
+    else {
+        return 0;
+    };
+
+    let order = Order::new_limit(book.book.symbol.clone(), side.into(), price, quantity);
+    book.submit(order)
+}
+
+/// Submit a market order. Returns the order id, or 0 on invalid input.
+///
+/// # Safety
+/// `book` must be a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn ob_submit_market(
+    book: *mut ObOrderBook,
+    side: ObSide,
+    quantity: f64,
+) -> u64 {
+    let Some(book) = book.as_mut() else {
+        return 0;
+    };
+    let Some(quantity) = Qty::try_positive(quantity) else {
+        return 0;
+    };
+
+    let order = Order::new_market(book.book.symbol.clone(), side.into(), quantity);
+    book.submit(order)
+}
+
+/// Cancel a resting order. Returns true if it was found.
+///
+/// # Safety
+/// `book` must be a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn ob_cancel(book: *mut ObOrderBook, order_id: u64) -> bool {
+    match book.as_mut() {
+        Some(book) => book.book.cancel_order(OrderId(order_id)).is_some(),
+        None => false,
+    }
+}
+
+/// Copy up to `max_levels` levels of one side into `out`, best price first.
+/// Returns the number of levels written.
+///
+/// # Safety
+/// `book` must be a live handle and `out` must point to `max_levels` writable levels.
+#[no_mangle]
+pub unsafe extern "C" fn ob_depth(
+    book: *const ObOrderBook,
+    side: ObSide,
+    out: *mut ObLevel,
+    max_levels: usize,
+) -> usize {
+    let Some(book) = book.as_ref() else {
+        return 0;
+    };
+    if out.is_null() {
+        return 0;
+    }
+
+    let (bids, asks) = book.book.get_depth(max_levels);
+    let levels = match side {
+        ObSide::Buy => bids,
+        ObSide::Sell => asks,
+    };
+
+    for (i, &(price, quantity)) in levels.iter().enumerate() {
+        out.add(i).write(ObLevel { price, quantity });
+    }
+    levels.len()
+}
+
+/// Invoke `callback` with the top `levels` of both sides
+///
+/// # Safety
+/// `book` must be a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn ob_snapshot(
+    book: *const ObOrderBook,
+    levels: usize,
+    callback: ObSnapshotCallback,
+    user_data: *mut c_void,
+) {
+    let Some(book) = book.as_ref() else {
+        return;
+    };
+
+    let (bids, asks) = book.book.get_depth(levels);
+    let to_levels = |side: Vec<(f64, f64)>| -> Vec<ObLevel> {
+        side.into_iter()
+            .map(|(price, quantity)| ObLevel { price, quantity })
+            .collect()
+    };
+    let (bids, asks) = (to_levels(bids), to_levels(asks));
+
+    callback(
+        bids.as_ptr(),
+        bids.len(),
+        asks.as_ptr(),
+        asks.len(),
+        user_data,
+    );
+}
+
+/// Best bid price, or NaN if the bid side is empty
+///
+/// # Safety
+/// `book` must be a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn ob_best_bid(book: *const ObOrderBook) -> f64 {
+    book.as_ref()
+        .and_then(|b| b.book.best_bid())
+        .unwrap_or(f64::NAN)
+}
+
+/// Best ask price, or NaN if the ask side is empty
+///
+/// # Safety
+/// `book` must be a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn ob_best_ask(book: *const ObOrderBook) -> f64 {
+    book.as_ref()
+        .and_then(|b| b.book.best_ask())
+        .unwrap_or(f64::NAN)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CString;
+
+    extern "C" fn collect_trades(trade: *const ObTrade, user_data: *mut c_void) {
+        let trades = unsafe { &mut *(user_data as *mut Vec<ObTrade>) };
+        trades.push(unsafe { *trade });
+    }
+
+    extern "C" fn count_levels(
+        _bids: *const ObLevel,
+        bid_count: usize,
+        _asks: *const ObLevel,
+        ask_count: usize,
+        user_data: *mut c_void,
+    ) {
+        let counts = unsafe { &mut *(user_data as *mut (usize, usize)) };
+        *counts = (bid_count, ask_count);
+    }
+
+    #[test]
+    fn test_c_api_round_trip() {
+        let symbol = CString::new("BTCUSDT").unwrap();
+        let mut trades: Vec<ObTrade> = Vec::new();
+
+        unsafe {
+            let book = ob_book_new(symbol.as_ptr());
+            assert!(!book.is_null());
+            ob_set_trade_callback(
+                book,
+                Some(collect_trades),
+                &mut trades as *mut _ as *mut c_void,
+            );
+
+            let sell = ob_submit_limit(book, ObSide::Sell, 101.0, 1.0);
+            ob_submit_limit(book, ObSide::Buy, 99.0, 2.0);
+            assert_ne!(sell, 0);
+            assert_eq!(ob_best_ask(book), 101.0);
+
+            let taker = ob_submit_market(book, ObSide::Buy, 0.4);
+            assert_eq!(trades.len(), 1);
+            assert_eq!(trades[0].maker_order_id, sell);
+            assert_eq!(trades[0].taker_order_id, taker);
+
+            let mut bids = [ObLevel::default(); 4];
+            assert_eq!(ob_depth(book, ObSide::Buy, bids.as_mut_ptr(), 4), 1);
+            assert_eq!(
+                bids[0],
+                ObLevel {
+                    price: 99.0,
+                    quantity: 2.0
+                }
+            );
+
+            let mut counts = (0usize, 0usize);
+            ob_snapshot(book, 5, count_levels, &mut counts as *mut _ as *mut c_void);
+            assert_eq!(counts, (1, 1));
+
+            assert!(ob_cancel(book, sell));
+            assert!(ob_best_ask(book).is_nan());
+
+            ob_book_free(book);
+        }
+    }
+
+    #[test]
+    fn test_rejects_invalid_input() {
+        unsafe {
+            assert!(ob_book_new(ptr::null()).is_null());
+            assert_eq!(ob_submit_limit(ptr::null_mut(), ObSide::Buy, 1.0, 1.0), 0);
+
+            let symbol = CString::new("ETHUSDT").unwrap();
+            let book = ob_book_new(symbol.as_ptr());
+            assert_eq!(ob_submit_limit(book, ObSide::Buy, -1.0, 1.0), 0);
+            assert_eq!(ob_submit_market(book, ObSide::Buy, 0.0), 0);
+            assert_eq!(ob_submit_limit(book, ObSide::Buy, f64::INFINITY, 1.0), 0);
+            assert_eq!(ob_submit_limit(book, ObSide::Buy, 1.0, f64::NAN), 0);
+            assert_eq!(ob_submit_market(book, ObSide::Buy, f64::INFINITY), 0);
+            assert!(ob_best_bid(book).is_nan());
+            ob_book_free(book);
+        }
+    }
+}
@@ -0,0 +1,311 @@
+// Service health from live state
+//
+// Each long-running service holds a handle it updates as it works: whether
+// it is running, when it last did something, how much is queued for it and
+// how many errors it hit. Rules registered with the service turn those into
+// a status, and dependencies between services decide how far a failure
+// spreads: a service whose dependency is unhealthy is at best degraded, and
+// the process as a whole is down once any critical service is.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Health of a service or of the whole process, worst last
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+    Healthy,
+    Degraded,
+    Down,
+}
+
+/// When a service stops counting as healthy
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ServiceRule {
+    /// Whether the process is down when this service is
+    pub critical: bool,
+    /// Degraded after this long without activity, e.g. a stale feed
+    pub max_idle: Option<Duration>,
+    /// Degraded while more than this is queued
+    pub max_backlog: Option<usize>,
+    /// Degraded once this many errors were counted
+    pub max_errors: Option<u64>,
+    /// Services this one needs to be healthy itself
+    pub depends_on: Vec<String>,
+}
+
+impl ServiceRule {
+    pub fn critical(mut self) -> Self {
+        self.critical = true;
+        self
+    }
+
+    pub fn with_max_idle(mut self, max_idle: Duration) -> Self {
+        self.max_idle = Some(max_idle);
+        self
+    }
+
+    pub fn with_max_backlog(mut self, max_backlog: usize) -> Self {
+        self.max_backlog = Some(max_backlog);
+        self
+    }
+
+    pub fn with_max_errors(mut self, max_errors: u64) -> Self {
+        self.max_errors = Some(max_errors);
+        self
+    }
+
+    pub fn depends_on(mut self, service: &str) -> Self {
+        self.depends_on.push(service.to_string());
+        self
+    }
+}
+
+/// Live state a service reports about itself; cheap to update from hot loops
+#[derive(Debug, Default)]
+pub struct ServiceState {
+    running: AtomicBool,
+    /// Milliseconds since the epoch; 0 until the first activity
+    last_activity_ms: AtomicI64,
+    backlog: AtomicUsize,
+    errors: AtomicU64,
+}
+
+impl ServiceState {
+    pub fn set_running(&self, running: bool) {
+        self.running.store(running, Ordering::Relaxed);
+    }
+
+    pub fn record_activity(&self) {
+        self.last_activity_ms
+            .store(Utc::now().timestamp_millis(), Ordering::Relaxed);
+    }
+
+    pub fn set_backlog(&self, backlog: usize) {
+        self.backlog.store(backlog, Ordering::Relaxed);
+    }
+
+    pub fn record_error(&self) {
+        self.errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Forget counted errors, e.g. after a successful reconnect
+    pub fn clear_errors(&self) {
+        self.errors.store(0, Ordering::Relaxed);
+    }
+
+    /// Whether the service is running and has done anything yet
+    pub fn is_active(&self) -> bool {
+        self.running.load(Ordering::Relaxed) && self.last_activity_ms.load(Ordering::Relaxed) > 0
+    }
+}
+
+/// Handle a service keeps to report its state
+pub type ServiceHandle = Arc<ServiceState>;
+
+/// One service as of a health check
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ServiceReport {
+    pub name: String,
+    pub status: HealthStatus,
+    pub critical: bool,
+    pub running: bool,
+    pub last_activity: Option<DateTime<Utc>>,
+    pub idle_ms: Option<i64>,
+    pub backlog: usize,
+    pub errors: u64,
+    /// Why the service is not healthy
+    pub reasons: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HealthReport {
+    pub status: HealthStatus,
+    pub services: Vec<ServiceReport>,
+    pub checked_at: DateTime<Utc>,
+}
+
+/// Every registered service, by name
+#[derive(Debug, Clone, Default)]
+pub struct HealthRegistry {
+    services: Arc<Mutex<BTreeMap<String, (ServiceRule, ServiceHandle)>>>,
+}
+
+impl HealthRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register `name` as running and return its handle
+    ///
+    /// Registering a name again replaces its rule and hands out a fresh state.
+    pub fn register(&self, name: &str, rule: ServiceRule) -> ServiceHandle {
+        let state = ServiceHandle::default();
+        state.set_running(true);
+        self.services
+            .lock()
+            .unwrap()
+            .insert(name.to_string(), (rule, Arc::clone(&state)));
+        state
+    }
+
+    pub fn check(&self) -> HealthReport {
+        self.check_at(Utc::now())
+    }
+
+    pub fn check_at(&self, now: DateTime<Utc>) -> HealthReport {
+        let services = self.services.lock().unwrap();
+        let mut reports: BTreeMap<&str, ServiceReport> = services
+            .iter()
+            .map(|(name, (rule, state))| (name.as_str(), own_status(name, rule, state, now)))
+            .collect();
+
+        // Spread failures along dependencies until nothing changes; bounded
+        // by the service count so a dependency cycle cannot loop forever
+        for _ in 0..services.len() {
+            let mut changed = false;
+            for (name, (rule, _)) in services.iter() {
+                for dependency in &rule.depends_on {
+                    let dependency_status = match reports.get(dependency.as_str()) {
+                        Some(report) => report.status,
+                        None => HealthStatus::Down,
+                    };
+                    let report = reports.get_mut(name.as_str()).unwrap();
+                    if dependency_status != HealthStatus::Healthy
+                        && report.status == HealthStatus::Healthy
+                    {
+                        report.status = HealthStatus::Degraded;
+                        report.reasons.push(format!(
+                            "dependency {} is {:?}",
+                            dependency, dependency_status
+                        ));
+                        changed = true;
+                    }
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        let services: Vec<ServiceReport> = reports.into_values().collect();
+        let status = if services
+            .iter()
+            .any(|s| s.critical && s.status == HealthStatus::Down)
+        {
+            HealthStatus::Down
+        } else if services.iter().any(|s| s.status != HealthStatus::Healthy) {
+            HealthStatus::Degraded
+        } else {
+            HealthStatus::Healthy
+        };
+        HealthReport {
+            status,
+            services,
+            checked_at: now,
+        }
+    }
+}
+
+fn own_status(
+    name: &str,
+    rule: &ServiceRule,
+    state: &ServiceState,
+    now: DateTime<Utc>,
+) -> ServiceReport {
+    let running = state.running.load(Ordering::Relaxed);
+    let last_activity_ms = state.last_activity_ms.load(Ordering::Relaxed);
+    let last_activity = (last_activity_ms > 0)
+        .then(|| Utc.timestamp_millis_opt(last_activity_ms).single())
+        .flatten();
+    let idle_ms = last_activity.map(|at| (now - at).num_milliseconds().max(0));
+    let backlog = state.backlog.load(Ordering::Relaxed);
+    let errors = state.errors.load(Ordering::Relaxed);
+
+    let mut reasons = Vec::new();
+    if let Some(max_idle) = rule.max_idle {
+        let max_idle_ms = max_idle.as_millis() as i64;
+        match idle_ms {
+            Some(idle_ms) if idle_ms > max_idle_ms => {
+                reasons.push(format!("idle for {}ms", idle_ms))
+            }
+            None => reasons.push("no activity yet".to_string()),
+            _ => {}
+        }
+    }
+    if rule.max_backlog.is_some_and(|max| backlog > max) {
+        reasons.push(format!("backlog of {}", backlog));
+    }
+    if rule.max_errors.is_some_and(|max| errors >= max) {
+        reasons.push(format!("{} errors", errors));
+    }
+    let status = if !running {
+        reasons.insert(0, "not running".to_string());
+        HealthStatus::Down
+    } else if reasons.is_empty() {
+        HealthStatus::Healthy
+    } else {
+        HealthStatus::Degraded
+    };
+
+    ServiceReport {
+        name: name.to_string(),
+        status,
+        critical: rule.critical,
+        running,
+        last_activity,
+        idle_ms,
+        backlog,
+        errors,
+        reasons,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_follows_rules_and_dependencies() {
+        let registry = HealthRegistry::new();
+        let feed = registry.register(
+            "depth_feed",
+            ServiceRule::default()
+                .critical()
+                .with_max_idle(Duration::from_secs(5)),
+        );
+        let api = registry.register(
+            "api",
+            ServiceRule::default()
+                .with_max_backlog(10)
+                .depends_on("depth_feed"),
+        );
+
+        // A feed that never delivered is stale, which degrades the API too
+        let report = registry.check();
+        assert_eq!(report.status, HealthStatus::Degraded);
+        assert_eq!(report.services[0].name, "api");
+        assert_eq!(report.services[0].status, HealthStatus::Degraded);
+
+        feed.record_activity();
+        assert_eq!(registry.check().status, HealthStatus::Healthy);
+        let later = Utc::now() + chrono::Duration::seconds(10);
+        assert_eq!(registry.check_at(later).status, HealthStatus::Degraded);
+
+        api.set_backlog(11);
+        feed.record_activity();
+        let report = registry.check();
+        assert_eq!(report.services[0].reasons, vec!["backlog of 11"]);
+        api.set_backlog(0);
+
+        feed.set_running(false);
+        let report = registry.check();
+        assert_eq!(report.status, HealthStatus::Down);
+        assert_eq!(report.services[0].status, HealthStatus::Degraded);
+    }
+}
@@ -0,0 +1,322 @@
+use std::collections::VecDeque;
+
+use serde::Serialize;
+
+/// Exponential moving average seeded with the first input
+#[derive(Debug, Clone)]
+pub struct Ema {
+    alpha: f64,
+    period: usize,
+    samples: usize,
+    value: f64,
+}
+
+impl Ema {
+    pub fn new(period: usize) -> Self {
+        let period = period.max(1);
+        Self {
+            alpha: 2.0 / (period as f64 + 1.0),
+            period,
+            samples: 0,
+            value: 0.0,
+        }
+    }
+
+    pub fn update(&mut self, x: f64) -> f64 {
+        self.value = if self.samples == 0 {
+            x
+        } else {
+            self.value + self.alpha * (x - self.value)
+        };
+        self.samples += 1;
+        self.value
+    }
+
+    /// None until `period` inputs have been seen
+    pub fn value(&self) -> Option<f64> {
+        (self.samples >= self.period).then_some(self.value)
+    }
+}
+
+/// Wilder's relative strength index
+#[derive(Debug, Clone)]
+pub struct Rsi {
+    period: usize,
+    previous: Option<f64>,
+    changes: usize,
+    avg_gain: f64,
+    avg_loss: f64,
+}
+
+impl Rsi {
+    pub fn new(period: usize) -> Self {
+        Self {
+            period: period.max(1),
+            previous: None,
+            changes: 0,
+            avg_gain: 0.0,
+            avg_loss: 0.0,
+        }
+    }
+
+    pub fn update(&mut self, close: f64) {
+        let Some(previous) = self.previous.replace(close) else {
+            return;
+        };
+        let change = close - previous;
+        let (gain, loss) = (change.max(0.0), (-change).max(0.0));
+        self.changes += 1;
+
+        // Simple average over the first period, Wilder smoothing afterwards
+        let n = self.changes.min(self.period) as f64;
+        self.avg_gain += (gain - self.avg_gain) / n;
+        self.avg_loss += (loss - self.avg_loss) / n;
+    }
+
+    pub fn value(&self) -> Option<f64> {
+        if self.changes < self.period {
+            return None;
+        }
+        if self.avg_loss == 0.0 {
+            return Some(100.0);
+        }
+        Some(100.0 - 100.0 / (1.0 + self.avg_gain / self.avg_loss))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct MacdValue {
+    pub macd: f64,
+    pub signal: f64,
+    pub histogram: f64,
+}
+
+/// Moving average convergence/divergence with its signal line
+#[derive(Debug, Clone)]
+pub struct Macd {
+    fast: Ema,
+    slow: Ema,
+    signal: Ema,
+}
+
+impl Macd {
+    pub fn new(fast: usize, slow: usize, signal: usize) -> Self {
+        Self {
+            fast: Ema::new(fast),
+            slow: Ema::new(slow),
+            signal: Ema::new(signal),
+        }
+    }
+
+    pub fn update(&mut self, close: f64) {
+        let macd = self.fast.update(close) - self.slow.update(close);
+        if self.slow.value().is_some() {
+            self.signal.update(macd);
+        }
+    }
+
+    /// None until the slow average and then the signal line have warmed up
+    pub fn value(&self) -> Option<MacdValue> {
+        let macd = self.fast.value()? - self.slow.value()?;
+        let signal = self.signal.value()?;
+        Some(MacdValue {
+            macd,
+            signal,
+            histogram: macd - signal,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct BollingerBands {
+    pub lower: f64,
+    pub middle: f64,
+    pub upper: f64,
+}
+
+/// Simple moving average plus and minus `width` standard deviations
+#[derive(Debug, Clone)]
+pub struct Bollinger {
+    period: usize,
+    width: f64,
+    window: VecDeque<f64>,
+    sum: f64,
+    sum_sq: f64,
+}
+
+impl Bollinger {
+    pub fn new(period: usize, width: f64) -> Self {
+        let period = period.max(1);
+        Self {
+            period,
+            width,
+            window: VecDeque::with_capacity(period),
+            sum: 0.0,
+            sum_sq: 0.0,
+        }
+    }
+
+    pub fn update(&mut self, close: f64) {
+        if self.window.len() == self.period {
+            if let Some(old) = self.window.pop_front() {
+                self.sum -= old;
+                self.sum_sq -= old * old;
+            }
+        }
+        self.window.push_back(close);
+        self.sum += close;
+        self.sum_sq += close * close;
+    }
+
+    pub fn value(&self) -> Option<BollingerBands> {
+        if self.window.len() < self.period {
+            return None;
+        }
+        let n = self.period as f64;
+        let middle = self.sum / n;
+        // Cancellation in the running sums can leave a tiny negative variance
+        let deviation = (self.sum_sq / n - middle * middle).max(0.0).sqrt();
+        Some(BollingerBands {
+            lower: middle - self.width * deviation,
+            middle,
+            upper: middle + self.width * deviation,
+        })
+    }
+}
+
+/// Wilder's average true range over OHLC bars
+#[derive(Debug, Clone)]
+pub struct Atr {
+    period: usize,
+    previous_close: Option<f64>,
+    bars: usize,
+    value: f64,
+}
+
+impl Atr {
+    pub fn new(period: usize) -> Self {
+        Self {
+            period: period.max(1),
+            previous_close: None,
+            bars: 0,
+            value: 0.0,
+        }
+    }
+
+    pub fn update(&mut self, high: f64, low: f64, close: f64) {
+        let range = match self.previous_close.replace(close) {
+            Some(prev) => (high - low)
+                .max((high - prev).abs())
+                .max((low - prev).abs()),
+            None => high - low,
+        };
+        self.bars += 1;
+        let n = self.bars.min(self.period) as f64;
+        self.value += (range - self.value) / n;
+    }
+
+    pub fn value(&self) -> Option<f64> {
+        (self.bars >= self.period).then_some(self.value)
+    }
+}
+
+/// Volume-weighted average price of trades in a trailing time window
+#[derive(Debug, Clone)]
+pub struct RollingVwap {
+    window_ms: i64,
+    trades: VecDeque<(i64, f64, f64)>,
+    notional: f64,
+    volume: f64,
+}
+
+impl RollingVwap {
+    pub fn new(window_ms: i64) -> Self {
+        Self {
+            window_ms: window_ms.max(1),
+            trades: VecDeque::new(),
+            notional: 0.0,
+            volume: 0.0,
+        }
+    }
+
+    pub fn update(&mut self, price: f64, quantity: f64, timestamp_ms: i64) {
+        self.trades
+            .push_back((timestamp_ms, price * quantity, quantity));
+        self.notional += price * quantity;
+        self.volume += quantity;
+        self.expire(timestamp_ms);
+    }
+
+    fn expire(&mut self, now_ms: i64) {
+        while let Some(&(at, notional, quantity)) = self.trades.front() {
+            if now_ms - at < self.window_ms {
+                break;
+            }
+            self.trades.pop_front();
+            self.notional -= notional;
+            self.volume -= quantity;
+        }
+    }
+
+    pub fn value(&self) -> Option<f64> {
+        (self.volume > 0.0).then(|| self.notional / self.volume)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn test_averages_and_bands() {
+        let mut ema = Ema::new(3);
+        for x in [1.0, 2.0, 3.0] {
+            ema.update(x);
+        }
+        // alpha = 0.5: 1 -> 1.5 -> 2.25
+        assert!(close(ema.value().unwrap(), 2.25));
+
+        let mut bands = Bollinger::new(4, 2.0);
+        for x in [2.0, 4.0, 4.0, 4.0, 6.0] {
+            bands.update(x);
+        }
+        // Window [4, 4, 4, 6]: mean 4.5, deviation sqrt(0.75)
+        let value = bands.value().unwrap();
+        assert!(close(value.middle, 4.5));
+        assert!(close(value.upper - value.middle, 2.0 * 0.75f64.sqrt()));
+
+        let mut macd = Macd::new(2, 3, 2);
+        for x in [1.0, 2.0, 3.0] {
+            macd.update(x);
+        }
+        assert!(macd.value().is_none());
+        macd.update(4.0);
+        assert!(macd.value().unwrap().macd > 0.0);
+    }
+
+    #[test]
+    fn test_rsi_atr_and_vwap() {
+        let mut rsi = Rsi::new(2);
+        for x in [10.0, 11.0, 10.5] {
+            rsi.update(x);
+        }
+        // Average gain 0.5, average loss 0.25
+        assert!(close(rsi.value().unwrap(), 100.0 - 100.0 / 3.0));
+
+        let mut atr = Atr::new(2);
+        atr.update(11.0, 9.0, 10.0);
+        atr.update(10.5, 10.0, 10.2);
+        assert!(close(atr.value().unwrap(), 1.25));
+
+        let mut vwap = RollingVwap::new(1_000);
+        vwap.update(100.0, 1.0, 0);
+        vwap.update(102.0, 3.0, 500);
+        assert!(close(vwap.value().unwrap(), 101.5));
+        vwap.update(104.0, 1.0, 1_200);
+        assert!(close(vwap.value().unwrap(), (306.0 + 104.0) / 4.0));
+    }
+}
@@ -0,0 +1,7 @@
+// Technical indicators computed incrementally from the trade stream
+
+pub mod calc;
+pub mod registry;
+
+pub use calc::{Atr, Bollinger, BollingerBands, Ema, Macd, MacdValue, RollingVwap, Rsi};
+pub use registry::{IndicatorConfig, IndicatorSet, IndicatorValues, Indicators, SharedIndicators};
@@ -0,0 +1,339 @@
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use crate::indicators::calc::{
+    Atr, Bollinger, BollingerBands, Ema, Macd, MacdValue, RollingVwap, Rsi,
+};
+use crate::signals::{SharedSignalBus, Signal};
+use crate::types::Symbol;
+
+/// Periods used for every symbol; bar-based indicators count closed bars
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct IndicatorConfig {
+    /// Trades are aggregated into bars of this length
+    pub bar_ms: i64,
+    pub ema_period: usize,
+    pub rsi_period: usize,
+    pub macd_fast: usize,
+    pub macd_slow: usize,
+    pub macd_signal: usize,
+    pub bollinger_period: usize,
+    pub bollinger_width: f64,
+    pub atr_period: usize,
+    /// Trailing window of the per-trade VWAP
+    pub vwap_window_ms: i64,
+}
+
+impl Default for IndicatorConfig {
+    fn default() -> Self {
+        Self {
+            bar_ms: 60_000,
+            ema_period: 20,
+            rsi_period: 14,
+            macd_fast: 12,
+            macd_slow: 26,
+            macd_signal: 9,
+            bollinger_period: 20,
+            bollinger_width: 2.0,
+            atr_period: 14,
+            vwap_window_ms: 300_000,
+        }
+    }
+}
+
+/// Latest indicator values; None while an indicator is warming up
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IndicatorValues {
+    pub symbol: Symbol,
+    /// Bars closed so far
+    pub bars: u64,
+    pub last_price: Option<f64>,
+    pub ema: Option<f64>,
+    pub rsi: Option<f64>,
+    pub macd: Option<MacdValue>,
+    pub bollinger: Option<BollingerBands>,
+    pub atr: Option<f64>,
+    pub vwap: Option<f64>,
+    pub timestamp_ms: i64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bar {
+    start_ms: i64,
+    high: f64,
+    low: f64,
+    close: f64,
+}
+
+/// Incremental indicators for one symbol, each update O(1)
+#[derive(Debug, Clone)]
+pub struct IndicatorSet {
+    config: IndicatorConfig,
+    bar: Option<Bar>,
+    bars: u64,
+    last_ms: i64,
+    ema: Ema,
+    rsi: Rsi,
+    macd: Macd,
+    bollinger: Bollinger,
+    atr: Atr,
+    vwap: RollingVwap,
+}
+
+impl IndicatorSet {
+    pub fn new(config: IndicatorConfig) -> Self {
+        Self {
+            config,
+            bar: None,
+            bars: 0,
+            last_ms: 0,
+            ema: Ema::new(config.ema_period),
+            rsi: Rsi::new(config.rsi_period),
+            macd: Macd::new(config.macd_fast, config.macd_slow, config.macd_signal),
+            bollinger: Bollinger::new(config.bollinger_period, config.bollinger_width),
+            atr: Atr::new(config.atr_period),
+            vwap: RollingVwap::new(config.vwap_window_ms),
+        }
+    }
+
+    /// Feed one trade; returns true when it closed a bar
+    pub fn on_trade(&mut self, price: f64, quantity: f64, timestamp_ms: i64) -> bool {
+        self.vwap.update(price, quantity, timestamp_ms);
+        self.last_ms = timestamp_ms;
+
+        let bar_ms = self.config.bar_ms.max(1);
+        let start_ms = timestamp_ms - timestamp_ms.rem_euclid(bar_ms);
+        let closed = match self.bar {
+            Some(bar) if start_ms > bar.start_ms => {
+                self.close_bar(bar);
+                true
+            }
+            _ => false,
+        };
+
+        match &mut self.bar {
+            Some(bar) if !closed => {
+                bar.high = bar.high.max(price);
+                bar.low = bar.low.min(price);
+                bar.close = price;
+            }
+            _ => {
+                self.bar = Some(Bar {
+                    start_ms,
+                    high: price,
+                    low: price,
+                    close: price,
+                })
+            }
+        }
+        closed
+    }
+
+    fn close_bar(&mut self, bar: Bar) {
+        self.bars += 1;
+        self.ema.update(bar.close);
+        self.rsi.update(bar.close);
+        self.macd.update(bar.close);
+        self.bollinger.update(bar.close);
+        self.atr.update(bar.high, bar.low, bar.close);
+    }
+
+    pub fn values(&self, symbol: &Symbol) -> IndicatorValues {
+        IndicatorValues {
+            symbol: symbol.clone(),
+            bars: self.bars,
+            last_price: self.bar.map(|bar| bar.close),
+            ema: self.ema.value(),
+            rsi: self.rsi.value(),
+            macd: self.macd.value(),
+            bollinger: self.bollinger.value(),
+            atr: self.atr.value(),
+            vwap: self.vwap.value(),
+            timestamp_ms: self.last_ms,
+        }
+    }
+}
+
+/// Indicators for every symbol seen on the trade stream
+///
+/// Subscribers receive the full [`IndicatorValues`] of a symbol each time
+/// one of its bars closes, and each value is also published as a named
+/// signal when a [`SharedSignalBus`] is attached.
+#[derive(Debug, Default)]
+pub struct Indicators {
+    config: IndicatorConfig,
+    sets: HashMap<Symbol, IndicatorSet>,
+    subscribers: Vec<Sender<IndicatorValues>>,
+    bus: Option<SharedSignalBus>,
+}
+
+impl Indicators {
+    pub fn new(config: IndicatorConfig) -> Self {
+        Self {
+            config,
+            sets: HashMap::new(),
+            subscribers: Vec::new(),
+            bus: None,
+        }
+    }
+
+    /// Publish `ema`, `rsi`, `macd`, `macd_signal`, `bollinger_upper`,
+    /// `bollinger_lower`, `atr` and `vwap` signals on every closed bar
+    pub fn with_signal_bus(mut self, bus: SharedSignalBus) -> Self {
+        self.bus = Some(bus);
+        self
+    }
+
+    pub fn on_trade(&mut self, symbol: &str, price: f64, quantity: f64, timestamp_ms: i64) {
+        let symbol = Symbol::new(symbol);
+        let set = self
+            .sets
+            .entry(symbol.clone())
+            .or_insert_with(|| IndicatorSet::new(self.config));
+        if !set.on_trade(price, quantity, timestamp_ms) {
+            return;
+        }
+        let values = set.values(&symbol);
+        if let Some(bus) = &self.bus {
+            publish_signals(bus, &values);
+        }
+        // Dropped receivers unsubscribe
+        self.subscribers
+            .retain(|subscriber| subscriber.send(values.clone()).is_ok());
+    }
+
+    pub fn get(&self, symbol: &str) -> Option<IndicatorValues> {
+        let symbol = Symbol::new(symbol);
+        self.sets.get(&symbol).map(|set| set.values(&symbol))
+    }
+
+    pub fn subscribe(&mut self) -> Receiver<IndicatorValues> {
+        let (sender, receiver) = mpsc::channel();
+        self.subscribers.push(sender);
+        receiver
+    }
+}
+
+fn publish_signals(bus: &SharedSignalBus, values: &IndicatorValues) {
+    let named = [
+        ("ema", values.ema),
+        ("rsi", values.rsi),
+        ("macd", values.macd.map(|m| m.macd)),
+        ("macd_signal", values.macd.map(|m| m.signal)),
+        ("bollinger_upper", values.bollinger.map(|b| b.upper)),
+        ("bollinger_lower", values.bollinger.map(|b| b.lower)),
+        ("atr", values.atr),
+        ("vwap", values.vwap),
+    ];
+    for (name, value) in named {
+        if let Some(value) = value {
+            bus.publish(Signal::number(
+                name,
+                values.symbol.as_str(),
+                value,
+                values.timestamp_ms,
+            ));
+        }
+    }
+}
+
+/// Thread-safe wrapper for Indicators
+pub struct SharedIndicators {
+    inner: Arc<Mutex<Indicators>>,
+}
+
+impl SharedIndicators {
+    pub fn new(config: IndicatorConfig) -> Self {
+        Self::from_indicators(Indicators::new(config))
+    }
+
+    pub fn from_indicators(indicators: Indicators) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(indicators)),
+        }
+    }
+
+    pub fn on_trade(&self, symbol: &str, price: f64, quantity: f64, timestamp_ms: i64) {
+        self.inner
+            .lock()
+            .unwrap()
+            .on_trade(symbol, price, quantity, timestamp_ms)
+    }
+
+    pub fn get(&self, symbol: &str) -> Option<IndicatorValues> {
+        self.inner.lock().unwrap().get(symbol)
+    }
+
+    pub fn subscribe(&self) -> Receiver<IndicatorValues> {
+        self.inner.lock().unwrap().subscribe()
+    }
+}
+
+impl Clone for SharedIndicators {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl Default for SharedIndicators {
+    fn default() -> Self {
+        Self::new(IndicatorConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bars_close_and_notify_subscribers() {
+        let config = IndicatorConfig {
+            bar_ms: 1_000,
+            ema_period: 2,
+            atr_period: 2,
+            ..Default::default()
+        };
+        let indicators = SharedIndicators::new(config);
+        let updates = indicators.subscribe();
+
+        indicators.on_trade("BTCUSDT", 100.0, 1.0, 0);
+        indicators.on_trade("BTCUSDT", 104.0, 1.0, 500);
+        indicators.on_trade("BTCUSDT", 102.0, 1.0, 1_100);
+        let first = updates.try_recv().unwrap();
+        assert_eq!(first.bars, 1);
+        assert!(first.ema.is_none());
+        assert!(updates.try_recv().is_err());
+
+        indicators.on_trade("BTCUSDT", 106.0, 2.0, 2_000);
+        let second = updates.try_recv().unwrap();
+        assert_eq!(second.bars, 2);
+
+        // Bars closed at 104 and 102: alpha 2/3 -> 104 + (102 - 104) * 2/3
+        assert!((second.ema.unwrap() - (104.0 - 4.0 / 3.0)).abs() < 1e-9);
+        // True ranges 4 and 2
+        assert_eq!(second.atr, Some(3.0));
+        assert_eq!(second.last_price, Some(106.0));
+        assert!(indicators.get("ETHUSDT").is_none());
+    }
+
+    #[test]
+    fn test_closed_bars_publish_signals() {
+        let bus = SharedSignalBus::default();
+        let mut vwap = bus.subscribe(&["vwap"]);
+        let mut indicators = Indicators::new(IndicatorConfig {
+            bar_ms: 1_000,
+            ..Default::default()
+        })
+        .with_signal_bus(bus);
+
+        indicators.on_trade("BTCUSDT", 100.0, 1.0, 0);
+        assert!(vwap.poll().is_empty());
+        indicators.on_trade("BTCUSDT", 102.0, 1.0, 1_000);
+        assert_eq!(vwap.poll().value("vwap", "BTCUSDT"), Some(101.0));
+    }
+}
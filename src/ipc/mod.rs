@@ -0,0 +1,7 @@
+// Shared-memory transport for sibling processes, enabled with the `ipc` feature
+
+pub mod region;
+pub mod ring;
+
+pub use region::MemoryMappedRegion;
+pub use ring::{MarketEvent, MarketEventKind, RingReader, RingWriter, SharedRingWriter};
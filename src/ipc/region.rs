@@ -0,0 +1,53 @@
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::path::Path;
+
+use memmap2::MmapMut;
+
+/// File-backed shared memory mapping
+///
+/// Place the file on a tmpfs such as `/dev/shm` to keep it in memory only.
+/// Every process mapping the same file sees the same bytes.
+pub struct MemoryMappedRegion {
+    mmap: MmapMut,
+}
+
+impl MemoryMappedRegion {
+    /// Create (or truncate) `path` to `len` zeroed bytes and map it
+    pub fn create(path: impl AsRef<Path>, len: usize) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+        file.set_len(len as u64)?;
+        Self::map(&file)
+    }
+
+    /// Map an existing region created by another process
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = OpenOptions::new().read(true).write(true).open(path)?;
+        Self::map(&file)
+    }
+
+    fn map(file: &File) -> io::Result<Self> {
+        // SAFETY: the mapping is shared with other processes on purpose; all
+        // access to shared bytes goes through atomics or volatile copies.
+        let mmap = unsafe { MmapMut::map_mut(file)? };
+        Ok(Self { mmap })
+    }
+
+    pub fn len(&self) -> usize {
+        self.mmap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.mmap.is_empty()
+    }
+
+    /// Start of the mapping, page aligned
+    pub fn as_ptr(&self) -> *mut u8 {
+        self.mmap.as_ptr() as *mut u8
+    }
+}
@@ -0,0 +1,358 @@
+use std::io;
+use std::path::Path;
+use std::sync::atomic::{fence, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use chrono::Utc;
+
+use crate::ipc::region::MemoryMappedRegion;
+
+const MAGIC: &[u8; 8] = b"OBRING01";
+const HEADER_LEN: usize = 64;
+const EVENT_LEN: usize = std::mem::size_of::<MarketEvent>();
+/// Sequence stamp followed by the event
+const SLOT_LEN: usize = 8 + EVENT_LEN;
+
+/// What a [`MarketEvent`] describes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum MarketEventKind {
+    /// An exchange trade: `price` and `quantity` are set
+    Trade = 1,
+    /// A top-of-book change: `bid` and `ask` are set
+    Quote = 2,
+}
+
+/// Fixed-size binary market event as laid out in the ring
+///
+/// Every field accepts any bit pattern, so readers can copy events straight
+/// out of shared memory.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
+pub struct MarketEvent {
+    /// ASCII symbol, NUL padded
+    pub symbol: [u8; 16],
+    kind: u8,
+    _padding: [u8; 7],
+    pub price: f64,
+    pub quantity: f64,
+    pub bid: f64,
+    pub ask: f64,
+    pub timestamp_ms: i64,
+}
+
+impl MarketEvent {
+    pub fn trade(symbol: &str, price: f64, quantity: f64, timestamp_ms: i64) -> Self {
+        Self {
+            price,
+            quantity,
+            ..Self::empty(symbol, MarketEventKind::Trade, timestamp_ms)
+        }
+    }
+
+    pub fn quote(symbol: &str, bid: f64, ask: f64, timestamp_ms: i64) -> Self {
+        Self {
+            bid,
+            ask,
+            ..Self::empty(symbol, MarketEventKind::Quote, timestamp_ms)
+        }
+    }
+
+    fn empty(symbol: &str, kind: MarketEventKind, timestamp_ms: i64) -> Self {
+        let mut padded = [0u8; 16];
+        let bytes = symbol.as_bytes();
+        let len = bytes.len().min(padded.len());
+        padded[..len].copy_from_slice(&bytes[..len]);
+        Self {
+            symbol: padded,
+            kind: kind as u8,
+            _padding: [0; 7],
+            price: 0.0,
+            quantity: 0.0,
+            bid: 0.0,
+            ask: 0.0,
+            timestamp_ms,
+        }
+    }
+
+    /// None for kinds written by a newer producer
+    pub fn kind(&self) -> Option<MarketEventKind> {
+        match self.kind {
+            1 => Some(MarketEventKind::Trade),
+            2 => Some(MarketEventKind::Quote),
+            _ => None,
+        }
+    }
+
+    pub fn symbol(&self) -> &str {
+        let len = self.symbol.iter().position(|&b| b == 0).unwrap_or(16);
+        std::str::from_utf8(&self.symbol[..len]).unwrap_or("")
+    }
+}
+
+/// Ring laid out in a [`MemoryMappedRegion`]
+///
+/// Layout: `OBRING01`, u64 capacity, u64 published count, padding to 64
+/// bytes, then `capacity` slots of a u64 stamp and a [`MarketEvent`]. Each
+/// slot is a seqlock: the stamp is odd while the writer is copying and
+/// `2 * seq + 2` once event `seq` is complete.
+struct Ring {
+    region: MemoryMappedRegion,
+    capacity: u64,
+}
+
+impl Ring {
+    fn head(&self) -> &AtomicU64 {
+        // SAFETY: the header is 8-byte aligned inside a page-aligned mapping
+        unsafe { &*(self.region.as_ptr().add(16) as *const AtomicU64) }
+    }
+
+    fn stamp(&self, seq: u64) -> &AtomicU64 {
+        let offset = HEADER_LEN + (seq % self.capacity) as usize * SLOT_LEN;
+        // SAFETY: offset is within the mapping and 8-byte aligned
+        unsafe { &*(self.region.as_ptr().add(offset) as *const AtomicU64) }
+    }
+
+    fn event_ptr(&self, seq: u64) -> *mut MarketEvent {
+        let offset = HEADER_LEN + (seq % self.capacity) as usize * SLOT_LEN + 8;
+        // SAFETY: offset is within the mapping and 8-byte aligned
+        unsafe { self.region.as_ptr().add(offset) as *mut MarketEvent }
+    }
+}
+
+/// The single producer of a ring
+pub struct RingWriter {
+    ring: Ring,
+    next: u64,
+}
+
+impl RingWriter {
+    /// Create a ring holding the last `capacity` events at `path`
+    pub fn create(path: impl AsRef<Path>, capacity: usize) -> io::Result<Self> {
+        let capacity = capacity.max(1);
+        let region = MemoryMappedRegion::create(path, HEADER_LEN + capacity * SLOT_LEN)?;
+        // SAFETY: the region was just created and is not yet shared
+        unsafe {
+            let base = region.as_ptr();
+            std::ptr::copy_nonoverlapping(MAGIC.as_ptr(), base, MAGIC.len());
+            (base.add(8) as *mut u64).write(capacity as u64);
+        }
+        Ok(Self {
+            ring: Ring {
+                region,
+                capacity: capacity as u64,
+            },
+            next: 0,
+        })
+    }
+
+    /// Events published so far
+    pub fn published(&self) -> u64 {
+        self.next
+    }
+
+    pub fn publish(&mut self, event: &MarketEvent) {
+        let seq = self.next;
+        let stamp = self.ring.stamp(seq);
+        stamp.store(2 * seq + 1, Ordering::Relaxed);
+        fence(Ordering::Release);
+        // SAFETY: only this writer stores to the slot; readers detect torn
+        // copies through the stamp
+        unsafe { std::ptr::write_volatile(self.ring.event_ptr(seq), *event) };
+        stamp.store(2 * seq + 2, Ordering::Release);
+
+        self.next = seq + 1;
+        self.ring.head().store(self.next, Ordering::Release);
+    }
+}
+
+/// Thread-safe wrapper for RingWriter, for feeds publishing from several tasks
+pub struct SharedRingWriter {
+    inner: Arc<Mutex<RingWriter>>,
+}
+
+impl SharedRingWriter {
+    pub fn new(writer: RingWriter) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(writer)),
+        }
+    }
+
+    pub fn publish(&self, event: &MarketEvent) {
+        self.inner.lock().unwrap().publish(event)
+    }
+
+    pub fn publish_trade(&self, symbol: &str, price: f64, quantity: f64) {
+        self.publish(&MarketEvent::trade(
+            symbol,
+            price,
+            quantity,
+            Utc::now().timestamp_millis(),
+        ))
+    }
+
+    pub fn publish_quote(&self, symbol: &str, bid: f64, ask: f64) {
+        self.publish(&MarketEvent::quote(
+            symbol,
+            bid,
+            ask,
+            Utc::now().timestamp_millis(),
+        ))
+    }
+}
+
+impl Clone for SharedRingWriter {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+/// One of any number of consumers of a ring, usually in another process
+///
+/// Readers never block the writer. A reader that falls more than the ring
+/// capacity behind skips ahead and counts the events it lost.
+pub struct RingReader {
+    ring: Ring,
+    cursor: u64,
+    lost: u64,
+}
+
+impl RingReader {
+    /// Attach to the ring at `path`, starting with the next event published
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let region = MemoryMappedRegion::open(path)?;
+        if region.len() < HEADER_LEN {
+            return Err(invalid("file too small for a ring"));
+        }
+        // SAFETY: the header is within the mapping; capacity is written once
+        // before the ring is shared
+        let (magic, capacity) = unsafe {
+            let base = region.as_ptr();
+            (
+                std::slice::from_raw_parts(base, MAGIC.len()),
+                (base.add(8) as *const u64).read(),
+            )
+        };
+        if magic != MAGIC || capacity == 0 {
+            return Err(invalid("not a market event ring"));
+        }
+        let ring_len = usize::try_from(capacity)
+            .ok()
+            .and_then(|capacity| capacity.checked_mul(SLOT_LEN))
+            .and_then(|slots| slots.checked_add(HEADER_LEN));
+        if ring_len.is_none_or(|ring_len| region.len() < ring_len) {
+            return Err(invalid("ring file truncated"));
+        }
+
+        let ring = Ring { region, capacity };
+        let cursor = ring.head().load(Ordering::Acquire);
+        Ok(Self {
+            ring,
+            cursor,
+            lost: 0,
+        })
+    }
+
+    /// Events skipped because the writer lapped this reader
+    pub fn lost(&self) -> u64 {
+        self.lost
+    }
+
+    /// Next event, or None when caught up with the writer
+    pub fn try_recv(&mut self) -> Option<MarketEvent> {
+        loop {
+            let seq = self.cursor;
+            let stamp = self.ring.stamp(seq);
+            let before = stamp.load(Ordering::Acquire);
+            let complete = 2 * seq + 2;
+            if before < complete {
+                return None;
+            }
+            if before == complete {
+                // SAFETY: the slot is within the mapping; a concurrent
+                // overwrite is detected by re-reading the stamp below
+                let event = unsafe { std::ptr::read_volatile(self.ring.event_ptr(seq)) };
+                fence(Ordering::Acquire);
+                if stamp.load(Ordering::Relaxed) == complete {
+                    self.cursor += 1;
+                    return Some(event);
+                }
+            }
+            self.skip_ahead();
+        }
+    }
+
+    /// Move past events the writer has already overwritten
+    fn skip_ahead(&mut self) {
+        let head = self.ring.head().load(Ordering::Acquire);
+        let oldest = head.saturating_sub(self.ring.capacity - 1);
+        let next = oldest.max(self.cursor + 1);
+        self.lost += next - self.cursor;
+        self.cursor = next;
+    }
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ring_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("{}-{}.ring", name, std::process::id()))
+    }
+
+    #[test]
+    fn test_readers_see_events_in_order() {
+        let path = ring_path("ring-order");
+        let mut writer = RingWriter::create(&path, 8).unwrap();
+        writer.publish(&MarketEvent::trade("BTCUSDT", 1.0, 1.0, 0));
+
+        // Readers start at the next event
+        let mut a = RingReader::open(&path).unwrap();
+        let mut b = RingReader::open(&path).unwrap();
+        writer.publish(&MarketEvent::trade("BTCUSDT", 43_000.5, 0.25, 1));
+        writer.publish(&MarketEvent::quote("ETHUSDT", 2_300.0, 2_300.5, 2));
+
+        for reader in [&mut a, &mut b] {
+            let trade = reader.try_recv().unwrap();
+            assert_eq!(trade.kind(), Some(MarketEventKind::Trade));
+            assert_eq!((trade.symbol(), trade.price), ("BTCUSDT", 43_000.5));
+            let quote = reader.try_recv().unwrap();
+            assert_eq!((quote.symbol(), quote.ask), ("ETHUSDT", 2_300.5));
+            assert!(reader.try_recv().is_none());
+        }
+
+        // A capacity whose slots could not fit in any file
+        let mut header = MAGIC.to_vec();
+        header.extend_from_slice(&u64::MAX.to_ne_bytes());
+        header.resize(HEADER_LEN, 0);
+        std::fs::write(&path, header).unwrap();
+        assert!(RingReader::open(&path).is_err());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_lapped_reader_skips_ahead() {
+        let path = ring_path("ring-lapped");
+        let mut writer = RingWriter::create(&path, 4).unwrap();
+        let mut reader = RingReader::open(&path).unwrap();
+        for i in 0..10 {
+            writer.publish(&MarketEvent::trade("BTCUSDT", i as f64, 1.0, i));
+        }
+
+        let prices: Vec<f64> = std::iter::from_fn(|| reader.try_recv())
+            .map(|event| event.price)
+            .collect();
+        assert_eq!(prices, vec![7.0, 8.0, 9.0]);
+        assert_eq!(reader.lost(), 7);
+        assert_eq!(writer.published(), 10);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,172 @@
+// Low-overhead latency measurement for the matching core
+//
+// With the `tsc` feature on x86_64 spans read the CPU timestamp counter,
+// calibrated once against `Instant`, which costs a few nanoseconds instead
+// of a clock syscall. Elsewhere they fall back to `Instant`. Samples land in
+// a pre-allocated lock-free ring so recording never allocates or blocks.
+
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::OnceLock;
+use std::time::Instant;
+
+use serde::Serialize;
+
+/// Raw timestamp in clock ticks
+#[inline]
+fn ticks() -> u64 {
+    #[cfg(all(feature = "tsc", target_arch = "x86_64"))]
+    {
+        #[allow(unused_unsafe)]
+        // SAFETY: RDTSC is available on every x86_64 CPU and has no side effects
+        unsafe {
+            std::arch::x86_64::_rdtsc()
+        }
+    }
+    #[cfg(not(all(feature = "tsc", target_arch = "x86_64")))]
+    {
+        static EPOCH: OnceLock<Instant> = OnceLock::new();
+        EPOCH.get_or_init(Instant::now).elapsed().as_nanos() as u64
+    }
+}
+
+/// Nanoseconds per tick, measured on first use
+///
+/// Call once at startup to keep the ~5ms TSC calibration off the hot path.
+pub fn calibrate() -> f64 {
+    static NS_PER_TICK: OnceLock<f64> = OnceLock::new();
+    *NS_PER_TICK.get_or_init(|| {
+        if cfg!(all(feature = "tsc", target_arch = "x86_64")) {
+            let (started, start_ticks) = (Instant::now(), ticks());
+            while started.elapsed().as_millis() < 5 {
+                std::hint::spin_loop();
+            }
+            let elapsed_ticks = ticks().saturating_sub(start_ticks).max(1);
+            started.elapsed().as_nanos() as f64 / elapsed_ticks as f64
+        } else {
+            1.0
+        }
+    })
+}
+
+/// Percentiles of recorded spans in nanoseconds
+#[derive(Debug, Clone, Serialize)]
+pub struct LatencySummary {
+    pub samples: usize,
+    pub p50_ns: f64,
+    pub p99_ns: f64,
+    pub max_ns: f64,
+}
+
+/// Fixed-size ring of span durations, safe to record from any thread
+#[derive(Debug)]
+pub struct LatencySamples {
+    slots: Box<[AtomicU64]>,
+    cursor: AtomicUsize,
+}
+
+impl LatencySamples {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            slots: (0..capacity.max(1)).map(|_| AtomicU64::new(0)).collect(),
+            cursor: AtomicUsize::new(0),
+        }
+    }
+
+    /// Start a span that records into this ring when dropped
+    #[inline]
+    pub fn span(&self) -> LatencySpan<'_> {
+        LatencySpan {
+            start: ticks(),
+            samples: self,
+        }
+    }
+
+    #[inline]
+    pub fn record_ns(&self, ns: u64) {
+        let index = self.cursor.fetch_add(1, Ordering::Relaxed) % self.slots.len();
+        self.slots[index].store(ns, Ordering::Relaxed);
+    }
+
+    pub fn summary(&self) -> Option<LatencySummary> {
+        let count = self.cursor.load(Ordering::Relaxed).min(self.slots.len());
+        if count == 0 {
+            return None;
+        }
+        let mut sorted: Vec<u64> = self.slots[..count]
+            .iter()
+            .map(|slot| slot.load(Ordering::Relaxed))
+            .collect();
+        sorted.sort_unstable();
+        let at = |q: f64| sorted[((count - 1) as f64 * q).round() as usize] as f64;
+        Some(LatencySummary {
+            samples: count,
+            p50_ns: at(0.5),
+            p99_ns: at(0.99),
+            max_ns: sorted[count - 1] as f64,
+        })
+    }
+}
+
+impl Default for LatencySamples {
+    fn default() -> Self {
+        Self::new(4_096)
+    }
+}
+
+/// Measures from creation until drop, recording into a [`LatencySamples`]
+#[must_use = "a span records when dropped"]
+pub struct LatencySpan<'a> {
+    start: u64,
+    samples: &'a LatencySamples,
+}
+
+impl LatencySpan<'_> {
+    #[inline]
+    pub fn elapsed_ns(&self) -> u64 {
+        (ticks().saturating_sub(self.start) as f64 * calibrate()) as u64
+    }
+
+    pub fn elapsed_ms(&self) -> f64 {
+        self.elapsed_ns() as f64 / 1_000_000.0
+    }
+}
+
+impl Drop for LatencySpan<'_> {
+    #[inline]
+    fn drop(&mut self) {
+        self.samples.record_ns(self.elapsed_ns());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_span_records_on_drop() {
+        let samples = LatencySamples::new(4);
+        assert!(samples.summary().is_none());
+
+        {
+            let span = samples.span();
+            std::thread::sleep(Duration::from_millis(2));
+            assert!(span.elapsed_ms() >= 1.0);
+        }
+        let summary = samples.summary().unwrap();
+        assert_eq!(summary.samples, 1);
+        assert!(summary.p50_ns >= 1_000_000.0);
+    }
+
+    #[test]
+    fn test_ring_keeps_latest_samples() {
+        let samples = LatencySamples::new(3);
+        for ns in [500, 10, 20, 30] {
+            samples.record_ns(ns);
+        }
+
+        let summary = samples.summary().unwrap();
+        assert_eq!(summary.samples, 3);
+        assert_eq!((summary.p50_ns, summary.max_ns), (20.0, 30.0));
+    }
+}
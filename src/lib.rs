@@ -1,10 +1,51 @@
 // High-Performance Cryptocurrency Order Book Engine
 // Demonstrates: Async Rust, WebSocket Integration, Order Matching, Market Microstructure
 
+pub mod account;
+#[cfg(feature = "web")]
+pub mod api;
+#[cfg(feature = "backtest")]
+pub mod backtest;
+#[cfg(feature = "net")]
+pub mod bootstrap;
+pub mod calendar;
+pub mod depeg;
+#[cfg(feature = "net")]
 pub mod exchange;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod health;
+#[cfg(feature = "ipc")]
+pub mod ipc;
+pub mod indicators;
+pub mod latency;
+pub mod liquidation;
+pub mod market;
+pub mod memory;
 pub mod orderbook;
+pub mod overload;
+#[cfg(feature = "python")]
+mod python;
+pub mod queue;
+pub mod risk;
+pub mod risk_journal;
+pub mod routing;
+pub mod signals;
+#[cfg(feature = "backtest")]
+pub mod strategy;
+#[cfg(any(test, feature = "testkit"))]
+pub mod testkit;
+pub mod throughput;
+pub mod timeseries;
 pub mod types;
+pub mod utils;
+pub mod var;
+#[cfg(feature = "wasm")]
+mod wasm;
 
+#[cfg(feature = "net")]
 pub use exchange::{BinanceFeed, MarketData};
-pub use orderbook::{OrderBook, SharedOrderBook};
-pub use types::{Order, OrderId, OrderSide, OrderStatus, OrderType, Trade};
+pub use orderbook::{BookKind, BookManager, OrderBook, SharedOrderBook};
+pub use types::{
+    Notional, Order, OrderId, OrderSide, OrderStatus, OrderType, Price, Qty, Symbol, Trade,
+};
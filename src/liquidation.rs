@@ -0,0 +1,569 @@
+// Liquidations, the insurance fund and auto-deleveraging
+//
+// Margin pools whose collateral falls below their maintenance margin are
+// closed out at the mark: an account's cross positions together, or one
+// isolated position on its own margin. What is left of their collateral
+// pays the liquidation fee of the risk limits into the insurance fund; a bankrupt pool's
+// shortfall is paid out of it. Once
+// the fund cannot cover a shortfall, the most profitable and most leveraged
+// positions on the other side are closed at the bankrupt position's
+// bankruptcy price, so the loss is socialized rather than left unpaid, the
+// way derivatives exchanges handle it. Ahead of all that, a monitor warns
+// as marks close in on positions' liquidation prices.
+
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::account::{Account, AccountId, Activity, MarginMode};
+use crate::market::{AlertCondition, AlertEvent, AlertId, AlertSeverity, AlertSink};
+use crate::risk::{MarginPool, RiskLimits};
+use crate::types::{OrderSide, Symbol};
+
+/// Why the insurance fund balance moved
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FundChange {
+    Deposit,
+    /// Fee taken from what a liquidated account had left
+    LiquidationFee,
+    /// Shortfall of a bankrupt account paid out
+    Shortfall,
+}
+
+/// One movement of the insurance fund
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FundEntry {
+    pub timestamp: DateTime<Utc>,
+    pub change: FundChange,
+    pub account: Option<AccountId>,
+    pub amount: f64,
+    /// Balance after the movement
+    pub balance: f64,
+}
+
+/// Fund absorbing losses of bankrupt liquidations
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InsuranceFund {
+    balance: f64,
+    history: Vec<FundEntry>,
+}
+
+impl InsuranceFund {
+    pub fn new(balance: f64) -> Self {
+        let mut fund = Self::default();
+        if balance > 0.0 {
+            fund.record(Utc::now(), FundChange::Deposit, None, balance);
+        }
+        fund
+    }
+
+    pub fn balance(&self) -> f64 {
+        self.balance
+    }
+
+    /// Every movement in order, to chart the balance over time
+    pub fn history(&self) -> &[FundEntry] {
+        &self.history
+    }
+
+    pub fn deposit(&mut self, amount: f64, timestamp: DateTime<Utc>) {
+        self.record(timestamp, FundChange::Deposit, None, amount);
+    }
+
+    fn record(
+        &mut self,
+        timestamp: DateTime<Utc>,
+        change: FundChange,
+        account: Option<&AccountId>,
+        amount: f64,
+    ) {
+        self.balance += amount;
+        self.history.push(FundEntry {
+            timestamp,
+            change,
+            account: account.cloned(),
+            amount,
+            balance: self.balance,
+        });
+    }
+}
+
+/// A position closed against the bankrupt side once the fund ran dry
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Deleverage {
+    pub account: AccountId,
+    pub symbol: Symbol,
+    /// Quantity closed, signed like the position it was taken from
+    pub quantity: f64,
+    /// Bankruptcy price it was closed at
+    pub price: f64,
+    /// Profit given up against closing at the mark
+    pub cost: f64,
+}
+
+/// A position closed out at the mark
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ClosedPosition {
+    pub symbol: Symbol,
+    /// Signed size of the position
+    pub quantity: f64,
+    pub price: f64,
+}
+
+impl ClosedPosition {
+    fn notional(&self) -> f64 {
+        (self.quantity * self.price).abs()
+    }
+}
+
+/// One liquidated margin pool
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LiquidationEvent {
+    pub account: AccountId,
+    pub timestamp: DateTime<Utc>,
+    /// Whether the account's cross positions or one isolated position went
+    pub mode: MarginMode,
+    /// Collateral of the liquidated pool, including unrealized PnL
+    pub equity: f64,
+    pub maintenance: f64,
+    pub closed: Vec<ClosedPosition>,
+    pub fee: f64,
+    /// Negative equity after closing out; zero when the account stayed solvent
+    pub shortfall: f64,
+    pub covered_by_fund: f64,
+    pub deleveraged: Vec<Deleverage>,
+    /// Shortfall nobody could be made to cover
+    pub uncovered: f64,
+}
+
+/// Finds and closes out under-margined accounts
+#[derive(Debug, Clone)]
+pub struct Liquidator {
+    limits: RiskLimits,
+    fund: InsuranceFund,
+}
+
+impl Liquidator {
+    pub fn new(limits: RiskLimits, fund: InsuranceFund) -> Self {
+        Self { limits, fund }
+    }
+
+    pub fn fund(&self) -> &InsuranceFund {
+        &self.fund
+    }
+
+    /// Liquidate every margin pool below its maintenance margin at `mark`
+    ///
+    /// Each isolated position is checked on its own margin and the cross
+    /// positions of an account together on what is left. Accounts are
+    /// checked in id order so a backtest replays the same way each time.
+    /// Positions without a mark are valued at entry.
+    pub fn run(
+        &mut self,
+        accounts: &mut BTreeMap<AccountId, Account>,
+        mark: impl Fn(&Symbol) -> Option<f64>,
+        timestamp: DateTime<Utc>,
+    ) -> Vec<LiquidationEvent> {
+        let ids: Vec<AccountId> = accounts.keys().cloned().collect();
+        let mut events = Vec::new();
+        for id in ids {
+            for pool in self.limits.margin_pools(&accounts[&id], &mark) {
+                if pool.collateral < pool.maintenance {
+                    events.push(self.liquidate(accounts, &id, pool, &mark, timestamp));
+                }
+            }
+        }
+        events
+    }
+
+    fn liquidate(
+        &mut self,
+        accounts: &mut BTreeMap<AccountId, Account>,
+        id: &AccountId,
+        pool: MarginPool,
+        mark: &impl Fn(&Symbol) -> Option<f64>,
+        timestamp: DateTime<Utc>,
+    ) -> LiquidationEvent {
+        let account = accounts.get_mut(id).unwrap();
+        let mut closed = Vec::new();
+        for position in account.open_positions() {
+            if !pool.contains(&position) {
+                continue;
+            }
+            let price = mark(&position.symbol).unwrap_or(position.entry_price);
+            account.apply(close(&position.symbol, position.quantity, price, timestamp));
+            closed.push(ClosedPosition {
+                symbol: position.symbol,
+                quantity: position.quantity,
+                price,
+            });
+        }
+        let mut event = LiquidationEvent {
+            account: id.clone(),
+            timestamp,
+            mode: pool.mode(),
+            equity: pool.collateral,
+            maintenance: pool.maintenance,
+            closed,
+            fee: 0.0,
+            shortfall: 0.0,
+            covered_by_fund: 0.0,
+            deleveraged: Vec::new(),
+            uncovered: 0.0,
+        };
+
+        // Closing at the mark realizes exactly the collateral the pool had
+        let left = pool.collateral;
+        if left >= 0.0 {
+            let notional: f64 = event.closed.iter().map(ClosedPosition::notional).sum();
+            event.fee = (notional * self.limits.liquidation_fee_rate).min(left);
+            if event.fee > 0.0 {
+                account.apply(transfer(-event.fee, timestamp));
+                self.fund
+                    .record(timestamp, FundChange::LiquidationFee, Some(id), event.fee);
+            }
+            return event;
+        }
+
+        event.shortfall = -left;
+        event.covered_by_fund = event.shortfall.min(self.fund.balance.max(0.0));
+        if event.covered_by_fund > 0.0 {
+            self.fund.record(
+                timestamp,
+                FundChange::Shortfall,
+                Some(id),
+                -event.covered_by_fund,
+            );
+        }
+        let mut remaining = event.shortfall - event.covered_by_fund;
+        if remaining > 0.0 {
+            tracing::warn!(
+                "Insurance fund depleted, deleveraging {:.2} of account {}'s shortfall",
+                remaining,
+                id
+            );
+            let notional: f64 = event.closed.iter().map(ClosedPosition::notional).sum();
+            for position in &event.closed {
+                // Each closed position carries its share of the shortfall
+                let share = if notional > 0.0 {
+                    remaining * position.notional() / notional
+                } else {
+                    0.0
+                };
+                event
+                    .deleveraged
+                    .extend(deleverage(accounts, id, position, share, mark, timestamp));
+            }
+            remaining -= event.deleveraged.iter().map(|d| d.cost).sum::<f64>();
+        }
+        event.uncovered = remaining.max(0.0);
+
+        // A cross account keeps whatever nobody covered as a negative
+        // balance; an isolated position's loss stops at its margin
+        let mut paid = event.shortfall - event.uncovered;
+        if event.mode == MarginMode::Isolated {
+            paid = event.shortfall;
+        }
+        accounts
+            .get_mut(id)
+            .unwrap()
+            .apply(transfer(paid, timestamp));
+        event
+    }
+}
+
+/// Escalating alerts as marks approach liquidation prices
+///
+/// Each position alerts once per threshold on the way in, nearest last, and
+/// is re-armed for a threshold once the mark moves back out past it. Alerts
+/// go to the same sinks as user price alerts, from `AlertId::SYSTEM` with
+/// the account id as the user.
+pub struct ProximityMonitor {
+    /// Distances in percent of the mark, farthest first
+    thresholds: Vec<f64>,
+    /// How many thresholds each position is already inside
+    reached: HashMap<(AccountId, Symbol), usize>,
+    sinks: Vec<Box<dyn AlertSink>>,
+}
+
+impl Default for ProximityMonitor {
+    fn default() -> Self {
+        Self::new(&[10.0, 5.0, 2.0])
+    }
+}
+
+impl ProximityMonitor {
+    pub fn new(thresholds: &[f64]) -> Self {
+        let mut thresholds = thresholds.to_vec();
+        thresholds.sort_by(|a, b| b.total_cmp(a));
+        Self {
+            thresholds,
+            reached: HashMap::new(),
+            sinks: Vec::new(),
+        }
+    }
+
+    pub fn with_sink(mut self, sink: impl AlertSink + 'static) -> Self {
+        self.sinks.push(Box::new(sink));
+        self
+    }
+
+    /// Compare the liquidation prices stored on `account`'s positions with
+    /// their marks, returning the alerts raised
+    pub fn check(
+        &mut self,
+        account: &Account,
+        mark: impl Fn(&Symbol) -> Option<f64>,
+        timestamp_ms: i64,
+    ) -> Vec<AlertEvent> {
+        let mut fired = Vec::new();
+        for (symbol, position) in &account.positions {
+            let key = (account.id.clone(), symbol.clone());
+            let (Some(liquidation_price), Some(price), true) =
+                (position.liquidation_price, mark(symbol), position.is_open())
+            else {
+                self.reached.remove(&key);
+                continue;
+            };
+            let distance = (price - liquidation_price).abs() / price * 100.0;
+            let inside = self.thresholds.iter().filter(|t| distance <= **t).count();
+            let before = self.reached.insert(key, inside).unwrap_or(0);
+            if inside <= before {
+                continue;
+            }
+            let threshold_percent = self.thresholds[inside - 1];
+            tracing::warn!(
+                "Account {} {} is {:.2}% from liquidation at {}",
+                account.id,
+                symbol,
+                distance,
+                liquidation_price
+            );
+            fired.push(AlertEvent {
+                alert_id: AlertId::SYSTEM,
+                user: account.id.to_string(),
+                symbol: symbol.clone(),
+                condition: AlertCondition::LiquidationProximity {
+                    liquidation_price,
+                    threshold_percent,
+                },
+                severity: AlertSeverity::Warning,
+                price,
+                timestamp_ms,
+            });
+        }
+        for event in &fired {
+            for sink in &self.sinks {
+                sink.deliver(event);
+            }
+        }
+        fired
+    }
+}
+
+/// Close counterparties of a bankrupt `position` until `shortfall` is paid,
+/// best ranked first
+///
+/// The bankruptcy price is where closing the bankrupt position would have
+/// left it at zero; counterparties are closed there instead of at the mark.
+fn deleverage(
+    accounts: &mut BTreeMap<AccountId, Account>,
+    bankrupt: &AccountId,
+    position: &ClosedPosition,
+    shortfall: f64,
+    mark: &impl Fn(&Symbol) -> Option<f64>,
+    timestamp: DateTime<Utc>,
+) -> Vec<Deleverage> {
+    let ClosedPosition {
+        symbol,
+        quantity,
+        price: mark_price,
+    } = position;
+    let (quantity, mark_price) = (*quantity, *mark_price);
+    if shortfall <= 0.0 || quantity == 0.0 {
+        return Vec::new();
+    }
+    // Long positions go bankrupt above the mark, short ones below it
+    let offset = shortfall / quantity.abs();
+    let bankruptcy_price = mark_price + offset * quantity.signum();
+
+    // Profitable positions on the other side, ranked by profit and leverage
+    let mut ranked: Vec<(f64, AccountId, f64)> = accounts
+        .iter()
+        .filter(|(id, _)| *id != bankrupt)
+        .filter_map(|(id, account)| {
+            let position = account.positions.get(symbol)?;
+            if position.quantity * quantity >= 0.0 {
+                return None;
+            }
+            let pnl = position.quantity * (mark_price - position.entry_price);
+            let cost = position.quantity.abs() * position.entry_price;
+            if pnl <= 0.0 || cost <= 0.0 {
+                return None;
+            }
+            let notional: f64 = account
+                .open_positions()
+                .iter()
+                .map(|p| (p.quantity * mark(&p.symbol).unwrap_or(p.entry_price)).abs())
+                .sum();
+            let equity = account.balance + pnl;
+            let leverage = if equity > 0.0 { notional / equity } else { 0.0 };
+            Some(((pnl / cost) * leverage, id.clone(), position.quantity))
+        })
+        .collect();
+    ranked.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+
+    let mut left = quantity.abs();
+    let mut deleveraged = Vec::new();
+    for (_, id, held) in ranked {
+        if left <= 0.0 {
+            break;
+        }
+        let closed = held.abs().min(left) * held.signum();
+        left -= closed.abs();
+        accounts
+            .get_mut(&id)
+            .unwrap()
+            .apply(close(symbol, closed, bankruptcy_price, timestamp));
+        deleveraged.push(Deleverage {
+            account: id,
+            symbol: symbol.clone(),
+            quantity: closed,
+            price: bankruptcy_price,
+            cost: closed.abs() * offset,
+        });
+    }
+    deleveraged
+}
+
+/// Trade closing `quantity` of a position, signed like the position
+fn close(symbol: &Symbol, quantity: f64, price: f64, timestamp: DateTime<Utc>) -> Activity {
+    Activity::Trade {
+        symbol: symbol.clone(),
+        side: if quantity > 0.0 {
+            OrderSide::Sell
+        } else {
+            OrderSide::Buy
+        },
+        price,
+        quantity: quantity.abs(),
+        fee: 0.0,
+        timestamp,
+        fill: None,
+    }
+}
+
+fn transfer(amount: f64, timestamp: DateTime<Utc>) -> Activity {
+    Activity::Transfer { amount, timestamp }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn account(id: &str, balance: f64, side: OrderSide, quantity: f64) -> (AccountId, Account) {
+        let id = AccountId::from(id);
+        let mut account = Account::new(id.clone());
+        account.apply(transfer(balance, Utc::now()));
+        account.apply(Activity::Trade {
+            symbol: Symbol::from("BTCUSDT"),
+            side,
+            price: 100.0,
+            quantity,
+            fee: 0.0,
+            timestamp: Utc::now(),
+            fill: None,
+        });
+        (id, account)
+    }
+
+    #[test]
+    fn test_fund_covers_shortfall_then_deleverages() {
+        let mut accounts: BTreeMap<AccountId, Account> = [
+            account("bankrupt", 100.0, OrderSide::Buy, 10.0),
+            account("short", 1_000.0, OrderSide::Sell, 10.0),
+            account("solvent", 1_000.0, OrderSide::Buy, 1.0),
+        ]
+        .into_iter()
+        .collect();
+        let limits = RiskLimits::default();
+        let mut liquidator = Liquidator::new(limits, InsuranceFund::new(30.0));
+
+        // A drop to 80 leaves the leveraged long 100 short of zero
+        let events = liquidator.run(&mut accounts, |_| Some(80.0), Utc::now());
+        assert_eq!(events.len(), 1);
+        let event = &events[0];
+        assert_eq!(event.account, AccountId::from("bankrupt"));
+        assert_eq!((event.shortfall, event.covered_by_fund), (100.0, 30.0));
+        assert_eq!(liquidator.fund().balance(), 0.0);
+
+        // The short gives up the rest, closed at the bankruptcy price of 87
+        assert_eq!(event.deleveraged.len(), 1);
+        let deleverage = &event.deleveraged[0];
+        assert_eq!(deleverage.quantity, -10.0);
+        assert!((deleverage.price - 87.0).abs() < 1e-9);
+        assert!((deleverage.cost - 70.0).abs() < 1e-9);
+        assert!(event.uncovered.abs() < 1e-9);
+        assert!(accounts[&AccountId::from("bankrupt")].balance.abs() < 1e-9);
+        assert!((accounts[&AccountId::from("short")].balance - 1_130.0).abs() < 1e-9);
+        assert_eq!(liquidator.fund().history().len(), 2);
+    }
+
+    #[test]
+    fn test_proximity_alerts_escalate_once_per_threshold() {
+        use std::sync::{Arc, Mutex};
+
+        let (_, mut account) = account("alice", 500.0, OrderSide::Buy, 10.0);
+        RiskLimits::default().update_liquidation_prices(&mut account, |_| None);
+        let liquidation = account.positions["BTCUSDT"].liquidation_price.unwrap();
+        assert!((liquidation - 500.0 / 9.45).abs() < 1e-9);
+
+        let delivered = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&delivered);
+        let mut monitor = ProximityMonitor::default()
+            .with_sink(move |event: &AlertEvent| sink.lock().unwrap().push(event.clone()));
+        let threshold = |event: &AlertEvent| match event.condition {
+            AlertCondition::LiquidationProximity {
+                threshold_percent, ..
+            } => threshold_percent,
+            _ => unreachable!(),
+        };
+
+        assert!(monitor.check(&account, |_| Some(60.0), 0).is_empty());
+        let events = monitor.check(&account, |_| Some(57.0), 1);
+        assert_eq!(events.iter().map(threshold).collect::<Vec<_>>(), [10.0]);
+        assert!(monitor.check(&account, |_| Some(56.5), 2).is_empty());
+        // Jumping straight inside 2% raises only the nearest threshold
+        let events = monitor.check(&account, |_| Some(53.5), 3);
+        assert_eq!(events.iter().map(threshold).collect::<Vec<_>>(), [2.0]);
+        assert_eq!(events[0].user, "alice");
+
+        // Moving back out re-arms the thresholds left behind
+        assert!(monitor.check(&account, |_| Some(58.0), 4).is_empty());
+        assert_eq!(monitor.check(&account, |_| Some(55.0), 5).len(), 1);
+        assert_eq!(delivered.lock().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_solvent_liquidation_pays_fee_into_fund() {
+        let mut accounts: BTreeMap<AccountId, Account> =
+            [account("thin", 100.0, OrderSide::Buy, 10.0)]
+                .into_iter()
+                .collect();
+        let mut liquidator = Liquidator::new(RiskLimits::default(), InsuranceFund::default());
+
+        // Equity of 50 still covers the 5% maintenance margin on 950 notional
+        assert!(liquidator
+            .run(&mut accounts, |_| Some(95.0), Utc::now())
+            .is_empty());
+        let events = liquidator.run(&mut accounts, |_| Some(94.5), Utc::now());
+        assert_eq!(events.len(), 1);
+        assert!((events[0].fee - 4.725).abs() < 1e-9);
+        assert!((liquidator.fund().balance() - 4.725).abs() < 1e-9);
+        assert!(accounts[&AccountId::from("thin")]
+            .open_positions()
+            .is_empty());
+    }
+}
@@ -1,11 +1,31 @@
 // High-Performance Cryptocurrency Trading Engine
 // Demonstrates: WebSocket feeds, Order book matching, Async Rust, Market microstructure
 
-use crypto_orderbook::{BinanceFeed, Order, OrderSide, SharedOrderBook};
+use crypto_orderbook::market::TapeTrade;
+use crypto_orderbook::orderbook::ExecutionReport;
+use crypto_orderbook::overload::SharedLoadShedder;
+use crypto_orderbook::throughput::SharedThroughputMeter;
+use crypto_orderbook::utils::{read_spill, Filter, Queryable};
+use crypto_orderbook::{BinanceFeed, BookManager, Order, OrderSide};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 use std::io::{self, Write};
+use std::path::Path;
+use std::time::Duration;
 
 #[tokio::main]
 async fn main() {
+    // `trading-engine history trades|orders <file.jsonl> [filter]` searches a
+    // spilled history instead of running the demo
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("history") {
+        if let Err(e) = history(&args[1..]) {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     // Initialize logging
     tracing_subscriber::fmt()
         .with_max_level(tracing::Level::INFO)
@@ -14,16 +34,30 @@ async fn main() {
     println!("\n🚀 High-Performance Crypto Order Book Engine");
     println!("==============================================\n");
 
+    // Calibrate latency spans before any order is timed
+    crypto_orderbook::latency::calibrate();
+
     // Create order book for BTC/USDT
-    let orderbook = SharedOrderBook::new("BTCUSDT".to_string());
+    // Order entry and feeds share one overload detector
+    let shedder = SharedLoadShedder::default();
+    let throughput = SharedThroughputMeter::default();
+    // Local orders match in the matching book; Binance depth goes to separate mirror books
+    let books = BookManager::new()
+        .with_load_shedder(shedder.clone())
+        .with_throughput(throughput.clone());
+    let orderbook = books.matching("BTCUSDT");
 
     // Initialize Binance WebSocket feeds
     let symbols = vec!["BTCUSDT".to_string(), "ETHUSDT".to_string(), "SOLUSDT".to_string()];
-    let feed = BinanceFeed::new(symbols);
+    let feed = BinanceFeed::new(symbols)
+        .with_load_shedder(shedder)
+        .with_throughput(throughput);
 
     // Start market data feeds
+    feed.start_clock_sync(Duration::from_secs(30)).await;
+    feed.start_rest_fallback(Duration::from_secs(10), Duration::from_secs(2)).await;
     feed.start_price_feed().await;
-    feed.start_depth_feed(orderbook.clone()).await;
+    feed.start_depth_feed(books.clone()).await;
 
     println!("✓ Connected to Binance WebSocket feeds");
     println!("✓ Streaming live market data...\n");
@@ -183,3 +217,32 @@ async fn main() {
         io::stdout().flush().unwrap();
     }
 }
+
+fn history(args: &[String]) -> Result<(), String> {
+    let usage = "usage: trading-engine history trades|orders <file.jsonl> [filter]";
+    let (kind, path) = match args {
+        [kind, path, ..] => (kind.as_str(), Path::new(path)),
+        _ => return Err(usage.to_string()),
+    };
+    let filter = args.get(2).map_or("", String::as_str);
+    match kind {
+        "trades" => print_matching::<TapeTrade>(path, filter),
+        "orders" => print_matching::<ExecutionReport>(path, filter),
+        _ => Err(usage.to_string()),
+    }
+}
+
+/// Print the records in `path` that `filter` keeps, one JSON line each
+fn print_matching<T: Queryable + Serialize + DeserializeOwned>(
+    path: &Path,
+    filter: &str,
+) -> Result<(), String> {
+    let filter = Filter::compile::<T>(filter).map_err(|e| format!("invalid filter: {}", e))?;
+    let records = read_spill::<T>(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let mut out = io::stdout().lock();
+    for record in records.iter().filter(|record| filter.matches(*record)) {
+        let line = serde_json::to_string(record).map_err(|e| e.to_string())?;
+        writeln!(out, "{}", line).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
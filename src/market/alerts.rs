@@ -0,0 +1,386 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::Symbol;
+
+/// Identifier of a price alert
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct AlertId(pub u64);
+
+impl AlertId {
+    /// Id of alerts raised by a monitor rather than created by a user
+    pub const SYSTEM: AlertId = AlertId(0);
+}
+
+/// What makes an alert fire
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum AlertCondition {
+    /// A trade at or above `level` after one below it
+    CrossesAbove { level: f64 },
+    /// A trade at or below `level` after one above it
+    CrossesBelow { level: f64 },
+    /// Price moved `percent` either way from any trade in the last `window_secs`
+    Move { percent: f64, window_secs: i64 },
+    /// Mark within `threshold_percent` of a position's liquidation price;
+    /// raised by the liquidation monitor, never by user alerts
+    LiquidationProximity {
+        liquidation_price: f64,
+        threshold_percent: f64,
+    },
+    /// A stablecoin's value `threshold_percent` or more off `peg`; raised
+    /// by the depeg monitor, never by user alerts
+    Depeg { peg: f64, threshold_percent: f64 },
+}
+
+/// How urgently a fired alert needs attention
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertSeverity {
+    Info,
+    Warning,
+    Critical,
+}
+
+impl AlertCondition {
+    pub fn severity(&self) -> AlertSeverity {
+        match self {
+            AlertCondition::CrossesAbove { .. }
+            | AlertCondition::CrossesBelow { .. }
+            | AlertCondition::Move { .. } => AlertSeverity::Info,
+            AlertCondition::LiquidationProximity { .. } => AlertSeverity::Warning,
+            AlertCondition::Depeg { .. } => AlertSeverity::Critical,
+        }
+    }
+
+    fn window_ms(&self) -> i64 {
+        match self {
+            AlertCondition::Move { window_secs, .. } => window_secs * 1_000,
+            _ => 0,
+        }
+    }
+}
+
+/// An alert as submitted by a user
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlertRequest {
+    pub symbol: Symbol,
+    pub condition: AlertCondition,
+    /// Keep the alert after it fires instead of removing it
+    #[serde(default)]
+    pub repeat: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Alert {
+    pub id: AlertId,
+    pub user: String,
+    pub symbol: Symbol,
+    pub condition: AlertCondition,
+    pub repeat: bool,
+}
+
+/// An alert that fired
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AlertEvent {
+    pub alert_id: AlertId,
+    pub user: String,
+    pub symbol: Symbol,
+    pub condition: AlertCondition,
+    pub severity: AlertSeverity,
+    pub price: f64,
+    pub timestamp_ms: i64,
+}
+
+/// Where fired alerts are delivered
+///
+/// Any `Fn(&AlertEvent)` closure is a sink, so WebSocket fan-out, webhooks
+/// or signal publishing need no new type.
+pub trait AlertSink: Send + Sync {
+    fn deliver(&self, event: &AlertEvent);
+}
+
+impl<F> AlertSink for F
+where
+    F: Fn(&AlertEvent) + Send + Sync,
+{
+    fn deliver(&self, event: &AlertEvent) {
+        self(event)
+    }
+}
+
+/// Recent trades of one symbol, kept as long as its longest move window
+#[derive(Debug, Default)]
+struct PriceWindow {
+    last: Option<f64>,
+    trades: VecDeque<(i64, f64)>,
+}
+
+/// Evaluates user price alerts against the trade stream
+///
+/// Crossings compare each trade with the previous one, so an alert created
+/// while price is already past its level waits for the next cross. Moves are
+/// measured against the lowest and highest trades still in the window.
+#[derive(Default)]
+pub struct AlertEngine {
+    next_id: u64,
+    alerts: BTreeMap<AlertId, Alert>,
+    windows: HashMap<Symbol, PriceWindow>,
+    sinks: Vec<Box<dyn AlertSink>>,
+}
+
+impl AlertEngine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Deliver every fired alert to `sink` as well
+    pub fn with_sink(mut self, sink: impl AlertSink + 'static) -> Self {
+        self.sinks.push(Box::new(sink));
+        self
+    }
+
+    pub fn add_sink(&mut self, sink: impl AlertSink + 'static) {
+        self.sinks.push(Box::new(sink));
+    }
+
+    pub fn create(&mut self, user: &str, request: AlertRequest) -> Alert {
+        self.next_id += 1;
+        let alert = Alert {
+            id: AlertId(self.next_id),
+            user: user.to_string(),
+            symbol: request.symbol,
+            condition: request.condition,
+            repeat: request.repeat,
+        };
+        self.alerts.insert(alert.id, alert.clone());
+        alert
+    }
+
+    /// Remove one of `user`'s alerts; false when they have no such alert
+    pub fn remove(&mut self, user: &str, id: AlertId) -> bool {
+        if self.alerts.get(&id).is_some_and(|alert| alert.user == user) {
+            self.alerts.remove(&id);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Alerts of `user`, oldest first
+    pub fn list(&self, user: &str) -> Vec<Alert> {
+        self.alerts
+            .values()
+            .filter(|alert| alert.user == user)
+            .cloned()
+            .collect()
+    }
+
+    pub fn on_trade(&mut self, symbol: &str, price: f64, timestamp_ms: i64) -> Vec<AlertEvent> {
+        let longest_ms = self
+            .alerts
+            .values()
+            .filter(|alert| alert.symbol == symbol)
+            .map(|alert| alert.condition.window_ms())
+            .max();
+        let Some(longest_ms) = longest_ms else {
+            self.windows.remove(symbol);
+            return Vec::new();
+        };
+
+        let window = self.windows.entry(Symbol::new(symbol)).or_default();
+        let previous = window.last.replace(price);
+        while window
+            .trades
+            .front()
+            .is_some_and(|(ts, _)| *ts < timestamp_ms - longest_ms)
+        {
+            window.trades.pop_front();
+        }
+
+        let mut fired = Vec::new();
+        for alert in self.alerts.values().filter(|alert| alert.symbol == symbol) {
+            let triggered = match alert.condition {
+                AlertCondition::CrossesAbove { level } => {
+                    previous.is_some_and(|previous| previous < level && price >= level)
+                }
+                AlertCondition::CrossesBelow { level } => {
+                    previous.is_some_and(|previous| previous > level && price <= level)
+                }
+                AlertCondition::Move {
+                    percent,
+                    window_secs,
+                } => {
+                    let since = timestamp_ms - window_secs * 1_000;
+                    window
+                        .trades
+                        .iter()
+                        .filter(|(ts, _)| *ts >= since)
+                        .any(|(_, then)| ((price - then) / then).abs() * 100.0 >= percent)
+                }
+                AlertCondition::LiquidationProximity { .. } | AlertCondition::Depeg { .. } => false,
+            };
+            if triggered {
+                fired.push(AlertEvent {
+                    alert_id: alert.id,
+                    user: alert.user.clone(),
+                    symbol: alert.symbol.clone(),
+                    condition: alert.condition,
+                    severity: alert.condition.severity(),
+                    price,
+                    timestamp_ms,
+                });
+            }
+        }
+        window.trades.push_back((timestamp_ms, price));
+
+        for event in &fired {
+            if !self.alerts[&event.alert_id].repeat {
+                self.alerts.remove(&event.alert_id);
+            } else if matches!(event.condition, AlertCondition::Move { .. }) {
+                // Measure the next move from here rather than firing on every trade
+                window.trades.clear();
+                window.trades.push_back((timestamp_ms, price));
+            }
+            tracing::info!(
+                "Alert {} for {} fired on {} at {}",
+                event.alert_id.0,
+                event.user,
+                event.symbol,
+                event.price
+            );
+            for sink in &self.sinks {
+                sink.deliver(event);
+            }
+        }
+        fired
+    }
+}
+
+/// Thread-safe wrapper for AlertEngine
+pub struct SharedAlertEngine {
+    inner: Arc<Mutex<AlertEngine>>,
+}
+
+impl SharedAlertEngine {
+    pub fn new(engine: AlertEngine) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(engine)),
+        }
+    }
+
+    pub fn add_sink(&self, sink: impl AlertSink + 'static) {
+        self.inner.lock().unwrap().add_sink(sink)
+    }
+
+    pub fn create(&self, user: &str, request: AlertRequest) -> Alert {
+        self.inner.lock().unwrap().create(user, request)
+    }
+
+    pub fn remove(&self, user: &str, id: AlertId) -> bool {
+        self.inner.lock().unwrap().remove(user, id)
+    }
+
+    pub fn list(&self, user: &str) -> Vec<Alert> {
+        self.inner.lock().unwrap().list(user)
+    }
+
+    pub fn on_trade(&self, symbol: &str, price: f64, timestamp_ms: i64) -> Vec<AlertEvent> {
+        self.inner
+            .lock()
+            .unwrap()
+            .on_trade(symbol, price, timestamp_ms)
+    }
+}
+
+impl Clone for SharedAlertEngine {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl Default for SharedAlertEngine {
+    fn default() -> Self {
+        Self::new(AlertEngine::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(condition: AlertCondition, repeat: bool) -> AlertRequest {
+        AlertRequest {
+            symbol: Symbol::new("BTCUSDT"),
+            condition,
+            repeat,
+        }
+    }
+
+    #[test]
+    fn test_cross_fires_once_and_is_removed() {
+        let delivered = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&delivered);
+        let mut engine = AlertEngine::new()
+            .with_sink(move |event: &AlertEvent| sink.lock().unwrap().push(event.alert_id));
+        let alert = engine.create(
+            "alice",
+            request(AlertCondition::CrossesAbove { level: 100.0 }, false),
+        );
+
+        // Already above the level when the first trade arrives: no cross
+        assert!(engine.on_trade("BTCUSDT", 101.0, 0).is_empty());
+        assert!(engine.on_trade("BTCUSDT", 99.0, 1).is_empty());
+        let fired = engine.on_trade("BTCUSDT", 100.0, 2);
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].price, 100.0);
+        assert_eq!(*delivered.lock().unwrap(), [alert.id]);
+
+        assert!(engine.list("alice").is_empty());
+        assert!(engine.on_trade("BTCUSDT", 99.0, 3).is_empty());
+        assert!(engine.on_trade("BTCUSDT", 101.0, 4).is_empty());
+    }
+
+    #[test]
+    fn test_move_within_window() {
+        let mut engine = AlertEngine::new();
+        engine.create(
+            "alice",
+            request(
+                AlertCondition::Move {
+                    percent: 5.0,
+                    window_secs: 60,
+                },
+                true,
+            ),
+        );
+
+        engine.on_trade("BTCUSDT", 100.0, 0);
+        // 5% up, but the 100 trade has left the window
+        assert!(engine.on_trade("BTCUSDT", 105.0, 61_000).is_empty());
+        assert!(engine.on_trade("BTCUSDT", 102.0, 62_000).is_empty());
+        assert_eq!(engine.on_trade("BTCUSDT", 107.5, 63_000).len(), 1);
+
+        // A repeating move alert measures from where it last fired
+        assert!(engine.on_trade("BTCUSDT", 103.0, 64_000).is_empty());
+        assert_eq!(engine.list("alice").len(), 1);
+    }
+
+    #[test]
+    fn test_users_only_remove_their_own_alerts() {
+        let mut engine = AlertEngine::new();
+        let alert = engine.create(
+            "alice",
+            request(AlertCondition::CrossesBelow { level: 90.0 }, false),
+        );
+        assert!(!engine.remove("bob", alert.id));
+        assert!(engine.list("bob").is_empty());
+        assert!(engine.remove("alice", alert.id));
+        assert!(!engine.remove("alice", alert.id));
+    }
+}
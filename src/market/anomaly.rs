@@ -0,0 +1,416 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use chrono::{TimeZone, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::signals::{SharedSignalBus, Signal};
+use crate::types::Symbol;
+use crate::utils::{BoundedHistory, Retention, Timestamped};
+
+/// Anomalies kept for [`AnomalyDetector::recent`] by default
+const RECENT_CAPACITY: usize = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AnomalyConfig {
+    /// EWMA weight of each new observation
+    pub alpha: f64,
+    /// Absolute z-score that counts as an anomaly
+    pub threshold: f64,
+    /// Observations needed before scoring starts
+    pub warmup: u64,
+    /// Keep a separate baseline per UTC hour of day
+    pub seasonal: bool,
+    /// Interval over which volume and message counts are aggregated
+    pub bucket_ms: i64,
+}
+
+impl Default for AnomalyConfig {
+    fn default() -> Self {
+        Self {
+            alpha: 0.05,
+            threshold: 4.0,
+            warmup: 30,
+            seasonal: false,
+            bucket_ms: 1_000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnomalyMetric {
+    /// Trade-to-trade log return
+    Return,
+    /// Traded quantity per bucket
+    Volume,
+    /// Feed messages per bucket
+    MessageRate,
+}
+
+impl AnomalyMetric {
+    /// Signal name anomalies are published under
+    pub fn signal_name(&self) -> &'static str {
+        match self {
+            AnomalyMetric::Return => "anomaly.return",
+            AnomalyMetric::Volume => "anomaly.volume",
+            AnomalyMetric::MessageRate => "anomaly.message_rate",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AnomalyEvent {
+    pub symbol: Symbol,
+    pub metric: AnomalyMetric,
+    pub value: f64,
+    pub z_score: f64,
+    pub timestamp_ms: i64,
+}
+
+impl Timestamped for AnomalyEvent {
+    fn timestamp_ms(&self) -> i64 {
+        self.timestamp_ms
+    }
+}
+
+/// Exponentially weighted mean and variance
+#[derive(Debug, Clone, Copy, Default)]
+struct Ewma {
+    mean: f64,
+    variance: f64,
+    samples: u64,
+}
+
+impl Ewma {
+    /// Score `x` against the baseline so far, then fold it in
+    fn score(&mut self, x: f64, config: &AnomalyConfig) -> Option<f64> {
+        let z = (self.samples >= config.warmup && self.variance > 0.0)
+            .then(|| (x - self.mean) / self.variance.sqrt());
+
+        if self.samples == 0 {
+            self.mean = x;
+        } else {
+            let diff = x - self.mean;
+            self.mean += config.alpha * diff;
+            self.variance = (1.0 - config.alpha) * (self.variance + config.alpha * diff * diff);
+        }
+        self.samples += 1;
+        z
+    }
+}
+
+/// One baseline, or one per hour of day when seasonal
+#[derive(Debug, Clone)]
+struct Baseline {
+    hours: Vec<Ewma>,
+}
+
+impl Baseline {
+    fn new(seasonal: bool) -> Self {
+        Self {
+            hours: vec![Ewma::default(); if seasonal { 24 } else { 1 }],
+        }
+    }
+
+    fn score(&mut self, x: f64, timestamp_ms: i64, config: &AnomalyConfig) -> Option<f64> {
+        let index = if self.hours.len() == 24 {
+            Utc.timestamp_millis_opt(timestamp_ms)
+                .single()
+                .map_or(0, |t| t.hour() as usize)
+        } else {
+            0
+        };
+        self.hours[index].score(x, config)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct SymbolState {
+    last_price: Option<f64>,
+    bucket_start: Option<i64>,
+    volume: f64,
+    messages: u64,
+    returns: Baseline,
+    volumes: Baseline,
+    rates: Baseline,
+}
+
+impl SymbolState {
+    fn new(seasonal: bool) -> Self {
+        Self {
+            last_price: None,
+            bucket_start: None,
+            volume: 0.0,
+            messages: 0,
+            returns: Baseline::new(seasonal),
+            volumes: Baseline::new(seasonal),
+            rates: Baseline::new(seasonal),
+        }
+    }
+}
+
+/// Online per-symbol detector for returns, volume and message rates
+///
+/// Each observation is scored against an EWMA baseline before being added
+/// to it. Anomalies are logged, kept in a short history and, with a signal
+/// bus attached, published as `anomaly.*` signals carrying the z-score.
+#[derive(Debug)]
+pub struct AnomalyDetector {
+    config: AnomalyConfig,
+    symbols: HashMap<Symbol, SymbolState>,
+    recent: BoundedHistory<AnomalyEvent>,
+    bus: Option<SharedSignalBus>,
+}
+
+impl AnomalyDetector {
+    pub fn new(config: AnomalyConfig) -> Self {
+        Self {
+            config,
+            symbols: HashMap::new(),
+            recent: BoundedHistory::new("anomalies", Retention::count(RECENT_CAPACITY)),
+            bus: None,
+        }
+    }
+
+    pub fn with_signal_bus(mut self, bus: SharedSignalBus) -> Self {
+        self.bus = Some(bus);
+        self
+    }
+
+    /// Replace the default of keeping the last 256 anomalies
+    pub fn with_retention(mut self, retention: Retention) -> Self {
+        self.recent = BoundedHistory::new("anomalies", retention);
+        self
+    }
+
+    pub fn on_trade(
+        &mut self,
+        symbol: &str,
+        price: f64,
+        quantity: f64,
+        timestamp_ms: i64,
+    ) -> Vec<AnomalyEvent> {
+        let mut events = self.roll(symbol, timestamp_ms);
+        let config = self.config;
+        let state = self.state(symbol);
+        state.volume += quantity;
+        state.messages += 1;
+
+        if let Some(previous) = state.last_price.replace(price) {
+            if previous > 0.0 && price > 0.0 {
+                let ret = (price / previous).ln();
+                if let Some(z) = state.returns.score(ret, timestamp_ms, &config) {
+                    events.extend(anomaly(
+                        &config,
+                        symbol,
+                        AnomalyMetric::Return,
+                        ret,
+                        z,
+                        timestamp_ms,
+                    ));
+                }
+            }
+        }
+        self.emit(&events);
+        events
+    }
+
+    /// Count a non-trade feed message, e.g. a depth update
+    pub fn on_message(&mut self, symbol: &str, timestamp_ms: i64) -> Vec<AnomalyEvent> {
+        let events = self.roll(symbol, timestamp_ms);
+        self.state(symbol).messages += 1;
+        self.emit(&events);
+        events
+    }
+
+    /// Most recent anomalies, oldest first
+    pub fn recent(&self) -> Vec<AnomalyEvent> {
+        self.recent.iter().cloned().collect()
+    }
+
+    fn state(&mut self, symbol: &str) -> &mut SymbolState {
+        let seasonal = self.config.seasonal;
+        self.symbols
+            .entry(Symbol::new(symbol))
+            .or_insert_with(|| SymbolState::new(seasonal))
+    }
+
+    /// Score the previous bucket once `timestamp_ms` moves past it
+    fn roll(&mut self, symbol: &str, timestamp_ms: i64) -> Vec<AnomalyEvent> {
+        let config = self.config;
+        let bucket_ms = config.bucket_ms.max(1);
+        let start = timestamp_ms - timestamp_ms.rem_euclid(bucket_ms);
+        let state = self.state(symbol);
+
+        let mut events = Vec::new();
+        match state.bucket_start {
+            Some(previous) if start > previous => {
+                let (volume, messages) = (state.volume, state.messages as f64);
+                if let Some(z) = state.volumes.score(volume, previous, &config) {
+                    events.extend(anomaly(
+                        &config,
+                        symbol,
+                        AnomalyMetric::Volume,
+                        volume,
+                        z,
+                        previous,
+                    ));
+                }
+                if let Some(z) = state.rates.score(messages, previous, &config) {
+                    events.extend(anomaly(
+                        &config,
+                        symbol,
+                        AnomalyMetric::MessageRate,
+                        messages,
+                        z,
+                        previous,
+                    ));
+                }
+                state.volume = 0.0;
+                state.messages = 0;
+                state.bucket_start = Some(start);
+            }
+            Some(_) => {}
+            None => state.bucket_start = Some(start),
+        }
+        events
+    }
+
+    fn emit(&mut self, events: &[AnomalyEvent]) {
+        for event in events {
+            tracing::warn!(
+                "Anomaly on {}: {:?} = {:.6} (z = {:.1})",
+                event.symbol,
+                event.metric,
+                event.value,
+                event.z_score
+            );
+            if let Some(bus) = &self.bus {
+                bus.publish(Signal::number(
+                    event.metric.signal_name(),
+                    event.symbol.as_str(),
+                    event.z_score,
+                    event.timestamp_ms,
+                ));
+            }
+            self.recent.push(event.clone());
+        }
+    }
+}
+
+fn anomaly(
+    config: &AnomalyConfig,
+    symbol: &str,
+    metric: AnomalyMetric,
+    value: f64,
+    z_score: f64,
+    timestamp_ms: i64,
+) -> Option<AnomalyEvent> {
+    (z_score.abs() >= config.threshold).then(|| AnomalyEvent {
+        symbol: Symbol::new(symbol),
+        metric,
+        value,
+        z_score,
+        timestamp_ms,
+    })
+}
+
+/// Thread-safe wrapper for AnomalyDetector
+pub struct SharedAnomalyDetector {
+    inner: Arc<Mutex<AnomalyDetector>>,
+}
+
+impl SharedAnomalyDetector {
+    pub fn new(detector: AnomalyDetector) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(detector)),
+        }
+    }
+
+    pub fn on_trade(
+        &self,
+        symbol: &str,
+        price: f64,
+        quantity: f64,
+        timestamp_ms: i64,
+    ) -> Vec<AnomalyEvent> {
+        self.inner
+            .lock()
+            .unwrap()
+            .on_trade(symbol, price, quantity, timestamp_ms)
+    }
+
+    pub fn on_message(&self, symbol: &str, timestamp_ms: i64) -> Vec<AnomalyEvent> {
+        self.inner.lock().unwrap().on_message(symbol, timestamp_ms)
+    }
+
+    pub fn recent(&self) -> Vec<AnomalyEvent> {
+        self.inner.lock().unwrap().recent()
+    }
+}
+
+impl Clone for SharedAnomalyDetector {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl Default for SharedAnomalyDetector {
+    fn default() -> Self {
+        Self::new(AnomalyDetector::new(AnomalyConfig::default()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> AnomalyConfig {
+        AnomalyConfig {
+            warmup: 20,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_price_jump_is_flagged_and_published() {
+        let bus = SharedSignalBus::default();
+        let mut signals = bus.subscribe(&[AnomalyMetric::Return.signal_name()]);
+        let mut detector = AnomalyDetector::new(config()).with_signal_bus(bus);
+
+        for i in 0..50 {
+            let price = if i % 2 == 0 { 100.0 } else { 100.1 };
+            assert!(detector.on_trade("BTCUSDT", price, 1.0, i).is_empty());
+        }
+        let events = detector.on_trade("BTCUSDT", 110.0, 1.0, 50);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].metric, AnomalyMetric::Return);
+        assert!(events[0].z_score > config().threshold);
+
+        assert!(signals.poll().value("anomaly.return", "BTCUSDT").is_some());
+        assert_eq!(detector.recent(), events);
+    }
+
+    #[test]
+    fn test_volume_spike_scored_when_bucket_closes() {
+        let detector = SharedAnomalyDetector::new(AnomalyDetector::new(config()));
+        for bucket in 0..40 {
+            let quantity = if bucket % 2 == 0 { 1.0 } else { 1.2 };
+            detector.on_trade("ETHUSDT", 2_000.0, quantity, bucket * 1_000);
+            detector.on_message("ETHUSDT", bucket * 1_000 + 500);
+        }
+        detector.on_trade("ETHUSDT", 2_000.0, 50.0, 40_000);
+        assert!(detector.recent().is_empty());
+
+        // The spike is only known once its bucket is complete
+        let events = detector.on_message("ETHUSDT", 41_000);
+        assert_eq!(events.len(), 1);
+        assert_eq!(
+            (events[0].metric, events[0].value, events[0].timestamp_ms),
+            (AnomalyMetric::Volume, 50.0, 40_000)
+        );
+    }
+}
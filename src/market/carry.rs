@@ -0,0 +1,222 @@
+use serde::Serialize;
+
+use crate::account::Account;
+use crate::market::Instrument;
+use crate::timeseries::SharedTimeSeriesStore;
+use crate::types::Symbol;
+
+/// Perpetual funding settles every eight hours
+pub const FUNDING_INTERVALS_PER_DAY: f64 = 3.0;
+const DAYS_PER_YEAR: f64 = 365.0;
+
+/// Latest funding and borrow rates in the time-series store
+#[derive(Clone)]
+pub struct CarryRates {
+    store: SharedTimeSeriesStore,
+    instruments: Vec<Instrument>,
+}
+
+impl CarryRates {
+    /// Rates of `store`, with the base asset of each symbol taken from
+    /// `instruments`
+    pub fn new(store: SharedTimeSeriesStore, instruments: Vec<Instrument>) -> Self {
+        Self { store, instruments }
+    }
+
+    /// Funding rate of `symbol` per interval
+    pub fn funding(&self, symbol: &Symbol) -> Option<f64> {
+        self.store
+            .latest(&format!("funding:{}", symbol))
+            .map(|(_, rate)| rate)
+    }
+
+    /// Daily borrow rate of `asset`
+    pub fn borrow(&self, asset: &str) -> Option<f64> {
+        self.store
+            .latest(&format!("borrow:{}", asset))
+            .map(|(_, rate)| rate)
+    }
+
+    fn base(&self, symbol: &Symbol) -> Option<&str> {
+        self.instruments
+            .iter()
+            .find(|i| &i.symbol == symbol)
+            .map(|i| i.base.as_str())
+    }
+}
+
+/// Side of the spot leg a funding rate pays to hold against the perpetual
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CarryDirection {
+    /// Positive funding: longs pay shorts, so buy spot and short the perp
+    LongSpot,
+    /// Negative funding: shorts pay longs, so borrow and sell spot, long the perp
+    ShortSpot,
+}
+
+/// Annualized cash-and-carry return of one symbol
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Basis {
+    pub symbol: Symbol,
+    /// Latest funding rate per interval
+    pub funding_rate: f64,
+    pub funding_apr: f64,
+    /// Borrow cost of the base asset, when known
+    pub borrow_apr: Option<f64>,
+    pub direction: CarryDirection,
+    /// Funding earned less what the spot leg costs to borrow
+    pub carry_apr: f64,
+}
+
+/// Cash-and-carry of every instrument with a funding rate, best first
+///
+/// Shorting spot needs the base asset borrowed, so negative funding only
+/// counts where a borrow rate is known.
+pub fn scan_basis(rates: &CarryRates) -> Vec<Basis> {
+    let mut scan: Vec<Basis> = rates
+        .instruments
+        .iter()
+        .filter_map(|instrument| {
+            let funding_rate = rates.funding(&instrument.symbol)?;
+            let funding_apr = funding_rate * FUNDING_INTERVALS_PER_DAY * DAYS_PER_YEAR;
+            let borrow_apr = rates
+                .borrow(&instrument.base)
+                .map(|rate| rate * DAYS_PER_YEAR);
+            let (direction, carry_apr) = if funding_rate >= 0.0 {
+                (CarryDirection::LongSpot, funding_apr)
+            } else {
+                (CarryDirection::ShortSpot, -funding_apr - borrow_apr?)
+            };
+            Some(Basis {
+                symbol: instrument.symbol.clone(),
+                funding_rate,
+                funding_apr,
+                borrow_apr,
+                direction,
+                carry_apr,
+            })
+        })
+        .collect();
+    scan.sort_by(|a, b| b.carry_apr.total_cmp(&a.carry_apr));
+    scan
+}
+
+/// Daily cost of holding one position; negative when it earns
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PositionCarry {
+    pub symbol: Symbol,
+    /// Signed notional at the mark
+    pub notional: f64,
+    /// Funding paid as a perpetual
+    pub funding: f64,
+    /// Interest on the borrowed base asset of a short
+    pub borrow: f64,
+}
+
+/// Daily cost of holding an account's open positions at the latest rates
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CarryCost {
+    pub positions: Vec<PositionCarry>,
+    pub funding: f64,
+    pub borrow: f64,
+    pub total: f64,
+}
+
+/// Funding and borrow cost per day of `account`'s positions
+///
+/// Longs pay funding and shorts receive it when the rate is positive.
+/// Shorts are taken to borrow their base asset; longs are taken to be
+/// funded from the balance. Positions without a rate cost nothing on it.
+pub fn carry_cost(
+    account: &Account,
+    mark: impl Fn(&Symbol) -> Option<f64>,
+    rates: &CarryRates,
+) -> CarryCost {
+    let positions: Vec<PositionCarry> = account
+        .open_positions()
+        .into_iter()
+        .map(|position| {
+            let price = mark(&position.symbol).unwrap_or(position.entry_price);
+            let notional = position.quantity * price;
+            let funding = rates
+                .funding(&position.symbol)
+                .map_or(0.0, |rate| notional * rate * FUNDING_INTERVALS_PER_DAY);
+            let borrow = match rates.base(&position.symbol) {
+                Some(base) if notional < 0.0 => {
+                    rates.borrow(base).map_or(0.0, |rate| -notional * rate)
+                }
+                _ => 0.0,
+            };
+            PositionCarry {
+                symbol: position.symbol,
+                notional,
+                funding,
+                borrow,
+            }
+        })
+        .collect();
+    let funding = positions.iter().map(|p| p.funding).sum::<f64>();
+    let borrow = positions.iter().map(|p| p.borrow).sum::<f64>();
+    CarryCost {
+        positions,
+        funding,
+        borrow,
+        total: funding + borrow,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testkit::{self, golden_time};
+    use crate::types::OrderSide;
+
+    fn instrument(symbol: &str, base: &str) -> Instrument {
+        serde_json::from_value(serde_json::json!({
+            "symbol": symbol, "base": base, "quote": "USDT", "tick_size": 0.01,
+            "lot_size": 0.001, "price_precision": 2, "quantity_precision": 3
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_basis_scan_and_carry_cost_follow_latest_rates() {
+        let store = SharedTimeSeriesStore::default();
+        let at = golden_time();
+        store.record_funding("BTCUSDT", 0.0003, at);
+        store.record_funding("BTCUSDT", 0.0001, at + chrono::Duration::hours(8));
+        store.record_funding("ETHUSDT", -0.0004, at);
+        store.record_funding("SOLUSDT", -0.0004, at);
+        store.record_borrow("ETH", 0.0002, at);
+        let rates = CarryRates::new(
+            store,
+            vec![
+                instrument("BTCUSDT", "BTC"),
+                instrument("ETHUSDT", "ETH"),
+                instrument("SOLUSDT", "SOL"),
+            ],
+        );
+
+        // SOL has negative funding but nothing to borrow it with
+        let scan = scan_basis(&rates);
+        assert_eq!(scan.len(), 2);
+        assert_eq!(scan[0].symbol, Symbol::from("ETHUSDT"));
+        assert_eq!(scan[0].direction, CarryDirection::ShortSpot);
+        assert!((scan[0].carry_apr - (0.0012 - 0.0002) * 365.0).abs() < 1e-9);
+        // The later BTC funding rate replaced the earlier one
+        assert!((scan[1].funding_apr - 0.0001 * 3.0 * 365.0).abs() < 1e-9);
+
+        let account = testkit::account("alice")
+            .deposit(10_000.0)
+            .trade("BTCUSDT", OrderSide::Buy, 1.0, 1_000.0, 0.0)
+            .trade("ETHUSDT", OrderSide::Sell, 10.0, 100.0, 0.0)
+            .build();
+        let cost = carry_cost(&account, |_| None, &rates);
+        // The long pays 3 x 0.01%; funding is negative on ETH, so the short
+        // pays 3 x 0.04% too, on top of borrowing at 0.02%
+        assert!((cost.funding - (0.3 + 1.2)).abs() < 1e-9);
+        assert!((cost.borrow - 0.2).abs() < 1e-9);
+        assert!((cost.total - 1.7).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,209 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+use serde_json::Value;
+
+use crate::market::tape::{TapeTrade, TradeSource};
+use crate::orderbook::{BookKind, BookManager};
+use crate::types::{OrderId, OrderSide};
+
+/// Fields attached to a trade by enrichers
+pub type Metadata = BTreeMap<String, Value>;
+
+/// Adds metadata to a trade before it is recorded
+///
+/// Any `Fn(&TapeTrade, &mut Metadata)` closure is an enricher, so ad-hoc
+/// fields need no new type.
+pub trait Enricher: Send + Sync {
+    fn enrich(&self, trade: &TapeTrade, metadata: &mut Metadata);
+}
+
+impl<F> Enricher for F
+where
+    F: Fn(&TapeTrade, &mut Metadata) + Send + Sync,
+{
+    fn enrich(&self, trade: &TapeTrade, metadata: &mut Metadata) {
+        self(trade, metadata)
+    }
+}
+
+/// Enrichers run in order, each seeing the fields added before it
+#[derive(Default)]
+pub struct EnrichmentPipeline {
+    enrichers: Vec<Box<dyn Enricher>>,
+}
+
+impl EnrichmentPipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, enricher: impl Enricher + 'static) -> Self {
+        self.enrichers.push(Box::new(enricher));
+        self
+    }
+
+    pub fn enrich(&self, trade: &mut TapeTrade) {
+        let mut metadata = std::mem::take(&mut trade.metadata);
+        for enricher in &self.enrichers {
+            enricher.enrich(trade, &mut metadata);
+        }
+        trade.metadata = metadata;
+    }
+}
+
+/// Mirror mid at the time of the fill and the fill's slippage against it
+///
+/// Adds `benchmark_mid` and, when the aggressor is known, `slippage_bps`
+/// (positive when the aggressor paid more than mid).
+pub struct BenchmarkEnricher {
+    books: BookManager,
+}
+
+impl BenchmarkEnricher {
+    pub fn new(books: BookManager) -> Self {
+        Self { books }
+    }
+}
+
+impl Enricher for BenchmarkEnricher {
+    fn enrich(&self, trade: &TapeTrade, metadata: &mut Metadata) {
+        let Some(mirror) = self.books.get(&trade.symbol, BookKind::Mirror) else {
+            return;
+        };
+        let (Some(bid), Some(ask)) = (mirror.best_bid(), mirror.best_ask()) else {
+            return;
+        };
+        let mid = (bid + ask) / 2.0;
+        metadata.insert("benchmark_mid".to_string(), mid.into());
+
+        if let Some(side) = trade.aggressor {
+            let sign = if side == OrderSide::Buy { 1.0 } else { -1.0 };
+            let slippage_bps = sign * (trade.price.value() - mid) / mid * 10_000.0;
+            metadata.insert("slippage_bps".to_string(), slippage_bps.into());
+        }
+    }
+}
+
+/// Touch and near-touch depth of the book the trade happened on
+///
+/// Local fills read the matching book and exchange trades the mirror. The
+/// book is read right after matching, so it already reflects the fill.
+pub struct BookStateEnricher {
+    books: BookManager,
+    levels: usize,
+}
+
+impl BookStateEnricher {
+    pub fn new(books: BookManager, levels: usize) -> Self {
+        Self { books, levels }
+    }
+}
+
+impl Enricher for BookStateEnricher {
+    fn enrich(&self, trade: &TapeTrade, metadata: &mut Metadata) {
+        let kind = match trade.source {
+            TradeSource::Local => BookKind::Matching,
+            TradeSource::Exchange => BookKind::Mirror,
+        };
+        let Some(book) = self.books.get(&trade.symbol, kind) else {
+            return;
+        };
+
+        let (bids, asks) = book.get_depth(self.levels);
+        let depth = |levels: &[(f64, f64)]| levels.iter().map(|&(_, qty)| qty).sum::<f64>();
+        metadata.insert("book_best_bid".to_string(), book.best_bid().into());
+        metadata.insert("book_best_ask".to_string(), book.best_ask().into());
+        metadata.insert("book_bid_depth".to_string(), depth(&bids).into());
+        metadata.insert("book_ask_depth".to_string(), depth(&asks).into());
+    }
+}
+
+/// Strategy that placed each order, plus any context it registered
+///
+/// Adds `maker_strategy`/`taker_strategy` and `maker_context`/`taker_context`
+/// for registered orders. Clones share the same registry.
+#[derive(Clone, Default)]
+pub struct StrategyContext {
+    orders: Arc<Mutex<HashMap<OrderId, (String, Value)>>>,
+}
+
+impl StrategyContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&self, order_id: OrderId, strategy: &str, context: Value) {
+        self.orders
+            .lock()
+            .unwrap()
+            .insert(order_id, (strategy.to_string(), context));
+    }
+
+    /// Stop tracking an order once it is done
+    pub fn forget(&self, order_id: OrderId) {
+        self.orders.lock().unwrap().remove(&order_id);
+    }
+}
+
+impl Enricher for StrategyContext {
+    fn enrich(&self, trade: &TapeTrade, metadata: &mut Metadata) {
+        let orders = self.orders.lock().unwrap();
+        for (role, order_id) in [
+            ("maker", trade.maker_order_id),
+            ("taker", trade.taker_order_id),
+        ] {
+            let Some((strategy, context)) = order_id.and_then(|id| orders.get(&id)) else {
+                continue;
+            };
+            metadata.insert(format!("{}_strategy", role), strategy.clone().into());
+            if !context.is_null() {
+                metadata.insert(format!("{}_context", role), context.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::market::SharedTradeTape;
+    use crate::orderbook::BookUpdate;
+    use crate::types::{Order, Price, Qty};
+    use serde_json::json;
+
+    #[test]
+    fn test_local_fill_is_enriched_before_recording() {
+        let books = BookManager::new();
+        books.mirror("BTCUSDT").apply_snapshot(&BookUpdate {
+            bids: vec![(Price::new(99.0), Qty::new(1.0))],
+            asks: vec![(Price::new(101.0), Qty::new(1.0))],
+        });
+        let context = StrategyContext::new();
+        let pipeline = EnrichmentPipeline::new()
+            .with(BenchmarkEnricher::new(books.clone()))
+            .with(BookStateEnricher::new(books.clone(), 5))
+            .with(context.clone())
+            .with(|trade: &TapeTrade, metadata: &mut Metadata| {
+                let notional = trade.price.value() * trade.quantity.value();
+                metadata.insert("notional".to_string(), notional.into());
+            });
+        let tape = SharedTradeTape::new(10).with_enrichment(pipeline);
+        let book = books.matching("BTCUSDT").with_trade_tape(tape.clone());
+
+        let maker = Order::new_limit("BTCUSDT", OrderSide::Sell, 100.5, 2.0);
+        context.register(maker.id, "maker-mm", json!({ "quote_level": 1 }));
+        book.add_order(maker);
+        book.add_order(Order::new_limit("BTCUSDT", OrderSide::Buy, 100.5, 1.0));
+
+        let metadata = &tape.recent("BTCUSDT", 1)[0].metadata;
+        assert_eq!(metadata["benchmark_mid"], json!(100.0));
+        assert_eq!(metadata["slippage_bps"], json!(50.0));
+        assert_eq!(metadata["book_best_ask"], json!(100.5));
+        assert_eq!(metadata["book_ask_depth"], json!(1.0));
+        assert_eq!(metadata["maker_strategy"], json!("maker-mm"));
+        assert_eq!(metadata["maker_context"], json!({ "quote_level": 1 }));
+        assert!(!metadata.contains_key("taker_strategy"));
+        assert_eq!(metadata["notional"], json!(100.5));
+    }
+}
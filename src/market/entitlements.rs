@@ -0,0 +1,129 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// What a single API key may see
+///
+/// Empty sets grant everything, so `{}` is an unrestricted key.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entitlement {
+    #[serde(default)]
+    pub symbols: BTreeSet<String>,
+    /// Venue names such as `binance` or `local`
+    #[serde(default)]
+    pub venues: BTreeSet<String>,
+}
+
+impl Entitlement {
+    pub fn allows_symbol(&self, symbol: &str) -> bool {
+        self.symbols.is_empty() || self.symbols.contains(&symbol.to_uppercase())
+    }
+
+    pub fn allows_venue(&self, venue: &str) -> bool {
+        self.venues.is_empty() || self.venues.contains(&venue.to_lowercase())
+    }
+
+    pub fn allows(&self, venue: &str, symbol: &str) -> bool {
+        self.allows_venue(venue) && self.allows_symbol(symbol)
+    }
+}
+
+/// Entitlements keyed by API key, typically loaded from a JSON file:
+///
+/// ```json
+/// { "keys": { "desk-a": { "symbols": ["BTCUSDT"], "venues": ["binance"] } } }
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Entitlements {
+    keys: HashMap<String, Entitlement>,
+}
+
+impl Entitlements {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_key(mut self, api_key: impl Into<String>, entitlement: Entitlement) -> Self {
+        self.keys.insert(api_key.into(), normalize(entitlement));
+        self
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let loaded: Self = serde_json::from_slice(&fs::read(path)?).map_err(io::Error::other)?;
+        Ok(Self {
+            keys: loaded
+                .keys
+                .into_iter()
+                .map(|(key, entitlement)| (key, normalize(entitlement)))
+                .collect(),
+        })
+    }
+
+    /// Entitlement of `api_key`, or None for unknown keys
+    pub fn get(&self, api_key: &str) -> Option<&Entitlement> {
+        self.keys.get(api_key)
+    }
+}
+
+/// Symbols are matched upper-case and venues lower-case
+fn normalize(entitlement: Entitlement) -> Entitlement {
+    Entitlement {
+        symbols: entitlement
+            .symbols
+            .iter()
+            .map(|s| s.to_uppercase())
+            .collect(),
+        venues: entitlement
+            .venues
+            .iter()
+            .map(|v| v.to_lowercase())
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scoped_and_unrestricted_keys() {
+        let scoped = Entitlement {
+            symbols: ["btcusdt".to_string()].into(),
+            venues: ["Binance".to_string()].into(),
+        };
+        let entitlements = Entitlements::new()
+            .with_key("desk-a", scoped)
+            .with_key("admin", Entitlement::default());
+
+        let desk = entitlements.get("desk-a").unwrap();
+        assert!(desk.allows("binance", "BTCUSDT"));
+        assert!(!desk.allows("binance", "ETHUSDT"));
+        assert!(!desk.allows("local", "BTCUSDT"));
+
+        assert!(entitlements
+            .get("admin")
+            .unwrap()
+            .allows("local", "SOLUSDT"));
+        assert!(entitlements.get("unknown").is_none());
+    }
+
+    #[test]
+    fn test_load_from_json() {
+        let path = std::env::temp_dir().join(format!("entitlements-{}.json", std::process::id()));
+        fs::write(
+            &path,
+            r#"{ "keys": { "desk-a": { "symbols": ["ethusdt"] } } }"#,
+        )
+        .unwrap();
+
+        let entitlements = Entitlements::load(&path).unwrap();
+        let desk = entitlements.get("desk-a").unwrap();
+        assert!(desk.allows("binance", "ETHUSDT"));
+        assert!(!desk.allows_symbol("BTCUSDT"));
+
+        fs::remove_file(&path).unwrap();
+    }
+}
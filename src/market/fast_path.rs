@@ -0,0 +1,497 @@
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::ptr;
+use std::sync::atomic::{AtomicBool, AtomicPtr, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, Thread};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::market::TapeTrade;
+use crate::types::Symbol;
+
+/// Market event delivered on the fast path
+#[derive(Debug, Clone)]
+pub enum FastEvent {
+    Trade(TapeTrade),
+    /// Top of book after a depth update
+    Quote {
+        symbol: Symbol,
+        bid: f64,
+        ask: f64,
+        timestamp: DateTime<Utc>,
+    },
+}
+
+impl FastEvent {
+    pub fn symbol(&self) -> &Symbol {
+        match self {
+            FastEvent::Trade(trade) => &trade.symbol,
+            FastEvent::Quote { symbol, .. } => symbol,
+        }
+    }
+}
+
+/// Delivery counts of one registration
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FastPathStats {
+    pub strategy: String,
+    pub symbols: Vec<Symbol>,
+    pub delivered: u64,
+    /// Oldest trades overwritten because the strategy had not drained its
+    /// buffer
+    pub dropped: u64,
+    /// Quotes replaced by a newer one of the same symbol before the strategy
+    /// read them
+    pub conflated: u64,
+}
+
+/// Holds at most one boxed value, handed between threads by atomic swaps
+struct Slot<T> {
+    value: AtomicPtr<T>,
+    _owns: PhantomData<Box<T>>,
+}
+
+impl<T> Slot<T> {
+    fn empty() -> Self {
+        Self {
+            value: AtomicPtr::new(ptr::null_mut()),
+            _owns: PhantomData,
+        }
+    }
+
+    /// Put `value` in, returning what was there unread
+    fn replace(&self, value: T) -> Option<Box<T>> {
+        let old = self
+            .value
+            .swap(Box::into_raw(Box::new(value)), Ordering::AcqRel);
+        // SAFETY: a slot only ever holds null or a pointer from Box::into_raw,
+        // and the swap took it out, so this is its only owner
+        (!old.is_null()).then(|| unsafe { Box::from_raw(old) })
+    }
+
+    fn take(&self) -> Option<Box<T>> {
+        let old = self.value.swap(ptr::null_mut(), Ordering::AcqRel);
+        // SAFETY: as in `replace`
+        (!old.is_null()).then(|| unsafe { Box::from_raw(old) })
+    }
+}
+
+impl<T> Drop for Slot<T> {
+    fn drop(&mut self) {
+        self.take();
+    }
+}
+
+/// Bounded ring that overwrites its oldest entry once full
+///
+/// Entries carry their sequence number, so the reader can tell an entry of
+/// a later lap from the one it expects and skip what was overwritten.
+struct Ring {
+    slots: Box<[Slot<(u64, FastEvent)>]>,
+    /// Sequence of the next event pushed
+    tail: AtomicU64,
+    /// Sequence of the next event to read
+    head: AtomicU64,
+}
+
+impl Ring {
+    fn new(capacity: usize) -> Self {
+        Self {
+            slots: (0..capacity.max(1)).map(|_| Slot::empty()).collect(),
+            tail: AtomicU64::new(0),
+            head: AtomicU64::new(0),
+        }
+    }
+
+    fn slot(&self, seq: u64) -> &Slot<(u64, FastEvent)> {
+        &self.slots[(seq % self.slots.len() as u64) as usize]
+    }
+
+    /// Append `event`; true if it overwrote one never read
+    fn push(&self, event: FastEvent) -> bool {
+        let seq = self.tail.fetch_add(1, Ordering::AcqRel);
+        self.slot(seq).replace((seq, event)).is_some()
+    }
+
+    /// Oldest event not overwritten yet, counting stale ones it discards
+    fn pop(&self, dropped: &AtomicU64) -> Option<FastEvent> {
+        loop {
+            let tail = self.tail.load(Ordering::Acquire);
+            let head = self.head.load(Ordering::Relaxed);
+            // Anything more than a ring behind has been overwritten
+            let head = head.max(tail.saturating_sub(self.slots.len() as u64));
+            if head >= tail {
+                return None;
+            }
+            // Empty while a push that claimed the sequence is still storing it
+            let (seq, event) = *self.slot(head).take()?;
+            if seq < head {
+                dropped.fetch_add(1, Ordering::Relaxed);
+                self.head.store(head, Ordering::Relaxed);
+                continue;
+            }
+            self.head.store(seq + 1, Ordering::Relaxed);
+            return Some(event);
+        }
+    }
+}
+
+/// Queue from the feeds to one registered strategy
+struct Channel {
+    strategy: String,
+    symbols: Vec<Symbol>,
+    trades: Ring,
+    /// Latest unread quote of each registered symbol
+    quotes: HashMap<Symbol, Slot<FastEvent>>,
+    delivered: AtomicU64,
+    dropped: AtomicU64,
+    conflated: AtomicU64,
+    /// Set while the strategy waits for an event
+    waiting: AtomicBool,
+    waiter: Mutex<Option<Thread>>,
+}
+
+impl Channel {
+    fn new(strategy: &str, symbols: &[Symbol], capacity: usize) -> Self {
+        Self {
+            strategy: strategy.to_string(),
+            symbols: symbols.to_vec(),
+            trades: Ring::new(capacity),
+            quotes: symbols
+                .iter()
+                .map(|symbol| (symbol.clone(), Slot::empty()))
+                .collect(),
+            delivered: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+            conflated: AtomicU64::new(0),
+            waiting: AtomicBool::new(false),
+            waiter: Mutex::new(None),
+        }
+    }
+
+    fn deliver(&self, event: FastEvent) {
+        match &event {
+            FastEvent::Quote { symbol, .. } => {
+                let Some(slot) = self.quotes.get(symbol) else {
+                    return;
+                };
+                if slot.replace(event).is_some() {
+                    self.conflated.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+            FastEvent::Trade(_) => {
+                if self.trades.push(event) {
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }
+        self.delivered.fetch_add(1, Ordering::Relaxed);
+        // Paired with the flag being set before the strategy looks again
+        if self.waiting.swap(false, Ordering::SeqCst) {
+            if let Some(waiter) = self.waiter.lock().unwrap().as_ref() {
+                waiter.unpark();
+            }
+        }
+    }
+
+    /// Latest unread quotes first, since they supersede anything older,
+    /// then trades oldest first
+    fn next(&self) -> Option<FastEvent> {
+        self.quotes
+            .values()
+            .find_map(Slot::take)
+            .map(|quote| *quote)
+            .or_else(|| self.trades.pop(&self.dropped))
+    }
+
+    fn stats(&self) -> FastPathStats {
+        FastPathStats {
+            strategy: self.strategy.clone(),
+            symbols: self.symbols.clone(),
+            delivered: self.delivered.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            conflated: self.conflated.load(Ordering::Relaxed),
+        }
+    }
+}
+
+type Routes = HashMap<Symbol, Vec<Arc<Channel>>>;
+
+#[derive(Default)]
+struct Channels {
+    registered: Vec<Arc<Channel>>,
+    /// Registered channels by symbol, rebuilt on every change
+    routes: Arc<Routes>,
+}
+
+#[derive(Default)]
+struct Registry {
+    channels: Mutex<Channels>,
+    /// Bumped on every change, so publishers know to fetch the new routes
+    generation: AtomicU64,
+}
+
+impl Registry {
+    fn update(&self, change: impl FnOnce(&mut Vec<Arc<Channel>>)) {
+        let mut channels = self.channels.lock().unwrap();
+        change(&mut channels.registered);
+        let mut routes = Routes::new();
+        for channel in &channels.registered {
+            for symbol in &channel.symbols {
+                routes
+                    .entry(symbol.clone())
+                    .or_default()
+                    .push(Arc::clone(channel));
+            }
+        }
+        channels.routes = Arc::new(routes);
+        self.generation.fetch_add(1, Ordering::Release);
+    }
+}
+
+/// Receiving end of a fast path registration; dropping it unregisters the
+/// strategy
+pub struct FastReceiver {
+    channel: Arc<Channel>,
+    registry: Arc<Registry>,
+}
+
+impl FastReceiver {
+    pub fn try_recv(&self) -> Option<FastEvent> {
+        self.channel.next()
+    }
+
+    /// Wait up to `timeout` for the next event
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<FastEvent> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(event) = self.try_recv() {
+                return Some(event);
+            }
+            *self.channel.waiter.lock().unwrap() = Some(thread::current());
+            self.channel.waiting.store(true, Ordering::SeqCst);
+            // Catches an event published before the flag went up
+            let event = self.try_recv();
+            let now = Instant::now();
+            if event.is_some() || now >= deadline {
+                self.channel.waiting.store(false, Ordering::SeqCst);
+                return event;
+            }
+            thread::park_timeout(deadline - now);
+        }
+    }
+
+    /// Events buffered so far, quotes first
+    pub fn try_iter(&self) -> impl Iterator<Item = FastEvent> + '_ {
+        std::iter::from_fn(|| self.try_recv())
+    }
+}
+
+impl Drop for FastReceiver {
+    fn drop(&mut self) {
+        self.registry
+            .update(|registered| registered.retain(|channel| !Arc::ptr_eq(channel, &self.channel)));
+    }
+}
+
+/// Publishing end of the fast path, one per market data feed
+///
+/// It keeps its own copy of the routes, so publishing takes no lock; the
+/// copy is only refreshed after a strategy registers or goes away.
+pub struct FastPublisher {
+    registry: Arc<Registry>,
+    generation: u64,
+    routes: Arc<Routes>,
+    /// Last published bid and ask of each symbol
+    quotes: HashMap<Symbol, (f64, f64)>,
+}
+
+impl FastPublisher {
+    fn routes(&mut self) -> &Routes {
+        if self.registry.generation.load(Ordering::Acquire) != self.generation {
+            let channels = self.registry.channels.lock().unwrap();
+            self.routes = Arc::clone(&channels.routes);
+            self.generation = self.registry.generation.load(Ordering::Acquire);
+        }
+        &self.routes
+    }
+
+    pub fn is_registered(&mut self, symbol: &str) -> bool {
+        self.routes().contains_key(symbol)
+    }
+
+    /// Send `event` to every strategy registered for its symbol
+    pub fn publish(&mut self, event: FastEvent) {
+        let Some((last, rest)) = self
+            .routes()
+            .get(event.symbol())
+            .and_then(|channels| channels.split_last())
+        else {
+            return;
+        };
+        for channel in rest {
+            channel.deliver(event.clone());
+        }
+        last.deliver(event);
+    }
+
+    pub fn publish_trade(&mut self, trade: &TapeTrade) {
+        // Cloning the trade is only worth it when someone is waiting for it
+        if self.is_registered(trade.symbol.as_str()) {
+            self.publish(FastEvent::Trade(trade.clone()));
+        }
+    }
+
+    /// Publish the top of book of `symbol` if it moved since the last call
+    pub fn publish_quote(&mut self, symbol: &str, bid: f64, ask: f64) {
+        if !self.is_registered(symbol) || self.quotes.get(symbol) == Some(&(bid, ask)) {
+            return;
+        }
+        let symbol = Symbol::new(symbol);
+        self.quotes.insert(symbol.clone(), (bid, ask));
+        self.publish(FastEvent::Quote {
+            symbol,
+            bid,
+            ask,
+            timestamp: Utc::now(),
+        });
+    }
+}
+
+/// Dedicated event queues for strategies trading specific symbols
+///
+/// A registered strategy gets its own queue, fed by the market data feeds
+/// as soon as an event is parsed, ahead of the tape, the indicators and load
+/// shedding. Each feed publishes through a [`FastPublisher`] of its own
+/// without taking any lock; the registry is only locked to register and
+/// unregister. Quotes go to a latest-quote slot per symbol, so an unread
+/// quote is replaced by the next, and are only published when the top of
+/// book moves. Trades go to a lock-free ring that overwrites its oldest
+/// trade once full. The feeds never wait on a strategy.
+#[derive(Default)]
+pub struct SharedFastPath {
+    registry: Arc<Registry>,
+}
+
+impl SharedFastPath {
+    /// Register `strategy` for events on `symbols`, buffering up to
+    /// `capacity` trades
+    pub fn register(&self, strategy: &str, symbols: &[Symbol], capacity: usize) -> FastReceiver {
+        let channel = Arc::new(Channel::new(strategy, symbols, capacity));
+        self.registry
+            .update(|registered| registered.push(Arc::clone(&channel)));
+        FastReceiver {
+            channel,
+            registry: Arc::clone(&self.registry),
+        }
+    }
+
+    /// Publishing end for one feed
+    pub fn publisher(&self) -> FastPublisher {
+        FastPublisher {
+            registry: Arc::clone(&self.registry),
+            // Fetches the routes on first use
+            generation: u64::MAX,
+            routes: Arc::default(),
+            quotes: HashMap::new(),
+        }
+    }
+
+    pub fn stats(&self) -> Vec<FastPathStats> {
+        let channels = self.registry.channels.lock().unwrap();
+        channels.registered.iter().map(|c| c.stats()).collect()
+    }
+}
+
+impl Clone for SharedFastPath {
+    fn clone(&self) -> Self {
+        Self {
+            registry: Arc::clone(&self.registry),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{OrderId, Trade};
+
+    #[test]
+    fn test_registered_strategies_get_their_symbols_without_blocking_the_feed() {
+        let fast_path = SharedFastPath::default();
+        let mut depth = fast_path.publisher();
+        let mut trades = fast_path.publisher();
+        let btc = Symbol::from("BTCUSDT");
+        let scalper = fast_path.register("scalper", std::slice::from_ref(&btc), 2);
+        let spread = fast_path.register("spread", &[btc, Symbol::from("ETHUSDT")], 8);
+
+        // Only moves of the top of book are published, and an unread quote
+        // gives way to the next
+        for bid in [100.0, 100.0, 101.0, 102.0] {
+            depth.publish_quote("BTCUSDT", bid, bid + 1.0);
+        }
+        depth.publish_quote("ETHUSDT", 10.0, 10.1);
+        depth.publish_quote("SOLUSDT", 1.0, 1.1);
+        let bids: Vec<_> = scalper
+            .try_iter()
+            .map(|event| match event {
+                FastEvent::Quote { bid, .. } => bid,
+                FastEvent::Trade(_) => unreachable!(),
+            })
+            .collect();
+        assert_eq!(bids, [102.0]);
+        assert_eq!(spread.try_iter().count(), 2);
+        let stats = fast_path.stats();
+        assert_eq!((stats[0].delivered, stats[0].conflated), (3, 2));
+
+        // A full ring overwrites its oldest trade
+        let trade = |price: f64| {
+            let trade = Trade::new(OrderId(1), OrderId(2), "BTCUSDT", price, 1.0);
+            TapeTrade::local(&trade)
+        };
+        for price in [102.0, 102.5, 103.0] {
+            trades.publish_trade(&trade(price));
+        }
+        let prices: Vec<_> = scalper
+            .try_iter()
+            .map(|event| match event {
+                FastEvent::Trade(trade) => trade.price.value(),
+                FastEvent::Quote { .. } => unreachable!(),
+            })
+            .collect();
+        assert_eq!(prices, [102.5, 103.0]);
+        assert_eq!(fast_path.stats()[0].dropped, 1);
+
+        // A strategy that went away is unregistered right away
+        drop(scalper);
+        assert_eq!(fast_path.stats().len(), 1);
+        spread.try_iter().for_each(drop);
+        let waiter = thread::spawn(move || spread.recv_timeout(Duration::from_secs(5)));
+        thread::sleep(Duration::from_millis(20));
+        depth.publish_quote("BTCUSDT", 103.0, 104.0);
+        assert!(waiter.join().unwrap().is_some());
+    }
+
+    #[test]
+    fn test_a_lapped_reader_keeps_the_newest_trades_in_order() {
+        let fast_path = SharedFastPath::default();
+        let mut feed = fast_path.publisher();
+        let receiver = fast_path.register("taker", &[Symbol::from("BTCUSDT")], 4);
+        for price in 1..=10 {
+            let trade = Trade::new(OrderId(1), OrderId(2), "BTCUSDT", price as f64, 1.0);
+            feed.publish_trade(&TapeTrade::local(&trade));
+        }
+        let prices: Vec<_> = receiver
+            .try_iter()
+            .map(|event| match event {
+                FastEvent::Trade(trade) => trade.price.value(),
+                FastEvent::Quote { .. } => unreachable!(),
+            })
+            .collect();
+        assert_eq!(prices, [7.0, 8.0, 9.0, 10.0]);
+        assert_eq!(fast_path.stats()[0].dropped, 6);
+    }
+}
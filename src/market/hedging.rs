@@ -0,0 +1,194 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::account::Account;
+use crate::market::Instrument;
+use crate::types::{Order, OrderSide, Symbol};
+
+/// Exposures smaller than this, in units of their currency, are left alone
+const EXPOSURE_EPSILON: f64 = 1e-9;
+
+/// Strategy tag of hedge orders
+pub const HEDGE_STRATEGY: &str = "fx_hedge";
+
+/// Net holding of one quote currency, in units of that currency
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CurrencyExposure {
+    pub currency: String,
+    /// Negative when short the currency
+    pub exposure: f64,
+    /// Exposure valued in the home currency, when a cross is priced
+    pub home_value: Option<f64>,
+}
+
+/// Trade offsetting a currency exposure through its cross with the home
+/// currency
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HedgeTrade {
+    pub currency: String,
+    pub symbol: Symbol,
+    pub side: OrderSide,
+    pub quantity: f64,
+}
+
+impl HedgeTrade {
+    /// Market order carrying out the hedge, tagged with [`HEDGE_STRATEGY`]
+    pub fn order(&self) -> Order {
+        Order::new_market(self.symbol.clone(), self.side, self.quantity)
+            .with_strategy(HEDGE_STRATEGY)
+    }
+}
+
+/// Currency exposure of an account and the trades neutralizing it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HedgePlan {
+    pub home: String,
+    pub exposures: Vec<CurrencyExposure>,
+    pub hedges: Vec<HedgeTrade>,
+    /// Currencies with exposure but no priced cross to hedge it with
+    pub unhedged: Vec<String>,
+}
+
+/// Exposure of `account` to every currency other than `home` that
+/// instruments are quoted in, with hedges through the crosses in
+/// `instruments`
+///
+/// A position is taken as the spot trade it stands for: long BTCUSDC holds
+/// the base and owes its cost in USDC, so it is short `quantity *
+/// entry_price` USDC however BTC moves. Positions in a currency's own cross
+/// hold it as their base, so a filled hedge shows up as offsetting exposure.
+/// The balance is collateral in `home` and carries no exposure.
+pub fn hedge_plan(
+    account: &Account,
+    home: &str,
+    instruments: &[Instrument],
+    mark: impl Fn(&Symbol) -> Option<f64>,
+) -> HedgePlan {
+    let instrument = |symbol: &Symbol| instruments.iter().find(|i| &i.symbol == symbol);
+    let is_currency = |asset: &str| asset != home && instruments.iter().any(|i| i.quote == asset);
+
+    let mut totals: BTreeMap<String, f64> = BTreeMap::new();
+    for position in account.open_positions() {
+        let Some(instrument) = instrument(&position.symbol) else {
+            continue;
+        };
+        if is_currency(&instrument.base) {
+            *totals.entry(instrument.base.clone()).or_default() += position.quantity;
+        }
+        if is_currency(&instrument.quote) {
+            *totals.entry(instrument.quote.clone()).or_default() -=
+                position.quantity * position.entry_price;
+        }
+    }
+
+    let mut plan = HedgePlan {
+        home: home.to_string(),
+        exposures: Vec::new(),
+        hedges: Vec::new(),
+        unhedged: Vec::new(),
+    };
+    for (currency, exposure) in totals {
+        if exposure.abs() <= EXPOSURE_EPSILON {
+            continue;
+        }
+        // Home per unit of the currency, and the hedge trading it
+        let cross = instruments.iter().find_map(|i| {
+            let price = mark(&i.symbol).filter(|p| *p > 0.0)?;
+            if i.base == currency && i.quote == home {
+                let side = if exposure < 0.0 {
+                    OrderSide::Buy
+                } else {
+                    OrderSide::Sell
+                };
+                Some((price, i, side, exposure.abs()))
+            } else if i.base == home && i.quote == currency {
+                let side = if exposure < 0.0 {
+                    OrderSide::Sell
+                } else {
+                    OrderSide::Buy
+                };
+                Some((1.0 / price, i, side, exposure.abs() / price))
+            } else {
+                None
+            }
+        });
+        plan.exposures.push(CurrencyExposure {
+            currency: currency.clone(),
+            exposure,
+            home_value: cross.map(|(rate, ..)| exposure * rate),
+        });
+        match cross {
+            Some((_, instrument, side, quantity)) => plan.hedges.push(HedgeTrade {
+                currency,
+                symbol: instrument.symbol.clone(),
+                side,
+                quantity: round_lot(quantity, instrument.lot_size),
+            }),
+            None => plan.unhedged.push(currency),
+        }
+    }
+    plan.hedges.retain(|hedge| hedge.quantity > 0.0);
+    plan
+}
+
+/// `quantity` to the nearest lot
+fn round_lot(quantity: f64, lot_size: f64) -> f64 {
+    if lot_size > 0.0 {
+        (quantity / lot_size).round() * lot_size
+    } else {
+        quantity
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testkit;
+
+    fn instrument(symbol: &str, base: &str, quote: &str) -> Instrument {
+        serde_json::from_value(serde_json::json!({
+            "symbol": symbol, "base": base, "quote": quote, "tick_size": 0.0001,
+            "lot_size": 0.01, "price_precision": 4, "quantity_precision": 2
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_hedges_offset_quote_currency_exposure() {
+        let instruments = vec![
+            instrument("BTCUSDT", "BTC", "USDT"),
+            instrument("BTCUSDC", "BTC", "USDC"),
+            instrument("ETHEUR", "ETH", "EUR"),
+            instrument("USDCUSDT", "USDC", "USDT"),
+            instrument("ETHDAI", "ETH", "DAI"),
+        ];
+        let account = testkit::account("alice")
+            .deposit(100_000.0)
+            .trade("BTCUSDT", OrderSide::Buy, 1.0, 30_000.0, 0.0)
+            .trade("BTCUSDC", OrderSide::Buy, 0.5, 30_000.0, 0.0)
+            .trade("ETHEUR", OrderSide::Sell, 2.0, 1_500.0, 0.0)
+            .trade("USDCUSDT", OrderSide::Buy, 5_000.0, 1.0, 0.0)
+            .build();
+        let marks = |symbol: &Symbol| match symbol.as_str() {
+            "USDCUSDT" => Some(0.999),
+            _ => None,
+        };
+        let plan = hedge_plan(&account, "USDT", &instruments, marks);
+
+        // Short 15,000 USDC on BTC, long 5,000 through the cross already
+        assert_eq!(plan.exposures.len(), 2);
+        assert_eq!(plan.exposures[0].currency, "EUR");
+        assert_eq!(plan.exposures[0].exposure, 3_000.0);
+        assert_eq!(plan.exposures[1].exposure, -10_000.0);
+        assert!((plan.exposures[1].home_value.unwrap() + 9_990.0).abs() < 1e-6);
+        assert_eq!(plan.hedges.len(), 1);
+        let hedge = &plan.hedges[0];
+        assert_eq!(hedge.symbol, Symbol::from("USDCUSDT"));
+        assert_eq!(hedge.side, OrderSide::Buy);
+        assert!((hedge.quantity - 10_000.0).abs() < 1e-6);
+        // EUR has no cross with USDT to hedge through
+        assert_eq!(plan.unhedged, ["EUR"]);
+        assert_eq!(hedge.order().strategy.as_deref(), Some(HEDGE_STRATEGY));
+    }
+}
@@ -0,0 +1,256 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::types::Symbol;
+
+/// Whether an instrument can be traded
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InstrumentStatus {
+    #[default]
+    Trading,
+    /// Temporarily suspended; market data may still flow
+    Halted,
+    Delisted,
+}
+
+/// One bracket of a symbol's margin schedule
+///
+/// Each tier's rates apply to the part of a position's notional between the
+/// previous tier's bound and its own, so larger positions pay more on the
+/// notional above each bound, as on perpetual-futures exchanges.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MarginTier {
+    /// Notional up to which this tier applies; None for the last tier
+    pub max_notional: Option<f64>,
+    pub initial_margin_rate: f64,
+    /// Collateral below which a position in this tier is liquidated
+    pub maintenance_margin_rate: f64,
+}
+
+/// How far from the reference price a symbol's orders may be priced
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PriceBand {
+    /// Widest distance from the reference price, in percent
+    pub percent: f64,
+    /// Factor the band is widened by during a volatility event
+    #[serde(default = "default_volatile_multiplier")]
+    pub volatile_multiplier: f64,
+    /// Realized volatility of the trade tape above which the band widens
+    /// on its own
+    #[serde(default)]
+    pub volatility_trigger: Option<f64>,
+    /// End of a volatility event declared by an operator
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub widened_until: Option<DateTime<Utc>>,
+}
+
+fn default_volatile_multiplier() -> f64 {
+    2.0
+}
+
+impl PriceBand {
+    /// Whether a volatility event is on at `now`, given the symbol's current
+    /// realized volatility
+    pub fn is_widened(&self, now: DateTime<Utc>, volatility: Option<f64>) -> bool {
+        self.widened_until.is_some_and(|until| now < until)
+            || self
+                .volatility_trigger
+                .zip(volatility)
+                .is_some_and(|(trigger, volatility)| volatility > trigger)
+    }
+
+    /// Lowest and highest acceptable price around `reference`
+    pub fn range(&self, reference: f64, widened: bool) -> (f64, f64) {
+        let percent = if widened {
+            self.percent * self.volatile_multiplier
+        } else {
+            self.percent
+        };
+        let distance = reference * percent / 100.0;
+        (reference - distance, reference + distance)
+    }
+}
+
+/// Static description of a tradable symbol
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Instrument {
+    pub symbol: Symbol,
+    pub base: String,
+    pub quote: String,
+    /// Smallest price increment
+    pub tick_size: f64,
+    /// Smallest quantity increment
+    pub lot_size: f64,
+    /// Decimal places prices are displayed with
+    pub price_precision: u32,
+    pub quantity_precision: u32,
+    #[serde(default)]
+    pub status: InstrumentStatus,
+    /// Margin schedule, smallest tier first; the flat risk limit rates apply
+    /// when empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub margin_tiers: Vec<MarginTier>,
+    /// Orders priced outside the band around the mark are rejected
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price_band: Option<PriceBand>,
+}
+
+/// Instruments by symbol, typically loaded from a JSON array:
+///
+/// ```json
+/// [{ "symbol": "BTCUSDT", "base": "BTC", "quote": "USDT", "tick_size": 0.01,
+///    "lot_size": 0.00001, "price_precision": 2, "quantity_precision": 5 }]
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct InstrumentRegistry {
+    instruments: BTreeMap<Symbol, Instrument>,
+}
+
+impl InstrumentRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_instrument(mut self, instrument: Instrument) -> Self {
+        self.insert(instrument);
+        self
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let instruments: Vec<Instrument> =
+            serde_json::from_slice(&fs::read(path)?).map_err(io::Error::other)?;
+        let mut registry = Self::new();
+        for instrument in instruments {
+            registry.insert(instrument);
+        }
+        Ok(registry)
+    }
+
+    /// Add or replace the instrument of its symbol
+    pub fn insert(&mut self, instrument: Instrument) {
+        self.instruments
+            .insert(instrument.symbol.clone(), instrument);
+    }
+
+    pub fn get(&self, symbol: &str) -> Option<&Instrument> {
+        self.instruments.get(symbol)
+    }
+
+    /// Every instrument, sorted by symbol
+    pub fn list(&self) -> Vec<Instrument> {
+        self.instruments.values().cloned().collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.instruments.is_empty()
+    }
+
+    /// Change the status of `symbol`; false when it is not registered
+    pub fn set_status(&mut self, symbol: &str, status: InstrumentStatus) -> bool {
+        match self.instruments.get_mut(symbol) {
+            Some(instrument) => {
+                instrument.status = status;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Widen the price band of `symbol` until `until`; false when it has none
+    pub fn widen_band(&mut self, symbol: &str, until: DateTime<Utc>) -> bool {
+        match self
+            .instruments
+            .get_mut(symbol)
+            .and_then(|instrument| instrument.price_band.as_mut())
+        {
+            Some(band) => {
+                band.widened_until = Some(until);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Thread-safe wrapper for InstrumentRegistry
+pub struct SharedInstruments {
+    inner: Arc<Mutex<InstrumentRegistry>>,
+}
+
+impl SharedInstruments {
+    pub fn new(registry: InstrumentRegistry) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(registry)),
+        }
+    }
+
+    pub fn insert(&self, instrument: Instrument) {
+        self.inner.lock().unwrap().insert(instrument)
+    }
+
+    pub fn get(&self, symbol: &str) -> Option<Instrument> {
+        self.inner.lock().unwrap().get(symbol).cloned()
+    }
+
+    pub fn list(&self) -> Vec<Instrument> {
+        self.inner.lock().unwrap().list()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.lock().unwrap().is_empty()
+    }
+
+    pub fn set_status(&self, symbol: &str, status: InstrumentStatus) -> bool {
+        self.inner.lock().unwrap().set_status(symbol, status)
+    }
+
+    pub fn widen_band(&self, symbol: &str, until: DateTime<Utc>) -> bool {
+        self.inner.lock().unwrap().widen_band(symbol, until)
+    }
+}
+
+impl Clone for SharedInstruments {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl Default for SharedInstruments {
+    fn default() -> Self {
+        Self::new(InstrumentRegistry::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_price_band_widens_during_volatility_events() {
+        let now = Utc::now();
+        let mut band = PriceBand {
+            percent: 5.0,
+            volatile_multiplier: 2.0,
+            volatility_trigger: Some(0.01),
+            widened_until: None,
+        };
+        assert!(!band.is_widened(now, Some(0.005)));
+        assert_eq!(band.range(100.0, false), (95.0, 105.0));
+
+        // Either a volatile tape or a declared event widens the band
+        assert!(band.is_widened(now, Some(0.02)));
+        band.widened_until = Some(now + chrono::Duration::minutes(5));
+        assert!(band.is_widened(now, None));
+        assert!(!band.is_widened(now + chrono::Duration::minutes(5), None));
+        assert_eq!(band.range(100.0, true), (90.0, 110.0));
+    }
+}
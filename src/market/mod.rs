@@ -0,0 +1,36 @@
+pub mod alerts;
+pub mod anomaly;
+pub mod carry;
+pub mod enrichment;
+pub mod entitlements;
+pub mod fast_path;
+pub mod hedging;
+pub mod instruments;
+pub mod rolling;
+pub mod tape;
+pub mod ticker;
+pub mod watchlists;
+
+pub use alerts::{
+    Alert, AlertCondition, AlertEngine, AlertEvent, AlertId, AlertRequest, AlertSeverity,
+    AlertSink, SharedAlertEngine,
+};
+pub use anomaly::{
+    AnomalyConfig, AnomalyDetector, AnomalyEvent, AnomalyMetric, SharedAnomalyDetector,
+};
+pub use carry::{
+    carry_cost, scan_basis, Basis, CarryCost, CarryDirection, CarryRates, PositionCarry,
+};
+pub use enrichment::{
+    BenchmarkEnricher, BookStateEnricher, Enricher, EnrichmentPipeline, Metadata, StrategyContext,
+};
+pub use entitlements::{Entitlement, Entitlements};
+pub use fast_path::{FastEvent, FastPathStats, FastPublisher, FastReceiver, SharedFastPath};
+pub use hedging::{hedge_plan, CurrencyExposure, HedgePlan, HedgeTrade, HEDGE_STRATEGY};
+pub use instruments::{
+    Instrument, InstrumentRegistry, InstrumentStatus, MarginTier, PriceBand, SharedInstruments,
+};
+pub use rolling::{RollingStats, SharedRollingStats, StatsCheck, StatsTolerance};
+pub use tape::{SharedTradeTape, TapeTrade, TradeSource, TradeTape};
+pub use ticker::{SharedTickers, TickerStats};
+pub use watchlists::{SharedWatchlists, Watchlist, Watchlists};
@@ -0,0 +1,326 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use chrono::{TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::market::ticker::TickerStats;
+use crate::types::Symbol;
+
+const DAY_MS: i64 = 24 * 60 * 60 * 1_000;
+
+/// How far local statistics may drift from the exchange ticker
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct StatsTolerance {
+    /// Allowed difference of last, high and low, in percent
+    pub price_percent: f64,
+    /// Allowed difference of base volume, in percent
+    pub volume_percent: f64,
+}
+
+impl Default for StatsTolerance {
+    fn default() -> Self {
+        Self {
+            price_percent: 0.5,
+            volume_percent: 5.0,
+        }
+    }
+}
+
+/// Local 24h statistics compared with the exchange's
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StatsCheck {
+    pub last_diff_percent: f64,
+    pub high_diff_percent: f64,
+    pub low_diff_percent: f64,
+    pub volume_diff_percent: f64,
+    /// Whether local trades cover the whole window yet
+    pub complete: bool,
+    /// Every difference within tolerance; None until the window is complete
+    pub consistent: Option<bool>,
+}
+
+impl StatsCheck {
+    pub fn compare(
+        local: &TickerStats,
+        exchange: &TickerStats,
+        complete: bool,
+        tolerance: &StatsTolerance,
+    ) -> Self {
+        let diff = |local: f64, exchange: f64| {
+            if exchange == 0.0 {
+                0.0
+            } else {
+                (local - exchange) / exchange * 100.0
+            }
+        };
+        let last = diff(local.last, exchange.last);
+        let high = diff(local.high, exchange.high);
+        let low = diff(local.low, exchange.low);
+        let volume = diff(local.volume, exchange.volume);
+        let consistent = [last, high, low]
+            .iter()
+            .all(|d| d.abs() <= tolerance.price_percent)
+            && volume.abs() <= tolerance.volume_percent;
+        Self {
+            last_diff_percent: last,
+            high_diff_percent: high,
+            low_diff_percent: low,
+            volume_diff_percent: volume,
+            complete,
+            consistent: complete.then_some(consistent),
+        }
+    }
+}
+
+/// Trades aggregated over one bucket
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    start_ms: i64,
+    open: f64,
+    high: f64,
+    low: f64,
+    close: f64,
+    volume: f64,
+    quote_volume: f64,
+    last_ms: i64,
+}
+
+#[derive(Debug, Default)]
+struct SymbolWindow {
+    buckets: VecDeque<Bucket>,
+    /// First trade seen, to tell when the window is fully covered
+    first_ms: Option<i64>,
+}
+
+/// 24h high, low, volume and change computed from ingested trades
+///
+/// Trades are folded into fixed buckets, so memory stays bounded however
+/// busy a symbol is; the window's open is the first trade of its oldest
+/// bucket.
+#[derive(Debug)]
+pub struct RollingStats {
+    bucket_ms: i64,
+    symbols: HashMap<Symbol, SymbolWindow>,
+}
+
+impl Default for RollingStats {
+    fn default() -> Self {
+        Self::new(60)
+    }
+}
+
+impl RollingStats {
+    pub fn new(bucket_secs: i64) -> Self {
+        Self {
+            bucket_ms: bucket_secs.max(1) * 1_000,
+            symbols: HashMap::new(),
+        }
+    }
+
+    pub fn on_trade(&mut self, symbol: &str, price: f64, quantity: f64, timestamp_ms: i64) {
+        let start_ms = timestamp_ms - timestamp_ms.rem_euclid(self.bucket_ms);
+        let window = self.symbols.entry(Symbol::new(symbol)).or_default();
+        window.first_ms.get_or_insert(timestamp_ms);
+        match window.buckets.back_mut() {
+            Some(bucket) if bucket.start_ms == start_ms => {
+                bucket.high = bucket.high.max(price);
+                bucket.low = bucket.low.min(price);
+                bucket.volume += quantity;
+                bucket.quote_volume += price * quantity;
+                if timestamp_ms >= bucket.last_ms {
+                    bucket.close = price;
+                    bucket.last_ms = timestamp_ms;
+                }
+            }
+            // Trades older than the current bucket are late; drop them
+            Some(bucket) if bucket.start_ms > start_ms => {}
+            _ => window.buckets.push_back(Bucket {
+                start_ms,
+                open: price,
+                high: price,
+                low: price,
+                close: price,
+                volume: quantity,
+                quote_volume: price * quantity,
+                last_ms: timestamp_ms,
+            }),
+        }
+        while window
+            .buckets
+            .front()
+            .is_some_and(|bucket| bucket.start_ms + self.bucket_ms <= timestamp_ms - DAY_MS)
+        {
+            window.buckets.pop_front();
+        }
+    }
+
+    /// Statistics of the 24h ending at `now_ms`, if any trade falls in it
+    pub fn stats(&self, symbol: &str, now_ms: i64) -> Option<TickerStats> {
+        let window = self.symbols.get(symbol)?;
+        let since = now_ms - DAY_MS;
+        let mut buckets = window
+            .buckets
+            .iter()
+            .filter(|bucket| bucket.start_ms + self.bucket_ms > since);
+        let first = *buckets.next()?;
+        let mut stats = first;
+        for bucket in buckets {
+            stats.high = stats.high.max(bucket.high);
+            stats.low = stats.low.min(bucket.low);
+            stats.volume += bucket.volume;
+            stats.quote_volume += bucket.quote_volume;
+            stats.close = bucket.close;
+            stats.last_ms = bucket.last_ms;
+        }
+        Some(TickerStats {
+            symbol: Symbol::new(symbol),
+            last: stats.close,
+            open: first.open,
+            high: stats.high,
+            low: stats.low,
+            volume: stats.volume,
+            quote_volume: stats.quote_volume,
+            change_percent: (stats.close - first.open) / first.open * 100.0,
+            updated_at: Utc
+                .timestamp_millis_opt(stats.last_ms)
+                .single()
+                .unwrap_or_else(Utc::now),
+        })
+    }
+
+    /// Whether trades have been seen for the whole 24h ending at `now_ms`
+    pub fn is_complete(&self, symbol: &str, now_ms: i64) -> bool {
+        self.symbols
+            .get(symbol)
+            .and_then(|window| window.first_ms)
+            .is_some_and(|first_ms| first_ms <= now_ms - DAY_MS)
+    }
+
+    /// Local statistics of the exchange ticker's symbol checked against it
+    pub fn check(
+        &self,
+        exchange: &TickerStats,
+        now_ms: i64,
+        tolerance: &StatsTolerance,
+    ) -> Option<StatsCheck> {
+        let symbol = exchange.symbol.as_str();
+        let local = self.stats(symbol, now_ms)?;
+        let complete = self.is_complete(symbol, now_ms);
+        Some(StatsCheck::compare(&local, exchange, complete, tolerance))
+    }
+
+    pub fn symbols(&self) -> Vec<Symbol> {
+        let mut symbols: Vec<Symbol> = self.symbols.keys().cloned().collect();
+        symbols.sort();
+        symbols
+    }
+}
+
+/// Thread-safe wrapper for RollingStats
+pub struct SharedRollingStats {
+    inner: Arc<Mutex<RollingStats>>,
+}
+
+impl SharedRollingStats {
+    pub fn new(stats: RollingStats) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(stats)),
+        }
+    }
+
+    pub fn on_trade(&self, symbol: &str, price: f64, quantity: f64, timestamp_ms: i64) {
+        self.inner
+            .lock()
+            .unwrap()
+            .on_trade(symbol, price, quantity, timestamp_ms)
+    }
+
+    pub fn stats(&self, symbol: &str, now_ms: i64) -> Option<TickerStats> {
+        self.inner.lock().unwrap().stats(symbol, now_ms)
+    }
+
+    pub fn is_complete(&self, symbol: &str, now_ms: i64) -> bool {
+        self.inner.lock().unwrap().is_complete(symbol, now_ms)
+    }
+
+    pub fn check(
+        &self,
+        exchange: &TickerStats,
+        now_ms: i64,
+        tolerance: &StatsTolerance,
+    ) -> Option<StatsCheck> {
+        self.inner
+            .lock()
+            .unwrap()
+            .check(exchange, now_ms, tolerance)
+    }
+
+    pub fn symbols(&self) -> Vec<Symbol> {
+        self.inner.lock().unwrap().symbols()
+    }
+}
+
+impl Clone for SharedRollingStats {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl Default for SharedRollingStats {
+    fn default() -> Self {
+        Self::new(RollingStats::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR_MS: i64 = 60 * 60 * 1_000;
+
+    #[test]
+    fn test_stats_roll_over_the_day() {
+        let mut rolling = RollingStats::new(60);
+        rolling.on_trade("BTCUSDT", 100.0, 1.0, 0);
+        rolling.on_trade("BTCUSDT", 120.0, 2.0, HOUR_MS);
+        rolling.on_trade("BTCUSDT", 90.0, 1.0, 2 * HOUR_MS);
+        rolling.on_trade("BTCUSDT", 110.0, 1.0, 2 * HOUR_MS + 1);
+
+        let stats = rolling.stats("BTCUSDT", 2 * HOUR_MS + 1).unwrap();
+        assert_eq!((stats.open, stats.high, stats.low), (100.0, 120.0, 90.0));
+        assert_eq!((stats.last, stats.volume), (110.0, 5.0));
+        assert_eq!(stats.quote_volume, 100.0 + 240.0 + 90.0 + 110.0);
+        assert!((stats.change_percent - 10.0).abs() < 1e-9);
+        assert!(!rolling.is_complete("BTCUSDT", 2 * HOUR_MS));
+
+        // A day later the first trade has left the window
+        let now = DAY_MS + HOUR_MS / 2;
+        let stats = rolling.stats("BTCUSDT", now).unwrap();
+        assert_eq!((stats.open, stats.high, stats.volume), (120.0, 120.0, 4.0));
+        assert!(rolling.is_complete("BTCUSDT", now));
+        assert!(rolling.stats("BTCUSDT", 3 * DAY_MS).is_none());
+    }
+
+    #[test]
+    fn test_check_against_exchange() {
+        let mut rolling = RollingStats::new(60);
+        rolling.on_trade("BTCUSDT", 100.0, 10.0, 0);
+        let local = rolling.stats("BTCUSDT", 0).unwrap();
+        let exchange = TickerStats {
+            volume: 11.0,
+            high: 100.2,
+            ..local.clone()
+        };
+        let tolerance = StatsTolerance::default();
+
+        let check = StatsCheck::compare(&local, &exchange, false, &tolerance);
+        assert_eq!(check.consistent, None);
+
+        let check = StatsCheck::compare(&local, &exchange, true, &tolerance);
+        assert!((check.volume_diff_percent + 100.0 / 11.0).abs() < 1e-9);
+        assert_eq!(check.consistent, Some(false));
+    }
+}
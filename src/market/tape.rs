@@ -0,0 +1,386 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::market::enrichment::{EnrichmentPipeline, Metadata};
+use crate::memory::MemoryUsage;
+use crate::types::{OrderId, OrderSide, Price, Qty, Symbol, Trade};
+use crate::utils::{BoundedHistory, Filter, Queryable, Retention, Timestamped, Value};
+
+/// Where a taped trade came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TradeSource {
+    /// Matched by our own order book
+    Local,
+    /// Reported by an exchange feed
+    Exchange,
+}
+
+impl TradeSource {
+    /// Venue name used for entitlement checks
+    pub fn venue(&self) -> &'static str {
+        match self {
+            TradeSource::Local => "local",
+            TradeSource::Exchange => "binance",
+        }
+    }
+}
+
+/// A trade as recorded on the tape
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TapeTrade {
+    pub symbol: Symbol,
+    pub price: Price,
+    pub quantity: Qty,
+    /// Side of the taker, when known
+    pub aggressor: Option<OrderSide>,
+    pub source: TradeSource,
+    pub timestamp: DateTime<Utc>,
+    /// Orders on each side, for local matches
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub maker_order_id: Option<OrderId>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub taker_order_id: Option<OrderId>,
+    /// Fields added by the tape's enrichment pipeline
+    #[serde(default, skip_serializing_if = "Metadata::is_empty")]
+    pub metadata: Metadata,
+}
+
+impl TapeTrade {
+    /// A trade matched by a local book
+    pub fn local(trade: &Trade) -> Self {
+        Self {
+            symbol: trade.symbol.clone(),
+            price: trade.price,
+            quantity: trade.quantity,
+            aggressor: trade.aggressor,
+            source: TradeSource::Local,
+            timestamp: trade.timestamp,
+            maker_order_id: Some(trade.maker_order_id),
+            taker_order_id: Some(trade.taker_order_id),
+            metadata: Metadata::new(),
+        }
+    }
+}
+
+impl Timestamped for TapeTrade {
+    fn timestamp_ms(&self) -> i64 {
+        self.timestamp.timestamp_millis()
+    }
+}
+
+impl Queryable for TapeTrade {
+    const FIELDS: &'static [&'static str] = &[
+        "symbol",
+        "price",
+        "quantity",
+        "side",
+        "source",
+        "time",
+        "maker_order_id",
+        "taker_order_id",
+    ];
+
+    fn field(&self, name: &str) -> Option<Value> {
+        let value = match name {
+            "symbol" => Value::Text(self.symbol.to_string()),
+            "price" => Value::Number(self.price.value()),
+            "quantity" => Value::Number(self.quantity.value()),
+            "side" => Value::Text(format!("{:?}", self.aggressor?)),
+            "source" => Value::Text(format!("{:?}", self.source)),
+            "time" => Value::Time(self.timestamp),
+            "maker_order_id" => Value::Number(self.maker_order_id?.0 as f64),
+            "taker_order_id" => Value::Number(self.taker_order_id?.0 as f64),
+            _ => return None,
+        };
+        Some(value)
+    }
+}
+
+/// Bounded per-symbol history of the most recent trades
+#[derive(Debug)]
+pub struct TradeTape {
+    retention: Retention,
+    tapes: HashMap<Symbol, BoundedHistory<TapeTrade>>,
+}
+
+impl TradeTape {
+    /// Keep the last `capacity` trades per symbol
+    pub fn new(capacity: usize) -> Self {
+        Self::with_retention(Retention::count(capacity))
+    }
+
+    /// Retention applies per symbol; spill files are named after the symbol
+    pub fn with_retention(retention: Retention) -> Self {
+        Self {
+            retention,
+            tapes: HashMap::new(),
+        }
+    }
+
+    pub fn record(&mut self, trade: TapeTrade) {
+        let retention = &self.retention;
+        self.tapes
+            .entry(trade.symbol.clone())
+            .or_insert_with(|| BoundedHistory::new(trade.symbol.as_str(), retention.clone()))
+            .push(trade);
+    }
+
+    /// Record a trade matched by a local book
+    pub fn record_local(&mut self, trade: &Trade) {
+        self.record(TapeTrade::local(trade));
+    }
+
+    /// Up to `limit` most recent trades for `symbol`, oldest first
+    pub fn recent(&self, symbol: &str, limit: usize) -> Vec<TapeTrade> {
+        self.tapes
+            .get(symbol)
+            .map(|tape| tape.latest(limit).cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Up to `limit` most recent trades for `symbol` that `filter` keeps,
+    /// spilled ones included, oldest first
+    pub fn query(&mut self, symbol: &str, filter: &Filter, limit: usize) -> Vec<TapeTrade> {
+        let Some(tape) = self.tapes.get_mut(symbol) else {
+            return Vec::new();
+        };
+        let mut trades = tape.scan(|trade| filter.matches(trade));
+        trades.drain(..trades.len().saturating_sub(limit));
+        trades
+    }
+
+    pub fn len(&self, symbol: &str) -> usize {
+        self.tapes.get(symbol).map_or(0, BoundedHistory::len)
+    }
+
+    pub fn symbols(&self) -> Vec<Symbol> {
+        self.tapes.keys().cloned().collect()
+    }
+
+    /// Standard deviation of trade-to-trade log returns over the tape
+    pub fn realized_volatility(&self, symbol: &str) -> Option<f64> {
+        let tape = self.tapes.get(symbol)?;
+        let returns: Vec<f64> = tape
+            .iter()
+            .zip(tape.iter().skip(1))
+            .filter(|(a, b)| a.price > 0.0 && b.price > 0.0)
+            .map(|(a, b)| (b.price.value() / a.price.value()).ln())
+            .collect();
+
+        if returns.len() < 2 {
+            return None;
+        }
+        let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+        let variance =
+            returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (returns.len() - 1) as f64;
+        Some(variance.sqrt())
+    }
+
+    /// Classify each trade as buyer- or seller-initiated
+    ///
+    /// Uses the recorded aggressor when known and falls back to the tick
+    /// rule (uptick = buy, downtick = sell, zero tick = previous class).
+    pub fn classify(&self, symbol: &str) -> Vec<(TapeTrade, Option<OrderSide>)> {
+        let Some(tape) = self.tapes.get(symbol) else {
+            return Vec::new();
+        };
+
+        let mut previous_price: Option<Price> = None;
+        let mut previous_class: Option<OrderSide> = None;
+
+        tape.iter()
+            .map(|trade| {
+                let tick = match previous_price {
+                    Some(prev) if trade.price > prev => Some(OrderSide::Buy),
+                    Some(prev) if trade.price < prev => Some(OrderSide::Sell),
+                    _ => previous_class,
+                };
+                let class = trade.aggressor.or(tick);
+                previous_price = Some(trade.price);
+                previous_class = class;
+                (trade.clone(), class)
+            })
+            .collect()
+    }
+
+    /// (buy-initiated volume, sell-initiated volume) over the tape
+    pub fn aggressor_volumes(&self, symbol: &str) -> (f64, f64) {
+        self.classify(symbol)
+            .iter()
+            .fold((0.0, 0.0), |(buy, sell), (trade, class)| match class {
+                Some(OrderSide::Buy) => (buy + trade.quantity.value(), sell),
+                Some(OrderSide::Sell) => (buy, sell + trade.quantity.value()),
+                None => (buy, sell),
+            })
+    }
+}
+
+impl MemoryUsage for TradeTape {
+    fn heap_bytes(&self) -> usize {
+        self.tapes.values().map(MemoryUsage::heap_bytes).sum()
+    }
+}
+
+/// Thread-safe wrapper for TradeTape
+pub struct SharedTradeTape {
+    inner: Arc<Mutex<TradeTape>>,
+    enrichment: Option<Arc<EnrichmentPipeline>>,
+}
+
+impl SharedTradeTape {
+    pub fn new(capacity: usize) -> Self {
+        Self::with_retention(Retention::count(capacity))
+    }
+
+    pub fn with_retention(retention: Retention) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(TradeTape::with_retention(retention))),
+            enrichment: None,
+        }
+    }
+
+    /// Run `pipeline` on every trade before it is recorded
+    pub fn with_enrichment(mut self, pipeline: EnrichmentPipeline) -> Self {
+        self.enrichment = Some(Arc::new(pipeline));
+        self
+    }
+
+    /// Enrichers run before the tape is locked, so they may read other books
+    pub fn record(&self, mut trade: TapeTrade) {
+        if let Some(pipeline) = &self.enrichment {
+            pipeline.enrich(&mut trade);
+        }
+        self.inner.lock().unwrap().record(trade)
+    }
+
+    pub fn record_local(&self, trade: &Trade) {
+        self.record(TapeTrade::local(trade))
+    }
+
+    pub fn recent(&self, symbol: &str, limit: usize) -> Vec<TapeTrade> {
+        self.inner.lock().unwrap().recent(symbol, limit)
+    }
+
+    pub fn query(&self, symbol: &str, filter: &Filter, limit: usize) -> Vec<TapeTrade> {
+        self.inner.lock().unwrap().query(symbol, filter, limit)
+    }
+
+    pub fn symbols(&self) -> Vec<Symbol> {
+        self.inner.lock().unwrap().symbols()
+    }
+
+    pub fn realized_volatility(&self, symbol: &str) -> Option<f64> {
+        self.inner.lock().unwrap().realized_volatility(symbol)
+    }
+
+    pub fn aggressor_volumes(&self, symbol: &str) -> (f64, f64) {
+        self.inner.lock().unwrap().aggressor_volumes(symbol)
+    }
+}
+
+impl MemoryUsage for SharedTradeTape {
+    fn heap_bytes(&self) -> usize {
+        self.inner.lock().unwrap().heap_bytes()
+    }
+}
+
+impl Clone for SharedTradeTape {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+            enrichment: self.enrichment.clone(),
+        }
+    }
+}
+
+impl Default for SharedTradeTape {
+    fn default() -> Self {
+        Self::new(1_000)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exchange_trade(price: f64, aggressor: Option<OrderSide>) -> TapeTrade {
+        TapeTrade {
+            symbol: Symbol::new("BTCUSDT"),
+            price: Price::new(price),
+            quantity: Qty::new(1.0),
+            aggressor,
+            source: TradeSource::Exchange,
+            timestamp: Utc::now(),
+            maker_order_id: None,
+            taker_order_id: None,
+            metadata: Metadata::new(),
+        }
+    }
+
+    #[test]
+    fn test_ring_buffer_keeps_last_n() {
+        let mut tape = TradeTape::new(3);
+        for price in [100.0, 101.0, 102.0, 103.0] {
+            tape.record(exchange_trade(price, None));
+        }
+
+        assert_eq!(tape.len("BTCUSDT"), 3);
+        let recent = tape.recent("BTCUSDT", 2);
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].price, 102.0);
+        assert_eq!(recent[1].price, 103.0);
+        assert!(tape.recent("ETHUSDT", 10).is_empty());
+    }
+
+    #[test]
+    fn test_tick_rule_classification() {
+        let mut tape = TradeTape::new(10);
+        tape.record(exchange_trade(100.0, None));
+        tape.record(exchange_trade(101.0, None));
+        tape.record(exchange_trade(101.0, None));
+        tape.record(exchange_trade(100.5, None));
+        tape.record(exchange_trade(100.5, Some(OrderSide::Buy)));
+
+        let classes: Vec<_> = tape
+            .classify("BTCUSDT")
+            .into_iter()
+            .map(|(_, c)| c)
+            .collect();
+        assert_eq!(
+            classes,
+            vec![
+                None,
+                Some(OrderSide::Buy),
+                Some(OrderSide::Buy),
+                Some(OrderSide::Sell),
+                Some(OrderSide::Buy),
+            ]
+        );
+        assert_eq!(tape.aggressor_volumes("BTCUSDT"), (3.0, 1.0));
+    }
+
+    #[test]
+    fn test_local_trades_and_volatility() {
+        let mut tape = TradeTape::new(10);
+        for price in [100.0, 101.0, 100.0, 102.0] {
+            let trade = Trade::new(
+                crate::types::OrderId(1),
+                crate::types::OrderId(2),
+                "BTCUSDT".to_string(),
+                price,
+                0.5,
+            )
+            .with_aggressor(OrderSide::Sell);
+            tape.record_local(&trade);
+        }
+
+        let recent = tape.recent("BTCUSDT", 1);
+        assert_eq!(recent[0].source, TradeSource::Local);
+        assert_eq!(recent[0].aggressor, Some(OrderSide::Sell));
+        assert!(tape.realized_volatility("BTCUSDT").unwrap() > 0.0);
+    }
+}
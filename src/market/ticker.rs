@@ -0,0 +1,61 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::types::Symbol;
+
+/// Rolling 24h statistics of one symbol as reported by the exchange
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TickerStats {
+    pub symbol: Symbol,
+    pub last: f64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    /// Traded base quantity
+    pub volume: f64,
+    /// Traded quote notional
+    pub quote_volume: f64,
+    pub change_percent: f64,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Latest exchange ticker per symbol
+pub struct SharedTickers {
+    inner: Arc<Mutex<HashMap<Symbol, TickerStats>>>,
+}
+
+impl SharedTickers {
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn update(&self, stats: TickerStats) {
+        self.inner
+            .lock()
+            .unwrap()
+            .insert(stats.symbol.clone(), stats);
+    }
+
+    pub fn get(&self, symbol: &str) -> Option<TickerStats> {
+        self.inner.lock().unwrap().get(symbol).cloned()
+    }
+}
+
+impl Clone for SharedTickers {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl Default for SharedTickers {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,177 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::Symbol;
+
+/// A named, ordered list of symbols
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Watchlist {
+    pub name: String,
+    pub symbols: Vec<Symbol>,
+}
+
+/// Every user's watchlists, by user and list name
+#[derive(Debug, Default)]
+pub struct Watchlists {
+    users: HashMap<String, BTreeMap<String, Vec<Symbol>>>,
+}
+
+impl Watchlists {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Lists of `user`, sorted by name
+    pub fn list(&self, user: &str) -> Vec<Watchlist> {
+        self.users
+            .get(user)
+            .map(|lists| {
+                lists
+                    .iter()
+                    .map(|(name, symbols)| watchlist(name, symbols))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn get(&self, user: &str, name: &str) -> Option<Watchlist> {
+        let symbols = self.users.get(user)?.get(name)?;
+        Some(watchlist(name, symbols))
+    }
+
+    /// Create or replace a list; repeated symbols are kept once, in order.
+    /// Returns true when the list is new.
+    pub fn put(&mut self, user: &str, name: &str, symbols: Vec<Symbol>) -> bool {
+        let mut unique: Vec<Symbol> = Vec::with_capacity(symbols.len());
+        for symbol in symbols {
+            if !unique.contains(&symbol) {
+                unique.push(symbol);
+            }
+        }
+        self.users
+            .entry(user.to_string())
+            .or_default()
+            .insert(name.to_string(), unique)
+            .is_none()
+    }
+
+    /// Append `symbol` to an existing list; None when there is no such list
+    pub fn add(&mut self, user: &str, name: &str, symbol: Symbol) -> Option<Watchlist> {
+        let symbols = self.users.get_mut(user)?.get_mut(name)?;
+        if !symbols.contains(&symbol) {
+            symbols.push(symbol);
+        }
+        Some(watchlist(name, symbols))
+    }
+
+    /// Remove `symbol` from a list; None when there is no such list
+    pub fn remove(&mut self, user: &str, name: &str, symbol: &str) -> Option<Watchlist> {
+        let symbols = self.users.get_mut(user)?.get_mut(name)?;
+        symbols.retain(|s| s != symbol);
+        Some(watchlist(name, symbols))
+    }
+
+    pub fn delete(&mut self, user: &str, name: &str) -> bool {
+        self.users
+            .get_mut(user)
+            .is_some_and(|lists| lists.remove(name).is_some())
+    }
+}
+
+fn watchlist(name: &str, symbols: &[Symbol]) -> Watchlist {
+    Watchlist {
+        name: name.to_string(),
+        symbols: symbols.to_vec(),
+    }
+}
+
+/// Thread-safe wrapper for Watchlists
+pub struct SharedWatchlists {
+    inner: Arc<Mutex<Watchlists>>,
+}
+
+impl SharedWatchlists {
+    pub fn new(watchlists: Watchlists) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(watchlists)),
+        }
+    }
+
+    pub fn list(&self, user: &str) -> Vec<Watchlist> {
+        self.inner.lock().unwrap().list(user)
+    }
+
+    pub fn get(&self, user: &str, name: &str) -> Option<Watchlist> {
+        self.inner.lock().unwrap().get(user, name)
+    }
+
+    pub fn put(&self, user: &str, name: &str, symbols: Vec<Symbol>) -> bool {
+        self.inner.lock().unwrap().put(user, name, symbols)
+    }
+
+    pub fn add(&self, user: &str, name: &str, symbol: Symbol) -> Option<Watchlist> {
+        self.inner.lock().unwrap().add(user, name, symbol)
+    }
+
+    pub fn remove(&self, user: &str, name: &str, symbol: &str) -> Option<Watchlist> {
+        self.inner.lock().unwrap().remove(user, name, symbol)
+    }
+
+    pub fn delete(&self, user: &str, name: &str) -> bool {
+        self.inner.lock().unwrap().delete(user, name)
+    }
+}
+
+impl Clone for SharedWatchlists {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl Default for SharedWatchlists {
+    fn default() -> Self {
+        Self::new(Watchlists::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbols(names: &[&str]) -> Vec<Symbol> {
+        names.iter().map(Symbol::new).collect()
+    }
+
+    #[test]
+    fn test_put_add_remove_delete() {
+        let mut lists = Watchlists::new();
+        assert!(lists.put(
+            "alice",
+            "majors",
+            symbols(&["btcusdt", "ETHUSDT", "BTCUSDT"])
+        ));
+        assert_eq!(
+            lists.get("alice", "majors").unwrap().symbols,
+            symbols(&["BTCUSDT", "ETHUSDT"])
+        );
+        assert!(!lists.put("alice", "majors", symbols(&["BTCUSDT"])));
+
+        let list = lists
+            .add("alice", "majors", Symbol::new("SOLUSDT"))
+            .unwrap();
+        assert_eq!(list.symbols, symbols(&["BTCUSDT", "SOLUSDT"]));
+        let list = lists.remove("alice", "majors", "BTCUSDT").unwrap();
+        assert_eq!(list.symbols, symbols(&["SOLUSDT"]));
+        assert!(lists.add("alice", "alts", Symbol::new("SOLUSDT")).is_none());
+
+        // Lists are private to their user
+        assert!(lists.list("bob").is_empty());
+        assert!(!lists.delete("bob", "majors"));
+        assert!(lists.delete("alice", "majors"));
+        assert!(lists.list("alice").is_empty());
+    }
+}
@@ -0,0 +1,126 @@
+// Per-subsystem memory accounting
+//
+// Sizes are estimated from what each structure holds, entry counts times
+// their in-memory size plus the heap buffers they own, rather than measured
+// by an instrumented allocator. That costs nothing until a report is asked
+// for and keeps the numbers comparable between reports, which is what
+// spotting a growing component needs.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// Something that can estimate the heap memory it holds
+pub trait MemoryUsage {
+    fn heap_bytes(&self) -> usize;
+}
+
+/// Estimated size of one subsystem
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SubsystemMemory {
+    pub name: String,
+    pub bytes: usize,
+    /// Change since the previous report; None on the first one
+    pub change_bytes: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MemoryReport {
+    /// Largest first
+    pub subsystems: Vec<SubsystemMemory>,
+    pub total_bytes: usize,
+    pub taken_at: DateTime<Utc>,
+}
+
+type Probe = Box<dyn Fn() -> usize + Send + Sync>;
+
+#[derive(Default)]
+struct Registry {
+    probes: Vec<(String, Probe)>,
+    last: BTreeMap<String, usize>,
+}
+
+/// Named memory probes, reported together
+#[derive(Clone, Default)]
+pub struct MemoryRegistry {
+    inner: Arc<Mutex<Registry>>,
+}
+
+impl MemoryRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Report `probe` under `name`; a later probe with the same name replaces it
+    pub fn register(&self, name: &str, probe: impl Fn() -> usize + Send + Sync + 'static) {
+        let mut registry = self.inner.lock().unwrap();
+        registry.probes.retain(|(existing, _)| existing != name);
+        registry.probes.push((name.to_string(), Box::new(probe)));
+    }
+
+    /// Sizes of every registered probe plus `measured`, with their growth
+    pub fn report(&self, measured: &[(&str, usize)]) -> MemoryReport {
+        let mut registry = self.inner.lock().unwrap();
+        let mut sizes: BTreeMap<String, usize> = registry
+            .probes
+            .iter()
+            .map(|(name, probe)| (name.clone(), probe()))
+            .collect();
+        sizes.extend(
+            measured
+                .iter()
+                .map(|(name, bytes)| (name.to_string(), *bytes)),
+        );
+
+        let mut subsystems: Vec<SubsystemMemory> = sizes
+            .iter()
+            .map(|(name, bytes)| SubsystemMemory {
+                name: name.clone(),
+                bytes: *bytes,
+                change_bytes: registry
+                    .last
+                    .get(name)
+                    .map(|last| *bytes as i64 - *last as i64),
+            })
+            .collect();
+        subsystems.sort_by_key(|subsystem| std::cmp::Reverse(subsystem.bytes));
+        let total_bytes = sizes.values().sum();
+        registry.last = sizes;
+
+        MemoryReport {
+            subsystems,
+            total_bytes,
+            taken_at: Utc::now(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_report_tracks_growth() {
+        let registry = MemoryRegistry::new();
+        let cache = Arc::new(AtomicUsize::new(100));
+        let probe = Arc::clone(&cache);
+        registry.register("cache", move || probe.load(Ordering::Relaxed));
+
+        let report = registry.report(&[("books", 500)]);
+        assert_eq!(report.total_bytes, 600);
+        assert_eq!(report.subsystems[0].name, "books");
+        assert_eq!(report.subsystems[1].change_bytes, None);
+
+        cache.store(400, Ordering::Relaxed);
+        let report = registry.report(&[("books", 500)]);
+        let cache = report
+            .subsystems
+            .iter()
+            .find(|s| s.name == "cache")
+            .unwrap();
+        assert_eq!(cache.change_bytes, Some(300));
+    }
+}
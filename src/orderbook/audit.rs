@@ -0,0 +1,330 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::orderbook::ExecutionReport;
+use crate::types::{Order, OrderId, OrderStatus, Symbol};
+use crate::utils::{BoundedHistory, Retention, Timestamped};
+
+/// Left-over quantity below which an order counts as filled
+const FILLED_EPSILON: f64 = 1e-12;
+
+/// Component an order transition came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Actor {
+    /// Order entry, cancels and quote refreshes requested through the API
+    Client,
+    /// The matching engine, for fills and unfilled immediate remainders
+    Matching,
+    /// An auction uncross
+    Auction,
+    /// Exchange trades going through resting prices
+    Market,
+    ExpirySweeper,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Transition {
+    New,
+    PartialFill,
+    Fill,
+    Amend,
+    Cancel,
+    Expire,
+    Reject,
+}
+
+/// What the audit trail knows of an order at one point
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderState {
+    pub status: OrderStatus,
+    /// Limit price, when the order was seen submitted or amended
+    pub price: Option<f64>,
+    pub filled_quantity: f64,
+    /// Unknown for orders filled before the trail saw them submitted
+    pub remaining_quantity: Option<f64>,
+}
+
+impl OrderState {
+    fn of(order: &Order) -> Self {
+        Self {
+            status: order.status,
+            price: Some(order.price.value()),
+            filled_quantity: (order.initial_quantity - order.remaining_quantity).value(),
+            remaining_quantity: Some(order.remaining_quantity.value()),
+        }
+    }
+}
+
+/// One state transition of an order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub order_id: OrderId,
+    pub symbol: Symbol,
+    pub transition: Transition,
+    pub actor: Actor,
+    /// Absent for the first transition the trail saw
+    pub before: Option<OrderState>,
+    pub after: OrderState,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl Timestamped for AuditEntry {
+    fn timestamp_ms(&self) -> i64 {
+        self.timestamp.timestamp_millis()
+    }
+}
+
+/// Append-only trail of every transition of local orders
+///
+/// Transitions are derived from the execution reports as they are
+/// published, with each order's last state kept until it is off the book
+/// so entries carry before and after values. With a spill dir in the
+/// retention, entries evicted from memory go to `<dir>/audit.jsonl` and
+/// lookups still find them, so nothing is ever lost.
+#[derive(Debug)]
+pub struct OrderAudit {
+    entries: BoundedHistory<AuditEntry>,
+    /// Last state of every order still working
+    live: HashMap<OrderId, OrderState>,
+}
+
+impl OrderAudit {
+    pub fn new(retention: Retention) -> Self {
+        Self {
+            entries: BoundedHistory::new("audit", retention),
+            live: HashMap::new(),
+        }
+    }
+
+    /// An order accepted for matching
+    pub fn submitted(&mut self, order: &Order, actor: Actor) {
+        let mut after = OrderState::of(order);
+        after.status = OrderStatus::Pending;
+        self.append(
+            order.id,
+            &order.symbol,
+            Transition::New,
+            actor,
+            after,
+            order.timestamp,
+        );
+    }
+
+    /// A working order changed in place, from `before` to `after`
+    pub fn amended(&mut self, before: &Order, after: &Order, actor: Actor) {
+        self.live.insert(before.id, OrderState::of(before));
+        self.append(
+            after.id,
+            &after.symbol,
+            Transition::Amend,
+            actor,
+            OrderState::of(after),
+            Utc::now(),
+        );
+    }
+
+    /// The transition `report` stands for, if it changes anything
+    ///
+    /// The summary report closing a submission repeats the state its fills
+    /// already left the order in and adds no entry.
+    pub fn record(&mut self, report: &ExecutionReport, actor: Actor) {
+        match report {
+            ExecutionReport::Fill {
+                order_id,
+                symbol,
+                quantity,
+                timestamp,
+                ..
+            } => {
+                let before = self.live.get(order_id);
+                let filled_quantity = before.map_or(0.0, |b| b.filled_quantity) + quantity;
+                let remaining_quantity = before
+                    .and_then(|b| b.remaining_quantity)
+                    .map(|r| (r - quantity).max(0.0));
+                let (transition, status) = match remaining_quantity {
+                    Some(r) if r <= FILLED_EPSILON => (Transition::Fill, OrderStatus::Filled),
+                    _ => (Transition::PartialFill, OrderStatus::PartiallyFilled),
+                };
+                let after = OrderState {
+                    status,
+                    price: before.and_then(|b| b.price),
+                    filled_quantity,
+                    remaining_quantity,
+                };
+                self.append(*order_id, symbol, transition, actor, after, *timestamp);
+            }
+            ExecutionReport::OrderUpdate {
+                order_id,
+                symbol,
+                status,
+                filled_quantity,
+                remaining_quantity,
+                timestamp,
+                ..
+            } => {
+                let before = self.live.get(order_id);
+                let after = OrderState {
+                    status: *status,
+                    price: before.and_then(|b| b.price),
+                    filled_quantity: *filled_quantity,
+                    remaining_quantity: Some(*remaining_quantity),
+                };
+                let transition = match status {
+                    OrderStatus::Pending => Transition::New,
+                    OrderStatus::PartiallyFilled => Transition::PartialFill,
+                    OrderStatus::Filled => Transition::Fill,
+                    OrderStatus::Cancelled => Transition::Cancel,
+                    OrderStatus::Expired => Transition::Expire,
+                    OrderStatus::Rejected => Transition::Reject,
+                };
+                // A submission filled in full is off the book by its summary
+                let last = before.or_else(|| {
+                    self.entries
+                        .iter()
+                        .next_back()
+                        .filter(|e| e.order_id == *order_id)
+                        .map(|e| &e.after)
+                });
+                let unchanged = last.is_some_and(|b| {
+                    b.status == after.status
+                        && (b.filled_quantity - after.filled_quantity).abs() <= FILLED_EPSILON
+                });
+                if !unchanged {
+                    self.append(*order_id, symbol, transition, actor, after, *timestamp);
+                }
+            }
+        }
+    }
+
+    /// Every transition of `order_id`, spilled ones included, oldest first
+    pub fn history(&mut self, order_id: OrderId) -> Vec<AuditEntry> {
+        self.entries.scan(|entry| entry.order_id == order_id)
+    }
+
+    fn append(
+        &mut self,
+        order_id: OrderId,
+        symbol: &Symbol,
+        transition: Transition,
+        actor: Actor,
+        after: OrderState,
+        timestamp: DateTime<Utc>,
+    ) {
+        let before = match after.status {
+            OrderStatus::Pending | OrderStatus::PartiallyFilled => {
+                self.live.insert(order_id, after.clone())
+            }
+            _ => self.live.remove(&order_id),
+        };
+        self.entries.push(AuditEntry {
+            order_id,
+            symbol: symbol.clone(),
+            transition,
+            actor,
+            before,
+            after,
+            timestamp,
+        });
+    }
+}
+
+impl Default for OrderAudit {
+    fn default() -> Self {
+        Self::new(Retention::count(100_000))
+    }
+}
+
+/// Thread-safe wrapper for OrderAudit
+#[derive(Default)]
+pub struct SharedOrderAudit {
+    inner: Arc<Mutex<OrderAudit>>,
+}
+
+impl SharedOrderAudit {
+    pub fn new(audit: OrderAudit) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(audit)),
+        }
+    }
+
+    pub fn submitted(&self, order: &Order, actor: Actor) {
+        self.inner.lock().unwrap().submitted(order, actor)
+    }
+
+    pub fn amended(&self, before: &Order, after: &Order, actor: Actor) {
+        self.inner.lock().unwrap().amended(before, after, actor)
+    }
+
+    pub fn record(&self, report: &ExecutionReport, actor: Actor) {
+        self.inner.lock().unwrap().record(report, actor)
+    }
+
+    pub fn history(&self, order_id: OrderId) -> Vec<AuditEntry> {
+        self.inner.lock().unwrap().history(order_id)
+    }
+}
+
+impl Clone for SharedOrderAudit {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orderbook::OrderBook;
+    use crate::testkit::{buy, sell};
+
+    #[test]
+    fn test_audit_trail_follows_an_order_through_fills_and_cancel() {
+        let mut book = OrderBook::new("BTCUSDT");
+        let mut audit = OrderAudit::default();
+        let maker = buy(100.0, 3.0).build();
+        audit.submitted(&maker, Actor::Client);
+        book.add_order(maker.clone());
+        audit.record(
+            &ExecutionReport::for_submission(&maker, &[])[0],
+            Actor::Matching,
+        );
+
+        let taker = sell(100.0, 1.0).build();
+        audit.submitted(&taker, Actor::Client);
+        let trades = book.add_order(taker.clone());
+        for report in ExecutionReport::for_submission(&taker, &trades) {
+            audit.record(&report, Actor::Matching);
+        }
+        let resting = book.cancel_order(maker.id).unwrap();
+        audit.record(&ExecutionReport::cancelled(&resting), Actor::Client);
+
+        let history = audit.history(maker.id);
+        let transitions: Vec<_> = history.iter().map(|e| (e.transition, e.actor)).collect();
+        assert_eq!(
+            transitions,
+            [
+                (Transition::New, Actor::Client),
+                (Transition::PartialFill, Actor::Matching),
+                (Transition::Cancel, Actor::Client),
+            ]
+        );
+        assert_eq!(
+            history[1].before.as_ref().unwrap().remaining_quantity,
+            Some(3.0)
+        );
+        assert_eq!(history[1].after.remaining_quantity, Some(2.0));
+        assert_eq!(history[2].after.price, Some(100.0));
+        assert_eq!(history[2].after.status, OrderStatus::Cancelled);
+
+        let taker = audit.history(taker.id);
+        assert_eq!(taker.len(), 2);
+        assert_eq!(taker[1].transition, Transition::Fill);
+        assert!(audit.live.is_empty());
+    }
+}
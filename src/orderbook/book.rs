@@ -1,28 +1,45 @@
 use std::collections::{BTreeMap, HashMap, VecDeque};
-use std::sync::{Arc, Mutex};
+use std::ops::RangeInclusive;
+use std::sync::{Arc, Mutex, MutexGuard};
 
-use crate::types::order::{Order, OrderId, OrderSide, OrderStatus, Trade};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::latency::{LatencySamples, LatencySummary};
+use crate::market::SharedTradeTape;
+use crate::memory::MemoryUsage;
+use crate::overload::SharedLoadShedder;
+use crate::throughput::SharedThroughputMeter;
+use crate::types::money::{Price, Qty, Symbol};
+use crate::types::order::{Order, OrderId, OrderSide, OrderStatus, TimeInForce, Trade};
+
+/// Bid and ask levels as (price, quantity) pairs, best price first
+pub type Depth = (Vec<(f64, f64)>, Vec<(f64, f64)>);
 
 /// Price level in the order book
 /// Contains all orders at a specific price
 #[derive(Debug, Clone)]
 pub struct PriceLevel {
-    pub price: f64,
+    pub price: Price,
     pub orders: VecDeque<Order>,
-    pub total_quantity: f64,
+    pub total_quantity: Qty,
+    /// Part of `total_quantity` iceberg orders keep off the displayed book
+    pub hidden_quantity: Qty,
 }
 
 impl PriceLevel {
-    pub fn new(price: f64) -> Self {
+    pub fn new(price: Price) -> Self {
         Self {
             price,
             orders: VecDeque::new(),
-            total_quantity: 0.0,
+            total_quantity: Qty::ZERO,
+            hidden_quantity: Qty::ZERO,
         }
     }
 
     pub fn add_order(&mut self, order: Order) {
         self.total_quantity += order.remaining_quantity;
+        self.hidden_quantity += order.hidden_quantity();
         self.orders.push_back(order);
     }
 
@@ -30,6 +47,7 @@ impl PriceLevel {
         if let Some(pos) = self.orders.iter().position(|o| o.id == order_id) {
             let order = self.orders.remove(pos)?;
             self.total_quantity -= order.remaining_quantity;
+            self.hidden_quantity -= order.hidden_quantity();
             Some(order)
         } else {
             None
@@ -39,76 +57,381 @@ impl PriceLevel {
     pub fn is_empty(&self) -> bool {
         self.orders.is_empty()
     }
+
+    /// Quantity shown on the book, without hidden iceberg quantity
+    pub fn visible_quantity(&self) -> Qty {
+        self.total_quantity - self.hidden_quantity
+    }
+
+    /// Fill the order at the front of the queue, hidden iceberg quantity
+    /// included, returning it once filled
+    fn fill_front(&mut self, quantity: Qty) -> Option<Order> {
+        let order = self.orders.front_mut()?;
+        self.hidden_quantity -= order.hidden_quantity();
+        order.fill(quantity);
+        order.refresh_tip();
+        self.hidden_quantity += order.hidden_quantity();
+        self.total_quantity -= quantity;
+        if order.is_filled() {
+            self.orders.pop_front()
+        } else {
+            None
+        }
+    }
+
+    /// Put an iceberg order whose slice just filled at the back of the queue
+    /// with its next slice showing, as a new slice has no time priority
+    fn requeue_front(&mut self) {
+        let mut order = self.orders.pop_front().unwrap();
+        self.hidden_quantity -= order.hidden_quantity();
+        order.refresh_tip();
+        self.hidden_quantity += order.hidden_quantity();
+        self.orders.push_back(order);
+    }
+}
+
+/// Absolute level quantities reported by an exchange, used to maintain a
+/// passive mirror book. A zero quantity removes the level.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BookUpdate {
+    pub bids: Vec<(Price, Qty)>,
+    pub asks: Vec<(Price, Qty)>,
+}
+
+/// Resting orders a mass cancel removes; fields left empty match every order
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct CancelFilter {
+    pub side: Option<OrderSide>,
+    pub strategy: Option<String>,
+    pub client_id: Option<String>,
+}
+
+impl CancelFilter {
+    /// Every order `strategy` placed
+    pub fn strategy(strategy: &str) -> Self {
+        Self {
+            strategy: Some(strategy.to_string()),
+            ..Self::default()
+        }
+    }
+
+    /// Every order placed for `client_id`
+    pub fn client(client_id: &str) -> Self {
+        Self {
+            client_id: Some(client_id.to_string()),
+            ..Self::default()
+        }
+    }
+
+    pub fn matches(&self, order: &Order) -> bool {
+        self.side.is_none_or(|side| side == order.side)
+            && self
+                .strategy
+                .as_ref()
+                .is_none_or(|strategy| order.strategy.as_ref() == Some(strategy))
+            && self
+                .client_id
+                .as_ref()
+                .is_none_or(|client_id| order.client_id.as_ref() == Some(client_id))
+    }
+}
+
+/// What a book does with a post-only order that would take liquidity
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum PostOnlyMode {
+    /// Reject the order outright
+    #[default]
+    Reject,
+    /// Move its price one tick behind the opposite touch so it rests
+    Reprice { tick_size: f64 },
+}
+
+/// Trading phase of a matching book
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BookPhase {
+    /// Orders match as they arrive
+    #[default]
+    Continuous,
+    /// Call phase of an auction: orders rest without matching, crossed or
+    /// not, until the book uncrosses
+    Auction,
+}
+
+/// Price an auction uncrosses at and how much trades there
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct AuctionQuote {
+    pub price: f64,
+    pub volume: f64,
+    /// Demand less supply at the price
+    pub imbalance: f64,
+}
+
+/// What an auction uncross did
+#[derive(Debug, Clone, Serialize)]
+pub struct AuctionResult {
+    pub symbol: Symbol,
+    /// None when nothing crossed
+    pub quote: Option<AuctionQuote>,
+    /// The uncross at one price, then trades of stops it triggered
+    pub trades: Vec<Trade>,
+    /// Good-till-crossing orders left unfilled
+    pub cancelled: Vec<Order>,
+}
+
+/// What a book holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BookKind {
+    /// Local orders matched by this engine
+    Matching,
+    /// Passive copy of an exchange book, maintained from L2 data only
+    Mirror,
 }
 
 /// High-performance order book
 /// Uses BTreeMap for price-sorted levels, inspired by Tzadiko's C++ implementation
 pub struct OrderBook {
-    pub symbol: String,
+    pub symbol: Symbol,
+    kind: BookKind,
 
     // Bids: highest price first (reverse order)
-    bids: BTreeMap<OrderedFloat, PriceLevel>,
+    bids: BTreeMap<Price, PriceLevel>,
 
     // Asks: lowest price first (natural order)
-    asks: BTreeMap<OrderedFloat, PriceLevel>,
+    asks: BTreeMap<Price, PriceLevel>,
 
     // Fast order lookup by ID
     orders: HashMap<OrderId, OrderSide>,
-}
 
-/// Wrapper for f64 to make it Ord for BTreeMap
-#[derive(Debug, Clone, Copy, PartialEq)]
-struct OrderedFloat(f64);
+    // Stop orders waiting off-book for their trigger, oldest first
+    stops: Vec<Order>,
 
-impl OrderedFloat {
-    fn new(value: f64) -> Self {
-        Self(value)
-    }
-}
+    // Price each resting pegged order sits at; entries for orders no longer
+    // on the book are dropped at the next reprice
+    pegs: HashMap<OrderId, Price>,
 
-impl Eq for OrderedFloat {}
+    last_trade_price: Option<Price>,
 
-impl PartialOrd for OrderedFloat {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        self.0.partial_cmp(&other.0)
-    }
-}
+    post_only_mode: PostOnlyMode,
 
-impl Ord for OrderedFloat {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.partial_cmp(other).unwrap_or(std::cmp::Ordering::Equal)
-    }
+    phase: BookPhase,
+
+    // Sequence number of the last trade
+    trade_sequence: u64,
 }
 
 impl OrderBook {
-    pub fn new(symbol: String) -> Self {
+    pub fn new(symbol: impl Into<Symbol>) -> Self {
+        Self::with_kind(symbol, BookKind::Matching)
+    }
+
+    /// Empty mirror book, fed by `apply_snapshot` and `apply_diff`
+    pub fn mirror(symbol: impl Into<Symbol>) -> Self {
+        Self::with_kind(symbol, BookKind::Mirror)
+    }
+
+    pub fn with_kind(symbol: impl Into<Symbol>, kind: BookKind) -> Self {
         Self {
-            symbol,
+            symbol: symbol.into(),
+            kind,
             bids: BTreeMap::new(),
             asks: BTreeMap::new(),
             orders: HashMap::new(),
+            stops: Vec::new(),
+            pegs: HashMap::new(),
+            last_trade_price: None,
+            post_only_mode: PostOnlyMode::default(),
+            phase: BookPhase::default(),
+            trade_sequence: 0,
         }
     }
 
+    pub fn kind(&self) -> BookKind {
+        self.kind
+    }
+
+    pub fn post_only_mode(&self) -> PostOnlyMode {
+        self.post_only_mode
+    }
+
+    pub fn set_post_only_mode(&mut self, mode: PostOnlyMode) {
+        self.post_only_mode = mode;
+    }
+
+    pub fn phase(&self) -> BookPhase {
+        self.phase
+    }
+
+    /// Open an auction's call phase, collecting orders until [`uncross`](Self::uncross)
+    pub fn start_auction(&mut self) {
+        self.phase = BookPhase::Auction;
+    }
+
+    /// Where the book would uncross now: the price matching the most
+    /// volume, then leaving the smallest imbalance, then nearest the last
+    /// trade
+    pub fn indicative_auction(&self) -> Option<AuctionQuote> {
+        let mut prices: Vec<f64> = self
+            .bids
+            .keys()
+            .chain(self.asks.keys())
+            .map(|k| k.value())
+            .collect();
+        prices.sort_by(f64::total_cmp);
+        prices.dedup();
+        let reference = self.last_trade_price.map(|p| p.value());
+        prices
+            .into_iter()
+            .filter_map(|price| {
+                let key = Price::new(price);
+                let total = |levels: &mut dyn Iterator<Item = &PriceLevel>| -> f64 {
+                    levels.map(|level| level.total_quantity.value()).sum()
+                };
+                let demand = total(&mut self.bids.range(key..).map(|(_, level)| level));
+                let supply = total(&mut self.asks.range(..=key).map(|(_, level)| level));
+                let volume = demand.min(supply);
+                (volume > 0.0).then_some(AuctionQuote {
+                    price,
+                    volume,
+                    imbalance: demand - supply,
+                })
+            })
+            .max_by(|a, b| {
+                let distance =
+                    |quote: &AuctionQuote| reference.map_or(0.0, |r| (quote.price - r).abs());
+                a.volume
+                    .total_cmp(&b.volume)
+                    .then(b.imbalance.abs().total_cmp(&a.imbalance.abs()))
+                    .then(distance(b).total_cmp(&distance(a)))
+            })
+    }
+
+    /// End the call phase: trade everything that crosses at the single
+    /// [`indicative_auction`](Self::indicative_auction) price, cancel what
+    /// good-till-crossing orders left, and return to continuous matching
+    ///
+    /// Orders fill in price then time priority; of each matched pair the
+    /// one that arrived first is the maker.
+    pub fn uncross(&mut self) -> AuctionResult {
+        self.phase = BookPhase::Continuous;
+        let quote = self.indicative_auction();
+        let mut trades = Vec::new();
+        if let Some(quote) = quote {
+            let mut left = Qty::new(quote.volume);
+            while left > 1e-9 {
+                let (Some(mut bids), Some(mut asks)) =
+                    (self.bids.last_entry(), self.asks.first_entry())
+                else {
+                    break;
+                };
+                let (bid, ask) = (&bids.get().orders[0], &asks.get().orders[0]);
+                let quantity = left.min(bid.remaining_quantity).min(ask.remaining_quantity);
+                let (maker, taker) = if ask.timestamp <= bid.timestamp {
+                    (ask.id, bid.id)
+                } else {
+                    (bid.id, ask.id)
+                };
+                trades.push(Trade::new(
+                    maker,
+                    taker,
+                    self.symbol.clone(),
+                    quote.price,
+                    quantity,
+                ));
+                left -= quantity;
+                for level in [&mut bids, &mut asks] {
+                    if let Some(filled) = level.get_mut().fill_front(quantity) {
+                        self.orders.remove(&filled.id);
+                    }
+                }
+                if bids.get().is_empty() {
+                    bids.remove();
+                }
+                if asks.get().is_empty() {
+                    asks.remove();
+                }
+            }
+        }
+        let cancelled = self.remove_where(None, |o| o.time_in_force == TimeInForce::Gtx);
+        self.settle(&mut trades);
+        AuctionResult {
+            symbol: self.symbol.clone(),
+            quote,
+            trades,
+            cancelled,
+        }
+    }
+
+    /// Whether `order` would trade on arrival against the opposite touch
+    pub fn would_cross(&self, order: &Order) -> bool {
+        self.opposite_touch(order.side)
+            .is_some_and(|touch| order.can_match(Price::new(touch)))
+    }
+
     /// Add an order to the book and attempt to match it
     /// Returns list of trades generated
+    ///
+    /// Mirror books refuse local orders so they never fill against exchange liquidity.
+    /// Orders priced or sized NaN, infinite or negative are refused everywhere.
+    /// Stop orders rest off-book until the last trade price reaches their stop
+    /// price, or activate at once if it already has; stops triggered by the
+    /// resulting trades activate in turn, oldest first. Pegged orders then
+    /// move to their new prices and trade if that crosses them.
     pub fn add_order(&mut self, order: Order) -> Vec<Trade> {
-        let mut trades = Vec::new();
-        let mut order = order;
+        if self.kind == BookKind::Mirror {
+            tracing::warn!("Rejected order #{} on {} mirror book", order.id.0, self.symbol);
+            return Vec::new();
+        }
+        let (price, quantity) = (order.price.value(), order.remaining_quantity.value());
+        if !(price.is_finite() && price >= 0.0 && quantity.is_finite() && quantity > 0.0) {
+            tracing::warn!(
+                "Rejected order #{} priced {} for {} on {}",
+                order.id.0,
+                price,
+                quantity,
+                self.symbol
+            );
+            return Vec::new();
+        }
 
-        // Try to match the order first
-        trades.extend(self.match_order(&mut order));
+        let mut order = order;
+        if order.is_stop() {
+            match self.last_trade_price {
+                Some(last) if order.is_triggered_by(last) => order.activate(),
+                _ => {
+                    self.stops.push(order);
+                    return Vec::new();
+                }
+            }
+        }
 
-        // If order has remaining quantity, add to book
-        if !order.is_filled() {
-            self.add_order_to_book(order);
+        if let Some(peg) = order.peg {
+            let (bid, ask) = self.peg_references();
+            let Some(price) = peg.price(bid, ask) else {
+                tracing::debug!(
+                    "Rejected pegged order #{} with nothing to track",
+                    order.id.0
+                );
+                return Vec::new();
+            };
+            order.price = Price::new(price);
         }
 
+        let mut trades = self.execute(order);
+        self.settle(&mut trades);
         trades
     }
 
     /// Cancel an order from the book
     pub fn cancel_order(&mut self, order_id: OrderId) -> Option<Order> {
+        if let Some(index) = self.stops.iter().position(|o| o.id == order_id) {
+            let mut order = self.stops.remove(index);
+            order.status = OrderStatus::Cancelled;
+            return Some(order);
+        }
+
         // Find which side the order is on
         let side = self.orders.remove(&order_id)?;
 
@@ -125,13 +448,14 @@ impl OrderBook {
 
                 // Clean up empty levels
                 if level.is_empty() {
-                    let price_key = OrderedFloat::new(level.price);
+                    let price_key = level.price;
                     match side {
                         OrderSide::Buy => self.bids.remove(&price_key),
                         OrderSide::Sell => self.asks.remove(&price_key),
                     };
                 }
 
+                self.reprice_pegs(true);
                 return Some(order);
             }
         }
@@ -139,14 +463,51 @@ impl OrderBook {
         None
     }
 
+    /// Cancel every resting and stop order matching `filter` in one pass
+    ///
+    /// Cancelled orders come back in book order, bids first.
+    pub fn mass_cancel(&mut self, filter: &CancelFilter) -> Vec<Order> {
+        let cancelled = self.remove_where(filter.side, |o| filter.matches(o));
+        self.reprice_pegs(true);
+        cancelled
+    }
+
+    /// Cancel every good-till-date order that expired by `now`
+    pub fn expire_orders(&mut self, now: DateTime<Utc>) -> Vec<Order> {
+        let mut expired = self.remove_where(None, |o| o.is_expired(now));
+        for order in &mut expired {
+            order.status = OrderStatus::Expired;
+        }
+        self.reprice_pegs(true);
+        expired
+    }
+
+    /// Replace every order `strategy` has in the book with `quotes`
+    ///
+    /// The old quote set is pulled before any new quote is placed, so the
+    /// strategy never trades against its own stale quotes. Returns the
+    /// cancelled orders and the trades the new quotes made.
+    pub fn replace_quotes(
+        &mut self,
+        strategy: &str,
+        quotes: Vec<Order>,
+    ) -> (Vec<Order>, Vec<Trade>) {
+        let cancelled = self.mass_cancel(&CancelFilter::strategy(strategy));
+        let mut trades = Vec::new();
+        for quote in quotes {
+            trades.extend(self.add_order(quote.with_strategy(strategy)));
+        }
+        (cancelled, trades)
+    }
+
     /// Get best bid price
     pub fn best_bid(&self) -> Option<f64> {
-        self.bids.keys().next_back().map(|k| k.0)
+        self.bids.keys().next_back().map(|k| k.value())
     }
 
     /// Get best ask price
     pub fn best_ask(&self) -> Option<f64> {
-        self.asks.keys().next().map(|k| k.0)
+        self.asks.keys().next().map(|k| k.value())
     }
 
     /// Get bid-ask spread
@@ -165,21 +526,48 @@ impl OrderBook {
         }
     }
 
+    /// Average of the volume-weighted bid and ask prices over the top N levels
+    ///
+    /// Less sensitive than the simple mid to a single thin level at the touch.
+    pub fn weighted_mid_price(&self, depth_levels: usize) -> Option<f64> {
+        let bid = volume_weighted_price(self.bids.values().rev().take(depth_levels))?;
+        let ask = volume_weighted_price(self.asks.values().take(depth_levels))?;
+        Some((bid + ask) / 2.0)
+    }
+
+    /// Top-of-book micro-price: the touch prices weighted by the opposite
+    /// side's size, so the price leans towards the side more likely to trade
+    pub fn micro_price(&self) -> Option<f64> {
+        let bid = self.bids.values().next_back()?;
+        let ask = self.asks.values().next()?;
+        let (bid_qty, ask_qty) = (
+            bid.visible_quantity().value(),
+            ask.visible_quantity().value(),
+        );
+        let total = bid_qty + ask_qty;
+        if total <= 0.0 {
+            return self.mid_price();
+        }
+        Some((bid.price.value() * ask_qty + ask.price.value() * bid_qty) / total)
+    }
+
     /// Get market depth (top N levels)
-    pub fn get_depth(&self, levels: usize) -> (Vec<(f64, f64)>, Vec<(f64, f64)>) {
+    ///
+    /// Levels show only the displayed slice of iceberg orders.
+    pub fn get_depth(&self, levels: usize) -> Depth {
         let bid_levels: Vec<(f64, f64)> = self
             .bids
             .iter()
             .rev()
             .take(levels)
-            .map(|(_, level)| (level.price, level.total_quantity))
+            .map(|(_, level)| (level.price.value(), level.visible_quantity().value()))
             .collect();
 
         let ask_levels: Vec<(f64, f64)> = self
             .asks
             .iter()
             .take(levels)
-            .map(|(_, level)| (level.price, level.total_quantity))
+            .map(|(_, level)| (level.price.value(), level.visible_quantity().value()))
             .collect();
 
         (bid_levels, ask_levels)
@@ -190,14 +578,492 @@ impl OrderBook {
         self.orders.len()
     }
 
+    /// Stop orders not yet triggered, oldest first
+    pub fn stop_orders(&self) -> &[Order] {
+        &self.stops
+    }
+
+    /// Price of the last trade matched in this book
+    pub fn last_trade_price(&self) -> Option<f64> {
+        self.last_trade_price.map(|p| p.value())
+    }
+
+    /// Displayed quantity at exactly `price` on either side
+    pub fn volume_at(&self, price: f64) -> f64 {
+        let key = Price::new(price);
+        self.bids
+            .get(&key)
+            .or_else(|| self.asks.get(&key))
+            .map_or(0.0, |level| level.visible_quantity().value())
+    }
+
+    /// Total displayed quantity, both sides, with prices inside `price_range`
+    pub fn volume_within(&self, price_range: RangeInclusive<f64>) -> f64 {
+        self.levels_in(price_range)
+            .map(|level| level.visible_quantity().value())
+            .sum()
+    }
+
+    /// Displayed quantity an aggressive order would consume walking from the
+    /// touch to `price`
+    ///
+    /// Walks the asks for prices at or above the best ask, the bids for prices
+    /// at or below the best bid, and returns 0.0 for prices inside the spread.
+    pub fn cumulative_depth_to(&self, price: f64) -> f64 {
+        let key = Price::new(price);
+        match (self.best_bid(), self.best_ask()) {
+            (_, Some(ask)) if price >= ask => self
+                .asks
+                .range(..=key)
+                .map(|(_, level)| level.visible_quantity().value())
+                .sum(),
+            (Some(bid), _) if price <= bid => self
+                .bids
+                .range(key..)
+                .map(|(_, level)| level.visible_quantity().value())
+                .sum(),
+            _ => 0.0,
+        }
+    }
+
+    /// (price, quantity) of every level between two prices, ascending by price
+    pub fn levels_between(&self, p1: f64, p2: f64) -> Vec<(f64, f64)> {
+        self.levels_in(p1.min(p2)..=p1.max(p2))
+            .map(|level| (level.price.value(), level.visible_quantity().value()))
+            .collect()
+    }
+
+    /// Replace the whole book with an exchange L2 snapshot
+    ///
+    /// Mirror levels carry only an aggregate quantity and no local orders, so
+    /// a mirrored book is for pricing and analytics, not for matching.
+    /// Ignored on matching books.
+    pub fn apply_snapshot(&mut self, levels: &BookUpdate) {
+        if self.kind != BookKind::Mirror {
+            tracing::warn!("Ignored L2 snapshot on {} matching book", self.symbol);
+            return;
+        }
+        self.bids.clear();
+        self.asks.clear();
+        self.apply_diff(levels);
+    }
+
+    /// Apply an exchange L2 diff on top of the last snapshot; ignored on matching books
+    pub fn apply_diff(&mut self, update: &BookUpdate) {
+        if self.kind != BookKind::Mirror {
+            tracing::warn!("Ignored L2 diff on {} matching book", self.symbol);
+            return;
+        }
+        Self::apply_levels(&mut self.bids, &update.bids);
+        Self::apply_levels(&mut self.asks, &update.asks);
+    }
+
+    /// Fill `order` against mirrored exchange liquidity, consuming it locally
+    ///
+    /// Nothing is sent to the exchange; consumed quantity comes back with the
+    /// next exchange update for the level. Only mirror books can be crossed.
+    pub fn cross(&mut self, order: &mut Order) -> Vec<Trade> {
+        let mut trades = Vec::new();
+        if self.kind != BookKind::Mirror {
+            return trades;
+        }
+
+        let levels: Box<dyn Iterator<Item = &mut PriceLevel>> = match order.side {
+            OrderSide::Buy => Box::new(self.asks.values_mut()),
+            OrderSide::Sell => Box::new(self.bids.values_mut().rev()),
+        };
+        for level in levels {
+            if order.is_filled() || !order.can_match(level.price) {
+                break;
+            }
+            let quantity = order.remaining_quantity.min(level.total_quantity);
+            level.total_quantity -= quantity;
+            order.fill(quantity);
+            trades.push(
+                Trade::new(
+                    OrderId::EXCHANGE,
+                    order.id,
+                    self.symbol.clone(),
+                    level.price,
+                    quantity,
+                )
+                .with_aggressor(order.side),
+            );
+        }
+
+        let side = match order.side {
+            OrderSide::Buy => &mut self.asks,
+            OrderSide::Sell => &mut self.bids,
+        };
+        side.retain(|_, level| level.total_quantity > 0.0);
+        self.number_trades(&mut trades);
+        trades
+    }
+
+    /// Fill resting orders the market has traded through
+    ///
+    /// An exchange trade at `price` means the exchange had nothing left at
+    /// better prices, so local bids above it and asks below it fill in full
+    /// at their own prices against the exchange, best price and oldest
+    /// first. Orders at exactly `price` keep waiting, as their place in the
+    /// exchange's queue is unknown. Stops and pegs then react to the fills.
+    pub fn fill_through(&mut self, price: f64) -> Vec<Trade> {
+        let mut trades = Vec::new();
+        if self.kind == BookKind::Mirror || self.phase == BookPhase::Auction {
+            return trades;
+        }
+
+        let key = Price::new(price);
+        let mut bids = self.bids.split_off(&key);
+        if let Some(level) = bids.remove(&key) {
+            self.bids.insert(key, level);
+        }
+        let rest = self.asks.split_off(&key);
+        let asks = std::mem::replace(&mut self.asks, rest);
+
+        for level in bids.into_values().rev().chain(asks.into_values()) {
+            for order in level.orders {
+                self.orders.remove(&order.id);
+                trades.push(
+                    Trade::new(
+                        order.id,
+                        OrderId::EXCHANGE,
+                        self.symbol.clone(),
+                        level.price,
+                        order.remaining_quantity,
+                    )
+                    .with_aggressor(order.side.opposite()),
+                );
+            }
+        }
+        self.settle(&mut trades);
+        trades
+    }
+
+    /// Chance that a passive order fills within a horizon over which
+    /// `expected_volume` is expected to trade at its price
+    ///
+    /// The order joins the back of the queue at `price`, and traded volume is
+    /// modelled as exponential, giving `exp(-(queue ahead + quantity) / expected_volume)`
+    /// with only the displayed queue counted.
+    /// Orders that would cross the book fill immediately.
+    pub fn passive_fill_probability(
+        &self,
+        side: OrderSide,
+        price: f64,
+        quantity: f64,
+        expected_volume: f64,
+    ) -> f64 {
+        let crosses = match side {
+            OrderSide::Buy => self.best_ask().is_some_and(|ask| price >= ask),
+            OrderSide::Sell => self.best_bid().is_some_and(|bid| price <= bid),
+        };
+        if crosses {
+            return 1.0;
+        }
+        if expected_volume <= 0.0 {
+            return 0.0;
+        }
+        (-(self.volume_at(price) + quantity.max(0.0)) / expected_volume).exp()
+    }
+
     // Private helper methods
 
-    fn add_order_to_book(&mut self, order: Order) {
-        let price_key = OrderedFloat::new(order.price);
+    fn apply_levels(side: &mut BTreeMap<Price, PriceLevel>, levels: &[(Price, Qty)]) {
+        for &(price, quantity) in levels {
+            let key = price;
+            if quantity > 0.0 {
+                side.entry(key)
+                    .or_insert_with(|| PriceLevel::new(price))
+                    .total_quantity = quantity;
+            } else {
+                side.remove(&key);
+            }
+        }
+    }
+
+    /// Levels of both sides inside `price_range`, ascending by price
+    fn levels_in(&self, price_range: RangeInclusive<f64>) -> impl Iterator<Item = &PriceLevel> {
+        let (low, high) = price_range.into_inner();
+        let valid = low <= high;
+        // BTreeMap::range panics on inverted bounds; such ranges are filtered out below
+        let keys = Price::new(low)..=Price::new(high.max(low));
+
+        // Bids sit below asks in an uncrossed book, so chaining keeps price order
+        self.bids
+            .range(keys.clone())
+            .chain(self.asks.range(keys))
+            .filter(move |_| valid)
+            .map(|(_, level)| level)
+    }
+
+    fn execute(&mut self, mut order: Order) -> Vec<Trade> {
+        if order.is_expired(Utc::now()) {
+            tracing::debug!("Rejected expired order #{} on {}", order.id.0, self.symbol);
+            return Vec::new();
+        }
+
+        match self.phase {
+            BookPhase::Continuous if order.time_in_force == TimeInForce::Gtx => {
+                tracing::debug!(
+                    "Rejected good-till-crossing order #{} outside an auction",
+                    order.id.0
+                );
+                return Vec::new();
+            }
+            BookPhase::Auction => {
+                // Only priced orders can wait for the uncross
+                if order.is_immediate() {
+                    tracing::debug!(
+                        "Rejected order #{} during the {} auction",
+                        order.id.0,
+                        self.symbol
+                    );
+                } else {
+                    self.add_order_to_book(order);
+                }
+                return Vec::new();
+            }
+            BookPhase::Continuous => {}
+        }
+
+        if order.post_only && self.would_cross(&order) {
+            let touch = self.opposite_touch(order.side).unwrap();
+            let repriced = match (self.post_only_mode, order.side) {
+                (PostOnlyMode::Reprice { tick_size }, OrderSide::Buy) => touch - tick_size,
+                (PostOnlyMode::Reprice { tick_size }, OrderSide::Sell) => touch + tick_size,
+                (PostOnlyMode::Reject, _) => 0.0,
+            };
+            if repriced <= 0.0 {
+                tracing::debug!("Rejected crossing post-only order #{}", order.id.0);
+                return Vec::new();
+            }
+            order.price = Price::new(repriced);
+        }
+
+        // Try to match the order first
+        let trades = self.match_order(&mut order);
+
+        // If order has remaining quantity, add to book unless it must not rest
+        if !order.is_filled() && !order.is_immediate() {
+            self.add_order_to_book(order);
+        }
+
+        trades
+    }
+
+    /// Take every resting and stop order matching `predicate` off the book in
+    /// one pass, in book order with bids first
+    fn remove_where(
+        &mut self,
+        side: Option<OrderSide>,
+        predicate: impl Fn(&Order) -> bool,
+    ) -> Vec<Order> {
+        let mut cancelled = Vec::new();
+        for (level_side, levels) in [
+            (OrderSide::Buy, &mut self.bids),
+            (OrderSide::Sell, &mut self.asks),
+        ] {
+            if side.is_some_and(|s| s != level_side) {
+                continue;
+            }
+            for level in levels.values_mut() {
+                let (removed, kept): (VecDeque<Order>, VecDeque<Order>) =
+                    level.orders.drain(..).partition(|o| predicate(o));
+                level.orders = kept;
+                for order in &removed {
+                    level.total_quantity -= order.remaining_quantity;
+                    level.hidden_quantity -= order.hidden_quantity();
+                }
+                cancelled.extend(removed);
+            }
+            levels.retain(|_, level| !level.is_empty());
+        }
+        for order in &cancelled {
+            self.orders.remove(&order.id);
+        }
+        let (stops, kept): (Vec<Order>, Vec<Order>) =
+            self.stops.drain(..).partition(|o| predicate(o));
+        self.stops = kept;
+        cancelled.extend(stops);
+
+        for order in &mut cancelled {
+            order.status = OrderStatus::Cancelled;
+        }
+        cancelled
+    }
+
+    fn opposite_touch(&self, side: OrderSide) -> Option<f64> {
+        match side {
+            OrderSide::Buy => self.best_ask(),
+            OrderSide::Sell => self.best_bid(),
+        }
+    }
+
+    /// Whether the opposite side holds enough at acceptable prices to fill
+    /// all of `order` at once
+    fn fully_fillable(&self, order: &Order) -> bool {
+        let levels: Box<dyn Iterator<Item = &PriceLevel>> = match order.side {
+            OrderSide::Buy => Box::new(self.asks.values()),
+            OrderSide::Sell => Box::new(self.bids.values().rev()),
+        };
+        let mut available = Qty::ZERO;
+        for level in levels {
+            if !order.can_match(level.price) {
+                break;
+            }
+            available += level.total_quantity;
+            if available >= order.remaining_quantity {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Let stops and pegs react to `trades` and to each other's trades until
+    /// the book is still
+    fn settle(&mut self, trades: &mut Vec<Trade>) {
+        loop {
+            self.trigger_stops(trades);
+            let traded = self.reprice_pegs(false);
+            if traded.is_empty() {
+                break;
+            }
+            trades.extend(traded);
+        }
+        self.number_trades(trades);
+    }
+
+    /// Give `trades` not numbered yet the book's next sequence numbers
+    fn number_trades(&mut self, trades: &mut [Trade]) {
+        for trade in trades.iter_mut().filter(|t| t.sequence == 0) {
+            self.trade_sequence += 1;
+            trade.sequence = self.trade_sequence;
+        }
+    }
+
+    /// Best bid and ask among levels holding an unpegged order; pegs track
+    /// these rather than each other
+    fn peg_references(&self) -> (Option<f64>, Option<f64>) {
+        let anchored = |level: &&PriceLevel| level.orders.iter().any(|o| o.peg.is_none());
+        (
+            self.bids
+                .values()
+                .rev()
+                .find(anchored)
+                .map(|l| l.price.value()),
+            self.asks.values().find(anchored).map(|l| l.price.value()),
+        )
+    }
+
+    /// Move resting pegged orders to where their pegs now put them, oldest
+    /// first, returning what they trade
+    ///
+    /// A moved order joins the back of its new level. `passive` leaves
+    /// orders in place whose new price would cross, for changes such as
+    /// cancels that have nowhere to report trades; the next order to arrive
+    /// moves them. Nothing moves during an auction.
+    fn reprice_pegs(&mut self, passive: bool) -> Vec<Trade> {
+        let mut trades = Vec::new();
+        if self.phase == BookPhase::Auction {
+            return trades;
+        }
+        loop {
+            let orders = &self.orders;
+            self.pegs.retain(|id, _| orders.contains_key(id));
+            let (bid, ask) = self.peg_references();
+            let mut moves = Vec::new();
+            for (&id, &price) in &self.pegs {
+                let side = self.orders[&id];
+                let levels = match side {
+                    OrderSide::Buy => &self.bids,
+                    OrderSide::Sell => &self.asks,
+                };
+                let Some(order) = levels
+                    .get(&price)
+                    .and_then(|level| level.orders.iter().find(|o| o.id == id))
+                else {
+                    continue;
+                };
+                let Some(target) = order.peg.and_then(|peg| peg.price(bid, ask)) else {
+                    continue;
+                };
+                let crosses = self.opposite_touch(side).is_some_and(|touch| match side {
+                    OrderSide::Buy => target >= touch,
+                    OrderSide::Sell => target <= touch,
+                });
+                if target != price.value() && !(passive && crosses) {
+                    moves.push((order.timestamp, id, side, price, target));
+                }
+            }
+            if moves.is_empty() {
+                return trades;
+            }
+            moves.sort_by_key(|&(timestamp, id, ..)| (timestamp, id.0));
+
+            let mut moved = Vec::with_capacity(moves.len());
+            for (_, id, side, price, target) in moves {
+                let levels = match side {
+                    OrderSide::Buy => &mut self.bids,
+                    OrderSide::Sell => &mut self.asks,
+                };
+                let key = price;
+                let Some(level) = levels.get_mut(&key) else {
+                    continue;
+                };
+                let Some(mut order) = level.remove_order(id) else {
+                    continue;
+                };
+                if level.is_empty() {
+                    levels.remove(&key);
+                }
+                self.orders.remove(&id);
+                self.pegs.remove(&id);
+                order.price = Price::new(target);
+                moved.push(order);
+            }
+            for order in moved {
+                if passive {
+                    self.add_order_to_book(order);
+                } else {
+                    trades.extend(self.execute(order));
+                }
+            }
+            if passive {
+                return trades;
+            }
+        }
+    }
+
+    /// Activate stops triggered by the last trade price until none are left,
+    /// appending what they trade to `trades`
+    fn trigger_stops(&mut self, trades: &mut Vec<Trade>) {
+        loop {
+            if let Some(trade) = trades.last() {
+                self.last_trade_price = Some(trade.price);
+            }
+            let Some(last) = self.last_trade_price else {
+                return;
+            };
+            let Some(index) = self.stops.iter().position(|o| o.is_triggered_by(last)) else {
+                return;
+            };
+            let mut stop = self.stops.remove(index);
+            stop.activate();
+            trades.extend(self.execute(stop));
+        }
+    }
+
+    fn add_order_to_book(&mut self, mut order: Order) {
+        order.refresh_tip();
+        let price_key = order.price;
         let side = order.side;
 
         // Track order
         self.orders.insert(order.id, side);
+        if order.peg.is_some() {
+            self.pegs.insert(order.id, order.price);
+        }
 
         // Add to appropriate side
         match side {
@@ -219,6 +1085,12 @@ impl OrderBook {
     fn match_order(&mut self, taker_order: &mut Order) -> Vec<Trade> {
         let mut trades = Vec::new();
 
+        // Fill-or-kill orders trade only when they can fill in full
+        if taker_order.time_in_force == TimeInForce::Fok && !self.fully_fillable(taker_order) {
+            taker_order.status = OrderStatus::Cancelled;
+            return trades;
+        }
+
         match taker_order.side {
             OrderSide::Buy => {
                 // Match against asks (sell orders)
@@ -243,7 +1115,7 @@ impl OrderBook {
             }
 
             // Check if buy order price is >= ask price
-            if !buy_order.can_match(price_key.0) {
+            if !buy_order.can_match(*price_key) {
                 break;
             }
 
@@ -251,7 +1123,7 @@ impl OrderBook {
             while !buy_order.is_filled() && !level.orders.is_empty() {
                 let maker_order = level.orders.front_mut().unwrap();
 
-                let match_quantity = buy_order.remaining_quantity.min(maker_order.remaining_quantity);
+                let match_quantity = buy_order.remaining_quantity.min(maker_order.visible_quantity());
                 let match_price = maker_order.price; // Price-time priority
 
                 // Create trade
@@ -261,7 +1133,8 @@ impl OrderBook {
                     self.symbol.clone(),
                     match_price,
                     match_quantity,
-                );
+                )
+                .with_aggressor(OrderSide::Buy);
                 trades.push(trade);
 
                 // Update quantities
@@ -269,10 +1142,12 @@ impl OrderBook {
                 maker_order.fill(match_quantity);
                 level.total_quantity -= match_quantity;
 
-                // Remove filled orders
+                // Remove filled orders, refresh consumed iceberg slices
                 if maker_order.is_filled() {
                     let filled_order = level.orders.pop_front().unwrap();
                     self.orders.remove(&filled_order.id);
+                } else if maker_order.visible_quantity() <= 0.0 {
+                    level.requeue_front();
                 }
             }
 
@@ -297,7 +1172,7 @@ impl OrderBook {
             }
 
             // Check if sell order price is <= bid price
-            if !sell_order.can_match(price_key.0) {
+            if !sell_order.can_match(*price_key) {
                 break;
             }
 
@@ -305,7 +1180,7 @@ impl OrderBook {
             while !sell_order.is_filled() && !level.orders.is_empty() {
                 let maker_order = level.orders.front_mut().unwrap();
 
-                let match_quantity = sell_order.remaining_quantity.min(maker_order.remaining_quantity);
+                let match_quantity = sell_order.remaining_quantity.min(maker_order.visible_quantity());
                 let match_price = maker_order.price; // Price-time priority
 
                 // Create trade
@@ -315,7 +1190,8 @@ impl OrderBook {
                     self.symbol.clone(),
                     match_price,
                     match_quantity,
-                );
+                )
+                .with_aggressor(OrderSide::Sell);
                 trades.push(trade);
 
                 // Update quantities
@@ -323,10 +1199,12 @@ impl OrderBook {
                 maker_order.fill(match_quantity);
                 level.total_quantity -= match_quantity;
 
-                // Remove filled orders
+                // Remove filled orders, refresh consumed iceberg slices
                 if maker_order.is_filled() {
                     let filled_order = level.orders.pop_front().unwrap();
                     self.orders.remove(&filled_order.id);
+                } else if maker_order.visible_quantity() <= 0.0 {
+                    level.requeue_front();
                 }
             }
 
@@ -342,20 +1220,182 @@ impl OrderBook {
     }
 }
 
+/// Volume-weighted average price of a run of levels
+fn volume_weighted_price<'a>(levels: impl Iterator<Item = &'a PriceLevel>) -> Option<f64> {
+    let (notional, quantity) = levels.fold((0.0, 0.0), |(notional, quantity), level| {
+        (
+            notional + level.price.value() * level.visible_quantity().value(),
+            quantity + level.visible_quantity().value(),
+        )
+    });
+    (quantity > 0.0).then(|| notional / quantity)
+}
+
+impl MemoryUsage for OrderBook {
+    fn heap_bytes(&self) -> usize {
+        let levels = self.bids.values().chain(self.asks.values());
+        let level_bytes: usize = levels
+            .map(|level| {
+                std::mem::size_of::<(Price, PriceLevel)>()
+                    + level.orders.capacity() * std::mem::size_of::<Order>()
+            })
+            .sum();
+        level_bytes
+            + self.orders.capacity() * std::mem::size_of::<(OrderId, OrderSide)>()
+            + self.stops.capacity() * std::mem::size_of::<Order>()
+    }
+}
+
 /// Thread-safe wrapper for OrderBook
 pub struct SharedOrderBook {
     inner: Arc<Mutex<OrderBook>>,
+    latency: Arc<LatencySamples>,
+    tape: Option<SharedTradeTape>,
+    shedder: Option<SharedLoadShedder>,
+    throughput: Option<SharedThroughputMeter>,
 }
 
 impl SharedOrderBook {
-    pub fn new(symbol: String) -> Self {
+    pub fn new(symbol: impl Into<Symbol>) -> Self {
         Self {
             inner: Arc::new(Mutex::new(OrderBook::new(symbol))),
+            latency: Arc::default(),
+            tape: None,
+            shedder: None,
+            throughput: None,
+        }
+    }
+
+    pub fn mirror(symbol: impl Into<Symbol>) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(OrderBook::mirror(symbol))),
+            latency: Arc::default(),
+            tape: None,
+            shedder: None,
+            throughput: None,
         }
     }
 
+    pub fn kind(&self) -> BookKind {
+        self.inner.lock().unwrap().kind()
+    }
+
+    /// Record every local match on `tape`
+    pub fn with_trade_tape(mut self, tape: SharedTradeTape) -> Self {
+        self.tape = Some(tape);
+        self
+    }
+
+    /// Report matching latency to `shedder`; order entry itself is never shed
+    pub fn with_load_shedder(mut self, shedder: SharedLoadShedder) -> Self {
+        self.shedder = Some(shedder);
+        self
+    }
+
+    /// Count orders, fills and matching latency on `throughput`
+    pub fn with_throughput(mut self, throughput: SharedThroughputMeter) -> Self {
+        self.throughput = Some(throughput);
+        self
+    }
+
     pub fn add_order(&self, order: Order) -> Vec<Trade> {
-        self.inner.lock().unwrap().add_order(order)
+        let span = self.latency.span();
+        let trades = self.inner.lock().unwrap().add_order(order);
+        let latency_ms = span.elapsed_ms();
+        drop(span);
+
+        if let Some(shedder) = &self.shedder {
+            shedder.record_latency(latency_ms);
+        }
+        if let Some(throughput) = &self.throughput {
+            throughput.record_order(trades.len(), latency_ms);
+        }
+
+        if let Some(tape) = &self.tape {
+            for trade in &trades {
+                tape.record_local(trade);
+            }
+        }
+
+        trades
+    }
+
+    /// Replace `strategy`'s quotes under a single lock, see [`OrderBook::replace_quotes`]
+    pub fn replace_quotes(&self, strategy: &str, quotes: Vec<Order>) -> (Vec<Order>, Vec<Trade>) {
+        let span = self.latency.span();
+        let (cancelled, trades) = self.inner.lock().unwrap().replace_quotes(strategy, quotes);
+        let latency_ms = span.elapsed_ms();
+        drop(span);
+
+        if let Some(shedder) = &self.shedder {
+            shedder.record_latency(latency_ms);
+        }
+        if let Some(throughput) = &self.throughput {
+            throughput.record_order(trades.len(), latency_ms);
+        }
+        if let Some(tape) = &self.tape {
+            for trade in &trades {
+                tape.record_local(trade);
+            }
+        }
+
+        (cancelled, trades)
+    }
+
+    pub fn mass_cancel(&self, filter: &CancelFilter) -> Vec<Order> {
+        self.inner.lock().unwrap().mass_cancel(filter)
+    }
+
+    pub fn phase(&self) -> BookPhase {
+        self.inner.lock().unwrap().phase()
+    }
+
+    pub fn start_auction(&self) {
+        self.inner.lock().unwrap().start_auction()
+    }
+
+    pub fn indicative_auction(&self) -> Option<AuctionQuote> {
+        self.inner.lock().unwrap().indicative_auction()
+    }
+
+    /// Uncross under a single lock, see [`OrderBook::uncross`]
+    ///
+    /// Auction prints go on the tape without an aggressor.
+    pub fn uncross(&self) -> AuctionResult {
+        let result = self.inner.lock().unwrap().uncross();
+        if let Some(tape) = &self.tape {
+            for trade in &result.trades {
+                tape.record_local(trade);
+            }
+        }
+        result
+    }
+
+    /// Hold the book for several operations that must not interleave with
+    /// others, such as cancelling across books at once
+    pub(crate) fn lock(&self) -> MutexGuard<'_, OrderBook> {
+        self.inner.lock().unwrap()
+    }
+
+    pub fn post_only_mode(&self) -> PostOnlyMode {
+        self.inner.lock().unwrap().post_only_mode()
+    }
+
+    pub fn set_post_only_mode(&self, mode: PostOnlyMode) {
+        self.inner.lock().unwrap().set_post_only_mode(mode)
+    }
+
+    pub fn would_cross(&self, order: &Order) -> bool {
+        self.inner.lock().unwrap().would_cross(order)
+    }
+
+    pub fn expire_orders(&self, now: DateTime<Utc>) -> Vec<Order> {
+        self.inner.lock().unwrap().expire_orders(now)
+    }
+
+    /// Percentiles of recent `add_order` latencies, lock wait included
+    pub fn matching_latency(&self) -> Option<LatencySummary> {
+        self.latency.summary()
     }
 
     pub fn cancel_order(&self, order_id: OrderId) -> Option<Order> {
@@ -378,19 +1418,95 @@ impl SharedOrderBook {
         self.inner.lock().unwrap().mid_price()
     }
 
-    pub fn get_depth(&self, levels: usize) -> (Vec<(f64, f64)>, Vec<(f64, f64)>) {
+    pub fn weighted_mid_price(&self, depth_levels: usize) -> Option<f64> {
+        self.inner.lock().unwrap().weighted_mid_price(depth_levels)
+    }
+
+    pub fn micro_price(&self) -> Option<f64> {
+        self.inner.lock().unwrap().micro_price()
+    }
+
+    pub fn get_depth(&self, levels: usize) -> Depth {
         self.inner.lock().unwrap().get_depth(levels)
     }
 
     pub fn order_count(&self) -> usize {
         self.inner.lock().unwrap().order_count()
     }
+
+    pub fn volume_at(&self, price: f64) -> f64 {
+        self.inner.lock().unwrap().volume_at(price)
+    }
+
+    pub fn volume_within(&self, price_range: RangeInclusive<f64>) -> f64 {
+        self.inner.lock().unwrap().volume_within(price_range)
+    }
+
+    pub fn cumulative_depth_to(&self, price: f64) -> f64 {
+        self.inner.lock().unwrap().cumulative_depth_to(price)
+    }
+
+    pub fn levels_between(&self, p1: f64, p2: f64) -> Vec<(f64, f64)> {
+        self.inner.lock().unwrap().levels_between(p1, p2)
+    }
+
+    pub fn stop_orders(&self) -> Vec<Order> {
+        self.inner.lock().unwrap().stop_orders().to_vec()
+    }
+
+    pub fn cross(&self, order: &mut Order) -> Vec<Trade> {
+        self.inner.lock().unwrap().cross(order)
+    }
+
+    /// Fill orders traded through at `price`, see [`OrderBook::fill_through`]
+    ///
+    /// The exchange is the aggressor of the fills on the tape.
+    pub fn fill_through(&self, price: f64) -> Vec<Trade> {
+        let trades = self.inner.lock().unwrap().fill_through(price);
+        if let Some(tape) = &self.tape {
+            for trade in &trades {
+                tape.record_local(trade);
+            }
+        }
+        trades
+    }
+
+    pub fn passive_fill_probability(
+        &self,
+        side: OrderSide,
+        price: f64,
+        quantity: f64,
+        expected_volume: f64,
+    ) -> f64 {
+        self.inner
+            .lock()
+            .unwrap()
+            .passive_fill_probability(side, price, quantity, expected_volume)
+    }
+
+    pub fn apply_snapshot(&self, levels: &BookUpdate) {
+        self.inner.lock().unwrap().apply_snapshot(levels)
+    }
+
+    pub fn apply_diff(&self, update: &BookUpdate) {
+        self.inner.lock().unwrap().apply_diff(update)
+    }
+}
+
+impl MemoryUsage for SharedOrderBook {
+    fn heap_bytes(&self) -> usize {
+        self.inner.lock().unwrap().heap_bytes()
+    }
 }
 
 impl Clone for SharedOrderBook {
     fn clone(&self) -> Self {
         Self {
             inner: Arc::clone(&self.inner),
+            latency: Arc::clone(&self.latency),
+            tape: self.tape.clone(),
+            shedder: self.shedder.clone(),
+            throughput: self.throughput.clone(),
         }
     }
 }
@@ -398,7 +1514,11 @@ impl Clone for SharedOrderBook {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::order::OrderSide;
+    use crate::types::order::{OrderSide, Peg, PegReference};
+
+    fn limit(side: OrderSide, price: f64, quantity: f64) -> Order {
+        Order::new_limit("BTCUSDT".to_string(), side, price, quantity)
+    }
 
     #[test]
     fn test_add_and_match_orders() {
@@ -417,7 +1537,12 @@ mod tests {
 
         assert_eq!(trades.len(), 1);
         assert_eq!(trades[0].quantity, 0.5);
+        assert_eq!(trades[0].aggressor, Some(OrderSide::Buy));
+        assert_eq!(trades[0].sequence, 1);
         assert_eq!(book.order_count(), 1); // Sell order still has 0.5 remaining
+
+        let trades = book.add_order(Order::new_market("BTCUSDT", OrderSide::Buy, 0.5));
+        assert_eq!(trades[0].sequence, 2);
     }
 
     #[test]
@@ -455,5 +1580,437 @@ mod tests {
         // Should match with first sell order (time priority)
         assert_eq!(trades.len(), 1);
         assert_eq!(trades[0].maker_order_id, sell1_id);
+
+        // Second sell order keeps resting in the book
+        assert!(book.cancel_order(sell2_id).is_some());
+    }
+
+    #[test]
+    fn test_stop_orders_trigger_on_last_trade() {
+        let mut book = OrderBook::new("BTCUSDT".to_string());
+        book.add_order(limit(OrderSide::Sell, 50000.0, 1.0));
+        book.add_order(limit(OrderSide::Sell, 50100.0, 1.0));
+        book.add_order(limit(OrderSide::Buy, 49900.0, 1.0));
+
+        // No trade yet, so both stops wait off-book
+        let buy_stop = Order::new_stop_market("BTCUSDT", OrderSide::Buy, 50000.0, 1.0);
+        let sell_stop = Order::new_stop_limit("BTCUSDT", OrderSide::Sell, 49000.0, 48900.0, 1.0);
+        let sell_stop_id = sell_stop.id;
+        assert!(book.add_order(buy_stop).is_empty());
+        assert!(book.add_order(sell_stop).is_empty());
+        assert_eq!(book.stop_orders().len(), 2);
+        assert_eq!(book.order_count(), 3);
+
+        // A trade at 50000 triggers the buy stop, which lifts the next ask
+        let trades = book.add_order(limit(OrderSide::Buy, 50000.0, 1.0));
+        assert_eq!(trades.len(), 2);
+        assert_eq!(trades[1].price, 50100.0);
+        assert_eq!(book.last_trade_price(), Some(50100.0));
+        assert_eq!(book.best_ask(), None);
+
+        let cancelled = book.cancel_order(sell_stop_id).unwrap();
+        assert_eq!(cancelled.status, OrderStatus::Cancelled);
+        assert!(book.stop_orders().is_empty());
+    }
+
+    #[test]
+    fn test_market_orders_never_rest() {
+        let mut book = OrderBook::new("BTCUSDT".to_string());
+        book.add_order(limit(OrderSide::Buy, 100.0, 1.0));
+
+        // The 2 left over has no price to rest at
+        let trades = book.add_order(Order::new_market("BTCUSDT", OrderSide::Sell, 3.0));
+        assert_eq!(trades.len(), 1);
+        assert_eq!((book.best_bid(), book.best_ask()), (None, None));
+        assert!(book.add_order(limit(OrderSide::Buy, 100.0, 1.0)).is_empty());
+
+        // Nor does what a triggered stop-market cannot fill
+        let stop = Order::new_stop_market("BTCUSDT", OrderSide::Sell, 99.5, 5.0);
+        book.add_order(stop);
+        book.add_order(limit(OrderSide::Buy, 99.0, 1.0));
+        let trades = book.add_order(limit(OrderSide::Sell, 99.0, 2.0));
+        assert_eq!(trades.len(), 2);
+        assert_eq!((book.best_bid(), book.best_ask()), (None, None));
+        assert!(book.stop_orders().is_empty());
+    }
+
+    #[test]
+    fn test_replace_quotes_and_mass_cancel() {
+        let mut book = OrderBook::new("BTCUSDT".to_string());
+        book.add_order(limit(OrderSide::Sell, 101.0, 1.0));
+        let quote = |side, price| limit(side, price, 1.0);
+
+        let (cancelled, trades) = book.replace_quotes(
+            "mm",
+            vec![quote(OrderSide::Buy, 99.0), quote(OrderSide::Sell, 102.0)],
+        );
+        assert!(cancelled.is_empty() && trades.is_empty());
+        assert_eq!(book.order_count(), 3);
+
+        // The new set replaces the old one rather than adding to it
+        let (cancelled, _) = book.replace_quotes(
+            "mm",
+            vec![quote(OrderSide::Buy, 99.5), quote(OrderSide::Sell, 101.5)],
+        );
+        assert_eq!(cancelled.len(), 2);
+        assert_eq!(book.order_count(), 3);
+        assert_eq!(
+            book.get_depth(5),
+            (vec![(99.5, 1.0)], vec![(101.0, 1.0), (101.5, 1.0)])
+        );
+
+        let filter = CancelFilter {
+            side: Some(OrderSide::Sell),
+            strategy: Some("mm".to_string()),
+            client_id: None,
+        };
+        let cancelled = book.mass_cancel(&filter);
+        assert_eq!(cancelled.len(), 1);
+        assert_eq!(cancelled[0].status, OrderStatus::Cancelled);
+        assert_eq!(book.best_ask(), Some(101.0));
+        assert_eq!(book.mass_cancel(&CancelFilter::default()).len(), 2);
+        assert_eq!(book.order_count(), 0);
+    }
+
+    #[test]
+    fn test_time_in_force() {
+        let mut book = OrderBook::new("BTCUSDT".to_string());
+        book.add_order(limit(OrderSide::Sell, 100.0, 1.0));
+        book.add_order(limit(OrderSide::Sell, 101.0, 1.0));
+
+        // Fill-or-kill needs all 3 within its price, so nothing trades
+        let fok = limit(OrderSide::Buy, 101.0, 3.0).with_time_in_force(TimeInForce::Fok);
+        assert!(book.add_order(fok).is_empty());
+        assert_eq!(book.order_count(), 2);
+
+        // Immediate-or-cancel takes what it can and leaves nothing behind
+        let ioc = limit(OrderSide::Buy, 100.0, 3.0).with_time_in_force(TimeInForce::Ioc);
+        assert_eq!(book.add_order(ioc).len(), 1);
+        assert_eq!((book.best_bid(), book.order_count()), (None, 1));
+
+        let now = Utc::now();
+        let gtd = limit(OrderSide::Buy, 99.0, 1.0).good_till(now + chrono::Duration::seconds(5));
+        let gtd_id = gtd.id;
+        book.add_order(gtd);
+        assert!(book.expire_orders(now).is_empty());
+        let expired = book.expire_orders(now + chrono::Duration::seconds(5));
+        assert_eq!((expired.len(), expired[0].id), (1, gtd_id));
+        assert_eq!(expired[0].status, OrderStatus::Expired);
+        assert_eq!(book.best_bid(), None);
+    }
+
+    #[test]
+    fn test_auction_uncrosses_at_the_max_volume_price() {
+        let mut book = OrderBook::new("BTCUSDT".to_string());
+        let gtx = |side, price, quantity| {
+            limit(side, price, quantity).with_time_in_force(TimeInForce::Gtx)
+        };
+        // Good-till-crossing orders wait for an auction
+        book.add_order(gtx(OrderSide::Buy, 99.0, 1.0));
+        assert_eq!(book.order_count(), 0);
+
+        book.start_auction();
+        for order in [
+            gtx(OrderSide::Buy, 102.0, 2.0),
+            limit(OrderSide::Buy, 101.0, 1.0),
+            gtx(OrderSide::Buy, 99.0, 1.0),
+            limit(OrderSide::Sell, 100.0, 1.0),
+            limit(OrderSide::Sell, 101.0, 2.0),
+            limit(OrderSide::Sell, 103.0, 1.0),
+        ] {
+            assert!(book.add_order(order).is_empty());
+        }
+        assert_eq!(book.best_bid(), Some(102.0));
+        let quote = book.indicative_auction().unwrap();
+        assert_eq!(
+            (quote.price, quote.volume, quote.imbalance),
+            (101.0, 3.0, 0.0)
+        );
+
+        let result = book.uncross();
+        assert_eq!(result.trades.len(), 3);
+        assert!(result.trades.iter().all(|t| t.price == 101.0));
+        assert_eq!(
+            (result.cancelled.len(), result.cancelled[0].price),
+            (1, Price::new(99.0))
+        );
+        assert_eq!(book.phase(), BookPhase::Continuous);
+        assert_eq!((book.best_bid(), book.best_ask()), (None, Some(103.0)));
+        assert_eq!(book.last_trade_price(), Some(101.0));
+    }
+
+    #[test]
+    fn test_pegged_orders_follow_the_book_and_cross_when_it_moves() {
+        let mut book = OrderBook::new("BTCUSDT");
+        book.add_order(limit(OrderSide::Buy, 99.0, 1.0));
+        book.add_order(limit(OrderSide::Sell, 102.0, 1.0));
+
+        // Primary peg one below the bid, mid peg at the mid
+        let primary =
+            limit(OrderSide::Buy, 0.0, 1.0).with_peg(Peg::new(PegReference::BestBid, -1.0));
+        let mid = limit(OrderSide::Sell, 0.0, 1.0).with_peg(Peg::new(PegReference::Mid, 0.0));
+        book.add_order(primary.clone());
+        book.add_order(mid.clone());
+        assert_eq!(book.volume_at(98.0), 1.0);
+        assert_eq!(book.best_ask(), Some(100.5));
+
+        // A better bid drags both pegs up
+        book.add_order(limit(OrderSide::Buy, 100.0, 1.0));
+        assert_eq!(book.volume_at(99.0), 2.0);
+        assert_eq!(book.best_ask(), Some(101.0));
+
+        // A peg the move puts through the ask trades as the taker
+        let mut book = OrderBook::new("BTCUSDT");
+        book.add_order(limit(OrderSide::Buy, 99.0, 1.0));
+        let ask = limit(OrderSide::Sell, 102.0, 1.0);
+        book.add_order(ask.clone());
+        let chaser = limit(OrderSide::Buy, 0.0, 1.0).with_peg(Peg::new(PegReference::BestBid, 1.5));
+        assert!(book.add_order(chaser.clone()).is_empty());
+        let trades = book.add_order(limit(OrderSide::Buy, 101.0, 1.0));
+        assert_eq!(trades.len(), 1);
+        assert_eq!(
+            (trades[0].maker_order_id, trades[0].taker_order_id),
+            (ask.id, chaser.id)
+        );
+        assert_eq!(trades[0].price, Price::new(102.0));
+
+        // Pegs with nothing to track are refused
+        let mut empty = OrderBook::new("BTCUSDT");
+        let peg = limit(OrderSide::Buy, 0.0, 1.0).with_peg(Peg::new(PegReference::BestAsk, 0.0));
+        assert!(empty.add_order(peg).is_empty());
+        assert_eq!(empty.order_count(), 0);
+    }
+
+    #[test]
+    fn test_market_trading_through_fills_resting_orders() {
+        let mut book = OrderBook::new("BTCUSDT".to_string());
+        let bid = limit(OrderSide::Buy, 100.0, 1.0);
+        let at_market = limit(OrderSide::Buy, 99.0, 1.0);
+        let ask = limit(OrderSide::Sell, 101.0, 2.0);
+        let (bid_id, ask_id) = (bid.id, ask.id);
+        book.add_order(bid);
+        book.add_order(at_market);
+        book.add_order(ask);
+
+        // A print at 99 went through the 100 bid but not the 99 one
+        let trades = book.fill_through(99.0);
+        assert_eq!(trades.len(), 1);
+        assert_eq!(trades[0].maker_order_id, bid_id);
+        assert_eq!(trades[0].taker_order_id, OrderId::EXCHANGE);
+        assert_eq!(trades[0].price.value(), 100.0);
+        assert_eq!(book.best_bid(), Some(99.0));
+
+        let trades = book.fill_through(101.5);
+        assert_eq!(trades[0].maker_order_id, ask_id);
+        assert_eq!(trades[0].quantity.value(), 2.0);
+        assert_eq!((book.best_ask(), book.order_count()), (None, 1));
+    }
+
+    #[test]
+    fn test_iceberg_shows_tip_and_refreshes() {
+        let mut book = OrderBook::new("BTCUSDT".to_string());
+        let iceberg = limit(OrderSide::Sell, 100.0, 10.0).with_display_quantity(2.0);
+        let iceberg_id = iceberg.id;
+        book.add_order(iceberg);
+        let plain = limit(OrderSide::Sell, 100.0, 1.0);
+        let plain_id = plain.id;
+        book.add_order(plain);
+        assert_eq!(book.get_depth(1).1, vec![(100.0, 3.0)]);
+
+        // Taking the tip shows the next slice behind the plain order
+        let trades = book.add_order(limit(OrderSide::Buy, 100.0, 3.5));
+        let makers: Vec<OrderId> = trades.iter().map(|t| t.maker_order_id).collect();
+        assert_eq!(makers, vec![iceberg_id, plain_id, iceberg_id]);
+        assert_eq!(trades[2].quantity, 0.5);
+        assert_eq!(book.get_depth(1).1, vec![(100.0, 1.5)]);
+        assert_eq!(book.volume_at(100.0), 1.5);
+    }
+
+    #[test]
+    fn test_analytics_never_reveal_iceberg_reserve() {
+        let mut book = OrderBook::new("BTCUSDT".to_string());
+        book.add_order(limit(OrderSide::Buy, 99.0, 1.0));
+        book.add_order(limit(OrderSide::Sell, 101.0, 100.0).with_display_quantity(1.0));
+        book.add_order(limit(OrderSide::Sell, 102.0, 1.0));
+
+        // The book looks the same as with a plain 1.0 ask at 101
+        assert_eq!(book.micro_price(), Some(100.0));
+        assert_eq!(book.weighted_mid_price(2), Some((99.0 + 101.5) / 2.0));
+        assert_eq!(book.volume_at(101.0), 1.0);
+        assert_eq!(book.volume_within(99.0..=102.0), 3.0);
+        assert_eq!(book.cumulative_depth_to(102.0), 2.0);
+        assert_eq!(
+            book.levels_between(101.0, 102.0),
+            vec![(101.0, 1.0), (102.0, 1.0)]
+        );
+        let chance = book.passive_fill_probability(OrderSide::Sell, 101.0, 1.0, 2.0);
+        assert!((chance - (-1.0f64).exp()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_post_only_never_takes_liquidity() {
+        let mut book = OrderBook::new("BTCUSDT".to_string());
+        book.add_order(limit(OrderSide::Sell, 100.0, 1.0));
+
+        let crossing = limit(OrderSide::Buy, 100.5, 1.0).with_post_only(true);
+        assert!(book.would_cross(&crossing));
+        assert!(book.add_order(crossing.clone()).is_empty());
+        assert_eq!((book.best_bid(), book.order_count()), (None, 1));
+
+        book.set_post_only_mode(PostOnlyMode::Reprice { tick_size: 0.5 });
+        assert!(book.add_order(crossing).is_empty());
+        assert_eq!(book.best_bid(), Some(99.5));
+    }
+
+    #[test]
+    fn test_weighted_mid_and_micro_price() {
+        let mut book = OrderBook::new("BTCUSDT".to_string());
+        book.add_order(limit(OrderSide::Buy, 99.0, 3.0));
+        book.add_order(limit(OrderSide::Buy, 98.0, 1.0));
+        book.add_order(limit(OrderSide::Sell, 101.0, 1.0));
+        book.add_order(limit(OrderSide::Sell, 103.0, 1.0));
+
+        // Top level only matches the simple mid
+        assert_eq!(book.weighted_mid_price(1), book.mid_price());
+
+        // Bid VWAP 98.75, ask VWAP 102.0
+        let weighted = book.weighted_mid_price(2).unwrap();
+        assert!((weighted - 100.375).abs() < 1e-9);
+
+        // Heavier bid pushes the micro-price towards the ask
+        let micro = book.micro_price().unwrap();
+        assert!((micro - (99.0 * 1.0 + 101.0 * 3.0) / 4.0).abs() < 1e-9);
+        assert!(micro > book.mid_price().unwrap());
+    }
+
+    #[test]
+    fn test_weighted_prices_need_both_sides() {
+        let mut book = OrderBook::new("BTCUSDT".to_string());
+        book.add_order(limit(OrderSide::Buy, 99.0, 1.0));
+
+        assert_eq!(book.weighted_mid_price(5), None);
+        assert_eq!(book.micro_price(), None);
+    }
+
+    #[test]
+    fn test_depth_queries() {
+        let mut book = OrderBook::new("BTCUSDT".to_string());
+        for (side, price, qty) in [
+            (OrderSide::Buy, 98.0, 2.0),
+            (OrderSide::Buy, 99.0, 1.0),
+            (OrderSide::Sell, 101.0, 1.5),
+            (OrderSide::Sell, 102.0, 0.5),
+            (OrderSide::Sell, 104.0, 3.0),
+        ] {
+            book.add_order(limit(side, price, qty));
+        }
+
+        assert_eq!(book.volume_at(99.0), 1.0);
+        assert_eq!(book.volume_at(101.0), 1.5);
+        assert_eq!(book.volume_at(100.0), 0.0);
+        // Levels are keyed on rounded prices, so float dust finds them
+        assert_eq!(book.volume_at(99.0 + 1e-12), 1.0);
+        // Prices and sizes that are not numbers never join a level
+        assert!(book
+            .add_order(limit(OrderSide::Sell, f64::NAN, 1.0))
+            .is_empty());
+        assert!(book
+            .add_order(limit(OrderSide::Sell, 101.0, f64::INFINITY))
+            .is_empty());
+        assert_eq!(book.volume_at(101.0), 1.5);
+        assert_eq!(book.order_count(), 5);
+
+        assert_eq!(book.volume_within(99.0..=102.0), 3.0);
+        assert_eq!(book.volume_within(102.0..=99.0), 0.0);
+
+        assert_eq!(book.cumulative_depth_to(102.0), 2.0);
+        assert_eq!(book.cumulative_depth_to(98.0), 3.0);
+        assert_eq!(book.cumulative_depth_to(100.0), 0.0);
+
+        assert_eq!(
+            book.levels_between(102.0, 98.5),
+            vec![(99.0, 1.0), (101.0, 1.5), (102.0, 0.5)]
+        );
+    }
+
+    #[test]
+    fn test_mirror_snapshot_and_diff() {
+        let level = |price: f64, qty: f64| (Price::new(price), Qty::new(qty));
+        let mut book = OrderBook::mirror("BTCUSDT");
+        book.apply_snapshot(&BookUpdate {
+            bids: vec![level(99.0, 1.0), level(98.0, 2.0)],
+            asks: vec![level(101.0, 1.5)],
+        });
+        assert_eq!(book.best_bid(), Some(99.0));
+
+        book.apply_diff(&BookUpdate {
+            bids: vec![level(99.0, 0.0), level(98.0, 3.0)],
+            asks: vec![level(100.5, 0.3)],
+        });
+        assert_eq!(book.best_bid(), Some(98.0));
+        assert_eq!(book.volume_at(98.0), 3.0);
+        assert_eq!(
+            book.get_depth(5),
+            (vec![(98.0, 3.0)], vec![(100.5, 0.3), (101.0, 1.5)])
+        );
+    }
+
+    #[test]
+    fn test_matching_and_mirror_books_stay_apart() {
+        let mut mirror = OrderBook::mirror("BTCUSDT");
+        mirror.apply_snapshot(&BookUpdate {
+            bids: Vec::new(),
+            asks: vec![(Price::new(101.0), Qty::new(1.0))],
+        });
+        assert!(mirror.add_order(limit(OrderSide::Buy, 101.0, 1.0)).is_empty());
+        assert_eq!(mirror.volume_at(101.0), 1.0);
+        assert_eq!(mirror.order_count(), 0);
+
+        let mut matching = OrderBook::new("BTCUSDT");
+        matching.add_order(limit(OrderSide::Sell, 101.0, 1.0));
+        matching.apply_snapshot(&BookUpdate::default());
+        assert_eq!(matching.kind(), BookKind::Matching);
+        assert_eq!(matching.order_count(), 1);
+    }
+
+    #[test]
+    fn test_cross_consumes_mirror_liquidity() {
+        let level = |price: f64, qty: f64| (Price::new(price), Qty::new(qty));
+        let mut mirror = OrderBook::mirror("BTCUSDT");
+        mirror.apply_snapshot(&BookUpdate {
+            bids: vec![level(99.0, 1.0)],
+            asks: vec![level(101.0, 0.5), level(102.0, 1.0), level(105.0, 1.0)],
+        });
+
+        let mut buy = limit(OrderSide::Buy, 102.0, 1.0);
+        let trades = mirror.cross(&mut buy);
+        assert!(buy.is_filled());
+        assert_eq!(trades.len(), 2);
+        assert_eq!(trades[0].maker_order_id, OrderId::EXCHANGE);
+        assert_eq!((trades[1].price, trades[1].quantity), (Price::new(102.0), Qty::new(0.5)));
+        assert_eq!(mirror.best_ask(), Some(102.0));
+        assert_eq!(mirror.volume_at(102.0), 0.5);
+
+        // Queue ahead lowers the chance of a passive fill
+        let at_touch = mirror.passive_fill_probability(OrderSide::Buy, 99.0, 1.0, 4.0);
+        let inside = mirror.passive_fill_probability(OrderSide::Buy, 100.0, 1.0, 4.0);
+        assert!((at_touch - (-0.5f64).exp()).abs() < 1e-9);
+        assert!(inside > at_touch);
+        assert_eq!(mirror.passive_fill_probability(OrderSide::Sell, 99.0, 1.0, 4.0), 1.0);
+    }
+
+    #[test]
+    fn test_shared_book_records_matches_on_tape() {
+        let tape = SharedTradeTape::new(10);
+        let book = SharedOrderBook::new("BTCUSDT".to_string()).with_trade_tape(tape.clone());
+
+        book.add_order(limit(OrderSide::Sell, 101.0, 1.0));
+        book.add_order(limit(OrderSide::Buy, 101.0, 0.4));
+
+        let trades = tape.recent("BTCUSDT", 10);
+        assert_eq!(trades.len(), 1);
+        assert_eq!(trades[0].quantity, 0.4);
+        assert_eq!(trades[0].aggressor, Some(OrderSide::Buy));
+        assert_eq!(book.matching_latency().unwrap().samples, 2);
     }
 }
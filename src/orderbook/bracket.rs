@@ -0,0 +1,264 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::{Order, OrderId, OrderSide, Symbol, Trade};
+
+/// Quantities closer than this are treated as equal
+const EPSILON: f64 = 1e-9;
+
+/// Exits attached to an entry order, placed once the entry fills
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Bracket {
+    /// Limit price the position is closed at in profit
+    pub take_profit: f64,
+    /// Last trade price that closes the position at market in loss
+    pub stop_loss: f64,
+}
+
+impl Bracket {
+    /// Whether both exits are positive and on the right side of an entry on
+    /// `side`, and of its limit price when it has one
+    pub fn is_valid_for(&self, side: OrderSide, entry_price: Option<f64>) -> bool {
+        let positive = |price: f64| price.is_finite() && price > 0.0;
+        if !(positive(self.take_profit) && positive(self.stop_loss)) {
+            return false;
+        }
+        let (low, high) = match side {
+            OrderSide::Buy => (self.stop_loss, self.take_profit),
+            OrderSide::Sell => (self.take_profit, self.stop_loss),
+        };
+        match entry_price {
+            Some(price) => low < price && price < high,
+            None => low < high,
+        }
+    }
+}
+
+/// Where one bracket stands
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BracketStatus {
+    pub entry_id: OrderId,
+    pub symbol: Symbol,
+    /// Side of the entry; the exits are on the other one
+    pub side: OrderSide,
+    pub bracket: Bracket,
+    /// Entry quantity that can still fill
+    pub entry_quantity: f64,
+    pub entry_filled: f64,
+    /// Filled entry quantity not exited yet, which both exits cover
+    pub open_quantity: f64,
+    pub take_profit_id: Option<OrderId>,
+    pub stop_loss_id: Option<OrderId>,
+}
+
+impl BracketStatus {
+    /// The entry cannot fill any further and nothing is left to exit
+    fn is_done(&self) -> bool {
+        self.entry_filled + EPSILON >= self.entry_quantity && self.open_quantity <= EPSILON
+    }
+}
+
+/// What the book manager has to do to keep exits in line with fills
+#[derive(Debug, Clone)]
+pub enum BracketAction {
+    Cancel { symbol: Symbol, order_id: OrderId },
+    Place(Order),
+}
+
+/// Live brackets by entry, and the exits that belong to them
+///
+/// Both exits always cover the open quantity: a fill of the entry grows
+/// them, a fill of one exit shrinks the other, so the position is closed
+/// once whichever exit trades first. Exits are resized by cancelling and
+/// placing them again.
+#[derive(Debug, Default)]
+pub struct Brackets {
+    brackets: HashMap<OrderId, BracketStatus>,
+    /// Entry of every live exit
+    exits: HashMap<OrderId, OrderId>,
+    /// Quantity every live exit has left
+    remaining: HashMap<OrderId, f64>,
+}
+
+impl Brackets {
+    /// Track `entry`, whose exits follow its fills
+    pub fn register(&mut self, entry: &Order, bracket: Bracket) {
+        self.brackets.insert(
+            entry.id,
+            BracketStatus {
+                entry_id: entry.id,
+                symbol: entry.symbol.clone(),
+                side: entry.side,
+                bracket,
+                entry_quantity: entry.remaining_quantity.value(),
+                entry_filled: 0.0,
+                open_quantity: 0.0,
+                take_profit_id: None,
+                stop_loss_id: None,
+            },
+        );
+    }
+
+    pub fn get(&self, entry_id: OrderId) -> Option<&BracketStatus> {
+        self.brackets.get(&entry_id)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.brackets.is_empty()
+    }
+
+    /// Account for `trades` and say which exits to cancel or place
+    pub fn on_trades(&mut self, trades: &[Trade]) -> Vec<BracketAction> {
+        let mut touched = Vec::new();
+        for trade in trades {
+            let quantity = trade.quantity.value();
+            for id in [trade.maker_order_id, trade.taker_order_id] {
+                if let Some(status) = self.brackets.get_mut(&id) {
+                    status.entry_filled += quantity;
+                    status.open_quantity += quantity;
+                    touched.push(id);
+                } else if let Some(&entry_id) = self.exits.get(&id) {
+                    *self.remaining.entry(id).or_default() -= quantity;
+                    if let Some(status) = self.brackets.get_mut(&entry_id) {
+                        status.open_quantity = (status.open_quantity - quantity).max(0.0);
+                    }
+                    touched.push(entry_id);
+                }
+            }
+        }
+        self.resize(touched)
+    }
+
+    /// Account for orders taken off the book without trading
+    ///
+    /// A cancelled entry fills no further; a cancelled exit is no longer
+    /// managed, and is not replaced.
+    pub fn on_cancelled(&mut self, orders: &[Order]) -> Vec<BracketAction> {
+        let mut touched = Vec::new();
+        for order in orders {
+            if let Some(status) = self.brackets.get_mut(&order.id) {
+                status.entry_quantity = status.entry_filled;
+                touched.push(order.id);
+            } else if let Some(entry_id) = self.exits.remove(&order.id) {
+                self.remaining.remove(&order.id);
+                if let Some(status) = self.brackets.get_mut(&entry_id) {
+                    for exit in [&mut status.take_profit_id, &mut status.stop_loss_id] {
+                        if *exit == Some(order.id) {
+                            *exit = None;
+                        }
+                    }
+                    // Without either exit there is nothing left to manage
+                    if status.take_profit_id.is_none() && status.stop_loss_id.is_none() {
+                        status.open_quantity = 0.0;
+                    }
+                }
+                touched.push(entry_id);
+            }
+        }
+        self.resize(touched)
+    }
+
+    /// Bring the exits of `entries` to their open quantity, dropping
+    /// brackets that are done
+    fn resize(&mut self, mut entries: Vec<OrderId>) -> Vec<BracketAction> {
+        let mut seen = HashSet::new();
+        entries.retain(|id| seen.insert(*id));
+        let mut actions = Vec::new();
+        for entry_id in entries {
+            let Some(status) = self.brackets.get_mut(&entry_id) else {
+                continue;
+            };
+            let open = status.open_quantity;
+            let exit_side = status.side.opposite();
+            let exits = [
+                (&mut status.take_profit_id, false),
+                (&mut status.stop_loss_id, true),
+            ];
+            for (exit, is_stop) in exits {
+                let left = exit.and_then(|id| self.remaining.get(&id).copied());
+                if left.is_some_and(|left| (left - open).abs() <= EPSILON) {
+                    continue;
+                }
+                if let Some(id) = exit.take() {
+                    self.exits.remove(&id);
+                    self.remaining.remove(&id);
+                    if left.is_some_and(|left| left > EPSILON) {
+                        actions.push(BracketAction::Cancel {
+                            symbol: status.symbol.clone(),
+                            order_id: id,
+                        });
+                    }
+                }
+                if open > EPSILON {
+                    let order = if is_stop {
+                        Order::new_stop_market(
+                            status.symbol.clone(),
+                            exit_side,
+                            status.bracket.stop_loss,
+                            open,
+                        )
+                    } else {
+                        Order::new_limit(
+                            status.symbol.clone(),
+                            exit_side,
+                            status.bracket.take_profit,
+                            open,
+                        )
+                    };
+                    *exit = Some(order.id);
+                    self.exits.insert(order.id, entry_id);
+                    self.remaining.insert(order.id, open);
+                    actions.push(BracketAction::Place(order));
+                }
+            }
+            if status.is_done() {
+                self.brackets.remove(&entry_id);
+            }
+        }
+        actions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testkit::{self, buy, sell};
+
+    #[test]
+    fn test_exits_follow_entry_fills_and_cancel_each_other() {
+        let books = testkit::book().ask(100.0, 0.4).manager();
+        let entry = buy(100.0, 1.0).build();
+        let bracket = Bracket {
+            take_profit: 110.0,
+            stop_loss: 95.0,
+        };
+        assert!(bracket.is_valid_for(OrderSide::Buy, Some(100.0)));
+        assert!(!bracket.is_valid_for(OrderSide::Sell, Some(100.0)));
+
+        // A partial entry fill is covered straight away
+        books.submit_bracket(entry.clone(), bracket);
+        let status = books.bracket(entry.id).unwrap();
+        assert_eq!((status.entry_filled, status.open_quantity), (0.4, 0.4));
+        let book = books.matching("BTCUSDT");
+        assert_eq!(book.volume_at(110.0), 0.4);
+        assert_eq!(book.stop_orders().len(), 1);
+
+        // The rest of the entry grows both exits
+        books.submit(sell(100.0, 0.6).build());
+        assert_eq!(book.volume_at(110.0), 1.0);
+        assert_eq!(book.stop_orders()[0].remaining_quantity, 1.0);
+
+        // Part of the take-profit trades, shrinking the stop-loss with it
+        books.submit(buy(110.0, 0.3).build());
+        assert_eq!(book.volume_at(110.0), 0.7);
+        assert_eq!(book.stop_orders()[0].remaining_quantity, 0.7);
+
+        // The stop-loss fires and closes what is left
+        books.submit(buy(94.0, 1.0).build());
+        books.submit(sell(94.0, 0.1).build());
+        assert_eq!(book.volume_at(110.0), 0.0);
+        assert!(book.stop_orders().is_empty());
+        assert!(books.bracket(entry.id).is_none());
+    }
+}
@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::orderbook::ExecutionReport;
+use crate::types::{Order, OrderId, OrderType, Symbol};
+
+/// Order a client order ID was first used for, and where it stands
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ClientOrder {
+    pub order_id: OrderId,
+    pub symbol: Symbol,
+    pub order_type: OrderType,
+    pub accepted_at: DateTime<Utc>,
+    /// Latest state of the order, once its matching pass is over
+    pub report: Option<ExecutionReport>,
+}
+
+/// Client order IDs in use, unique per client
+///
+/// Resubmitting an ID finds the order it was first used for instead of
+/// placing another, so a client can retry a submission it never heard back
+/// about without trading twice.
+#[derive(Debug, Default)]
+pub struct ClientOrders {
+    orders: HashMap<(String, String), ClientOrder>,
+    /// Client and client order ID of every order claimed with one
+    keys: HashMap<OrderId, (String, String)>,
+}
+
+impl ClientOrders {
+    pub fn get(&self, client_id: &str, client_order_id: &str) -> Option<&ClientOrder> {
+        self.orders
+            .get(&(client_id.to_string(), client_order_id.to_string()))
+    }
+
+    /// Take the client order ID of `order` for it, or return the order that
+    /// already has it
+    ///
+    /// Orders without a client or a client order ID always succeed.
+    pub fn claim(&mut self, order: &Order, at: DateTime<Utc>) -> Result<(), ClientOrder> {
+        let (Some(client_id), Some(client_order_id)) = (&order.client_id, &order.client_order_id)
+        else {
+            return Ok(());
+        };
+        let key = (client_id.clone(), client_order_id.clone());
+        if let Some(original) = self.orders.get(&key) {
+            return Err(original.clone());
+        }
+        self.keys.insert(order.id, key.clone());
+        self.orders.insert(
+            key,
+            ClientOrder {
+                order_id: order.id,
+                symbol: order.symbol.clone(),
+                order_type: order.order_type,
+                accepted_at: at,
+                report: None,
+            },
+        );
+        Ok(())
+    }
+
+    /// Keep the latest state of orders placed with a client order ID
+    pub fn record(&mut self, report: &ExecutionReport) {
+        if !matches!(report, ExecutionReport::OrderUpdate { .. }) {
+            return;
+        }
+        if let Some(order) = self
+            .keys
+            .get(&report.order_id())
+            .and_then(|key| self.orders.get_mut(key))
+        {
+            order.report = Some(report.clone());
+        }
+    }
+}
+
+/// Thread-safe wrapper for ClientOrders
+#[derive(Default)]
+pub struct SharedClientOrders {
+    inner: Arc<Mutex<ClientOrders>>,
+}
+
+impl SharedClientOrders {
+    pub fn get(&self, client_id: &str, client_order_id: &str) -> Option<ClientOrder> {
+        self.inner
+            .lock()
+            .unwrap()
+            .get(client_id, client_order_id)
+            .cloned()
+    }
+
+    pub fn claim(&self, order: &Order, at: DateTime<Utc>) -> Result<(), ClientOrder> {
+        self.inner.lock().unwrap().claim(order, at)
+    }
+
+    pub fn record(&self, report: &ExecutionReport) {
+        self.inner.lock().unwrap().record(report)
+    }
+}
+
+impl Clone for SharedClientOrders {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testkit::buy;
+    use crate::types::OrderStatus;
+
+    #[test]
+    fn test_client_order_ids_are_unique_per_client() {
+        let mut orders = ClientOrders::default();
+        let first = buy(100.0, 1.0)
+            .client("alice")
+            .client_order_id("a-1")
+            .build();
+        assert!(orders.claim(&first, Utc::now()).is_ok());
+
+        let retry = buy(100.0, 1.0)
+            .client("alice")
+            .client_order_id("a-1")
+            .build();
+        let original = orders.claim(&retry, Utc::now()).unwrap_err();
+        assert_eq!((original.order_id, original.report), (first.id, None));
+
+        // Another client can use the same ID
+        let other = buy(100.0, 1.0).client("bob").client_order_id("a-1").build();
+        assert!(orders.claim(&other, Utc::now()).is_ok());
+
+        orders.record(&ExecutionReport::cancelled(&first));
+        assert!(matches!(
+            orders.get("alice", "a-1").unwrap().report,
+            Some(ExecutionReport::OrderUpdate {
+                status: OrderStatus::Cancelled,
+                ..
+            })
+        ));
+    }
+}
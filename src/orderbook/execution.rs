@@ -0,0 +1,244 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::types::{Order, OrderId, OrderSide, OrderStatus, Symbol, Trade};
+use crate::utils::{Queryable, Timestamped, Value};
+
+/// Which side of a match an order was on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Liquidity {
+    Maker,
+    Taker,
+}
+
+/// Asynchronous report on an order after it was acknowledged
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum ExecutionReport {
+    Fill {
+        order_id: OrderId,
+        symbol: Symbol,
+        price: f64,
+        quantity: f64,
+        liquidity: Liquidity,
+        timestamp: DateTime<Utc>,
+    },
+    /// State of a submitted order once its matching pass is over
+    OrderUpdate {
+        order_id: OrderId,
+        symbol: Symbol,
+        side: OrderSide,
+        status: OrderStatus,
+        filled_quantity: f64,
+        remaining_quantity: f64,
+        timestamp: DateTime<Utc>,
+        /// Orders of the client queued at the throttle when this one was
+        /// accepted, itself included, if it had to wait
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        queue_depth: Option<u32>,
+    },
+}
+
+impl ExecutionReport {
+    pub fn order_id(&self) -> OrderId {
+        match self {
+            ExecutionReport::Fill { order_id, .. }
+            | ExecutionReport::OrderUpdate { order_id, .. } => *order_id,
+        }
+    }
+
+    pub fn timestamp(&self) -> DateTime<Utc> {
+        match self {
+            ExecutionReport::Fill { timestamp, .. }
+            | ExecutionReport::OrderUpdate { timestamp, .. } => *timestamp,
+        }
+    }
+
+    /// Final state of an order taken off the book
+    pub fn cancelled(order: &Order) -> Self {
+        Self::closed(order, OrderStatus::Cancelled)
+    }
+
+    /// Final state of a good-till-date order the expiry sweeper took off the book
+    pub fn expired(order: &Order) -> Self {
+        Self::closed(order, OrderStatus::Expired)
+    }
+
+    fn closed(order: &Order, status: OrderStatus) -> Self {
+        ExecutionReport::OrderUpdate {
+            order_id: order.id,
+            symbol: order.symbol.clone(),
+            side: order.side,
+            status,
+            filled_quantity: (order.initial_quantity - order.remaining_quantity).value(),
+            remaining_quantity: order.remaining_quantity.value(),
+            timestamp: Utc::now(),
+            queue_depth: None,
+        }
+    }
+
+    /// The report, noting that its order waited behind `depth` queued
+    /// orders of its client
+    pub fn with_queue_depth(mut self, depth: u32) -> Self {
+        if let ExecutionReport::OrderUpdate { queue_depth, .. } = &mut self {
+            *queue_depth = Some(depth);
+        }
+        self
+    }
+
+    /// Fills for both sides of every trade, maker first
+    ///
+    /// Fills against mirrored exchange liquidity have no local maker and only
+    /// produce the taker's fill.
+    pub fn fills(trades: &[Trade]) -> Vec<Self> {
+        let mut reports = Vec::with_capacity(trades.len() * 2 + 1);
+        for trade in trades {
+            let fill = |order_id, liquidity| ExecutionReport::Fill {
+                order_id,
+                symbol: trade.symbol.clone(),
+                price: trade.price.value(),
+                quantity: trade.quantity.value(),
+                liquidity,
+                timestamp: trade.timestamp,
+            };
+            if trade.maker_order_id != OrderId::EXCHANGE {
+                reports.push(fill(trade.maker_order_id, Liquidity::Maker));
+            }
+            if trade.taker_order_id != OrderId::EXCHANGE {
+                reports.push(fill(trade.taker_order_id, Liquidity::Taker));
+            }
+        }
+        reports
+    }
+
+    /// Fills for both sides of every trade, then the submitted order's state
+    ///
+    /// A market, IOC or FOK order that did not fill in full ends up cancelled.
+    pub fn for_submission(order: &Order, trades: &[Trade]) -> Vec<Self> {
+        let mut reports = Self::fills(trades);
+        let filled: f64 = trades
+            .iter()
+            .filter(|trade| trade.taker_order_id == order.id)
+            .map(|trade| trade.quantity.value())
+            .sum();
+
+        let remaining = (order.initial_quantity.value() - filled).max(0.0);
+        let status = if remaining <= 0.0 {
+            OrderStatus::Filled
+        } else if order.is_immediate() {
+            // The unfilled remainder of a market, IOC or FOK order never rests
+            OrderStatus::Cancelled
+        } else if filled > 0.0 {
+            OrderStatus::PartiallyFilled
+        } else {
+            OrderStatus::Pending
+        };
+        reports.push(ExecutionReport::OrderUpdate {
+            order_id: order.id,
+            symbol: order.symbol.clone(),
+            side: order.side,
+            status,
+            filled_quantity: filled,
+            remaining_quantity: remaining,
+            timestamp: Utc::now(),
+            queue_depth: None,
+        });
+        reports
+    }
+}
+
+impl Timestamped for ExecutionReport {
+    fn timestamp_ms(&self) -> i64 {
+        self.timestamp().timestamp_millis()
+    }
+}
+
+impl Queryable for ExecutionReport {
+    const FIELDS: &'static [&'static str] = &[
+        "type",
+        "order_id",
+        "symbol",
+        "time",
+        "price",
+        "quantity",
+        "liquidity",
+        "side",
+        "status",
+        "filled",
+        "remaining",
+    ];
+
+    fn field(&self, name: &str) -> Option<Value> {
+        let value = match (self, name) {
+            (_, "order_id") => Value::Number(self.order_id().0 as f64),
+            (_, "time") => Value::Time(self.timestamp()),
+            (ExecutionReport::Fill { .. }, "type") => Value::Text("fill".to_string()),
+            (ExecutionReport::OrderUpdate { .. }, "type") => {
+                Value::Text("order_update".to_string())
+            }
+            (ExecutionReport::Fill { symbol, .. }, "symbol")
+            | (ExecutionReport::OrderUpdate { symbol, .. }, "symbol") => {
+                Value::Text(symbol.to_string())
+            }
+            (ExecutionReport::Fill { price, .. }, "price") => Value::Number(*price),
+            (ExecutionReport::Fill { quantity, .. }, "quantity") => Value::Number(*quantity),
+            (ExecutionReport::Fill { liquidity, .. }, "liquidity") => {
+                Value::Text(format!("{:?}", liquidity))
+            }
+            (ExecutionReport::OrderUpdate { side, .. }, "side") => {
+                Value::Text(format!("{:?}", side))
+            }
+            (ExecutionReport::OrderUpdate { status, .. }, "status") => {
+                Value::Text(format!("{:?}", status))
+            }
+            (
+                ExecutionReport::OrderUpdate {
+                    filled_quantity, ..
+                },
+                "filled",
+            ) => Value::Number(*filled_quantity),
+            (
+                ExecutionReport::OrderUpdate {
+                    remaining_quantity, ..
+                },
+                "remaining",
+            ) => Value::Number(*remaining_quantity),
+            _ => return None,
+        };
+        Some(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orderbook::BookManager;
+
+    #[test]
+    fn test_reports_cover_both_sides_and_final_state() {
+        let books = BookManager::new();
+        let maker = Order::new_limit("BTCUSDT", OrderSide::Sell, 100.0, 1.0);
+        books.submit(maker.clone());
+
+        let taker = Order::new_limit("BTCUSDT", OrderSide::Buy, 100.0, 3.0);
+        let trades = books.submit(taker.clone());
+        let reports = ExecutionReport::for_submission(&taker, &trades);
+
+        assert_eq!(reports.len(), 3);
+        assert!(matches!(
+            reports[0],
+            ExecutionReport::Fill { order_id, liquidity: Liquidity::Maker, .. } if order_id == maker.id
+        ));
+        assert_eq!(reports[1].order_id(), taker.id);
+        assert!(matches!(
+            reports[2],
+            ExecutionReport::OrderUpdate {
+                status: OrderStatus::PartiallyFilled,
+                filled_quantity: 1.0,
+                remaining_quantity: 2.0,
+                ..
+            }
+        ));
+    }
+}
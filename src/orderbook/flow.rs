@@ -0,0 +1,174 @@
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::orderbook::ExecutionReport;
+use crate::types::{Order, OrderId, OrderSide, OrderStatus, OrderType, Symbol, Trade};
+
+/// One step of the order flow, as drawn by book animations
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum FlowEvent {
+    Arrival {
+        order_id: OrderId,
+        symbol: Symbol,
+        side: OrderSide,
+        /// None for market orders
+        price: Option<f64>,
+        quantity: f64,
+        timestamp: DateTime<Utc>,
+    },
+    Cancel {
+        order_id: OrderId,
+        symbol: Symbol,
+        side: OrderSide,
+        remaining_quantity: f64,
+        timestamp: DateTime<Utc>,
+    },
+    Trade {
+        symbol: Symbol,
+        price: f64,
+        quantity: f64,
+        /// Side of the taker, when it was an order arriving with the trade
+        #[serde(skip_serializing_if = "Option::is_none")]
+        aggressor: Option<OrderSide>,
+        timestamp: DateTime<Utc>,
+    },
+}
+
+impl FlowEvent {
+    pub fn arrival(order: &Order) -> Self {
+        let priced = !matches!(order.order_type, OrderType::Market | OrderType::StopMarket);
+        FlowEvent::Arrival {
+            order_id: order.id,
+            symbol: order.symbol.clone(),
+            side: order.side,
+            price: priced.then(|| order.price.value()),
+            quantity: order.initial_quantity.value(),
+            timestamp: order.timestamp,
+        }
+    }
+
+    /// Arrival of each of `orders`, then each of `trades` with its taker's
+    /// side when the taker is one of them
+    pub fn submission(orders: &[Order], trades: &[Trade]) -> Vec<Self> {
+        let mut events: Vec<Self> = orders.iter().map(Self::arrival).collect();
+        events.extend(trades.iter().map(|trade| {
+            FlowEvent::Trade {
+                symbol: trade.symbol.clone(),
+                price: trade.price.value(),
+                quantity: trade.quantity.value(),
+                aggressor: orders
+                    .iter()
+                    .find(|order| order.id == trade.taker_order_id)
+                    .map(|order| order.side),
+                timestamp: trade.timestamp,
+            }
+        }));
+        events
+    }
+
+    /// The cancel an execution report stands for, if it is one; expiries
+    /// are drawn as cancels
+    pub fn from_report(report: &ExecutionReport) -> Option<Self> {
+        match report {
+            ExecutionReport::OrderUpdate {
+                order_id,
+                symbol,
+                side,
+                status: OrderStatus::Cancelled | OrderStatus::Expired,
+                remaining_quantity,
+                timestamp,
+                ..
+            } => Some(FlowEvent::Cancel {
+                order_id: *order_id,
+                symbol: symbol.clone(),
+                side: *side,
+                remaining_quantity: *remaining_quantity,
+                timestamp: *timestamp,
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn symbol(&self) -> &Symbol {
+        match self {
+            FlowEvent::Arrival { symbol, .. }
+            | FlowEvent::Cancel { symbol, .. }
+            | FlowEvent::Trade { symbol, .. } => symbol,
+        }
+    }
+}
+
+/// Caps a feed at a number of events per second, counting what it drops
+#[derive(Debug)]
+pub struct FlowThrottle {
+    max_per_sec: u32,
+    window_start: Option<Instant>,
+    sent: u32,
+    dropped: u64,
+}
+
+impl FlowThrottle {
+    pub fn new(max_per_sec: u32) -> Self {
+        Self {
+            max_per_sec,
+            window_start: None,
+            sent: 0,
+            dropped: 0,
+        }
+    }
+
+    /// Whether an event arriving at `now` fits in this second's budget
+    pub fn admit(&mut self, now: Instant) -> bool {
+        let fresh = self
+            .window_start
+            .is_none_or(|start| now.duration_since(start) >= Duration::from_secs(1));
+        if fresh {
+            self.window_start = Some(now);
+            self.sent = 0;
+        }
+        if self.sent < self.max_per_sec {
+            self.sent += 1;
+            true
+        } else {
+            self.dropped += 1;
+            false
+        }
+    }
+
+    /// Events dropped since the last call
+    pub fn take_dropped(&mut self) -> u64 {
+        std::mem::take(&mut self.dropped)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_throttle_caps_each_second_and_counts_drops() {
+        let mut throttle = FlowThrottle::new(2);
+        let start = Instant::now();
+        let admitted: Vec<bool> = (0..4).map(|_| throttle.admit(start)).collect();
+        assert_eq!(admitted, [true, true, false, false]);
+        assert!(!throttle.admit(start + Duration::from_millis(999)));
+
+        assert!(throttle.admit(start + Duration::from_secs(1)));
+        assert_eq!(throttle.take_dropped(), 3);
+        assert_eq!(throttle.take_dropped(), 0);
+
+        let order = Order::new_limit("BTCUSDT", OrderSide::Buy, 100.0, 1.0);
+        let trade = Trade::new(OrderId(1), order.id, order.symbol.clone(), 100.0, 0.4);
+        let events = FlowEvent::submission(std::slice::from_ref(&order), &[trade]);
+        assert!(matches!(
+            events[1],
+            FlowEvent::Trade {
+                aggressor: Some(OrderSide::Buy),
+                ..
+            }
+        ));
+    }
+}
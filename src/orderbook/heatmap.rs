@@ -0,0 +1,390 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, BufRead, BufReader};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use crate::memory::MemoryUsage;
+use crate::orderbook::book::BookKind;
+use crate::orderbook::manager::BookManager;
+use crate::types::Symbol;
+use crate::utils::{BoundedHistory, Retention, Timestamped};
+
+/// Levels per side kept in each snapshot
+const SNAPSHOT_LEVELS: usize = 50;
+
+/// Resting liquidity of one book at one moment
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DepthSnapshot {
+    pub symbol: Symbol,
+    pub timestamp_ms: i64,
+    pub bids: Vec<(f64, f64)>,
+    pub asks: Vec<(f64, f64)>,
+}
+
+impl DepthSnapshot {
+    fn mid(&self) -> Option<f64> {
+        let bid = self.bids.first()?.0;
+        let ask = self.asks.first()?.0;
+        Some((bid + ask) / 2.0)
+    }
+}
+
+impl Timestamped for DepthSnapshot {
+    fn timestamp_ms(&self) -> i64 {
+        self.timestamp_ms
+    }
+}
+
+/// Shape of a heatmap query
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct HeatmapQuery {
+    pub from_ms: i64,
+    pub to_ms: i64,
+    /// Width of each time column
+    pub bucket_ms: i64,
+    /// Height of each price row; derived from the observed range when absent
+    pub price_step: Option<f64>,
+}
+
+/// Average resting quantity per price row and time column
+///
+/// `liquidity[t][p]` is the quantity between `prices[p]` and
+/// `prices[p] + price_step` during the column starting at `times[t]`, summed
+/// over both sides and averaged over the column's snapshots.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Heatmap {
+    pub symbol: Symbol,
+    pub price_step: f64,
+    pub prices: Vec<f64>,
+    pub times: Vec<i64>,
+    pub liquidity: Vec<Vec<f64>>,
+    /// Average mid of each column, for overlaying the price path
+    pub mids: Vec<Option<f64>>,
+}
+
+/// Rows used when the query leaves the price step to the recorder
+const DEFAULT_ROWS: f64 = 50.0;
+/// Cap on either dimension so one query cannot exhaust memory
+const MAX_CELLS: usize = 1_000;
+
+impl Heatmap {
+    pub fn build(symbol: Symbol, snapshots: &[DepthSnapshot], query: &HeatmapQuery) -> Self {
+        let bucket_ms = query
+            .bucket_ms
+            .max(1)
+            .max((query.to_ms - query.from_ms) / MAX_CELLS as i64 + 1);
+        let columns = ((query.to_ms - query.from_ms).max(0) / bucket_ms + 1) as usize;
+        let snapshots: Vec<&DepthSnapshot> = snapshots
+            .iter()
+            .filter(|s| s.timestamp_ms >= query.from_ms && s.timestamp_ms <= query.to_ms)
+            .collect();
+
+        let prices = snapshots
+            .iter()
+            .flat_map(|snapshot| snapshot.bids.iter().chain(&snapshot.asks))
+            .map(|(price, _)| *price);
+        let (low, high) = prices.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), p| {
+            (lo.min(p), hi.max(p))
+        });
+        if !low.is_finite() {
+            return Self {
+                symbol,
+                price_step: query.price_step.unwrap_or(0.0),
+                prices: Vec::new(),
+                times: Vec::new(),
+                liquidity: Vec::new(),
+                mids: Vec::new(),
+            };
+        }
+        let minimum_step = (high - low) / MAX_CELLS as f64;
+        let price_step = query
+            .price_step
+            .filter(|step| *step > 0.0)
+            .unwrap_or((high - low) / DEFAULT_ROWS)
+            .max(minimum_step)
+            .max(f64::EPSILON);
+        let base = (low / price_step).floor() * price_step;
+        let rows = ((high - base) / price_step).floor() as usize + 1;
+
+        let mut liquidity = vec![vec![0.0; rows]; columns];
+        let mut counts = vec![0usize; columns];
+        let mut mids = vec![(0.0, 0usize); columns];
+        for snapshot in snapshots {
+            let column = ((snapshot.timestamp_ms - query.from_ms) / bucket_ms) as usize;
+            counts[column] += 1;
+            for (price, quantity) in snapshot.bids.iter().chain(&snapshot.asks) {
+                let row = (((price - base) / price_step).floor() as usize).min(rows - 1);
+                liquidity[column][row] += quantity;
+            }
+            if let Some(mid) = snapshot.mid() {
+                mids[column].0 += mid;
+                mids[column].1 += 1;
+            }
+        }
+        for (cells, count) in liquidity.iter_mut().zip(&counts) {
+            if *count > 0 {
+                cells.iter_mut().for_each(|cell| *cell /= *count as f64);
+            }
+        }
+
+        Self {
+            symbol,
+            price_step,
+            prices: (0..rows)
+                .map(|row| base + row as f64 * price_step)
+                .collect(),
+            times: (0..columns)
+                .map(|column| query.from_ms + column as i64 * bucket_ms)
+                .collect(),
+            liquidity,
+            mids: mids
+                .into_iter()
+                .map(|(sum, count)| (count > 0).then(|| sum / count as f64))
+                .collect(),
+        }
+    }
+}
+
+/// Periodic depth snapshots of the mirror books, per symbol
+///
+/// Recent snapshots stay in memory under `retention`; with a spill dir the
+/// evicted ones are appended to `depth-<SYMBOL>.jsonl` and read back when a
+/// query reaches further than memory does.
+#[derive(Debug)]
+pub struct DepthRecorder {
+    retention: Retention,
+    histories: HashMap<Symbol, BoundedHistory<DepthSnapshot>>,
+}
+
+impl DepthRecorder {
+    pub fn new(retention: Retention) -> Self {
+        Self {
+            retention,
+            histories: HashMap::new(),
+        }
+    }
+
+    pub fn record(&mut self, snapshot: DepthSnapshot) {
+        let retention = &self.retention;
+        let history = self
+            .histories
+            .entry(snapshot.symbol.clone())
+            .or_insert_with(|| {
+                BoundedHistory::new(&spill_name(snapshot.symbol.as_str()), retention.clone())
+            });
+        history.push(snapshot);
+        // Evicted snapshots must be readable as soon as they leave memory
+        if let Err(e) = history.flush() {
+            tracing::warn!("Failed to flush spilled depth: {}", e);
+        }
+    }
+
+    /// Snapshot every mirror book in `books`
+    pub fn capture(&mut self, books: &BookManager, timestamp_ms: i64) {
+        for symbol in books.symbols(BookKind::Mirror) {
+            let Some(book) = books.get(&symbol, BookKind::Mirror) else {
+                continue;
+            };
+            let (bids, asks) = book.get_depth(SNAPSHOT_LEVELS);
+            self.record(DepthSnapshot {
+                symbol,
+                timestamp_ms,
+                bids,
+                asks,
+            });
+        }
+    }
+
+    /// Snapshots of `symbol` between `from_ms` and `to_ms`, oldest first
+    pub fn snapshots(&self, symbol: &str, from_ms: i64, to_ms: i64) -> Vec<DepthSnapshot> {
+        let in_range = |snapshot: &DepthSnapshot| {
+            snapshot.timestamp_ms >= from_ms && snapshot.timestamp_ms <= to_ms
+        };
+        let history = self.histories.get(symbol);
+        let oldest_in_memory = history
+            .and_then(|history| history.iter().next())
+            .map(|snapshot| snapshot.timestamp_ms);
+
+        let mut snapshots = Vec::new();
+        if oldest_in_memory.is_none_or(|oldest| from_ms < oldest) {
+            match self.spilled(symbol) {
+                Ok(spilled) => snapshots.extend(spilled.into_iter().filter(in_range)),
+                Err(e) => tracing::warn!("Failed to read spilled depth of {}: {}", symbol, e),
+            }
+        }
+        if let Some(history) = history {
+            snapshots.extend(history.iter().filter(|s| in_range(s)).cloned());
+        }
+        snapshots
+    }
+
+    pub fn heatmap(&self, symbol: &str, query: &HeatmapQuery) -> Heatmap {
+        let snapshots = self.snapshots(symbol, query.from_ms, query.to_ms);
+        Heatmap::build(Symbol::new(symbol), &snapshots, query)
+    }
+
+    fn spilled(&self, symbol: &str) -> io::Result<Vec<DepthSnapshot>> {
+        let Some(dir) = &self.retention.spill_dir else {
+            return Ok(Vec::new());
+        };
+        let path: PathBuf = dir.join(format!("{}.jsonl", spill_name(symbol)));
+        let file = match fs::File::open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        // The writer may still hold a partial last line; skip what doesn't parse
+        Ok(BufReader::new(file)
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|line| serde_json::from_str(&line).ok())
+            .collect())
+    }
+}
+
+fn spill_name(symbol: &str) -> String {
+    format!("depth-{}", symbol)
+}
+
+impl MemoryUsage for DepthRecorder {
+    fn heap_bytes(&self) -> usize {
+        self.histories
+            .values()
+            .map(|history| {
+                let levels: usize = history
+                    .iter()
+                    .map(|snapshot| snapshot.bids.capacity() + snapshot.asks.capacity())
+                    .sum();
+                history.heap_bytes() + levels * std::mem::size_of::<(f64, f64)>()
+            })
+            .sum()
+    }
+}
+
+impl Default for DepthRecorder {
+    /// A day of one-second snapshots in memory
+    fn default() -> Self {
+        Self::new(
+            Retention::count(86_400).with_max_age(std::time::Duration::from_secs(24 * 60 * 60)),
+        )
+    }
+}
+
+/// Thread-safe wrapper for DepthRecorder
+pub struct SharedDepthRecorder {
+    inner: Arc<Mutex<DepthRecorder>>,
+}
+
+impl SharedDepthRecorder {
+    pub fn new(recorder: DepthRecorder) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(recorder)),
+        }
+    }
+
+    pub fn record(&self, snapshot: DepthSnapshot) {
+        self.inner.lock().unwrap().record(snapshot)
+    }
+
+    pub fn capture(&self, books: &BookManager, timestamp_ms: i64) {
+        self.inner.lock().unwrap().capture(books, timestamp_ms)
+    }
+
+    pub fn snapshots(&self, symbol: &str, from_ms: i64, to_ms: i64) -> Vec<DepthSnapshot> {
+        self.inner.lock().unwrap().snapshots(symbol, from_ms, to_ms)
+    }
+
+    pub fn heatmap(&self, symbol: &str, query: &HeatmapQuery) -> Heatmap {
+        self.inner.lock().unwrap().heatmap(symbol, query)
+    }
+
+    /// Snapshot the mirror books of `books` every `interval`
+    #[cfg(feature = "net")]
+    pub fn start(&self, books: BookManager, interval: std::time::Duration) {
+        let recorder = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            loop {
+                interval.tick().await;
+                recorder.capture(&books, chrono::Utc::now().timestamp_millis());
+            }
+        });
+    }
+}
+
+impl MemoryUsage for SharedDepthRecorder {
+    fn heap_bytes(&self) -> usize {
+        self.inner.lock().unwrap().heap_bytes()
+    }
+}
+
+impl Clone for SharedDepthRecorder {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl Default for SharedDepthRecorder {
+    fn default() -> Self {
+        Self::new(DepthRecorder::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(timestamp_ms: i64, bid: (f64, f64), ask: (f64, f64)) -> DepthSnapshot {
+        DepthSnapshot {
+            symbol: Symbol::new("BTCUSDT"),
+            timestamp_ms,
+            bids: vec![bid],
+            asks: vec![ask],
+        }
+    }
+
+    #[test]
+    fn test_heatmap_averages_columns_into_price_rows() {
+        let snapshots = [
+            snapshot(0, (99.0, 2.0), (101.0, 1.0)),
+            snapshot(500, (99.5, 4.0), (101.0, 3.0)),
+            snapshot(1_000, (100.0, 1.0), (102.0, 5.0)),
+        ];
+        let query = HeatmapQuery {
+            from_ms: 0,
+            to_ms: 1_999,
+            bucket_ms: 1_000,
+            price_step: Some(1.0),
+        };
+        let heatmap = Heatmap::build(Symbol::new("BTCUSDT"), &snapshots, &query);
+
+        assert_eq!(heatmap.prices, [99.0, 100.0, 101.0, 102.0]);
+        assert_eq!(heatmap.times, [0, 1_000]);
+        assert_eq!(heatmap.liquidity[0], [3.0, 0.0, 2.0, 0.0]);
+        assert_eq!(heatmap.liquidity[1], [0.0, 1.0, 0.0, 5.0]);
+        assert_eq!(heatmap.mids, [Some(100.125), Some(101.0)]);
+    }
+
+    #[test]
+    fn test_queries_reach_into_spilled_snapshots() {
+        let dir = std::env::temp_dir().join(format!("depth-{}", std::process::id()));
+        let mut recorder = DepthRecorder::new(Retention::count(2).with_spill_dir(&dir));
+        for ts in [0, 1_000, 2_000, 3_000] {
+            recorder.record(snapshot(ts, (99.0, 1.0), (101.0, 1.0)));
+        }
+
+        let times: Vec<i64> = recorder
+            .snapshots("BTCUSDT", 500, 3_000)
+            .iter()
+            .map(|snapshot| snapshot.timestamp_ms)
+            .collect();
+        assert_eq!(times, [1_000, 2_000, 3_000]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,101 @@
+use std::sync::{Arc, Mutex};
+
+use crate::orderbook::ExecutionReport;
+use crate::utils::{BoundedHistory, Filter, Retention};
+
+/// Execution reports published for local orders, newest last
+///
+/// With a spill dir in the retention, reports evicted from memory go to
+/// `<dir>/orders.jsonl` and queries still find them.
+#[derive(Debug)]
+pub struct OrderHistory {
+    reports: BoundedHistory<ExecutionReport>,
+}
+
+impl OrderHistory {
+    pub fn new(retention: Retention) -> Self {
+        Self {
+            reports: BoundedHistory::new("orders", retention),
+        }
+    }
+
+    pub fn record(&mut self, report: ExecutionReport) {
+        self.reports.push(report);
+    }
+
+    /// Up to `limit` most recent reports that `filter` keeps, oldest first
+    pub fn query(&mut self, filter: &Filter, limit: usize) -> Vec<ExecutionReport> {
+        let mut reports = self.reports.scan(|report| filter.matches(report));
+        reports.drain(..reports.len().saturating_sub(limit));
+        reports
+    }
+}
+
+impl Default for OrderHistory {
+    fn default() -> Self {
+        Self::new(Retention::count(10_000))
+    }
+}
+
+/// Thread-safe wrapper for OrderHistory
+#[derive(Default)]
+pub struct SharedOrderHistory {
+    inner: Arc<Mutex<OrderHistory>>,
+}
+
+impl SharedOrderHistory {
+    pub fn new(history: OrderHistory) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(history)),
+        }
+    }
+
+    pub fn record(&self, report: ExecutionReport) {
+        self.inner.lock().unwrap().record(report)
+    }
+
+    pub fn query(&self, filter: &Filter, limit: usize) -> Vec<ExecutionReport> {
+        self.inner.lock().unwrap().query(filter, limit)
+    }
+}
+
+impl Clone for SharedOrderHistory {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testkit::buy;
+
+    #[test]
+    fn test_history_query_filters_and_keeps_the_latest() {
+        let mut history = OrderHistory::default();
+        let orders: Vec<_> = [100.0, 101.0, 102.0]
+            .into_iter()
+            .map(|price| buy(price, 1.0).build())
+            .collect();
+        for order in &orders {
+            history.record(ExecutionReport::cancelled(order));
+        }
+
+        let filter = Filter::compile::<ExecutionReport>(&format!(
+            "status = cancelled AND order_id != {}",
+            orders[1].id.0
+        ))
+        .unwrap();
+        let ids = |reports: Vec<ExecutionReport>| -> Vec<_> {
+            reports.iter().map(ExecutionReport::order_id).collect()
+        };
+        assert_eq!(
+            ids(history.query(&filter, 10)),
+            [orders[0].id, orders[2].id]
+        );
+        assert_eq!(ids(history.query(&Filter::all(), 1)), [orders[2].id]);
+        assert!(Filter::compile::<ExecutionReport>("venue = binance").is_err());
+    }
+}
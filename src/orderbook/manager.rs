@@ -0,0 +1,471 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+
+use crate::market::SharedTradeTape;
+use crate::memory::MemoryUsage;
+use crate::orderbook::book::{
+    AuctionResult, BookKind, CancelFilter, Depth, PostOnlyMode, SharedOrderBook,
+};
+use crate::orderbook::bracket::{Bracket, BracketAction, BracketStatus, Brackets};
+use crate::orderbook::simulate::Simulation;
+use crate::overload::SharedLoadShedder;
+use crate::throughput::SharedThroughputMeter;
+use crate::types::money::Symbol;
+use crate::types::order::{Order, OrderId, Trade};
+
+/// Books by symbol and kind
+///
+/// Client orders are only ever routed to matching books; exchange L2 data
+/// only ever lands in mirror books, so simulated orders cannot fill against
+/// passive exchange liquidity by accident. Crossing with the market is an
+/// explicit opt-in for paper trading and backtests.
+pub struct BookManager {
+    books: Arc<Mutex<HashMap<(Symbol, BookKind), SharedOrderBook>>>,
+    /// Entries whose exits are placed as they fill
+    brackets: Arc<Mutex<Brackets>>,
+    tape: Option<SharedTradeTape>,
+    shedder: Option<SharedLoadShedder>,
+    throughput: Option<SharedThroughputMeter>,
+    cross_with_market: bool,
+}
+
+impl BookManager {
+    pub fn new() -> Self {
+        Self {
+            books: Arc::new(Mutex::new(HashMap::new())),
+            brackets: Arc::new(Mutex::new(Brackets::default())),
+            tape: None,
+            shedder: None,
+            throughput: None,
+            cross_with_market: false,
+        }
+    }
+
+    /// Record matches of every matching book on `tape`
+    pub fn with_trade_tape(mut self, tape: SharedTradeTape) -> Self {
+        self.tape = Some(tape);
+        self
+    }
+
+    /// Report matching latency of every matching book to `shedder`
+    pub fn with_load_shedder(mut self, shedder: SharedLoadShedder) -> Self {
+        self.shedder = Some(shedder);
+        self
+    }
+
+    /// Count orders, fills and matching latency of every matching book on `throughput`
+    pub fn with_throughput(mut self, throughput: SharedThroughputMeter) -> Self {
+        self.throughput = Some(throughput);
+        self
+    }
+
+    /// Let submitted orders fill against the mirror book before resting locally
+    ///
+    /// Mirror liquidity is only consumed locally, giving more realistic paper
+    /// fills than trading at the last price.
+    pub fn with_cross_with_market(mut self, enabled: bool) -> Self {
+        self.cross_with_market = enabled;
+        self
+    }
+
+    pub fn cross_with_market(&self) -> bool {
+        self.cross_with_market
+    }
+
+    /// Matching book for `symbol`, created on first use
+    pub fn matching(&self, symbol: impl Into<Symbol>) -> SharedOrderBook {
+        self.book(symbol.into(), BookKind::Matching)
+    }
+
+    /// Mirror book for `symbol`, created on first use
+    pub fn mirror(&self, symbol: impl Into<Symbol>) -> SharedOrderBook {
+        self.book(symbol.into(), BookKind::Mirror)
+    }
+
+    pub fn get(&self, symbol: &Symbol, kind: BookKind) -> Option<SharedOrderBook> {
+        self.books
+            .lock()
+            .unwrap()
+            .get(&(symbol.clone(), kind))
+            .cloned()
+    }
+
+    /// Mid of the mirror book, or of the matching book when there is none
+    pub fn mark_price(&self, symbol: &Symbol) -> Option<f64> {
+        [BookKind::Mirror, BookKind::Matching]
+            .into_iter()
+            .find_map(|kind| self.get(symbol, kind)?.mid_price())
+    }
+
+    /// Top `levels` of the mirror book, or of the matching book when there
+    /// is none
+    pub fn depth(&self, symbol: &Symbol, levels: usize) -> Option<Depth> {
+        [BookKind::Mirror, BookKind::Matching]
+            .into_iter()
+            .find_map(|kind| self.get(symbol, kind))
+            .map(|book| book.get_depth(levels))
+    }
+
+    /// Symbols with a book of `kind`, sorted
+    pub fn symbols(&self, kind: BookKind) -> Vec<Symbol> {
+        let mut symbols: Vec<Symbol> = self
+            .books
+            .lock()
+            .unwrap()
+            .keys()
+            .filter(|(_, k)| *k == kind)
+            .map(|(symbol, _)| symbol.clone())
+            .collect();
+        symbols.sort();
+        symbols
+    }
+
+    /// Route a client order to the matching book of its symbol
+    ///
+    /// With cross-with-market enabled the order first takes mirrored
+    /// exchange liquidity and only the remainder reaches the matching book.
+    /// Exits of brackets filled along the way are placed or resized, and
+    /// their trades returned too.
+    pub fn submit(&self, order: Order) -> Vec<Trade> {
+        let mut trades = self.route(order);
+        self.settle_brackets(&mut trades);
+        trades
+    }
+
+    /// Submit `entry`, placing a take-profit and a stop-loss for whatever
+    /// of it fills, now or later
+    ///
+    /// The exits cover the filled quantity not exited yet, and a fill of
+    /// one shrinks the other.
+    pub fn submit_bracket(&self, entry: Order, bracket: Bracket) -> Vec<Trade> {
+        self.brackets.lock().unwrap().register(&entry, bracket);
+        self.submit(entry)
+    }
+
+    /// Bracket of `entry_id`, until its entry and exits are done
+    pub fn bracket(&self, entry_id: OrderId) -> Option<BracketStatus> {
+        self.brackets.lock().unwrap().get(entry_id).cloned()
+    }
+
+    fn route(&self, order: Order) -> Vec<Trade> {
+        let mut order = order;
+        let mut trades = Vec::new();
+
+        if self.cross_with_market {
+            if let Some(mirror) = self.get(&order.symbol, BookKind::Mirror) {
+                trades = mirror.cross(&mut order);
+                if let Some(tape) = &self.tape {
+                    for trade in &trades {
+                        tape.record_local(trade);
+                    }
+                }
+            }
+        }
+
+        if !order.is_filled() {
+            trades.extend(self.matching(order.symbol.clone()).add_order(order));
+        }
+        trades
+    }
+
+    /// Feed `trades` to the brackets until placing exits trades no further
+    fn settle_brackets(&self, trades: &mut Vec<Trade>) {
+        let mut settled = 0;
+        while settled < trades.len() {
+            let actions = {
+                let mut brackets = self.brackets.lock().unwrap();
+                if brackets.is_empty() {
+                    return;
+                }
+                brackets.on_trades(&trades[settled..])
+            };
+            settled = trades.len();
+            self.apply_bracket_actions(actions, trades);
+        }
+    }
+
+    /// Tell the brackets about orders removed without trading
+    fn settle_cancelled(&self, cancelled: &[Order]) {
+        let actions = self.brackets.lock().unwrap().on_cancelled(cancelled);
+        let mut trades = Vec::new();
+        self.apply_bracket_actions(actions, &mut trades);
+        self.settle_brackets(&mut trades);
+    }
+
+    fn apply_bracket_actions(&self, actions: Vec<BracketAction>, trades: &mut Vec<Trade>) {
+        for action in actions {
+            match action {
+                BracketAction::Cancel { symbol, order_id } => {
+                    if let Some(book) = self.get(&symbol, BookKind::Matching) {
+                        book.cancel_order(order_id);
+                    }
+                }
+                BracketAction::Place(order) => trades.extend(self.route(order)),
+            }
+        }
+    }
+
+    /// Replace `strategy`'s quote set on the matching book of `symbol`
+    ///
+    /// Quotes go straight to the matching book; they never cross with the
+    /// market.
+    pub fn replace_quotes(
+        &self,
+        symbol: &Symbol,
+        strategy: &str,
+        quotes: Vec<Order>,
+    ) -> (Vec<Order>, Vec<Trade>) {
+        let (replaced, mut trades) = self
+            .matching(symbol.clone())
+            .replace_quotes(strategy, quotes);
+        self.settle_brackets(&mut trades);
+        (replaced, trades)
+    }
+
+    /// How the matching book of `symbol` treats post-only orders that would cross
+    pub fn set_post_only_mode(&self, symbol: impl Into<Symbol>, mode: PostOnlyMode) {
+        self.matching(symbol).set_post_only_mode(mode)
+    }
+
+    /// Cancel matching orders on the matching book of `symbol`, or of every
+    /// symbol when none is given
+    pub fn mass_cancel(&self, symbol: Option<&Symbol>, filter: &CancelFilter) -> Vec<Order> {
+        let symbols = match symbol {
+            Some(symbol) => vec![symbol.clone()],
+            None => self.symbols(BookKind::Matching),
+        };
+        let cancelled: Vec<Order> = symbols
+            .iter()
+            .filter_map(|symbol| self.get(symbol, BookKind::Matching))
+            .flat_map(|book| book.mass_cancel(filter))
+            .collect();
+        self.settle_cancelled(&cancelled);
+        cancelled
+    }
+
+    /// Cancel every order placed for `client_id`, on `symbol` or on every
+    /// matching book
+    ///
+    /// All the books involved are held until every order is off, so no
+    /// order of the client can trade on one book while another is being
+    /// cleared.
+    pub fn cancel_all(&self, client_id: &str, symbol: Option<&Symbol>) -> Vec<Order> {
+        let books: Vec<SharedOrderBook> = match symbol {
+            Some(symbol) => vec![symbol.clone()],
+            None => self.symbols(BookKind::Matching),
+        }
+        .iter()
+        .filter_map(|symbol| self.get(symbol, BookKind::Matching))
+        .collect();
+        // Locked in symbol order, like any other caller holding several
+        let mut guards: Vec<_> = books.iter().map(|book| book.lock()).collect();
+        let filter = CancelFilter::client(client_id);
+        let cancelled: Vec<Order> = guards
+            .iter_mut()
+            .flat_map(|book| book.mass_cancel(&filter))
+            .collect();
+        drop(guards);
+        self.settle_cancelled(&cancelled);
+        cancelled
+    }
+
+    /// Open an auction call phase on the matching book of `symbol`
+    pub fn start_auction(&self, symbol: impl Into<Symbol>) {
+        self.matching(symbol).start_auction();
+    }
+
+    /// Uncross the auction on `symbol`, placing and resizing bracket exits
+    /// for what traded; None without a matching book
+    pub fn uncross(&self, symbol: &Symbol) -> Option<AuctionResult> {
+        let mut result = self.get(symbol, BookKind::Matching)?.uncross();
+        self.settle_cancelled(&result.cancelled);
+        self.settle_brackets(&mut result.trades);
+        Some(result)
+    }
+
+    /// Fill orders on the matching book of `symbol` that an exchange trade at
+    /// `price` went through, see [`SharedOrderBook::fill_through`]
+    ///
+    /// Only with cross-with-market enabled; otherwise local orders never
+    /// fill against the exchange.
+    pub fn fill_through(&self, symbol: &Symbol, price: f64) -> Vec<Trade> {
+        if !self.cross_with_market {
+            return Vec::new();
+        }
+        let Some(book) = self.get(symbol, BookKind::Matching) else {
+            return Vec::new();
+        };
+        let mut trades = book.fill_through(price);
+        self.settle_brackets(&mut trades);
+        trades
+    }
+
+    /// Cancel good-till-date orders that expired by `now`, on `symbol` or on
+    /// every matching book
+    pub fn expire_orders(&self, symbol: Option<&Symbol>, now: DateTime<Utc>) -> Vec<Order> {
+        let expired: Vec<Order> = match symbol {
+            Some(symbol) => vec![symbol.clone()],
+            None => self.symbols(BookKind::Matching),
+        }
+        .iter()
+        .filter_map(|symbol| self.get(symbol, BookKind::Matching))
+        .flat_map(|book| book.expire_orders(now))
+        .collect();
+        self.settle_cancelled(&expired);
+        expired
+    }
+
+    /// Sweep expired good-till-date orders off the books every `interval`,
+    /// handing each non-empty batch to `on_expired`
+    #[cfg(feature = "net")]
+    pub fn start_expiry_sweeper(
+        &self,
+        interval: std::time::Duration,
+        on_expired: impl Fn(Vec<Order>) + Send + 'static,
+    ) {
+        let books = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            loop {
+                interval.tick().await;
+                let expired = books.expire_orders(None, Utc::now());
+                if !expired.is_empty() {
+                    tracing::debug!("Expired {} good-till-date orders", expired.len());
+                    on_expired(expired);
+                }
+            }
+        });
+    }
+
+    /// What [`submit`](Self::submit) would do with `order`, without doing it
+    pub fn simulate(&self, order: &Order, fee_bps: f64) -> Simulation {
+        let mut books = Vec::new();
+        if self.cross_with_market {
+            if let Some(mirror) = self.get(&order.symbol, BookKind::Mirror) {
+                books.push((BookKind::Mirror, mirror));
+            }
+        }
+        if let Some(matching) = self.get(&order.symbol, BookKind::Matching) {
+            books.push((BookKind::Matching, matching));
+        }
+        Simulation::walk(order, &books, fee_bps)
+    }
+
+    fn book(&self, symbol: Symbol, kind: BookKind) -> SharedOrderBook {
+        self.books
+            .lock()
+            .unwrap()
+            .entry((symbol.clone(), kind))
+            .or_insert_with(|| match kind {
+                BookKind::Matching => {
+                    let mut book = SharedOrderBook::new(symbol);
+                    if let Some(tape) = &self.tape {
+                        book = book.with_trade_tape(tape.clone());
+                    }
+                    if let Some(shedder) = &self.shedder {
+                        book = book.with_load_shedder(shedder.clone());
+                    }
+                    if let Some(throughput) = &self.throughput {
+                        book = book.with_throughput(throughput.clone());
+                    }
+                    book
+                }
+                BookKind::Mirror => SharedOrderBook::mirror(symbol),
+            })
+            .clone()
+    }
+}
+
+impl MemoryUsage for BookManager {
+    fn heap_bytes(&self) -> usize {
+        // Collect first so no book is locked while the map is
+        let books: Vec<SharedOrderBook> = self.books.lock().unwrap().values().cloned().collect();
+        books.iter().map(MemoryUsage::heap_bytes).sum()
+    }
+}
+
+impl Clone for BookManager {
+    fn clone(&self) -> Self {
+        Self {
+            books: Arc::clone(&self.books),
+            brackets: Arc::clone(&self.brackets),
+            tape: self.tape.clone(),
+            shedder: self.shedder.clone(),
+            throughput: self.throughput.clone(),
+            cross_with_market: self.cross_with_market,
+        }
+    }
+}
+
+impl Default for BookManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testkit::{self, buy, sell};
+    use crate::types::OrderId;
+
+    #[test]
+    fn test_orders_never_reach_mirror_books() {
+        let books = BookManager::new();
+        let snapshot = testkit::book().ask(101.0, 2.0).snapshot();
+        books.mirror("btcusdt").apply_snapshot(&snapshot);
+
+        assert!(books.submit(buy(101.0, 1.0).build()).is_empty());
+
+        let symbol = Symbol::new("BTCUSDT");
+        let matching = books.get(&symbol, BookKind::Matching).unwrap();
+        assert_eq!(matching.best_bid(), Some(101.0));
+        assert_eq!(books.mirror("BTCUSDT").volume_at(101.0), 2.0);
+        assert_eq!(books.symbols(BookKind::Mirror), vec![symbol]);
+    }
+
+    #[test]
+    fn test_cross_with_market_is_opt_in() {
+        let books = BookManager::new().with_cross_with_market(true);
+        let snapshot = testkit::book().ask(101.0, 0.4).snapshot();
+        books.mirror("BTCUSDT").apply_snapshot(&snapshot);
+
+        let trades = books.submit(buy(101.0, 1.0).build());
+        assert_eq!(trades.len(), 1);
+        assert_eq!(trades[0].maker_order_id, OrderId::EXCHANGE);
+        assert_eq!(trades[0].quantity, 0.4);
+
+        // Mirror liquidity is used up locally, the remainder rests
+        assert_eq!(books.mirror("BTCUSDT").best_ask(), None);
+        assert_eq!(books.matching("BTCUSDT").volume_at(101.0), 0.6);
+
+        // ...until the market trades through it
+        let btc = Symbol::new("BTCUSDT");
+        assert!(books.fill_through(&btc, 101.0).is_empty());
+        let trades = books.fill_through(&btc, 100.5);
+        assert_eq!(trades[0].quantity, 0.6);
+        assert_eq!(books.matching("BTCUSDT").best_bid(), None);
+
+        let isolated = BookManager::new();
+        isolated.submit(buy(101.0, 1.0).build());
+        assert!(isolated.fill_through(&btc, 100.0).is_empty());
+    }
+
+    #[test]
+    fn test_cancel_all_clears_one_clients_orders() {
+        let books = BookManager::new();
+        books.submit(buy(99.0, 1.0).client("alice").build());
+        books.submit(sell(101.0, 1.0).client("alice").build());
+        books.submit(buy(98.0, 1.0).client("bob").build());
+        books.submit(sell(2_001.0, 1.0).symbol("ETHUSDT").client("alice").build());
+
+        let btc = Symbol::new("BTCUSDT");
+        assert_eq!(books.cancel_all("alice", Some(&btc)).len(), 2);
+        assert_eq!(books.matching("ETHUSDT").best_ask(), Some(2_001.0));
+        assert_eq!(books.cancel_all("alice", None).len(), 1);
+        assert_eq!(books.matching("BTCUSDT").best_bid(), Some(98.0));
+    }
+}
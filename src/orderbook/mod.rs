@@ -1,3 +1,36 @@
+pub mod audit;
 pub mod book;
+pub mod bracket;
+pub mod client_orders;
+pub mod execution;
+pub mod flow;
+pub mod heatmap;
+pub mod history;
+pub mod manager;
+pub mod owners;
+pub mod protection;
+pub mod quotes;
+pub mod shards;
+pub mod simulate;
+pub mod throttle;
 
-pub use book::{OrderBook, PriceLevel, SharedOrderBook};
+pub use audit::{Actor, AuditEntry, OrderAudit, OrderState, SharedOrderAudit, Transition};
+pub use book::{
+    AuctionQuote, AuctionResult, BookKind, BookPhase, BookUpdate, CancelFilter, Depth, OrderBook,
+    PostOnlyMode, PriceLevel, SharedOrderBook,
+};
+pub use bracket::{Bracket, BracketStatus};
+pub use client_orders::{ClientOrder, ClientOrders, SharedClientOrders};
+pub use execution::{ExecutionReport, Liquidity};
+pub use flow::{FlowEvent, FlowThrottle};
+pub use heatmap::{DepthRecorder, DepthSnapshot, Heatmap, HeatmapQuery, SharedDepthRecorder};
+pub use history::{OrderHistory, SharedOrderHistory};
+pub use manager::BookManager;
+pub use owners::{OrderOwners, OwnedOrder, SharedOrderOwners};
+pub use protection::{
+    ProtectionConfig, ProtectionMonitor, ProtectiveCancel, SharedProtectionMonitor, Threat,
+};
+pub use quotes::{QuoteStats, QuoteTracker, SharedQuoteTracker};
+pub use shards::MatchingShards;
+pub use simulate::{SimulatedFill, Simulation};
+pub use throttle::{Admission, OrderThrottle, SharedOrderThrottle, ThrottleConfig};
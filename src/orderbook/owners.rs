@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::account::AccountId;
+use crate::orderbook::ExecutionReport;
+use crate::types::{Order, OrderId, OrderSide, OrderStatus, Symbol};
+
+/// Open order placed for an account
+#[derive(Debug, Clone, PartialEq)]
+pub struct OwnedOrder {
+    pub account: AccountId,
+    pub symbol: Symbol,
+    pub side: OrderSide,
+    pub reduce_only: bool,
+    /// Quantity left to fill
+    pub remaining: f64,
+    /// Fills reported so far
+    pub fills: u64,
+}
+
+/// Account behind each open order, so fills on either side of a trade can
+/// be charged and booked to it, and what its open reduce-only orders may
+/// still close
+///
+/// Orders are forgotten once filled or closed.
+#[derive(Debug, Default)]
+pub struct OrderOwners {
+    orders: HashMap<OrderId, OwnedOrder>,
+}
+
+impl OrderOwners {
+    pub fn insert(&mut self, order: &Order, account: AccountId) {
+        self.orders.insert(
+            order.id,
+            OwnedOrder {
+                account,
+                symbol: order.symbol.clone(),
+                side: order.side,
+                reduce_only: order.reduce_only,
+                remaining: order.remaining_quantity.value(),
+                fills: 0,
+            },
+        );
+    }
+
+    pub fn get(&self, order_id: OrderId) -> Option<&OwnedOrder> {
+        self.orders.get(&order_id)
+    }
+
+    /// Quantity `account`'s open reduce-only orders on `symbol` and `side`
+    /// may still trade
+    pub fn open_reduce_only(&self, account: &AccountId, symbol: &Symbol, side: OrderSide) -> f64 {
+        self.orders
+            .values()
+            .filter(|order| {
+                order.reduce_only
+                    && order.side == side
+                    && &order.account == account
+                    && &order.symbol == symbol
+            })
+            .map(|order| order.remaining)
+            .sum()
+    }
+
+    /// Follow an order through its reports, dropping it once it is done
+    pub fn record(&mut self, report: &ExecutionReport) {
+        let order_id = report.order_id();
+        let done = match report {
+            ExecutionReport::Fill { quantity, .. } => {
+                let Some(order) = self.orders.get_mut(&order_id) else {
+                    return;
+                };
+                order.remaining -= quantity;
+                order.fills += 1;
+                order.remaining <= 0.0
+            }
+            ExecutionReport::OrderUpdate { status, .. } => {
+                !matches!(status, OrderStatus::Pending | OrderStatus::PartiallyFilled)
+            }
+        };
+        if done {
+            self.orders.remove(&order_id);
+        }
+    }
+}
+
+/// Thread-safe wrapper for OrderOwners
+#[derive(Default)]
+pub struct SharedOrderOwners {
+    inner: Arc<Mutex<OrderOwners>>,
+}
+
+impl SharedOrderOwners {
+    pub fn insert(&self, order: &Order, account: AccountId) {
+        self.inner.lock().unwrap().insert(order, account)
+    }
+
+    pub fn get(&self, order_id: OrderId) -> Option<OwnedOrder> {
+        self.inner.lock().unwrap().get(order_id).cloned()
+    }
+
+    pub fn open_reduce_only(&self, account: &AccountId, symbol: &Symbol, side: OrderSide) -> f64 {
+        self.inner
+            .lock()
+            .unwrap()
+            .open_reduce_only(account, symbol, side)
+    }
+
+    pub fn record(&self, report: &ExecutionReport) {
+        self.inner.lock().unwrap().record(report)
+    }
+}
+
+impl Clone for SharedOrderOwners {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orderbook::Liquidity;
+    use crate::testkit::{buy, sell};
+    use chrono::Utc;
+
+    fn fill(order: &Order, quantity: f64) -> ExecutionReport {
+        ExecutionReport::Fill {
+            order_id: order.id,
+            symbol: order.symbol.clone(),
+            price: order.price.value(),
+            quantity,
+            liquidity: Liquidity::Maker,
+            timestamp: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_orders_are_forgotten_once_done() {
+        let mut owners = OrderOwners::default();
+        let alice = AccountId::from("alice");
+        let resting = sell(100.0, 2.0).build().with_reduce_only(true);
+        let cancelled = buy(99.0, 1.0).build();
+        owners.insert(&resting, alice.clone());
+        owners.insert(&cancelled, AccountId::from("bob"));
+
+        owners.record(&fill(&resting, 1.5));
+        assert_eq!(owners.get(resting.id).unwrap().remaining, 0.5);
+        let symbol = &resting.symbol;
+        assert_eq!(
+            owners.open_reduce_only(&alice, symbol, OrderSide::Sell),
+            0.5
+        );
+        assert_eq!(owners.open_reduce_only(&alice, symbol, OrderSide::Buy), 0.0);
+        owners.record(&fill(&resting, 0.5));
+        assert!(owners.get(resting.id).is_none());
+
+        owners.record(&ExecutionReport::cancelled(&cancelled));
+        assert!(owners.get(cancelled.id).is_none());
+    }
+}
@@ -0,0 +1,320 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::orderbook::manager::BookManager;
+use crate::types::{Order, OrderId, OrderSide, Symbol};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ProtectionConfig {
+    /// Mirror levels per side used for the imbalance
+    pub depth_levels: usize,
+    /// `|bid - ask| / (bid + ask)` volume imbalance that threatens the light side
+    pub imbalance_threshold: f64,
+    /// Touch move in basis points between checks that counts as a sweep
+    pub sweep_bps: f64,
+    /// Longest time between checks; the monitor runs at least this often
+    pub reaction_time: Duration,
+}
+
+impl Default for ProtectionConfig {
+    fn default() -> Self {
+        Self {
+            depth_levels: 5,
+            imbalance_threshold: 0.8,
+            sweep_bps: 10.0,
+            reaction_time: Duration::from_millis(50),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum Threat {
+    /// Opposite side outweighs ours by this imbalance
+    Imbalance { imbalance: f64 },
+    /// Our side's touch moved away by this many basis points
+    Sweep { moved_bps: f64 },
+}
+
+/// An order cancelled to avoid adverse selection
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProtectiveCancel {
+    pub strategy: String,
+    pub symbol: Symbol,
+    pub order_id: OrderId,
+    pub side: OrderSide,
+    pub threat: Threat,
+}
+
+#[derive(Debug, Clone)]
+struct TrackedOrder {
+    strategy: String,
+    symbol: Symbol,
+    side: OrderSide,
+}
+
+/// Cancels resting orders when the mirror book turns against them
+///
+/// Buys are threatened when asks dominate the mirror or its best bid drops
+/// sharply; sells mirror that. Only orders of strategies that opted in are
+/// touched, and they are cancelled on the local matching book.
+pub struct ProtectionMonitor {
+    books: BookManager,
+    config: ProtectionConfig,
+    opted_in: HashSet<String>,
+    orders: HashMap<OrderId, TrackedOrder>,
+    /// Mirror touch at the previous check
+    touches: HashMap<Symbol, (Option<f64>, Option<f64>)>,
+}
+
+impl ProtectionMonitor {
+    pub fn new(books: BookManager, config: ProtectionConfig) -> Self {
+        Self {
+            books,
+            config,
+            opted_in: HashSet::new(),
+            orders: HashMap::new(),
+            touches: HashMap::new(),
+        }
+    }
+
+    pub fn opt_in(&mut self, strategy: &str) {
+        self.opted_in.insert(strategy.to_string());
+    }
+
+    pub fn opt_out(&mut self, strategy: &str) {
+        self.opted_in.remove(strategy);
+    }
+
+    /// Watch a resting order placed by `strategy`
+    pub fn track(&mut self, strategy: &str, order: &Order) {
+        self.orders.insert(
+            order.id,
+            TrackedOrder {
+                strategy: strategy.to_string(),
+                symbol: order.symbol.clone(),
+                side: order.side,
+            },
+        );
+    }
+
+    pub fn untrack(&mut self, order_id: OrderId) {
+        self.orders.remove(&order_id);
+    }
+
+    /// Threatened sides of `symbol`, updating the remembered touch
+    pub fn threats(&mut self, symbol: &Symbol) -> Vec<(OrderSide, Threat)> {
+        let mirror = self.books.mirror(symbol.clone());
+        let mut threats = Vec::new();
+
+        let (bids, asks) = mirror.get_depth(self.config.depth_levels);
+        let bid_volume: f64 = bids.iter().map(|&(_, qty)| qty).sum();
+        let ask_volume: f64 = asks.iter().map(|&(_, qty)| qty).sum();
+        if bid_volume + ask_volume > 0.0 {
+            let imbalance = (bid_volume - ask_volume) / (bid_volume + ask_volume);
+            if imbalance <= -self.config.imbalance_threshold {
+                threats.push((OrderSide::Buy, Threat::Imbalance { imbalance }));
+            } else if imbalance >= self.config.imbalance_threshold {
+                threats.push((OrderSide::Sell, Threat::Imbalance { imbalance }));
+            }
+        }
+
+        let touch = (mirror.best_bid(), mirror.best_ask());
+        if let Some((previous_bid, previous_ask)) = self.touches.insert(symbol.clone(), touch) {
+            let moved_bps = |from: f64, to: f64| (to - from) / from * 10_000.0;
+            if let (Some(from), Some(to)) = (previous_bid, touch.0) {
+                let moved = moved_bps(from, to);
+                if -moved >= self.config.sweep_bps {
+                    threats.push((OrderSide::Buy, Threat::Sweep { moved_bps: moved }));
+                }
+            }
+            if let (Some(from), Some(to)) = (previous_ask, touch.1) {
+                let moved = moved_bps(from, to);
+                if moved >= self.config.sweep_bps {
+                    threats.push((OrderSide::Sell, Threat::Sweep { moved_bps: moved }));
+                }
+            }
+        }
+        threats
+    }
+
+    /// Check every symbol with tracked orders and cancel the threatened ones
+    pub fn check(&mut self) -> Vec<ProtectiveCancel> {
+        let symbols: HashSet<Symbol> = self
+            .orders
+            .values()
+            .filter(|order| self.opted_in.contains(&order.strategy))
+            .map(|order| order.symbol.clone())
+            .collect();
+
+        let mut cancels = Vec::new();
+        for symbol in symbols {
+            for (side, threat) in self.threats(&symbol) {
+                let matching = self.books.matching(symbol.clone());
+                let threatened: Vec<OrderId> = self
+                    .orders
+                    .iter()
+                    .filter(|(_, o)| {
+                        o.symbol == symbol && o.side == side && self.opted_in.contains(&o.strategy)
+                    })
+                    .map(|(id, _)| *id)
+                    .collect();
+
+                for order_id in threatened {
+                    let Some(tracked) = self.orders.remove(&order_id) else {
+                        continue;
+                    };
+                    // Already filled or cancelled orders just stop being tracked
+                    if matching.cancel_order(order_id).is_some() {
+                        tracing::info!(
+                            "Protective cancel of {:?} {:?} on {} for {}: {:?}",
+                            order_id,
+                            side,
+                            symbol,
+                            tracked.strategy,
+                            threat
+                        );
+                        cancels.push(ProtectiveCancel {
+                            strategy: tracked.strategy,
+                            symbol: symbol.clone(),
+                            order_id,
+                            side,
+                            threat,
+                        });
+                    }
+                }
+            }
+        }
+        cancels
+    }
+}
+
+/// Thread-safe wrapper for ProtectionMonitor
+pub struct SharedProtectionMonitor {
+    inner: Arc<Mutex<ProtectionMonitor>>,
+}
+
+impl SharedProtectionMonitor {
+    pub fn new(monitor: ProtectionMonitor) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(monitor)),
+        }
+    }
+
+    pub fn opt_in(&self, strategy: &str) {
+        self.inner.lock().unwrap().opt_in(strategy)
+    }
+
+    pub fn opt_out(&self, strategy: &str) {
+        self.inner.lock().unwrap().opt_out(strategy)
+    }
+
+    pub fn track(&self, strategy: &str, order: &Order) {
+        self.inner.lock().unwrap().track(strategy, order)
+    }
+
+    pub fn untrack(&self, order_id: OrderId) {
+        self.inner.lock().unwrap().untrack(order_id)
+    }
+
+    pub fn check(&self) -> Vec<ProtectiveCancel> {
+        self.inner.lock().unwrap().check()
+    }
+
+    /// Run [`check`](Self::check) every `reaction_time`
+    #[cfg(feature = "net")]
+    pub fn start(&self) {
+        let monitor = self.clone();
+        let reaction_time = self.inner.lock().unwrap().config.reaction_time;
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(reaction_time);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            loop {
+                interval.tick().await;
+                monitor.check();
+            }
+        });
+    }
+}
+
+impl Clone for SharedProtectionMonitor {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orderbook::book::BookUpdate;
+    use crate::types::{Price, Qty};
+
+    fn levels(levels: &[(f64, f64)]) -> Vec<(Price, Qty)> {
+        levels
+            .iter()
+            .map(|&(p, q)| (Price::new(p), Qty::new(q)))
+            .collect()
+    }
+
+    #[test]
+    fn test_imbalance_cancels_opted_in_bids_only() {
+        let books = BookManager::new();
+        books.mirror("BTCUSDT").apply_snapshot(&BookUpdate {
+            bids: levels(&[(99.0, 1.0)]),
+            asks: levels(&[(101.0, 20.0)]),
+        });
+        let mut monitor = ProtectionMonitor::new(books.clone(), ProtectionConfig::default());
+        monitor.opt_in("maker");
+
+        let ours = Order::new_limit("BTCUSDT", OrderSide::Buy, 98.0, 1.0);
+        let other = Order::new_limit("BTCUSDT", OrderSide::Buy, 97.0, 1.0);
+        let ask = Order::new_limit("BTCUSDT", OrderSide::Sell, 105.0, 1.0);
+        for (strategy, order) in [("maker", &ours), ("manual", &other), ("maker", &ask)] {
+            monitor.track(strategy, order);
+            books.matching("BTCUSDT").add_order(order.clone());
+        }
+
+        let cancels = monitor.check();
+        assert_eq!(cancels.len(), 1);
+        assert_eq!(
+            (cancels[0].order_id, cancels[0].side),
+            (ours.id, OrderSide::Buy)
+        );
+        assert!(matches!(cancels[0].threat, Threat::Imbalance { .. }));
+        assert_eq!(books.matching("BTCUSDT").order_count(), 2);
+    }
+
+    #[test]
+    fn test_sweep_of_asks_cancels_resting_sells() {
+        let books = BookManager::new();
+        let mirror = books.mirror("ETHUSDT");
+        mirror.apply_snapshot(&BookUpdate {
+            bids: levels(&[(1_999.0, 5.0)]),
+            asks: levels(&[(2_001.0, 5.0)]),
+        });
+        let monitor = SharedProtectionMonitor::new(ProtectionMonitor::new(
+            books.clone(),
+            ProtectionConfig::default(),
+        ));
+        monitor.opt_in("maker");
+        let sell = Order::new_limit("ETHUSDT", OrderSide::Sell, 2_010.0, 1.0);
+        monitor.track("maker", &sell);
+        books.matching("ETHUSDT").add_order(sell.clone());
+        assert!(monitor.check().is_empty());
+
+        // Asks lifted through 2_001 up to 2_010: 45bps
+        mirror.apply_diff(&BookUpdate {
+            bids: levels(&[(2_005.0, 5.0)]),
+            asks: levels(&[(2_001.0, 0.0), (2_010.0, 5.0)]),
+        });
+        let cancels = monitor.check();
+        assert_eq!(cancels.len(), 1);
+        assert_eq!(cancels[0].order_id, sell.id);
+        assert!(matches!(cancels[0].threat, Threat::Sweep { moved_bps } if moved_bps > 40.0));
+    }
+}
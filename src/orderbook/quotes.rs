@@ -0,0 +1,169 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::types::{Order, Symbol};
+
+/// Refresh and expiry counters of one strategy's quotes on one symbol
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct QuoteStats {
+    /// Quote sets sent, each replacing the previous one
+    pub refreshes: u64,
+    pub quotes_placed: u64,
+    /// Quotes pulled by a refresh
+    pub quotes_replaced: u64,
+    /// Quotes cancelled because their TTL ran out before a refresh
+    pub quotes_expired: u64,
+    /// Sweeps that found quotes of this set stale, i.e. times the strategy stalled
+    pub expiries: u64,
+    /// TTL of the last quote set, if it had one
+    pub ttl_ms: Option<u64>,
+    pub last_refresh: Option<DateTime<Utc>>,
+    pub last_expiry: Option<DateTime<Utc>>,
+}
+
+/// Quote activity by strategy, then symbol
+#[derive(Debug, Default)]
+pub struct QuoteTracker {
+    stats: BTreeMap<String, BTreeMap<Symbol, QuoteStats>>,
+}
+
+impl QuoteTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count a quote set replacing `replaced` earlier quotes with `placed` new ones
+    pub fn record_refresh(
+        &mut self,
+        strategy: &str,
+        symbol: &Symbol,
+        placed: usize,
+        replaced: usize,
+        ttl_ms: Option<u64>,
+        at: DateTime<Utc>,
+    ) {
+        let stats = self.entry(strategy, symbol);
+        stats.refreshes += 1;
+        stats.quotes_placed += placed as u64;
+        stats.quotes_replaced += replaced as u64;
+        stats.ttl_ms = ttl_ms;
+        stats.last_refresh = Some(at);
+    }
+
+    /// Count expired orders that were quotes, i.e. carry a strategy
+    pub fn record_expired(&mut self, orders: &[Order], at: DateTime<Utc>) {
+        let mut stale: BTreeMap<(&str, &Symbol), u64> = BTreeMap::new();
+        for order in orders {
+            if let Some(strategy) = &order.strategy {
+                *stale.entry((strategy, &order.symbol)).or_default() += 1;
+            }
+        }
+        for ((strategy, symbol), expired) in stale {
+            let stats = self.entry(strategy, symbol);
+            stats.quotes_expired += expired;
+            stats.expiries += 1;
+            stats.last_expiry = Some(at);
+        }
+    }
+
+    pub fn stats(&self) -> &BTreeMap<String, BTreeMap<Symbol, QuoteStats>> {
+        &self.stats
+    }
+
+    fn entry(&mut self, strategy: &str, symbol: &Symbol) -> &mut QuoteStats {
+        self.stats
+            .entry(strategy.to_string())
+            .or_default()
+            .entry(symbol.clone())
+            .or_default()
+    }
+}
+
+/// Thread-safe wrapper for QuoteTracker
+#[derive(Default)]
+pub struct SharedQuoteTracker {
+    inner: Arc<Mutex<QuoteTracker>>,
+}
+
+impl SharedQuoteTracker {
+    pub fn new(tracker: QuoteTracker) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(tracker)),
+        }
+    }
+
+    pub fn record_refresh(
+        &self,
+        strategy: &str,
+        symbol: &Symbol,
+        placed: usize,
+        replaced: usize,
+        ttl_ms: Option<u64>,
+        at: DateTime<Utc>,
+    ) {
+        self.inner
+            .lock()
+            .unwrap()
+            .record_refresh(strategy, symbol, placed, replaced, ttl_ms, at)
+    }
+
+    pub fn record_expired(&self, orders: &[Order], at: DateTime<Utc>) {
+        self.inner.lock().unwrap().record_expired(orders, at)
+    }
+
+    pub fn stats(&self) -> BTreeMap<String, BTreeMap<Symbol, QuoteStats>> {
+        self.inner.lock().unwrap().stats().clone()
+    }
+}
+
+impl Clone for SharedQuoteTracker {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orderbook::OrderBook;
+    use crate::testkit::{buy, sell};
+
+    #[test]
+    fn test_unrefreshed_quotes_expire_and_are_counted() {
+        let symbol = Symbol::from("BTCUSDT");
+        let mut book = OrderBook::new(symbol.clone());
+        let mut tracker = QuoteTracker::new();
+        let now = Utc::now();
+        let ttl = chrono::Duration::milliseconds(500);
+        let quotes = |at: DateTime<Utc>| {
+            vec![
+                buy(99.0, 1.0).good_till(at + ttl).build(),
+                sell(101.0, 1.0).good_till(at + ttl).build(),
+            ]
+        };
+
+        book.replace_quotes("mm", quotes(now));
+        tracker.record_refresh("mm", &symbol, 2, 0, Some(500), now);
+        // A refresh before the TTL pushes the expiry out
+        let refreshed = now + chrono::Duration::milliseconds(400);
+        let (replaced, _) = book.replace_quotes("mm", quotes(refreshed));
+        tracker.record_refresh("mm", &symbol, 2, replaced.len(), Some(500), refreshed);
+        assert!(book.expire_orders(now + ttl).is_empty());
+
+        // Then the strategy stalls
+        let stalled = refreshed + ttl;
+        let expired = book.expire_orders(stalled);
+        tracker.record_expired(&expired, stalled);
+        assert_eq!(book.order_count(), 0);
+
+        let stats = &tracker.stats()["mm"][&symbol];
+        assert_eq!((stats.refreshes, stats.quotes_replaced), (2, 2));
+        assert_eq!((stats.quotes_expired, stats.expiries), (2, 1));
+        assert_eq!(stats.last_expiry, Some(stalled));
+    }
+}
@@ -0,0 +1,301 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::types::Symbol;
+
+/// How often an idle worker looks at the other shards for work to steal
+const STEAL_INTERVAL: Duration = Duration::from_millis(2);
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// Queued jobs of the symbols one shard owns
+#[derive(Default)]
+struct Queue {
+    /// Jobs not started yet per symbol, in dispatch order
+    pending: HashMap<Symbol, VecDeque<Job>>,
+    /// Symbols with pending jobs and no worker on them
+    ready: VecDeque<Symbol>,
+    /// Symbols a worker is running a job of
+    running: HashSet<Symbol>,
+}
+
+impl Queue {
+    /// Start the next job of the oldest ready symbol, or of the newest when
+    /// stealing
+    fn start(&mut self, newest: bool) -> Option<(Symbol, Job)> {
+        let symbol = if newest {
+            self.ready.pop_back()
+        } else {
+            self.ready.pop_front()
+        }?;
+        let job = self.pending.get_mut(&symbol)?.pop_front()?;
+        self.running.insert(symbol.clone());
+        Some((symbol, job))
+    }
+
+    /// Mark a job of `symbol` done; true if the symbol has more waiting
+    fn finish(&mut self, symbol: Symbol) -> bool {
+        self.running.remove(&symbol);
+        if self
+            .pending
+            .get(&symbol)
+            .is_some_and(|jobs| !jobs.is_empty())
+        {
+            // Behind the symbols already waiting, so a busy one cannot
+            // hold up the rest of its shard
+            self.ready.push_back(symbol);
+            true
+        } else {
+            self.pending.remove(&symbol);
+            false
+        }
+    }
+}
+
+struct Shard {
+    queue: Mutex<Queue>,
+    wake: Condvar,
+}
+
+struct Pool {
+    shards: Vec<Shard>,
+    steals: AtomicU64,
+    closed: AtomicBool,
+}
+
+impl Pool {
+    fn shard_of(&self, symbol: &Symbol) -> usize {
+        let mut hasher = DefaultHasher::new();
+        symbol.hash(&mut hasher);
+        (hasher.finish() % self.shards.len() as u64) as usize
+    }
+
+    fn work(&self, home: usize) {
+        loop {
+            let own = self.shards[home].queue.lock().unwrap().start(false);
+            let started = own
+                .map(|(symbol, job)| (home, symbol, job))
+                .or_else(|| self.steal(home));
+            let Some((owner, symbol, job)) = started else {
+                let shard = &self.shards[home];
+                let queue = shard.queue.lock().unwrap();
+                if self.closed.load(Ordering::Acquire) && queue.pending.is_empty() {
+                    return;
+                }
+                if queue.ready.is_empty() {
+                    // Dispatches to this shard wake it early
+                    drop(shard.wake.wait_timeout(queue, STEAL_INTERVAL).unwrap());
+                }
+                continue;
+            };
+
+            // A failed job must not take the worker, or other symbols, down
+            if panic::catch_unwind(AssertUnwindSafe(job)).is_err() {
+                tracing::error!("Matching job for {} panicked", symbol);
+            }
+
+            let shard = &self.shards[owner];
+            if shard.queue.lock().unwrap().finish(symbol) {
+                shard.wake.notify_one();
+            }
+        }
+    }
+
+    /// Start the newest ready symbol of the shard with the most waiting,
+    /// skipping shards whose lock is taken
+    fn steal(&self, thief: usize) -> Option<(usize, Symbol, Job)> {
+        let busiest = (0..self.shards.len())
+            .filter(|&shard| shard != thief)
+            .map(|shard| {
+                let waiting = self.shards[shard]
+                    .queue
+                    .try_lock()
+                    .map_or(0, |queue| queue.ready.len());
+                (waiting, shard)
+            })
+            .filter(|&(waiting, _)| waiting > 0)
+            .max()?
+            .1;
+        let (symbol, job) = self.shards[busiest].queue.lock().unwrap().start(true)?;
+        self.steals.fetch_add(1, Ordering::Relaxed);
+        Some((busiest, symbol, job))
+    }
+}
+
+/// Stops the workers once the last handle is gone
+struct Shutdown(Arc<Pool>);
+
+impl Drop for Shutdown {
+    fn drop(&mut self) {
+        self.0.closed.store(true, Ordering::Release);
+        for shard in &self.0.shards {
+            let _queue = shard.queue.lock().unwrap();
+            shard.wake.notify_all();
+        }
+    }
+}
+
+/// Matching workers, one thread per shard of symbols
+///
+/// Every symbol belongs to one shard and runs the jobs sent for it one at a
+/// time in the order they were dispatched, so orders on a symbol match in
+/// arrival order while other symbols match in parallel. A worker takes
+/// turns between the symbols of its shard, one job each, and when it has
+/// none waiting steals from the shard with the most, so one busy symbol
+/// never holds up the symbols sharing its shard. Each shard queues its jobs
+/// under a lock of its own, so dispatching never waits on other shards;
+/// idle workers look for work to steal every few milliseconds. Workers stop
+/// once every handle is dropped and the queued jobs are done.
+pub struct MatchingShards {
+    pool: Arc<Pool>,
+    _shutdown: Arc<Shutdown>,
+}
+
+impl MatchingShards {
+    /// Start `shards` workers, at least one
+    pub fn new(shards: usize) -> Self {
+        let shards = shards.max(1);
+        let pool = Arc::new(Pool {
+            shards: (0..shards)
+                .map(|_| Shard {
+                    queue: Mutex::default(),
+                    wake: Condvar::new(),
+                })
+                .collect(),
+            steals: AtomicU64::new(0),
+            closed: AtomicBool::new(false),
+        });
+        for shard in 0..shards {
+            let pool = Arc::clone(&pool);
+            thread::Builder::new()
+                .name(format!("matching-{}", shard))
+                .spawn(move || pool.work(shard))
+                .expect("failed to spawn matching worker");
+        }
+        Self {
+            _shutdown: Arc::new(Shutdown(Arc::clone(&pool))),
+            pool,
+        }
+    }
+
+    /// One shard per available core
+    pub fn per_core() -> Self {
+        Self::new(thread::available_parallelism().map_or(1, |n| n.get()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.pool.shards.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Shard the jobs of `symbol` are assigned to
+    pub fn shard_of(&self, symbol: &Symbol) -> usize {
+        self.pool.shard_of(symbol)
+    }
+
+    /// Symbols a worker took from another shard so far
+    pub fn steals(&self) -> u64 {
+        self.pool.steals.load(Ordering::Relaxed)
+    }
+
+    /// Queue `job` behind the jobs already sent for `symbol`
+    ///
+    /// A job with a result to hand back carries its own channel for it, as
+    /// each v2 order does.
+    pub fn dispatch(&self, symbol: &Symbol, job: impl FnOnce() + Send + 'static) {
+        let shard = &self.pool.shards[self.shard_of(symbol)];
+        let mut queue = shard.queue.lock().unwrap();
+        let jobs = queue.pending.entry(symbol.clone()).or_default();
+        jobs.push_back(Box::new(job));
+        // Otherwise it is ready already, or goes back once its running job is done
+        if jobs.len() == 1 && !queue.running.contains(symbol) {
+            queue.ready.push_back(symbol.clone());
+            shard.wake.notify_one();
+        }
+    }
+}
+
+impl Clone for MatchingShards {
+    fn clone(&self) -> Self {
+        Self {
+            pool: Arc::clone(&self.pool),
+            _shutdown: Arc::clone(&self._shutdown),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[test]
+    fn test_jobs_run_in_order_per_symbol_and_survive_panics() {
+        let shards = MatchingShards::new(4);
+        let (results, received) = mpsc::channel();
+        for symbol in ["BTCUSDT", "ETHUSDT"] {
+            let symbol = Symbol::from(symbol);
+            for n in 0..50 {
+                let results = results.clone();
+                let tag = symbol.clone();
+                shards.dispatch(&symbol, move || results.send((tag, n)).unwrap());
+            }
+        }
+        shards.dispatch(&Symbol::from("BTCUSDT"), || panic!("bad order"));
+        let results_after = results.clone();
+        shards.dispatch(&Symbol::from("BTCUSDT"), move || {
+            results_after.send((Symbol::from("BTCUSDT"), 50)).unwrap()
+        });
+
+        let seen: Vec<(Symbol, i32)> = (0..101)
+            .map(|_| received.recv_timeout(Duration::from_secs(5)).unwrap())
+            .collect();
+        for symbol in ["BTCUSDT", "ETHUSDT"] {
+            let order: Vec<_> = seen
+                .iter()
+                .filter(|(s, _)| s == symbol)
+                .map(|(_, n)| *n)
+                .collect();
+            let expected = if symbol == "BTCUSDT" { 51 } else { 50 };
+            assert_eq!(order, (0..expected).collect::<Vec<_>>());
+        }
+        assert_eq!(
+            shards.shard_of(&Symbol::from("BTCUSDT")),
+            shards.shard_of(&"btcusdt".into())
+        );
+    }
+
+    #[test]
+    fn test_busy_symbol_does_not_block_its_shard() {
+        let shards = MatchingShards::new(2);
+        // Two symbols on the same shard
+        let busy = Symbol::from("BTCUSDT");
+        let other = (0..)
+            .map(|n| Symbol::from(format!("ALT{}USDT", n)))
+            .find(|s| shards.shard_of(s) == shards.shard_of(&busy))
+            .unwrap();
+
+        // The busy symbol's job only finishes once the other one has run,
+        // which takes the idle worker stealing it
+        let (unblock, blocked) = mpsc::channel::<()>();
+        let (done, finished) = mpsc::channel();
+        let busy_done = done.clone();
+        shards.dispatch(&busy, move || {
+            let waited = blocked.recv_timeout(Duration::from_secs(5));
+            busy_done.send(waited.is_ok()).unwrap();
+        });
+        shards.dispatch(&other, move || unblock.send(()).unwrap());
+
+        assert!(finished.recv_timeout(Duration::from_secs(10)).unwrap());
+        assert!(shards.steals() >= 1);
+    }
+}
@@ -0,0 +1,151 @@
+use chrono::Utc;
+use serde::Serialize;
+
+use crate::account::Activity;
+use crate::orderbook::book::{BookKind, SharedOrderBook};
+use crate::types::{Order, OrderSide, OrderType, Price, Symbol};
+
+/// One level an order would take liquidity from
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SimulatedFill {
+    pub book: BookKind,
+    pub price: f64,
+    pub quantity: f64,
+}
+
+/// Expected outcome of an order, computed without touching any book
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Simulation {
+    pub symbol: Symbol,
+    pub side: OrderSide,
+    pub requested_quantity: f64,
+    pub filled_quantity: f64,
+    pub fills: Vec<SimulatedFill>,
+    pub average_price: Option<f64>,
+    pub notional: f64,
+    pub fees: f64,
+    /// Mid before the order, mirror first, used for slippage
+    pub reference_mid: Option<f64>,
+    /// Cost of the average price versus the reference mid, positive when adverse
+    pub slippage_bps: Option<f64>,
+    /// Quantity a limit order would leave resting on the matching book
+    pub resting_quantity: f64,
+}
+
+impl Simulation {
+    /// Walk `books` in order, as submission would, charging `fee_bps` on notional
+    pub fn walk(order: &Order, books: &[(BookKind, SharedOrderBook)], fee_bps: f64) -> Self {
+        let mut remaining = order.remaining_quantity.value();
+        let mut fills = Vec::new();
+        for (kind, book) in books {
+            let (bids, asks) = book.get_depth(usize::MAX);
+            let levels = match order.side {
+                OrderSide::Buy => asks,
+                OrderSide::Sell => bids,
+            };
+            for (price, available) in levels {
+                if remaining <= 0.0 || !order.can_match(Price::new(price)) {
+                    break;
+                }
+                let quantity = remaining.min(available);
+                remaining -= quantity;
+                fills.push(SimulatedFill {
+                    book: *kind,
+                    price,
+                    quantity,
+                });
+            }
+        }
+
+        let filled_quantity: f64 = fills.iter().map(|fill| fill.quantity).sum();
+        let notional: f64 = fills.iter().map(|fill| fill.price * fill.quantity).sum();
+        let average_price = (filled_quantity > 0.0).then(|| notional / filled_quantity);
+        let reference_mid = books.iter().find_map(|(_, book)| book.mid_price());
+        let slippage_bps = average_price.zip(reference_mid).map(|(average, mid)| {
+            let sign = if order.side == OrderSide::Buy {
+                1.0
+            } else {
+                -1.0
+            };
+            sign * (average - mid) / mid * 10_000.0
+        });
+
+        Self {
+            symbol: order.symbol.clone(),
+            side: order.side,
+            requested_quantity: order.remaining_quantity.value(),
+            filled_quantity,
+            fills,
+            average_price,
+            notional,
+            fees: notional * fee_bps / 10_000.0,
+            reference_mid,
+            slippage_bps,
+            resting_quantity: if matches!(
+                order.order_type,
+                OrderType::Market | OrderType::StopMarket
+            ) {
+                0.0
+            } else {
+                remaining.max(0.0)
+            },
+        }
+    }
+
+    /// The fills as account activity, each charged its share of the fees
+    pub fn trades(&self) -> Vec<Activity> {
+        let timestamp = Utc::now();
+        self.fills
+            .iter()
+            .map(|fill| Activity::Trade {
+                symbol: self.symbol.clone(),
+                side: self.side,
+                price: fill.price,
+                quantity: fill.quantity,
+                fee: self.fees * fill.price * fill.quantity / self.notional,
+                timestamp,
+                fill: None,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orderbook::BookManager;
+    use crate::testkit::{self, buy, sell};
+
+    #[test]
+    fn test_walk_matches_submission_without_filling() {
+        let books = BookManager::new().with_cross_with_market(true);
+        let mirror = testkit::book()
+            .bid(99.0, 1.0)
+            .asks(&[(101.0, 1.0), (102.0, 1.0)]);
+        books.mirror("BTCUSDT").apply_snapshot(&mirror.snapshot());
+        books.submit(sell(101.5, 0.5).build());
+
+        let order = buy(101.5, 2.0).build();
+        let simulation = books.simulate(&order, 10.0);
+        let fills: Vec<(BookKind, f64, f64)> = simulation
+            .fills
+            .iter()
+            .map(|fill| (fill.book, fill.price, fill.quantity))
+            .collect();
+        assert_eq!(
+            fills,
+            [
+                (BookKind::Mirror, 101.0, 1.0),
+                (BookKind::Matching, 101.5, 0.5)
+            ]
+        );
+        assert_eq!(simulation.resting_quantity, 0.5);
+        assert_eq!(simulation.notional, 151.75);
+        assert!((simulation.fees - 0.15175).abs() < 1e-12);
+        assert_eq!(simulation.reference_mid, Some(100.0));
+
+        // Nothing was consumed
+        let trades = books.submit(order);
+        assert_eq!(trades.len(), 2);
+    }
+}
@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+/// Order rate allowed per client, and how a burst over it is handled
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ThrottleConfig {
+    /// Sustained orders per second; queued orders drain at this rate
+    pub orders_per_sec: f64,
+    /// Orders a client may send at once before the rate applies
+    pub burst: u32,
+    /// Orders held back per client once over the rate; none rejects them
+    #[serde(default)]
+    pub max_queue_depth: Option<u32>,
+}
+
+impl ThrottleConfig {
+    pub fn new(orders_per_sec: f64, burst: u32) -> Self {
+        Self {
+            orders_per_sec,
+            burst: burst.max(1),
+            max_queue_depth: None,
+        }
+    }
+
+    /// Queue up to `depth` orders past the rate instead of rejecting them
+    pub fn with_queue(mut self, depth: u32) -> Self {
+        self.max_queue_depth = Some(depth);
+        self
+    }
+
+    fn interval(&self) -> Duration {
+        Duration::from_secs_f64(1.0 / self.orders_per_sec)
+    }
+}
+
+/// What happens to an order arriving at the throttle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Admission {
+    /// Within the rate, goes to the book right away
+    Now,
+    /// Held back for `delay`, behind `depth - 1` other queued orders
+    Queued { depth: u32, delay: Duration },
+    /// Over the rate with the queue full or disabled
+    Rejected,
+}
+
+/// Per-client order rate limit with an optional queue smoothing bursts
+///
+/// Each client gets a theoretical time its next order is due (GCRA): an
+/// order may run ahead of it by the burst, and is otherwise queued until
+/// the time comes, so queued orders leave at exactly the configured rate
+/// and the queue depth follows from how far ahead the client is.
+#[derive(Debug)]
+pub struct OrderThrottle {
+    config: ThrottleConfig,
+    due: HashMap<String, Instant>,
+}
+
+impl OrderThrottle {
+    pub fn new(config: ThrottleConfig) -> Self {
+        Self {
+            config,
+            due: HashMap::new(),
+        }
+    }
+
+    pub fn config(&self) -> ThrottleConfig {
+        self.config
+    }
+
+    /// Admit one order of `client` arriving at `now`
+    pub fn admit(&mut self, client: &str, now: Instant) -> Admission {
+        let interval = self.config.interval();
+        let due = self.due.get(client).copied().unwrap_or(now).max(now);
+        let delay = due.saturating_duration_since(now + self.tolerance());
+        let admission = if delay.is_zero() {
+            Admission::Now
+        } else {
+            let depth = self.depth_at(delay);
+            match self.config.max_queue_depth {
+                Some(max) if depth <= max => Admission::Queued { depth, delay },
+                _ => return Admission::Rejected,
+            }
+        };
+        self.due.insert(client.to_string(), due + interval);
+        admission
+    }
+
+    /// Orders of `client` still waiting at `now`
+    pub fn queue_depth(&self, client: &str, now: Instant) -> u32 {
+        self.due.get(client).map_or(0, |due| {
+            // `due` is for the next order; the last queued one waits an
+            // interval less
+            let last = due.saturating_duration_since(now + self.tolerance());
+            self.depth_at(last.saturating_sub(self.config.interval()))
+        })
+    }
+
+    /// Drop clients that are back within their burst
+    pub fn prune(&mut self, now: Instant) {
+        self.due.retain(|_, due| *due > now);
+    }
+
+    /// How far ahead of its due time a client may run
+    fn tolerance(&self) -> Duration {
+        self.config.interval() * (self.config.burst.max(1) - 1)
+    }
+
+    /// Orders queued, the last one included, when it waits `delay`
+    fn depth_at(&self, delay: Duration) -> u32 {
+        (delay.as_secs_f64() / self.config.interval().as_secs_f64()).ceil() as u32
+    }
+}
+
+/// Thread-safe wrapper for OrderThrottle
+pub struct SharedOrderThrottle {
+    inner: Arc<Mutex<OrderThrottle>>,
+}
+
+impl SharedOrderThrottle {
+    pub fn new(throttle: OrderThrottle) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(throttle)),
+        }
+    }
+
+    pub fn admit(&self, client: &str, now: Instant) -> Admission {
+        let mut throttle = self.inner.lock().unwrap();
+        throttle.prune(now);
+        throttle.admit(client, now)
+    }
+
+    pub fn queue_depth(&self, client: &str, now: Instant) -> u32 {
+        self.inner.lock().unwrap().queue_depth(client, now)
+    }
+}
+
+impl Clone for SharedOrderThrottle {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_burst_over_the_rate_is_queued_then_rejected() {
+        // 10 a second, 2 at once, 2 more held back
+        let mut throttle = OrderThrottle::new(ThrottleConfig::new(10.0, 2).with_queue(2));
+        let start = Instant::now();
+        let admitted: Vec<_> = (0..5).map(|_| throttle.admit("alice", start)).collect();
+        assert_eq!(admitted[..2], [Admission::Now, Admission::Now]);
+        assert_eq!(
+            admitted[2],
+            Admission::Queued {
+                depth: 1,
+                delay: Duration::from_millis(100)
+            }
+        );
+        assert!(matches!(admitted[3], Admission::Queued { depth: 2, .. }));
+        assert_eq!(admitted[4], Admission::Rejected);
+        assert_eq!(throttle.queue_depth("alice", start), 2);
+        assert_eq!(throttle.admit("bob", start), Admission::Now);
+
+        // The queue drains at the rate
+        let later = start + Duration::from_millis(150);
+        assert_eq!(throttle.queue_depth("alice", later), 1);
+        assert!(matches!(
+            throttle.admit("alice", later),
+            Admission::Queued { depth: 2, .. }
+        ));
+
+        // Without a queue the same burst is rejected outright
+        let mut strict = OrderThrottle::new(ThrottleConfig::new(10.0, 2));
+        strict.admit("alice", start);
+        strict.admit("alice", start);
+        assert_eq!(strict.admit("alice", start), Admission::Rejected);
+    }
+}
@@ -0,0 +1,277 @@
+// Overload protection: shed low-priority work when the engine falls behind
+//
+// Producers report queue depths and processing latencies; the shedder moves
+// between modes with hysteresis and tells callers whether to run optional
+// work. Order entry and risk checks are `Priority::Critical` and always run.
+
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+
+/// How important a unit of work is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Priority {
+    /// Analytics, UI conflation and other optional work
+    Low,
+    /// Market data processing
+    Normal,
+    /// Order entry and risk checks, never shed
+    Critical,
+}
+
+/// Current protection level
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverloadMode {
+    Normal,
+    /// Low-priority work is shed
+    Shedding,
+    /// Only critical work runs
+    Severe,
+}
+
+impl OverloadMode {
+    pub fn admits(&self, priority: Priority) -> bool {
+        match self {
+            OverloadMode::Normal => true,
+            OverloadMode::Shedding => priority >= Priority::Normal,
+            OverloadMode::Severe => priority == Priority::Critical,
+        }
+    }
+}
+
+/// Limits that trigger shedding
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct OverloadThresholds {
+    /// Queue depth that starts shedding; twice this is severe
+    pub queue_depth: usize,
+    /// Smoothed processing latency that starts shedding; twice this is severe
+    pub latency_ms: f64,
+    /// Load must fall below this fraction of a limit to step down a mode
+    pub recovery_ratio: f64,
+}
+
+impl Default for OverloadThresholds {
+    fn default() -> Self {
+        Self {
+            queue_depth: 1_000,
+            latency_ms: 5.0,
+            recovery_ratio: 0.5,
+        }
+    }
+}
+
+/// Counters exposed for monitoring
+#[derive(Debug, Clone, Serialize)]
+pub struct OverloadMetrics {
+    pub mode: OverloadMode,
+    pub queue_depth: usize,
+    pub latency_ms: f64,
+    pub transitions: u64,
+    pub shed_low: u64,
+    pub shed_normal: u64,
+}
+
+/// Weight of the newest sample in the latency moving average
+const LATENCY_ALPHA: f64 = 0.1;
+
+/// Tracks load and decides which work may run
+#[derive(Debug)]
+pub struct LoadShedder {
+    thresholds: OverloadThresholds,
+    mode: OverloadMode,
+    queue_depth: usize,
+    latency_ms: f64,
+    transitions: u64,
+    shed_low: u64,
+    shed_normal: u64,
+}
+
+impl LoadShedder {
+    pub fn new(thresholds: OverloadThresholds) -> Self {
+        Self {
+            thresholds,
+            mode: OverloadMode::Normal,
+            queue_depth: 0,
+            latency_ms: 0.0,
+            transitions: 0,
+            shed_low: 0,
+            shed_normal: 0,
+        }
+    }
+
+    pub fn mode(&self) -> OverloadMode {
+        self.mode
+    }
+
+    pub fn record_queue_depth(&mut self, depth: usize) {
+        self.queue_depth = depth;
+        self.evaluate();
+    }
+
+    pub fn record_latency(&mut self, latency_ms: f64) {
+        self.latency_ms += LATENCY_ALPHA * (latency_ms - self.latency_ms);
+        self.evaluate();
+    }
+
+    /// Whether work of `priority` should run now; shed work is counted
+    pub fn admit(&mut self, priority: Priority) -> bool {
+        if self.mode.admits(priority) {
+            return true;
+        }
+        match priority {
+            Priority::Low => self.shed_low += 1,
+            Priority::Normal => self.shed_normal += 1,
+            Priority::Critical => {}
+        }
+        false
+    }
+
+    pub fn metrics(&self) -> OverloadMetrics {
+        OverloadMetrics {
+            mode: self.mode,
+            queue_depth: self.queue_depth,
+            latency_ms: self.latency_ms,
+            transitions: self.transitions,
+            shed_low: self.shed_low,
+            shed_normal: self.shed_normal,
+        }
+    }
+
+    /// Load as a multiple of the thresholds (1.0 = at the shedding limit)
+    fn load(&self) -> f64 {
+        let queue = self.queue_depth as f64 / self.thresholds.queue_depth.max(1) as f64;
+        let latency = self.latency_ms / self.thresholds.latency_ms.max(f64::EPSILON);
+        queue.max(latency)
+    }
+
+    fn evaluate(&mut self) {
+        let load = self.load();
+        let recovery = self.thresholds.recovery_ratio;
+
+        let target = match self.mode {
+            _ if load > 2.0 => OverloadMode::Severe,
+            OverloadMode::Severe if load > 2.0 * recovery => OverloadMode::Severe,
+            _ if load > 1.0 => OverloadMode::Shedding,
+            OverloadMode::Severe | OverloadMode::Shedding if load > recovery => {
+                OverloadMode::Shedding
+            }
+            _ => OverloadMode::Normal,
+        };
+
+        if target != self.mode {
+            tracing::warn!(
+                "Overload mode {:?} -> {:?} (queue {}, latency {:.2}ms)",
+                self.mode,
+                target,
+                self.queue_depth,
+                self.latency_ms
+            );
+            self.mode = target;
+            self.transitions += 1;
+        }
+    }
+}
+
+impl Default for LoadShedder {
+    fn default() -> Self {
+        Self::new(OverloadThresholds::default())
+    }
+}
+
+/// Thread-safe wrapper for LoadShedder
+pub struct SharedLoadShedder {
+    inner: Arc<Mutex<LoadShedder>>,
+}
+
+impl SharedLoadShedder {
+    pub fn new(thresholds: OverloadThresholds) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(LoadShedder::new(thresholds))),
+        }
+    }
+
+    pub fn mode(&self) -> OverloadMode {
+        self.inner.lock().unwrap().mode()
+    }
+
+    pub fn record_queue_depth(&self, depth: usize) {
+        self.inner.lock().unwrap().record_queue_depth(depth)
+    }
+
+    pub fn record_latency(&self, latency_ms: f64) {
+        self.inner.lock().unwrap().record_latency(latency_ms)
+    }
+
+    pub fn admit(&self, priority: Priority) -> bool {
+        self.inner.lock().unwrap().admit(priority)
+    }
+
+    pub fn metrics(&self) -> OverloadMetrics {
+        self.inner.lock().unwrap().metrics()
+    }
+}
+
+impl Clone for SharedLoadShedder {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl Default for SharedLoadShedder {
+    fn default() -> Self {
+        Self::new(OverloadThresholds::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shedder() -> LoadShedder {
+        LoadShedder::new(OverloadThresholds {
+            queue_depth: 100,
+            latency_ms: 5.0,
+            recovery_ratio: 0.5,
+        })
+    }
+
+    #[test]
+    fn test_queue_depth_escalates_with_hysteresis() {
+        let mut shedder = shedder();
+        shedder.record_queue_depth(150);
+        assert_eq!(shedder.mode(), OverloadMode::Shedding);
+        assert!(!shedder.admit(Priority::Low));
+        assert!(shedder.admit(Priority::Normal));
+
+        shedder.record_queue_depth(250);
+        assert_eq!(shedder.mode(), OverloadMode::Severe);
+        assert!(!shedder.admit(Priority::Normal));
+        assert!(shedder.admit(Priority::Critical));
+
+        // Falling just under a limit is not enough to step down
+        shedder.record_queue_depth(150);
+        assert_eq!(shedder.mode(), OverloadMode::Severe);
+        shedder.record_queue_depth(80);
+        assert_eq!(shedder.mode(), OverloadMode::Shedding);
+        shedder.record_queue_depth(40);
+        assert_eq!(shedder.mode(), OverloadMode::Normal);
+
+        let metrics = shedder.metrics();
+        assert_eq!(metrics.transitions, 4);
+        assert_eq!((metrics.shed_low, metrics.shed_normal), (1, 1));
+    }
+
+    #[test]
+    fn test_latency_triggers_shedding() {
+        let mut shedder = shedder();
+        for _ in 0..50 {
+            shedder.record_latency(8.0);
+        }
+        assert_eq!(shedder.mode(), OverloadMode::Shedding);
+        assert!(shedder.admit(Priority::Critical));
+    }
+}
@@ -0,0 +1,286 @@
+// Python bindings, enabled with the `python` feature
+//
+// Build with `maturin develop --features python` and use from Python:
+//
+//     import crypto_orderbook as cob
+//     book = cob.OrderBook("BTCUSDT")
+//     book.add_limit("sell", 50000.0, 1.0)
+//     result = cob.run_backtest(snapshots, strategy, initial_cash=10_000.0)
+
+// pyo3 0.22's #[pymethods] expansion trips this lint on every PyResult return
+#![allow(clippy::useless_conversion)]
+
+use chrono::DateTime;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::backtest::{
+    Backtest, BacktestConfig, BacktestMetrics, MarketSnapshot, OrderIntent, SlippageConfig,
+    Strategy,
+};
+use crate::orderbook::{Depth, OrderBook};
+use crate::types::{Order, OrderId, OrderSide, Price, Qty, Trade};
+
+/// (timestamp seconds, bids, asks) as passed from Python
+type PySnapshot = (f64, Vec<(f64, f64)>, Vec<(f64, f64)>);
+
+fn parse_side(side: &str) -> PyResult<OrderSide> {
+    match side.to_ascii_lowercase().as_str() {
+        "buy" | "bid" => Ok(OrderSide::Buy),
+        "sell" | "ask" => Ok(OrderSide::Sell),
+        other => Err(PyValueError::new_err(format!("unknown side '{}'", other))),
+    }
+}
+
+fn positive<T>(name: &str, value: f64, parse: fn(f64) -> Option<T>) -> PyResult<T> {
+    parse(value).ok_or_else(|| {
+        PyValueError::new_err(format!("{} must be a positive number, got {}", name, value))
+    })
+}
+
+fn side_name(side: OrderSide) -> &'static str {
+    match side {
+        OrderSide::Buy => "buy",
+        OrderSide::Sell => "sell",
+    }
+}
+
+fn trade_to_dict<'py>(py: Python<'py>, trade: &Trade) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new_bound(py);
+    dict.set_item("maker_order_id", trade.maker_order_id.0)?;
+    dict.set_item("taker_order_id", trade.taker_order_id.0)?;
+    dict.set_item("symbol", trade.symbol.as_str())?;
+    dict.set_item("price", trade.price.value())?;
+    dict.set_item("quantity", trade.quantity.value())?;
+    dict.set_item("timestamp", trade.timestamp.timestamp_millis())?;
+    dict.set_item("aggressor", trade.aggressor.map(side_name))?;
+    dict.set_item("sequence", trade.sequence)?;
+    Ok(dict)
+}
+
+fn metrics_to_dict<'py>(
+    py: Python<'py>,
+    metrics: &BacktestMetrics,
+) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new_bound(py);
+    dict.set_item("final_equity", metrics.final_equity)?;
+    dict.set_item("total_return", metrics.total_return)?;
+    dict.set_item("max_drawdown", metrics.max_drawdown)?;
+    dict.set_item("trade_count", metrics.trade_count)?;
+    dict.set_item("total_fees", metrics.total_fees)?;
+    dict.set_item("total_slippage_cost", metrics.total_slippage_cost)?;
+    Ok(dict)
+}
+
+/// Order book exposed to Python
+#[pyclass(name = "OrderBook")]
+struct PyOrderBook {
+    book: OrderBook,
+}
+
+#[pymethods]
+impl PyOrderBook {
+    #[new]
+    fn new(symbol: String) -> Self {
+        Self {
+            book: OrderBook::new(symbol),
+        }
+    }
+
+    /// Submit a limit order; returns (order_id, trades)
+    ///
+    /// Raises ValueError unless price and quantity are finite and positive.
+    fn add_limit<'py>(
+        &mut self,
+        py: Python<'py>,
+        side: &str,
+        price: f64,
+        quantity: f64,
+    ) -> PyResult<(u64, Vec<Bound<'py, PyDict>>)> {
+        let order = Order::new_limit(
+            self.book.symbol.clone(),
+            parse_side(side)?,
+            positive("price", price, Price::try_positive)?,
+            positive("quantity", quantity, Qty::try_positive)?,
+        );
+        let id = order.id.0;
+        let trades = self.book.add_order(order);
+        let trades = trades
+            .iter()
+            .map(|t| trade_to_dict(py, t))
+            .collect::<PyResult<_>>()?;
+        Ok((id, trades))
+    }
+
+    /// Submit a market order; returns (order_id, trades)
+    ///
+    /// Raises ValueError unless quantity is finite and positive.
+    fn add_market<'py>(
+        &mut self,
+        py: Python<'py>,
+        side: &str,
+        quantity: f64,
+    ) -> PyResult<(u64, Vec<Bound<'py, PyDict>>)> {
+        let order = Order::new_market(
+            self.book.symbol.clone(),
+            parse_side(side)?,
+            positive("quantity", quantity, Qty::try_positive)?,
+        );
+        let id = order.id.0;
+        let trades = self.book.add_order(order);
+        let trades = trades
+            .iter()
+            .map(|t| trade_to_dict(py, t))
+            .collect::<PyResult<_>>()?;
+        Ok((id, trades))
+    }
+
+    /// Cancel a resting order; returns True if it was found
+    fn cancel(&mut self, order_id: u64) -> bool {
+        self.book.cancel_order(OrderId(order_id)).is_some()
+    }
+
+    fn best_bid(&self) -> Option<f64> {
+        self.book.best_bid()
+    }
+
+    fn best_ask(&self) -> Option<f64> {
+        self.book.best_ask()
+    }
+
+    fn spread(&self) -> Option<f64> {
+        self.book.spread()
+    }
+
+    fn mid_price(&self) -> Option<f64> {
+        self.book.mid_price()
+    }
+
+    /// Top N levels as (bids, asks) lists of (price, quantity)
+    #[pyo3(signature = (levels = 10))]
+    fn depth(&self, levels: usize) -> Depth {
+        self.book.get_depth(levels)
+    }
+
+    fn order_count(&self) -> usize {
+        self.book.order_count()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "OrderBook(symbol={}, orders={}, bid={:?}, ask={:?})",
+            self.book.symbol,
+            self.book.order_count(),
+            self.book.best_bid(),
+            self.book.best_ask()
+        )
+    }
+}
+
+/// Adapts a Python callable `strategy(bids, asks, position) -> None | (side, qty)`
+struct PyStrategy<'py> {
+    callback: Bound<'py, PyAny>,
+    error: Option<PyErr>,
+}
+
+impl Strategy for PyStrategy<'_> {
+    fn on_snapshot(&mut self, snapshot: &MarketSnapshot, position: f64) -> Option<OrderIntent> {
+        if self.error.is_some() {
+            return None;
+        }
+
+        let decision = self
+            .callback
+            .call1((snapshot.bids.clone(), snapshot.asks.clone(), position))
+            .and_then(|out| out.extract::<Option<(String, f64)>>())
+            .and_then(|out| match out {
+                Some((side, quantity)) => Ok(Some(OrderIntent {
+                    side: parse_side(&side)?,
+                    quantity,
+                })),
+                None => Ok(None),
+            });
+
+        // Remember the first Python error and stop trading; it is raised after the run
+        decision.unwrap_or_else(|e| {
+            self.error = Some(e);
+            None
+        })
+    }
+}
+
+/// Run a backtest over `snapshots` driven by a Python strategy callable
+///
+/// `slippage` is a JSON model selection, e.g. `{"model": "fixed_bps", "bps": 2.0}`.
+#[pyfunction]
+#[pyo3(signature = (snapshots, strategy, symbol = "BTCUSDT".to_string(), initial_cash = 10_000.0, fee_bps = 0.0, slippage = None))]
+fn run_backtest<'py>(
+    py: Python<'py>,
+    snapshots: Vec<PySnapshot>,
+    strategy: Bound<'py, PyAny>,
+    symbol: String,
+    initial_cash: f64,
+    fee_bps: f64,
+    slippage: Option<&str>,
+) -> PyResult<Bound<'py, PyDict>> {
+    let mut config = BacktestConfig::new(symbol, initial_cash);
+    config.fee_bps = fee_bps;
+    if let Some(json) = slippage {
+        config.slippage = serde_json::from_str::<SlippageConfig>(json)
+            .map_err(|e| PyValueError::new_err(format!("invalid slippage model: {}", e)))?;
+    }
+
+    let snapshots: Vec<MarketSnapshot> = snapshots
+        .into_iter()
+        .map(|(seconds, bids, asks)| MarketSnapshot {
+            timestamp: DateTime::from_timestamp_nanos((seconds * 1e9) as i64),
+            bids,
+            asks,
+        })
+        .collect();
+
+    let mut adapter = PyStrategy {
+        callback: strategy,
+        error: None,
+    };
+    let result = Backtest::new(config).run(&mut adapter, &snapshots);
+    if let Some(error) = adapter.error {
+        return Err(error);
+    }
+
+    let out = PyDict::new_bound(py);
+    out.set_item("metrics", metrics_to_dict(py, &result.metrics)?)?;
+    out.set_item(
+        "equity_curve",
+        result
+            .equity_curve
+            .iter()
+            .map(|(time, equity)| (time.timestamp_millis(), *equity))
+            .collect::<Vec<_>>(),
+    )?;
+    out.set_item(
+        "fills",
+        result
+            .fills
+            .iter()
+            .map(|f| {
+                (
+                    f.timestamp.timestamp_millis(),
+                    side_name(f.side),
+                    f.quantity.value(),
+                    f.price.value(),
+                    f.fee.value(),
+                )
+            })
+            .collect::<Vec<_>>(),
+    )?;
+    Ok(out)
+}
+
+#[pymodule]
+fn crypto_orderbook(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyOrderBook>()?;
+    m.add_function(wrap_pyfunction!(run_backtest, m)?)?;
+    Ok(())
+}
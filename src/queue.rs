@@ -0,0 +1,395 @@
+// Disk-backed queue between services
+//
+// Every message is appended to a write-ahead log before `send` returns, and
+// each named consumer persists the last sequence number it acknowledged. A
+// consumer that lags or restarts resumes after its last ack, so downstream
+// services see every message at least once. The log is split into segments
+// that are deleted once every consumer has acknowledged them.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Seek, SeekFrom, Write};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+const SEGMENT_EXTENSION: &str = "wal";
+const OFFSET_EXTENSION: &str = "offset";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueueConfig {
+    /// Messages per log segment before rolling to a new file
+    pub segment_entries: u64,
+    /// fsync after every message instead of leaving it to the OS
+    pub sync: bool,
+}
+
+impl Default for QueueConfig {
+    fn default() -> Self {
+        Self {
+            segment_entries: 10_000,
+            sync: false,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Record<T> {
+    seq: u64,
+    item: T,
+}
+
+/// Writing end of a queue stored in `dir`
+#[derive(Debug)]
+pub struct PersistentQueue<T> {
+    dir: PathBuf,
+    config: QueueConfig,
+    segment: File,
+    segment_start: u64,
+    next_seq: u64,
+    _item: PhantomData<fn(T)>,
+}
+
+impl<T: Serialize + DeserializeOwned> PersistentQueue<T> {
+    /// Open or create the queue, dropping a torn final write from a crash
+    pub fn open(dir: impl Into<PathBuf>, config: QueueConfig) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+
+        let segment_start = segments(&dir)?.last().copied().unwrap_or(1);
+        let path = segment_path(&dir, segment_start);
+        let mut segment = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(&path)?;
+
+        // Keep complete lines only; a partial one was never acknowledged to the sender
+        let mut reader = BufReader::new(&segment);
+        let (mut valid_len, mut next_seq) = (0u64, segment_start);
+        let mut line = String::new();
+        loop {
+            line.clear();
+            let read = reader.read_line(&mut line)?;
+            if read == 0 || !line.ends_with('\n') {
+                break;
+            }
+            match serde_json::from_str::<Record<serde_json::Value>>(&line) {
+                Ok(record) => next_seq = record.seq + 1,
+                Err(_) => break,
+            }
+            valid_len += read as u64;
+        }
+        if valid_len < segment.metadata()?.len() {
+            tracing::warn!("Truncating torn write at the end of {:?}", path);
+            segment.set_len(valid_len)?;
+        }
+        segment.seek(SeekFrom::End(0))?;
+
+        Ok(Self {
+            dir,
+            config,
+            segment,
+            segment_start,
+            next_seq,
+            _item: PhantomData,
+        })
+    }
+
+    /// Append `item` and return its sequence number once it is on disk
+    pub fn send(&mut self, item: &T) -> io::Result<u64> {
+        if self.next_seq - self.segment_start >= self.config.segment_entries.max(1) {
+            self.segment = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(segment_path(&self.dir, self.next_seq))?;
+            self.segment_start = self.next_seq;
+        }
+
+        let seq = self.next_seq;
+        let mut line = serde_json::to_vec(&Record { seq, item }).map_err(io::Error::other)?;
+        line.push(b'\n');
+        self.segment.write_all(&line)?;
+        if self.config.sync {
+            self.segment.sync_data()?;
+        }
+        self.next_seq += 1;
+        Ok(seq)
+    }
+
+    /// Sequence number the next message will get
+    pub fn next_seq(&self) -> u64 {
+        self.next_seq
+    }
+
+    /// Open the consumer `name`, resuming after its last acknowledgement
+    pub fn consumer(&self, name: &str) -> io::Result<QueueConsumer<T>> {
+        QueueConsumer::open(&self.dir, name)
+    }
+
+    /// Delete segments every consumer has acknowledged; returns how many
+    ///
+    /// With no consumers nothing is deleted.
+    pub fn compact(&self) -> io::Result<usize> {
+        let mut acked: Option<u64> = None;
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) == Some(OFFSET_EXTENSION) {
+                let offset = read_offset(&path)?;
+                acked = Some(acked.map_or(offset, |a| a.min(offset)));
+            }
+        }
+        let Some(acked) = acked else {
+            return Ok(0);
+        };
+
+        let starts = segments(&self.dir)?;
+        let mut removed = 0;
+        for pair in starts.windows(2) {
+            // Segment pair[0] holds pair[0]..pair[1]; the active one is never in pair[0]
+            if pair[1] <= acked + 1 {
+                fs::remove_file(segment_path(&self.dir, pair[0]))?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+}
+
+/// Reading end with a durable position
+pub struct QueueConsumer<T> {
+    dir: PathBuf,
+    offset_path: PathBuf,
+    reader: Option<(u64, BufReader<File>)>,
+    /// Last sequence number returned by `poll`
+    delivered: u64,
+    acked: u64,
+    _item: PhantomData<fn() -> T>,
+}
+
+impl<T: DeserializeOwned> QueueConsumer<T> {
+    fn open(dir: &Path, name: &str) -> io::Result<Self> {
+        let offset_path = dir.join(format!("{}.{}", name, OFFSET_EXTENSION));
+        let acked = if offset_path.exists() {
+            read_offset(&offset_path)?
+        } else {
+            0
+        };
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            offset_path,
+            reader: None,
+            delivered: acked,
+            acked,
+            _item: PhantomData,
+        })
+    }
+
+    /// Next unread message, or None when caught up with the sender
+    pub fn poll(&mut self) -> io::Result<Option<(u64, T)>> {
+        loop {
+            if self.reader.is_none() {
+                let starts = segments(&self.dir)?;
+                let wanted = self.delivered + 1;
+                let Some(start) = starts
+                    .iter()
+                    .rev()
+                    .find(|&&s| s <= wanted)
+                    .or(starts.first())
+                else {
+                    return Ok(None);
+                };
+                let file = File::open(segment_path(&self.dir, *start))?;
+                self.reader = Some((*start, BufReader::new(file)));
+            }
+
+            let (start, reader) = self.reader.as_mut().unwrap();
+            let mut line = String::new();
+            let read = reader.read_line(&mut line)?;
+            if read > 0 && line.ends_with('\n') {
+                let record: Record<T> = serde_json::from_str(&line)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+                if record.seq <= self.delivered {
+                    continue;
+                }
+                self.delivered = record.seq;
+                return Ok(Some((record.seq, record.item)));
+            }
+            // Partial line: the sender is mid-write, read it again next time
+            reader.seek_relative(-(read as i64))?;
+
+            // At the end of a segment that has been rolled over, move on
+            let start = *start;
+            let rolled = segments(&self.dir)?.into_iter().any(|s| s > start);
+            if read == 0 && rolled {
+                self.reader = None;
+                continue;
+            }
+            return Ok(None);
+        }
+    }
+
+    /// Wait up to `timeout` for the next message
+    pub fn recv_timeout(&mut self, timeout: Duration) -> io::Result<Option<(u64, T)>> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(message) = self.poll()? {
+                return Ok(Some(message));
+            }
+            if Instant::now() >= deadline {
+                return Ok(None);
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    /// Persist that everything up to `seq` has been processed
+    pub fn ack(&mut self, seq: u64) -> io::Result<()> {
+        if seq <= self.acked {
+            return Ok(());
+        }
+        let tmp = self.offset_path.with_extension("tmp");
+        fs::write(&tmp, seq.to_string())?;
+        fs::rename(&tmp, &self.offset_path)?;
+        self.acked = seq;
+        Ok(())
+    }
+
+    pub fn acked(&self) -> u64 {
+        self.acked
+    }
+}
+
+/// Thread-safe wrapper for PersistentQueue
+#[derive(Debug)]
+pub struct SharedPersistentQueue<T> {
+    inner: Arc<Mutex<PersistentQueue<T>>>,
+}
+
+impl<T: Serialize + DeserializeOwned> SharedPersistentQueue<T> {
+    pub fn new(queue: PersistentQueue<T>) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(queue)),
+        }
+    }
+
+    pub fn send(&self, item: &T) -> io::Result<u64> {
+        self.inner.lock().unwrap().send(item)
+    }
+
+    pub fn consumer(&self, name: &str) -> io::Result<QueueConsumer<T>> {
+        self.inner.lock().unwrap().consumer(name)
+    }
+
+    pub fn compact(&self) -> io::Result<usize> {
+        self.inner.lock().unwrap().compact()
+    }
+}
+
+impl<T> Clone for SharedPersistentQueue<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+/// First sequence number of each segment, ascending
+fn segments(dir: &Path) -> io::Result<Vec<u64>> {
+    let mut starts = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some(SEGMENT_EXTENSION) {
+            continue;
+        }
+        if let Some(start) = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .and_then(|s| s.parse().ok())
+        {
+            starts.push(start);
+        }
+    }
+    starts.sort_unstable();
+    Ok(starts)
+}
+
+fn segment_path(dir: &Path, start: u64) -> PathBuf {
+    dir.join(format!("{:020}.{}", start, SEGMENT_EXTENSION))
+}
+
+fn read_offset(path: &Path) -> io::Result<u64> {
+    fs::read_to_string(path)?
+        .trim()
+        .parse()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("queue-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_consumer_resumes_after_last_ack() {
+        let dir = temp_dir("resume");
+        let config = QueueConfig {
+            segment_entries: 2,
+            sync: false,
+        };
+        let mut queue = PersistentQueue::<String>::open(&dir, config).unwrap();
+        for n in 1..=5 {
+            queue.send(&format!("report-{}", n)).unwrap();
+        }
+
+        let mut audit = queue.consumer("audit").unwrap();
+        assert_eq!(audit.poll().unwrap(), Some((1, "report-1".to_string())));
+        assert_eq!(audit.poll().unwrap().unwrap().0, 2);
+        assert_eq!(audit.poll().unwrap().unwrap().0, 3);
+        audit.ack(2).unwrap();
+        drop(audit);
+
+        // Restarted consumer gets everything after its ack, across segments
+        let mut audit = queue.consumer("audit").unwrap();
+        let seqs: Vec<u64> = std::iter::from_fn(|| audit.poll().unwrap())
+            .map(|(seq, _)| seq)
+            .collect();
+        assert_eq!(seqs, [3, 4, 5]);
+        audit.ack(5).unwrap();
+
+        // Segments 1-2 and 3-4 are fully acked; 5 is still being written
+        assert_eq!(queue.compact().unwrap(), 2);
+        queue.send(&"report-6".to_string()).unwrap();
+        assert_eq!(audit.poll().unwrap(), Some((6, "report-6".to_string())));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_torn_write_is_dropped_on_reopen() {
+        let dir = temp_dir("torn");
+        let mut queue = PersistentQueue::<u32>::open(&dir, QueueConfig::default()).unwrap();
+        queue.send(&7).unwrap();
+        drop(queue);
+
+        let segment = segment_path(&dir, 1);
+        let mut file = OpenOptions::new().append(true).open(&segment).unwrap();
+        file.write_all(br#"{"seq":2,"it"#).unwrap();
+
+        let mut consumer = QueueConsumer::<u32>::open(&dir, "portfolio").unwrap();
+        assert_eq!(consumer.poll().unwrap(), Some((1, 7)));
+        assert_eq!(consumer.poll().unwrap(), None);
+
+        let mut queue = PersistentQueue::<u32>::open(&dir, QueueConfig::default()).unwrap();
+        assert_eq!(queue.send(&8).unwrap(), 2);
+        assert_eq!(consumer.poll().unwrap(), Some((2, 8)));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
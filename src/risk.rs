@@ -0,0 +1,1336 @@
+// Pre-trade risk limits and margin
+//
+// Checks never change the account they are given: callers evaluate an
+// account as it would look after a set of fills, which lets order entry and
+// what-if queries share the same limits. Open positions are marked with
+// whatever mark source the caller has, falling back to the entry price.
+
+use std::collections::BTreeMap;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::account::{Account, Activity, MarginMethod, MarginMode, Position};
+use crate::market::{Instrument, MarginTier, PriceBand};
+use crate::types::{Order, OrderSide, OrderType, Symbol};
+
+/// Caps on marked notional held long and held short
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct DirectionalLimits {
+    pub max_long_notional: Option<f64>,
+    pub max_short_notional: Option<f64>,
+}
+
+impl DirectionalLimits {
+    fn check(&self, symbol: Option<&Symbol>, long: f64, short: f64, breaches: &mut Vec<Breach>) {
+        if let Some(limit) = self.max_long_notional.filter(|limit| long > *limit) {
+            breaches.push(Breach::LongNotional {
+                symbol: symbol.cloned(),
+                notional: long,
+                limit,
+            });
+        }
+        if let Some(limit) = self.max_short_notional.filter(|limit| short > *limit) {
+            breaches.push(Breach::ShortNotional {
+                symbol: symbol.cloned(),
+                notional: short,
+                limit,
+            });
+        }
+    }
+}
+
+/// Limits applied before an order is accepted
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RiskLimits {
+    pub max_order_quantity: Option<f64>,
+    pub max_order_notional: Option<f64>,
+    /// Largest absolute marked position in any one symbol
+    pub max_position_notional: Option<f64>,
+    /// Long and short caps for each symbol's position
+    #[serde(default)]
+    pub symbol_exposure: DirectionalLimits,
+    /// Caps for particular symbols, used instead of `symbol_exposure`
+    #[serde(default)]
+    pub symbol_overrides: BTreeMap<Symbol, DirectionalLimits>,
+    /// Caps on the account's total long and total short notional
+    #[serde(default)]
+    pub account_exposure: DirectionalLimits,
+    /// Collateral required per unit of marked notional, e.g. 0.1 for 10x
+    pub initial_margin_rate: f64,
+    /// Collateral below which positions are liquidated, per unit of notional
+    #[serde(default = "default_maintenance_margin_rate")]
+    pub maintenance_margin_rate: f64,
+    /// Fee charged on the notional of a liquidated position
+    #[serde(default = "default_liquidation_fee_rate")]
+    pub liquidation_fee_rate: f64,
+    /// Tiered schedules replacing the flat rates for their symbols
+    #[serde(default)]
+    pub margin_tiers: BTreeMap<Symbol, Vec<MarginTier>>,
+    /// Scales order and position caps, and price bands, with a symbol's
+    /// realized volatility
+    #[serde(default)]
+    pub volatility_scaling: Option<VolatilityScaling>,
+    /// Scenarios accounts on portfolio margin are stressed with
+    #[serde(default)]
+    pub portfolio_margin: PortfolioMargin,
+}
+
+/// Stress scenarios of portfolio margining
+///
+/// Every symbol moves with one market factor by its correlation to it, over
+/// a grid of market moves up to `price_shock` either way, with the moves
+/// scaled down and up by `vol_shock`. What each symbol can move on its own
+/// is added on as a root-sum-square, so offsetting positions in correlated
+/// symbols net down to their residual risk. A lone position needs exactly
+/// the shock on its notional.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PortfolioMargin {
+    /// Largest market move, as a fraction of price
+    pub price_shock: f64,
+    /// Change in volatility, as a fraction, the moves are scaled by
+    pub vol_shock: f64,
+    /// Moves tested on each side of zero, evenly spaced up to the largest
+    pub steps: u32,
+    /// Correlation of each symbol with the market factor
+    #[serde(default)]
+    pub correlations: BTreeMap<Symbol, f64>,
+    /// Correlation of symbols not in `correlations`
+    pub default_correlation: f64,
+    /// Maintenance margin as a fraction of the worst loss
+    pub maintenance_ratio: f64,
+}
+
+impl Default for PortfolioMargin {
+    fn default() -> Self {
+        Self {
+            price_shock: 0.1,
+            vol_shock: 0.25,
+            steps: 4,
+            correlations: BTreeMap::new(),
+            default_correlation: 0.8,
+            maintenance_ratio: 0.5,
+        }
+    }
+}
+
+/// Scenario a portfolio loses the most in
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct StressScenario {
+    /// Market move, as a fraction of price
+    pub price_move: f64,
+    /// Factor the moves were scaled by for the volatility shift
+    pub vol_factor: f64,
+    /// Loss of the positions, residual risk included
+    pub loss: f64,
+}
+
+impl PortfolioMargin {
+    fn correlation(&self, symbol: &Symbol) -> f64 {
+        self.correlations
+            .get(symbol)
+            .copied()
+            .unwrap_or(self.default_correlation)
+            .clamp(-1.0, 1.0)
+    }
+
+    /// Worst scenario for `positions` as (symbol, signed notional) pairs;
+    /// None without positions
+    pub fn worst_case(&self, positions: &[(Symbol, f64)]) -> Option<StressScenario> {
+        if positions.is_empty() {
+            return None;
+        }
+        let steps = self.steps.max(1);
+        let mut worst: Option<StressScenario> = None;
+        for vol_factor in [1.0 - self.vol_shock, 1.0, 1.0 + self.vol_shock] {
+            let shock = self.price_shock * vol_factor.max(0.0);
+            let residual = positions
+                .iter()
+                .map(|(symbol, notional)| {
+                    let idiosyncratic = (1.0 - self.correlation(symbol).powi(2)).sqrt();
+                    (notional * idiosyncratic * shock).powi(2)
+                })
+                .sum::<f64>();
+            for step in -(steps as i32)..=steps as i32 {
+                let price_move = shock * step as f64 / steps as f64;
+                let pnl: f64 = positions
+                    .iter()
+                    .map(|(symbol, notional)| notional * self.correlation(symbol) * price_move)
+                    .sum();
+                let loss = ((-pnl).max(0.0).powi(2) + residual).sqrt();
+                if worst.as_ref().is_none_or(|worst| loss > worst.loss) {
+                    worst = Some(StressScenario {
+                        price_move,
+                        vol_factor,
+                        loss,
+                    });
+                }
+            }
+        }
+        worst
+    }
+}
+
+/// How limits scale with realized volatility
+///
+/// The result is a factor caps are multiplied by: below 1 in volatile
+/// markets, tightening them, and above 1 in quiet ones. Price bands are
+/// divided by it instead, so they widen as the market moves faster.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum VolatilityScaling {
+    /// `reference_volatility / volatility`, clamped to the given factors
+    Inverse {
+        reference_volatility: f64,
+        min_factor: f64,
+        max_factor: f64,
+    },
+    /// The factor of the first step whose bound the volatility is within;
+    /// the last step's beyond that
+    Steps { steps: Vec<VolatilityStep> },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct VolatilityStep {
+    pub max_volatility: f64,
+    pub factor: f64,
+}
+
+impl VolatilityScaling {
+    pub fn factor(&self, volatility: f64) -> f64 {
+        match self {
+            VolatilityScaling::Inverse {
+                reference_volatility,
+                min_factor,
+                max_factor,
+            } => {
+                if volatility <= 0.0 {
+                    return *max_factor;
+                }
+                (reference_volatility / volatility).clamp(*min_factor, *max_factor)
+            }
+            VolatilityScaling::Steps { steps } => steps
+                .iter()
+                .find(|step| volatility <= step.max_volatility)
+                .or(steps.last())
+                .map_or(1.0, |step| step.factor),
+        }
+    }
+}
+
+/// Caps in force for one symbol at its current volatility
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EffectiveLimits {
+    pub symbol: Symbol,
+    /// Realized volatility the limits were scaled with, if known
+    pub volatility: Option<f64>,
+    pub factor: f64,
+    pub max_order_quantity: Option<f64>,
+    pub max_order_notional: Option<f64>,
+    pub max_position_notional: Option<f64>,
+    /// Price band width in percent, before any volatility-event widening
+    pub band_percent: Option<f64>,
+}
+
+fn default_maintenance_margin_rate() -> f64 {
+    0.05
+}
+
+fn default_liquidation_fee_rate() -> f64 {
+    0.005
+}
+
+impl Default for RiskLimits {
+    fn default() -> Self {
+        Self {
+            max_order_quantity: None,
+            max_order_notional: None,
+            max_position_notional: None,
+            symbol_exposure: DirectionalLimits::default(),
+            symbol_overrides: BTreeMap::new(),
+            account_exposure: DirectionalLimits::default(),
+            initial_margin_rate: 0.1,
+            maintenance_margin_rate: default_maintenance_margin_rate(),
+            liquidation_fee_rate: default_liquidation_fee_rate(),
+            margin_tiers: BTreeMap::new(),
+            volatility_scaling: None,
+            portfolio_margin: PortfolioMargin::default(),
+        }
+    }
+}
+
+/// A limit an order or account would exceed
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum Breach {
+    OrderQuantity {
+        quantity: f64,
+        limit: f64,
+    },
+    OrderNotional {
+        notional: f64,
+        limit: f64,
+    },
+    PositionNotional {
+        symbol: Symbol,
+        notional: f64,
+        limit: f64,
+    },
+    /// Long notional over a symbol's cap, or the account's when no symbol
+    LongNotional {
+        symbol: Option<Symbol>,
+        notional: f64,
+        limit: f64,
+    },
+    ShortNotional {
+        symbol: Option<Symbol>,
+        notional: f64,
+        limit: f64,
+    },
+    Margin {
+        required: f64,
+        available: f64,
+    },
+}
+
+/// Collateral against the margin open positions and orders require
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MarginSummary {
+    pub balance: f64,
+    pub unrealized_pnl: f64,
+    /// Balance plus unrealized PnL
+    pub equity: f64,
+    pub required: f64,
+    /// Collateral positions need to stay open
+    pub maintenance: f64,
+    /// Equity left after the requirement; negative when under-margined
+    pub free: f64,
+    pub method: MarginMethod,
+    /// Scenario that set the cross positions' requirement, on portfolio margin
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scenario: Option<StressScenario>,
+}
+
+/// Margin one position needs under its symbol's schedule
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct PositionMargin {
+    pub initial: f64,
+    pub maintenance: f64,
+}
+
+/// Initial margin an order adds against the free margin there is for it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OrderMargin {
+    /// Order quantity at the mark
+    pub notional: f64,
+    /// Extra initial margin the account needs once the order fills in full;
+    /// zero for orders that only reduce exposure
+    pub required: f64,
+    /// Free margin before the order
+    pub available: f64,
+}
+
+impl OrderMargin {
+    pub fn sufficient(&self) -> bool {
+        self.required <= 0.0 || self.required <= self.available
+    }
+}
+
+/// Exposure of an account's open positions at their marks
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PortfolioMetrics {
+    /// Sum of absolute position notionals
+    pub gross_exposure: f64,
+    /// Long minus short notional
+    pub net_exposure: f64,
+    /// Gross exposure over equity; None without positive equity
+    pub leverage: Option<f64>,
+    /// Largest position as a fraction of gross exposure
+    pub concentration: Option<f64>,
+    pub largest_position: Option<Symbol>,
+}
+
+/// Margin and limit breaches of one account
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RiskReport {
+    pub metrics: PortfolioMetrics,
+    pub margin: MarginSummary,
+    pub breaches: Vec<Breach>,
+}
+
+/// A hypothetical change to one position
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PositionChange {
+    pub symbol: Symbol,
+    /// Signed quantity to add, negative to sell
+    pub quantity: f64,
+    /// Price the change is assumed to trade at; the mark when absent
+    #[serde(default)]
+    pub price: Option<f64>,
+}
+
+impl PositionChange {
+    /// `order` filled in full, at its limit or stop price when it has one
+    pub fn from_order(order: &Order) -> Self {
+        let quantity = order.remaining_quantity.value();
+        Self {
+            symbol: order.symbol.clone(),
+            quantity: match order.side {
+                OrderSide::Buy => quantity,
+                OrderSide::Sell => -quantity,
+            },
+            price: match order.order_type {
+                OrderType::Market => None,
+                OrderType::StopMarket => order.stop_price.map(|p| p.value()),
+                _ => Some(order.price.value()),
+            },
+        }
+    }
+}
+
+/// Risk of an account before and after hypothetical changes
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WhatIf {
+    pub before: RiskReport,
+    pub after: RiskReport,
+}
+
+/// Risk of a set of orders checked together, e.g. a market maker's requote
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QuoteCheck {
+    /// Whether the whole set may be placed; it is accepted or rejected as one
+    pub accepted: bool,
+    /// Order-level breaches, by position in the set
+    pub orders: Vec<Vec<Breach>>,
+    /// Breaches of the account with the whole set resting
+    pub breaches: Vec<Breach>,
+    pub margin: MarginSummary,
+}
+
+/// Collateral one margin pool has against what it must keep
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MarginPool {
+    /// The isolated position's symbol; None for the account's cross pool
+    pub symbol: Option<Symbol>,
+    /// Balance or set-aside margin plus unrealized PnL
+    pub collateral: f64,
+    pub maintenance: f64,
+}
+
+impl MarginPool {
+    pub fn mode(&self) -> MarginMode {
+        match self.symbol {
+            Some(_) => MarginMode::Isolated,
+            None => MarginMode::Cross,
+        }
+    }
+
+    /// Whether `position` is backed by this pool
+    pub fn contains(&self, position: &Position) -> bool {
+        match &self.symbol {
+            Some(symbol) => position.is_isolated() && position.symbol == *symbol,
+            None => !position.is_isolated(),
+        }
+    }
+}
+
+/// Mark of `position`, falling back to its entry price
+fn mark_of(position: &Position, mark: &impl Fn(&Symbol) -> Option<f64>) -> f64 {
+    mark(&position.symbol).unwrap_or(position.entry_price)
+}
+
+/// Unrealized PnL at `price`; an isolated position cannot lose more than
+/// its margin
+fn capped_pnl(position: &Position, price: f64) -> f64 {
+    let pnl = position.quantity * (price - position.entry_price);
+    if position.is_isolated() {
+        pnl.max(-position.isolated_margin)
+    } else {
+        pnl
+    }
+}
+
+impl RiskLimits {
+    /// Use the margin schedules of `instruments` for their symbols
+    pub fn with_margin_tiers(mut self, instruments: &[Instrument]) -> Self {
+        for instrument in instruments.iter().filter(|i| !i.margin_tiers.is_empty()) {
+            let mut tiers = instrument.margin_tiers.clone();
+            tiers.sort_by(|a, b| {
+                let bound = |tier: &MarginTier| tier.max_notional.unwrap_or(f64::INFINITY);
+                bound(a).total_cmp(&bound(b))
+            });
+            self.margin_tiers.insert(instrument.symbol.clone(), tiers);
+        }
+        self
+    }
+
+    /// Margin of a position in `symbol` worth `notional` at its mark
+    ///
+    /// Tiered symbols charge each slice of the notional at its tier's rates,
+    /// with anything past the last bounded tier at that tier's rates.
+    pub fn position_margin(&self, symbol: &Symbol, notional: f64) -> PositionMargin {
+        let notional = notional.abs();
+        let tiers = match self.margin_tiers.get(symbol) {
+            Some(tiers) if !tiers.is_empty() => tiers,
+            _ => {
+                return PositionMargin {
+                    initial: notional * self.initial_margin_rate,
+                    maintenance: notional * self.maintenance_margin_rate,
+                }
+            }
+        };
+        let mut margin = PositionMargin {
+            initial: 0.0,
+            maintenance: 0.0,
+        };
+        let mut lower = 0.0;
+        for (i, tier) in tiers.iter().enumerate() {
+            let upper = match tier.max_notional {
+                Some(bound) if i + 1 < tiers.len() => bound,
+                _ => f64::INFINITY,
+            };
+            let slice = notional.min(upper) - lower;
+            if slice <= 0.0 {
+                break;
+            }
+            margin.initial += slice * tier.initial_margin_rate;
+            margin.maintenance += slice * tier.maintenance_margin_rate;
+            lower = upper;
+        }
+        margin
+    }
+
+    /// Factor caps are scaled by at `volatility`; 1 without a scaling or an estimate
+    pub fn volatility_factor(&self, volatility: Option<f64>) -> f64 {
+        match (&self.volatility_scaling, volatility) {
+            (Some(scaling), Some(volatility)) => scaling.factor(volatility),
+            _ => 1.0,
+        }
+    }
+
+    /// These limits with their order and position caps scaled for `volatility`
+    pub fn scaled(&self, volatility: Option<f64>) -> RiskLimits {
+        let factor = self.volatility_factor(volatility);
+        RiskLimits {
+            max_order_quantity: self.max_order_quantity.map(|limit| limit * factor),
+            max_order_notional: self.max_order_notional.map(|limit| limit * factor),
+            max_position_notional: self.max_position_notional.map(|limit| limit * factor),
+            ..self.clone()
+        }
+    }
+
+    /// Caps and price band in force for `symbol` at `volatility`
+    pub fn effective(
+        &self,
+        symbol: &Symbol,
+        volatility: Option<f64>,
+        band: Option<&PriceBand>,
+    ) -> EffectiveLimits {
+        let factor = self.volatility_factor(volatility);
+        let scaled = self.scaled(volatility);
+        EffectiveLimits {
+            symbol: symbol.clone(),
+            volatility,
+            factor,
+            max_order_quantity: scaled.max_order_quantity,
+            max_order_notional: scaled.max_order_notional,
+            max_position_notional: scaled.max_position_notional,
+            band_percent: band.map(|band| band.percent / factor),
+        }
+    }
+
+    /// Order-level limits, with `notional` the order's expected value
+    pub fn check_order(&self, order: &Order, notional: f64) -> Vec<Breach> {
+        let mut breaches = Vec::new();
+        let quantity = order.remaining_quantity.value();
+        if let Some(limit) = self.max_order_quantity {
+            if quantity > limit {
+                breaches.push(Breach::OrderQuantity { quantity, limit });
+            }
+        }
+        if let Some(limit) = self.max_order_notional {
+            if notional > limit {
+                breaches.push(Breach::OrderNotional { notional, limit });
+            }
+        }
+        breaches
+    }
+
+    /// Margin of `account`, plus `reserved` notional held by resting orders
+    ///
+    /// On portfolio margin the cross positions need their worst stress loss
+    /// instead of their summed position margins; isolated positions and
+    /// resting orders are margined the standard way either way.
+    pub fn margin(
+        &self,
+        account: &Account,
+        mark: impl Fn(&Symbol) -> Option<f64>,
+        reserved: f64,
+    ) -> MarginSummary {
+        let portfolio = account.margin_method == MarginMethod::Portfolio;
+        let mut unrealized_pnl = 0.0;
+        let mut required = reserved * self.initial_margin_rate;
+        let mut maintenance = 0.0;
+        let mut cross = Vec::new();
+        for position in account.open_positions() {
+            let price = mark_of(&position, &mark);
+            unrealized_pnl += capped_pnl(&position, price);
+            if portfolio && !position.is_isolated() {
+                cross.push((position.symbol, position.quantity * price));
+                continue;
+            }
+            let margin = self.position_margin(&position.symbol, position.quantity * price);
+            required += margin.initial;
+            maintenance += margin.maintenance;
+        }
+        let scenario = self.portfolio_margin.worst_case(&cross);
+        if let Some(scenario) = &scenario {
+            required += scenario.loss;
+            maintenance += scenario.loss * self.portfolio_margin.maintenance_ratio;
+        }
+        let equity = account.balance + unrealized_pnl;
+        MarginSummary {
+            balance: account.balance,
+            unrealized_pnl,
+            equity,
+            required,
+            maintenance,
+            free: equity - required,
+            method: account.margin_method,
+            scenario,
+        }
+    }
+
+    /// The account's cross pool, when it has cross positions, then one pool
+    /// per isolated position
+    ///
+    /// On portfolio margin the cross pool keeps the stress maintenance of
+    /// its positions together.
+    pub fn margin_pools(
+        &self,
+        account: &Account,
+        mark: impl Fn(&Symbol) -> Option<f64>,
+    ) -> Vec<MarginPool> {
+        let mut cross: Option<MarginPool> = None;
+        let mut isolated = Vec::new();
+        let mut cross_notionals = Vec::new();
+        for position in account.open_positions() {
+            let price = mark_of(&position, &mark);
+            let pnl = position.quantity * (price - position.entry_price);
+            let maintenance = self
+                .position_margin(&position.symbol, position.quantity * price)
+                .maintenance;
+            if position.is_isolated() {
+                isolated.push(MarginPool {
+                    symbol: Some(position.symbol),
+                    collateral: position.isolated_margin + pnl,
+                    maintenance,
+                });
+            } else {
+                let pool = cross.get_or_insert_with(|| MarginPool {
+                    symbol: None,
+                    collateral: account.cross_balance(),
+                    maintenance: 0.0,
+                });
+                pool.collateral += pnl;
+                pool.maintenance += maintenance;
+                cross_notionals.push((position.symbol, position.quantity * price));
+            }
+        }
+        if let (Some(pool), MarginMethod::Portfolio) = (&mut cross, account.margin_method) {
+            pool.maintenance = self
+                .portfolio_margin
+                .worst_case(&cross_notionals)
+                .map_or(0.0, |scenario| {
+                    scenario.loss * self.portfolio_margin.maintenance_ratio
+                });
+        }
+        cross.into_iter().chain(isolated).collect()
+    }
+
+    /// Mark at which `symbol`'s position in `account` would be liquidated
+    ///
+    /// An isolated position is backed by its own margin; a cross position by
+    /// the cross balance plus what the other cross positions have over their
+    /// maintenance margin, taken at current marks. The position must keep its
+    /// current effective maintenance rate plus the liquidation fee, so higher
+    /// leverage and higher fees both bring the price closer. None without an
+    /// open position or when no positive price would liquidate it.
+    pub fn liquidation_price(
+        &self,
+        account: &Account,
+        symbol: &Symbol,
+        mark: impl Fn(&Symbol) -> Option<f64>,
+    ) -> Option<f64> {
+        let position = account.positions.get(symbol).filter(|p| p.is_open())?;
+        let quantity = position.quantity;
+        let notional = (quantity * mark_of(position, &mark)).abs();
+        let rate = self.liquidation_fee_rate
+            + if notional > 0.0 {
+                self.position_margin(symbol, notional).maintenance / notional
+            } else {
+                self.maintenance_margin_rate
+            };
+
+        let collateral = if position.is_isolated() {
+            position.isolated_margin
+        } else {
+            let others: f64 = account
+                .open_positions()
+                .iter()
+                .filter(|other| !other.is_isolated() && other.symbol != *symbol)
+                .map(|other| {
+                    let price = mark_of(other, &mark);
+                    other.quantity * (price - other.entry_price)
+                        - self
+                            .position_margin(&other.symbol, other.quantity * price)
+                            .maintenance
+                })
+                .sum();
+            account.cross_balance() + others
+        };
+
+        // Solve collateral + q * (p - entry) = rate * |q| * p for p
+        let price =
+            (quantity * position.entry_price - collateral) / (quantity - rate * quantity.abs());
+        (price.is_finite() && price > 0.0).then_some(price)
+    }
+
+    /// Store the liquidation price of every position of `account` on it
+    pub fn update_liquidation_prices(
+        &self,
+        account: &mut Account,
+        mark: impl Fn(&Symbol) -> Option<f64>,
+    ) {
+        let prices: Vec<(Symbol, Option<f64>)> = account
+            .positions
+            .keys()
+            .map(|symbol| {
+                (
+                    symbol.clone(),
+                    self.liquidation_price(account, symbol, &mark),
+                )
+            })
+            .collect();
+        for (symbol, price) in prices {
+            if let Some(position) = account.positions.get_mut(&symbol) {
+                position.liquidation_price = price;
+            }
+        }
+    }
+
+    /// Margin `order` needs before it is accepted for `account`
+    ///
+    /// The order is valued at the mark, or at its limit or stop price when the
+    /// symbol has none, and assumed to fill in full. Fails with the symbol when a
+    /// market order has no mark to value it at.
+    pub fn order_margin(
+        &self,
+        account: &Account,
+        order: &Order,
+        mark: impl Fn(&Symbol) -> Option<f64>,
+    ) -> Result<OrderMargin, Symbol> {
+        let price = mark(&order.symbol)
+            .or_else(|| match order.order_type {
+                OrderType::Market => None,
+                OrderType::StopMarket => order.stop_price.map(|p| p.value()),
+                _ => Some(order.price.value()),
+            })
+            .ok_or_else(|| order.symbol.clone())?;
+        let quantity = order.remaining_quantity.value();
+        let before = self.margin(account, &mark, 0.0);
+        let mut after = account.clone();
+        after.apply(Activity::Trade {
+            symbol: order.symbol.clone(),
+            side: order.side,
+            price,
+            quantity,
+            fee: 0.0,
+            timestamp: Utc::now(),
+            fill: None,
+        });
+        let after = self.margin(&after, &mark, 0.0);
+        Ok(OrderMargin {
+            notional: quantity * price,
+            required: (after.required - before.required).max(0.0),
+            available: before.free,
+        })
+    }
+
+    /// Exposure metrics of `account`, with leverage against its marked equity
+    pub fn metrics(
+        &self,
+        account: &Account,
+        mark: impl Fn(&Symbol) -> Option<f64>,
+    ) -> PortfolioMetrics {
+        let mut gross_exposure = 0.0;
+        let mut net_exposure = 0.0;
+        let mut largest: Option<(Symbol, f64)> = None;
+        for position in account.open_positions() {
+            let notional = position.quantity * mark_of(&position, &mark);
+            gross_exposure += notional.abs();
+            net_exposure += notional;
+            if largest
+                .as_ref()
+                .is_none_or(|(_, size)| notional.abs() > *size)
+            {
+                largest = Some((position.symbol, notional.abs()));
+            }
+        }
+        let equity = self.margin(account, &mark, 0.0).equity;
+        PortfolioMetrics {
+            gross_exposure,
+            net_exposure,
+            leverage: (equity > 0.0).then(|| gross_exposure / equity),
+            concentration: largest.as_ref().map(|(_, size)| size / gross_exposure),
+            largest_position: largest.map(|(symbol, _)| symbol),
+        }
+    }
+
+    /// Position and margin limits of `account` as it stands
+    pub fn check_account(
+        &self,
+        account: &Account,
+        mark: impl Fn(&Symbol) -> Option<f64>,
+        reserved: f64,
+    ) -> RiskReport {
+        let mut breaches = Vec::new();
+        if let Some(limit) = self.max_position_notional {
+            for position in account.open_positions() {
+                let notional = position.quantity.abs() * mark_of(&position, &mark);
+                if notional > limit {
+                    breaches.push(Breach::PositionNotional {
+                        symbol: position.symbol,
+                        notional,
+                        limit,
+                    });
+                }
+            }
+        }
+        breaches.extend(self.check_exposure(account, &mark, &[]));
+        let margin = self.margin(account, &mark, reserved);
+        if margin.free < 0.0 {
+            breaches.push(Breach::Margin {
+                required: margin.required,
+                available: margin.equity,
+            });
+        }
+        RiskReport {
+            metrics: self.metrics(account, &mark),
+            margin,
+            breaches,
+        }
+    }
+
+    /// Directional caps on `account` if every order in `resting` filled too
+    ///
+    /// Long and short exposure are capped separately, so a book can be
+    /// allowed to lean one way further than the other. Resting orders are
+    /// counted in full since any of them may fill; changes without a price
+    /// are valued at the mark.
+    pub fn check_exposure(
+        &self,
+        account: &Account,
+        mark: impl Fn(&Symbol) -> Option<f64>,
+        resting: &[PositionChange],
+    ) -> Vec<Breach> {
+        let mut exposure: BTreeMap<Symbol, (f64, f64)> = BTreeMap::new();
+        for position in account.open_positions() {
+            let price = mark_of(&position, &mark);
+            exposure.insert(position.symbol, (position.quantity, price));
+        }
+        for change in resting {
+            let price = mark(&change.symbol).or(change.price);
+            let entry = exposure.entry(change.symbol.clone()).or_default();
+            entry.0 += change.quantity;
+            if let Some(price) = price {
+                entry.1 = price;
+            }
+        }
+
+        let mut breaches = Vec::new();
+        let (mut total_long, mut total_short) = (0.0, 0.0);
+        for (symbol, (quantity, price)) in &exposure {
+            let notional = quantity * price;
+            let (long, short) = (notional.max(0.0), (-notional).max(0.0));
+            total_long += long;
+            total_short += short;
+            self.symbol_overrides
+                .get(symbol)
+                .unwrap_or(&self.symbol_exposure)
+                .check(Some(symbol), long, short, &mut breaches);
+        }
+        self.account_exposure
+            .check(None, total_long, total_short, &mut breaches);
+        breaches
+    }
+
+    /// Check `orders` as one set against `account`, without placing any
+    ///
+    /// Every order is valued at its limit price, or at the mark (then its
+    /// stop price) when it has none, and all of them reserve margin at once.
+    /// Directional caps are checked as if every buy filled, then as if every
+    /// sell did, since either side of a quote may be swept. Fails with the
+    /// symbol of an order that cannot be valued.
+    pub fn check_quotes(
+        &self,
+        account: &Account,
+        orders: &[Order],
+        mark: impl Fn(&Symbol) -> Option<f64>,
+    ) -> Result<QuoteCheck, Symbol> {
+        let mut reserved = 0.0;
+        let mut order_breaches = Vec::with_capacity(orders.len());
+        let (mut bids, mut asks) = (Vec::new(), Vec::new());
+        for order in orders {
+            let price = match order.order_type {
+                OrderType::Market | OrderType::StopMarket => {
+                    mark(&order.symbol).or_else(|| order.stop_price.map(|p| p.value()))
+                }
+                _ => Some(order.price.value()),
+            }
+            .ok_or_else(|| order.symbol.clone())?;
+            let quantity = order.remaining_quantity.value();
+            reserved += quantity * price;
+            order_breaches.push(self.check_order(order, quantity * price));
+            let (changes, quantity) = match order.side {
+                OrderSide::Buy => (&mut bids, quantity),
+                OrderSide::Sell => (&mut asks, -quantity),
+            };
+            changes.push(PositionChange {
+                symbol: order.symbol.clone(),
+                quantity,
+                price: Some(price),
+            });
+        }
+
+        let report = self.check_account(account, &mark, reserved);
+        let mut breaches: Vec<Breach> = report
+            .breaches
+            .into_iter()
+            .filter(|breach| {
+                !matches!(
+                    breach,
+                    Breach::LongNotional { .. } | Breach::ShortNotional { .. }
+                )
+            })
+            .collect();
+        for side in [&bids, &asks] {
+            for breach in self.check_exposure(account, &mark, side) {
+                if !breaches.contains(&breach) {
+                    breaches.push(breach);
+                }
+            }
+        }
+
+        Ok(QuoteCheck {
+            accepted: breaches.is_empty() && order_breaches.iter().all(Vec::is_empty),
+            orders: order_breaches,
+            breaches,
+            margin: report.margin,
+        })
+    }
+
+    /// Reports on `account` with and without `changes`, leaving it untouched
+    ///
+    /// Fails with the symbol of a change that has neither a price nor a mark.
+    pub fn what_if(
+        &self,
+        account: &Account,
+        changes: &[PositionChange],
+        mark: impl Fn(&Symbol) -> Option<f64>,
+    ) -> Result<WhatIf, Symbol> {
+        let mut after = account.clone();
+        let timestamp = Utc::now();
+        for change in changes.iter().filter(|change| change.quantity != 0.0) {
+            let price = change
+                .price
+                .or_else(|| mark(&change.symbol))
+                .ok_or_else(|| change.symbol.clone())?;
+            after.apply(Activity::Trade {
+                symbol: change.symbol.clone(),
+                side: if change.quantity > 0.0 {
+                    OrderSide::Buy
+                } else {
+                    OrderSide::Sell
+                },
+                price,
+                quantity: change.quantity.abs(),
+                fee: 0.0,
+                timestamp,
+                fill: None,
+            });
+        }
+        Ok(WhatIf {
+            before: self.check_account(account, &mark, 0.0),
+            after: self.check_account(&after, &mark, 0.0),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testkit;
+
+    fn account(balance: f64, side: OrderSide, price: f64, quantity: f64) -> Account {
+        testkit::account("alice")
+            .deposit(balance)
+            .trade("BTCUSDT", side, quantity, price, 0.0)
+            .build()
+    }
+
+    #[test]
+    fn test_margin_marks_positions_and_reserves_resting_orders() {
+        let limits = RiskLimits::default();
+        let account = account(1_000.0, OrderSide::Sell, 100.0, 10.0);
+
+        let margin = limits.margin(&account, |_| Some(90.0), 500.0);
+        assert_eq!(margin.unrealized_pnl, 100.0);
+        assert_eq!(margin.equity, 1_100.0);
+        assert!((margin.required - 140.0).abs() < 1e-9);
+
+        // Without a mark the position is valued at entry
+        let margin = limits.margin(&account, |_| None, 0.0);
+        assert_eq!((margin.unrealized_pnl, margin.required), (0.0, 100.0));
+    }
+
+    #[test]
+    fn test_quotes_are_checked_as_one_set() {
+        let limits = RiskLimits {
+            max_order_quantity: Some(5.0),
+            symbol_exposure: DirectionalLimits {
+                max_long_notional: Some(500.0),
+                max_short_notional: None,
+            },
+            ..RiskLimits::default()
+        };
+        let account = account(1_000.0, OrderSide::Buy, 100.0, 1.0);
+        let bid = |price: f64| Order::new_limit("BTCUSDT", OrderSide::Buy, price, 2.0);
+
+        // Each bid fits under the long cap alone, but not all three together
+        let quotes = [bid(99.0), bid(98.0), bid(97.0)];
+        for quote in &quotes {
+            let single = limits.check_quotes(&account, std::slice::from_ref(quote), |_| None);
+            assert!(single.unwrap().accepted);
+        }
+        let mut quotes = quotes.to_vec();
+        quotes.push(Order::new_limit("BTCUSDT", OrderSide::Sell, 101.0, 6.0));
+        let check = limits.check_quotes(&account, &quotes, |_| None).unwrap();
+        assert!(!check.accepted);
+        assert!(matches!(check.breaches[..], [Breach::LongNotional { .. }]));
+        assert!(check.orders[..3].iter().all(Vec::is_empty));
+        assert!(matches!(
+            check.orders[3][..],
+            [Breach::OrderQuantity { .. }]
+        ));
+        assert!((check.margin.required - (10.0 + 0.1 * (588.0 + 606.0))).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_limits_scale_with_volatility() {
+        let limits = RiskLimits {
+            max_order_quantity: Some(10.0),
+            max_position_notional: Some(100_000.0),
+            volatility_scaling: Some(VolatilityScaling::Inverse {
+                reference_volatility: 0.01,
+                min_factor: 0.25,
+                max_factor: 2.0,
+            }),
+            ..RiskLimits::default()
+        };
+        let btc = Symbol::from("BTCUSDT");
+        let band = PriceBand {
+            percent: 5.0,
+            volatile_multiplier: 2.0,
+            volatility_trigger: None,
+            widened_until: None,
+        };
+
+        // Twice the reference volatility halves the caps and doubles the band
+        let effective = limits.effective(&btc, Some(0.02), Some(&band));
+        assert_eq!(effective.factor, 0.5);
+        assert_eq!(effective.max_order_quantity, Some(5.0));
+        assert_eq!(effective.max_position_notional, Some(50_000.0));
+        assert_eq!(effective.band_percent, Some(10.0));
+        assert_eq!(limits.volatility_factor(Some(0.0001)), 2.0);
+        assert_eq!(limits.volatility_factor(None), 1.0);
+
+        let steps = VolatilityScaling::Steps {
+            steps: vec![
+                VolatilityStep {
+                    max_volatility: 0.01,
+                    factor: 1.0,
+                },
+                VolatilityStep {
+                    max_volatility: 0.03,
+                    factor: 0.5,
+                },
+            ],
+        };
+        assert_eq!((steps.factor(0.02), steps.factor(0.1)), (0.5, 0.5));
+    }
+
+    #[test]
+    fn test_check_account_reports_every_breach() {
+        let limits = RiskLimits {
+            max_position_notional: Some(5_000.0),
+            initial_margin_rate: 0.5,
+            ..RiskLimits::default()
+        };
+        let account = account(2_000.0, OrderSide::Buy, 100.0, 60.0);
+
+        let report = limits.check_account(&account, |_| Some(100.0), 0.0);
+        assert_eq!(
+            report.breaches,
+            [
+                Breach::PositionNotional {
+                    symbol: Symbol::from("BTCUSDT"),
+                    notional: 6_000.0,
+                    limit: 5_000.0,
+                },
+                Breach::Margin {
+                    required: 3_000.0,
+                    available: 2_000.0,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_what_if_reports_before_and_after_without_changing_account() {
+        let limits = RiskLimits::default();
+        let account = account(1_000.0, OrderSide::Buy, 100.0, 30.0);
+        let mark = |symbol: &Symbol| (symbol.as_str() == "BTCUSDT").then_some(100.0);
+
+        let changes = [
+            PositionChange {
+                symbol: Symbol::from("BTCUSDT"),
+                quantity: -10.0,
+                price: None,
+            },
+            PositionChange {
+                symbol: Symbol::from("ETHUSDT"),
+                quantity: -50.0,
+                price: Some(20.0),
+            },
+        ];
+        let what_if = limits.what_if(&account, &changes, mark).unwrap();
+        assert_eq!(what_if.before.metrics.gross_exposure, 3_000.0);
+        assert_eq!(what_if.before.metrics.leverage, Some(3.0));
+        assert_eq!(what_if.after.metrics.gross_exposure, 3_000.0);
+        assert_eq!(what_if.after.metrics.net_exposure, 1_000.0);
+        assert_eq!(what_if.after.metrics.concentration, Some(2.0 / 3.0));
+        assert_eq!(
+            what_if.after.metrics.largest_position,
+            Some(Symbol::from("BTCUSDT"))
+        );
+        assert!(what_if.after.breaches.is_empty());
+        assert_eq!(account.positions["BTCUSDT"].quantity, 30.0);
+
+        let unpriced = [PositionChange {
+            symbol: Symbol::from("SOLUSDT"),
+            quantity: 1.0,
+            price: None,
+        }];
+        assert_eq!(
+            limits.what_if(&account, &unpriced, mark),
+            Err(Symbol::from("SOLUSDT"))
+        );
+    }
+
+    #[test]
+    fn test_long_and_short_caps_are_separate() {
+        let limits = RiskLimits {
+            symbol_exposure: DirectionalLimits {
+                max_long_notional: Some(5_000.0),
+                max_short_notional: Some(1_000.0),
+            },
+            account_exposure: DirectionalLimits {
+                max_long_notional: Some(4_000.0),
+                max_short_notional: None,
+            },
+            ..RiskLimits::default()
+        };
+        let long = account(10_000.0, OrderSide::Buy, 100.0, 45.0);
+        let report = limits.check_account(&long, |_| Some(100.0), 0.0);
+        assert_eq!(
+            report.breaches,
+            [Breach::LongNotional {
+                symbol: None,
+                notional: 4_500.0,
+                limit: 4_000.0,
+            }]
+        );
+
+        // The same size short breaches the tighter short cap instead
+        let short = account(10_000.0, OrderSide::Sell, 100.0, 5.0);
+        assert!(limits
+            .check_account(&short, |_| Some(100.0), 0.0)
+            .breaches
+            .is_empty());
+        let resting = [PositionChange {
+            symbol: Symbol::from("BTCUSDT"),
+            quantity: -6.0,
+            price: Some(100.0),
+        }];
+        assert_eq!(
+            limits.check_exposure(&short, |_| Some(100.0), &resting),
+            [Breach::ShortNotional {
+                symbol: Some(Symbol::from("BTCUSDT")),
+                notional: 1_100.0,
+                limit: 1_000.0,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_order_margin_only_charges_added_exposure() {
+        let limits = RiskLimits::default();
+        let account = account(1_000.0, OrderSide::Buy, 100.0, 50.0);
+        let mark = |_: &Symbol| Some(100.0);
+
+        let buy = Order::new_limit("BTCUSDT", OrderSide::Buy, 100.0, 60.0);
+        let margin = limits.order_margin(&account, &buy, mark).unwrap();
+        assert_eq!((margin.required, margin.available), (600.0, 500.0));
+        assert!(!margin.sufficient());
+
+        // Reducing the position frees margin instead of using it
+        let sell = Order::new_market("BTCUSDT", OrderSide::Sell, 20.0);
+        let margin = limits.order_margin(&account, &sell, mark).unwrap();
+        assert_eq!((margin.notional, margin.required), (2_000.0, 0.0));
+        assert!(margin.sufficient());
+        assert_eq!(
+            limits.order_margin(&account, &sell, |_| None),
+            Err(Symbol::from("BTCUSDT"))
+        );
+    }
+
+    #[test]
+    fn test_tiers_charge_larger_positions_more() {
+        let tier = |max_notional, initial_margin_rate, maintenance_margin_rate| MarginTier {
+            max_notional,
+            initial_margin_rate,
+            maintenance_margin_rate,
+        };
+        let instrument = Instrument {
+            symbol: Symbol::from("BTCUSDT"),
+            base: "BTC".into(),
+            quote: "USDT".into(),
+            tick_size: 0.01,
+            lot_size: 0.001,
+            price_precision: 2,
+            quantity_precision: 3,
+            status: Default::default(),
+            margin_tiers: vec![
+                tier(Some(10_000.0), 0.2, 0.1),
+                tier(Some(1_000.0), 0.1, 0.05),
+            ],
+            price_band: None,
+        };
+        let limits = RiskLimits::default().with_margin_tiers(&[instrument]);
+        let btc = Symbol::from("BTCUSDT");
+
+        assert_eq!(
+            limits.position_margin(&btc, -500.0),
+            PositionMargin {
+                initial: 50.0,
+                maintenance: 25.0
+            }
+        );
+        // 1k at 10%, the next 9k at 20%, and the rest at the last tier's 20%
+        let margin = limits.position_margin(&btc, 20_000.0);
+        assert!((margin.initial - 3_900.0).abs() < 1e-9);
+        assert!((margin.maintenance - 1_950.0).abs() < 1e-9);
+        // Untiered symbols keep the flat rates
+        let eth = limits.position_margin(&Symbol::from("ETHUSDT"), 20_000.0);
+        assert_eq!((eth.initial, eth.maintenance), (2_000.0, 1_000.0));
+
+        let account = account(10_000.0, OrderSide::Buy, 100.0, 200.0);
+        let margin = limits.margin(&account, |_| Some(100.0), 0.0);
+        assert!((margin.required - 3_900.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_isolated_positions_cap_losses_and_liquidate_sooner() {
+        let limits = RiskLimits::default();
+        let btc = Symbol::from("BTCUSDT");
+        let mut account = account(500.0, OrderSide::Buy, 100.0, 10.0);
+
+        // Cross: the whole balance backs the position
+        let cross = limits
+            .liquidation_price(&account, &btc, |_| Some(100.0))
+            .unwrap();
+        assert!((cross - 500.0 / 9.45).abs() < 1e-9);
+
+        account.apply(Activity::Margin {
+            symbol: btc.clone(),
+            mode: MarginMode::Isolated,
+            amount: 200.0,
+            timestamp: Utc::now(),
+        });
+        limits.update_liquidation_prices(&mut account, |_| Some(100.0));
+        let isolated = account.positions[&btc].liquidation_price.unwrap();
+        assert!((isolated - 800.0 / 9.45).abs() < 1e-9);
+
+        // A crash takes at most the set-aside margin out of equity
+        let margin = limits.margin(&account, |_| Some(50.0), 0.0);
+        assert_eq!(margin.equity, 300.0);
+        let pools = limits.margin_pools(&account, |_| Some(50.0));
+        assert_eq!(pools.len(), 1);
+        assert_eq!(pools[0].mode(), MarginMode::Isolated);
+        assert_eq!(pools[0].collateral, -300.0);
+    }
+
+    #[test]
+    fn test_portfolio_margin_nets_correlated_hedges() {
+        let limits = RiskLimits {
+            portfolio_margin: PortfolioMargin {
+                correlations: BTreeMap::from([(Symbol::from("ETHUSDT"), 0.9)]),
+                default_correlation: 0.9,
+                ..PortfolioMargin::default()
+            },
+            ..RiskLimits::default()
+        };
+        // Long 1000 of BTC against short 1000 of ETH
+        let mut account = testkit::account("alice")
+            .deposit(1_000.0)
+            .trade("BTCUSDT", OrderSide::Buy, 10.0, 100.0, 0.0)
+            .trade("ETHUSDT", OrderSide::Sell, 10.0, 100.0, 0.0)
+            .build();
+        let standard = limits.margin(&account, |_| Some(100.0), 0.0);
+        assert!((standard.required - 200.0).abs() < 1e-9);
+        assert_eq!(standard.scenario, None);
+
+        account.apply(Activity::MarginMethod {
+            method: MarginMethod::Portfolio,
+            timestamp: Utc::now(),
+        });
+        let portfolio = limits.margin(&account, |_| Some(100.0), 0.0);
+        // Only residual risk is left: sqrt(2) * 1000 * sqrt(1 - 0.81) * 12.5%
+        let residual = 2f64.sqrt() * 1_000.0 * 0.19f64.sqrt() * 0.125;
+        let scenario = portfolio.scenario.unwrap();
+        assert!((portfolio.required - residual).abs() < 1e-9);
+        assert_eq!(scenario.vol_factor, 1.25);
+        assert!((portfolio.maintenance - residual / 2.0).abs() < 1e-9);
+
+        // A lone position needs the whole shock on its notional
+        let lone = limits
+            .portfolio_margin
+            .worst_case(&[(Symbol::from("BTCUSDT"), 1_000.0)])
+            .unwrap();
+        assert!((lone.loss - 125.0).abs() < 1e-9);
+        assert!(lone.price_move < 0.0);
+    }
+
+    #[test]
+    fn test_check_order_limits() {
+        let limits = RiskLimits {
+            max_order_quantity: Some(1.0),
+            max_order_notional: Some(1_000.0),
+            ..RiskLimits::default()
+        };
+        let order = Order::new_limit("BTCUSDT", OrderSide::Buy, 100.0, 2.0);
+        assert_eq!(limits.check_order(&order, 200.0).len(), 1);
+        assert_eq!(limits.check_order(&order, 2_000.0).len(), 2);
+    }
+}
@@ -0,0 +1,208 @@
+// Intraday journal of account risk
+//
+// Every few minutes each account's VaR, exposure, leverage and
+// concentration are captured into a snapshot, so how risk built up and came
+// down over a trading day can be reviewed and charted afterwards. Snapshots
+// are kept per account under a retention; with a spill dir the evicted
+// ones go to `risk-<account>.jsonl` and range queries still find them.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::account::AccountId;
+use crate::risk::PortfolioMetrics;
+use crate::types::Symbol;
+use crate::utils::{BoundedHistory, Retention, Timestamped};
+use crate::var::VarReport;
+
+/// Risk of one account at one moment
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RiskSnapshot {
+    pub account: AccountId,
+    pub timestamp: DateTime<Utc>,
+    pub equity: f64,
+    pub var: f64,
+    pub lvar: f64,
+    #[serde(default)]
+    pub expected_shortfall: Option<f64>,
+    pub gross_exposure: f64,
+    pub net_exposure: f64,
+    #[serde(default)]
+    pub leverage: Option<f64>,
+    #[serde(default)]
+    pub concentration: Option<f64>,
+    #[serde(default)]
+    pub largest_position: Option<Symbol>,
+}
+
+impl RiskSnapshot {
+    pub fn new(
+        account: AccountId,
+        timestamp: DateTime<Utc>,
+        equity: f64,
+        metrics: PortfolioMetrics,
+        var: &VarReport,
+    ) -> Self {
+        Self {
+            account,
+            timestamp,
+            equity,
+            var: var.var,
+            lvar: var.lvar,
+            expected_shortfall: var.metrics.as_ref().map(|m| m.expected_shortfall),
+            gross_exposure: metrics.gross_exposure,
+            net_exposure: metrics.net_exposure,
+            leverage: metrics.leverage,
+            concentration: metrics.concentration,
+            largest_position: metrics.largest_position,
+        }
+    }
+}
+
+impl Timestamped for RiskSnapshot {
+    fn timestamp_ms(&self) -> i64 {
+        self.timestamp.timestamp_millis()
+    }
+}
+
+/// Risk snapshots per account, oldest first
+#[derive(Debug)]
+pub struct RiskJournal {
+    retention: Retention,
+    histories: HashMap<AccountId, BoundedHistory<RiskSnapshot>>,
+}
+
+impl RiskJournal {
+    pub fn new(retention: Retention) -> Self {
+        Self {
+            retention,
+            histories: HashMap::new(),
+        }
+    }
+
+    pub fn record(&mut self, snapshot: RiskSnapshot) {
+        let retention = &self.retention;
+        let history = self
+            .histories
+            .entry(snapshot.account.clone())
+            .or_insert_with(|| {
+                BoundedHistory::new(&format!("risk-{}", snapshot.account), retention.clone())
+            });
+        history.push(snapshot);
+        // Evicted snapshots must be readable as soon as they leave memory
+        if let Err(e) = history.flush() {
+            tracing::warn!("Failed to flush spilled risk snapshots: {}", e);
+        }
+    }
+
+    /// Snapshots of `account` taken within `from..=to`, oldest first
+    pub fn snapshots(
+        &mut self,
+        account: &AccountId,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Vec<RiskSnapshot> {
+        self.histories
+            .get_mut(account)
+            .map_or_else(Vec::new, |history| {
+                history.scan(|snapshot| snapshot.timestamp >= from && snapshot.timestamp <= to)
+            })
+    }
+}
+
+impl Default for RiskJournal {
+    /// A day of snapshots taken every minute
+    fn default() -> Self {
+        Self::new(Retention::count(24 * 60))
+    }
+}
+
+/// Thread-safe wrapper for RiskJournal
+#[derive(Default)]
+pub struct SharedRiskJournal {
+    inner: Arc<Mutex<RiskJournal>>,
+}
+
+impl SharedRiskJournal {
+    pub fn new(journal: RiskJournal) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(journal)),
+        }
+    }
+
+    pub fn record(&self, snapshot: RiskSnapshot) {
+        self.inner.lock().unwrap().record(snapshot)
+    }
+
+    pub fn snapshots(
+        &self,
+        account: &AccountId,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Vec<RiskSnapshot> {
+        self.inner.lock().unwrap().snapshots(account, from, to)
+    }
+}
+
+impl Clone for SharedRiskJournal {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::risk::RiskLimits;
+    use crate::testkit::{self, golden_time};
+    use crate::types::OrderSide;
+    use crate::var::{value_at_risk, SymbolMarket, VarConfig};
+
+    #[test]
+    fn test_journal_keeps_snapshots_per_account_and_spills_old_ones() {
+        let dir = std::env::temp_dir().join(format!("risk-journal-{}", std::process::id()));
+        let mut journal = RiskJournal::new(Retention::count(2).with_spill_dir(&dir));
+        let account = testkit::account("alice")
+            .deposit(1_000.0)
+            .trade("BTCUSDT", OrderSide::Buy, 1.0, 100.0, 0.0)
+            .build();
+        let var = value_at_risk(&account, &VarConfig::default(), |_| SymbolMarket {
+            daily_volatility: Some(0.02),
+            ..Default::default()
+        });
+        let limits = RiskLimits::default();
+        let start = golden_time();
+        for minutes in 0..4 {
+            let mark = 100.0 + minutes as f64;
+            let metrics = limits.metrics(&account, |_| Some(mark));
+            journal.record(RiskSnapshot::new(
+                account.id.clone(),
+                start + chrono::Duration::minutes(minutes),
+                1_000.0 + mark - 100.0,
+                metrics,
+                &var,
+            ));
+        }
+
+        let all = journal.snapshots(&account.id, start, start + chrono::Duration::hours(1));
+        assert_eq!(all.len(), 4);
+        assert_eq!(all[0].timestamp, start);
+        assert_eq!(all[3].gross_exposure, 103.0);
+        assert_eq!(all[0].largest_position, Some(Symbol::from("BTCUSDT")));
+        let later = journal.snapshots(
+            &account.id,
+            start + chrono::Duration::minutes(2),
+            start + chrono::Duration::hours(1),
+        );
+        assert_eq!(later.len(), 2);
+        assert!(journal
+            .snapshots(&AccountId("bob".to_string()), start, start)
+            .is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
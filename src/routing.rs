@@ -0,0 +1,298 @@
+// Smart order routing across venues
+//
+// Venue connectors report how long each order took to be acknowledged; the
+// router keeps a latency ring per venue and, for time-sensitive orders, only
+// considers venues whose p99 ack latency fits the order's budget. Every
+// decision is kept with the candidates it was chosen from so transaction
+// cost analysis can replay why a venue won.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::latency::{LatencySamples, LatencySummary};
+use crate::types::{Order, OrderId, OrderSide, Symbol};
+use crate::utils::{BoundedHistory, Retention, Timestamped};
+
+/// Ack latencies kept per venue
+const ACK_SAMPLES: usize = 1_024;
+
+/// Liquidity a venue offers for an order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VenueQuote {
+    pub venue: String,
+    pub price: f64,
+    pub quantity: f64,
+}
+
+/// How much an order cares about reaching the venue quickly
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum Urgency {
+    #[default]
+    Normal,
+    /// Only venues whose p99 ack latency is within the budget qualify
+    TimeSensitive { budget_ms: f64 },
+}
+
+/// Why the router picked the venue it did
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RouteReason {
+    BestPrice,
+    BestPriceWithinBudget,
+    /// No venue met the budget; the fastest one was used instead
+    FastestOverBudget,
+    NoLiquidity,
+}
+
+/// One candidate venue as the router saw it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VenueEvaluation {
+    pub venue: String,
+    pub price: f64,
+    pub quantity: f64,
+    /// None until the venue has acknowledged an order
+    pub ack_p99_ms: Option<f64>,
+    pub within_budget: bool,
+}
+
+/// A routing decision, kept for transaction cost analysis
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoutingDecision {
+    pub order_id: OrderId,
+    pub symbol: Symbol,
+    pub side: OrderSide,
+    pub urgency: Urgency,
+    pub venue: Option<String>,
+    pub reason: RouteReason,
+    pub candidates: Vec<VenueEvaluation>,
+    pub timestamp_ms: i64,
+}
+
+impl Timestamped for RoutingDecision {
+    fn timestamp_ms(&self) -> i64 {
+        self.timestamp_ms
+    }
+}
+
+/// Routes orders to the best priced venue, within latency budgets
+#[derive(Debug)]
+pub struct SmartOrderRouter {
+    acks: HashMap<String, LatencySamples>,
+    decisions: BoundedHistory<RoutingDecision>,
+}
+
+impl Default for SmartOrderRouter {
+    fn default() -> Self {
+        Self::new(Retention::count(10_000))
+    }
+}
+
+impl SmartOrderRouter {
+    /// `retention` bounds the decision log; spilled to `routing-decisions.jsonl`
+    pub fn new(retention: Retention) -> Self {
+        Self {
+            acks: HashMap::new(),
+            decisions: BoundedHistory::new("routing-decisions", retention),
+        }
+    }
+
+    /// Record how long `venue` took to acknowledge an order
+    pub fn record_ack(&mut self, venue: &str, latency: Duration) {
+        self.acks
+            .entry(venue.to_string())
+            .or_insert_with(|| LatencySamples::new(ACK_SAMPLES))
+            .record_ns(latency.as_nanos() as u64);
+    }
+
+    pub fn ack_latency(&self, venue: &str) -> Option<LatencySummary> {
+        self.acks.get(venue)?.summary()
+    }
+
+    /// Ack latency of every venue that has reported one, by venue
+    pub fn ack_latencies(&self) -> BTreeMap<String, LatencySummary> {
+        self.acks
+            .iter()
+            .filter_map(|(venue, samples)| Some((venue.clone(), samples.summary()?)))
+            .collect()
+    }
+
+    /// Pick a venue for `order` among `quotes` and log the decision
+    pub fn route(
+        &mut self,
+        order: &Order,
+        quotes: &[VenueQuote],
+        urgency: Urgency,
+    ) -> RoutingDecision {
+        let candidates: Vec<VenueEvaluation> = quotes
+            .iter()
+            .filter(|quote| quote.quantity > 0.0)
+            .map(|quote| {
+                let ack_p99_ms = self
+                    .ack_latency(&quote.venue)
+                    .map(|summary| summary.p99_ns / 1_000_000.0);
+                let within_budget = match urgency {
+                    Urgency::Normal => true,
+                    Urgency::TimeSensitive { budget_ms } => {
+                        ack_p99_ms.is_some_and(|p99| p99 <= budget_ms)
+                    }
+                };
+                VenueEvaluation {
+                    venue: quote.venue.clone(),
+                    price: quote.price,
+                    quantity: quote.quantity,
+                    ack_p99_ms,
+                    within_budget,
+                }
+            })
+            .collect();
+
+        // Better price first; between equal prices the faster venue
+        let better = |a: &&VenueEvaluation, b: &&VenueEvaluation| {
+            let price = match order.side {
+                OrderSide::Buy => a.price.total_cmp(&b.price),
+                OrderSide::Sell => b.price.total_cmp(&a.price),
+            };
+            price.then(latency_order(a, b))
+        };
+        let best = candidates
+            .iter()
+            .filter(|candidate| candidate.within_budget)
+            .min_by(better);
+        let (venue, reason) = match (best, urgency) {
+            (Some(best), Urgency::Normal) => (Some(best.venue.clone()), RouteReason::BestPrice),
+            (Some(best), Urgency::TimeSensitive { .. }) => {
+                (Some(best.venue.clone()), RouteReason::BestPriceWithinBudget)
+            }
+            (None, _) => match candidates.iter().min_by(|a, b| latency_order(a, b)) {
+                Some(fastest) => (Some(fastest.venue.clone()), RouteReason::FastestOverBudget),
+                None => (None, RouteReason::NoLiquidity),
+            },
+        };
+
+        let decision = RoutingDecision {
+            order_id: order.id,
+            symbol: order.symbol.clone(),
+            side: order.side,
+            urgency,
+            venue,
+            reason,
+            candidates,
+            timestamp_ms: Utc::now().timestamp_millis(),
+        };
+        tracing::info!(
+            "Routed order #{} {} to {:?} ({:?})",
+            decision.order_id.0,
+            decision.symbol,
+            decision.venue,
+            decision.reason
+        );
+        self.decisions.push(decision.clone());
+        decision
+    }
+
+    /// Most recent decisions, oldest first
+    pub fn decisions(&self, limit: usize) -> Vec<RoutingDecision> {
+        self.decisions.latest(limit).cloned().collect()
+    }
+}
+
+/// Lower p99 first; venues without samples last
+fn latency_order(a: &VenueEvaluation, b: &VenueEvaluation) -> std::cmp::Ordering {
+    let p99 = |candidate: &VenueEvaluation| candidate.ack_p99_ms.unwrap_or(f64::INFINITY);
+    p99(a).total_cmp(&p99(b))
+}
+
+/// Thread-safe wrapper for SmartOrderRouter
+pub struct SharedOrderRouter {
+    inner: Arc<Mutex<SmartOrderRouter>>,
+}
+
+impl SharedOrderRouter {
+    pub fn new(router: SmartOrderRouter) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(router)),
+        }
+    }
+
+    pub fn record_ack(&self, venue: &str, latency: Duration) {
+        self.inner.lock().unwrap().record_ack(venue, latency)
+    }
+
+    pub fn ack_latencies(&self) -> BTreeMap<String, LatencySummary> {
+        self.inner.lock().unwrap().ack_latencies()
+    }
+
+    pub fn route(&self, order: &Order, quotes: &[VenueQuote], urgency: Urgency) -> RoutingDecision {
+        self.inner.lock().unwrap().route(order, quotes, urgency)
+    }
+
+    pub fn decisions(&self, limit: usize) -> Vec<RoutingDecision> {
+        self.inner.lock().unwrap().decisions(limit)
+    }
+}
+
+impl Clone for SharedOrderRouter {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl Default for SharedOrderRouter {
+    fn default() -> Self {
+        Self::new(SmartOrderRouter::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quote(venue: &str, price: f64) -> VenueQuote {
+        VenueQuote {
+            venue: venue.to_string(),
+            price,
+            quantity: 1.0,
+        }
+    }
+
+    #[test]
+    fn test_time_sensitive_orders_respect_budget() {
+        let mut router = SmartOrderRouter::default();
+        for _ in 0..10 {
+            router.record_ack("fast", Duration::from_millis(2));
+            router.record_ack("slow", Duration::from_millis(40));
+        }
+        let order = Order::new_limit("BTCUSDT", OrderSide::Buy, 101.0, 1.0);
+        let quotes = [
+            quote("slow", 100.0),
+            quote("fast", 100.5),
+            quote("new", 99.0),
+        ];
+
+        let decision = router.route(&order, &quotes, Urgency::Normal);
+        assert_eq!(decision.venue.as_deref(), Some("new"));
+        assert_eq!(decision.reason, RouteReason::BestPrice);
+
+        let urgent = Urgency::TimeSensitive { budget_ms: 10.0 };
+        let decision = router.route(&order, &quotes, urgent);
+        assert_eq!(decision.venue.as_deref(), Some("fast"));
+        assert_eq!(decision.reason, RouteReason::BestPriceWithinBudget);
+
+        let decision = router.route(&order, &quotes, Urgency::TimeSensitive { budget_ms: 1.0 });
+        assert_eq!(decision.venue.as_deref(), Some("fast"));
+        assert_eq!(decision.reason, RouteReason::FastestOverBudget);
+
+        assert_eq!(
+            router.route(&order, &[], urgent).reason,
+            RouteReason::NoLiquidity
+        );
+        assert_eq!(router.decisions(10).len(), 4);
+    }
+}
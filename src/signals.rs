@@ -0,0 +1,286 @@
+// Named signals published by indicators, analytics and risk monitors
+//
+// Producers publish without knowing who listens; strategies subscribe by
+// name and read the latest value per symbol. A bus can record everything it
+// sees so a backtest can replay the same signals against historical data.
+
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::Symbol;
+use crate::utils::{BoundedHistory, Retention, Timestamped};
+
+/// Subscribe to this name to receive every signal
+pub const ALL_SIGNALS: &str = "*";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum SignalValue {
+    Number(f64),
+    Flag(bool),
+}
+
+impl SignalValue {
+    /// Flags read as 1.0 or 0.0
+    pub fn as_f64(&self) -> f64 {
+        match *self {
+            SignalValue::Number(value) => value,
+            SignalValue::Flag(flag) => flag as u8 as f64,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Signal {
+    pub name: String,
+    pub symbol: Symbol,
+    pub value: SignalValue,
+    pub timestamp_ms: i64,
+}
+
+impl Signal {
+    pub fn number(name: &str, symbol: &str, value: f64, timestamp_ms: i64) -> Self {
+        Self {
+            name: name.to_string(),
+            symbol: Symbol::new(symbol),
+            value: SignalValue::Number(value),
+            timestamp_ms,
+        }
+    }
+
+    pub fn flag(name: &str, symbol: &str, flag: bool, timestamp_ms: i64) -> Self {
+        Self {
+            name: name.to_string(),
+            symbol: Symbol::new(symbol),
+            value: SignalValue::Flag(flag),
+            timestamp_ms,
+        }
+    }
+}
+
+impl Timestamped for Signal {
+    fn timestamp_ms(&self) -> i64 {
+        self.timestamp_ms
+    }
+}
+
+/// Latest signal per (name, symbol)
+#[derive(Debug, Clone, Default)]
+pub struct SignalSet {
+    latest: HashMap<(String, Symbol), Signal>,
+}
+
+impl SignalSet {
+    pub fn apply(&mut self, signal: Signal) {
+        self.latest
+            .insert((signal.name.clone(), signal.symbol.clone()), signal);
+    }
+
+    pub fn get(&self, name: &str, symbol: &str) -> Option<&Signal> {
+        self.latest.get(&(name.to_string(), Symbol::new(symbol)))
+    }
+
+    pub fn value(&self, name: &str, symbol: &str) -> Option<f64> {
+        self.get(name, symbol).map(|signal| signal.value.as_f64())
+    }
+
+    pub fn len(&self) -> usize {
+        self.latest.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.latest.is_empty()
+    }
+}
+
+/// Receiving end of a subscription, caching the latest value of each signal
+pub struct SignalSubscriber {
+    receiver: Receiver<Signal>,
+    latest: SignalSet,
+}
+
+impl SignalSubscriber {
+    /// Take every signal published since the last poll
+    pub fn poll(&mut self) -> &SignalSet {
+        while let Ok(signal) = self.receiver.try_recv() {
+            self.latest.apply(signal);
+        }
+        &self.latest
+    }
+}
+
+/// Recorded signals fed back in timestamp order
+#[derive(Debug, Clone)]
+pub struct SignalReplay {
+    signals: Vec<Signal>,
+    cursor: usize,
+    latest: SignalSet,
+}
+
+impl SignalReplay {
+    pub fn new(mut signals: Vec<Signal>) -> Self {
+        signals.sort_by_key(|signal| signal.timestamp_ms);
+        Self {
+            signals,
+            cursor: 0,
+            latest: SignalSet::default(),
+        }
+    }
+
+    /// Apply every signal at or before `timestamp_ms`
+    pub fn advance_to(&mut self, timestamp_ms: i64) -> &SignalSet {
+        while let Some(signal) = self.signals.get(self.cursor) {
+            if signal.timestamp_ms > timestamp_ms {
+                break;
+            }
+            self.latest.apply(signal.clone());
+            self.cursor += 1;
+        }
+        &self.latest
+    }
+}
+
+/// Routes published signals to subscribers by name
+#[derive(Debug, Default)]
+pub struct SignalBus {
+    subscribers: HashMap<String, Vec<Sender<Signal>>>,
+    recording: Option<BoundedHistory<Signal>>,
+}
+
+impl SignalBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep a copy of every published signal for replay
+    pub fn with_recording(self) -> Self {
+        self.with_recording_retention(Retention::default())
+    }
+
+    /// Record, keeping only what `retention` allows in memory
+    pub fn with_recording_retention(mut self, retention: Retention) -> Self {
+        self.recording = Some(BoundedHistory::new("signals", retention));
+        self
+    }
+
+    pub fn publish(&mut self, signal: Signal) {
+        for name in [signal.name.as_str(), ALL_SIGNALS] {
+            if let Some(senders) = self.subscribers.get_mut(name) {
+                // Dropped subscribers are removed on the next publish
+                senders.retain(|sender| sender.send(signal.clone()).is_ok());
+            }
+        }
+        if let Some(recording) = &mut self.recording {
+            recording.push(signal);
+        }
+    }
+
+    /// Subscribe to the given signal names, or [`ALL_SIGNALS`]
+    pub fn subscribe(&mut self, names: &[&str]) -> SignalSubscriber {
+        let (sender, receiver) = mpsc::channel();
+        for name in names {
+            self.subscribers
+                .entry(name.to_string())
+                .or_default()
+                .push(sender.clone());
+        }
+        SignalSubscriber {
+            receiver,
+            latest: SignalSet::default(),
+        }
+    }
+
+    /// Signals recorded so far, empty unless recording is enabled
+    pub fn recorded(&self) -> Vec<Signal> {
+        self.recording
+            .as_ref()
+            .map(|recording| recording.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+/// Thread-safe wrapper for SignalBus
+#[derive(Debug)]
+pub struct SharedSignalBus {
+    inner: Arc<Mutex<SignalBus>>,
+}
+
+impl SharedSignalBus {
+    pub fn new(bus: SignalBus) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(bus)),
+        }
+    }
+
+    pub fn publish(&self, signal: Signal) {
+        self.inner.lock().unwrap().publish(signal)
+    }
+
+    pub fn subscribe(&self, names: &[&str]) -> SignalSubscriber {
+        self.inner.lock().unwrap().subscribe(names)
+    }
+
+    pub fn recorded(&self) -> Vec<Signal> {
+        self.inner.lock().unwrap().recorded()
+    }
+}
+
+impl Clone for SharedSignalBus {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl Default for SharedSignalBus {
+    fn default() -> Self {
+        Self::new(SignalBus::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subscribers_receive_only_their_names() {
+        let bus = SharedSignalBus::default();
+        let mut rsi_only = bus.subscribe(&["rsi"]);
+        let mut everything = bus.subscribe(&[ALL_SIGNALS]);
+
+        bus.publish(Signal::number("rsi", "BTCUSDT", 71.0, 1));
+        bus.publish(Signal::flag("risk_halt", "BTCUSDT", true, 2));
+        bus.publish(Signal::number("rsi", "BTCUSDT", 65.0, 3));
+
+        let seen = rsi_only.poll();
+        assert_eq!(seen.len(), 1);
+        assert_eq!(seen.value("rsi", "BTCUSDT"), Some(65.0));
+        assert!(seen.get("risk_halt", "BTCUSDT").is_none());
+
+        let seen = everything.poll();
+        assert_eq!(seen.value("risk_halt", "BTCUSDT"), Some(1.0));
+        assert!(seen.value("rsi", "ETHUSDT").is_none());
+    }
+
+    #[test]
+    fn test_recorded_signals_replay_in_time_order() {
+        let bus = SharedSignalBus::new(SignalBus::new().with_recording());
+        bus.publish(Signal::number("vwap", "BTCUSDT", 101.0, 2_000));
+        bus.publish(Signal::number("vwap", "BTCUSDT", 100.0, 1_000));
+
+        let mut replay = SignalReplay::new(bus.recorded());
+        assert!(replay.advance_to(500).is_empty());
+        assert_eq!(
+            replay.advance_to(1_500).value("vwap", "BTCUSDT"),
+            Some(100.0)
+        );
+        assert_eq!(
+            replay.advance_to(2_000).value("vwap", "BTCUSDT"),
+            Some(101.0)
+        );
+    }
+}
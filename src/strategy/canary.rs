@@ -0,0 +1,366 @@
+use serde::{Deserialize, Serialize};
+
+use crate::backtest::runner::{MarketSnapshot, OrderIntent, Strategy};
+use crate::strategy::shadow::{ExecutionOutcome, ExecutionVenue};
+use crate::types::OrderSide;
+
+/// Which version of a strategy a sleeve runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Version {
+    Baseline,
+    Canary,
+}
+
+/// Where a canary deployment stands
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CanaryState {
+    Running,
+    /// The canary took over all capital
+    Promoted,
+    /// The baseline took back all capital
+    RolledBack,
+}
+
+/// How capital is split and when the canary is judged
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CanaryConfig {
+    pub capital: f64,
+    /// Share of capital the canary trades, e.g. 0.1 for a 90/10 split
+    pub canary_share: f64,
+    /// Snapshots to run before either decision is taken
+    pub min_snapshots: usize,
+    /// Promote once the canary's return beats the baseline's by this much
+    pub promote_edge: f64,
+    /// Roll back once the canary's return trails the baseline's by this much
+    pub rollback_gap: f64,
+    /// Roll back immediately when the canary draws down further than this
+    pub max_drawdown: f64,
+}
+
+impl Default for CanaryConfig {
+    fn default() -> Self {
+        Self {
+            capital: 10_000.0,
+            canary_share: 0.1,
+            min_snapshots: 1_000,
+            promote_edge: 0.01,
+            rollback_gap: 0.01,
+            max_drawdown: 0.05,
+        }
+    }
+}
+
+/// Performance of one version over its share of capital
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SleevePerformance {
+    pub capital: f64,
+    pub equity: f64,
+    pub total_return: f64,
+    pub max_drawdown: f64,
+    pub position: f64,
+    pub fills: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Sleeve {
+    capital: f64,
+    cash: f64,
+    position: f64,
+    peak: f64,
+    equity: f64,
+    max_drawdown: f64,
+    fills: usize,
+}
+
+impl Sleeve {
+    fn new(capital: f64) -> Self {
+        Self {
+            capital,
+            cash: capital,
+            peak: capital,
+            equity: capital,
+            ..Default::default()
+        }
+    }
+
+    fn apply(&mut self, side: OrderSide, outcome: &ExecutionOutcome) {
+        let Some(price) = outcome.average_price else {
+            return;
+        };
+        let notional = price * outcome.filled_quantity;
+        match side {
+            OrderSide::Buy => {
+                self.cash -= notional;
+                self.position += outcome.filled_quantity;
+            }
+            OrderSide::Sell => {
+                self.cash += notional;
+                self.position -= outcome.filled_quantity;
+            }
+        }
+        self.fills += 1;
+    }
+
+    fn mark(&mut self, mid: f64) {
+        self.equity = self.cash + self.position * mid;
+        self.peak = self.peak.max(self.equity);
+        if self.peak > 0.0 {
+            self.max_drawdown = self.max_drawdown.max((self.peak - self.equity) / self.peak);
+        }
+    }
+
+    fn total_return(&self) -> f64 {
+        if self.capital > 0.0 {
+            (self.equity - self.capital) / self.capital
+        } else {
+            0.0
+        }
+    }
+
+    fn performance(&self) -> SleevePerformance {
+        SleevePerformance {
+            capital: self.capital,
+            equity: self.equity,
+            total_return: self.total_return(),
+            max_drawdown: self.max_drawdown,
+            position: self.position,
+            fills: self.fills,
+        }
+    }
+}
+
+/// Side by side performance of a canary deployment
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CanaryReport {
+    pub state: CanaryState,
+    pub snapshots: usize,
+    pub baseline: SleevePerformance,
+    pub canary: SleevePerformance,
+}
+
+/// Two versions of a strategy trading split capital until one wins
+///
+/// Each version sizes its orders as if it had all the capital; its intents
+/// are scaled down to its share before execution and it sees its position
+/// scaled back up, so both versions behave exactly as they would alone.
+/// Once promoted or rolled back the surviving version takes over the other
+/// sleeve's cash and position and is the only one called from then on.
+pub struct Canary<S, E> {
+    baseline: S,
+    canary: S,
+    venue: E,
+    config: CanaryConfig,
+    sleeves: [Sleeve; 2],
+    state: CanaryState,
+    snapshots: usize,
+}
+
+impl<S: Strategy, E: ExecutionVenue> Canary<S, E> {
+    pub fn new(baseline: S, canary: S, venue: E, config: CanaryConfig) -> Self {
+        let share = config.canary_share.clamp(0.0, 1.0);
+        Self {
+            baseline,
+            canary,
+            venue,
+            sleeves: [
+                Sleeve::new(config.capital * (1.0 - share)),
+                Sleeve::new(config.capital * share),
+            ],
+            config,
+            state: CanaryState::Running,
+            snapshots: 0,
+        }
+    }
+
+    pub fn state(&self) -> CanaryState {
+        self.state
+    }
+
+    /// Drive the live versions with `snapshot`; returns the executed intents
+    pub fn on_snapshot(&mut self, snapshot: &MarketSnapshot) -> Vec<(Version, OrderIntent)> {
+        self.snapshots += 1;
+        let versions: &[Version] = match self.state {
+            CanaryState::Running => &[Version::Baseline, Version::Canary],
+            CanaryState::Promoted => &[Version::Canary],
+            CanaryState::RolledBack => &[Version::Baseline],
+        };
+
+        let mut executed = Vec::new();
+        for &version in versions {
+            let share = self.share(version);
+            if share <= 0.0 {
+                continue;
+            }
+            let (strategy, sleeve) = match version {
+                Version::Baseline => (&mut self.baseline, &mut self.sleeves[0]),
+                Version::Canary => (&mut self.canary, &mut self.sleeves[1]),
+            };
+            let Some(intent) = strategy.on_snapshot(snapshot, sleeve.position / share) else {
+                continue;
+            };
+            let scaled = OrderIntent {
+                side: intent.side,
+                quantity: intent.quantity * share,
+            };
+            let outcome = self.venue.execute(&scaled, snapshot);
+            sleeve.apply(scaled.side, &outcome);
+            executed.push((version, scaled));
+        }
+
+        if let Some(mid) = snapshot.mid_price() {
+            self.sleeves.iter_mut().for_each(|sleeve| sleeve.mark(mid));
+        }
+        self.evaluate();
+        executed
+    }
+
+    pub fn report(&self) -> CanaryReport {
+        CanaryReport {
+            state: self.state,
+            snapshots: self.snapshots,
+            baseline: self.sleeves[0].performance(),
+            canary: self.sleeves[1].performance(),
+        }
+    }
+
+    /// Share of capital `version` trades with, relative to the whole
+    fn share(&self, version: Version) -> f64 {
+        let sleeve = match version {
+            Version::Baseline => &self.sleeves[0],
+            Version::Canary => &self.sleeves[1],
+        };
+        if self.config.capital > 0.0 {
+            sleeve.capital / self.config.capital
+        } else {
+            0.0
+        }
+    }
+
+    fn evaluate(&mut self) {
+        if self.state != CanaryState::Running {
+            return;
+        }
+        let [baseline, canary] = &self.sleeves;
+        let edge = canary.total_return() - baseline.total_return();
+        let next = if canary.max_drawdown > self.config.max_drawdown {
+            CanaryState::RolledBack
+        } else if self.snapshots < self.config.min_snapshots {
+            return;
+        } else if edge >= self.config.promote_edge {
+            CanaryState::Promoted
+        } else if -edge >= self.config.rollback_gap {
+            CanaryState::RolledBack
+        } else {
+            return;
+        };
+
+        tracing::info!(
+            "Canary {:?} after {} snapshots (return edge {:.4})",
+            next,
+            self.snapshots,
+            edge
+        );
+        self.state = next;
+        // The surviving version trades all capital from here on
+        let [baseline, canary] = &mut self.sleeves;
+        let (winner, loser) = match next {
+            CanaryState::Promoted => (canary, baseline),
+            _ => (baseline, canary),
+        };
+        winner.capital += loser.capital;
+        winner.cash += loser.cash;
+        winner.equity += loser.equity;
+        winner.peak += loser.peak;
+        winner.position += loser.position;
+        *loser = Sleeve {
+            fills: loser.fills,
+            max_drawdown: loser.max_drawdown,
+            ..Default::default()
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backtest::slippage::{FixedBps, SlippageConfig};
+    use crate::strategy::shadow::PaperExecution;
+    use chrono::Utc;
+
+    /// Buys once and holds
+    struct BuyOnce(f64);
+
+    impl Strategy for BuyOnce {
+        fn on_snapshot(&mut self, _: &MarketSnapshot, position: f64) -> Option<OrderIntent> {
+            (position == 0.0 && self.0 > 0.0).then_some(OrderIntent {
+                side: OrderSide::Buy,
+                quantity: self.0,
+            })
+        }
+    }
+
+    fn snapshot(mid: f64) -> MarketSnapshot {
+        MarketSnapshot {
+            timestamp: Utc::now(),
+            bids: vec![(mid - 0.5, 100.0)],
+            asks: vec![(mid + 0.5, 100.0)],
+        }
+    }
+
+    fn paper() -> PaperExecution {
+        PaperExecution::new(SlippageConfig::FixedBps(FixedBps { bps: 0.0 }))
+    }
+
+    #[test]
+    fn test_canary_is_promoted_when_it_outperforms() {
+        let config = CanaryConfig {
+            capital: 1_000.0,
+            min_snapshots: 3,
+            ..Default::default()
+        };
+        // The baseline sits out a rally the canary is long for
+        let mut canary = Canary::new(BuyOnce(0.0), BuyOnce(5.0), paper(), config);
+
+        let executed = canary.on_snapshot(&snapshot(100.0));
+        assert_eq!(
+            executed,
+            vec![(
+                Version::Canary,
+                OrderIntent {
+                    side: OrderSide::Buy,
+                    quantity: 0.5
+                }
+            )]
+        );
+        canary.on_snapshot(&snapshot(105.0));
+        assert_eq!(canary.state(), CanaryState::Running);
+        canary.on_snapshot(&snapshot(110.0));
+
+        let report = canary.report();
+        assert_eq!(report.state, CanaryState::Promoted);
+        assert_eq!(
+            (report.canary.capital, report.baseline.capital),
+            (1_000.0, 0.0)
+        );
+        assert_eq!(report.canary.position, 0.5);
+    }
+
+    #[test]
+    fn test_drawdown_rolls_back_early() {
+        let mut canary = Canary::new(
+            BuyOnce(0.0),
+            BuyOnce(100.0),
+            paper(),
+            CanaryConfig::default(),
+        );
+        canary.on_snapshot(&snapshot(100.0));
+        canary.on_snapshot(&snapshot(90.0));
+
+        let report = canary.report();
+        assert_eq!(report.state, CanaryState::RolledBack);
+        assert!(report.canary.max_drawdown > 0.05);
+        assert_eq!(report.baseline.capital, 10_000.0);
+    }
+}
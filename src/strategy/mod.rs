@@ -0,0 +1,20 @@
+// Running user strategies outside of backtests
+
+pub mod canary;
+pub mod rules;
+pub mod sandbox;
+pub mod session;
+pub mod shadow;
+pub mod signal_driven;
+
+pub use canary::{Canary, CanaryConfig, CanaryReport, CanaryState, SleevePerformance, Version};
+pub use rules::{Comparison, Condition, Metric, Operand, Rule, RuleAction, RuleSet, RuleStrategy};
+#[cfg(feature = "net")]
+pub use sandbox::spawn_sandboxed;
+pub use sandbox::{SandboxReport, SandboxedStrategy, StrategyBudget, SuspendReason};
+pub use session::{Recording, Session, SessionDebugger, SessionEvent, SessionRecorder, Step};
+pub use shadow::{
+    DepthFills, DivergenceReport, ExecutionOutcome, ExecutionVenue, PaperExecution, PartialFill,
+    ShadowDecision, ShadowTrader,
+};
+pub use signal_driven::{SignalDriven, SignalSource, SignalStrategy};
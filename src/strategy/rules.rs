@@ -0,0 +1,326 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::backtest::runner::{MarketSnapshot, OrderIntent, Strategy};
+use crate::types::OrderSide;
+
+/// Value a condition looks at, computed from each snapshot
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Metric {
+    BestBid,
+    BestAsk,
+    Mid,
+    MicroPrice,
+    Spread,
+    SpreadBps,
+    /// Signed position held by the strategy
+    Position,
+    /// Simple moving average of the mid over the last `window` snapshots
+    MidSma {
+        window: usize,
+    },
+    /// Mid change in basis points against `window` snapshots ago
+    MidChangeBps {
+        window: usize,
+    },
+}
+
+impl Metric {
+    /// Snapshots of mid history this metric needs
+    fn lookback(&self) -> usize {
+        match *self {
+            Metric::MidSma { window } => window,
+            Metric::MidChangeBps { window } => window + 1,
+            _ => 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Comparison {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Comparison {
+    fn holds(self, left: f64, right: f64) -> bool {
+        match self {
+            Comparison::Lt => left < right,
+            Comparison::Le => left <= right,
+            Comparison::Gt => left > right,
+            Comparison::Ge => left >= right,
+        }
+    }
+}
+
+/// Right-hand side of a condition: a constant or another metric
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Operand {
+    Value(f64),
+    Metric(Metric),
+}
+
+/// `metric op operand`, e.g. `mid lt {"mid_sma": {"window": 20}}`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Condition {
+    pub metric: Metric,
+    pub op: Comparison,
+    pub operand: Operand,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RuleAction {
+    pub side: OrderSide,
+    pub quantity: f64,
+}
+
+/// Fires `then` when every condition in `when` holds
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Rule {
+    pub name: String,
+    pub when: Vec<Condition>,
+    pub then: RuleAction,
+}
+
+/// Rule configuration as written by users
+///
+/// Rules are checked in order and the first match fires. An order that
+/// would take the absolute position past `max_position` is skipped.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RuleSet {
+    pub rules: Vec<Rule>,
+    #[serde(default)]
+    pub max_position: Option<f64>,
+}
+
+impl RuleSet {
+    /// Parse and validate a JSON rule set
+    pub fn from_json(json: &str) -> Result<Self, Vec<String>> {
+        let rules: RuleSet = serde_json::from_str(json).map_err(|e| vec![e.to_string()])?;
+        rules.validate()?;
+        Ok(rules)
+    }
+
+    /// Every problem found, prefixed with the rule it belongs to
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+        if self.rules.is_empty() {
+            errors.push("rule set has no rules".to_string());
+        }
+        if let Some(max) = self.max_position {
+            if !max.is_finite() || max < 0.0 {
+                errors.push(format!("max_position must be non-negative, got {}", max));
+            }
+        }
+
+        for (i, rule) in self.rules.iter().enumerate() {
+            let label = if rule.name.trim().is_empty() {
+                errors.push(format!("rule {}: name is empty", i));
+                format!("rule {}", i)
+            } else {
+                format!("rule '{}'", rule.name)
+            };
+            if rule.when.is_empty() {
+                errors.push(format!("{}: no conditions", label));
+            }
+            if !(rule.then.quantity.is_finite() && rule.then.quantity > 0.0) {
+                errors.push(format!(
+                    "{}: quantity must be positive, got {}",
+                    label, rule.then.quantity
+                ));
+            }
+            for condition in &rule.when {
+                let metrics = match condition.operand {
+                    Operand::Value(value) => {
+                        if !value.is_finite() {
+                            errors.push(format!("{}: comparison value must be finite", label));
+                        }
+                        vec![condition.metric]
+                    }
+                    Operand::Metric(other) => vec![condition.metric, other],
+                };
+                for metric in metrics {
+                    if matches!(
+                        metric,
+                        Metric::MidSma { window: 0 } | Metric::MidChangeBps { window: 0 }
+                    ) {
+                        errors.push(format!("{}: {:?} window must be at least 1", label, metric));
+                    }
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Validate and build the strategy
+    pub fn compile(self) -> Result<RuleStrategy, Vec<String>> {
+        self.validate()?;
+        let lookback = self
+            .rules
+            .iter()
+            .flat_map(|rule| &rule.when)
+            .flat_map(|c| match c.operand {
+                Operand::Metric(other) => [c.metric.lookback(), other.lookback()],
+                Operand::Value(_) => [c.metric.lookback(), 0],
+            })
+            .max()
+            .unwrap_or(0);
+        Ok(RuleStrategy {
+            rules: self,
+            mids: VecDeque::with_capacity(lookback),
+            lookback,
+        })
+    }
+}
+
+/// Strategy evaluating a compiled [`RuleSet`]
+pub struct RuleStrategy {
+    rules: RuleSet,
+    /// Most recent mids, newest last
+    mids: VecDeque<f64>,
+    lookback: usize,
+}
+
+impl RuleStrategy {
+    pub fn rules(&self) -> &RuleSet {
+        &self.rules
+    }
+
+    /// None while the book is one-sided or history is still warming up
+    fn evaluate(&self, metric: Metric, snapshot: &MarketSnapshot, position: f64) -> Option<f64> {
+        match metric {
+            Metric::BestBid => snapshot.best_bid(),
+            Metric::BestAsk => snapshot.best_ask(),
+            Metric::Mid => snapshot.mid_price(),
+            Metric::MicroPrice => snapshot.micro_price(),
+            Metric::Spread => Some(snapshot.best_ask()? - snapshot.best_bid()?),
+            Metric::SpreadBps => {
+                let (bid, ask) = (snapshot.best_bid()?, snapshot.best_ask()?);
+                let mid = (bid + ask) / 2.0;
+                (mid > 0.0).then(|| (ask - bid) / mid * 10_000.0)
+            }
+            Metric::Position => Some(position),
+            Metric::MidSma { window } => (self.mids.len() >= window)
+                .then(|| self.mids.iter().rev().take(window).sum::<f64>() / window as f64),
+            Metric::MidChangeBps { window } => {
+                let then = *self.mids.iter().rev().nth(window)?;
+                let now = *self.mids.back()?;
+                (then > 0.0).then(|| (now - then) / then * 10_000.0)
+            }
+        }
+    }
+
+    fn holds(&self, condition: &Condition, snapshot: &MarketSnapshot, position: f64) -> bool {
+        let left = self.evaluate(condition.metric, snapshot, position);
+        let right = match condition.operand {
+            Operand::Value(value) => Some(value),
+            Operand::Metric(metric) => self.evaluate(metric, snapshot, position),
+        };
+        match (left, right) {
+            (Some(left), Some(right)) => condition.op.holds(left, right),
+            _ => false,
+        }
+    }
+}
+
+impl Strategy for RuleStrategy {
+    fn on_snapshot(&mut self, snapshot: &MarketSnapshot, position: f64) -> Option<OrderIntent> {
+        if self.lookback > 0 {
+            if let Some(mid) = snapshot.mid_price() {
+                if self.mids.len() == self.lookback {
+                    self.mids.pop_front();
+                }
+                self.mids.push_back(mid);
+            }
+        }
+
+        let rule = self.rules.rules.iter().find(|rule| {
+            rule.when
+                .iter()
+                .all(|condition| self.holds(condition, snapshot, position))
+        })?;
+
+        let RuleAction { side, quantity } = rule.then;
+        let after = match side {
+            OrderSide::Buy => position + quantity,
+            OrderSide::Sell => position - quantity,
+        };
+        if self
+            .rules
+            .max_position
+            .is_some_and(|max| after.abs() > max + 1e-12)
+        {
+            return None;
+        }
+        Some(OrderIntent { side, quantity })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn snapshot(mid: f64) -> MarketSnapshot {
+        MarketSnapshot {
+            timestamp: Utc::now(),
+            bids: vec![(mid - 0.5, 1.0)],
+            asks: vec![(mid + 0.5, 1.0)],
+        }
+    }
+
+    #[test]
+    fn test_rules_fire_in_order_within_position_limit() {
+        let json = r#"{
+            "max_position": 1.0,
+            "rules": [
+                {"name": "buy dip", "then": {"side": "Buy", "quantity": 1.0},
+                 "when": [{"metric": "mid", "op": "lt", "operand": {"mid_sma": {"window": 3}}},
+                          {"metric": "spread_bps", "op": "le", "operand": 200.0}]},
+                {"name": "sell rip", "then": {"side": "Sell", "quantity": 1.0},
+                 "when": [{"metric": {"mid_change_bps": {"window": 1}}, "op": "gt", "operand": 100.0}]}
+            ]
+        }"#;
+        let mut strategy = RuleSet::from_json(json).unwrap().compile().unwrap();
+
+        // SMA needs three mids before the dip rule can fire
+        assert!(strategy.on_snapshot(&snapshot(100.0), 0.0).is_none());
+        assert!(strategy.on_snapshot(&snapshot(100.0), 0.0).is_none());
+        let intent = strategy.on_snapshot(&snapshot(97.0), 0.0).unwrap();
+        assert_eq!(intent.side, OrderSide::Buy);
+
+        // Already at the limit, so a second buy is skipped
+        assert!(strategy.on_snapshot(&snapshot(96.0), 1.0).is_none());
+        let intent = strategy.on_snapshot(&snapshot(110.0), 1.0).unwrap();
+        assert_eq!(intent.side, OrderSide::Sell);
+    }
+
+    #[test]
+    fn test_validation_reports_every_problem() {
+        let json = r#"{
+            "rules": [
+                {"name": "", "when": [], "then": {"side": "Buy", "quantity": 0.0}},
+                {"name": "bad window", "then": {"side": "Sell", "quantity": 1.0},
+                 "when": [{"metric": {"mid_sma": {"window": 0}}, "op": "gt", "operand": 1.0}]}
+            ]
+        }"#;
+        let errors = RuleSet::from_json(json).unwrap_err();
+        assert_eq!(errors.len(), 4);
+        assert!(errors[0].contains("name is empty"));
+        assert!(errors[3].starts_with("rule 'bad window'"));
+
+        let errors = RuleSet::from_json(r#"{"rules": [{"name": "x"}]}"#).unwrap_err();
+        assert!(errors[0].contains("missing field"));
+    }
+}
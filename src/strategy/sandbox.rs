@@ -0,0 +1,301 @@
+use std::collections::VecDeque;
+use std::panic::{self, AssertUnwindSafe};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::backtest::runner::{MarketSnapshot, OrderIntent, Strategy};
+
+/// Resource limits for one sandboxed strategy
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct StrategyBudget {
+    /// Longest a single callback may run
+    pub max_call_time: Duration,
+    /// Total callback time allowed per `window`
+    pub cpu_time_per_window: Duration,
+    /// Orders allowed per `window`
+    pub orders_per_window: usize,
+    pub window: Duration,
+}
+
+impl Default for StrategyBudget {
+    fn default() -> Self {
+        Self {
+            max_call_time: Duration::from_millis(5),
+            cpu_time_per_window: Duration::from_millis(250),
+            orders_per_window: 50,
+            window: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Why a strategy stopped receiving data
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case", tag = "reason", content = "detail")]
+pub enum SuspendReason {
+    SlowCall,
+    CpuBudget,
+    OrderRate,
+    /// The strategy panicked; the message is kept for diagnosis
+    Crashed(String),
+}
+
+/// Counters exposed for monitoring
+#[derive(Debug, Clone, Serialize)]
+pub struct SandboxReport {
+    pub suspended: Option<SuspendReason>,
+    pub calls: u64,
+    pub orders: u64,
+    pub rejected_orders: u64,
+    pub cpu_time_ms: f64,
+}
+
+/// Runs a strategy under a [`StrategyBudget`]
+///
+/// Time is measured as wall time spent inside the strategy. A strategy that
+/// runs too long, sends too many orders or panics is suspended: it gets no
+/// more snapshots and its orders are dropped until [`resume`](Self::resume).
+/// Panics are caught, so a faulty strategy cannot take its caller down.
+pub struct SandboxedStrategy<S> {
+    strategy: S,
+    budget: StrategyBudget,
+    suspended: Option<SuspendReason>,
+    /// (call start, call duration, produced an order) within the window
+    recent: VecDeque<(Instant, Duration, bool)>,
+    calls: u64,
+    orders: u64,
+    rejected_orders: u64,
+    cpu_time: Duration,
+}
+
+impl<S: Strategy> SandboxedStrategy<S> {
+    pub fn new(strategy: S, budget: StrategyBudget) -> Self {
+        Self {
+            strategy,
+            budget,
+            suspended: None,
+            recent: VecDeque::new(),
+            calls: 0,
+            orders: 0,
+            rejected_orders: 0,
+            cpu_time: Duration::ZERO,
+        }
+    }
+
+    pub fn is_suspended(&self) -> bool {
+        self.suspended.is_some()
+    }
+
+    /// Let a suspended strategy run again with a fresh window
+    pub fn resume(&mut self) {
+        self.suspended = None;
+        self.recent.clear();
+    }
+
+    pub fn report(&self) -> SandboxReport {
+        SandboxReport {
+            suspended: self.suspended.clone(),
+            calls: self.calls,
+            orders: self.orders,
+            rejected_orders: self.rejected_orders,
+            cpu_time_ms: self.cpu_time.as_secs_f64() * 1_000.0,
+        }
+    }
+
+    pub fn into_inner(self) -> S {
+        self.strategy
+    }
+
+    fn suspend(&mut self, reason: SuspendReason) {
+        tracing::warn!("Suspending strategy: {:?}", reason);
+        self.suspended = Some(reason);
+    }
+
+    /// Check the window after a call; returns false if the strategy was suspended
+    fn within_budget(&mut self, started: Instant, elapsed: Duration, ordered: bool) -> bool {
+        while self
+            .recent
+            .front()
+            .is_some_and(|(at, _, _)| started.saturating_duration_since(*at) >= self.budget.window)
+        {
+            self.recent.pop_front();
+        }
+        self.recent.push_back((started, elapsed, ordered));
+
+        let window_time: Duration = self.recent.iter().map(|(_, d, _)| *d).sum();
+        let window_orders = self.recent.iter().filter(|(_, _, o)| *o).count();
+
+        if elapsed > self.budget.max_call_time {
+            self.suspend(SuspendReason::SlowCall);
+        } else if window_time > self.budget.cpu_time_per_window {
+            self.suspend(SuspendReason::CpuBudget);
+        } else if window_orders > self.budget.orders_per_window {
+            self.suspend(SuspendReason::OrderRate);
+        }
+        !self.is_suspended()
+    }
+}
+
+impl<S: Strategy> Strategy for SandboxedStrategy<S> {
+    fn on_snapshot(&mut self, snapshot: &MarketSnapshot, position: f64) -> Option<OrderIntent> {
+        if self.is_suspended() {
+            return None;
+        }
+
+        let started = Instant::now();
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            self.strategy.on_snapshot(snapshot, position)
+        }));
+        let elapsed = started.elapsed();
+        self.calls += 1;
+        self.cpu_time += elapsed;
+
+        let intent = match result {
+            Ok(intent) => intent,
+            Err(payload) => {
+                let message = payload
+                    .downcast_ref::<&str>()
+                    .map(|s| s.to_string())
+                    .or_else(|| payload.downcast_ref::<String>().cloned())
+                    .unwrap_or_else(|| "unknown panic".to_string());
+                self.suspend(SuspendReason::Crashed(message));
+                return None;
+            }
+        };
+
+        if !self.within_budget(started, elapsed, intent.is_some()) {
+            self.rejected_orders += intent.is_some() as u64;
+            return None;
+        }
+        self.orders += intent.is_some() as u64;
+        intent
+    }
+}
+
+/// Run `strategy` on a blocking worker, isolated from the async runtime
+///
+/// Snapshots are offered with `try_send`, so a slow strategy only loses
+/// stale data and never stalls the feed or matching tasks that drive it.
+/// The sandbox report is returned when the snapshot channel closes.
+#[cfg(feature = "net")]
+pub fn spawn_sandboxed<S>(
+    strategy: S,
+    budget: StrategyBudget,
+    mut snapshots: tokio::sync::mpsc::Receiver<(MarketSnapshot, f64)>,
+    intents: tokio::sync::mpsc::UnboundedSender<OrderIntent>,
+) -> tokio::task::JoinHandle<SandboxReport>
+where
+    S: Strategy + Send + 'static,
+{
+    tokio::task::spawn_blocking(move || {
+        let mut sandbox = SandboxedStrategy::new(strategy, budget);
+        while let Some((snapshot, position)) = snapshots.blocking_recv() {
+            if let Some(intent) = sandbox.on_snapshot(&snapshot, position) {
+                if intents.send(intent).is_err() {
+                    break;
+                }
+            }
+        }
+        sandbox.report()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::OrderSide;
+    use chrono::Utc;
+
+    struct Scripted<F: FnMut(u64) -> Option<OrderIntent>> {
+        calls: u64,
+        step: F,
+    }
+
+    impl<F: FnMut(u64) -> Option<OrderIntent>> Strategy for Scripted<F> {
+        fn on_snapshot(&mut self, _: &MarketSnapshot, _: f64) -> Option<OrderIntent> {
+            self.calls += 1;
+            (self.step)(self.calls)
+        }
+    }
+
+    fn snapshot() -> MarketSnapshot {
+        MarketSnapshot {
+            timestamp: Utc::now(),
+            bids: vec![(99.0, 1.0)],
+            asks: vec![(101.0, 1.0)],
+        }
+    }
+
+    fn buy() -> Option<OrderIntent> {
+        Some(OrderIntent {
+            side: OrderSide::Buy,
+            quantity: 1.0,
+        })
+    }
+
+    #[test]
+    fn test_order_rate_suspends_until_resumed() {
+        let budget = StrategyBudget {
+            orders_per_window: 2,
+            window: Duration::from_secs(60),
+            ..Default::default()
+        };
+        let mut sandbox = SandboxedStrategy::new(
+            Scripted {
+                calls: 0,
+                step: |_| buy(),
+            },
+            budget,
+        );
+
+        assert!(sandbox.on_snapshot(&snapshot(), 0.0).is_some());
+        assert!(sandbox.on_snapshot(&snapshot(), 0.0).is_some());
+        assert!(sandbox.on_snapshot(&snapshot(), 0.0).is_none());
+        assert_eq!(sandbox.report().suspended, Some(SuspendReason::OrderRate));
+
+        // Suspended strategies are not called at all
+        assert!(sandbox.on_snapshot(&snapshot(), 0.0).is_none());
+        assert_eq!(sandbox.report().calls, 3);
+
+        sandbox.resume();
+        assert!(sandbox.on_snapshot(&snapshot(), 0.0).is_some());
+        let report = sandbox.report();
+        assert_eq!((report.orders, report.rejected_orders), (3, 1));
+    }
+
+    #[test]
+    fn test_slow_and_crashing_strategies_are_isolated() {
+        let budget = StrategyBudget {
+            max_call_time: Duration::from_millis(1),
+            ..Default::default()
+        };
+        let slow = |_| {
+            std::thread::sleep(Duration::from_millis(5));
+            buy()
+        };
+        let mut sandbox = SandboxedStrategy::new(
+            Scripted {
+                calls: 0,
+                step: slow,
+            },
+            budget,
+        );
+        assert!(sandbox.on_snapshot(&snapshot(), 0.0).is_none());
+        assert_eq!(sandbox.report().suspended, Some(SuspendReason::SlowCall));
+
+        let crashing = |call| if call == 2 { panic!("bad tick") } else { None };
+        let mut sandbox = SandboxedStrategy::new(
+            Scripted {
+                calls: 0,
+                step: crashing,
+            },
+            budget,
+        );
+        assert!(sandbox.on_snapshot(&snapshot(), 0.0).is_none());
+        assert!(sandbox.on_snapshot(&snapshot(), 0.0).is_none());
+        assert_eq!(
+            sandbox.report().suspended,
+            Some(SuspendReason::Crashed("bad tick".to_string()))
+        );
+    }
+}
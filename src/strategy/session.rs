@@ -0,0 +1,343 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::backtest::runner::{MarketSnapshot, OrderIntent, Strategy};
+
+/// One input of a trading session, in the order it happened
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum SessionEvent {
+    /// Seed the strategy's randomness was drawn from
+    Seed { seed: u64 },
+    /// Book the strategy saw, with the position it was told it held
+    Market {
+        snapshot: MarketSnapshot,
+        position: f64,
+    },
+    /// Order the strategy asked for on the preceding market event
+    Intent {
+        timestamp: DateTime<Utc>,
+        intent: OrderIntent,
+    },
+}
+
+/// Appends session events as JSON lines
+pub struct SessionRecorder<W: Write> {
+    out: W,
+}
+
+impl SessionRecorder<BufWriter<File>> {
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self::new(BufWriter::new(File::create(path)?)))
+    }
+}
+
+impl<W: Write> SessionRecorder<W> {
+    pub fn new(out: W) -> Self {
+        Self { out }
+    }
+
+    pub fn record(&mut self, event: &SessionEvent) -> io::Result<()> {
+        serde_json::to_writer(&mut self.out, event)?;
+        self.out.write_all(b"\n")
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+/// Strategy wrapper recording every input and decision of a live session
+///
+/// Recording failures are logged and never interrupt trading.
+pub struct Recording<S, W: Write> {
+    strategy: S,
+    recorder: SessionRecorder<W>,
+}
+
+impl<S: Strategy, W: Write> Recording<S, W> {
+    /// `seed` is whatever the strategy's randomness was initialised with
+    pub fn new(strategy: S, mut recorder: SessionRecorder<W>, seed: Option<u64>) -> Self {
+        if let Some(seed) = seed {
+            if let Err(e) = recorder.record(&SessionEvent::Seed { seed }) {
+                tracing::warn!("Failed to record session seed: {}", e);
+            }
+        }
+        Self { strategy, recorder }
+    }
+
+    pub fn into_parts(self) -> (S, SessionRecorder<W>) {
+        (self.strategy, self.recorder)
+    }
+}
+
+impl<S: Strategy, W: Write> Strategy for Recording<S, W> {
+    fn on_snapshot(&mut self, snapshot: &MarketSnapshot, position: f64) -> Option<OrderIntent> {
+        let intent = self.strategy.on_snapshot(snapshot, position);
+        let mut events = vec![SessionEvent::Market {
+            snapshot: snapshot.clone(),
+            position,
+        }];
+        if let Some(intent) = intent {
+            events.push(SessionEvent::Intent {
+                timestamp: snapshot.timestamp,
+                intent,
+            });
+        }
+        for event in &events {
+            if let Err(e) = self.recorder.record(event) {
+                tracing::warn!("Failed to record session event: {}", e);
+            }
+        }
+        intent
+    }
+}
+
+/// A recorded session loaded back for replay
+#[derive(Debug, Clone, Default)]
+pub struct Session {
+    pub events: Vec<SessionEvent>,
+}
+
+impl Session {
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let mut events = Vec::new();
+        for line in BufReader::new(File::open(path)?).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            events.push(serde_json::from_str(&line).map_err(io::Error::other)?);
+        }
+        Ok(Self { events })
+    }
+
+    /// Seed recorded for the session, to rebuild the strategy with
+    pub fn seed(&self) -> Option<u64> {
+        self.events.iter().find_map(|event| match event {
+            SessionEvent::Seed { seed } => Some(*seed),
+            _ => None,
+        })
+    }
+
+    /// Market events between `from` and `to` with their intents
+    ///
+    /// Each market event carries the position the strategy held, so an
+    /// interval replays exactly without re-running everything before it.
+    pub fn interval(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Session {
+        let in_range = |timestamp: &DateTime<Utc>| *timestamp >= from && *timestamp <= to;
+        let events = self
+            .events
+            .iter()
+            .filter(|event| match event {
+                SessionEvent::Seed { .. } => true,
+                SessionEvent::Market { snapshot, .. } => in_range(&snapshot.timestamp),
+                SessionEvent::Intent { timestamp, .. } => in_range(timestamp),
+            })
+            .cloned()
+            .collect();
+        Session { events }
+    }
+}
+
+/// Outcome of replaying one market event
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Step {
+    /// Index of the market event in the session
+    pub index: usize,
+    pub timestamp: DateTime<Utc>,
+    pub position: f64,
+    /// What the strategy decided on replay
+    pub intent: Option<OrderIntent>,
+    /// What it decided during the recorded session
+    pub recorded: Option<OrderIntent>,
+}
+
+impl Step {
+    pub fn diverged(&self) -> bool {
+        self.intent != self.recorded
+    }
+}
+
+type Hook = Box<dyn FnMut(&Step, &MarketSnapshot)>;
+
+/// Replays a session through a strategy one market event at a time
+///
+/// Hooks see every step with the book it was taken on; `run_until` acts as a
+/// breakpoint, stopping on the first step its condition matches.
+pub struct SessionDebugger<S> {
+    strategy: S,
+    session: Session,
+    cursor: usize,
+    current: Option<(Step, MarketSnapshot)>,
+    hooks: Vec<Hook>,
+}
+
+impl<S: Strategy> SessionDebugger<S> {
+    /// `strategy` should be built fresh, from `session.seed()` if it uses one
+    pub fn new(strategy: S, session: Session) -> Self {
+        Self {
+            strategy,
+            session,
+            cursor: 0,
+            current: None,
+            hooks: Vec::new(),
+        }
+    }
+
+    pub fn with_hook(mut self, hook: impl FnMut(&Step, &MarketSnapshot) + 'static) -> Self {
+        self.hooks.push(Box::new(hook));
+        self
+    }
+
+    /// Replay the next market event; None at the end of the session
+    pub fn step(&mut self) -> Option<Step> {
+        let events = &self.session.events;
+        let index = (self.cursor..events.len())
+            .find(|i| matches!(events[*i], SessionEvent::Market { .. }))?;
+        let SessionEvent::Market { snapshot, position } = &events[index] else {
+            unreachable!("index points at a market event");
+        };
+        let recorded = match events.get(index + 1) {
+            Some(SessionEvent::Intent { intent, .. }) => Some(*intent),
+            _ => None,
+        };
+        self.cursor = index + 1;
+
+        let step = Step {
+            index,
+            timestamp: snapshot.timestamp,
+            position: *position,
+            intent: self.strategy.on_snapshot(snapshot, *position),
+            recorded,
+        };
+        for hook in &mut self.hooks {
+            hook(&step, snapshot);
+        }
+        self.current = Some((step.clone(), snapshot.clone()));
+        Some(step)
+    }
+
+    /// Step until `stop` matches or the session ends
+    pub fn run_until(&mut self, mut stop: impl FnMut(&Step) -> bool) -> Option<Step> {
+        while let Some(step) = self.step() {
+            if stop(&step) {
+                return Some(step);
+            }
+        }
+        None
+    }
+
+    /// Replay the rest of the session, returning the steps that diverged
+    pub fn run(&mut self) -> Vec<Step> {
+        let mut diverged = Vec::new();
+        while let Some(step) = self.step() {
+            if step.diverged() {
+                diverged.push(step);
+            }
+        }
+        diverged
+    }
+
+    /// Last replayed step
+    pub fn current(&self) -> Option<&Step> {
+        self.current.as_ref().map(|(step, _)| step)
+    }
+
+    /// Book of the last replayed step
+    pub fn book(&self) -> Option<&MarketSnapshot> {
+        self.current.as_ref().map(|(_, snapshot)| snapshot)
+    }
+
+    pub fn into_strategy(self) -> S {
+        self.strategy
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::OrderSide;
+    use chrono::TimeZone;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    /// Buys whenever the spread is at most `max_spread`
+    struct TightSpread {
+        max_spread: f64,
+    }
+
+    impl Strategy for TightSpread {
+        fn on_snapshot(&mut self, snapshot: &MarketSnapshot, _: f64) -> Option<OrderIntent> {
+            let spread = snapshot.best_ask()? - snapshot.best_bid()?;
+            (spread <= self.max_spread).then_some(OrderIntent {
+                side: OrderSide::Buy,
+                quantity: 1.0,
+            })
+        }
+    }
+
+    fn snapshot(second: i64, spread: f64) -> MarketSnapshot {
+        MarketSnapshot {
+            timestamp: Utc.timestamp_opt(second, 0).unwrap(),
+            bids: vec![(100.0, 1.0)],
+            asks: vec![(100.0 + spread, 1.0)],
+        }
+    }
+
+    fn record() -> Session {
+        let recorder = SessionRecorder::new(Vec::new());
+        let mut live = Recording::new(TightSpread { max_spread: 1.0 }, recorder, Some(7));
+        for (second, spread) in [(0, 0.5), (1, 2.0), (2, 0.8), (3, 0.1)] {
+            live.on_snapshot(&snapshot(second, spread), second as f64);
+        }
+        let (_, recorder) = live.into_parts();
+        let bytes = recorder.into_inner();
+        let events = String::from_utf8(bytes)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        Session { events }
+    }
+
+    #[test]
+    fn test_replay_matches_recording() {
+        let session = record();
+        assert_eq!(session.seed(), Some(7));
+        assert_eq!(session.events.len(), 1 + 4 + 3);
+
+        let seen = Rc::new(Cell::new(0));
+        let counter = Rc::clone(&seen);
+        let mut debugger = SessionDebugger::new(TightSpread { max_spread: 1.0 }, session)
+            .with_hook(move |_, _| counter.set(counter.get() + 1));
+        let step = debugger.run_until(|step| step.intent.is_none()).unwrap();
+        assert_eq!((step.position, step.recorded), (1.0, None));
+        assert_eq!(debugger.book().unwrap().best_ask(), Some(102.0));
+        assert!(debugger.run().is_empty());
+        assert_eq!(seen.get(), 4);
+    }
+
+    #[test]
+    fn test_interval_replay_flags_changed_decisions() {
+        let session = record();
+        let from = Utc.timestamp_opt(2, 0).unwrap();
+        let to = Utc.timestamp_opt(3, 0).unwrap();
+
+        // A stricter version of the strategy no longer buys at 0.8
+        let mut debugger =
+            SessionDebugger::new(TightSpread { max_spread: 0.5 }, session.interval(from, to));
+        let diverged = debugger.run();
+        assert_eq!(diverged.len(), 1);
+        assert_eq!(diverged[0].position, 2.0);
+        assert!(diverged[0].recorded.is_some() && diverged[0].intent.is_none());
+    }
+}
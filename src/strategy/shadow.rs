@@ -0,0 +1,482 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::backtest::runner::{MarketSnapshot, OrderIntent, Strategy};
+use crate::backtest::slippage::{SlippageConfig, SlippageModel};
+use crate::types::OrderSide;
+
+/// Quantity filled at one price level
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PartialFill {
+    pub price: f64,
+    pub quantity: f64,
+}
+
+/// What a venue did, or would have done, with an order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExecutionOutcome {
+    pub filled_quantity: f64,
+    pub average_price: Option<f64>,
+    pub rejection: Option<String>,
+    /// Fills level by level; empty when the venue only reports the total
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fills: Vec<PartialFill>,
+    /// Quantity left resting for later snapshots to fill
+    #[serde(default)]
+    pub resting_quantity: f64,
+}
+
+impl ExecutionOutcome {
+    pub fn filled(quantity: f64, price: f64) -> Self {
+        Self {
+            filled_quantity: quantity,
+            average_price: Some(price),
+            rejection: None,
+            fills: Vec::new(),
+            resting_quantity: 0.0,
+        }
+    }
+
+    /// `fills` taken so far, with `resting_quantity` still working
+    pub fn partial(fills: Vec<PartialFill>, resting_quantity: f64) -> Self {
+        let filled_quantity: f64 = fills.iter().map(|fill| fill.quantity).sum();
+        let notional: f64 = fills.iter().map(|fill| fill.price * fill.quantity).sum();
+        Self {
+            filled_quantity,
+            average_price: (filled_quantity > 0.0).then(|| notional / filled_quantity),
+            rejection: None,
+            fills,
+            resting_quantity,
+        }
+    }
+
+    pub fn rejected(reason: impl Into<String>) -> Self {
+        Self {
+            filled_quantity: 0.0,
+            average_price: None,
+            rejection: Some(reason.into()),
+            fills: Vec::new(),
+            resting_quantity: 0.0,
+        }
+    }
+}
+
+/// Somewhere a strategy's orders can be executed
+///
+/// Live and testnet connectors implement this to report what they returned
+/// for an order; shadow trading never acts on their result.
+pub trait ExecutionVenue {
+    fn execute(&mut self, intent: &OrderIntent, snapshot: &MarketSnapshot) -> ExecutionOutcome;
+
+    /// Fills of a remainder left resting by an earlier order, with its side
+    fn fill_resting(
+        &mut self,
+        _snapshot: &MarketSnapshot,
+    ) -> Option<(OrderSide, ExecutionOutcome)> {
+        None
+    }
+}
+
+/// Fills paper orders against the snapshot's visible depth, level by level
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DepthFills {
+    /// Share of each level's quantity a paper order may take, as others
+    /// compete for the same liquidity
+    pub participation: f64,
+    /// Levels an order walks before its remainder rests
+    pub max_levels: usize,
+}
+
+impl Default for DepthFills {
+    fn default() -> Self {
+        Self {
+            participation: 1.0,
+            max_levels: 10,
+        }
+    }
+}
+
+impl DepthFills {
+    /// Fills of `quantity` on `side`, no worse than `limit` when given
+    fn walk(
+        &self,
+        side: OrderSide,
+        quantity: f64,
+        limit: Option<f64>,
+        snapshot: &MarketSnapshot,
+    ) -> Vec<PartialFill> {
+        let levels = match side {
+            OrderSide::Buy => &snapshot.asks,
+            OrderSide::Sell => &snapshot.bids,
+        };
+        let within = |price: f64| match (side, limit) {
+            (_, None) => true,
+            (OrderSide::Buy, Some(limit)) => price <= limit,
+            (OrderSide::Sell, Some(limit)) => price >= limit,
+        };
+        let mut remaining = quantity;
+        let mut fills = Vec::new();
+        for &(price, available) in levels.iter().take(self.max_levels) {
+            if remaining <= 0.0 || !within(price) {
+                break;
+            }
+            let taken = remaining.min(available * self.participation.clamp(0.0, 1.0));
+            if taken > 0.0 {
+                remaining -= taken;
+                fills.push(PartialFill {
+                    price,
+                    quantity: taken,
+                });
+            }
+        }
+        fills
+    }
+}
+
+/// Remainder of a depth-filled paper order, priced at the worst level it
+/// reached
+#[derive(Debug, Clone, Copy)]
+struct RestingOrder {
+    side: OrderSide,
+    quantity: f64,
+    limit: f64,
+}
+
+/// Fills at the snapshot using a backtest slippage model
+///
+/// With `with_depth_fills` orders instead take the snapshot's depth level
+/// by level, and whatever the visible liquidity cannot fill rests at the
+/// worst price reached, to be filled by later snapshots. A new order
+/// replaces the resting remainder.
+pub struct PaperExecution {
+    model: Box<dyn SlippageModel>,
+    depth: Option<DepthFills>,
+    resting: Option<RestingOrder>,
+}
+
+impl PaperExecution {
+    pub fn new(slippage: SlippageConfig) -> Self {
+        Self {
+            model: slippage.build(),
+            depth: None,
+            resting: None,
+        }
+    }
+
+    pub fn with_depth_fills(mut self, depth: DepthFills) -> Self {
+        self.depth = Some(depth);
+        self
+    }
+
+    /// Quantity still resting from the last order
+    pub fn resting_quantity(&self) -> f64 {
+        self.resting.map_or(0.0, |resting| resting.quantity)
+    }
+}
+
+impl ExecutionVenue for PaperExecution {
+    fn execute(&mut self, intent: &OrderIntent, snapshot: &MarketSnapshot) -> ExecutionOutcome {
+        let Some(depth) = self.depth else {
+            return match self
+                .model
+                .fill_price(intent.side, intent.quantity, snapshot)
+            {
+                Some(price) => ExecutionOutcome::filled(intent.quantity, price),
+                None => ExecutionOutcome::rejected("no liquidity"),
+            };
+        };
+        self.resting = None;
+        let fills = depth.walk(intent.side, intent.quantity, None, snapshot);
+        let Some(worst) = fills.last().map(|fill| fill.price) else {
+            return ExecutionOutcome::rejected("no liquidity");
+        };
+        let filled: f64 = fills.iter().map(|fill| fill.quantity).sum();
+        let remaining = (intent.quantity - filled).max(0.0);
+        if remaining > 0.0 {
+            self.resting = Some(RestingOrder {
+                side: intent.side,
+                quantity: remaining,
+                limit: worst,
+            });
+        }
+        ExecutionOutcome::partial(fills, remaining)
+    }
+
+    fn fill_resting(&mut self, snapshot: &MarketSnapshot) -> Option<(OrderSide, ExecutionOutcome)> {
+        let (depth, mut resting) = (self.depth?, self.resting?);
+        let fills = depth.walk(
+            resting.side,
+            resting.quantity,
+            Some(resting.limit),
+            snapshot,
+        );
+        if fills.is_empty() {
+            return None;
+        }
+        resting.quantity -= fills.iter().map(|fill| fill.quantity).sum::<f64>();
+        resting.quantity = resting.quantity.max(0.0);
+        self.resting = (resting.quantity > 0.0).then_some(resting);
+        Some((
+            resting.side,
+            ExecutionOutcome::partial(fills, resting.quantity),
+        ))
+    }
+}
+
+/// One strategy order with its paper and live outcomes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ShadowDecision {
+    pub timestamp: DateTime<Utc>,
+    pub side: OrderSide,
+    pub quantity: f64,
+    pub paper: ExecutionOutcome,
+    pub live: ExecutionOutcome,
+    /// How much worse live priced than paper, in bps; None unless both filled
+    pub adverse_bps: Option<f64>,
+}
+
+impl ShadowDecision {
+    /// One venue filled while the other rejected
+    pub fn diverged(&self) -> bool {
+        self.paper.rejection.is_some() != self.live.rejection.is_some()
+    }
+}
+
+/// How closely paper execution tracked live over a shadow session
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DivergenceReport {
+    pub decisions: usize,
+    pub paper_fills: usize,
+    pub live_fills: usize,
+    /// Orders one venue filled and the other rejected
+    pub outcome_mismatches: usize,
+    pub mean_adverse_bps: Option<f64>,
+    pub max_abs_price_diff_bps: Option<f64>,
+    /// Summed absolute difference of filled quantity
+    pub quantity_diff: f64,
+    pub paper_position: f64,
+    pub live_position: f64,
+}
+
+/// Runs a strategy on paper while recording what live execution returned
+///
+/// The strategy only ever sees the paper position, so live outcomes cannot
+/// feed back into its decisions; that keeps the divergence report a clean
+/// measure of simulation fidelity.
+pub struct ShadowTrader<S, P, L> {
+    strategy: S,
+    paper: P,
+    live: L,
+    paper_position: f64,
+    live_position: f64,
+    decisions: Vec<ShadowDecision>,
+}
+
+impl<S: Strategy, P: ExecutionVenue, L: ExecutionVenue> ShadowTrader<S, P, L> {
+    pub fn new(strategy: S, paper: P, live: L) -> Self {
+        Self {
+            strategy,
+            paper,
+            live,
+            paper_position: 0.0,
+            live_position: 0.0,
+            decisions: Vec::new(),
+        }
+    }
+
+    /// Offer `snapshot` to the strategy and send any order to both venues
+    ///
+    /// Remainders resting at either venue fill first, so the strategy sees
+    /// its position after them.
+    pub fn on_snapshot(&mut self, snapshot: &MarketSnapshot) -> Option<&ShadowDecision> {
+        if let Some((side, outcome)) = self.paper.fill_resting(snapshot) {
+            self.paper_position += signed(side, outcome.filled_quantity);
+        }
+        if let Some((side, outcome)) = self.live.fill_resting(snapshot) {
+            self.live_position += signed(side, outcome.filled_quantity);
+        }
+        let intent = self.strategy.on_snapshot(snapshot, self.paper_position)?;
+        let paper = self.paper.execute(&intent, snapshot);
+        let live = self.live.execute(&intent, snapshot);
+
+        let sign = signed(intent.side, 1.0);
+        self.paper_position += sign * paper.filled_quantity;
+        self.live_position += sign * live.filled_quantity;
+        let adverse_bps = match (paper.average_price, live.average_price) {
+            (Some(paper), Some(live)) if paper > 0.0 => {
+                Some(sign * (live - paper) / paper * 10_000.0)
+            }
+            _ => None,
+        };
+
+        let decision = ShadowDecision {
+            timestamp: snapshot.timestamp,
+            side: intent.side,
+            quantity: intent.quantity,
+            paper,
+            live,
+            adverse_bps,
+        };
+        if decision.diverged() {
+            tracing::warn!(
+                "Shadow divergence at {}: paper {:?}, live {:?}",
+                decision.timestamp,
+                decision.paper.rejection,
+                decision.live.rejection
+            );
+        }
+        self.decisions.push(decision);
+        self.decisions.last()
+    }
+
+    pub fn decisions(&self) -> &[ShadowDecision] {
+        &self.decisions
+    }
+
+    pub fn report(&self) -> DivergenceReport {
+        let priced: Vec<f64> = self
+            .decisions
+            .iter()
+            .filter_map(|decision| decision.adverse_bps)
+            .collect();
+        let filled = |outcome: &ExecutionOutcome| outcome.filled_quantity > 0.0;
+        DivergenceReport {
+            decisions: self.decisions.len(),
+            paper_fills: self.decisions.iter().filter(|d| filled(&d.paper)).count(),
+            live_fills: self.decisions.iter().filter(|d| filled(&d.live)).count(),
+            outcome_mismatches: self.decisions.iter().filter(|d| d.diverged()).count(),
+            mean_adverse_bps: (!priced.is_empty())
+                .then(|| priced.iter().sum::<f64>() / priced.len() as f64),
+            max_abs_price_diff_bps: priced.iter().map(|bps| bps.abs()).reduce(f64::max),
+            quantity_diff: self
+                .decisions
+                .iter()
+                .map(|d| (d.paper.filled_quantity - d.live.filled_quantity).abs())
+                .sum(),
+            paper_position: self.paper_position,
+            live_position: self.live_position,
+        }
+    }
+}
+
+fn signed(side: OrderSide, quantity: f64) -> f64 {
+    match side {
+        OrderSide::Buy => quantity,
+        OrderSide::Sell => -quantity,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backtest::slippage::FixedBps;
+
+    struct AlwaysBuy;
+
+    impl Strategy for AlwaysBuy {
+        fn on_snapshot(&mut self, _: &MarketSnapshot, _: f64) -> Option<OrderIntent> {
+            Some(OrderIntent {
+                side: OrderSide::Buy,
+                quantity: 1.0,
+            })
+        }
+    }
+
+    /// Fills 10bps through the touch, then rejects everything
+    struct Scripted {
+        calls: usize,
+    }
+
+    impl ExecutionVenue for Scripted {
+        fn execute(&mut self, intent: &OrderIntent, snapshot: &MarketSnapshot) -> ExecutionOutcome {
+            self.calls += 1;
+            match self.calls {
+                1 => ExecutionOutcome::filled(
+                    intent.quantity,
+                    snapshot.touch_price(intent.side).unwrap() * 1.001,
+                ),
+                _ => ExecutionOutcome::rejected("insufficient balance"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_report_measures_live_against_paper() {
+        let paper = PaperExecution::new(SlippageConfig::FixedBps(FixedBps { bps: 0.0 }));
+        let mut shadow = ShadowTrader::new(AlwaysBuy, paper, Scripted { calls: 0 });
+        let snapshot = MarketSnapshot {
+            timestamp: Utc::now(),
+            bids: vec![(99.0, 5.0)],
+            asks: vec![(100.0, 5.0)],
+        };
+
+        let decision = shadow.on_snapshot(&snapshot).unwrap();
+        assert!((decision.adverse_bps.unwrap() - 10.0).abs() < 1e-6);
+        assert!(shadow.on_snapshot(&snapshot).unwrap().adverse_bps.is_none());
+
+        let report = shadow.report();
+        assert_eq!(
+            (report.decisions, report.paper_fills, report.live_fills),
+            (2, 2, 1)
+        );
+        assert_eq!(report.outcome_mismatches, 1);
+        assert_eq!((report.paper_position, report.live_position), (2.0, 1.0));
+        assert_eq!(report.quantity_diff, 1.0);
+    }
+
+    #[test]
+    fn test_depth_fills_walk_levels_and_rest_the_remainder() {
+        let mut paper =
+            PaperExecution::new(SlippageConfig::default()).with_depth_fills(DepthFills {
+                participation: 0.5,
+                max_levels: 2,
+            });
+        let snapshot = |asks: Vec<(f64, f64)>| MarketSnapshot {
+            timestamp: Utc::now(),
+            bids: vec![(99.0, 5.0)],
+            asks,
+        };
+        let intent = OrderIntent {
+            side: OrderSide::Buy,
+            quantity: 4.0,
+        };
+
+        // Half of each of the first two levels; the third is out of reach
+        let outcome = paper.execute(
+            &intent,
+            &snapshot(vec![(100.0, 2.0), (101.0, 2.0), (102.0, 10.0)]),
+        );
+        assert_eq!(
+            outcome.fills,
+            [
+                PartialFill {
+                    price: 100.0,
+                    quantity: 1.0
+                },
+                PartialFill {
+                    price: 101.0,
+                    quantity: 1.0
+                }
+            ]
+        );
+        assert_eq!(outcome.average_price, Some(100.5));
+        assert_eq!(outcome.resting_quantity, 2.0);
+
+        // The remainder rests at 101 and only fills at or below it
+        assert!(paper.fill_resting(&snapshot(vec![(102.0, 10.0)])).is_none());
+        let (side, resting) = paper
+            .fill_resting(&snapshot(vec![(100.5, 2.0), (101.0, 10.0)]))
+            .unwrap();
+        assert_eq!(side, OrderSide::Buy);
+        assert_eq!(
+            (resting.filled_quantity, resting.resting_quantity),
+            (2.0, 0.0)
+        );
+        assert_eq!(resting.fills.len(), 2);
+        assert_eq!(paper.resting_quantity(), 0.0);
+        assert!(paper
+            .execute(&intent, &snapshot(Vec::new()))
+            .rejection
+            .is_some());
+    }
+}
@@ -0,0 +1,99 @@
+use crate::backtest::runner::{MarketSnapshot, OrderIntent, Strategy};
+use crate::signals::{SignalReplay, SignalSet, SignalSubscriber};
+
+/// Where a signal-driven strategy reads its signals from
+pub trait SignalSource {
+    /// Signals known as of `timestamp_ms`
+    fn signals_at(&mut self, timestamp_ms: i64) -> &SignalSet;
+}
+
+/// Live signals: everything published so far, regardless of time
+impl SignalSource for SignalSubscriber {
+    fn signals_at(&mut self, _timestamp_ms: i64) -> &SignalSet {
+        self.poll()
+    }
+}
+
+/// Recorded signals up to the snapshot being replayed
+impl SignalSource for SignalReplay {
+    fn signals_at(&mut self, timestamp_ms: i64) -> &SignalSet {
+        self.advance_to(timestamp_ms)
+    }
+}
+
+/// Strategy logic that reacts to named signals instead of computing them
+pub trait SignalStrategy {
+    fn on_signals(
+        &mut self,
+        snapshot: &MarketSnapshot,
+        signals: &SignalSet,
+        position: f64,
+    ) -> Option<OrderIntent>;
+}
+
+/// Adapts a [`SignalStrategy`] to [`Strategy`] with a live or replayed source
+///
+/// The same strategy runs against a [`SignalSubscriber`] live and a
+/// [`SignalReplay`] of the recorded bus in backtests.
+pub struct SignalDriven<S, Src> {
+    strategy: S,
+    source: Src,
+}
+
+impl<S: SignalStrategy, Src: SignalSource> SignalDriven<S, Src> {
+    pub fn new(strategy: S, source: Src) -> Self {
+        Self { strategy, source }
+    }
+}
+
+impl<S: SignalStrategy, Src: SignalSource> Strategy for SignalDriven<S, Src> {
+    fn on_snapshot(&mut self, snapshot: &MarketSnapshot, position: f64) -> Option<OrderIntent> {
+        let signals = self
+            .source
+            .signals_at(snapshot.timestamp.timestamp_millis());
+        self.strategy.on_signals(snapshot, signals, position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signals::Signal;
+    use crate::types::OrderSide;
+    use chrono::{TimeZone, Utc};
+
+    /// Buys while RSI is oversold
+    struct Oversold;
+
+    impl SignalStrategy for Oversold {
+        fn on_signals(
+            &mut self,
+            _: &MarketSnapshot,
+            signals: &SignalSet,
+            _: f64,
+        ) -> Option<OrderIntent> {
+            (signals.value("rsi", "BTCUSDT")? < 30.0).then_some(OrderIntent {
+                side: OrderSide::Buy,
+                quantity: 1.0,
+            })
+        }
+    }
+
+    #[test]
+    fn test_replayed_signals_follow_snapshot_time() {
+        let replay = SignalReplay::new(vec![
+            Signal::number("rsi", "BTCUSDT", 50.0, 1_000),
+            Signal::number("rsi", "BTCUSDT", 25.0, 3_000),
+        ]);
+        let mut strategy = SignalDriven::new(Oversold, replay);
+        let at = |ms| MarketSnapshot {
+            timestamp: Utc.timestamp_millis_opt(ms).unwrap(),
+            bids: vec![(99.0, 1.0)],
+            asks: vec![(101.0, 1.0)],
+        };
+
+        assert!(strategy.on_snapshot(&at(500), 0.0).is_none());
+        assert!(strategy.on_snapshot(&at(2_000), 0.0).is_none());
+        assert!(strategy.on_snapshot(&at(3_000), 0.0).is_some());
+    }
+}
@@ -0,0 +1,539 @@
+// Fixture builders for tests
+//
+// Fluent builders for orders, populated books, accounts holding positions
+// and timed market data, so a scenario reads as a few lines instead of a
+// page of struct literals. Built for this crate's own tests, and for
+// downstream crates behind the `testkit` feature. Everything defaults to
+// one symbol so most fixtures never name it.
+//
+// Golden files pin the JSON shape of API payloads: a renamed field or a
+// changed enum representation fails a test instead of a client.
+
+use std::fs;
+use std::path::PathBuf;
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use serde::Serialize;
+
+use crate::account::{Account, AccountId, Accounts, Activity};
+#[cfg(feature = "backtest")]
+use crate::backtest::MarketSnapshot;
+use crate::market::{Metadata, TapeTrade, TradeSource};
+use crate::orderbook::{BookManager, BookUpdate, OrderBook, SharedOrderBook};
+use crate::types::{Order, OrderSide, Price, Qty, Symbol, TimeInForce};
+
+/// Symbol every builder uses unless told otherwise
+pub const SYMBOL: &str = "BTCUSDT";
+
+/// Limit buy of `quantity` at `price`
+pub fn buy(price: f64, quantity: f64) -> OrderBuilder {
+    OrderBuilder::limit(OrderSide::Buy, price, quantity)
+}
+
+/// Limit sell of `quantity` at `price`
+pub fn sell(price: f64, quantity: f64) -> OrderBuilder {
+    OrderBuilder::limit(OrderSide::Sell, price, quantity)
+}
+
+pub fn market_buy(quantity: f64) -> OrderBuilder {
+    OrderBuilder::market(OrderSide::Buy, quantity)
+}
+
+pub fn market_sell(quantity: f64) -> OrderBuilder {
+    OrderBuilder::market(OrderSide::Sell, quantity)
+}
+
+/// An order with whatever options a test needs
+#[derive(Debug, Clone)]
+pub struct OrderBuilder {
+    order: Order,
+}
+
+impl OrderBuilder {
+    pub fn limit(side: OrderSide, price: f64, quantity: f64) -> Self {
+        Self {
+            order: Order::new_limit(SYMBOL, side, price, quantity),
+        }
+    }
+
+    pub fn market(side: OrderSide, quantity: f64) -> Self {
+        Self {
+            order: Order::new_market(SYMBOL, side, quantity),
+        }
+    }
+
+    /// Stop market order triggered by a last trade at `stop_price`
+    pub fn stop(side: OrderSide, stop_price: f64, quantity: f64) -> Self {
+        Self {
+            order: Order::new_stop_market(SYMBOL, side, stop_price, quantity),
+        }
+    }
+
+    pub fn symbol(mut self, symbol: impl Into<Symbol>) -> Self {
+        self.order.symbol = symbol.into();
+        self
+    }
+
+    pub fn ioc(self) -> Self {
+        self.time_in_force(TimeInForce::Ioc)
+    }
+
+    pub fn fok(self) -> Self {
+        self.time_in_force(TimeInForce::Fok)
+    }
+
+    pub fn time_in_force(mut self, time_in_force: TimeInForce) -> Self {
+        self.order = self.order.with_time_in_force(time_in_force);
+        self
+    }
+
+    pub fn good_till(mut self, expires_at: DateTime<Utc>) -> Self {
+        self.order = self.order.good_till(expires_at);
+        self
+    }
+
+    pub fn post_only(mut self) -> Self {
+        self.order = self.order.with_post_only(true);
+        self
+    }
+
+    /// Iceberg showing `display` at a time
+    pub fn iceberg(mut self, display: f64) -> Self {
+        self.order = self.order.with_display_quantity(display);
+        self
+    }
+
+    /// Quote of `strategy`, replaced and mass cancelled with its other quotes
+    pub fn strategy(mut self, strategy: &str) -> Self {
+        self.order = self.order.with_strategy(strategy);
+        self
+    }
+
+    /// Order of `client_id`, cancelled with its others by cancel-all
+    pub fn client(mut self, client_id: &str) -> Self {
+        self.order = self.order.with_client_id(client_id);
+        self
+    }
+
+    pub fn client_order_id(mut self, client_order_id: &str) -> Self {
+        self.order = self.order.with_client_order_id(client_order_id);
+        self
+    }
+
+    pub fn build(self) -> Order {
+        self.order
+    }
+}
+
+impl From<OrderBuilder> for Order {
+    fn from(builder: OrderBuilder) -> Self {
+        builder.build()
+    }
+}
+
+/// Matching book of [`SYMBOL`] with no orders yet
+pub fn book() -> BookBuilder {
+    BookBuilder {
+        symbol: Symbol::new(SYMBOL),
+        orders: Vec::new(),
+    }
+}
+
+/// A book populated with resting orders, added in the order given
+#[derive(Debug, Clone)]
+pub struct BookBuilder {
+    symbol: Symbol,
+    orders: Vec<Order>,
+}
+
+impl BookBuilder {
+    /// Book of `symbol`; orders added afterwards are moved to it
+    pub fn symbol(mut self, symbol: impl Into<Symbol>) -> Self {
+        self.symbol = symbol.into();
+        self
+    }
+
+    pub fn bid(self, price: f64, quantity: f64) -> Self {
+        self.order(buy(price, quantity))
+    }
+
+    pub fn ask(self, price: f64, quantity: f64) -> Self {
+        self.order(sell(price, quantity))
+    }
+
+    /// Bids of (price, quantity), best first
+    pub fn bids(self, levels: &[(f64, f64)]) -> Self {
+        levels
+            .iter()
+            .fold(self, |book, &(price, quantity)| book.bid(price, quantity))
+    }
+
+    /// Asks of (price, quantity), best first
+    pub fn asks(self, levels: &[(f64, f64)]) -> Self {
+        levels
+            .iter()
+            .fold(self, |book, &(price, quantity)| book.ask(price, quantity))
+    }
+
+    pub fn order(mut self, order: impl Into<Order>) -> Self {
+        let mut order = order.into();
+        order.symbol = self.symbol.clone();
+        self.orders.push(order);
+        self
+    }
+
+    /// The orders added so far, e.g. to keep their IDs
+    pub fn orders(&self) -> &[Order] {
+        &self.orders
+    }
+
+    pub fn build(self) -> OrderBook {
+        let mut book = OrderBook::new(self.symbol);
+        for order in self.orders {
+            book.add_order(order);
+        }
+        book
+    }
+
+    pub fn shared(self) -> SharedOrderBook {
+        let book = SharedOrderBook::new(self.symbol.clone());
+        for order in self.orders {
+            book.add_order(order);
+        }
+        book
+    }
+
+    /// Submit the orders to the matching book of a new manager
+    pub fn manager(self) -> BookManager {
+        let books = BookManager::new();
+        for order in self.orders {
+            books.submit(order);
+        }
+        books
+    }
+
+    /// The orders' levels as an exchange snapshot, for mirror books
+    pub fn snapshot(&self) -> BookUpdate {
+        let levels = |side| {
+            self.orders
+                .iter()
+                .filter(|order| order.side == side)
+                .map(|order| (order.price, order.remaining_quantity))
+                .collect()
+        };
+        BookUpdate {
+            bids: levels(OrderSide::Buy),
+            asks: levels(OrderSide::Sell),
+        }
+    }
+}
+
+/// Account `id` with no balance or positions
+pub fn account(id: &str) -> AccountBuilder {
+    AccountBuilder {
+        id: AccountId::from(id),
+        activity: Vec::new(),
+    }
+}
+
+/// An account built from the activity that would have produced it
+#[derive(Debug, Clone)]
+pub struct AccountBuilder {
+    id: AccountId,
+    activity: Vec<Activity>,
+}
+
+impl AccountBuilder {
+    pub fn deposit(mut self, amount: f64) -> Self {
+        self.activity.push(Activity::Transfer {
+            amount,
+            timestamp: Utc::now(),
+        });
+        self
+    }
+
+    /// Buy `quantity` of `symbol` at `price`, free of fees
+    pub fn long(self, symbol: &str, quantity: f64, price: f64) -> Self {
+        self.trade(symbol, OrderSide::Buy, quantity, price, 0.0)
+    }
+
+    /// Sell `quantity` of `symbol` at `price`, free of fees
+    pub fn short(self, symbol: &str, quantity: f64, price: f64) -> Self {
+        self.trade(symbol, OrderSide::Sell, quantity, price, 0.0)
+    }
+
+    pub fn trade(
+        mut self,
+        symbol: &str,
+        side: OrderSide,
+        quantity: f64,
+        price: f64,
+        fee: f64,
+    ) -> Self {
+        self.activity.push(Activity::Trade {
+            symbol: Symbol::new(symbol),
+            side,
+            price,
+            quantity,
+            fee,
+            timestamp: Utc::now(),
+            fill: None,
+        });
+        self
+    }
+
+    pub fn build(self) -> Account {
+        let mut account = Account::new(self.id);
+        for activity in self.activity {
+            account.apply(activity);
+        }
+        account
+    }
+
+    /// Apply the account's activity to `accounts`, returning its ID
+    pub fn open_in(self, accounts: &mut Accounts) -> AccountId {
+        for activity in self.activity {
+            accounts.apply(&self.id, activity);
+        }
+        self.id
+    }
+}
+
+/// Market data of [`SYMBOL`] starting now, one event per second
+pub fn market() -> MarketScript {
+    MarketScript {
+        symbol: Symbol::new(SYMBOL),
+        clock: Utc::now(),
+        step: Duration::seconds(1),
+        trades: Vec::new(),
+        #[cfg(feature = "backtest")]
+        snapshots: Vec::new(),
+    }
+}
+
+/// Exchange trades and book snapshots at evenly spaced timestamps
+#[derive(Debug, Clone)]
+pub struct MarketScript {
+    symbol: Symbol,
+    clock: DateTime<Utc>,
+    step: Duration,
+    trades: Vec<TapeTrade>,
+    #[cfg(feature = "backtest")]
+    snapshots: Vec<MarketSnapshot>,
+}
+
+impl MarketScript {
+    pub fn symbol(mut self, symbol: impl Into<Symbol>) -> Self {
+        self.symbol = symbol.into();
+        self
+    }
+
+    pub fn starting_at(mut self, at: DateTime<Utc>) -> Self {
+        self.clock = at;
+        self
+    }
+
+    /// Time between consecutive events
+    pub fn every(mut self, step: Duration) -> Self {
+        self.step = step;
+        self
+    }
+
+    /// Let `duration` pass without events
+    pub fn wait(mut self, duration: Duration) -> Self {
+        self.clock += duration;
+        self
+    }
+
+    /// Exchange trade with a known aggressor
+    pub fn trade(mut self, aggressor: OrderSide, price: f64, quantity: f64) -> Self {
+        let timestamp = self.tick();
+        self.trades.push(TapeTrade {
+            symbol: self.symbol.clone(),
+            price: Price::new(price),
+            quantity: Qty::new(quantity),
+            aggressor: Some(aggressor),
+            source: TradeSource::Exchange,
+            timestamp,
+            maker_order_id: None,
+            taker_order_id: None,
+            metadata: Metadata::new(),
+        });
+        self
+    }
+
+    /// A buy trade of one unit at each of `prices`
+    pub fn prices(self, prices: &[f64]) -> Self {
+        prices.iter().fold(self, |script, &price| {
+            script.trade(OrderSide::Buy, price, 1.0)
+        })
+    }
+
+    /// Top of book at `bid` and `ask`, `size` deep on each side
+    #[cfg(feature = "backtest")]
+    pub fn quote(mut self, bid: f64, ask: f64, size: f64) -> Self {
+        let timestamp = self.tick();
+        self.snapshots.push(MarketSnapshot {
+            timestamp,
+            bids: vec![(bid, size)],
+            asks: vec![(ask, size)],
+        });
+        self
+    }
+
+    pub fn trades(&self) -> &[TapeTrade] {
+        &self.trades
+    }
+
+    #[cfg(feature = "backtest")]
+    pub fn snapshots(&self) -> &[MarketSnapshot] {
+        &self.snapshots
+    }
+
+    fn tick(&mut self) -> DateTime<Utc> {
+        let at = self.clock;
+        self.clock += self.step;
+        at
+    }
+}
+
+/// Directory under the package root golden files are kept in
+pub const GOLDEN_DIR: &str = "tests/golden";
+
+/// Fixed timestamp for payloads compared against golden files
+pub fn golden_time() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap()
+}
+
+/// Assert `value` serializes to the JSON in golden file `name`
+///
+/// Run with `UPDATE_GOLDEN=1` to write the file instead, after checking the
+/// change is meant to reach API consumers.
+pub fn assert_golden(name: &str, value: &impl Serialize) {
+    let root = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_else(|_| ".".to_string());
+    let path: PathBuf = [root.as_str(), GOLDEN_DIR, &format!("{}.json", name)]
+        .iter()
+        .collect();
+    let actual = serde_json::to_string_pretty(value).expect("payload serializes") + "\n";
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::create_dir_all(path.parent().unwrap()).expect("golden directory is writable");
+        fs::write(&path, actual).expect("golden file is writable");
+        return;
+    }
+    let expected = fs::read_to_string(&path).unwrap_or_else(|_| {
+        panic!(
+            "no golden file {}; run with UPDATE_GOLDEN=1 to create it",
+            path.display()
+        )
+    });
+    assert!(
+        expected == actual,
+        "{} no longer matches {}; rerun with UPDATE_GOLDEN=1 if the change is intended\n\
+         expected:\n{}\nactual:\n{}",
+        name,
+        path.display(),
+        expected,
+        actual
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account::{FeeSchedule, FeeTier, FeeTracker};
+    use crate::market::{AlertCondition, AlertEvent, AlertId, AlertSeverity};
+    use crate::orderbook::{ExecutionReport, FlowEvent, Liquidity, QuoteTracker};
+    use crate::types::{OrderId, OrderStatus};
+
+    #[test]
+    fn test_stream_payloads_match_golden_files() {
+        let at = golden_time();
+        let symbol = Symbol::new(SYMBOL);
+        assert_golden(
+            "execution_reports",
+            &[
+                ExecutionReport::Fill {
+                    order_id: OrderId(1),
+                    symbol: symbol.clone(),
+                    price: 100.5,
+                    quantity: 0.25,
+                    liquidity: Liquidity::Maker,
+                    timestamp: at,
+                },
+                ExecutionReport::OrderUpdate {
+                    order_id: OrderId(1),
+                    symbol: symbol.clone(),
+                    side: OrderSide::Buy,
+                    status: OrderStatus::PartiallyFilled,
+                    filled_quantity: 0.25,
+                    remaining_quantity: 0.75,
+                    timestamp: at,
+                    queue_depth: None,
+                },
+            ],
+        );
+        assert_golden(
+            "flow_events",
+            &[
+                FlowEvent::Arrival {
+                    order_id: OrderId(2),
+                    symbol: symbol.clone(),
+                    side: OrderSide::Sell,
+                    price: Some(101.0),
+                    quantity: 1.0,
+                    timestamp: at,
+                },
+                FlowEvent::Cancel {
+                    order_id: OrderId(2),
+                    symbol: symbol.clone(),
+                    side: OrderSide::Sell,
+                    remaining_quantity: 1.0,
+                    timestamp: at,
+                },
+                FlowEvent::Trade {
+                    symbol: symbol.clone(),
+                    price: 101.0,
+                    quantity: 0.5,
+                    aggressor: None,
+                    timestamp: at,
+                },
+            ],
+        );
+        assert_golden(
+            "alert_event",
+            &AlertEvent {
+                alert_id: AlertId(3),
+                user: "alice".to_string(),
+                symbol,
+                condition: AlertCondition::CrossesAbove { level: 100.0 },
+                severity: AlertSeverity::Info,
+                price: 100.5,
+                timestamp_ms: at.timestamp_millis(),
+            },
+        );
+    }
+
+    #[test]
+    fn test_rest_payloads_match_golden_files() {
+        let at = golden_time();
+        let mut quotes = QuoteTracker::new();
+        quotes.record_refresh("mm", &Symbol::new(SYMBOL), 2, 1, Some(500), at);
+        assert_golden("quote_stats", quotes.stats());
+
+        let mut fees = FeeTracker::new(FeeSchedule::new(vec![
+            FeeTier {
+                min_volume: 0.0,
+                maker_bps: 1.0,
+                taker_bps: 5.0,
+            },
+            FeeTier {
+                min_volume: 1_000_000.0,
+                maker_bps: -0.5,
+                taker_bps: 3.0,
+            },
+        ]));
+        let id = AccountId::from("alice");
+        fees.charge(&id, Liquidity::Taker, 250_000.0, at);
+        assert_golden("fee_status", &fees.status(&id, at));
+    }
+}
@@ -0,0 +1,274 @@
+// Throughput soak metrics for capacity planning
+//
+// Order entry, fills and market data messages are counted in one-second
+// buckets for the last hour and averaged over 1m/5m/1h windows. Recent
+// matching latencies give percentiles and an estimate of the sustainable
+// order rate, so operators can see how much headroom is left.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use serde::Serialize;
+
+/// Seconds of per-second buckets kept (the longest window)
+const HISTORY_SECS: u64 = 3_600;
+
+/// Latency samples kept for percentiles
+const LATENCY_SAMPLES: usize = 4_096;
+
+/// Averaging windows reported, in seconds
+const WINDOWS: [(&str, u64); 3] = [("1m", 60), ("5m", 300), ("1h", 3_600)];
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Bucket {
+    second: u64,
+    orders: u64,
+    fills: u64,
+    market_messages: u64,
+}
+
+/// Sustained rates over one window
+#[derive(Debug, Clone, Serialize)]
+pub struct WindowRates {
+    pub window: &'static str,
+    pub orders_per_sec: f64,
+    pub fills_per_sec: f64,
+    pub market_messages_per_sec: f64,
+}
+
+/// Matching latency percentiles in milliseconds
+#[derive(Debug, Clone, Serialize)]
+pub struct LatencyPercentiles {
+    pub p50: f64,
+    pub p99: f64,
+    pub p999: f64,
+}
+
+/// Throughput and headroom report
+#[derive(Debug, Clone, Serialize)]
+pub struct CapacityReport {
+    pub uptime_secs: u64,
+    pub windows: Vec<WindowRates>,
+    pub latency_ms: Option<LatencyPercentiles>,
+    /// Orders/sec one matching thread sustains at the current p99 latency
+    pub estimated_capacity_per_sec: Option<f64>,
+    /// Capacity as a multiple of the 1m order rate (2.0 = room to double)
+    pub headroom: Option<f64>,
+}
+
+/// Counts order, fill and market data throughput
+#[derive(Debug)]
+pub struct ThroughputMeter {
+    started: Instant,
+    buckets: VecDeque<Bucket>,
+    latencies: VecDeque<f64>,
+}
+
+impl ThroughputMeter {
+    pub fn new() -> Self {
+        Self::starting_at(Instant::now())
+    }
+
+    pub fn starting_at(started: Instant) -> Self {
+        Self {
+            started,
+            buckets: VecDeque::new(),
+            latencies: VecDeque::with_capacity(LATENCY_SAMPLES),
+        }
+    }
+
+    pub fn record_orders(&mut self, count: u64, now: Instant) {
+        self.bucket(now).orders += count;
+    }
+
+    pub fn record_fills(&mut self, count: u64, now: Instant) {
+        self.bucket(now).fills += count;
+    }
+
+    pub fn record_market_messages(&mut self, count: u64, now: Instant) {
+        self.bucket(now).market_messages += count;
+    }
+
+    pub fn record_latency(&mut self, latency_ms: f64) {
+        if self.latencies.len() == LATENCY_SAMPLES {
+            self.latencies.pop_front();
+        }
+        self.latencies.push_back(latency_ms);
+    }
+
+    pub fn report(&self, now: Instant) -> CapacityReport {
+        let uptime_secs = now.saturating_duration_since(self.started).as_secs();
+        let windows: Vec<WindowRates> = WINDOWS
+            .iter()
+            .map(|&(window, secs)| self.rates(window, secs, uptime_secs))
+            .collect();
+
+        let latency_ms = self.percentiles();
+        let estimated_capacity_per_sec = latency_ms
+            .as_ref()
+            .filter(|l| l.p99 > 0.0)
+            .map(|l| 1_000.0 / l.p99);
+        let headroom = estimated_capacity_per_sec
+            .zip(windows.first())
+            .filter(|(_, rates)| rates.orders_per_sec > 0.0)
+            .map(|(capacity, rates)| capacity / rates.orders_per_sec);
+
+        CapacityReport {
+            uptime_secs,
+            windows,
+            latency_ms,
+            estimated_capacity_per_sec,
+            headroom,
+        }
+    }
+
+    /// Bucket for the second containing `now`, dropping expired ones
+    fn bucket(&mut self, now: Instant) -> &mut Bucket {
+        let second = now.saturating_duration_since(self.started).as_secs();
+        if self.buckets.back().is_none_or(|b| b.second != second) {
+            self.buckets.push_back(Bucket {
+                second,
+                ..Default::default()
+            });
+        }
+        while self
+            .buckets
+            .front()
+            .is_some_and(|b| b.second + HISTORY_SECS <= second)
+        {
+            self.buckets.pop_front();
+        }
+        self.buckets.back_mut().unwrap()
+    }
+
+    /// Average over the last `secs` seconds, or the uptime if shorter
+    fn rates(&self, window: &'static str, secs: u64, uptime_secs: u64) -> WindowRates {
+        let since = (uptime_secs + 1).saturating_sub(secs);
+        let (orders, fills, messages) = self
+            .buckets
+            .iter()
+            .filter(|b| b.second >= since && b.second <= uptime_secs)
+            .fold((0, 0, 0), |(o, f, m), b| {
+                (o + b.orders, f + b.fills, m + b.market_messages)
+            });
+        let elapsed = secs.min(uptime_secs + 1) as f64;
+        WindowRates {
+            window,
+            orders_per_sec: orders as f64 / elapsed,
+            fills_per_sec: fills as f64 / elapsed,
+            market_messages_per_sec: messages as f64 / elapsed,
+        }
+    }
+
+    fn percentiles(&self) -> Option<LatencyPercentiles> {
+        if self.latencies.is_empty() {
+            return None;
+        }
+        let mut sorted: Vec<f64> = self.latencies.iter().copied().collect();
+        sorted.sort_by(f64::total_cmp);
+        let at = |q: f64| sorted[((sorted.len() - 1) as f64 * q).round() as usize];
+        Some(LatencyPercentiles {
+            p50: at(0.5),
+            p99: at(0.99),
+            p999: at(0.999),
+        })
+    }
+}
+
+impl Default for ThroughputMeter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Thread-safe wrapper for ThroughputMeter
+pub struct SharedThroughputMeter {
+    inner: Arc<Mutex<ThroughputMeter>>,
+}
+
+impl SharedThroughputMeter {
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(ThroughputMeter::new())),
+        }
+    }
+
+    /// Record one order with its matching latency and resulting fills
+    pub fn record_order(&self, fills: usize, latency_ms: f64) {
+        let now = Instant::now();
+        let mut meter = self.inner.lock().unwrap();
+        meter.record_orders(1, now);
+        meter.record_fills(fills as u64, now);
+        meter.record_latency(latency_ms);
+    }
+
+    pub fn record_market_messages(&self, count: u64) {
+        self.inner
+            .lock()
+            .unwrap()
+            .record_market_messages(count, Instant::now())
+    }
+
+    pub fn report(&self) -> CapacityReport {
+        self.inner.lock().unwrap().report(Instant::now())
+    }
+}
+
+impl Clone for SharedThroughputMeter {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl Default for SharedThroughputMeter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_window_rates() {
+        let start = Instant::now();
+        let mut meter = ThroughputMeter::starting_at(start);
+        for second in 0..120 {
+            let now = start + Duration::from_secs(second);
+            meter.record_orders(10, now);
+            meter.record_fills(5, now);
+            meter.record_market_messages(if second >= 60 { 200 } else { 100 }, now);
+        }
+
+        let report = meter.report(start + Duration::from_secs(119));
+        assert_eq!(report.uptime_secs, 119);
+        let one_minute = &report.windows[0];
+        assert_eq!(one_minute.orders_per_sec, 10.0);
+        assert_eq!(one_minute.fills_per_sec, 5.0);
+        assert_eq!(one_minute.market_messages_per_sec, 200.0);
+        // Longer windows average over the uptime so far
+        assert_eq!(report.windows[1].market_messages_per_sec, 150.0);
+        assert!(report.headroom.is_none());
+    }
+
+    #[test]
+    fn test_headroom_from_latency() {
+        let start = Instant::now();
+        let mut meter = ThroughputMeter::starting_at(start);
+        meter.record_orders(50, start);
+        for i in 0..100 {
+            meter.record_latency(if i >= 98 { 10.0 } else { 1.0 });
+        }
+
+        let report = meter.report(start);
+        let latency = report.latency_ms.unwrap();
+        assert_eq!((latency.p50, latency.p99), (1.0, 10.0));
+        assert_eq!(report.estimated_capacity_per_sec, Some(100.0));
+        assert_eq!(report.headroom, Some(2.0));
+    }
+}
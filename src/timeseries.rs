@@ -0,0 +1,343 @@
+// Multi-resolution time-series store for metrics and prices
+//
+// Every sample lands in a raw tier and is rolled up into 1s, 1m and 1h
+// buckets as it arrives. Each tier keeps its own retention, so recent
+// history is there at full resolution and long ranges come from coarse
+// buckets. Prices, spreads, equity and latency metrics share one range
+// query, keyed by series name: `price:<symbol>`, `spread:<symbol>`,
+// `equity:<account>`, `latency:<metric>`, and the funding and daily borrow
+// rates fetched from the exchange, `funding:<symbol>` and `borrow:<asset>`.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::types::Symbol;
+
+/// Tier of a series, finest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Resolution {
+    #[serde(rename = "raw")]
+    Raw,
+    #[serde(rename = "1s")]
+    Second,
+    #[serde(rename = "1m")]
+    Minute,
+    #[serde(rename = "1h")]
+    Hour,
+}
+
+impl Resolution {
+    pub const ALL: [Resolution; 4] = [
+        Resolution::Raw,
+        Resolution::Second,
+        Resolution::Minute,
+        Resolution::Hour,
+    ];
+
+    /// Width of a bucket; None for raw samples
+    pub fn step(self) -> Option<Duration> {
+        match self {
+            Resolution::Raw => None,
+            Resolution::Second => Some(Duration::seconds(1)),
+            Resolution::Minute => Some(Duration::minutes(1)),
+            Resolution::Hour => Some(Duration::hours(1)),
+        }
+    }
+
+    /// Start of the bucket `at` falls in
+    fn bucket_start(self, at: DateTime<Utc>) -> DateTime<Utc> {
+        let Some(step) = self.step() else {
+            return at;
+        };
+        let step = step.num_seconds();
+        let secs = at.timestamp();
+        DateTime::from_timestamp(secs - secs.rem_euclid(step), 0).unwrap_or(at)
+    }
+}
+
+/// How long each tier keeps its buckets
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Retention {
+    pub raw: Duration,
+    pub second: Duration,
+    pub minute: Duration,
+    pub hour: Duration,
+}
+
+impl Retention {
+    pub fn of(&self, resolution: Resolution) -> Duration {
+        match resolution {
+            Resolution::Raw => self.raw,
+            Resolution::Second => self.second,
+            Resolution::Minute => self.minute,
+            Resolution::Hour => self.hour,
+        }
+    }
+}
+
+impl Default for Retention {
+    fn default() -> Self {
+        Self {
+            raw: Duration::minutes(5),
+            second: Duration::hours(1),
+            minute: Duration::days(7),
+            hour: Duration::days(365),
+        }
+    }
+}
+
+/// Samples of one bucket, or a single raw sample
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Bucket {
+    pub start: DateTime<Utc>,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub sum: f64,
+    pub count: u64,
+}
+
+impl Bucket {
+    fn new(start: DateTime<Utc>, value: f64) -> Self {
+        Self {
+            start,
+            open: value,
+            high: value,
+            low: value,
+            close: value,
+            sum: value,
+            count: 1,
+        }
+    }
+
+    fn add(&mut self, value: f64) {
+        self.high = self.high.max(value);
+        self.low = self.low.min(value);
+        self.close = value;
+        self.sum += value;
+        self.count += 1;
+    }
+
+    pub fn mean(&self) -> f64 {
+        self.sum / self.count as f64
+    }
+}
+
+/// Buckets of one series in the range asked for
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SeriesRange {
+    pub series: String,
+    pub resolution: Resolution,
+    pub buckets: Vec<Bucket>,
+}
+
+#[derive(Debug, Default)]
+struct Series {
+    /// One tier per resolution, oldest bucket first
+    tiers: [VecDeque<Bucket>; 4],
+    latest: Option<DateTime<Utc>>,
+}
+
+/// Named series, each rolled up into every resolution
+///
+/// Samples are expected roughly in time order; a late one still lands in
+/// the right bucket, but does not become its close.
+#[derive(Debug, Default)]
+pub struct TimeSeriesStore {
+    retention: Retention,
+    series: BTreeMap<String, Series>,
+}
+
+impl TimeSeriesStore {
+    pub fn new(retention: Retention) -> Self {
+        Self {
+            retention,
+            series: BTreeMap::new(),
+        }
+    }
+
+    pub fn record(&mut self, name: &str, at: DateTime<Utc>, value: f64) {
+        if !value.is_finite() {
+            return;
+        }
+        let series = self.series.entry(name.to_string()).or_default();
+        let latest = series.latest.map_or(at, |latest| latest.max(at));
+        series.latest = Some(latest);
+        for resolution in Resolution::ALL {
+            let tier = &mut series.tiers[resolution as usize];
+            let start = resolution.bucket_start(at);
+            let merges = resolution != Resolution::Raw;
+            // Raw samples at the same instant stay apart, in arrival order
+            let index = tier.partition_point(|bucket| {
+                bucket.start < start || (!merges && bucket.start == start)
+            });
+            match tier.get_mut(index) {
+                Some(bucket) if merges && bucket.start == start => bucket.add(value),
+                _ => tier.insert(index, Bucket::new(start, value)),
+            }
+            let cutoff = latest - self.retention.of(resolution);
+            while tier.front().is_some_and(|bucket| bucket.start < cutoff) {
+                tier.pop_front();
+            }
+        }
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.series.keys().cloned().collect()
+    }
+
+    /// Most recent sample of `name`, from the finest tier holding it
+    pub fn latest(&self, name: &str) -> Option<(DateTime<Utc>, f64)> {
+        let series = self.series.get(name)?;
+        let latest = series.latest?;
+        series
+            .tiers
+            .iter()
+            .find_map(|tier| tier.back())
+            .map(|bucket| (latest, bucket.close))
+    }
+
+    /// Buckets of `name` starting within `from..=to`, at `resolution` or
+    /// else the finest one still reaching back to `from`
+    pub fn query(
+        &self,
+        name: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        resolution: Option<Resolution>,
+    ) -> Option<SeriesRange> {
+        let series = self.series.get(name)?;
+        let resolution = resolution.unwrap_or_else(|| {
+            let latest = series.latest.unwrap_or(to);
+            Resolution::ALL
+                .into_iter()
+                .find(|&r| latest - self.retention.of(r) <= from)
+                .unwrap_or(Resolution::Hour)
+        });
+        let buckets = series.tiers[resolution as usize]
+            .iter()
+            .filter(|bucket| bucket.start >= resolution.bucket_start(from) && bucket.start <= to)
+            .copied()
+            .collect();
+        Some(SeriesRange {
+            series: name.to_string(),
+            resolution,
+            buckets,
+        })
+    }
+}
+
+/// Thread-safe wrapper for TimeSeriesStore
+#[derive(Default)]
+pub struct SharedTimeSeriesStore {
+    inner: Arc<Mutex<TimeSeriesStore>>,
+}
+
+impl SharedTimeSeriesStore {
+    pub fn new(store: TimeSeriesStore) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(store)),
+        }
+    }
+
+    pub fn record(&self, name: &str, at: DateTime<Utc>, value: f64) {
+        self.inner.lock().unwrap().record(name, at, value)
+    }
+
+    /// Mid price and spread of `symbol`
+    pub fn record_quote(&self, symbol: &Symbol, bid: f64, ask: f64, at: DateTime<Utc>) {
+        let mut store = self.inner.lock().unwrap();
+        store.record(&format!("price:{}", symbol), at, (bid + ask) / 2.0);
+        store.record(&format!("spread:{}", symbol), at, ask - bid);
+    }
+
+    pub fn record_equity(&self, account: &str, equity: f64, at: DateTime<Utc>) {
+        self.record(&format!("equity:{}", account), at, equity)
+    }
+
+    pub fn record_latency(&self, metric: &str, ms: f64, at: DateTime<Utc>) {
+        self.record(&format!("latency:{}", metric), at, ms)
+    }
+
+    /// Funding rate of the perpetual `symbol` per funding interval
+    pub fn record_funding(&self, symbol: &str, rate: f64, at: DateTime<Utc>) {
+        self.record(&format!("funding:{}", symbol), at, rate)
+    }
+
+    /// Daily borrow rate of `asset`
+    pub fn record_borrow(&self, asset: &str, rate: f64, at: DateTime<Utc>) {
+        self.record(&format!("borrow:{}", asset), at, rate)
+    }
+
+    pub fn latest(&self, name: &str) -> Option<(DateTime<Utc>, f64)> {
+        self.inner.lock().unwrap().latest(name)
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.inner.lock().unwrap().names()
+    }
+
+    pub fn query(
+        &self,
+        name: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        resolution: Option<Resolution>,
+    ) -> Option<SeriesRange> {
+        self.inner.lock().unwrap().query(name, from, to, resolution)
+    }
+}
+
+impl Clone for SharedTimeSeriesStore {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testkit::golden_time;
+
+    #[test]
+    fn test_samples_roll_up_and_age_out_per_tier() {
+        let mut store = TimeSeriesStore::new(Retention {
+            raw: Duration::seconds(30),
+            ..Retention::default()
+        });
+        let start = Resolution::Hour.bucket_start(golden_time());
+        for i in 0..120 {
+            let at = start + Duration::milliseconds(500 * i);
+            store.record("price:BTCUSDT", at, 100.0 + i as f64);
+        }
+        let end = start + Duration::seconds(60);
+
+        // A minute back is past the raw tier's 30s, so 1s buckets answer
+        let range = store.query("price:BTCUSDT", start, end, None).unwrap();
+        assert_eq!(range.resolution, Resolution::Second);
+        assert_eq!(range.buckets.len(), 60);
+        assert_eq!(range.buckets[0].count, 2);
+        assert_eq!(
+            (range.buckets[0].open, range.buckets[0].close),
+            (100.0, 101.0)
+        );
+
+        let minutes = store
+            .query("price:BTCUSDT", start, end, Some(Resolution::Minute))
+            .unwrap();
+        assert_eq!(minutes.buckets.len(), 1);
+        assert_eq!(minutes.buckets[0].high, 219.0);
+
+        let raw = store
+            .query("price:BTCUSDT", start, end, Some(Resolution::Raw))
+            .unwrap();
+        assert_eq!(raw.buckets.len(), 61);
+        assert!(store.query("price:ETHUSDT", start, end, None).is_none());
+    }
+}
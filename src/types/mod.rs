@@ -1,3 +1,7 @@
+pub mod money;
 pub mod order;
 
-pub use order::{Order, OrderId, OrderSide, OrderStatus, OrderType, Trade};
+pub use money::{Notional, Price, Qty, Symbol};
+pub use order::{
+    Order, OrderId, OrderSide, OrderStatus, OrderType, Peg, PegReference, TimeInForce, Trade,
+};
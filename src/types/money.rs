@@ -0,0 +1,331 @@
+use std::borrow::Borrow;
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
+
+use serde::{Deserialize, Serialize};
+
+/// Decimal places kept for prices, quantities and notionals (Binance's maximum)
+pub(crate) const DECIMALS: i32 = 8;
+
+/// Largest scaled value an f64 still holds to the unit
+const MAX_EXACT: f64 = (1u64 << f64::MANTISSA_DIGITS) as f64;
+
+/// Round to [`DECIMALS`] places so float dust never survives arithmetic
+///
+/// Past about 9e7 an f64 is already coarser than [`DECIMALS`] places, and
+/// scaling it would only add error, so larger values are kept as they are.
+fn round(value: f64) -> f64 {
+    let scale = 10f64.powi(DECIMALS);
+    let scaled = value * scale;
+    if scaled.abs() >= MAX_EXACT {
+        return value;
+    }
+    let rounded = scaled.round() / scale;
+    // Avoid serializing -0.0
+    if rounded == 0.0 {
+        0.0
+    } else {
+        rounded
+    }
+}
+
+/// Trading pair symbol, always upper-case (e.g. `BTCUSDT`)
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub struct Symbol(String);
+
+impl Symbol {
+    pub fn new(symbol: impl AsRef<str>) -> Self {
+        Self(symbol.as_ref().to_uppercase())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<String> for Symbol {
+    fn from(symbol: String) -> Self {
+        Self::new(symbol)
+    }
+}
+
+impl From<&str> for Symbol {
+    fn from(symbol: &str) -> Self {
+        Self::new(symbol)
+    }
+}
+
+impl From<Symbol> for String {
+    fn from(symbol: Symbol) -> Self {
+        symbol.0
+    }
+}
+
+impl AsRef<str> for Symbol {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for Symbol {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl PartialEq<str> for Symbol {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<String> for Symbol {
+    fn eq(&self, other: &String) -> bool {
+        &self.0 == other
+    }
+}
+
+impl PartialEq<&str> for Symbol {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+/// Defines a rounded f64 newtype with same-unit arithmetic
+macro_rules! decimal_newtype {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+        #[serde(from = "f64", into = "f64")]
+        pub struct $name(f64);
+
+        // Rounded values compare exactly, so they can key price-sorted maps;
+        // NaN sorts apart from every number rather than equal to it.
+        // Equality goes through the same ordering so the two always agree.
+        impl PartialEq for $name {
+            fn eq(&self, other: &Self) -> bool {
+                self.cmp(other) == std::cmp::Ordering::Equal
+            }
+        }
+
+        impl Eq for $name {}
+
+        impl PartialOrd for $name {
+            fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for $name {
+            fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+                // Adding zero turns -0.0 into 0.0 so both zeros are one level
+                (self.0 + 0.0).total_cmp(&(other.0 + 0.0))
+            }
+        }
+
+        impl $name {
+            pub const ZERO: Self = Self(0.0);
+
+            /// Round `value` as it is; input from outside goes through
+            /// [`try_new`](Self::try_new)
+            pub fn new(value: f64) -> Self {
+                Self(round(value))
+            }
+
+            /// None for NaN, infinite or negative input
+            pub fn try_new(value: f64) -> Option<Self> {
+                (value.is_finite() && value >= 0.0).then(|| Self::new(value))
+            }
+
+            /// None unless `value` is finite and above zero, as order prices
+            /// and sizes must be
+            pub fn try_positive(value: f64) -> Option<Self> {
+                (value.is_finite() && value > 0.0).then(|| Self::new(value))
+            }
+
+            pub fn value(self) -> f64 {
+                self.0
+            }
+
+            pub fn is_zero(self) -> bool {
+                self.0 == 0.0
+            }
+
+            pub fn abs(self) -> Self {
+                Self(self.0.abs())
+            }
+
+            pub fn min(self, other: Self) -> Self {
+                if other < self { other } else { self }
+            }
+
+            pub fn max(self, other: Self) -> Self {
+                if other > self { other } else { self }
+            }
+        }
+
+        impl From<f64> for $name {
+            fn from(value: f64) -> Self {
+                Self::new(value)
+            }
+        }
+
+        impl From<$name> for f64 {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                fmt::Display::fmt(&self.0, f)
+            }
+        }
+
+        impl Add for $name {
+            type Output = Self;
+            fn add(self, rhs: Self) -> Self {
+                Self::new(self.0 + rhs.0)
+            }
+        }
+
+        impl Sub for $name {
+            type Output = Self;
+            fn sub(self, rhs: Self) -> Self {
+                Self::new(self.0 - rhs.0)
+            }
+        }
+
+        impl AddAssign for $name {
+            fn add_assign(&mut self, rhs: Self) {
+                *self = *self + rhs;
+            }
+        }
+
+        impl SubAssign for $name {
+            fn sub_assign(&mut self, rhs: Self) {
+                *self = *self - rhs;
+            }
+        }
+
+        impl Neg for $name {
+            type Output = Self;
+            fn neg(self) -> Self {
+                Self(-self.0)
+            }
+        }
+
+        impl Sum for $name {
+            fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+                iter.fold(Self::ZERO, Add::add)
+            }
+        }
+
+        impl PartialEq<f64> for $name {
+            fn eq(&self, other: &f64) -> bool {
+                self.0 == *other
+            }
+        }
+
+        impl PartialOrd<f64> for $name {
+            fn partial_cmp(&self, other: &f64) -> Option<std::cmp::Ordering> {
+                self.0.partial_cmp(other)
+            }
+        }
+    };
+}
+
+decimal_newtype!(
+    /// Price in quote currency per unit of base
+    Price
+);
+decimal_newtype!(
+    /// Quantity in base currency
+    Qty
+);
+decimal_newtype!(
+    /// Value in quote currency (price x quantity)
+    Notional
+);
+
+impl Mul<Qty> for Price {
+    type Output = Notional;
+    fn mul(self, rhs: Qty) -> Notional {
+        Notional::new(self.0 * rhs.0)
+    }
+}
+
+impl Mul<Price> for Qty {
+    type Output = Notional;
+    fn mul(self, rhs: Price) -> Notional {
+        rhs * self
+    }
+}
+
+impl Div<Qty> for Notional {
+    type Output = Option<Price>;
+    /// Average price; None when the quantity is zero
+    fn div(self, rhs: Qty) -> Option<Price> {
+        (!rhs.is_zero()).then(|| Price::new(self.0 / rhs.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cmp::Ordering;
+
+    #[test]
+    fn test_units_and_rounding() {
+        let price = Price::new(100.5);
+        let qty = Qty::new(1.0) - Qty::new(0.4) - Qty::new(0.6);
+        assert!(qty.is_zero());
+
+        let notional = price * Qty::new(2.0);
+        assert_eq!(notional, Notional::new(201.0));
+        assert_eq!(notional / Qty::new(2.0), Some(price));
+        assert_eq!(notional / Qty::ZERO, None);
+        assert_eq!(Price::new(0.1 + 0.2), Price::new(0.3));
+        assert!(Price::try_new(f64::NAN).is_none());
+        assert!(Qty::try_new(f64::INFINITY).is_none());
+        assert!(Qty::try_new(-1.0).is_none());
+        assert_eq!(Qty::try_new(0.0), Some(Qty::ZERO));
+        assert_eq!(Qty::try_positive(0.5), Some(Qty::new(0.5)));
+        assert!(Qty::try_positive(0.0).is_none());
+
+        // NaN never lands on a number's level
+        assert_ne!(
+            Price::new(f64::NAN).cmp(&Price::new(100.0)),
+            Ordering::Equal
+        );
+        assert_eq!((-Price::ZERO).cmp(&Price::ZERO), Ordering::Equal);
+        assert_eq!(-Price::ZERO, Price::ZERO);
+        assert_eq!(Price::new(f64::NAN), Price::new(f64::NAN));
+
+        // Beyond what an f64 holds to 8 places, values are left alone
+        assert_eq!(
+            Notional::new(90_072_949.26381999).value(),
+            90_072_949.26381999
+        );
+        assert_eq!(Notional::new(1e12 + 0.5).value(), 1e12 + 0.5);
+    }
+
+    #[test]
+    fn test_serde_is_transparent() {
+        let json =
+            serde_json::to_string(&(Symbol::new("btcusdt"), Price::new(43000.1), Qty::new(0.5)))
+                .unwrap();
+        assert_eq!(json, r#"["BTCUSDT",43000.1,0.5]"#);
+
+        let symbol: Symbol = serde_json::from_str(r#""ethusdt""#).unwrap();
+        assert_eq!(symbol, "ETHUSDT");
+    }
+}
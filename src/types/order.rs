@@ -1,11 +1,16 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::types::money::{Price, Qty, Symbol};
+
 /// Unique identifier for an order
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct OrderId(pub u64);
 
 impl OrderId {
+    /// Counterparty of simulated fills against mirrored exchange liquidity
+    pub const EXCHANGE: OrderId = OrderId(0);
+
     pub fn new() -> Self {
         use std::sync::atomic::{AtomicU64, Ordering};
         static COUNTER: AtomicU64 = AtomicU64::new(1);
@@ -26,6 +31,15 @@ pub enum OrderSide {
     Sell,
 }
 
+impl OrderSide {
+    pub fn opposite(self) -> Self {
+        match self {
+            OrderSide::Buy => OrderSide::Sell,
+            OrderSide::Sell => OrderSide::Buy,
+        }
+    }
+}
+
 /// Order type
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum OrderType {
@@ -35,6 +49,76 @@ pub enum OrderType {
     Limit,
     /// Good-till-cancel - remains in book until filled or cancelled
     GoodTillCancel,
+    /// Waits off-book for the last trade to reach its stop price, then
+    /// executes as a market order
+    StopMarket,
+    /// Waits off-book for the last trade to reach its stop price, then
+    /// becomes a limit order at its price
+    StopLimit,
+}
+
+/// How long an order stays on the book
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeInForce {
+    /// Rests until filled or cancelled
+    #[default]
+    Gtc,
+    /// Immediate-or-cancel: whatever does not fill at once is cancelled
+    Ioc,
+    /// Fill-or-kill: fills in full at once or not at all
+    Fok,
+    /// Good-till-date: rests until its expiry
+    Gtd,
+    /// Good-till-crossing: only takes part in the next auction uncross, and
+    /// is cancelled with whatever it leaves unfilled
+    Gtx,
+}
+
+/// Book price a pegged order tracks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PegReference {
+    BestBid,
+    BestAsk,
+    Mid,
+}
+
+/// Price that follows the book instead of staying where it was placed
+///
+/// A buy pegged to the best bid is a primary peg, one pegged to the best
+/// ask a market peg.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Peg {
+    pub reference: PegReference,
+    /// Added to the reference price; negative to sit below it
+    #[serde(default)]
+    pub offset: f64,
+}
+
+impl Peg {
+    pub fn new(reference: PegReference, offset: f64) -> Self {
+        Self { reference, offset }
+    }
+
+    /// Where the order belongs given the book's touch; None while the
+    /// reference is missing or the price would not be positive
+    pub fn price(&self, best_bid: Option<f64>, best_ask: Option<f64>) -> Option<f64> {
+        let reference = match self.reference {
+            PegReference::BestBid => best_bid?,
+            PegReference::BestAsk => best_ask?,
+            PegReference::Mid => (best_bid? + best_ask?) / 2.0,
+        };
+        let price = reference + self.offset;
+        (price.is_finite() && price > 0.0).then_some(price)
+    }
+}
+
+impl TimeInForce {
+    /// Whether the order never rests on the book
+    pub fn is_immediate(self) -> bool {
+        matches!(self, TimeInForce::Ioc | TimeInForce::Fok)
+    }
 }
 
 /// Order status
@@ -45,56 +129,279 @@ pub enum OrderStatus {
     Filled,
     Cancelled,
     Rejected,
+    /// Taken off the book once its good-till-date passed
+    Expired,
 }
 
 /// Core order structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Order {
     pub id: OrderId,
-    pub symbol: String,
+    pub symbol: Symbol,
     pub side: OrderSide,
     pub order_type: OrderType,
-    pub price: f64,
-    pub initial_quantity: f64,
-    pub remaining_quantity: f64,
+    pub price: Price,
+    pub initial_quantity: Qty,
+    pub remaining_quantity: Qty,
     pub status: OrderStatus,
     pub timestamp: DateTime<Utc>,
+    /// Last trade price that activates a stop order
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop_price: Option<Price>,
+    /// Strategy that placed the order, for quote management
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strategy: Option<String>,
+    /// Client the order was placed for, which cancel-all works by
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    /// The client's own ID for the order, unique per client
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_order_id: Option<String>,
+    #[serde(default)]
+    pub time_in_force: TimeInForce,
+    /// When a good-till-date order expires
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Largest quantity an iceberg order shows on the book; the rest is hidden
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_quantity: Option<Qty>,
+    /// Only ever rests as a maker; see the book's post-only mode for what
+    /// happens when it would cross
+    #[serde(default)]
+    pub post_only: bool,
+    /// May only shrink the submitting account's position, never grow it
+    #[serde(default)]
+    pub reduce_only: bool,
+    /// Reprices the order as the book moves; its price is wherever the peg
+    /// last put it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub peg: Option<Peg>,
+    /// Visible slice of an iceberg order still to fill before the next is shown
+    #[serde(skip)]
+    tip: Qty,
 }
 
 impl Order {
-    pub fn new_limit(symbol: String, side: OrderSide, price: f64, quantity: f64) -> Self {
+    pub fn new_limit(
+        symbol: impl Into<Symbol>,
+        side: OrderSide,
+        price: impl Into<Price>,
+        quantity: impl Into<Qty>,
+    ) -> Self {
+        let quantity = quantity.into();
         Self {
             id: OrderId::new(),
-            symbol,
+            symbol: symbol.into(),
             side,
             order_type: OrderType::Limit,
-            price,
+            price: price.into(),
             initial_quantity: quantity,
             remaining_quantity: quantity,
             status: OrderStatus::Pending,
             timestamp: Utc::now(),
+            stop_price: None,
+            strategy: None,
+            client_id: None,
+            client_order_id: None,
+            time_in_force: TimeInForce::Gtc,
+            expires_at: None,
+            display_quantity: None,
+            post_only: false,
+            reduce_only: false,
+            peg: None,
+            tip: Qty::ZERO,
         }
     }
 
-    pub fn new_market(symbol: String, side: OrderSide, quantity: f64) -> Self {
+    pub fn new_market(
+        symbol: impl Into<Symbol>,
+        side: OrderSide,
+        quantity: impl Into<Qty>,
+    ) -> Self {
+        let quantity = quantity.into();
         Self {
             id: OrderId::new(),
-            symbol,
+            symbol: symbol.into(),
             side,
             order_type: OrderType::Market,
-            price: 0.0, // Market orders don't have a price
+            price: Price::ZERO, // Market orders don't have a price
             initial_quantity: quantity,
             remaining_quantity: quantity,
             status: OrderStatus::Pending,
             timestamp: Utc::now(),
+            stop_price: None,
+            strategy: None,
+            client_id: None,
+            client_order_id: None,
+            time_in_force: TimeInForce::Gtc,
+            expires_at: None,
+            display_quantity: None,
+            post_only: false,
+            reduce_only: false,
+            peg: None,
+            tip: Qty::ZERO,
+        }
+    }
+
+    pub fn new_stop_market(
+        symbol: impl Into<Symbol>,
+        side: OrderSide,
+        stop_price: impl Into<Price>,
+        quantity: impl Into<Qty>,
+    ) -> Self {
+        Self {
+            order_type: OrderType::StopMarket,
+            stop_price: Some(stop_price.into()),
+            ..Self::new_market(symbol, side, quantity)
         }
     }
 
+    pub fn new_stop_limit(
+        symbol: impl Into<Symbol>,
+        side: OrderSide,
+        stop_price: impl Into<Price>,
+        price: impl Into<Price>,
+        quantity: impl Into<Qty>,
+    ) -> Self {
+        Self {
+            order_type: OrderType::StopLimit,
+            stop_price: Some(stop_price.into()),
+            ..Self::new_limit(symbol, side, price, quantity)
+        }
+    }
+
+    /// Tag the order with the strategy placing it
+    pub fn with_strategy(mut self, strategy: &str) -> Self {
+        self.strategy = Some(strategy.to_string());
+        self
+    }
+
+    /// Tag the order with the client it is placed for
+    pub fn with_client_id(mut self, client_id: &str) -> Self {
+        self.client_id = Some(client_id.to_string());
+        self
+    }
+
+    pub fn with_client_order_id(mut self, client_order_id: &str) -> Self {
+        self.client_order_id = Some(client_order_id.to_string());
+        self
+    }
+
+    pub fn with_time_in_force(mut self, time_in_force: TimeInForce) -> Self {
+        self.time_in_force = time_in_force;
+        self
+    }
+
+    /// Make the order good-till-date, resting until `expires_at`
+    pub fn good_till(mut self, expires_at: DateTime<Utc>) -> Self {
+        self.time_in_force = TimeInForce::Gtd;
+        self.expires_at = Some(expires_at);
+        self
+    }
+
+    /// Make the order an iceberg showing at most `display_quantity` at a time
+    pub fn with_display_quantity(mut self, display_quantity: impl Into<Qty>) -> Self {
+        self.display_quantity = Some(display_quantity.into());
+        self.refresh_tip();
+        self
+    }
+
+    pub fn with_post_only(mut self, post_only: bool) -> Self {
+        self.post_only = post_only;
+        self
+    }
+
+    pub fn with_reduce_only(mut self, reduce_only: bool) -> Self {
+        self.reduce_only = reduce_only;
+        self
+    }
+
+    /// Peg the order's price to the book
+    pub fn with_peg(mut self, peg: Peg) -> Self {
+        self.peg = Some(peg);
+        self
+    }
+
+    /// Cut an order not yet submitted down to `quantity`
+    pub fn resize(&mut self, quantity: impl Into<Qty>) {
+        let quantity = quantity.into();
+        self.initial_quantity = quantity;
+        self.remaining_quantity = quantity;
+        self.refresh_tip();
+    }
+
+    pub fn is_iceberg(&self) -> bool {
+        self.display_quantity.is_some()
+    }
+
+    /// Quantity shown on the book: the current slice of an iceberg order, or
+    /// everything that remains of any other
+    pub fn visible_quantity(&self) -> Qty {
+        match self.display_quantity {
+            Some(_) => self.tip,
+            None => self.remaining_quantity,
+        }
+    }
+
+    pub fn hidden_quantity(&self) -> Qty {
+        self.remaining_quantity - self.visible_quantity()
+    }
+
+    /// Show the next slice of an iceberg order once the last one filled
+    pub fn refresh_tip(&mut self) {
+        if let Some(display) = self.display_quantity {
+            self.tip = display.min(self.remaining_quantity);
+        }
+    }
+
+    /// Whether a good-till-date order has expired by `now`
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.time_in_force == TimeInForce::Gtd && self.expires_at.is_some_and(|at| at <= now)
+    }
+
+    /// Whether the unfilled remainder is dropped rather than rested
+    ///
+    /// Market orders, triggered stop-markets included, have no price to rest
+    /// at, so they behave as IOC whatever their time in force.
+    pub fn is_immediate(&self) -> bool {
+        self.time_in_force.is_immediate() || self.order_type == OrderType::Market
+    }
+
+    /// Whether this is a stop order still waiting for its trigger
+    pub fn is_stop(&self) -> bool {
+        matches!(
+            self.order_type,
+            OrderType::StopMarket | OrderType::StopLimit
+        )
+    }
+
+    /// Whether a trade at `last_price` activates this stop order: buy stops
+    /// at or above their stop price, sell stops at or below it
+    pub fn is_triggered_by(&self, last_price: Price) -> bool {
+        match (self.stop_price, self.side) {
+            (Some(stop), OrderSide::Buy) => last_price >= stop,
+            (Some(stop), OrderSide::Sell) => last_price <= stop,
+            (None, _) => false,
+        }
+    }
+
+    /// Turn a triggered stop into the market or limit order it stands for
+    pub fn activate(&mut self) {
+        self.order_type = match self.order_type {
+            OrderType::StopMarket => OrderType::Market,
+            OrderType::StopLimit => OrderType::Limit,
+            other => other,
+        };
+    }
+
     /// Fill the order with the specified quantity
-    pub fn fill(&mut self, quantity: f64) {
+    pub fn fill(&mut self, quantity: Qty) {
         self.remaining_quantity -= quantity;
+        if self.is_iceberg() {
+            self.tip = (self.tip - quantity).max(Qty::ZERO);
+        }
         if self.remaining_quantity <= 0.0 {
-            self.remaining_quantity = 0.0;
+            self.remaining_quantity = Qty::ZERO;
             self.status = OrderStatus::Filled;
         } else {
             self.status = OrderStatus::PartiallyFilled;
@@ -105,20 +412,22 @@ impl Order {
         self.remaining_quantity <= 0.0
     }
 
-    pub fn filled_quantity(&self) -> f64 {
+    pub fn filled_quantity(&self) -> Qty {
         self.initial_quantity - self.remaining_quantity
     }
 
     /// Check if this order can match with the given price
-    pub fn can_match(&self, market_price: f64) -> bool {
+    pub fn can_match(&self, market_price: Price) -> bool {
         match (self.order_type, self.side) {
-            (OrderType::Market, _) => true,
-            (OrderType::Limit, OrderSide::Buy) | (OrderType::GoodTillCancel, OrderSide::Buy) => {
-                self.price >= market_price
-            }
-            (OrderType::Limit, OrderSide::Sell) | (OrderType::GoodTillCancel, OrderSide::Sell) => {
-                self.price <= market_price
-            }
+            (OrderType::Market | OrderType::StopMarket, _) => true,
+            (
+                OrderType::Limit | OrderType::GoodTillCancel | OrderType::StopLimit,
+                OrderSide::Buy,
+            ) => self.price >= market_price,
+            (
+                OrderType::Limit | OrderType::GoodTillCancel | OrderType::StopLimit,
+                OrderSide::Sell,
+            ) => self.price <= market_price,
         }
     }
 }
@@ -128,27 +437,41 @@ impl Order {
 pub struct Trade {
     pub maker_order_id: OrderId,
     pub taker_order_id: OrderId,
-    pub symbol: String,
-    pub price: f64,
-    pub quantity: f64,
+    pub symbol: Symbol,
+    pub price: Price,
+    pub quantity: Qty,
     pub timestamp: DateTime<Utc>,
+    /// Side of the taker; auction trades have none
+    #[serde(default)]
+    pub aggressor: Option<OrderSide>,
+    /// Position among the trades of the book, from 1; 0 until the book
+    /// numbers it
+    #[serde(default)]
+    pub sequence: u64,
 }
 
 impl Trade {
     pub fn new(
         maker_order_id: OrderId,
         taker_order_id: OrderId,
-        symbol: String,
-        price: f64,
-        quantity: f64,
+        symbol: impl Into<Symbol>,
+        price: impl Into<Price>,
+        quantity: impl Into<Qty>,
     ) -> Self {
         Self {
             maker_order_id,
             taker_order_id,
-            symbol,
-            price,
-            quantity,
+            symbol: symbol.into(),
+            price: price.into(),
+            quantity: quantity.into(),
             timestamp: Utc::now(),
+            aggressor: None,
+            sequence: 0,
         }
     }
+
+    pub fn with_aggressor(mut self, side: OrderSide) -> Self {
+        self.aggressor = Some(side);
+        self
+    }
 }
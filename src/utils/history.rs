@@ -0,0 +1,268 @@
+use std::collections::vec_deque::{self, VecDeque};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::memory::MemoryUsage;
+
+/// Items that carry their own time, used for age-based retention
+pub trait Timestamped {
+    fn timestamp_ms(&self) -> i64;
+}
+
+/// How much of a history to keep in memory
+///
+/// Both limits apply when set; with neither the history is unbounded.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Retention {
+    pub max_len: Option<usize>,
+    /// Items older than this, relative to the newest item, are evicted
+    pub max_age_ms: Option<i64>,
+    /// Append evicted items as JSON lines to `<dir>/<name>.jsonl`
+    pub spill_dir: Option<PathBuf>,
+}
+
+impl Retention {
+    /// Keep at most `max_len` items
+    pub fn count(max_len: usize) -> Self {
+        Self {
+            max_len: Some(max_len.max(1)),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_max_age(mut self, max_age: std::time::Duration) -> Self {
+        self.max_age_ms = Some(max_age.as_millis() as i64);
+        self
+    }
+
+    pub fn with_spill_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.spill_dir = Some(dir.into());
+        self
+    }
+}
+
+/// Ring buffer with count and age retention and optional spill to disk
+///
+/// Eviction happens on every push, oldest first, so memory stays bounded
+/// without callers compacting by hand.
+#[derive(Debug)]
+pub struct BoundedHistory<T> {
+    items: VecDeque<T>,
+    retention: Retention,
+    spill: Option<BufWriter<File>>,
+    /// File evictions are spilled to, once it opened
+    spill_path: Option<PathBuf>,
+}
+
+impl<T: Timestamped + Serialize> BoundedHistory<T> {
+    /// `name` identifies the spill file when the retention has a spill dir
+    pub fn new(name: &str, retention: Retention) -> Self {
+        let spill = retention
+            .spill_dir
+            .as_ref()
+            .and_then(|dir| match open_spill(dir, name) {
+                Ok(file) => Some((BufWriter::new(file), spill_path(dir, name))),
+                Err(e) => {
+                    tracing::warn!("Not spilling history {} to {:?}: {}", name, dir, e);
+                    None
+                }
+            });
+        let (spill, spill_path) = spill.unzip();
+        Self {
+            items: VecDeque::with_capacity(retention.max_len.unwrap_or(0).min(1_024)),
+            retention,
+            spill,
+            spill_path,
+        }
+    }
+
+    pub fn push(&mut self, item: T) {
+        let newest = item.timestamp_ms();
+        self.items.push_back(item);
+        self.compact(newest);
+    }
+
+    /// Evict everything outside the retention as of `now_ms`
+    pub fn compact(&mut self, now_ms: i64) {
+        while let Some(oldest) = self.items.front() {
+            let too_many = self
+                .retention
+                .max_len
+                .is_some_and(|max| self.items.len() > max);
+            let too_old = self
+                .retention
+                .max_age_ms
+                .is_some_and(|max| now_ms - oldest.timestamp_ms() > max);
+            if !too_many && !too_old {
+                break;
+            }
+            let evicted = self.items.pop_front().unwrap();
+            self.spill(&evicted);
+        }
+    }
+
+    /// Write buffered spills through to disk
+    pub fn flush(&mut self) -> io::Result<()> {
+        match &mut self.spill {
+            Some(writer) => writer.flush(),
+            None => Ok(()),
+        }
+    }
+
+    fn spill(&mut self, item: &T) {
+        let Some(writer) = &mut self.spill else {
+            return;
+        };
+        let written = serde_json::to_writer(&mut *writer, item)
+            .map_err(io::Error::other)
+            .and_then(|_| writer.write_all(b"\n"));
+        if let Err(e) = written {
+            tracing::warn!("History spill failed, dropping further evictions: {}", e);
+            self.spill = None;
+        }
+    }
+}
+
+impl<T: Timestamped + Serialize + DeserializeOwned + Clone> BoundedHistory<T> {
+    /// Items `keep` accepts, spilled ones first, then those still retained,
+    /// oldest first
+    ///
+    /// An unreadable spill file is logged and only retained items are
+    /// searched.
+    pub fn scan(&mut self, keep: impl Fn(&T) -> bool) -> Vec<T> {
+        let mut found = Vec::new();
+        if let Some(path) = self.spill_path.clone() {
+            match self.flush().and_then(|_| read_spill::<T>(&path)) {
+                Ok(spilled) => found.extend(spilled.into_iter().filter(|item| keep(item))),
+                Err(e) => tracing::warn!("Not searching spilled history {:?}: {}", path, e),
+            }
+        }
+        found.extend(self.items.iter().filter(|item| keep(item)).cloned());
+        found
+    }
+}
+
+impl<T> MemoryUsage for BoundedHistory<T> {
+    /// Buffer of retained items; heap data the items own is not counted
+    fn heap_bytes(&self) -> usize {
+        self.items.capacity() * std::mem::size_of::<T>()
+    }
+}
+
+impl<T> BoundedHistory<T> {
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Oldest first
+    pub fn iter(&self) -> vec_deque::Iter<'_, T> {
+        self.items.iter()
+    }
+
+    /// Up to `limit` most recent items, oldest first
+    pub fn latest(&self, limit: usize) -> impl Iterator<Item = &T> {
+        self.items
+            .iter()
+            .skip(self.items.len().saturating_sub(limit))
+    }
+}
+
+fn spill_path(dir: &Path, name: &str) -> PathBuf {
+    dir.join(format!("{}.jsonl", name))
+}
+
+fn open_spill(dir: &Path, name: &str) -> io::Result<File> {
+    fs::create_dir_all(dir)?;
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(spill_path(dir, name))
+}
+
+/// Items spilled to `path`, oldest first; lines that do not parse as `T`
+/// are skipped
+pub fn read_spill<T: DeserializeOwned>(path: &Path) -> io::Result<Vec<T>> {
+    let mut items = Vec::new();
+    for line in BufReader::new(File::open(path)?).lines() {
+        match serde_json::from_str(&line?) {
+            Ok(item) => items.push(item),
+            Err(e) => tracing::debug!("Skipping spilled line in {:?}: {}", path, e),
+        }
+    }
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct Tick(i64);
+
+    impl Timestamped for Tick {
+        fn timestamp_ms(&self) -> i64 {
+            self.0
+        }
+    }
+
+    #[test]
+    fn test_count_and_age_retention() {
+        let mut history = BoundedHistory::new(
+            "ticks",
+            Retention::count(3).with_max_age(Duration::from_millis(100)),
+        );
+        for ts in [0, 10, 20, 30] {
+            history.push(Tick(ts));
+        }
+        assert_eq!(
+            history.iter().map(|t| t.0).collect::<Vec<_>>(),
+            [10, 20, 30]
+        );
+
+        history.push(Tick(125));
+        assert_eq!(history.iter().map(|t| t.0).collect::<Vec<_>>(), [30, 125]);
+        assert_eq!(history.latest(1).next(), Some(&Tick(125)));
+
+        history.compact(1_000);
+        assert!(history.is_empty());
+    }
+
+    #[test]
+    fn test_evictions_spill_to_disk() {
+        let dir = std::env::temp_dir().join(format!("history-{}", std::process::id()));
+        {
+            let mut history =
+                BoundedHistory::new("ticks", Retention::count(1).with_spill_dir(&dir));
+            for ts in [1, 2, 3] {
+                history.push(Tick(ts));
+            }
+            assert_eq!(history.len(), 1);
+        }
+
+        let spilled = fs::read_to_string(dir.join("ticks.jsonl")).unwrap();
+        let ticks: Vec<Tick> = spilled
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(ticks, [Tick(1), Tick(2)]);
+
+        // A scan reads the spill back ahead of what is still retained
+        let mut history = BoundedHistory::new("ticks", Retention::count(1).with_spill_dir(&dir));
+        history.push(Tick(4));
+        history.push(Tick(5));
+        assert_eq!(
+            history.scan(|tick| tick.0 != 2),
+            [Tick(1), Tick(4), Tick(5)]
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,7 @@
+pub mod history;
+pub mod query;
+pub mod sparse_vector;
+
+pub use history::{read_spill, BoundedHistory, Retention, Timestamped};
+pub use query::{Filter, Queryable, Value};
+pub use sparse_vector::SparseVector;
@@ -0,0 +1,476 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+use chrono::{DateTime, NaiveDate, Utc};
+
+/// Value of a field as a filter compares it
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Number(f64),
+    /// Compared case-insensitively
+    Text(String),
+    Time(DateTime<Utc>),
+}
+
+/// Records a filter can run over
+pub trait Queryable {
+    /// Fields a filter may name
+    const FIELDS: &'static [&'static str];
+
+    /// None when the record has no such field, or no value for it
+    fn field(&self, name: &str) -> Option<Value>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Op {
+    fn holds(self, ordering: Ordering) -> bool {
+        match self {
+            Op::Eq => ordering.is_eq(),
+            Op::Ne => ordering.is_ne(),
+            Op::Lt => ordering.is_lt(),
+            Op::Le => ordering.is_le(),
+            Op::Gt => ordering.is_gt(),
+            Op::Ge => ordering.is_ge(),
+        }
+    }
+}
+
+/// Literal with its readings parsed once, up front
+#[derive(Debug, Clone, PartialEq)]
+struct Literal {
+    text: String,
+    number: Option<f64>,
+    time: Option<DateTime<Utc>>,
+}
+
+impl Literal {
+    fn new(text: &str) -> Self {
+        Self {
+            number: text.parse().ok(),
+            time: parse_time(text),
+            text: text.to_string(),
+        }
+    }
+
+    /// How `value` orders against the literal; None when the literal cannot
+    /// be read as the value's type
+    fn compare(&self, value: &Value) -> Option<Ordering> {
+        match value {
+            Value::Number(n) => n.partial_cmp(&self.number?),
+            Value::Time(t) => Some(t.cmp(&self.time?)),
+            Value::Text(s) => Some(s.to_lowercase().cmp(&self.text.to_lowercase())),
+        }
+    }
+}
+
+/// `2024-01-02T03:04:05Z`, or a date alone for its midnight
+fn parse_time(text: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(text)
+        .map(|t| t.with_timezone(&Utc))
+        .ok()
+        .or_else(|| {
+            NaiveDate::parse_from_str(text, "%Y-%m-%d")
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+                .map(|t| t.and_utc())
+        })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Compare {
+        field: String,
+        op: Op,
+        literal: Literal,
+    },
+    /// `field in from..to`, from inclusive and to exclusive
+    Range {
+        field: String,
+        from: Literal,
+        to: Literal,
+    },
+}
+
+impl Expr {
+    fn matches<T: Queryable>(&self, record: &T) -> bool {
+        match self {
+            Expr::And(a, b) => a.matches(record) && b.matches(record),
+            Expr::Or(a, b) => a.matches(record) || b.matches(record),
+            Expr::Not(a) => !a.matches(record),
+            Expr::Compare { field, op, literal } => record
+                .field(field)
+                .and_then(|value| literal.compare(&value))
+                .is_some_and(|ordering| op.holds(ordering)),
+            Expr::Range { field, from, to } => record.field(field).is_some_and(|value| {
+                from.compare(&value).is_some_and(Ordering::is_ge)
+                    && to.compare(&value).is_some_and(Ordering::is_lt)
+            }),
+        }
+    }
+
+    fn fields<'a>(&'a self, out: &mut Vec<&'a str>) {
+        match self {
+            Expr::And(a, b) | Expr::Or(a, b) => {
+                a.fields(out);
+                b.fields(out);
+            }
+            Expr::Not(a) => a.fields(out),
+            Expr::Compare { field, .. } | Expr::Range { field, .. } => out.push(field),
+        }
+    }
+}
+
+/// Longest filter accepted; filters come from query strings, and this also
+/// bounds how long AND and OR chains get
+const MAX_FILTER_LEN: usize = 1024;
+
+/// Deepest nesting of parentheses and NOTs accepted
+const MAX_DEPTH: usize = 64;
+
+/// Compiled filter over history records
+///
+/// Fields are compared against literals with `= != < <= > >=`, combined with
+/// `AND`, `OR` and `NOT` and grouped with parentheses; `AND` binds tighter
+/// than `OR`. `time in 2024-01-01..2024-01-02` is shorthand for a half-open
+/// range. Literals with spaces or operator characters go in double quotes.
+/// A comparison with a missing field, or with a literal that does not read
+/// as the field's type, is false.
+///
+/// ```text
+/// symbol = BTCUSDT AND (side = buy OR price >= 50000) AND time in 2024-01-01..2024-01-02
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filter {
+    expr: Option<Expr>,
+}
+
+impl Filter {
+    /// Filter that keeps everything
+    pub fn all() -> Self {
+        Self { expr: None }
+    }
+
+    /// Parse `source` and check every field it names is one `T` has
+    ///
+    /// A blank source keeps everything.
+    pub fn compile<T: Queryable>(source: &str) -> Result<Self, String> {
+        let filter = Self::parse(source)?;
+        let mut fields = Vec::new();
+        if let Some(expr) = &filter.expr {
+            expr.fields(&mut fields);
+        }
+        match fields.into_iter().find(|field| !T::FIELDS.contains(field)) {
+            Some(field) => Err(format!(
+                "unknown field {:?}, expected one of {}",
+                field,
+                T::FIELDS.join(", ")
+            )),
+            None => Ok(filter),
+        }
+    }
+
+    /// Parse `source` without checking field names
+    pub fn parse(source: &str) -> Result<Self, String> {
+        if source.len() > MAX_FILTER_LEN {
+            return Err(format!(
+                "filter is longer than {} characters",
+                MAX_FILTER_LEN
+            ));
+        }
+        let tokens = tokenize(source)?;
+        if tokens.is_empty() {
+            return Ok(Self::all());
+        }
+        let mut parser = Parser {
+            tokens,
+            at: 0,
+            depth: 0,
+        };
+        let expr = parser.or()?;
+        match parser.peek() {
+            None => Ok(Self { expr: Some(expr) }),
+            Some(token) => Err(format!("unexpected {} after the filter", token)),
+        }
+    }
+
+    pub fn matches<T: Queryable>(&self, record: &T) -> bool {
+        self.expr.as_ref().is_none_or(|expr| expr.matches(record))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Open,
+    Close,
+    Op(Op),
+    Word(String),
+    /// Quoted, so never a keyword
+    Quoted(String),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Open => write!(f, "'('"),
+            Token::Close => write!(f, "')'"),
+            Token::Op(op) => write!(f, "operator {:?}", op),
+            Token::Word(word) => write!(f, "{:?}", word),
+            Token::Quoted(text) => write!(f, "\"{}\"", text),
+        }
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = source.char_indices().peekable();
+    while let Some((at, c)) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '(' => Token::Open,
+            ')' => Token::Close,
+            '=' => Token::Op(Op::Eq),
+            '!' | '<' | '>' => {
+                let equals = chars.next_if(|&(_, c)| c == '=').is_some();
+                match (c, equals) {
+                    ('!', true) => Token::Op(Op::Ne),
+                    ('<', false) => Token::Op(Op::Lt),
+                    ('<', true) => Token::Op(Op::Le),
+                    ('>', false) => Token::Op(Op::Gt),
+                    ('>', true) => Token::Op(Op::Ge),
+                    _ => return Err(format!("expected '!=' at {}", at)),
+                }
+            }
+            '"' => {
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '"')) => break,
+                        Some((_, c)) => text.push(c),
+                        None => return Err(format!("unterminated quote at {}", at)),
+                    }
+                }
+                Token::Quoted(text)
+            }
+            c => {
+                let mut word = c.to_string();
+                while let Some((_, c)) =
+                    chars.next_if(|&(_, c)| !c.is_whitespace() && !"()=!<>\"".contains(c))
+                {
+                    word.push(c);
+                }
+                Token::Word(word)
+            }
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent over `or := and (OR and)*`, `and := unary (AND unary)*`
+struct Parser {
+    tokens: Vec<Token>,
+    at: usize,
+    /// Parentheses and NOTs open around the current token
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.at)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.at).cloned();
+        self.at += 1;
+        token
+    }
+
+    fn keyword(&mut self, keyword: &str) -> bool {
+        let found = matches!(self.peek(), Some(Token::Word(w)) if w.eq_ignore_ascii_case(keyword));
+        if found {
+            self.at += 1;
+        }
+        found
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut expr = self.and()?;
+        while self.keyword("or") {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut expr = self.unary()?;
+        while self.keyword("and") {
+            expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.keyword("not") {
+            self.nest()?;
+            let expr = self.unary()?;
+            self.depth -= 1;
+            return Ok(Expr::Not(Box::new(expr)));
+        }
+        match self.next() {
+            Some(Token::Open) => {
+                self.nest()?;
+                let expr = self.or()?;
+                self.depth -= 1;
+                match self.next() {
+                    Some(Token::Close) => Ok(expr),
+                    _ => Err("missing ')'".to_string()),
+                }
+            }
+            Some(Token::Word(field)) => self.comparison(field),
+            Some(token) => Err(format!("expected a field, found {}", token)),
+            None => Err("expected a field, found the end".to_string()),
+        }
+    }
+
+    /// Go one level deeper, refusing nesting that would exhaust the stack
+    fn nest(&mut self) -> Result<(), String> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(format!("filter nests deeper than {} levels", MAX_DEPTH));
+        }
+        Ok(())
+    }
+
+    fn comparison(&mut self, field: String) -> Result<Expr, String> {
+        if self.keyword("in") {
+            let range = self.literal()?;
+            let (from, to) = range
+                .split_once("..")
+                .ok_or_else(|| format!("expected a range like a..b after {} in", field))?;
+            return Ok(Expr::Range {
+                field,
+                from: Literal::new(from),
+                to: Literal::new(to),
+            });
+        }
+        let op = match self.next() {
+            Some(Token::Op(op)) => op,
+            Some(token) => {
+                return Err(format!(
+                    "expected an operator after {}, found {}",
+                    field, token
+                ))
+            }
+            None => return Err(format!("expected an operator after {}", field)),
+        };
+        Ok(Expr::Compare {
+            field,
+            op,
+            literal: Literal::new(&self.literal()?),
+        })
+    }
+
+    fn literal(&mut self) -> Result<String, String> {
+        match self.next() {
+            Some(Token::Word(text) | Token::Quoted(text)) => Ok(text),
+            Some(token) => Err(format!("expected a value, found {}", token)),
+            None => Err("expected a value, found the end".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Row {
+        symbol: &'static str,
+        price: f64,
+        time: &'static str,
+    }
+
+    impl Queryable for Row {
+        const FIELDS: &'static [&'static str] = &["symbol", "price", "time"];
+
+        fn field(&self, name: &str) -> Option<Value> {
+            match name {
+                "symbol" => Some(Value::Text(self.symbol.to_string())),
+                "price" => Some(Value::Number(self.price)),
+                "time" => parse_time(self.time).map(Value::Time),
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn test_filters_combine_comparisons_and_time_ranges() {
+        let rows = [
+            Row {
+                symbol: "BTCUSDT",
+                price: 100.0,
+                time: "2024-01-01T12:00:00Z",
+            },
+            Row {
+                symbol: "ETHUSDT",
+                price: 10.0,
+                time: "2024-01-01T13:00:00Z",
+            },
+            Row {
+                symbol: "BTCUSDT",
+                price: 90.0,
+                time: "2024-01-02T00:00:00Z",
+            },
+        ];
+        let matching = |source: &str| -> Vec<f64> {
+            let filter = Filter::compile::<Row>(source).unwrap();
+            rows.iter()
+                .filter(|row| filter.matches(*row))
+                .map(|row| row.price)
+                .collect()
+        };
+
+        assert_eq!(matching(""), [100.0, 10.0, 90.0]);
+        assert_eq!(matching("symbol = btcusdt AND price > 95"), [100.0]);
+        // AND binds tighter than OR
+        assert_eq!(
+            matching("symbol = ETHUSDT OR symbol = BTCUSDT AND price < 95"),
+            [10.0, 90.0]
+        );
+        assert_eq!(
+            matching("NOT (symbol = ETHUSDT) AND time in 2024-01-01..2024-01-02"),
+            [100.0]
+        );
+        assert_eq!(matching("time >= \"2024-01-01T13:00:00Z\""), [10.0, 90.0]);
+        // A value that is not a number never compares
+        assert!(matching("price < abc").is_empty());
+
+        assert!(Filter::compile::<Row>("side = buy").is_err());
+        assert!(Filter::compile::<Row>("price >").is_err());
+        assert!(Filter::compile::<Row>("(price > 1").is_err());
+        assert!(Filter::compile::<Row>("price = 1 symbol").is_err());
+    }
+
+    #[test]
+    fn test_deep_or_long_filters_are_rejected() {
+        let nested = format!("{}price = 1{}", "(".repeat(100), ")".repeat(100));
+        assert!(Filter::parse(&nested).unwrap_err().contains("deeper"));
+        assert!(Filter::parse(&"NOT ".repeat(100)).is_err());
+        let chained = vec!["price = 1"; 200].join(" AND ");
+        assert!(Filter::parse(&chained).unwrap_err().contains("longer"));
+
+        let shallow = format!("{}price = 1{}", "(".repeat(64), ")".repeat(64));
+        assert!(Filter::parse(&shallow).is_ok());
+    }
+}
@@ -0,0 +1,271 @@
+use std::fmt;
+use std::marker::PhantomData;
+
+use serde::de::{Deserialize, Deserializer, SeqAccess, Visitor};
+use serde::ser::{Serialize, Serializer};
+
+/// Slots per page; pages are allocated only once a slot in them is set
+const PAGE_SIZE: usize = 64;
+
+#[derive(Debug, Clone)]
+struct Page<T> {
+    slots: Vec<Option<T>>,
+    occupied: usize,
+}
+
+impl<T> Page<T> {
+    fn new() -> Self {
+        Self {
+            slots: (0..PAGE_SIZE).map(|_| None).collect(),
+            occupied: 0,
+        }
+    }
+}
+
+/// Vector indexed like an array but storing only occupied slots
+///
+/// Slots are grouped in fixed-size pages that are allocated on first write,
+/// so a price ladder indexed by tick only pays for the ranges that trade.
+/// Iteration is in index order.
+#[derive(Debug, Clone)]
+pub struct SparseVector<T> {
+    pages: Vec<Option<Box<Page<T>>>>,
+    len: usize,
+}
+
+impl<T> SparseVector<T> {
+    pub fn new() -> Self {
+        Self {
+            pages: Vec::new(),
+            len: 0,
+        }
+    }
+
+    /// Number of occupied slots
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Pages currently holding memory
+    pub fn allocated_pages(&self) -> usize {
+        self.pages.iter().filter(|page| page.is_some()).count()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.pages.get(index / PAGE_SIZE)?.as_ref()?.slots[index % PAGE_SIZE].as_ref()
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.pages.get_mut(index / PAGE_SIZE)?.as_mut()?.slots[index % PAGE_SIZE].as_mut()
+    }
+
+    pub fn contains(&self, index: usize) -> bool {
+        self.get(index).is_some()
+    }
+
+    /// Store `value` at `index`, returning the previous value
+    pub fn set(&mut self, index: usize, value: T) -> Option<T> {
+        let page_index = index / PAGE_SIZE;
+        if page_index >= self.pages.len() {
+            self.pages.resize_with(page_index + 1, || None);
+        }
+        let page = self.pages[page_index].get_or_insert_with(|| Box::new(Page::new()));
+        let previous = page.slots[index % PAGE_SIZE].replace(value);
+        if previous.is_none() {
+            page.occupied += 1;
+            self.len += 1;
+        }
+        previous
+    }
+
+    /// Clear `index`; the page is kept until [`compact`](Self::compact)
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        let page = self.pages.get_mut(index / PAGE_SIZE)?.as_mut()?;
+        let removed = page.slots[index % PAGE_SIZE].take();
+        if removed.is_some() {
+            page.occupied -= 1;
+            self.len -= 1;
+        }
+        removed
+    }
+
+    /// Store every `(index, value)` pair
+    pub fn set_many(&mut self, entries: impl IntoIterator<Item = (usize, T)>) {
+        for (index, value) in entries {
+            self.set(index, value);
+        }
+    }
+
+    /// Values at `indices`, in the same order
+    pub fn get_many(&self, indices: &[usize]) -> Vec<Option<&T>> {
+        indices.iter().map(|&index| self.get(index)).collect()
+    }
+
+    /// Occupied `(index, value)` pairs in index order
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (usize, &T)> + '_ {
+        self.pages
+            .iter()
+            .enumerate()
+            .filter_map(|(page_index, page)| Some((page_index, page.as_ref()?)))
+            .flat_map(|(page_index, page)| {
+                page.slots
+                    .iter()
+                    .enumerate()
+                    .filter_map(move |(slot, value)| {
+                        Some((page_index * PAGE_SIZE + slot, value.as_ref()?))
+                    })
+            })
+    }
+
+    /// Occupied indices in order
+    pub fn indices(&self) -> impl DoubleEndedIterator<Item = usize> + '_ {
+        self.iter().map(|(index, _)| index)
+    }
+
+    pub fn first_index(&self) -> Option<usize> {
+        self.indices().next()
+    }
+
+    pub fn last_index(&self) -> Option<usize> {
+        self.indices().next_back()
+    }
+
+    /// Remove and return every occupied pair in index order, freeing all pages
+    pub fn drain(&mut self) -> Vec<(usize, T)> {
+        let pages = std::mem::take(&mut self.pages);
+        self.len = 0;
+        pages
+            .into_iter()
+            .enumerate()
+            .filter_map(|(page_index, page)| Some((page_index, page?)))
+            .flat_map(|(page_index, page)| {
+                page.slots
+                    .into_iter()
+                    .enumerate()
+                    .filter_map(move |(slot, value)| Some((page_index * PAGE_SIZE + slot, value?)))
+            })
+            .collect()
+    }
+
+    /// Free pages with no occupied slots and trim the page table
+    pub fn compact(&mut self) {
+        for page in &mut self.pages {
+            if page.as_ref().is_some_and(|p| p.occupied == 0) {
+                *page = None;
+            }
+        }
+        while self.pages.last().is_some_and(Option::is_none) {
+            self.pages.pop();
+        }
+        self.pages.shrink_to_fit();
+    }
+
+    pub fn clear(&mut self) {
+        self.pages.clear();
+        self.len = 0;
+    }
+}
+
+impl<T> Default for SparseVector<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> FromIterator<(usize, T)> for SparseVector<T> {
+    fn from_iter<I: IntoIterator<Item = (usize, T)>>(iter: I) -> Self {
+        let mut vector = Self::new();
+        vector.set_many(iter);
+        vector
+    }
+}
+
+impl<T: PartialEq> PartialEq for SparseVector<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+/// Serialized as a sequence of `[index, value]` pairs
+impl<T: Serialize> Serialize for SparseVector<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for SparseVector<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct PairsVisitor<T>(PhantomData<T>);
+
+        impl<'de, T: Deserialize<'de>> Visitor<'de> for PairsVisitor<T> {
+            type Value = SparseVector<T>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a sequence of [index, value] pairs")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut vector = SparseVector::new();
+                while let Some((index, value)) = seq.next_element::<(usize, T)>()? {
+                    vector.set(index, value);
+                }
+                Ok(vector)
+            }
+        }
+
+        deserializer.deserialize_seq(PairsVisitor(PhantomData))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_get_remove_and_compact() {
+        let mut ladder = SparseVector::new();
+        assert_eq!(ladder.set(5, 1.5), None);
+        assert_eq!(ladder.set(10_000, 2.0), None);
+        assert_eq!(ladder.set(5, 3.0), Some(1.5));
+        assert_eq!(ladder.len(), 2);
+        assert_eq!(ladder.allocated_pages(), 2);
+        assert_eq!(
+            ladder.get_many(&[5, 6, 10_000]),
+            vec![Some(&3.0), None, Some(&2.0)]
+        );
+
+        assert_eq!(ladder.remove(10_000), Some(2.0));
+        assert_eq!(ladder.allocated_pages(), 2);
+        ladder.compact();
+        assert_eq!(ladder.allocated_pages(), 1);
+        assert_eq!(
+            (ladder.first_index(), ladder.last_index()),
+            (Some(5), Some(5))
+        );
+    }
+
+    #[test]
+    fn test_iteration_and_drain_are_ordered() {
+        let mut ladder: SparseVector<u32> = [(300, 3), (2, 1), (64, 2)].into_iter().collect();
+        assert_eq!(ladder.indices().collect::<Vec<_>>(), vec![2, 64, 300]);
+        assert_eq!(ladder.iter().next_back(), Some((300, &3)));
+
+        assert_eq!(ladder.drain(), vec![(2, 1), (64, 2), (300, 3)]);
+        assert!(ladder.is_empty());
+        assert_eq!(ladder.allocated_pages(), 0);
+    }
+
+    #[test]
+    fn test_serde_round_trip() {
+        let ladder: SparseVector<f64> = [(7, 0.5), (1_000, 1.25)].into_iter().collect();
+        let json = serde_json::to_string(&ladder).unwrap();
+        assert_eq!(json, "[[7,0.5],[1000,1.25]]");
+
+        let parsed: SparseVector<f64> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, ladder);
+    }
+}
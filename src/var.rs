@@ -0,0 +1,395 @@
+// Value at risk of account positions
+//
+// Standard VaR is parametric: each position loses its notional times the
+// symbol's volatility over the horizon, scaled to the confidence level.
+// Liquidity-adjusted VaR (LVaR) adds what it takes to get out: the spread
+// and depth a market exit would walk through on the book right now, and
+// the price risk of the extra days an orderly unwind takes at a sustainable
+// share of traded volume. Positions are summed without diversification, so
+// both figures are conservative for a portfolio. Tail metrics (expected
+// shortfall, skewness, kurtosis, loss streaks) come from a history of
+// returns, the account's own when one is recorded.
+
+use serde::{Deserialize, Serialize};
+
+use crate::account::Account;
+use crate::orderbook::Depth;
+use crate::timeseries::SeriesRange;
+use crate::types::Symbol;
+
+/// Confidence level, horizon and unwind pace of a VaR estimate
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VarConfig {
+    /// Probability the loss stays within VaR, such as 0.99
+    pub confidence: f64,
+    pub horizon_days: f64,
+    /// Share of daily volume a position can be unwound at
+    pub participation: f64,
+}
+
+impl Default for VarConfig {
+    fn default() -> Self {
+        Self {
+            confidence: 0.99,
+            horizon_days: 1.0,
+            participation: 0.1,
+        }
+    }
+}
+
+impl VarConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.confidence > 0.5 && self.confidence < 1.0) {
+            return Err("confidence must be between 0.5 and 1".to_string());
+        }
+        if !(self.horizon_days.is_finite() && self.horizon_days > 0.0) {
+            return Err("horizon_days must be positive".to_string());
+        }
+        if !(self.participation > 0.0 && self.participation <= 1.0) {
+            return Err("participation must be in (0, 1]".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// What VaR needs to know of one symbol's market
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SymbolMarket {
+    pub mark: Option<f64>,
+    /// Standard deviation of daily log returns
+    pub daily_volatility: Option<f64>,
+    /// Base quantity traded over the last day
+    pub daily_volume: Option<f64>,
+    /// Bids best first, then asks best first
+    pub depth: Depth,
+}
+
+/// VaR and LVaR of one position, as positive losses
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PositionVar {
+    pub symbol: Symbol,
+    pub quantity: f64,
+    /// Signed notional at the mark
+    pub notional: f64,
+    pub var: f64,
+    /// Loss against the mark of selling or buying back the whole position
+    /// into the book now
+    pub liquidation_cost: f64,
+    /// Part of the position the book had no depth for; its cost is taken
+    /// at the worst price reached
+    pub depth_shortfall: f64,
+    /// Days to unwind at the configured share of daily volume, when the
+    /// volume is known
+    pub time_to_liquidate_days: Option<f64>,
+    pub lvar: f64,
+}
+
+/// VaR and LVaR of an account's open positions
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct VarReport {
+    pub confidence: f64,
+    pub horizon_days: f64,
+    pub positions: Vec<PositionVar>,
+    pub var: f64,
+    pub lvar: f64,
+    /// Positions left out for lack of a volatility estimate
+    pub missing_volatility: Vec<Symbol>,
+    /// Tail of the returns history, when one was given
+    pub metrics: Option<RiskMetrics>,
+}
+
+impl VarReport {
+    /// Add the tail metrics of `returns` at the report's confidence
+    pub fn with_returns(mut self, returns: &[f64]) -> Self {
+        self.metrics = RiskMetrics::from_returns(returns, self.confidence);
+        self
+    }
+}
+
+/// Tail risk of a history of returns, per period of the history
+///
+/// Losses are positive fractions. Expected shortfall (CVaR) is the mean
+/// loss of the worst `1 - confidence` of periods, VaR the smallest of them.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RiskMetrics {
+    pub observations: usize,
+    /// Historical VaR
+    pub var: f64,
+    pub expected_shortfall: f64,
+    pub skewness: f64,
+    /// Kurtosis above the normal distribution's 3
+    pub excess_kurtosis: f64,
+    /// Longest run of consecutive losing periods
+    pub max_loss_streak: usize,
+}
+
+impl RiskMetrics {
+    pub fn from_returns(returns: &[f64], confidence: f64) -> Option<Self> {
+        if returns.is_empty() {
+            return None;
+        }
+        let n = returns.len() as f64;
+        let mut sorted = returns.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        let tail = (((1.0 - confidence) * n).ceil() as usize).clamp(1, sorted.len());
+        let worst = &sorted[..tail];
+
+        let mean = returns.iter().sum::<f64>() / n;
+        let moment = |k: i32| returns.iter().map(|r| (r - mean).powi(k)).sum::<f64>() / n;
+        let variance = moment(2);
+        let (skewness, excess_kurtosis) = if variance > 0.0 {
+            (
+                moment(3) / variance.powf(1.5),
+                moment(4) / (variance * variance) - 3.0,
+            )
+        } else {
+            (0.0, 0.0)
+        };
+
+        let mut streak = 0;
+        let mut max_loss_streak = 0;
+        for r in returns {
+            streak = if *r < 0.0 { streak + 1 } else { 0 };
+            max_loss_streak = max_loss_streak.max(streak);
+        }
+
+        Some(Self {
+            observations: returns.len(),
+            var: -worst[tail - 1],
+            expected_shortfall: -worst.iter().sum::<f64>() / tail as f64,
+            skewness,
+            excess_kurtosis,
+            max_loss_streak,
+        })
+    }
+}
+
+/// VaR and LVaR of `account`'s open positions
+///
+/// An unwind taking `t` days sheds the position evenly, so on top of the
+/// horizon it carries on average a third of the variance a full position
+/// would over `t`: LVaR scales volatility by `sqrt(horizon + t / 3)` and
+/// adds the liquidation cost. Positions without a mark are valued at their
+/// entry price.
+pub fn value_at_risk(
+    account: &Account,
+    config: &VarConfig,
+    market: impl Fn(&Symbol) -> SymbolMarket,
+) -> VarReport {
+    let z = normal_quantile(config.confidence);
+    let mut report = VarReport {
+        confidence: config.confidence,
+        horizon_days: config.horizon_days,
+        positions: Vec::new(),
+        var: 0.0,
+        lvar: 0.0,
+        missing_volatility: Vec::new(),
+        metrics: None,
+    };
+    for position in account.open_positions() {
+        let market = market(&position.symbol);
+        let Some(volatility) = market.daily_volatility else {
+            report.missing_volatility.push(position.symbol);
+            continue;
+        };
+        let mark = market.mark.unwrap_or(position.entry_price);
+        let notional = position.quantity * mark;
+        let loss = |days: f64| z * volatility * days.sqrt() * notional.abs();
+
+        let (liquidation_cost, depth_shortfall) =
+            liquidation_cost(position.quantity, mark, &market.depth);
+        let time_to_liquidate_days = market
+            .daily_volume
+            .filter(|volume| *volume > 0.0)
+            .map(|volume| position.quantity.abs() / (config.participation * volume));
+        let unwind_days = time_to_liquidate_days.unwrap_or(0.0);
+        let var = loss(config.horizon_days);
+        let lvar = loss(config.horizon_days + unwind_days / 3.0) + liquidation_cost;
+
+        report.var += var;
+        report.lvar += lvar;
+        report.positions.push(PositionVar {
+            symbol: position.symbol,
+            quantity: position.quantity,
+            notional,
+            var,
+            liquidation_cost,
+            depth_shortfall,
+            time_to_liquidate_days,
+            lvar,
+        });
+    }
+    report
+}
+
+/// Loss against `mark` of closing `quantity` (signed, negative when short)
+/// into `depth`, and the quantity left once the depth ran out
+pub fn liquidation_cost(quantity: f64, mark: f64, depth: &Depth) -> (f64, f64) {
+    let levels = if quantity > 0.0 { &depth.0 } else { &depth.1 };
+    let mut left = quantity.abs();
+    let mut cost = 0.0;
+    let mut worst = mark;
+    for &(price, size) in levels {
+        if left <= 0.0 {
+            break;
+        }
+        let take = left.min(size);
+        cost += take * (price - mark).abs();
+        worst = price;
+        left -= take;
+    }
+    let shortfall = left.max(0.0);
+    (cost + shortfall * (worst - mark).abs(), shortfall)
+}
+
+/// Log returns between the bucket closes of a series, oldest first
+pub fn log_returns(range: &SeriesRange) -> Vec<f64> {
+    range
+        .buckets
+        .windows(2)
+        .filter(|w| w[0].close > 0.0 && w[1].close > 0.0)
+        .map(|w| (w[1].close / w[0].close).ln())
+        .collect()
+}
+
+/// Daily volatility of a price series from its [`log_returns`], scaled up
+/// from the bucket width
+///
+/// Raw samples have no width and give no estimate.
+pub fn daily_volatility(range: &SeriesRange) -> Option<f64> {
+    let step = range.resolution.step()?.num_seconds() as f64;
+    let returns = log_returns(range);
+    if returns.len() < 2 {
+        return None;
+    }
+    let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+    let variance =
+        returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (returns.len() - 1) as f64;
+    Some((variance * 86_400.0 / step).sqrt())
+}
+
+/// Inverse of the standard normal distribution function
+///
+/// Acklam's rational approximation, good to about 1e-9 relative.
+pub fn normal_quantile(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969683028665376e1,
+        2.209460984245205e2,
+        -2.759285104469687e2,
+        1.38357751867269e2,
+        -3.066479806614716e1,
+        2.506628277459239,
+    ];
+    const B: [f64; 5] = [
+        -5.447609879822406e1,
+        1.615858368580409e2,
+        -1.556989798598866e2,
+        6.680131188771972e1,
+        -1.328068155288572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784894002430293e-3,
+        -3.223964580411365e-1,
+        -2.400758277161838,
+        -2.549732539343734,
+        4.374664141464968,
+        2.938163982698783,
+    ];
+    const D: [f64; 4] = [
+        7.784695709041462e-3,
+        3.224671290700398e-1,
+        2.445134137142996,
+        3.754408661907416,
+    ];
+    const P_LOW: f64 = 0.02425;
+
+    let tail = |q: f64| {
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    };
+    if p <= 0.0 {
+        f64::NEG_INFINITY
+    } else if p >= 1.0 {
+        f64::INFINITY
+    } else if p < P_LOW {
+        tail((-2.0 * p.ln()).sqrt())
+    } else if p > 1.0 - P_LOW {
+        -tail((-2.0 * (1.0 - p).ln()).sqrt())
+    } else {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testkit;
+    use crate::types::OrderSide;
+
+    #[test]
+    fn test_lvar_adds_depth_cost_and_unwind_time_to_var() {
+        assert!((normal_quantile(0.99) - 2.326348).abs() < 1e-6);
+        assert!((normal_quantile(0.5)).abs() < 1e-12);
+
+        let account = testkit::account("alice")
+            .deposit(100_000.0)
+            .trade("BTCUSDT", OrderSide::Buy, 10.0, 100.0, 0.0)
+            .trade("ETHUSDT", OrderSide::Sell, 5.0, 10.0, 0.0)
+            .trade("SOLUSDT", OrderSide::Buy, 1.0, 1.0, 0.0)
+            .build();
+        let market = |symbol: &Symbol| match symbol.as_str() {
+            // Deep enough for 4 of the 10, then 6 more a dollar lower
+            "BTCUSDT" => SymbolMarket {
+                mark: Some(100.0),
+                daily_volatility: Some(0.02),
+                daily_volume: Some(300.0),
+                depth: (vec![(99.5, 4.0), (98.5, 20.0)], vec![(100.5, 5.0)]),
+            },
+            // The book runs out after 2 of the 5 bought back
+            "ETHUSDT" => SymbolMarket {
+                mark: Some(10.0),
+                daily_volatility: Some(0.05),
+                daily_volume: None,
+                depth: (vec![(9.9, 10.0)], vec![(10.2, 2.0)]),
+            },
+            _ => SymbolMarket::default(),
+        };
+        let report = value_at_risk(&account, &VarConfig::default(), market);
+        assert_eq!(report.missing_volatility, [Symbol::from("SOLUSDT")]);
+
+        let z = normal_quantile(0.99);
+        let btc = &report.positions[0];
+        assert!((btc.var - z * 0.02 * 1_000.0).abs() < 1e-9);
+        assert!((btc.liquidation_cost - (4.0 * 0.5 + 6.0 * 1.5)).abs() < 1e-9);
+        // 10 at 10% of 300 a day takes a third of a day
+        assert!((btc.time_to_liquidate_days.unwrap() - 1.0 / 3.0).abs() < 1e-9);
+        let unwind = z * 0.02 * (1.0f64 + 1.0 / 9.0).sqrt() * 1_000.0;
+        assert!((btc.lvar - (unwind + 11.0)).abs() < 1e-9);
+
+        let eth = &report.positions[1];
+        assert_eq!(eth.depth_shortfall, 3.0);
+        assert!((eth.liquidation_cost - 5.0 * 0.2).abs() < 1e-9);
+        assert!((eth.lvar - (eth.var + 1.0)).abs() < 1e-9);
+        assert!((report.lvar - (btc.lvar + eth.lvar)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_tail_metrics_of_a_returns_history() {
+        let returns = [
+            0.01, -0.02, -0.01, 0.03, -0.05, -0.01, -0.02, 0.02, 0.0, 0.01,
+        ];
+        let metrics = RiskMetrics::from_returns(&returns, 0.8).unwrap();
+        // The worst 20% are -5% and -2%
+        assert_eq!(metrics.observations, 10);
+        assert!((metrics.var - 0.02).abs() < 1e-12);
+        assert!((metrics.expected_shortfall - 0.035).abs() < 1e-12);
+        assert_eq!(metrics.max_loss_streak, 3);
+        // A single -5% drags the tail left
+        assert!(metrics.skewness < 0.0);
+        assert!(RiskMetrics::from_returns(&[], 0.99).is_none());
+    }
+}
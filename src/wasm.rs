@@ -0,0 +1,135 @@
+// WebAssembly bindings, enabled with the `wasm` feature
+//
+// Build without the networking stack for in-browser matching demos:
+//
+//     wasm-pack build --target web -- --no-default-features --features wasm
+//
+// and drive the book from JavaScript:
+//
+//     const book = new OrderBook("BTCUSDT");
+//     book.addLimit("sell", 50100, 0.5);
+//     const trades = book.addLimit("buy", 50100, 0.2);
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::orderbook::OrderBook;
+use crate::types::{Order, OrderId, OrderSide, Price, Qty, Trade};
+
+/// Result of submitting an order from JavaScript
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Submission {
+    order_id: u64,
+    trades: Vec<Trade>,
+}
+
+/// Depth snapshot passed to JavaScript as `{ bids: [[price, qty]], asks: [...] }`
+#[derive(Serialize)]
+struct DepthView {
+    bids: Vec<(f64, f64)>,
+    asks: Vec<(f64, f64)>,
+}
+
+fn parse_side(side: &str) -> Result<OrderSide, JsError> {
+    match side.to_ascii_lowercase().as_str() {
+        "buy" | "bid" => Ok(OrderSide::Buy),
+        "sell" | "ask" => Ok(OrderSide::Sell),
+        other => Err(JsError::new(&format!("unknown side '{}'", other))),
+    }
+}
+
+fn positive<T>(name: &str, value: f64, parse: fn(f64) -> Option<T>) -> Result<T, JsError> {
+    parse(value).ok_or_else(|| {
+        JsError::new(&format!(
+            "{} must be a positive number, got {}",
+            name, value
+        ))
+    })
+}
+
+fn to_js<T: Serialize>(value: &T) -> Result<JsValue, JsError> {
+    serde_wasm_bindgen::to_value(value).map_err(|e| JsError::new(&e.to_string()))
+}
+
+/// Order book with the full matching engine, running client-side
+#[wasm_bindgen(js_name = OrderBook)]
+pub struct WasmOrderBook {
+    book: OrderBook,
+}
+
+#[wasm_bindgen(js_class = OrderBook)]
+impl WasmOrderBook {
+    #[wasm_bindgen(constructor)]
+    pub fn new(symbol: String) -> Self {
+        Self {
+            book: OrderBook::new(symbol),
+        }
+    }
+
+    /// Submit a limit order; returns `{ orderId, trades }`, or throws unless
+    /// price and quantity are finite and positive
+    #[wasm_bindgen(js_name = addLimit)]
+    pub fn add_limit(&mut self, side: &str, price: f64, quantity: f64) -> Result<JsValue, JsError> {
+        let order = Order::new_limit(
+            self.book.symbol.clone(),
+            parse_side(side)?,
+            positive("price", price, Price::try_positive)?,
+            positive("quantity", quantity, Qty::try_positive)?,
+        );
+        self.submit(order)
+    }
+
+    /// Submit a market order; returns `{ orderId, trades }`, or throws unless
+    /// quantity is finite and positive
+    #[wasm_bindgen(js_name = addMarket)]
+    pub fn add_market(&mut self, side: &str, quantity: f64) -> Result<JsValue, JsError> {
+        let order = Order::new_market(
+            self.book.symbol.clone(),
+            parse_side(side)?,
+            positive("quantity", quantity, Qty::try_positive)?,
+        );
+        self.submit(order)
+    }
+
+    /// Cancel a resting order; returns true if it was found
+    pub fn cancel(&mut self, order_id: u64) -> bool {
+        self.book.cancel_order(OrderId(order_id)).is_some()
+    }
+
+    /// Top N levels on each side
+    pub fn depth(&self, levels: usize) -> Result<JsValue, JsError> {
+        let (bids, asks) = self.book.get_depth(levels);
+        to_js(&DepthView { bids, asks })
+    }
+
+    #[wasm_bindgen(js_name = bestBid)]
+    pub fn best_bid(&self) -> Option<f64> {
+        self.book.best_bid()
+    }
+
+    #[wasm_bindgen(js_name = bestAsk)]
+    pub fn best_ask(&self) -> Option<f64> {
+        self.book.best_ask()
+    }
+
+    pub fn spread(&self) -> Option<f64> {
+        self.book.spread()
+    }
+
+    #[wasm_bindgen(js_name = midPrice)]
+    pub fn mid_price(&self) -> Option<f64> {
+        self.book.mid_price()
+    }
+
+    #[wasm_bindgen(js_name = orderCount)]
+    pub fn order_count(&self) -> usize {
+        self.book.order_count()
+    }
+
+    fn submit(&mut self, order: Order) -> Result<JsValue, JsError> {
+        let order_id = order.id.0;
+        let trades = self.book.add_order(order);
+        to_js(&Submission { order_id, trades })
+    }
+}